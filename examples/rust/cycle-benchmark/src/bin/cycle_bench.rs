@@ -14,10 +14,16 @@
 use com_api::LolaRuntimeImpl;
 use cycle_benchmark::config::{ApplicationConfig, SignallingType};
 use feo::ids::AgentId;
+use feo::metrics::{self, Metrics};
+use feo::statistics::CycleStatistics;
 use feo_time::Duration;
+use std::thread;
 
 const DEFAULT_FEO_CYCLE_TIME: Duration = Duration::from_millis(5);
 
+/// Interval at which the primary agent prints the cycle statistics
+const STATISTICS_INTERVAL: Duration = Duration::from_secs(5);
+
 fn main() {
     // Uncomment the following lines for benchmarking with logging
     // use score_log::LevelFilter;
@@ -68,29 +74,45 @@ fn run_as_primary(params: Params, app_config: ApplicationConfig) {
 
     let runtime = mw_com_runtime();
 
+    // Collect the cycle statistics in the scheduler and print them periodically
+    let statistics = CycleStatistics::new();
+    let reporter = statistics.clone();
+    thread::spawn(move || loop {
+        thread::sleep(STATISTICS_INTERVAL.into());
+        println!("Cycle statistics:\n{}", reporter.snapshot());
+    });
+    if let Some(metrics_addr) = app_config.metrics_addr() {
+        let address = metrics::serve(metrics_addr, Metrics::new(statistics.clone())).expect("failed to serve metrics");
+        println!("Serving metrics on http://{address}/metrics");
+    }
+
     match signalling {
         SignallingType::DirectMpsc => {
-            let config = direct_mpsc::make_primary_config(params, app_config);
+            let config = direct_mpsc::make_primary_config(params, app_config, statistics.clone());
             direct_mpsc::Primary::new(config)
                 .expect("failed to create mpsc primary")
                 .run()
                 .unwrap();
         },
-        signalling @ SignallingType::DirectTcp | signalling @ SignallingType::DirectUnix => {
-            let config = direct_sockets::make_primary_config(params, app_config, signalling);
+        signalling @ SignallingType::DirectTcp
+        | signalling @ SignallingType::DirectUnix
+        | signalling @ SignallingType::DirectShm => {
+            let config = direct_sockets::make_primary_config(params, app_config, signalling, statistics.clone());
             direct_sockets::Primary::new(config, runtime)
                 .expect("failed to create direct socket primary")
                 .run()
                 .unwrap();
         },
         signalling @ SignallingType::RelayedTcp | signalling @ SignallingType::RelayedUnix => {
-            let config = relayed_sockets::make_primary_config(params, app_config, signalling);
+            let config = relayed_sockets::make_primary_config(params, app_config, signalling, statistics.clone());
             relayed_sockets::Primary::new(config, runtime)
                 .expect("failed to create relayed socket primary")
                 .run()
                 .unwrap();
         },
     }
+
+    println!("Final cycle statistics:\n{}", statistics.snapshot());
 }
 
 fn run_as_secondary(params: Params, app_config: ApplicationConfig) {
@@ -105,15 +127,20 @@ fn run_as_secondary(params: Params, app_config: ApplicationConfig) {
     match signalling {
         SignallingType::DirectMpsc => {
             let config = direct_mpsc::make_secondary_config(params, app_config);
-            direct_mpsc::Secondary::new(config, runtime).run();
+            direct_mpsc::Secondary::new(config, runtime).run().unwrap();
         },
-        signalling @ SignallingType::DirectTcp | signalling @ SignallingType::DirectUnix => {
+        signalling @ SignallingType::DirectTcp
+        | signalling @ SignallingType::DirectUnix
+        | signalling @ SignallingType::DirectShm => {
             let config = direct_sockets::make_secondary_config(params, app_config, signalling);
-            direct_sockets::Secondary::new(config, runtime).run();
+            direct_sockets::Secondary::new(config, runtime).run().unwrap();
         },
         signalling @ SignallingType::RelayedTcp | signalling @ SignallingType::RelayedUnix => {
             let config = relayed_sockets::make_secondary_config(params, app_config, signalling);
-            relayed_sockets::Secondary::new(config).run();
+            relayed_sockets::Secondary::new(config)
+                .expect("failed to create relayed socket secondary")
+                .run()
+                .unwrap();
         },
     }
 }
@@ -154,13 +181,17 @@ impl Params {
 }
 
 mod direct_mpsc {
-    use super::{Duration, Params};
+    use super::{CycleStatistics, Duration, Params};
     use cycle_benchmark::config::ApplicationConfig;
 
     pub(super) use feo::agent::direct::primary_mpsc::{Primary, PrimaryConfig};
     pub(super) use feo::agent::direct::secondary::{Secondary, SecondaryConfig};
 
-    pub(super) fn make_primary_config(params: Params, app_config: ApplicationConfig) -> PrimaryConfig {
+    pub(super) fn make_primary_config(
+        params: Params,
+        app_config: ApplicationConfig,
+        statistics: CycleStatistics,
+    ) -> PrimaryConfig {
         assert!(
             app_config.secondaries().is_empty(),
             "mpsc-only signalling does not support multi-agent configurations",
@@ -174,6 +205,8 @@ mod direct_mpsc {
             worker_assignments: app_config.worker_assignments().remove(&agent_id).unwrap(),
             timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
+            statistics: Some(statistics),
+            ..Default::default()
         }
     }
 
@@ -183,7 +216,7 @@ mod direct_mpsc {
 }
 
 mod direct_sockets {
-    use super::{Duration, Params};
+    use super::{CycleStatistics, Duration, Params};
     use cycle_benchmark::config::{ApplicationConfig, SignallingType};
     use feo::agent::NodeAddress;
 
@@ -194,6 +227,7 @@ mod direct_sockets {
         match signalling {
             SignallingType::DirectTcp => NodeAddress::Tcp(app_config.bind_addrs().0),
             SignallingType::DirectUnix => NodeAddress::UnixSocket(app_config.socket_paths().0),
+            SignallingType::DirectShm => NodeAddress::SharedMemory(app_config.shm_path()),
             other => panic!("no endpoint defined for signalling type {other:?}"),
         }
     }
@@ -202,6 +236,7 @@ mod direct_sockets {
         params: Params,
         app_config: ApplicationConfig,
         signalling: SignallingType,
+        statistics: CycleStatistics,
     ) -> PrimaryConfig {
         let agent_id = params.agent_id;
         let all_agent_assignments = app_config
@@ -225,12 +260,14 @@ mod direct_sockets {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
+            statistics: Some(statistics),
             endpoint: endpoint(&app_config, signalling),
             activity_agent_map: app_config
                 .activity_worker_map()
                 .iter()
                 .map(|(act_id, w_id)| (*act_id, app_config.worker_agent_map().get(w_id).copied().unwrap()))
                 .collect(),
+            ..Default::default()
         }
     }
 
@@ -243,13 +280,15 @@ mod direct_sockets {
             id: params.agent_id,
             worker_assignments: app_config.worker_assignments().remove(&params.agent_id).unwrap(),
             timeout: Duration::from_secs(1),
+            heartbeat_interval: None,
             endpoint: endpoint(&app_config, signalling),
+            ..Default::default()
         }
     }
 }
 
 mod relayed_sockets {
-    use super::{Duration, Params};
+    use super::{CycleStatistics, Duration, Params};
     use cycle_benchmark::config::{ApplicationConfig, SignallingType};
     use feo::agent::NodeAddress;
 
@@ -274,6 +313,7 @@ mod relayed_sockets {
         params: Params,
         app_config: ApplicationConfig,
         signalling: SignallingType,
+        statistics: CycleStatistics,
     ) -> PrimaryConfig {
         let agent_id = params.agent_id;
         let endpoints = endpoints(&app_config, signalling);
@@ -285,11 +325,13 @@ mod relayed_sockets {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
+            statistics: Some(statistics),
             bind_address_senders: endpoints.0,
             bind_address_receivers: endpoints.1,
             id: agent_id,
             worker_agent_map: app_config.worker_agent_map(),
             activity_worker_map: app_config.activity_worker_map(),
+            ..Default::default()
         }
     }
 
@@ -305,8 +347,10 @@ mod relayed_sockets {
             id: agent_id,
             worker_assignments: app_config.worker_assignments().remove(&agent_id).unwrap(),
             timeout: Duration::from_secs(10),
+            heartbeat_interval: None,
             bind_address_senders: endpoints.0,
             bind_address_receivers: endpoints.1,
+            ..Default::default()
         }
     }
 }
@@ -16,6 +16,7 @@ use crate::composites::{composite_builder, find_composites};
 use core::net::{IpAddr, Ipv4Addr, SocketAddr};
 use feo::activity::ActivityIdAndBuilder;
 use feo::ids::{ActivityId, AgentId, WorkerId};
+use feo::paths;
 use score_log::info;
 use serde::Deserialize;
 use serde_json;
@@ -36,11 +37,15 @@ pub const BIND_ADDR2: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOS
 
 pub fn socket_paths() -> (PathBuf, PathBuf) {
     (
-        Path::new("/tmp/feo_listener1.socket").to_owned(),
-        Path::new("/tmp/feo_listener2.socket").to_owned(),
+        paths::signalling_socket(None, "listener1"),
+        paths::signalling_socket(None, "listener2"),
     )
 }
 
+pub fn shm_path() -> PathBuf {
+    Path::new("/dev/shm/feo_cycle_bench").to_owned()
+}
+
 /// Configuration of the benchmark application
 #[derive(Clone)]
 pub struct ApplicationConfig {
@@ -50,8 +55,12 @@ pub struct ApplicationConfig {
     primary_agent: AgentId,
     /// Bind address(es) of primary agent
     bind_addrs: (SocketAddr, SocketAddr),
+    /// Listen address of the metrics server of the primary agent, if any
+    metrics_addr: Option<SocketAddr>,
     /// Socket bind paths of primary agent
     socket_paths: (PathBuf, PathBuf),
+    /// Shared memory file of primary agent
+    shm_path: PathBuf,
     /// Agent assignments
     ///
     /// For each agent id, a set of worker ids running on that agent.
@@ -76,6 +85,7 @@ pub enum SignallingType {
     DirectMpsc,
     DirectTcp,
     DirectUnix,
+    DirectShm,
     RelayedTcp,
     RelayedUnix,
 }
@@ -112,10 +122,18 @@ impl ApplicationConfig {
         self.bind_addrs
     }
 
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics_addr
+    }
+
     pub fn socket_paths(&self) -> (PathBuf, PathBuf) {
         self.socket_paths.clone()
     }
 
+    pub fn shm_path(&self) -> PathBuf {
+        self.shm_path.clone()
+    }
+
     pub fn agent_assignments(&self) -> AgentAssignments {
         self.agent_assignments.clone()
     }
@@ -283,8 +301,10 @@ fn application_config() -> ApplicationConfig {
     let app_config = ApplicationConfig {
         signalling: config.signalling,
         primary_agent: AgentId::new(config.primary_agent),
-        bind_addrs: (BIND_ADDR, BIND_ADDR2),
+        bind_addrs: config.bind_addrs.unwrap_or((BIND_ADDR, BIND_ADDR2)),
+        metrics_addr: config.metrics_addr,
         socket_paths: socket_paths(),
+        shm_path: shm_path(),
         agent_assignments,
         worker_assignments,
         activity_deps,
@@ -352,6 +372,17 @@ struct RawConfig {
     signalling: SignallingType,
     /// ID of primary agent
     primary_agent: u64,
+    /// TCP bind addresses of the primary agent
+    ///
+    /// Only relevant for TCP signalling. Defaults to two ports on localhost if not given;
+    /// set it to addresses reachable from other hosts to distribute agents across machines.
+    #[serde(default)]
+    bind_addrs: Option<(SocketAddr, SocketAddr)>,
+    /// Listen address of the metrics server of the primary agent
+    ///
+    /// If given, the primary agent exposes its cycle statistics for Prometheus, see `feo::metrics`.
+    #[serde(default)]
+    metrics_addr: Option<SocketAddr>,
     /// Agent assignments
     ///
     /// For each agent id, a set of worker ids running on that agent.
@@ -24,6 +24,7 @@ use feo::activity::Activity;
 use feo::error::ActivityError;
 use feo::ids::ActivityId;
 use feo_com::interface::{ActivityInput, ActivityOutput};
+use feo_com::topic::Topic;
 use feo_tracing::instrument;
 #[cfg(feature = "com_mw")]
 use mini_adas_gen::{
@@ -58,7 +59,7 @@ pub struct Camera {
 }
 
 impl Camera {
-    pub fn build(activity_id: ActivityId, image_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, image_topic: Topic<'_, CameraImage>) -> Box<dyn Activity> {
         let output_image = output!(CameraInterface, image_topic, |i: CameraOfferedProducer<_>| i.image);
         Box::new(Self {
             activity_id,
@@ -135,7 +136,7 @@ pub struct Radar {
 }
 
 impl Radar {
-    pub fn build(activity_id: ActivityId, radar_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, radar_topic: Topic<'_, RadarScan>) -> Box<dyn Activity> {
         let output_scan = output!(RadarInterface, radar_topic, |r: RadarOfferedProducer<_>| r.scan);
         Box::new(Self {
             activity_id,
@@ -210,7 +211,12 @@ pub struct NeuralNet {
 }
 
 impl NeuralNet {
-    pub fn build(activity_id: ActivityId, image_topic: &str, scan_topic: &str, scene_topic: &str) -> Box<dyn Activity> {
+    pub fn build(
+        activity_id: ActivityId,
+        image_topic: Topic<'_, CameraImage>,
+        scan_topic: Topic<'_, RadarScan>,
+        scene_topic: Topic<'_, Scene>,
+    ) -> Box<dyn Activity> {
         let output_scene = output!(NeuralNetInterface, scene_topic, |s: NeuralNetOfferedProducer<_>| s
             .scene);
         Box::new(Self {
@@ -308,7 +314,11 @@ pub struct EmergencyBraking {
 }
 
 impl EmergencyBraking {
-    pub fn build(activity_id: ActivityId, scene_topic: &str, brake_instruction_topic: &str) -> Box<dyn Activity> {
+    pub fn build(
+        activity_id: ActivityId,
+        scene_topic: Topic<'_, Scene>,
+        brake_instruction_topic: Topic<'_, BrakeInstruction>,
+    ) -> Box<dyn Activity> {
         let output_brake_instruction = output!(
             BrakeControllerInterface,
             brake_instruction_topic,
@@ -388,7 +398,7 @@ pub struct BrakeController {
 }
 
 impl BrakeController {
-    pub fn build(activity_id: ActivityId, brake_instruction_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, brake_instruction_topic: Topic<'_, BrakeInstruction>) -> Box<dyn Activity> {
         Box::new(Self {
             activity_id,
             input_brake_instruction: input!(
@@ -446,7 +456,7 @@ pub struct EnvironmentRenderer {
 }
 
 impl EnvironmentRenderer {
-    pub fn build(activity_id: ActivityId, scene_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, scene_topic: Topic<'_, Scene>) -> Box<dyn Activity> {
         Box::new(Self {
             activity_id,
             input_scene: input!(
@@ -500,7 +510,7 @@ pub struct SteeringController {
 }
 
 impl SteeringController {
-    pub fn build(activity_id: ActivityId, steering_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, steering_topic: Topic<'_, Steering>) -> Box<dyn Activity> {
         Box::new(Self {
             activity_id,
             input_steering: input!(
@@ -550,7 +560,7 @@ pub struct LaneAssist {
 }
 
 impl LaneAssist {
-    pub fn build(activity_id: ActivityId, steering_topic: &str) -> Box<dyn Activity> {
+    pub fn build(activity_id: ActivityId, steering_topic: Topic<'_, Steering>) -> Box<dyn Activity> {
         let steering_controller = output!(
             SteeringControllerInterface,
             steering_topic,
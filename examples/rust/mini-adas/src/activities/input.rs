@@ -15,6 +15,8 @@
 use feo_com::interface::ActivityInput;
 #[cfg(not(feature = "com_mw"))]
 use feo_com::interface::FeoComData;
+#[cfg(not(feature = "com_mw"))]
+use feo_com::topic::Topic;
 
 #[cfg(feature = "com_mw")]
 use crate::config::mw_com_runtime;
@@ -31,7 +33,8 @@ use com_api::{
 macro_rules! input {
     ($interface:ident, $topic:ident, $mapping_fn:expr) => {
         Box::new(feo_com::mw_com::MwComInput::new(
-            ($mapping_fn)($crate::activities::input::create_consumer::<$interface>($topic))
+            $topic,
+            ($mapping_fn)($crate::activities::input::create_consumer::<$interface>($topic.name()))
                 .subscribe(1)
                 .unwrap(),
             feo_com::interface::DebugWrapper(core::cell::RefCell::new(SampleContainer::new(1))),
@@ -81,7 +84,7 @@ pub fn create_consumer<I: Interface + Send>(topic: &str) -> <I as Interface>::Co
 
 /// Create an activity input.
 #[cfg(not(feature = "com_mw"))]
-pub fn activity_input<T>(topic: &str) -> Box<dyn ActivityInput<T>>
+pub fn activity_input<T>(topic: Topic<'_, T>) -> Box<dyn ActivityInput<T>>
 where
     T: FeoComData + 'static,
 {
@@ -19,6 +19,8 @@ use com_api::{Builder, InstanceSpecifier, Interface, LolaRuntimeImpl, Producer,
 use feo_com::interface::ActivityOutput;
 #[cfg(not(feature = "com_mw"))]
 use feo_com::interface::FeoComData;
+#[cfg(not(feature = "com_mw"))]
+use feo_com::topic::Topic;
 #[cfg(feature = "com_mw")]
 use score_log::debug;
 
@@ -30,9 +32,12 @@ use feo_com::linux_shm::LinuxShmOutput;
 #[cfg(feature = "com_mw")]
 macro_rules! output {
     ($interface:ident, $topic:expr, $mapping_fn:expr) => {
-        Box::new(feo_com::mw_com::MwComOutput::new(feo_com::interface::DebugWrapper(
-            ($mapping_fn)($crate::activities::output::create_producer::<$interface>($topic)),
-        )))
+        Box::new(feo_com::mw_com::MwComOutput::new(
+            $topic,
+            feo_com::interface::DebugWrapper(($mapping_fn)(
+                $crate::activities::output::create_producer::<$interface>($topic.name()),
+            )),
+        ))
     };
 }
 
@@ -58,7 +63,7 @@ pub fn create_producer<I: Interface>(
 
 /// Create an activity output.
 #[cfg(not(feature = "com_mw"))]
-pub fn activity_output<T>(topic: &str) -> Box<dyn ActivityOutput<T>>
+pub fn activity_output<T>(topic: Topic<'_, T>) -> Box<dyn ActivityOutput<T>>
 where
     T: FeoComData + 'static,
 {
@@ -13,7 +13,7 @@
 
 use adas::config::init_mw_com_runtime;
 #[cfg(not(feature = "com_mw"))]
-use adas::config::{agent_assignments_ids, topic_dependencies, COM_BACKEND};
+use adas::config::{activity_dependencies, agent_assignments_ids, topic_dependencies, COM_BACKEND};
 #[cfg(not(feature = "com_mw"))]
 use feo::agent::com_init::initialize_com_primary;
 use feo::ids::AgentId;
@@ -39,6 +39,13 @@ fn main() {
 
     let config = cfg::make_config(params);
 
+    // Check the topics against the activity dependencies
+    #[cfg(not(feature = "com_mw"))]
+    if let Err(errors) = feo::dependency::validate(&activity_dependencies(), &topic_dependencies()) {
+        error!("Invalid activity dependencies: {:?}", errors);
+        std::process::exit(1);
+    }
+
     // Initialize topics. Do not drop.
     #[cfg(not(feature = "com_mw"))]
     let _topic_guards =
@@ -61,6 +68,9 @@ fn main() {
 struct Params {
     /// Cycle time in milli seconds
     feo_cycle_time: Duration,
+    /// Whether the primary starts the secondaries itself
+    #[cfg_attr(feature = "signalling_direct_mpsc", allow(dead_code))]
+    launch_secondaries: bool,
 }
 
 impl Params {
@@ -74,7 +84,37 @@ impl Params {
             .map(Duration::from_millis)
             .unwrap_or(DEFAULT_FEO_CYCLE_TIME);
 
-        Self { feo_cycle_time }
+        // Optional flag to start the secondaries from the binaries next to the primary
+        let launch_secondaries = args.iter().any(|arg| arg == "--launch-secondaries");
+
+        Self {
+            feo_cycle_time,
+            launch_secondaries,
+        }
+    }
+
+    /// Secondaries started by the primary, if requested
+    ///
+    /// The secondary binary of the same variant is expected next to the primary binary.
+    #[cfg(not(feature = "signalling_direct_mpsc"))]
+    fn secondary_processes(&self) -> Vec<feo::launcher::SecondaryProcess> {
+        if !self.launch_secondaries {
+            return Vec::new();
+        }
+        let primary = std::env::current_exe().expect("failed to get path of primary binary");
+        let name = primary
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("invalid name of primary binary")
+            .replace("adas_primary", "adas_secondary");
+        let secondary = primary.with_file_name(name).display().to_string();
+        [(1, 101), (2, 102)]
+            .into_iter()
+            .map(|(index, id)| {
+                feo::launcher::SecondaryProcess::new(AgentId::new(id), [secondary.clone(), index.to_string()])
+                    .with_max_restarts(3)
+            })
+            .collect()
     }
 }
 
@@ -93,6 +133,7 @@ mod cfg {
             worker_assignments: agent_assignments().remove(&AGENT_ID).unwrap(),
             timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
+            ..Default::default()
         }
     }
 }
@@ -103,6 +144,7 @@ mod cfg {
     use adas::config::{activity_dependencies, agent_assignments, worker_agent_map, BIND_ADDR};
     use feo::{
         agent::NodeAddress,
+        discovery::PrimaryEndpoints,
         ids::{ActivityId, WorkerId},
     };
     use std::collections::HashMap;
@@ -110,6 +152,7 @@ mod cfg {
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
+        let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
         let activity_worker_map: HashMap<ActivityId, WorkerId> = agent_assignments()
             .values()
             .flat_map(|vec| {
@@ -138,7 +181,8 @@ mod cfg {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
-            endpoint: NodeAddress::Tcp(BIND_ADDR),
+            secondary_processes: params.secondary_processes(),
+            endpoint: endpoints.endpoint_or(NodeAddress::Tcp(BIND_ADDR)),
             activity_agent_map: activity_worker_map
                 .iter()
                 .map(|(activity_id, worker_id)| {
@@ -147,6 +191,7 @@ mod cfg {
                 })
                 .collect(),
             all_agent_assignments,
+            ..Default::default()
         }
     }
 }
@@ -157,6 +202,7 @@ mod cfg {
     use adas::config::{activity_dependencies, agent_assignments, socket_paths, worker_agent_map};
     use feo::{
         agent::NodeAddress,
+        discovery::PrimaryEndpoints,
         ids::{ActivityId, WorkerId},
     };
     use std::collections::HashMap;
@@ -164,6 +210,7 @@ mod cfg {
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
+        let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
         let activity_worker_map: HashMap<ActivityId, WorkerId> = agent_assignments()
             .values()
             .flat_map(|vec| {
@@ -191,7 +238,8 @@ mod cfg {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
-            endpoint: NodeAddress::UnixSocket(socket_paths().0),
+            secondary_processes: params.secondary_processes(),
+            endpoint: endpoints.endpoint_or(NodeAddress::UnixSocket(socket_paths().0)),
             activity_agent_map: activity_worker_map
                 .iter()
                 .map(|(activity_id, worker_id)| {
@@ -200,6 +248,7 @@ mod cfg {
                 })
                 .collect(),
             all_agent_assignments,
+            ..Default::default()
         }
     }
 }
@@ -209,12 +258,14 @@ mod cfg {
     use super::{Duration, Params, AGENT_ID};
     use adas::config::{activity_dependencies, agent_assignments, worker_agent_map, BIND_ADDR, BIND_ADDR2};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo::ids::{ActivityId, WorkerId};
     use std::collections::HashMap;
 
     pub(super) use feo::agent::relayed::primary::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
+        let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
         let activity_worker_map: HashMap<ActivityId, WorkerId> = agent_assignments()
             .values()
             .flat_map(|vec| {
@@ -230,11 +281,13 @@ mod cfg {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
-            bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
-            bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+            secondary_processes: params.secondary_processes(),
+            bind_address_senders: endpoints.endpoint_or(NodeAddress::Tcp(BIND_ADDR)),
+            bind_address_receivers: endpoints.receivers_or(NodeAddress::Tcp(BIND_ADDR2)),
             id: AGENT_ID,
             worker_agent_map: worker_agent_map(),
             activity_worker_map,
+            ..Default::default()
         }
     }
 }
@@ -244,12 +297,14 @@ mod cfg {
     use super::{Duration, Params, AGENT_ID};
     use adas::config::{activity_dependencies, agent_assignments, socket_paths, worker_agent_map};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo::ids::{ActivityId, WorkerId};
     use std::collections::HashMap;
 
     pub(super) use feo::agent::relayed::primary::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
+        let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
         let activity_worker_map: HashMap<ActivityId, WorkerId> = agent_assignments()
             .values()
             .flat_map(|vec| {
@@ -265,11 +320,13 @@ mod cfg {
             timeout: Duration::from_secs(10),
             connection_timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
-            bind_address_senders: NodeAddress::UnixSocket(socket_paths().0),
-            bind_address_receivers: NodeAddress::UnixSocket(socket_paths().1),
+            secondary_processes: params.secondary_processes(),
+            bind_address_senders: endpoints.endpoint_or(NodeAddress::UnixSocket(socket_paths().0)),
+            bind_address_receivers: endpoints.receivers_or(NodeAddress::UnixSocket(socket_paths().1)),
             id: AGENT_ID,
             worker_agent_map: worker_agent_map(),
             activity_worker_map,
+            ..Default::default()
         }
     }
 }
@@ -280,6 +337,7 @@ mod cfg {
     use adas::config::worker_agent_map;
     use adas::config::{activity_dependencies, agent_assignments};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo::ids::ActivityId;
     use feo::ids::WorkerId;
     use std::collections::HashMap;
@@ -287,6 +345,7 @@ mod cfg {
     pub(super) use feo::agent::direct::primary::{Primary, PrimaryConfig};
 
     pub(super) fn make_config(params: Params) -> PrimaryConfig {
+        let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
         let all_agent_assignments = agent_assignments()
             .iter()
             .map(|(a, v)| {
@@ -315,8 +374,9 @@ mod cfg {
             worker_assignments: agent_assignments().remove(&AGENT_ID).unwrap(),
             timeout: Duration::from_secs(10),
             startup_timeout: Duration::from_secs(10),
+            secondary_processes: params.secondary_processes(),
             connection_timeout: Duration::from_secs(10),
-            endpoint: NodeAddress::MwCom,
+            endpoint: endpoints.endpoint_or(NodeAddress::MwCom),
             activity_agent_map: activity_worker_map
                 .iter()
                 .map(|(activity_id, worker_id)| {
@@ -324,6 +384,7 @@ mod cfg {
                     (*activity_id, agent_id)
                 })
                 .collect(),
+            ..Default::default()
         }
     }
 }
@@ -36,6 +36,7 @@ fn main() {
     use adas::config::BIND_ADDR;
     use feo::agent::direct::secondary::{Secondary, SecondaryConfig};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo_time::Duration;
     use params::Params;
 
@@ -46,16 +47,19 @@ fn main() {
     let params = Params::from_args();
     info!("Starting agent {}", params.agent_id);
 
+    let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
     let config = SecondaryConfig {
         id: params.agent_id,
         worker_assignments: agent_assignments().remove(&params.agent_id).unwrap(),
         timeout: Duration::from_secs(1),
+        heartbeat_interval: None,
         #[cfg(feature = "signalling_direct_tcp")]
-        endpoint: NodeAddress::Tcp(BIND_ADDR),
+        endpoint: endpoints.endpoint_or(NodeAddress::Tcp(BIND_ADDR)),
         #[cfg(feature = "signalling_direct_unix")]
-        endpoint: NodeAddress::UnixSocket(socket_paths().0),
+        endpoint: endpoints.endpoint_or(NodeAddress::UnixSocket(socket_paths().0)),
         #[cfg(feature = "signalling_direct_mw_com")]
-        endpoint: NodeAddress::MwCom,
+        endpoint: endpoints.endpoint_or(NodeAddress::MwCom),
+        ..Default::default()
     };
 
     // determine set of activity ids belonging to this agent
@@ -76,7 +80,7 @@ fn main() {
     let _topic_guards = initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
 
     let secondary = Secondary::new(config, runtime);
-    secondary.run();
+    secondary.run().unwrap();
 }
 
 #[cfg(feature = "signalling_relayed_tcp")]
@@ -87,6 +91,7 @@ fn main() {
     use adas::config::{BIND_ADDR, BIND_ADDR2};
     use feo::agent::relayed::secondary::{Secondary, SecondaryConfig};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo_time::Duration;
     use params::Params;
 
@@ -97,12 +102,15 @@ fn main() {
     let params = Params::from_args();
     info!("Starting agent {}", params.agent_id);
 
+    let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
     let config = SecondaryConfig {
         id: params.agent_id,
         worker_assignments: agent_assignments().remove(&params.agent_id).unwrap(),
         timeout: Duration::from_secs(10),
-        bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
-        bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+        heartbeat_interval: None,
+        bind_address_senders: endpoints.endpoint_or(NodeAddress::Tcp(BIND_ADDR)),
+        bind_address_receivers: endpoints.receivers_or(NodeAddress::Tcp(BIND_ADDR2)),
+        ..Default::default()
     };
 
     // determine set of activity ids belonging to this agent
@@ -123,8 +131,8 @@ fn main() {
     #[cfg(not(feature = "com_mw"))]
     let _topic_guards = initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
 
-    let secondary = Secondary::new(config);
-    secondary.run();
+    let secondary = Secondary::new(config).expect("failed to create secondary agent");
+    secondary.run().unwrap();
 }
 
 #[cfg(feature = "signalling_relayed_unix")]
@@ -135,6 +143,7 @@ fn main() {
     use adas::config::socket_paths;
     use feo::agent::relayed::secondary::{Secondary, SecondaryConfig};
     use feo::agent::NodeAddress;
+    use feo::discovery::PrimaryEndpoints;
     use feo_time::Duration;
     use params::Params;
 
@@ -144,12 +153,15 @@ fn main() {
     let params = Params::from_args();
     info!("Starting agent {}", params.agent_id);
 
+    let endpoints = PrimaryEndpoints::lookup().expect("failed to look up primary endpoints");
     let config = SecondaryConfig {
         id: params.agent_id,
         worker_assignments: agent_assignments().remove(&params.agent_id).unwrap(),
         timeout: Duration::from_secs(10),
-        bind_address_senders: NodeAddress::UnixSocket(socket_paths().0),
-        bind_address_receivers: NodeAddress::UnixSocket(socket_paths().1),
+        heartbeat_interval: None,
+        bind_address_senders: endpoints.endpoint_or(NodeAddress::UnixSocket(socket_paths().0)),
+        bind_address_receivers: endpoints.receivers_or(NodeAddress::UnixSocket(socket_paths().1)),
+        ..Default::default()
     };
 
     // determine set of activity ids belonging to this agent
@@ -170,8 +182,8 @@ fn main() {
     #[cfg(not(feature = "com_mw"))]
     let _topic_guards = initialize_com_secondary(COM_BACKEND, topic_dependencies(), &local_activities);
 
-    let secondary = Secondary::new(config);
-    secondary.run();
+    let secondary = Secondary::new(config).expect("failed to create secondary agent");
+    secondary.run().unwrap();
 }
 
 #[cfg(feature = "signalling_direct_mpsc")]
@@ -19,12 +19,13 @@ use com_api::{Builder, LolaRuntimeBuilderImpl, LolaRuntimeImpl, RuntimeBuilder};
 use core::net::{IpAddr, Ipv4Addr, SocketAddr};
 use feo::activity::{ActivityBuilder, ActivityIdAndBuilder};
 use feo::ids::{ActivityId, AgentId, WorkerId};
+use feo::paths;
 use feo::topicspec::{Direction, TopicSpecification};
 #[cfg(not(feature = "com_mw"))]
 use feo_com::interface::ComBackend;
 use mini_adas_gen::{BrakeInstruction, CameraImage, RadarScan, Scene, Steering};
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 pub type WorkerAssignment = (WorkerId, Vec<(ActivityId, Box<dyn ActivityBuilder>)>);
@@ -40,11 +41,13 @@ pub const COM_BACKEND: ComBackend = ComBackend::LinuxShm;
 pub const BIND_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8081);
 pub const BIND_ADDR2: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8082);
 
-pub const TOPIC_INFERRED_SCENE: &str = "/feo/com/MiniAdasNeuralNet";
-pub const TOPIC_CONTROL_BRAKES: &str = "/feo/com/MiniAdasBrakeController";
-pub const TOPIC_CONTROL_STEERING: &str = "/feo/com/MiniAdasSteeringController";
-pub const TOPIC_CAMERA_FRONT: &str = "/feo/com/MiniAdasCamera";
-pub const TOPIC_RADAR_FRONT: &str = "/feo/com/MiniAdasRadar";
+feo_com::topics! {
+    pub TOPIC_INFERRED_SCENE: Scene = "/feo/com/MiniAdasNeuralNet";
+    pub TOPIC_CONTROL_BRAKES: BrakeInstruction = "/feo/com/MiniAdasBrakeController";
+    pub TOPIC_CONTROL_STEERING: Steering = "/feo/com/MiniAdasSteeringController";
+    pub TOPIC_CAMERA_FRONT: CameraImage = "/feo/com/MiniAdasCamera";
+    pub TOPIC_RADAR_FRONT: RadarScan = "/feo/com/MiniAdasRadar";
+}
 
 static MW_COM_RUNTIME: OnceLock<LolaRuntimeImpl> = OnceLock::new();
 
@@ -65,8 +68,8 @@ pub fn mw_com_runtime() -> &'static LolaRuntimeImpl {
 
 pub fn socket_paths() -> (PathBuf, PathBuf) {
     (
-        Path::new("/tmp/feo_listener1.socket").to_owned(),
-        Path::new("/tmp/feo_listener2.socket").to_owned(),
+        paths::signalling_socket(None, "listener1"),
+        paths::signalling_socket(None, "listener2"),
     )
 }
 
@@ -190,9 +193,9 @@ pub fn topic_dependencies<'a>() -> Vec<TopicSpecification<'a>> {
     use Direction::*;
 
     vec![
-        TopicSpecification::new::<CameraImage>(TOPIC_CAMERA_FRONT, vec![(0.into(), Outgoing), (2.into(), Incoming)]),
-        TopicSpecification::new::<RadarScan>(TOPIC_RADAR_FRONT, vec![(1.into(), Outgoing), (2.into(), Incoming)]),
-        TopicSpecification::new::<Scene>(
+        TopicSpecification::new(TOPIC_CAMERA_FRONT, vec![(0.into(), Outgoing), (2.into(), Incoming)]),
+        TopicSpecification::new(TOPIC_RADAR_FRONT, vec![(1.into(), Outgoing), (2.into(), Incoming)]),
+        TopicSpecification::new(
             TOPIC_INFERRED_SCENE,
             vec![
                 (2.into(), Outgoing),
@@ -201,11 +204,8 @@ pub fn topic_dependencies<'a>() -> Vec<TopicSpecification<'a>> {
                 (5.into(), Incoming),
             ],
         ),
-        TopicSpecification::new::<BrakeInstruction>(
-            TOPIC_CONTROL_BRAKES,
-            vec![(4.into(), Outgoing), (6.into(), Incoming)],
-        ),
-        TopicSpecification::new::<Steering>(TOPIC_CONTROL_STEERING, vec![(5.into(), Outgoing), (7.into(), Incoming)]),
+        TopicSpecification::new(TOPIC_CONTROL_BRAKES, vec![(4.into(), Outgoing), (6.into(), Incoming)]),
+        TopicSpecification::new(TOPIC_CONTROL_STEERING, vec![(5.into(), Outgoing), (7.into(), Incoming)]),
     ]
 }
 
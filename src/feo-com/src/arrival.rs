@@ -0,0 +1,84 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Notification of samples arriving on a topic
+//!
+//! Listeners registered with [on_arrival] are called after each sample sent successfully on their topic,
+//! e.g. to start the processing of a new camera frame as soon as it arrives.
+//!
+//! Like the [latency](crate::latency) measurement, arrivals are observed for outputs in the same
+//! process only. Listeners are called on the thread of the sending activity and must return quickly.
+//! While no listener is registered for a topic, sending only checks a flag.
+
+use crate::topic::Topic;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+/// Listener called on the arrival of a sample
+type Listener = Box<dyn Fn() + Send + Sync>;
+
+/// Arrival state per topic
+static TOPICS: LazyLock<Mutex<BTreeMap<Box<str>, Arc<TopicArrival>>>> = LazyLock::new(Default::default);
+
+/// Call the given listener after each sample sent on the given topic in this process
+///
+/// Listeners cannot be removed and apply to outputs created before and after registering.
+pub fn on_arrival<T>(topic: Topic<'_, T>, listener: impl Fn() + Send + Sync + 'static) {
+    let arrival = TopicArrival::of(topic.name());
+    lock(&arrival.listeners).push(Box::new(listener));
+    arrival.active.store(true, Ordering::Release);
+}
+
+/// Arrival state of a topic, shared by its outputs in this process
+#[derive(Default)]
+pub(crate) struct TopicArrival {
+    /// Whether any listener is registered
+    active: AtomicBool,
+    /// Registered listeners
+    listeners: Mutex<Vec<Listener>>,
+}
+
+impl TopicArrival {
+    /// Get the arrival state of the given topic
+    pub(crate) fn of(topic: &str) -> Arc<Self> {
+        lock(&TOPICS).entry(topic.into()).or_default().clone()
+    }
+
+    /// Note a sample sent on the topic, calling the registered listeners
+    pub(crate) fn sent(&self) {
+        if !self.active.load(Ordering::Acquire) {
+            return;
+        }
+        for listener in lock(&self.listeners).iter() {
+            listener();
+        }
+    }
+}
+
+// Implemented manually as listeners are not `Debug`
+impl fmt::Debug for TopicArrival {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopicArrival")
+            .field("active", &self.active)
+            .finish_non_exhaustive()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,398 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! End-to-end (E2E) protection of topic data
+//!
+//! Wrap the [ActivityOutput] of a publisher in an [E2eOutput] and the [ActivityInput]
+//! of a subscriber in an [E2eInput] to protect the samples of a topic against corruption,
+//! repetition and loss on their way between activities. The topic then transports [E2eSample]s:
+//! the payload, followed by an alive counter incremented with each published sample
+//! and a CRC over the data id, the counter and the payload.
+//!
+//! The CRC is the CRC-32P4 of the AUTOSAR E2E profile 4. The bytes of the payload are
+//! protected as they are in memory, so payload types have to implement [E2eData].
+
+use crate::interface::{ActivityInput, ActivityOutput, Error, FeoComData, InputGuard};
+use alloc::boxed::Box;
+use core::fmt;
+use core::mem::{size_of, MaybeUninit};
+use core::ops::Deref;
+use core::ptr::addr_of_mut;
+use score_log::fmt::ScoreDebug;
+
+/// Reflected polynomial of the CRC-32P4 (0xF4ACFB13)
+const CRC32P4_POLYNOMIAL: u32 = 0xC8DF_352F;
+
+/// Lookup table of the CRC-32P4
+const CRC32P4_TABLE: [u32; 256] = crc32p4_table();
+
+/// Marker for payload types which can be protected by their bytes in memory
+///
+/// # Safety
+///
+/// All bytes of the type have to be initialized for every valid value, i.e. the type
+/// must not contain padding bytes or [MaybeUninit] fields. As received samples may be
+/// corrupted, every bit pattern also has to be a valid value, which rules out e.g. `bool`,
+/// `char`, enums and references. This holds for primitive numbers, arrays of them and
+/// `#[repr(C)]` structs of them whose fields leave no gaps.
+pub unsafe trait E2eData {}
+
+macro_rules! impl_e2e_data {
+    ($($ty:ty),*) => {
+        $(
+            // SAFETY: primitive numbers have no padding and every bit pattern is a valid value
+            unsafe impl E2eData for $ty {}
+        )*
+    };
+}
+
+impl_e2e_data!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+// SAFETY: arrays have no padding between their elements
+unsafe impl<T: E2eData, const N: usize> E2eData for [T; N] {}
+
+/// Result of the E2E check of a received sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, score_log::ScoreDebug)]
+pub enum E2eStatus {
+    /// The sample is valid and directly follows the previously received one
+    Ok,
+    /// The sample is valid but has already been received before
+    Repeated,
+    /// The CRC of the sample does not match, the sample must not be used
+    WrongCrc,
+    /// The sample is valid but samples have been lost since the previously received one
+    Lost,
+}
+
+/// Sample of an E2E protected topic
+#[derive(Debug)]
+#[repr(C)]
+pub struct E2eSample<T> {
+    payload: T,
+    counter: u16,
+    crc: u32,
+}
+
+impl<T> E2eSample<T> {
+    /// Payload of the sample
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+
+    /// Alive counter of the sample
+    pub fn counter(&self) -> u16 {
+        self.counter
+    }
+}
+
+impl<T> Deref for E2eSample<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.payload
+    }
+}
+
+impl<T: ScoreDebug> ScoreDebug for E2eSample<T> {
+    fn fmt(&self, w: &mut dyn score_log::fmt::ScoreWrite, spec: &score_log::fmt::FormatSpec) -> score_log::fmt::Result {
+        ScoreDebug::fmt(&self.payload, w, spec)
+    }
+}
+
+#[cfg(feature = "ipc_mw_com")]
+// SAFETY: safe to relocate if the payload is
+unsafe impl<T: com_api::Reloc> com_api::Reloc for E2eSample<T> {}
+
+#[cfg(feature = "ipc_mw_com")]
+impl<T: com_api::CommData> com_api::CommData for E2eSample<T> {
+    const ID: &'static str = T::ID;
+}
+
+/// Publisher side of the E2E protection, setting counter and CRC of outgoing samples
+#[derive(Debug, Clone)]
+pub struct E2eProtector {
+    data_id: u32,
+    counter: u16,
+}
+
+impl E2eProtector {
+    /// Create a protector for the data with the given id
+    ///
+    /// The data id is part of the CRC, so that samples of other topics are detected.
+    pub fn new(data_id: u32) -> Self {
+        Self { data_id, counter: 0 }
+    }
+
+    /// Create a protected sample from the given payload
+    pub fn protect<T: E2eData>(&mut self, payload: T) -> E2eSample<T> {
+        let counter = self.next_counter();
+        let crc = sample_crc(self.data_id, counter, &payload);
+        E2eSample { payload, counter, crc }
+    }
+
+    /// Protect a sample in place whose payload has been initialized already
+    ///
+    /// # Safety
+    ///
+    /// The payload of `sample` has to be initialized with a valid value.
+    unsafe fn protect_in_place<T: E2eData>(&mut self, sample: &mut MaybeUninit<E2eSample<T>>) {
+        let counter = self.next_counter();
+        let sample = sample.as_mut_ptr();
+        // SAFETY: The caller guarantees that the payload is initialized
+        let crc = sample_crc(self.data_id, counter, unsafe { &*addr_of_mut!((*sample).payload) });
+        // SAFETY: Fields are written through raw pointers without creating references to uninitialized memory
+        unsafe {
+            addr_of_mut!((*sample).counter).write(counter);
+            addr_of_mut!((*sample).crc).write(crc);
+        }
+    }
+
+    fn next_counter(&mut self) -> u16 {
+        let counter = self.counter;
+        self.counter = self.counter.wrapping_add(1);
+        counter
+    }
+}
+
+/// Subscriber side of the E2E protection, checking counter and CRC of incoming samples
+#[derive(Debug, Clone)]
+pub struct E2eChecker {
+    data_id: u32,
+    last_counter: Option<u16>,
+}
+
+impl E2eChecker {
+    /// Create a checker for the data with the given id
+    pub fn new(data_id: u32) -> Self {
+        Self {
+            data_id,
+            last_counter: None,
+        }
+    }
+
+    /// Check a received sample
+    ///
+    /// Samples with a wrong CRC do not affect the state of the checker.
+    pub fn check<T: E2eData>(&mut self, sample: &E2eSample<T>) -> E2eStatus {
+        if sample_crc(self.data_id, sample.counter, &sample.payload) != sample.crc {
+            return E2eStatus::WrongCrc;
+        }
+        let status = match self.last_counter {
+            None => E2eStatus::Ok,
+            Some(last) => match sample.counter.wrapping_sub(last) {
+                0 => E2eStatus::Repeated,
+                1 => E2eStatus::Ok,
+                _ => E2eStatus::Lost,
+            },
+        };
+        self.last_counter = Some(sample.counter);
+        status
+    }
+}
+
+/// Activity output publishing E2E protected samples
+pub struct E2eOutput<T>
+where
+    E2eSample<T>: FeoComData,
+{
+    output: Box<dyn ActivityOutput<E2eSample<T>>>,
+    protector: E2eProtector,
+}
+
+impl<T> E2eOutput<T>
+where
+    T: E2eData,
+    E2eSample<T>: FeoComData,
+{
+    /// Protect the samples written to `output` with the given data id
+    pub fn new(output: Box<dyn ActivityOutput<E2eSample<T>>>, data_id: u32) -> Self {
+        Self {
+            output,
+            protector: E2eProtector::new(data_id),
+        }
+    }
+
+    /// Protect and send the given payload
+    pub fn publish(&mut self, payload: T) -> Result<(), Error> {
+        let sample = self.protector.protect(payload);
+        self.output.write_uninit()?.write_payload(sample).send()
+    }
+
+    /// Loan an output buffer, initialize its payload in place with `init`, protect and send it
+    ///
+    /// # Safety
+    ///
+    /// The caller has to ensure that `init` completely initializes
+    /// the payload with a valid value.
+    pub unsafe fn publish_loaned(&mut self, init: impl FnOnce(&mut MaybeUninit<T>)) -> Result<(), Error> {
+        let mut sample = self.output.loan()?;
+        // SAFETY: MaybeUninit<T> has the same layout as T, the payload is not read before `init` returns
+        let payload = unsafe { &mut *addr_of_mut!((*sample.as_mut_ptr()).payload).cast::<MaybeUninit<T>>() };
+        init(payload);
+        // SAFETY: The caller guarantees that `init` fully initialized the payload,
+        // counter and CRC are written by the protector.
+        let sample = unsafe {
+            self.protector.protect_in_place(&mut *sample);
+            sample.assume_init()
+        };
+        sample.send()
+    }
+}
+
+impl<T> fmt::Debug for E2eOutput<T>
+where
+    E2eSample<T>: FeoComData,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("E2eOutput")
+            .field("output", &self.output)
+            .field("protector", &self.protector)
+            .finish()
+    }
+}
+
+/// Activity input checking E2E protected samples
+pub struct E2eInput<T>
+where
+    E2eSample<T>: FeoComData,
+{
+    input: Box<dyn ActivityInput<E2eSample<T>>>,
+    checker: E2eChecker,
+}
+
+impl<T> E2eInput<T>
+where
+    T: E2eData,
+    E2eSample<T>: FeoComData,
+{
+    /// Check the samples read from `input` against the given data id
+    pub fn new(input: Box<dyn ActivityInput<E2eSample<T>>>, data_id: u32) -> Self {
+        Self {
+            input,
+            checker: E2eChecker::new(data_id),
+        }
+    }
+
+    /// Get a handle to the current input buffer together with the result of its E2E check
+    ///
+    /// The payload must not be used if the status is [E2eStatus::WrongCrc].
+    pub fn read(&mut self) -> Result<(InputGuard<'_, E2eSample<T>>, E2eStatus), Error> {
+        let sample = self.input.read()?;
+        let status = self.checker.check(&*sample);
+        Ok((sample, status))
+    }
+}
+
+impl<T> fmt::Debug for E2eInput<T>
+where
+    E2eSample<T>: FeoComData,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("E2eInput")
+            .field("input", &self.input)
+            .field("checker", &self.checker)
+            .finish()
+    }
+}
+
+/// CRC of a sample over data id, counter and payload
+fn sample_crc<T: E2eData>(data_id: u32, counter: u16, payload: &T) -> u32 {
+    // SAFETY: E2eData guarantees that all bytes of the payload are initialized
+    let payload = unsafe { core::slice::from_raw_parts((payload as *const T).cast::<u8>(), size_of::<T>()) };
+    let crc = crc32p4_update(!0, &data_id.to_le_bytes());
+    let crc = crc32p4_update(crc, &counter.to_le_bytes());
+    !crc32p4_update(crc, payload)
+}
+
+/// Feed `bytes` into a running CRC-32P4
+fn crc32p4_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, byte| {
+        CRC32P4_TABLE[((crc ^ u32::from(*byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Generate the lookup table of the CRC-32P4
+const fn crc32p4_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32P4_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32p4_matches_check_value() {
+        assert_eq!(!crc32p4_update(!0, b"123456789"), 0x1697_D06A);
+    }
+
+    #[test]
+    fn checker_reports_repeated_lost_and_corrupted_samples() {
+        let mut protector = E2eProtector::new(0x0100);
+        let mut checker = E2eChecker::new(0x0100);
+
+        let first = protector.protect([1u32, 2, 3]);
+        assert_eq!(checker.check(&first), E2eStatus::Ok);
+        assert_eq!(checker.check(&first), E2eStatus::Repeated);
+
+        let second = protector.protect([4u32, 5, 6]);
+        assert_eq!(checker.check(&second), E2eStatus::Ok);
+
+        // The third sample is lost
+        let _ = protector.protect([7u32, 8, 9]);
+        let fourth = protector.protect([10u32, 11, 12]);
+        assert_eq!(checker.check(&fourth), E2eStatus::Lost);
+
+        // Corrupted samples do not affect the state of the checker
+        let mut fifth = protector.protect([13u32, 14, 15]);
+        fifth.payload[1] ^= 0x10;
+        assert_eq!(checker.check(&fifth), E2eStatus::WrongCrc);
+        fifth.payload[1] ^= 0x10;
+        fifth.crc ^= 1;
+        assert_eq!(checker.check(&fifth), E2eStatus::WrongCrc);
+        fifth.crc ^= 1;
+        assert_eq!(checker.check(&fifth), E2eStatus::Ok);
+
+        // Samples of other data ids are detected
+        let other = E2eProtector::new(0x0200).protect([13u32, 14, 15]);
+        assert_eq!(checker.check(&other), E2eStatus::WrongCrc);
+    }
+
+    #[test]
+    fn counter_wraps_around() {
+        let mut protector = E2eProtector {
+            data_id: 7,
+            counter: u16::MAX,
+        };
+        let mut checker = E2eChecker::new(7);
+        assert_eq!(checker.check(&protector.protect(1u8)), E2eStatus::Ok);
+        let sample = protector.protect(2u8);
+        assert_eq!(sample.counter(), 0);
+        assert_eq!(checker.check(&sample), E2eStatus::Ok);
+    }
+}
@@ -24,6 +24,11 @@
 //! We have [ActivityInput], [ActivityOutput] and [ActivityOutputDefault] defined as traits,
 //! but their trait methods return types of a known size,
 //! the enums [InputGuard], [OutputGuard] and [OutputUninitGuard].
+//!
+//! Large payloads should be constructed in place using `loan` or `publish_loaned`
+//! of [ActivityOutput] trait objects. With shared-memory backends such as iceoryx2,
+//! the loaned buffer is the very sample handed to subscribers,
+//! so neither serialization nor copying of the payload takes place.
 
 #[cfg(feature = "ipc_iceoryx2")]
 use crate::iox2;
@@ -44,7 +49,8 @@ use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
 use score_log::fmt::ScoreDebug;
 
-pub type Topic<'a> = &'a str;
+/// Name of a topic, see [Topic](crate::topic::Topic) for the typed handle of a topic
+pub type TopicName<'a> = &'a str;
 
 #[cfg(feature = "ipc_mw_com")]
 pub trait FeoComData: Debug + ScoreDebug + com_api::CommData {}
@@ -185,6 +191,32 @@ where
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<'_, T>, Error>;
 }
 
+impl<T> dyn ActivityOutput<T> + '_
+where
+    T: FeoComData,
+{
+    /// Loan an uninitialized output buffer to construct a payload in place
+    ///
+    /// For shared-memory backends, the buffer is located in the shared memory
+    /// read by subscribers, avoiding any copy of the payload when sending it.
+    pub fn loan(&mut self) -> Result<OutputUninitGuard<'_, T>, Error> {
+        self.write_uninit()
+    }
+
+    /// Loan an output buffer, initialize it in place with `init` and send it
+    ///
+    /// # Safety
+    ///
+    /// The caller has to ensure that `init` completely initializes
+    /// the buffer with a valid value.
+    pub unsafe fn publish_loaned(&mut self, init: impl FnOnce(&mut MaybeUninit<T>)) -> Result<(), Error> {
+        let mut sample = self.loan()?;
+        init(&mut sample);
+        // Safety: The caller guarantees that `init` fully initialized the buffer.
+        unsafe { sample.assume_init() }.send()
+    }
+}
+
 /// A trait for structs which can provide handles to default-initialized output buffers
 pub trait ActivityOutputDefault<T>: Debug
 where
@@ -409,7 +441,7 @@ where
 #[derive(Clone, Copy)]
 #[allow(unused)]
 pub struct ComBackendTopicPrimaryInitialization<'a> {
-    topic: Topic<'a>,
+    topic: TopicName<'a>,
     backend: ComBackend,
     readers: usize,
     writers: usize,
@@ -419,7 +451,7 @@ pub struct ComBackendTopicPrimaryInitialization<'a> {
 
 impl<'a> ComBackendTopicPrimaryInitialization<'a> {
     pub fn new(
-        topic: Topic<'a>,
+        topic: TopicName<'a>,
         backend: ComBackend,
         readers: usize,
         writers: usize,
@@ -440,13 +472,13 @@ impl<'a> ComBackendTopicPrimaryInitialization<'a> {
 /// COM backend topic initialization arguments for secondary agents
 #[derive(Clone, Copy)]
 pub struct ComBackendTopicSecondaryInitialization<'a> {
-    topic: Topic<'a>,
+    topic: TopicName<'a>,
     backend: ComBackend,
     is_local_write: bool,
 }
 
 impl<'a> ComBackendTopicSecondaryInitialization<'a> {
-    pub fn new(topic: Topic<'a>, backend: ComBackend, is_local_write: bool) -> Self {
+    pub fn new(topic: TopicName<'a>, backend: ComBackend, is_local_write: bool) -> Self {
         Self {
             topic,
             backend,
@@ -13,12 +13,16 @@
 
 //! iceoryx2 com backend
 
+pub mod service;
+
 use crate::interface::FeoComData;
 use crate::interface::FeoComDefault;
 use crate::interface::{
-    ActivityInput, ActivityOutput, ActivityOutputDefault, Error, InputGuard, OutputGuard, OutputUninitGuard, Topic,
-    TopicHandle,
+    ActivityInput, ActivityOutput, ActivityOutputDefault, Error, InputGuard, OutputGuard, OutputUninitGuard,
+    TopicHandle, TopicName,
 };
+use crate::topic::Topic;
+use crate::trace::{InputTrace, OutputTrace};
 use alloc::boxed::Box;
 use alloc::format;
 use core::mem::MaybeUninit;
@@ -36,7 +40,7 @@ use score_log::{error, info};
 use std::process;
 
 /// Initialize topic with the given number of writers (publishers) and readers (subscribers).
-pub fn init_topic<T: FeoComData + 'static>(topic: Topic, writers: usize, readers: usize) -> TopicHandle {
+pub fn init_topic<T: FeoComData + 'static>(topic: TopicName, writers: usize, readers: usize) -> TopicHandle {
     info!(
         "Initializing topic {} (Iceoryx2, {} writers and {} readers)",
         topic, writers, readers
@@ -60,6 +64,7 @@ where
     T: FeoComData + 'static,
 {
     subscriber: Subscriber<ipc::Service, T, ()>,
+    trace: InputTrace,
 }
 
 impl<T> Iox2Input<T>
@@ -67,7 +72,8 @@ where
     T: FeoComData + 'static,
 {
     // Create a new instance for the given `topic`
-    pub fn new(topic: &str) -> Self {
+    pub fn new(topic: Topic<'_, T>) -> Self {
+        let topic = topic.name();
         let subscriber = ipc_node()
             .service_builder(&topic.try_into().unwrap_or_else(|_| panic!("invalid topic {topic}")))
             .publish_subscribe::<T>()
@@ -76,7 +82,8 @@ where
             .subscriber_builder()
             .create()
             .unwrap_or_else(|_| panic!("failed to create subscriber for topic {topic}"));
-        Self { subscriber }
+        let trace = InputTrace::new(topic);
+        Self { subscriber, trace }
     }
 }
 
@@ -86,7 +93,10 @@ where
 {
     fn read(&self) -> Result<InputGuard<'_, T>, Error> {
         match self.subscriber.receive() {
-            Ok(Some(sample)) => Ok(InputGuard::Iox2(Iox2InputGuard { sample })),
+            Ok(Some(sample)) => {
+                self.trace.received::<T>();
+                Ok(InputGuard::Iox2(Iox2InputGuard { sample }))
+            },
             Ok(None) | Err(_) => Err(Error::NoEmptyBuffer),
         }
     }
@@ -99,6 +109,7 @@ where
     T: FeoComData + 'static,
 {
     publisher: Publisher<ipc::Service, T, ()>,
+    trace: OutputTrace,
 }
 
 impl<T> Iox2Output<T>
@@ -106,7 +117,8 @@ where
     T: FeoComData + 'static,
 {
     // Create a new instance for the given `topic`
-    pub fn new(topic: &str) -> Self {
+    pub fn new(topic: Topic<'_, T>) -> Self {
+        let topic = topic.name();
         let publisher = ipc_node()
             .service_builder(&topic.try_into().unwrap_or_else(|_| panic!("invalid topic {topic}")))
             .publish_subscribe::<T>()
@@ -115,7 +127,8 @@ where
             .publisher_builder()
             .create()
             .unwrap_or_else(|_| panic!("failed to create subscriber for topic {topic}"));
-        Self { publisher }
+        let trace = OutputTrace::new(topic);
+        Self { publisher, trace }
     }
 }
 
@@ -127,7 +140,10 @@ where
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<'_, T>, Error> {
         self.publisher
             .loan_uninit()
-            .map(|sample| OutputUninitGuard::Iox2(Iox2OutputUninitGuard { sample }))
+            .map(|sample| {
+                let trace = self.trace.clone();
+                OutputUninitGuard::Iox2(Iox2OutputUninitGuard { sample, trace })
+            })
             .map_err(|_| Error::NoEmptyBuffer)
     }
 }
@@ -140,7 +156,10 @@ where
     fn write_init(&mut self) -> Result<OutputGuard<'_, T>, Error> {
         self.publisher
             .loan()
-            .map(|sample| OutputGuard::Iox2(Iox2OutputGuard { sample }))
+            .map(|sample| {
+                let trace = self.trace.clone();
+                OutputGuard::Iox2(Iox2OutputGuard { sample, trace })
+            })
             .map_err(|_| Error::NoEmptyBuffer)
     }
 }
@@ -161,6 +180,7 @@ impl<T: FeoComData> Deref for Iox2InputGuard<T> {
 /// Handle to an initialized output buffer
 pub struct Iox2OutputGuard<T: FeoComData> {
     sample: SampleMut<ipc::Service, T, ()>,
+    trace: OutputTrace,
 }
 
 impl<T> Iox2OutputGuard<T>
@@ -169,7 +189,9 @@ where
 {
     /// Send this buffer, making it receivable as input and consuming the buffer
     pub(crate) fn send(self) -> Result<(), Error> {
-        self.sample.send().map(|_| {}).map_err(|_| Error::SendFailed)
+        self.sample.send().map_err(|_| Error::SendFailed)?;
+        self.trace.sent::<T>();
+        Ok(())
     }
 }
 
@@ -190,6 +212,7 @@ impl<T: FeoComData> DerefMut for Iox2OutputGuard<T> {
 /// Handle to an uninitialized output buffer
 pub struct Iox2OutputUninitGuard<T: FeoComData> {
     sample: SampleMutUninit<ipc::Service, MaybeUninit<T>, ()>,
+    trace: OutputTrace,
 }
 
 impl<T> Iox2OutputUninitGuard<T>
@@ -203,13 +226,19 @@ where
     /// This is safe as long as the backing buffer has been validly initialized beforehand.
     pub(crate) unsafe fn assume_init(self) -> Iox2OutputGuard<T> {
         let sample = unsafe { self.sample.assume_init() };
-        Iox2OutputGuard { sample }
+        Iox2OutputGuard {
+            sample,
+            trace: self.trace,
+        }
     }
 
     /// Write a complete valid type into the uninitialized buffer, initializing it in the process
     pub(crate) fn write_payload(self, value: T) -> Iox2OutputGuard<T> {
         let sample = self.sample.write_payload(value);
-        Iox2OutputGuard { sample }
+        Iox2OutputGuard {
+            sample,
+            trace: self.trace,
+        }
     }
 }
 
@@ -220,7 +249,10 @@ where
     /// Initialize this buffer with its [Default] implementation
     pub(crate) fn init(self) -> Iox2OutputGuard<T> {
         let sample = self.sample.write_payload(T::default());
-        Iox2OutputGuard { sample }
+        Iox2OutputGuard {
+            sample,
+            trace: self.trace,
+        }
     }
 }
 
@@ -238,7 +270,8 @@ impl<T: FeoComData> DerefMut for Iox2OutputUninitGuard<T> {
     }
 }
 
-fn ipc_node() -> &'static Node<ipc::Service> {
+/// Get the iceoryx2 node of this process, shared by all topics and services
+pub fn ipc_node() -> &'static Node<ipc::Service> {
     static ICEORYX_NODE: std::sync::OnceLock<Node<ipc::Service>> = std::sync::OnceLock::new();
 
     ICEORYX_NODE.get_or_init(|| {
@@ -0,0 +1,236 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! iceoryx2 backend of request/response services
+//!
+//! A [Service] is mapped to two publish-subscribe services, `<name>/request` written by the clients
+//! and read by the server, and `<name>/response` written by the server and read by all clients.
+//! Each request carries the ID of its client and a sequence number, which the response repeats,
+//! so clients pick their own responses.
+
+use super::ipc_node;
+use crate::interface::{Error, FeoComData, TopicHandle};
+use crate::service::{ActivityClient, ActivityServer, Service};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::sync::atomic::{AtomicU32, Ordering};
+use iceoryx2::port::publisher::Publisher;
+use iceoryx2::port::subscriber::Subscriber;
+use iceoryx2::service::ipc;
+use score_log::info;
+use std::process;
+
+/// Counter of the clients created in this process, part of their IDs
+static NEXT_CLIENT: AtomicU32 = AtomicU32::new(0);
+
+/// Request or response with the ID of the client and the sequence number of the request
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct Envelope<T> {
+    client: u64,
+    sequence: u64,
+    payload: T,
+}
+
+/// Names of the request and response services of the given service
+fn service_names<Req, Rep>(service: Service<'_, Req, Rep>) -> (String, String) {
+    let name = service.name();
+    (format!("{name}/request"), format!("{name}/response"))
+}
+
+/// Initialize a service with the given maximum number of clients
+pub fn init_service<Req, Rep>(service: Service<'_, Req, Rep>, clients: usize) -> TopicHandle
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    info!(
+        "Initializing service {} (Iceoryx2, {} clients)",
+        service.name(),
+        clients
+    );
+    let (request, response) = service_names(service);
+    // Each client sends at most one request per cycle, so buffers of one sample per client suffice
+    let requests = ipc_node()
+        .service_builder(&request.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {request}")))
+        .publish_subscribe::<Envelope<Req>>()
+        .max_publishers(clients)
+        .max_subscribers(1)
+        .enable_safe_overflow(true)
+        .subscriber_max_buffer_size(clients)
+        .create()
+        .unwrap_or_else(|e| panic!("failed to create service {request}: {e}"));
+    let responses = ipc_node()
+        .service_builder(&response.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {response}")))
+        .publish_subscribe::<Envelope<Rep>>()
+        .max_publishers(1)
+        .max_subscribers(clients)
+        .enable_safe_overflow(true)
+        .subscriber_max_buffer_size(clients)
+        .create()
+        .unwrap_or_else(|e| panic!("failed to create service {response}: {e}"));
+    Box::new((requests, responses)).into()
+}
+
+/// Client of a service implementing [ActivityClient]
+#[derive(Debug)]
+pub struct Iox2Client<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    publisher: Publisher<ipc::Service, Envelope<Req>, ()>,
+    subscriber: Subscriber<ipc::Service, Envelope<Rep>, ()>,
+    /// ID of this client, unique across processes
+    id: u64,
+    /// Sequence number of the last request
+    sequence: u64,
+    /// Whether the last request is not answered yet
+    pending: bool,
+}
+
+impl<Req, Rep> Iox2Client<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    /// Create a new client of the given `service`
+    pub fn new(service: Service<'_, Req, Rep>) -> Self {
+        let (request, response) = service_names(service);
+        let publisher = ipc_node()
+            .service_builder(&request.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {request}")))
+            .publish_subscribe::<Envelope<Req>>()
+            .open()
+            .unwrap_or_else(|e| panic!("failed to open service {request}: {e}"))
+            .publisher_builder()
+            .create()
+            .unwrap_or_else(|_| panic!("failed to create client of service {request}"));
+        let subscriber = ipc_node()
+            .service_builder(&response.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {response}")))
+            .publish_subscribe::<Envelope<Rep>>()
+            .open()
+            .unwrap_or_else(|e| panic!("failed to open service {response}: {e}"))
+            .subscriber_builder()
+            .create()
+            .unwrap_or_else(|_| panic!("failed to create client of service {response}"));
+        let id = (u64::from(process::id()) << 32) | u64::from(NEXT_CLIENT.fetch_add(1, Ordering::Relaxed));
+        Self {
+            publisher,
+            subscriber,
+            id,
+            sequence: 0,
+            pending: false,
+        }
+    }
+}
+
+impl<Req, Rep> ActivityClient<Req, Rep> for Iox2Client<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    fn request(&mut self, request: Req) -> Result<(), Error> {
+        let sequence = self.sequence.wrapping_add(1);
+        let sample = self.publisher.loan_uninit().map_err(|_| Error::NoEmptyBuffer)?;
+        sample
+            .write_payload(Envelope {
+                client: self.id,
+                sequence,
+                payload: request,
+            })
+            .send()
+            .map_err(|_| Error::SendFailed)?;
+        self.sequence = sequence;
+        self.pending = true;
+        Ok(())
+    }
+
+    fn response(&mut self) -> Result<Option<Rep>, Error> {
+        let mut response = None;
+        // Drain all responses, skipping those for other clients and earlier requests
+        while let Some(sample) = self.subscriber.receive().map_err(|_| Error::NoEmptyBuffer)? {
+            let envelope = sample.payload();
+            if self.pending && envelope.client == self.id && envelope.sequence == self.sequence {
+                self.pending = false;
+                response = Some(envelope.payload);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Server of a service implementing [ActivityServer]
+#[derive(Debug)]
+pub struct Iox2Server<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    subscriber: Subscriber<ipc::Service, Envelope<Req>, ()>,
+    publisher: Publisher<ipc::Service, Envelope<Rep>, ()>,
+}
+
+impl<Req, Rep> Iox2Server<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    /// Create the server of the given `service`
+    pub fn new(service: Service<'_, Req, Rep>) -> Self {
+        let (request, response) = service_names(service);
+        let subscriber = ipc_node()
+            .service_builder(&request.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {request}")))
+            .publish_subscribe::<Envelope<Req>>()
+            .open()
+            .unwrap_or_else(|e| panic!("failed to open service {request}: {e}"))
+            .subscriber_builder()
+            .create()
+            .unwrap_or_else(|_| panic!("failed to create server of service {request}"));
+        let publisher = ipc_node()
+            .service_builder(&response.as_str().try_into().unwrap_or_else(|_| panic!("invalid service {response}")))
+            .publish_subscribe::<Envelope<Rep>>()
+            .open()
+            .unwrap_or_else(|e| panic!("failed to open service {response}: {e}"))
+            .publisher_builder()
+            .create()
+            .unwrap_or_else(|_| panic!("failed to create server of service {response}"));
+        Self { subscriber, publisher }
+    }
+}
+
+impl<Req, Rep> ActivityServer<Req, Rep> for Iox2Server<Req, Rep>
+where
+    Req: FeoComData + Copy + 'static,
+    Rep: FeoComData + Copy + 'static,
+{
+    fn serve(&mut self, handler: &mut dyn FnMut(&Req) -> Rep) -> Result<usize, Error> {
+        let mut answered = 0;
+        while let Some(sample) = self.subscriber.receive().map_err(|_| Error::NoEmptyBuffer)? {
+            let request = sample.payload();
+            let response = Envelope {
+                client: request.client,
+                sequence: request.sequence,
+                payload: handler(&request.payload),
+            };
+            self.publisher
+                .loan_uninit()
+                .map_err(|_| Error::NoEmptyBuffer)?
+                .write_payload(response)
+                .send()
+                .map_err(|_| Error::SendFailed)?;
+            answered += 1;
+        }
+        Ok(answered)
+    }
+}
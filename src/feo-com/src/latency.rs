@@ -0,0 +1,195 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Latency of samples from publish to consume
+//!
+//! When enabled with [enable], each output notes the time of each sample it sends and each input
+//! measures the time from the latest send on its topic to its first read of the sample.
+//! The measurements are aggregated into a [LatencyHistogram] per topic, available with [histograms],
+//! and emitted as [LATENCY_EVENT] at trace level, which feo-tracer shows as counter tracks.
+//!
+//! Measurement is lightweight: while disabled, sending and reading only check a flag.
+//! Latencies are measured for publishers and subscribers in the same process only,
+//! since samples do not carry the time they have been sent.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::time::Duration;
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// Name of the event emitted for a measured latency
+pub const LATENCY_EVENT: &str = "com_latency";
+/// Field name of the measured latency in nanoseconds
+pub const LATENCY_FIELD: &str = "latency_ns";
+
+/// Number of buckets of a [LatencyHistogram]
+pub const BUCKETS: usize = 24;
+
+/// Whether latencies are measured
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Latency state per topic
+static TOPICS: LazyLock<Mutex<BTreeMap<Box<str>, Arc<TopicLatency>>>> = LazyLock::new(Default::default);
+
+/// Reference point of the send times
+static EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Start measuring latencies
+pub fn enable() {
+    LazyLock::force(&EPOCH);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stop measuring latencies, keeping the histograms
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether latencies are measured
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Get the histograms of all topics with measured latencies
+pub fn histograms() -> Vec<(String, LatencyHistogram)> {
+    lock(&TOPICS)
+        .iter()
+        .map(|(topic, latency)| (topic.to_string(), lock(&latency.histogram).clone()))
+        .filter(|(_, histogram)| histogram.count() > 0)
+        .collect()
+}
+
+/// Clear the histograms of all topics
+pub fn reset() {
+    for latency in lock(&TOPICS).values() {
+        *lock(&latency.histogram) = LatencyHistogram::default();
+    }
+}
+
+/// Histogram of latencies with logarithmic buckets
+///
+/// Bucket `i` counts latencies of less than `2^(i+1)` microseconds and at least `2^i` microseconds,
+/// except for the first bucket counting all latencies below 2 µs and the last bucket counting all
+/// latencies of at least `2^(BUCKETS-1)` microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    sum_ns: u64,
+    max_ns: u64,
+}
+
+impl LatencyHistogram {
+    /// Add a latency
+    pub fn record(&mut self, latency: Duration) {
+        let nanos = latency.as_nanos().min(u64::MAX as u128) as u64;
+        let micros = nanos / 1000;
+        let bucket = (u64::BITS - micros.leading_zeros()).saturating_sub(1) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.sum_ns = self.sum_ns.saturating_add(nanos);
+        self.max_ns = self.max_ns.max(nanos);
+    }
+
+    /// Number of latencies
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Counts of the buckets
+    pub fn buckets(&self) -> &[u64; BUCKETS] {
+        &self.buckets
+    }
+
+    /// Mean latency, zero if empty
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos(self.sum_ns.checked_div(self.count).unwrap_or_default())
+    }
+
+    /// Maximum latency, zero if empty
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max_ns)
+    }
+
+    /// Upper bound of the latency below which the given fraction of the latencies lie, zero if empty
+    ///
+    /// The bound is the upper end of the bucket containing the quantile, limited to the maximum latency.
+    pub fn quantile(&self, fraction: f64) -> Duration {
+        let rank = ((self.count as f64) * fraction.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper = Duration::from_micros(2u64 << bucket);
+                return upper.min(self.max());
+            }
+        }
+        Duration::ZERO
+    }
+}
+
+/// Latency state of a topic, shared by its outputs and inputs in this process
+#[derive(Debug, Default)]
+pub(crate) struct TopicLatency {
+    /// Number of samples sent
+    sent: AtomicU64,
+    /// Time of the latest send in nanoseconds since [EPOCH]
+    sent_at: AtomicU64,
+    /// Measured latencies
+    histogram: Mutex<LatencyHistogram>,
+}
+
+impl TopicLatency {
+    /// Get the latency state of the given topic
+    pub(crate) fn of(topic: &str) -> Arc<Self> {
+        lock(&TOPICS).entry(topic.into()).or_default().clone()
+    }
+
+    /// Note a sample sent on the topic
+    pub(crate) fn sent(&self) {
+        if is_enabled() {
+            self.sent_at.store(now(), Ordering::Relaxed);
+            self.sent.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    /// Measure the latency of a sample read by an input, which has seen `seen` samples so far
+    ///
+    /// Returns the latency on the first read of a sample by the input, `None` otherwise.
+    pub(crate) fn read(&self, seen: &AtomicU64) -> Option<Duration> {
+        if !is_enabled() {
+            return None;
+        }
+        let sent = self.sent.load(Ordering::Acquire);
+        if sent == 0 || seen.swap(sent, Ordering::Relaxed) == sent {
+            return None;
+        }
+        let latency = Duration::from_nanos(now().saturating_sub(self.sent_at.load(Ordering::Relaxed)));
+        lock(&self.histogram).record(latency);
+        Some(latency)
+    }
+}
+
+/// Current time in nanoseconds since [EPOCH]
+fn now() -> u64 {
+    EPOCH.elapsed().as_nanos() as u64
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -23,10 +23,16 @@
 extern crate alloc;
 extern crate std;
 
+pub mod arrival;
+pub mod e2e;
 pub mod interface;
 #[cfg(feature = "ipc_iceoryx2")]
 pub mod iox2;
+pub mod latency;
 #[cfg(feature = "ipc_linux_shm")]
 pub mod linux_shm;
 #[cfg(feature = "ipc_mw_com")]
 pub mod mw_com;
+pub mod service;
+pub mod topic;
+pub mod trace;
@@ -34,11 +34,13 @@ pub(crate) mod shared_memory;
 
 use crate::interface::{
     ActivityInput, ActivityOutput, ActivityOutputDefault, Error, FeoComData, FeoComDefault, InputGuard, OutputGuard,
-    OutputUninitGuard, Topic, TopicHandle,
+    OutputUninitGuard, TopicHandle, TopicName,
 };
 use crate::linux_shm::shared_memory::{
     MappedPtrReadGuard, MappedPtrWriteGuard, MappingMode, ReadWriteAccessControlPtr, TopicInitializationAgentRole,
 };
+use crate::topic::Topic;
+use crate::trace::{InputTrace, OutputTrace};
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::format;
@@ -185,7 +187,7 @@ impl ComRuntime {
     /// Initialize the topic and register it in the COM runtime
     fn init_topic<T: Debug + Default + 'static>(
         &mut self,
-        topic: Topic,
+        topic: TopicName,
         mapping_mode: MappingMode,
         initialization: TopicInitializationAgentRole,
     ) {
@@ -201,7 +203,7 @@ impl ComRuntime {
 
     fn init_topic_primary<T: Debug + Default + 'static>(
         &mut self,
-        topic: Topic,
+        topic: TopicName,
         mapping_mode: MappingMode,
         also_map: bool,
     ) {
@@ -232,7 +234,7 @@ impl ComRuntime {
         );
     }
 
-    fn init_topic_secondary<T: Debug + 'static>(&mut self, topic: Topic, mapping_mode: MappingMode) {
+    fn init_topic_secondary<T: Debug + 'static>(&mut self, topic: TopicName, mapping_mode: MappingMode) {
         debug!("Initializing topic {} on secondary (LinuxShm)...", topic);
         let (size, mapping_id) = Self::request_primary(topic);
         assert_eq!(size_of::<T>(), size);
@@ -259,7 +261,7 @@ impl ComRuntime {
     }
 
     // Make a request to primary
-    fn request_primary(topic: Topic) -> (usize, String) {
+    fn request_primary(topic: TopicName) -> (usize, String) {
         let mut stream = UnixStream::connect(SOCKET)
             .unwrap_or_else(|e| panic!("can't connect to socket {SOCKET} for topic {topic}: {e}"));
         stream.write_all(topic.as_bytes()).expect("socket write failed");
@@ -282,7 +284,7 @@ impl ComRuntime {
     }
 
     // Create and register mapping for topic
-    pub(crate) fn topic_mapping<T>(&mut self, topic: Topic, mode: MappingMode) -> Arc<ReadWriteAccessControlPtr> {
+    pub(crate) fn topic_mapping<T>(&mut self, topic: TopicName, mode: MappingMode) -> Arc<ReadWriteAccessControlPtr> {
         const {
             assert!(size_of::<T>() != 0, "zero-sized type is not allowed");
             assert!(size_of::<T>() <= isize::MAX as usize, "type size is too big");
@@ -303,7 +305,7 @@ impl ComRuntime {
 
 // Initialize the topic and register it in the global COM runtime
 pub fn init_topic<T: FeoComData + Default + 'static>(
-    topic: Topic,
+    topic: TopicName,
     mapping_mode: MappingMode,
     agent_role: TopicInitializationAgentRole,
 ) -> TopicHandle {
@@ -323,6 +325,7 @@ impl<T: FeoComData> Deref for LinuxShmInputGuard<T> {
 
 pub struct LinuxShmOutputGuard<T: FeoComData> {
     ptr: MappedPtrWriteGuard<T>,
+    trace: OutputTrace,
 }
 
 impl<T> LinuxShmOutputGuard<T>
@@ -331,6 +334,7 @@ where
 {
     pub(crate) fn send(self) -> Result<(), Error> {
         self.ptr.send();
+        self.trace.sent::<T>();
         Ok(())
     }
 }
@@ -349,7 +353,7 @@ impl<T: FeoComData> DerefMut for LinuxShmOutputGuard<T> {
     }
 }
 
-pub struct LinuxShmOutputUninitGuard<T: FeoComData>(MappedPtrWriteGuard<T>);
+pub struct LinuxShmOutputUninitGuard<T: FeoComData>(MappedPtrWriteGuard<T>, OutputTrace);
 
 impl<T> LinuxShmOutputUninitGuard<T>
 where
@@ -357,13 +361,19 @@ where
 {
     // Value is initialized when allocated
     pub(crate) fn assume_init(self) -> LinuxShmOutputGuard<T> {
-        LinuxShmOutputGuard { ptr: self.0 }
+        LinuxShmOutputGuard {
+            ptr: self.0,
+            trace: self.1,
+        }
     }
 
     // Overwrites with given value
     pub(crate) fn write_payload(mut self, value: T) -> LinuxShmOutputGuard<T> {
         *DerefMut::deref_mut(&mut self.0) = value;
-        LinuxShmOutputGuard { ptr: self.0 }
+        LinuxShmOutputGuard {
+            ptr: self.0,
+            trace: self.1,
+        }
     }
 }
 
@@ -374,7 +384,10 @@ where
     // Overwrites with [Default::default]
     pub(crate) fn init(mut self) -> LinuxShmOutputGuard<T> {
         *DerefMut::deref_mut(&mut self.0) = T::default();
-        LinuxShmOutputGuard { ptr: self.0 }
+        LinuxShmOutputGuard {
+            ptr: self.0,
+            trace: self.1,
+        }
     }
 }
 
@@ -399,13 +412,15 @@ impl<T: FeoComData> DerefMut for LinuxShmOutputUninitGuard<T> {
 #[derive(Debug)]
 pub struct LinuxShmInput<T> {
     ptr: Arc<ReadWriteAccessControlPtr>,
+    trace: InputTrace,
     _type: PhantomData<T>,
 }
 
 impl<T: FeoComData + 'static> LinuxShmInput<T> {
-    pub fn new(topic: Topic) -> Self {
+    pub fn new(topic: Topic<'_, T>) -> Self {
         Self {
-            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Read),
+            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic.name(), MappingMode::Read),
+            trace: InputTrace::new(topic.name()),
             _type: PhantomData,
         }
     }
@@ -416,20 +431,24 @@ where
     T: FeoComData + 'static,
 {
     fn read(&self) -> Result<InputGuard<'_, T>, Error> {
-        Ok(InputGuard::LinuxShm(LinuxShmInputGuard(self.ptr.get())))
+        let guard = LinuxShmInputGuard(self.ptr.get());
+        self.trace.received::<T>();
+        Ok(InputGuard::LinuxShm(guard))
     }
 }
 
 #[derive(Debug)]
 pub struct LinuxShmOutput<T> {
     ptr: Arc<ReadWriteAccessControlPtr>,
+    trace: OutputTrace,
     _type: PhantomData<T>,
 }
 
 impl<T: FeoComData + 'static> LinuxShmOutput<T> {
-    pub fn new(topic: Topic) -> Self {
+    pub fn new(topic: Topic<'_, T>) -> Self {
         Self {
-            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic, MappingMode::Write),
+            ptr: ComRuntime::global_runtime().topic_mapping::<T>(topic.name(), MappingMode::Write),
+            trace: OutputTrace::new(topic.name()),
             _type: PhantomData,
         }
     }
@@ -443,6 +462,7 @@ where
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<'_, T>, Error> {
         Ok(OutputUninitGuard::LinuxShm(LinuxShmOutputUninitGuard(
             self.ptr.get_mut(),
+            self.trace.clone(),
         )))
     }
 }
@@ -455,6 +475,7 @@ where
     fn write_init(&mut self) -> Result<OutputGuard<'_, T>, Error> {
         let mut ptr = self.ptr.get_mut();
         *ptr = T::default();
-        Ok(OutputGuard::LinuxShm(LinuxShmOutputGuard { ptr }))
+        let trace = self.trace.clone();
+        Ok(OutputGuard::LinuxShm(LinuxShmOutputGuard { ptr, trace }))
     }
 }
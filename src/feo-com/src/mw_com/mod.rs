@@ -15,6 +15,8 @@ use crate::interface::{
     ActivityInput, ActivityOutput, ActivityOutputDefault, DebugWrapper, Error, FeoComData, FeoComDefault, InputGuard,
     OutputGuard, OutputUninitGuard,
 };
+use crate::topic::Topic;
+use crate::trace::{InputTrace, OutputTrace};
 use com_api::{
     LolaRuntimeImpl, PlacementDefault, Publisher, Runtime, SampleContainer, SampleMaybeUninit, SampleMut, Subscriber,
     Subscription,
@@ -47,7 +49,7 @@ impl<T: FeoComData> Deref for MwComInputGuard<'_, T> {
     }
 }
 
-pub struct MwComOutputGuard<'a, T: FeoComData>(MwComSampleMut<'a, T>);
+pub struct MwComOutputGuard<'a, T: FeoComData>(MwComSampleMut<'a, T>, OutputTrace);
 
 impl<'a, T> MwComOutputGuard<'a, T>
 where
@@ -55,6 +57,7 @@ where
 {
     pub(crate) fn send(self) -> Result<(), Error> {
         self.0.send()?;
+        self.1.sent::<T>();
         Ok(())
     }
 }
@@ -75,6 +78,7 @@ impl<T: FeoComData> DerefMut for MwComOutputGuard<'_, T> {
 
 pub struct MwComOutputUninitGuard<'a, T: FeoComData>(
     UnsafeCell<<MwComPublisher<T> as Publisher<T, LolaRuntimeImpl>>::SampleMaybeUninit<'a>>,
+    OutputTrace,
 );
 
 impl<'a, T> MwComOutputUninitGuard<'a, T>
@@ -83,12 +87,12 @@ where
 {
     // Value is initialized when allocated
     pub(crate) unsafe fn assume_init(self) -> MwComOutputGuard<'a, T> {
-        MwComOutputGuard(self.0.into_inner().assume_init())
+        MwComOutputGuard(self.0.into_inner().assume_init(), self.1)
     }
 
     // Overwrites with given value
     pub(crate) fn write_payload(self, value: T) -> MwComOutputGuard<'a, T> {
-        MwComOutputGuard(self.0.into_inner().write(value))
+        MwComOutputGuard(self.0.into_inner().write(value), self.1)
     }
 }
 
@@ -98,7 +102,7 @@ where
 {
     // Overwrites with [Default::default]
     pub(crate) fn init(self) -> MwComOutputGuard<'a, T> {
-        MwComOutputGuard(self.0.into_inner().write_default())
+        MwComOutputGuard(self.0.into_inner().write_default(), self.1)
     }
 }
 
@@ -121,16 +125,19 @@ impl<T: FeoComData> DerefMut for MwComOutputUninitGuard<'_, T> {
 pub struct MwComInput<T: FeoComData> {
     subscription: MwComSubscription<T>,
     sample_container: DebugWrapper<RefCell<SampleContainer<MwComSample<'static, T>>>>,
+    trace: InputTrace,
 }
 
 impl<T: FeoComData + 'static> MwComInput<T> {
     pub fn new(
+        topic: Topic<'_, T>,
         subscription: MwComSubscription<T>,
         sample_container: DebugWrapper<RefCell<SampleContainer<MwComSample<'static, T>>>>,
     ) -> Self {
         Self {
             subscription,
             sample_container,
+            trace: InputTrace::new(topic.name()),
         }
     }
 }
@@ -152,16 +159,17 @@ where
             .borrow_mut()
             .pop_front()
             .expect("pop_front failed");
+        self.trace.received::<T>();
         Ok(InputGuard::MwCom(MwComInputGuard(result)))
     }
 }
 
 #[derive(Debug)]
-pub struct MwComOutput<T: FeoComData>(DebugWrapper<MwComPublisher<T>>);
+pub struct MwComOutput<T: FeoComData>(DebugWrapper<MwComPublisher<T>>, OutputTrace);
 
 impl<T: FeoComData + 'static> MwComOutput<T> {
-    pub fn new(publisher: DebugWrapper<MwComPublisher<T>>) -> Self {
-        Self(publisher)
+    pub fn new(topic: Topic<'_, T>, publisher: DebugWrapper<MwComPublisher<T>>) -> Self {
+        Self(publisher, OutputTrace::new(topic.name()))
     }
 }
 
@@ -171,9 +179,10 @@ where
 {
     // Initialized when allocated
     fn write_uninit(&mut self) -> Result<OutputUninitGuard<'_, T>, Error> {
-        Ok(OutputUninitGuard::MwCom(MwComOutputUninitGuard(UnsafeCell::new(
-            self.0.allocate()?,
-        ))))
+        Ok(OutputUninitGuard::MwCom(MwComOutputUninitGuard(
+            UnsafeCell::new(self.0.allocate()?),
+            self.1.clone(),
+        )))
     }
 }
 
@@ -183,6 +192,9 @@ where
 {
     // Overwrites with [Default::default]
     fn write_init(&mut self) -> Result<OutputGuard<'_, T>, Error> {
-        Ok(OutputGuard::MwCom(MwComOutputGuard(self.0.allocate()?.write_default())))
+        Ok(OutputGuard::MwCom(MwComOutputGuard(
+            self.0.allocate()?.write_default(),
+            self.1.clone(),
+        )))
     }
 }
@@ -0,0 +1,96 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Typed request/response services
+//!
+//! Some interactions between activities are naturally request/response, e.g. an activity looking up
+//! map data for its current position. A [Service] couples the name of a service with the types of its
+//! requests and responses. Client activities send a request with [ActivityClient::request] and get
+//! the response with [ActivityClient::response], the server activity answers all pending requests
+//! with [ActivityServer::serve].
+//!
+//! Services follow the fixed execution order: a server answers in its step the requests sent up to
+//! then, and a client gets the response in its step of the next cycle. For this, the server activity
+//! must be in the task chain of its clients and either depend on them or they on the server.
+//! A client has at most one request outstanding; sending a new request discards the response to the
+//! previous one, so each response belongs to the last request of the client.
+//!
+//! Requests and responses are copied, so services are meant for small payloads. Large data, e.g. images,
+//! is exchanged via topics.
+
+use crate::interface::{Error, FeoComData, TopicName};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Handle of a service with requests of type `Req` and responses of type `Rep`
+pub struct Service<'a, Req, Rep> {
+    name: TopicName<'a>,
+    _types: PhantomData<fn(Req) -> Rep>,
+}
+
+impl<'a, Req, Rep> Service<'a, Req, Rep> {
+    /// Create a handle of the service with the given name
+    ///
+    /// All handles of a service must be created with the same request and response types.
+    pub const fn new(name: TopicName<'a>) -> Self {
+        Self {
+            name,
+            _types: PhantomData,
+        }
+    }
+
+    /// Name of the service
+    pub const fn name(&self) -> TopicName<'a> {
+        self.name
+    }
+}
+
+// Implemented manually to not require `Req: Clone` and `Rep: Clone`
+impl<Req, Rep> Clone for Service<'_, Req, Rep> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Req, Rep> Copy for Service<'_, Req, Rep> {}
+
+impl<Req, Rep> fmt::Debug for Service<'_, Req, Rep> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Service").field(&self.name).finish()
+    }
+}
+
+/// A trait for structs sending requests to a service
+pub trait ActivityClient<Req, Rep>: fmt::Debug
+where
+    Req: FeoComData + Copy,
+    Rep: FeoComData + Copy,
+{
+    /// Send a request, discarding the response to a previous request
+    fn request(&mut self, request: Req) -> Result<(), Error>;
+
+    /// Get the response to the last request, if answered
+    ///
+    /// The response is returned once, responses to earlier requests are skipped.
+    fn response(&mut self) -> Result<Option<Rep>, Error>;
+}
+
+/// A trait for structs answering the requests to a service
+pub trait ActivityServer<Req, Rep>: fmt::Debug
+where
+    Req: FeoComData + Copy,
+    Rep: FeoComData + Copy,
+{
+    /// Answer all pending requests with the given handler, returning the number of answered requests
+    fn serve(&mut self, handler: &mut dyn FnMut(&Req) -> Rep) -> Result<usize, Error>;
+}
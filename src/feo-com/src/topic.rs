@@ -0,0 +1,88 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Typed topics
+//!
+//! A [Topic] couples the name of a topic with the type of its samples. Inputs, outputs and topic
+//! specifications are created from a [Topic] instead of a plain name, so a publisher and a subscriber
+//! disagreeing on the sample type do not compile, instead of failing at runtime.
+//!
+//! Applications define their topics once in a central registry with the [topics](crate::topics) macro:
+//!
+//! ```ignore
+//! feo_com::topics! {
+//!     /// Images of the front camera
+//!     pub TOPIC_CAMERA_FRONT: CameraImage = "/feo/com/MiniAdasCamera";
+//!     /// Scans of the front radar
+//!     pub TOPIC_RADAR_FRONT: RadarScan = "/feo/com/MiniAdasRadar";
+//! }
+//! ```
+//!
+//! Topics whose names are only known at runtime, e.g. read from a configuration file,
+//! are created with [Topic::new] during startup.
+
+use crate::interface::TopicName;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Handle of a topic with samples of type `T`
+pub struct Topic<'a, T> {
+    name: TopicName<'a>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Topic<'a, T> {
+    /// Create a handle of the topic with the given name
+    ///
+    /// All handles of a topic must be created with the same sample type.
+    pub const fn new(name: TopicName<'a>) -> Self {
+        Self {
+            name,
+            _type: PhantomData,
+        }
+    }
+
+    /// Name of the topic
+    pub const fn name(&self) -> TopicName<'a> {
+        self.name
+    }
+}
+
+// Implemented manually to not require `T: Clone`
+impl<T> Clone for Topic<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Topic<'_, T> {}
+
+impl<T> fmt::Debug for Topic<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Topic").field(&self.name).finish()
+    }
+}
+
+/// Define a registry of typed topics as constants
+///
+/// Each entry `vis NAME: Type = "name";` defines a constant [Topic] of the given sample type,
+/// see [topic](crate::topic).
+#[macro_export]
+macro_rules! topics {
+    ($($(#[$attr:meta])* $vis:vis $name:ident: $ty:ty = $topic:expr;)*) => {
+        $(
+            $(#[$attr])*
+            $vis const $name: $crate::topic::Topic<'static, $ty> = $crate::topic::Topic::new($topic);
+        )*
+    };
+}
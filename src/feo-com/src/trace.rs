@@ -0,0 +1,130 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Trace events of sent and received samples
+//!
+//! Every sample sent by an output emits a [PUBLISH_EVENT] and every sample received by an input
+//! emits a [RECEIVE_EVENT], both at trace level. The events carry the topic ([TOPIC_FIELD]) and
+//! the payload size in bytes ([SIZE_FIELD]). Publish events also carry the sequence number of the
+//! sample per output ([SEQUENCE_FIELD]), starting at 0.
+//!
+//! feo-tracer connects each received sample to the latest sample sent on its topic,
+//! showing the data flow between activities.
+//!
+//! With [latency](crate::latency) measurement enabled, the first read of each sample by an input
+//! additionally emits a [LATENCY_EVENT] carrying the latency since the sample was sent.
+//!
+//! Sending a sample also notifies the [arrival](crate::arrival) listeners of its topic.
+
+use crate::arrival::TopicArrival;
+use crate::latency::{TopicLatency, LATENCY_EVENT};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicU64, Ordering};
+use tracing::{event, Level};
+
+/// Name of the event emitted for a sent sample
+pub const PUBLISH_EVENT: &str = "com_publish";
+/// Name of the event emitted for a received sample
+pub const RECEIVE_EVENT: &str = "com_receive";
+/// Field name of the topic of a sample
+pub const TOPIC_FIELD: &str = "topic";
+/// Field name of the sequence number of a sent sample
+pub const SEQUENCE_FIELD: &str = "seq";
+/// Field name of the payload size of a sample
+pub const SIZE_FIELD: &str = "size";
+
+/// Trace of the samples sent by an output
+///
+/// Cloned into the buffers loaned by the output, so that sending a buffer can emit its event.
+#[derive(Debug, Clone)]
+pub(crate) struct OutputTrace(Arc<OutputTraceInner>);
+
+#[derive(Debug)]
+struct OutputTraceInner {
+    /// Topic of the output
+    topic: Box<str>,
+    /// Sequence number of the next sample sent
+    sequence: AtomicU64,
+    /// Latency state of the topic
+    latency: Arc<TopicLatency>,
+    /// Arrival state of the topic
+    arrival: Arc<TopicArrival>,
+}
+
+impl OutputTrace {
+    /// Create a trace for an output on the given topic
+    pub(crate) fn new(topic: &str) -> Self {
+        Self(Arc::new(OutputTraceInner {
+            topic: topic.into(),
+            sequence: AtomicU64::new(0),
+            latency: TopicLatency::of(topic),
+            arrival: TopicArrival::of(topic),
+        }))
+    }
+
+    /// Emit the event of a sample of type `T` sent successfully
+    pub(crate) fn sent<T>(&self) {
+        let seq = self.0.sequence.fetch_add(1, Ordering::Relaxed);
+        self.0.latency.sent();
+        event!(
+            name: PUBLISH_EVENT,
+            Level::TRACE,
+            topic = &*self.0.topic,
+            seq,
+            size = size_of::<T>() as u64
+        );
+        self.0.arrival.sent();
+    }
+}
+
+/// Trace of the samples received by an input
+#[derive(Debug)]
+pub(crate) struct InputTrace {
+    /// Topic of the input
+    topic: Box<str>,
+    /// Latency state of the topic
+    latency: Arc<TopicLatency>,
+    /// Number of samples sent on the topic when last reading
+    seen: AtomicU64,
+}
+
+impl InputTrace {
+    /// Create a trace for an input on the given topic
+    pub(crate) fn new(topic: &str) -> Self {
+        Self {
+            topic: topic.into(),
+            latency: TopicLatency::of(topic),
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Emit the event of a received sample of type `T`
+    pub(crate) fn received<T>(&self) {
+        event!(
+            name: RECEIVE_EVENT,
+            Level::TRACE,
+            topic = &*self.topic,
+            size = size_of::<T>() as u64
+        );
+        if let Some(latency) = self.latency.read(&self.seen) {
+            event!(
+                name: LATENCY_EVENT,
+                Level::TRACE,
+                topic = &*self.topic,
+                latency_ns = latency.as_nanos() as u64
+            );
+        }
+    }
+}
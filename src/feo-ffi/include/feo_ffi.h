@@ -0,0 +1,97 @@
+/********************************************************************************
+ * Copyright (c) 2025 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+
+#ifndef __FEO_FFI_H__
+#define __FEO_FFI_H__
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include <stddef.h>
+#include <stdint.h>
+
+/// Callbacks of an activity, returning zero on success. Callbacks may be NULL.
+/// `free` is called with the user data when the activity is dropped.
+struct feo_activity_callbacks {
+    int (*startup)(void* user_data);
+    int (*step)(void* user_data);
+    int (*shutdown)(void* user_data);
+    void (*free)(void* user_data);
+};
+
+// Opaque handles
+struct feo_secondary;
+struct feo_publisher;
+struct feo_subscriber;
+
+// Com backends of topics
+#define FEO_COM_IOX2 0
+#define FEO_COM_LINUX_SHM 1
+
+// Directions of topics
+#define FEO_TOPIC_READ 0
+#define FEO_TOPIC_WRITE 1
+
+// Maximum size of a sample in bytes
+#define FEO_SAMPLE_CAPACITY 4096
+
+// Create a secondary agent connecting to the primary agent on the given endpoint, e.g. "unix:/tmp/feo.socket".
+// If endpoint is NULL, it is looked up from FEO_PRIMARY_ENDPOINT or FEO_DISCOVERY_FILE. Returns NULL on failure.
+struct feo_secondary* feo_secondary_new(uint64_t agent_id, const char* endpoint, const char* mw_com_config);
+
+// Select the com backend of the topics of an agent, FEO_COM_IOX2 by default. Returns 0 on success and -1 on failure
+int feo_secondary_set_com_backend(struct feo_secondary* secondary, uint32_t backend);
+
+// Add an activity to a worker of an agent, returns 0 on success and -1 on failure
+int feo_secondary_add_activity(struct feo_secondary* secondary,
+                               uint64_t worker_id,
+                               uint64_t activity_id,
+                               const struct feo_activity_callbacks* callbacks,
+                               void* user_data);
+
+// Declare that an activity reads (FEO_TOPIC_READ) or writes (FEO_TOPIC_WRITE) a topic,
+// returns 0 on success and -1 on failure
+int feo_secondary_add_topic(struct feo_secondary* secondary, const char* topic, uint64_t activity_id, uint32_t direction);
+
+// Run an agent until it is shut down, consuming the agent. Returns 0 after shutdown and -1 on failure
+int feo_secondary_run(struct feo_secondary* secondary);
+
+// Free an agent which has not been run
+void feo_secondary_free(struct feo_secondary* secondary);
+
+// Create a publisher on a topic, returns NULL on failure
+struct feo_publisher* feo_publisher_new(const char* topic, uint32_t backend);
+
+// Publish a sample of up to FEO_SAMPLE_CAPACITY bytes, returns 0 on success and -1 on failure
+int feo_publish(struct feo_publisher* publisher, const void* data, size_t len);
+
+// Free a publisher
+void feo_publisher_free(struct feo_publisher* publisher);
+
+// Create a subscriber of a topic, returns NULL on failure
+struct feo_subscriber* feo_subscriber_new(const char* topic, uint32_t backend);
+
+// Copy the latest sample into the buffer and store its size in len.
+// Returns 0 on success, 1 if no sample is available and -1 on failure
+int feo_subscriber_read(struct feo_subscriber* subscriber, void* buffer, size_t capacity, size_t* len);
+
+// Free a subscriber
+void feo_subscriber_free(struct feo_subscriber* subscriber);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif
+
+#endif  // __FEO_FFI_H__
@@ -0,0 +1,139 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Activities implemented by C callbacks
+
+use core::ffi::{c_int, c_void};
+use feo::activity::Activity;
+use feo::error::ActivityError;
+use feo::ids::ActivityId;
+use score_log::error;
+
+/// Callback of an activity, returning zero on success
+pub type ActivityCallback = Option<unsafe extern "C" fn(user_data: *mut c_void) -> c_int>;
+
+/// Callbacks of an activity implemented in C
+///
+/// Callbacks not set are treated as succeeding without doing anything.
+/// `free` is called with the user data when the activity is dropped.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FeoActivityCallbacks {
+    pub startup: ActivityCallback,
+    pub step: ActivityCallback,
+    pub shutdown: ActivityCallback,
+    pub free: Option<unsafe extern "C" fn(user_data: *mut c_void)>,
+}
+
+/// User data passed to the callbacks of an activity
+#[derive(Debug)]
+pub(crate) struct UserData(pub(crate) *mut c_void);
+
+// Safety: the caller registering an activity guarantees that its user data may be used
+// from the worker thread running the activity
+unsafe impl Send for UserData {}
+
+/// Activity calling the callbacks of a C implementation
+#[derive(Debug)]
+pub struct FfiActivity {
+    id: ActivityId,
+    callbacks: FeoActivityCallbacks,
+    user_data: UserData,
+}
+
+impl FfiActivity {
+    /// Create an activity calling the given callbacks with the given user data
+    ///
+    /// # Safety
+    ///
+    /// The callbacks must be safe to call with the user data from the thread running the activity.
+    pub(crate) unsafe fn new(id: ActivityId, callbacks: FeoActivityCallbacks, user_data: UserData) -> Self {
+        Self {
+            id,
+            callbacks,
+            user_data,
+        }
+    }
+
+    /// Call the given callback, mapping a non-zero result to the given error
+    fn call(&mut self, callback: ActivityCallback, error: ActivityError) -> Result<(), ActivityError> {
+        let Some(callback) = callback else {
+            return Ok(());
+        };
+        // Safety: guaranteed by the caller registering the activity, see [FfiActivity::new]
+        match unsafe { callback(self.user_data.0) } {
+            0 => Ok(()),
+            code => {
+                error!("Activity {} failed with code {}", self.id, code);
+                Err(error)
+            },
+        }
+    }
+}
+
+impl Drop for FfiActivity {
+    fn drop(&mut self) {
+        if let Some(free) = self.callbacks.free {
+            // Safety: guaranteed by the caller registering the activity, see [FfiActivity::new]
+            unsafe { free(self.user_data.0) };
+        }
+    }
+}
+
+impl Activity for FfiActivity {
+    fn id(&self) -> ActivityId {
+        self.id
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        self.call(self.callbacks.startup, ActivityError::Startup)
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        self.call(self.callbacks.step, ActivityError::Step)
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        self.call(self.callbacks.shutdown, ActivityError::Shutdown)
+    }
+}
+
+#[test]
+fn callbacks_are_called_with_user_data() {
+    unsafe extern "C" fn step(user_data: *mut c_void) -> c_int {
+        let steps = unsafe { &mut *(user_data as *mut c_int) };
+        *steps += 1;
+        if *steps > 1 {
+            -1
+        } else {
+            0
+        }
+    }
+
+    let mut steps: c_int = 0;
+    let callbacks = FeoActivityCallbacks {
+        startup: None,
+        step: Some(step),
+        shutdown: None,
+        free: None,
+    };
+    let user_data = UserData(&mut steps as *mut c_int as *mut c_void);
+    // Safety: the step callback only accesses the counter, which outlives the activity
+    let mut activity = unsafe { FfiActivity::new(ActivityId::new(7), callbacks, user_data) };
+
+    assert_eq!(activity.startup(), Ok(()));
+    assert_eq!(activity.step(), Ok(()));
+    assert_eq!(activity.step(), Err(ActivityError::Step));
+    drop(activity);
+    assert_eq!(steps, 2);
+}
@@ -0,0 +1,281 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Secondary agents running activities implemented in C
+
+use crate::activity::{FeoActivityCallbacks, FfiActivity, UserData};
+use crate::topic::ByteSample;
+use com_api::{Builder, LolaRuntimeBuilderImpl, LolaRuntimeImpl, RuntimeBuilder};
+use core::ffi::{c_char, c_int, c_void, CStr};
+use core::str::FromStr;
+use feo::activity::{Activity, ActivityIdAndBuilder};
+use feo::agent::com_init::initialize_com_secondary;
+use feo::agent::direct::secondary::{Secondary, SecondaryConfig};
+use feo::agent::NodeAddress;
+use feo::discovery::PrimaryEndpoints;
+use feo::error::Error;
+use feo::ids::{ActivityId, AgentId, WorkerId};
+use feo::topicspec::{Direction, TopicSpecification};
+use feo_com::interface::ComBackend;
+use feo_com::topic::Topic;
+use feo_time::Duration;
+use score_log::{error, info};
+use std::collections::HashSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Value of the C direction argument for topics read by an activity
+const TOPIC_READ: u32 = 0;
+/// Value of the C direction argument for topics written by an activity
+const TOPIC_WRITE: u32 = 1;
+
+/// Maximum time for a worker to make no progress without panicking
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// MW COM runtime of the secondary agent of this process
+static MW_COM_RUNTIME: OnceLock<LolaRuntimeImpl> = OnceLock::new();
+
+/// Secondary agent under construction by a C program
+pub struct FeoSecondary {
+    id: AgentId,
+    endpoint: NodeAddress,
+    mw_com_config: PathBuf,
+    backend: ComBackend,
+    worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
+    topics: Vec<(String, Vec<(ActivityId, Direction)>)>,
+}
+
+impl FeoSecondary {
+    /// Add an activity to the given worker
+    fn add_activity(&mut self, worker_id: WorkerId, activity_id: ActivityId, activity: FfiActivity) {
+        let builder: ActivityIdAndBuilder = (activity_id, Box::new(move |_| Box::new(activity) as Box<dyn Activity>));
+        match self.worker_assignments.iter_mut().find(|(id, _)| *id == worker_id) {
+            Some((_, activities)) => activities.push(builder),
+            None => self.worker_assignments.push((worker_id, vec![builder])),
+        }
+    }
+
+    /// Add a peer of a topic
+    fn add_topic(&mut self, topic: &str, activity_id: ActivityId, direction: Direction) {
+        match self.topics.iter_mut().find(|(name, _)| name == topic) {
+            Some((_, peers)) => peers.push((activity_id, direction)),
+            None => self.topics.push((topic.to_owned(), vec![(activity_id, direction)])),
+        }
+    }
+
+    /// Initialize the topics and run the agent until it is shut down by the primary agent
+    fn run(self) -> Result<(), Error> {
+        let runtime = MW_COM_RUNTIME.get_or_init(|| {
+            let mut lola_runtime_builder = LolaRuntimeBuilderImpl::new();
+            lola_runtime_builder.load_config(&self.mw_com_config);
+            lola_runtime_builder.build().expect("failed to build MW COM runtime")
+        });
+
+        let local_activities: HashSet<ActivityId> = self
+            .worker_assignments
+            .iter()
+            .flat_map(|(_, activities)| activities.iter().map(|(id, _)| *id))
+            .collect();
+        let topic_specs = self
+            .topics
+            .iter()
+            .map(|(topic, peers)| TopicSpecification::new(Topic::<ByteSample>::new(topic), peers.clone()))
+            .collect();
+        // Initialize topics. Do not drop.
+        let _topic_guards = initialize_com_secondary(self.backend, topic_specs, &local_activities);
+
+        let config = SecondaryConfig {
+            id: self.id,
+            worker_assignments: self.worker_assignments,
+            timeout: TIMEOUT,
+            heartbeat_interval: None,
+            endpoint: self.endpoint,
+            ..Default::default()
+        };
+        Secondary::new(config, runtime).run()
+    }
+}
+
+/// Get a string argument, returns `None` if it is null or not valid UTF-8
+///
+/// # Safety
+///
+/// The pointer must be null or point to a nul-terminated string.
+unsafe fn string_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// Create a secondary agent connecting to the primary agent on `endpoint`.
+///
+/// If `endpoint` is null, the endpoint is looked up with the discovery of the primary agent endpoints.
+/// Returns null on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_new(
+    agent_id: u64,
+    endpoint: *const c_char,
+    mw_com_config: *const c_char,
+) -> *mut FeoSecondary {
+    // Safety: the C API requires nul-terminated strings
+    let Some(mw_com_config) = (unsafe { string_arg(mw_com_config) }) else {
+        error!("Missing MW COM configuration of agent {}", agent_id);
+        return core::ptr::null_mut();
+    };
+    let endpoint = if endpoint.is_null() {
+        PrimaryEndpoints::lookup().ok().and_then(|endpoints| endpoints.endpoint)
+    } else {
+        // Safety: the C API requires nul-terminated strings
+        unsafe { string_arg(endpoint) }.and_then(|endpoint| NodeAddress::from_str(endpoint).ok())
+    };
+    let Some(endpoint) = endpoint else {
+        error!("Missing or invalid endpoint of agent {}", agent_id);
+        return core::ptr::null_mut();
+    };
+
+    info!("Creating agent {}", agent_id);
+    Box::into_raw(Box::new(FeoSecondary {
+        id: agent_id.into(),
+        endpoint,
+        mw_com_config: PathBuf::from(mw_com_config),
+        backend: ComBackend::Iox2,
+        worker_assignments: Vec::new(),
+        topics: Vec::new(),
+    }))
+}
+
+/// Select the com backend of the topics of an agent, iceoryx2 by default.
+///
+/// Returns 0 on success and -1 on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_set_com_backend(secondary: *mut FeoSecondary, backend: u32) -> c_int {
+    let Some(backend) = crate::topic::backend(backend) else {
+        return -1;
+    };
+    // Safety: the C API requires an agent created with `feo_secondary_new`
+    let Some(secondary) = (unsafe { secondary.as_mut() }) else {
+        return -1;
+    };
+    secondary.backend = backend;
+    0
+}
+
+/// Add an activity implemented by the given callbacks to a worker of an agent.
+///
+/// The callbacks are called with `user_data` from the worker thread running the activity.
+/// Returns 0 on success and -1 on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_add_activity(
+    secondary: *mut FeoSecondary,
+    worker_id: u64,
+    activity_id: u64,
+    callbacks: *const FeoActivityCallbacks,
+    user_data: *mut c_void,
+) -> c_int {
+    // Safety: the C API requires an agent created with `feo_secondary_new` and valid callbacks
+    let (Some(secondary), Some(callbacks)) = (unsafe { secondary.as_mut() }, unsafe { callbacks.as_ref() }) else {
+        return -1;
+    };
+    // Safety: the C API requires callbacks which are safe to call with `user_data` from any thread
+    let activity = unsafe { FfiActivity::new(activity_id.into(), *callbacks, UserData(user_data)) };
+    secondary.add_activity(worker_id.into(), activity_id.into(), activity);
+    0
+}
+
+/// Declare that an activity of an agent reads (`FEO_TOPIC_READ`) or writes (`FEO_TOPIC_WRITE`) a topic.
+///
+/// Returns 0 on success and -1 on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_add_topic(
+    secondary: *mut FeoSecondary,
+    topic: *const c_char,
+    activity_id: u64,
+    direction: u32,
+) -> c_int {
+    let direction = match direction {
+        TOPIC_READ => Direction::Incoming,
+        TOPIC_WRITE => Direction::Outgoing,
+        _ => return -1,
+    };
+    // Safety: the C API requires an agent created with `feo_secondary_new` and a nul-terminated topic
+    let (Some(secondary), Some(topic)) = (unsafe { secondary.as_mut() }, unsafe { string_arg(topic) }) else {
+        return -1;
+    };
+    secondary.add_topic(topic, activity_id.into(), direction);
+    0
+}
+
+/// Run an agent until it is shut down by the primary agent, consuming the agent.
+///
+/// Returns 0 after shutdown and -1 on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_run(secondary: *mut FeoSecondary) -> c_int {
+    if secondary.is_null() {
+        return -1;
+    }
+    // Safety: the C API requires an agent created with `feo_secondary_new`
+    let secondary = unsafe { Box::from_raw(secondary) };
+    let id = secondary.id;
+    match catch_unwind(AssertUnwindSafe(move || secondary.run())) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            error!("Agent {} failed: {:?}", id, e);
+            -1
+        },
+        Err(_) => {
+            error!("Agent {} failed", id);
+            -1
+        },
+    }
+}
+
+/// Free an agent which has not been run.
+#[unsafe(no_mangle)]
+extern "C" fn feo_secondary_free(secondary: *mut FeoSecondary) {
+    if !secondary.is_null() {
+        // Safety: the C API requires an agent created with `feo_secondary_new`
+        drop(unsafe { Box::from_raw(secondary) });
+    }
+}
+
+#[test]
+fn activities_and_topics_are_grouped() {
+    let mut secondary = FeoSecondary {
+        id: 101.into(),
+        endpoint: NodeAddress::MwCom,
+        mw_com_config: PathBuf::new(),
+        backend: ComBackend::Iox2,
+        worker_assignments: Vec::new(),
+        topics: Vec::new(),
+    };
+    let callbacks = FeoActivityCallbacks {
+        startup: None,
+        step: None,
+        shutdown: None,
+        free: None,
+    };
+    for (worker_id, activity_id) in [(40, 0), (41, 1), (40, 2)] {
+        // Safety: no callbacks are set
+        let activity = unsafe { FfiActivity::new(activity_id.into(), callbacks, UserData(core::ptr::null_mut())) };
+        secondary.add_activity(worker_id.into(), activity_id.into(), activity);
+    }
+    secondary.add_topic("feo/com/vehicle/camera", 0.into(), Direction::Outgoing);
+    secondary.add_topic("feo/com/vehicle/camera", 2.into(), Direction::Incoming);
+
+    assert_eq!(secondary.worker_assignments.len(), 2);
+    assert_eq!(secondary.worker_assignments[0].1.len(), 2);
+    assert_eq!(secondary.topics.len(), 1);
+    assert_eq!(secondary.topics[0].1.len(), 2);
+}
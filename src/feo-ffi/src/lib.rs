@@ -0,0 +1,40 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! C interface for implementing FEO activities in C or C++
+//!
+//! Existing C and C++ components can run inside FEO workers without the Rust API of FEO.
+//! A C program creates a secondary agent with `feo_secondary_new`, registers its activities as sets of
+//! callbacks with user data, declares the topics they read and write and runs the agent:
+//!
+//! ```c
+//! static int step(void* user_data) {
+//!     struct camera* camera = user_data;
+//!     feo_publish(camera->output, &camera->image, sizeof(camera->image));
+//!     return 0;
+//! }
+//!
+//! struct feo_activity_callbacks callbacks = { .startup = startup, .step = step, .shutdown = shutdown };
+//! struct feo_secondary* agent = feo_secondary_new(101, "unix:/tmp/feo_listener1.socket", "mw_com_config.json");
+//! feo_secondary_add_activity(agent, 40, 0, &callbacks, camera);
+//! feo_secondary_add_topic(agent, "feo/com/vehicle/camera", 0, FEO_TOPIC_WRITE);
+//! feo_secondary_run(agent);
+//! ```
+//!
+//! Samples are exchanged as byte buffers of up to [topic::SAMPLE_CAPACITY] bytes, see [topic::ByteSample].
+//! Rust activities communicating with C activities use [topic::ByteSample] as type of the shared topics.
+//! See `include/feo_ffi.h` for the declarations of the C functions.
+
+pub mod activity;
+pub mod agent;
+pub mod topic;
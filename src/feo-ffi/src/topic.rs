@@ -0,0 +1,257 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Topics exchanging byte samples with C activities
+//!
+//! C activities publish and read samples through opaque publisher and subscriber handles.
+//! The topics carry [ByteSample]s on the iceoryx2 or Linux shared memory backend.
+
+use com_api::{CommData, PlacementDefault, Reloc};
+use core::ffi::{c_char, c_int, CStr};
+use core::fmt;
+use feo_com::interface::{ActivityInput, ActivityOutput, ComBackend, Error};
+use feo_com::iox2::{Iox2Input, Iox2Output};
+use feo_com::linux_shm::{LinuxShmInput, LinuxShmOutput};
+use feo_com::topic::Topic;
+use score_log::error;
+use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use std::panic::catch_unwind;
+
+/// Maximum size of a sample exchanged with C activities in bytes
+pub const SAMPLE_CAPACITY: usize = 4096;
+
+/// Value of the C backend argument selecting iceoryx2
+const BACKEND_IOX2: u32 = 0;
+/// Value of the C backend argument selecting Linux shared memory
+const BACKEND_LINUX_SHM: u32 = 1;
+
+/// Sample of up to [SAMPLE_CAPACITY] bytes
+#[repr(C)]
+pub struct ByteSample {
+    /// Number of valid bytes in `data`
+    len: u32,
+    /// Content of the sample
+    data: [u8; SAMPLE_CAPACITY],
+}
+
+impl ByteSample {
+    /// Get the content of the sample
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..(self.len as usize).min(SAMPLE_CAPACITY)]
+    }
+
+    /// Set the content of the sample, returning `false` if it exceeds [SAMPLE_CAPACITY]
+    pub fn set(&mut self, bytes: &[u8]) -> bool {
+        let Some(data) = self.data.get_mut(..bytes.len()) else {
+            return false;
+        };
+        data.copy_from_slice(bytes);
+        self.len = bytes.len() as u32;
+        true
+    }
+}
+
+impl Default for ByteSample {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            data: [0; SAMPLE_CAPACITY],
+        }
+    }
+}
+
+impl fmt::Debug for ByteSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteSample").field("len", &self.len).finish()
+    }
+}
+
+impl ScoreDebug for ByteSample {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("ByteSample(", spec)?;
+        ScoreDebug::fmt(&self.len, f, spec)?;
+        f.write_str(")", spec)
+    }
+}
+
+// SAFETY: plain bytes, safe to relocate
+unsafe impl Reloc for ByteSample {}
+
+impl CommData for ByteSample {
+    const ID: &'static str = "FeoByteSample";
+}
+
+// SAFETY: only writes via field access
+unsafe impl PlacementDefault for ByteSample {
+    #[allow(clippy::not_unsafe_ptr_arg_deref)] // part of MW COM API
+    fn placement_default(s: *mut Self) {
+        unsafe {
+            (*s).len = 0;
+            (*s).data = [0; SAMPLE_CAPACITY];
+        }
+    }
+}
+
+/// Publisher of byte samples on a topic
+pub struct FeoPublisher(Box<dyn ActivityOutput<ByteSample>>);
+
+/// Subscriber of byte samples of a topic
+pub struct FeoSubscriber(Box<dyn ActivityInput<ByteSample>>);
+
+/// Get the com backend selected by a C backend argument
+pub(crate) fn backend(backend: u32) -> Option<ComBackend> {
+    match backend {
+        BACKEND_IOX2 => Some(ComBackend::Iox2),
+        BACKEND_LINUX_SHM => Some(ComBackend::LinuxShm),
+        _ => None,
+    }
+}
+
+/// Get the topic of a C string argument
+///
+/// # Safety
+///
+/// The pointer must be null or point to a nul-terminated string.
+unsafe fn topic<'a>(topic: *const c_char) -> Option<&'a str> {
+    if topic.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(topic) }.to_str().ok()
+}
+
+/// Create a publisher on a topic, returns null on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_publisher_new(topic: *const c_char, backend: u32) -> *mut FeoPublisher {
+    // Safety: the C API requires a nul-terminated topic
+    let (Some(topic), Some(backend)) = (unsafe { self::topic(topic) }, self::backend(backend)) else {
+        return core::ptr::null_mut();
+    };
+    let output = catch_unwind(|| -> Box<dyn ActivityOutput<ByteSample>> {
+        match backend {
+            ComBackend::LinuxShm => Box::new(LinuxShmOutput::new(Topic::new(topic))),
+            _ => Box::new(Iox2Output::new(Topic::new(topic))),
+        }
+    });
+    match output {
+        Ok(output) => Box::into_raw(Box::new(FeoPublisher(output))),
+        Err(_) => {
+            error!("Failed to create publisher for topic {}", topic);
+            core::ptr::null_mut()
+        },
+    }
+}
+
+/// Publish a sample of `len` bytes, returns 0 on success and -1 on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_publish(publisher: *mut FeoPublisher, data: *const u8, len: usize) -> c_int {
+    if publisher.is_null() || (data.is_null() && len > 0) {
+        return -1;
+    }
+    // Safety: the C API requires a publisher created with `feo_publisher_new` and a buffer of `len` bytes
+    let (publisher, bytes) = unsafe { (&mut *publisher, core::slice::from_raw_parts(data, len)) };
+    let mut sample = ByteSample::default();
+    if !sample.set(bytes) {
+        return -1;
+    }
+    let sent = publisher
+        .0
+        .write_uninit()
+        .and_then(|guard| guard.write_payload(sample).send());
+    match sent {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Free a publisher created with `feo_publisher_new`.
+#[unsafe(no_mangle)]
+extern "C" fn feo_publisher_free(publisher: *mut FeoPublisher) {
+    if !publisher.is_null() {
+        // Safety: the C API requires a publisher created with `feo_publisher_new`
+        drop(unsafe { Box::from_raw(publisher) });
+    }
+}
+
+/// Create a subscriber of a topic, returns null on failure.
+#[unsafe(no_mangle)]
+extern "C" fn feo_subscriber_new(topic: *const c_char, backend: u32) -> *mut FeoSubscriber {
+    // Safety: the C API requires a nul-terminated topic
+    let (Some(topic), Some(backend)) = (unsafe { self::topic(topic) }, self::backend(backend)) else {
+        return core::ptr::null_mut();
+    };
+    let input = catch_unwind(|| -> Box<dyn ActivityInput<ByteSample>> {
+        match backend {
+            ComBackend::LinuxShm => Box::new(LinuxShmInput::new(Topic::new(topic))),
+            _ => Box::new(Iox2Input::new(Topic::new(topic))),
+        }
+    });
+    match input {
+        Ok(input) => Box::into_raw(Box::new(FeoSubscriber(input))),
+        Err(_) => {
+            error!("Failed to create subscriber for topic {}", topic);
+            core::ptr::null_mut()
+        },
+    }
+}
+
+/// Copy the latest sample into `buffer` of `capacity` bytes and store its size in `len`.
+///
+/// Returns 0 on success, 1 if no sample is available and -1 on failure,
+/// e.g. if the sample exceeds the capacity of the buffer.
+#[unsafe(no_mangle)]
+extern "C" fn feo_subscriber_read(
+    subscriber: *mut FeoSubscriber,
+    buffer: *mut u8,
+    capacity: usize,
+    len: *mut usize,
+) -> c_int {
+    if subscriber.is_null() || buffer.is_null() || len.is_null() {
+        return -1;
+    }
+    // Safety: the C API requires a subscriber created with `feo_subscriber_new`
+    let subscriber = unsafe { &*subscriber };
+    let sample = match subscriber.0.read() {
+        Ok(sample) => sample,
+        Err(Error::NoEmptyBuffer) => return 1,
+        Err(_) => return -1,
+    };
+    let bytes = sample.bytes();
+    if bytes.len() > capacity {
+        return -1;
+    }
+    // Safety: the C API requires a buffer of `capacity` bytes and a valid `len`
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer, bytes.len());
+        *len = bytes.len();
+    }
+    0
+}
+
+/// Free a subscriber created with `feo_subscriber_new`.
+#[unsafe(no_mangle)]
+extern "C" fn feo_subscriber_free(subscriber: *mut FeoSubscriber) {
+    if !subscriber.is_null() {
+        // Safety: the C API requires a subscriber created with `feo_subscriber_new`
+        drop(unsafe { Box::from_raw(subscriber) });
+    }
+}
+
+#[test]
+fn byte_samples_are_bounded() {
+    let mut sample = ByteSample::default();
+    assert!(sample.bytes().is_empty());
+    assert!(sample.set(b"lane"));
+    assert_eq!(sample.bytes(), b"lane");
+    assert!(!sample.set(&[0; SAMPLE_CAPACITY + 1]));
+    assert_eq!(sample.bytes(), b"lane");
+}
@@ -0,0 +1,111 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Cycle-aligned buffering of received events
+
+use crate::config::Buffering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Payload of an event as received, with the number of events it replaced since the previous cycle
+#[derive(Debug, Default)]
+struct Slot {
+    buffering: Buffering,
+    /// Latest payload received
+    payload: Option<Vec<u8>>,
+    /// Whether the payload was received since the previous cycle
+    fresh: bool,
+    /// Events overwritten before being taken in a cycle
+    overwritten: u64,
+}
+
+/// Buffer collecting the events received between two cycles, keyed by service and event ID
+///
+/// The receiver of the events puts each event into the buffer as it arrives. At the start of each cycle,
+/// the gateway takes the events of the cycle out of the buffer, so that events arriving while the cycle is
+/// processed are deferred to the next cycle. Only the latest event per key is kept.
+#[derive(Debug, Clone, Default)]
+pub struct CycleBuffer {
+    slots: Arc<Mutex<HashMap<(u16, u16), Slot>>>,
+}
+
+impl CycleBuffer {
+    /// Create a buffer without keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer events of the given key with the given buffering
+    ///
+    /// Events of keys not added are dropped.
+    pub fn add_key(&self, service: u16, event: u16, buffering: Buffering) {
+        let mut slots = self.slots.lock().expect("cycle buffer poisoned");
+        slots.entry((service, event)).or_default().buffering = buffering;
+    }
+
+    /// Put a received event into the buffer, returning whether its key is buffered
+    pub fn put(&self, service: u16, event: u16, payload: &[u8]) -> bool {
+        let mut slots = self.slots.lock().expect("cycle buffer poisoned");
+        let Some(slot) = slots.get_mut(&(service, event)) else {
+            return false;
+        };
+        if slot.fresh {
+            slot.overwritten += 1;
+        }
+        slot.fresh = true;
+        let buffer = slot.payload.get_or_insert_with(Vec::new);
+        buffer.clear();
+        buffer.extend_from_slice(payload);
+        true
+    }
+
+    /// Take the events of the current cycle, calling the given function with the key and payload of each
+    ///
+    /// Returns the number of events overwritten by newer ones since the previous cycle.
+    pub fn take_cycle(&self, mut event: impl FnMut(u16, u16, &[u8])) -> u64 {
+        let mut slots = self.slots.lock().expect("cycle buffer poisoned");
+        let mut overwritten = 0;
+        for ((service, event_id), slot) in slots.iter_mut() {
+            overwritten += core::mem::take(&mut slot.overwritten);
+            let fresh = core::mem::take(&mut slot.fresh);
+            if !fresh && slot.buffering == Buffering::Latest {
+                continue;
+            }
+            if let Some(payload) = slot.payload.as_deref() {
+                event(*service, *event_id, payload);
+            }
+        }
+        overwritten
+    }
+}
+
+#[test]
+fn events_are_taken_per_cycle() {
+    let buffer = CycleBuffer::new();
+    buffer.add_key(1, 1, Buffering::Latest);
+    buffer.add_key(2, 1, Buffering::Hold);
+    assert!(!buffer.put(3, 1, &[0]));
+    assert!(buffer.put(1, 1, &[1]));
+    assert!(buffer.put(1, 1, &[2]));
+    assert!(buffer.put(2, 1, &[3]));
+
+    let mut taken = Vec::new();
+    assert_eq!(buffer.take_cycle(|service, _, payload| taken.push((service, payload.to_vec()))), 1);
+    taken.sort();
+    assert_eq!(taken, [(1, vec![2]), (2, vec![3])]);
+
+    // Without new events, only held events are taken again
+    taken.clear();
+    assert_eq!(buffer.take_cycle(|service, _, payload| taken.push((service, payload.to_vec()))), 0);
+    assert_eq!(taken, [(2, vec![3])]);
+}
@@ -0,0 +1,217 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Configuration of the mapping between FEO topics and SOME/IP events
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+
+/// Configuration of a [SomeIpGateway](crate::SomeIpGateway)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GatewayConfig {
+    /// Local address receiving events and sending notifications
+    pub bind: SocketAddr,
+    /// Client ID put into the header of sent notifications
+    #[serde(default)]
+    pub client_id: u16,
+    /// Mapped topics
+    #[serde(default)]
+    pub mappings: Vec<Mapping>,
+}
+
+/// Mapping of a FEO topic to an event of a SOME/IP service
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping {
+    /// Name of the topic
+    pub topic: String,
+    /// ID of the service
+    pub service: u16,
+    /// ID of the event within the service
+    pub event: u16,
+    /// Interface version of the service
+    #[serde(default = "default_interface_version")]
+    pub interface_version: u8,
+    /// Direction of the forwarding
+    pub direction: Direction,
+    /// Address notifications are sent to, required for [Direction::ToSomeIp]
+    #[serde(default)]
+    pub destination: Option<SocketAddr>,
+    /// Buffering of received events, only used for [Direction::ToFeo]
+    #[serde(default)]
+    pub buffering: Buffering,
+}
+
+fn default_interface_version() -> u8 {
+    1
+}
+
+/// Direction of the forwarding of a [Mapping]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Direction {
+    /// Publish received events on the topic
+    #[serde(rename = "to_feo")]
+    ToFeo,
+    /// Send the samples of the topic as notifications
+    #[serde(rename = "to_someip")]
+    ToSomeIp,
+}
+
+/// Buffering of the events received between two steps of the gateway
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Buffering {
+    /// Publish the latest event received since the previous step, nothing if none was received
+    #[default]
+    Latest,
+    /// Publish the latest event received so far on every step, repeating it until a newer one is received
+    Hold,
+}
+
+/// Error loading a [GatewayConfig] or registering a bridge
+#[derive(Debug)]
+pub enum GatewayError {
+    /// The configuration file could not be read
+    Io(std::io::Error),
+    /// The configuration could not be parsed
+    Parse(String),
+    /// The file extension is neither `toml` nor `json`
+    UnknownFormat,
+    /// The topic is mapped more than once
+    DuplicateTopic(String),
+    /// The event of the service is forwarded to FEO more than once
+    DuplicateEvent { service: u16, event: u16 },
+    /// The topic forwarded to SOME/IP has no destination
+    MissingDestination(String),
+    /// The topic is not mapped in the given direction
+    TopicNotMapped(String),
+}
+
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatewayError::Io(e) => write!(f, "failed to read gateway configuration: {e}"),
+            GatewayError::Parse(e) => write!(f, "failed to parse gateway configuration: {e}"),
+            GatewayError::UnknownFormat => write!(f, "unknown configuration format, expected .toml or .json"),
+            GatewayError::DuplicateTopic(topic) => write!(f, "topic {topic} is mapped more than once"),
+            GatewayError::DuplicateEvent { service, event } => {
+                write!(f, "event {event:#06x} of service {service:#06x} is mapped more than once")
+            },
+            GatewayError::MissingDestination(topic) => write!(f, "topic {topic} is mapped without destination"),
+            GatewayError::TopicNotMapped(topic) => write!(f, "topic {topic} is not mapped in this direction"),
+        }
+    }
+}
+
+impl core::error::Error for GatewayError {}
+
+impl GatewayConfig {
+    /// Load and validate a configuration file, choosing the format by its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GatewayError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(GatewayError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => Err(GatewayError::UnknownFormat),
+        }
+    }
+
+    /// Parse and validate a configuration in TOML format
+    pub fn from_toml(content: &str) -> Result<Self, GatewayError> {
+        let config: Self = toml::from_str(content).map_err(|e| GatewayError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse and validate a configuration in JSON format
+    pub fn from_json(content: &str) -> Result<Self, GatewayError> {
+        let config: Self = serde_json::from_str(content).map_err(|e| GatewayError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that topics and received events are mapped once and sent topics have a destination
+    pub fn validate(&self) -> Result<(), GatewayError> {
+        let mut topics = HashSet::new();
+        let mut events = HashSet::new();
+        for mapping in self.mappings.iter() {
+            if !topics.insert(mapping.topic.as_str()) {
+                return Err(GatewayError::DuplicateTopic(mapping.topic.clone()));
+            }
+            match mapping.direction {
+                Direction::ToFeo if !events.insert((mapping.service, mapping.event)) => {
+                    return Err(GatewayError::DuplicateEvent {
+                        service: mapping.service,
+                        event: mapping.event,
+                    });
+                },
+                Direction::ToSomeIp if mapping.destination.is_none() => {
+                    return Err(GatewayError::MissingDestination(mapping.topic.clone()));
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the mapping of the given topic in the given direction
+    pub(crate) fn mapping(&self, topic: &str, direction: Direction) -> Result<&Mapping, GatewayError> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.topic == topic && mapping.direction == direction)
+            .ok_or_else(|| GatewayError::TopicNotMapped(topic.to_string()))
+    }
+}
+
+#[test]
+fn config_is_parsed_and_validated() {
+    let content = r#"
+        bind = "0.0.0.0:30490"
+
+        [[mappings]]
+        topic = "feo/com/vehicle/speed"
+        service = 0x1234
+        event = 0x8001
+        direction = "to_feo"
+        buffering = "hold"
+
+        [[mappings]]
+        topic = "feo/com/vehicle/brake"
+        service = 0x1235
+        event = 0x8002
+        direction = "to_someip"
+        destination = "192.168.0.2:30501"
+    "#;
+    let config = GatewayConfig::from_toml(content).unwrap();
+    assert_eq!(config.client_id, 0);
+    let speed = config.mapping("feo/com/vehicle/speed", Direction::ToFeo).unwrap();
+    assert_eq!((speed.service, speed.event, speed.interface_version), (0x1234, 0x8001, 1));
+    assert_eq!(speed.buffering, Buffering::Hold);
+    assert!(config.mapping("feo/com/vehicle/brake", Direction::ToFeo).is_err());
+
+    let missing = content.replace("destination = \"192.168.0.2:30501\"", "");
+    assert!(matches!(
+        GatewayConfig::from_toml(&missing),
+        Err(GatewayError::MissingDestination(_))
+    ));
+    let duplicate = content.replace("feo/com/vehicle/brake", "feo/com/vehicle/speed");
+    assert!(matches!(
+        GatewayConfig::from_toml(&duplicate),
+        Err(GatewayError::DuplicateTopic(_))
+    ));
+}
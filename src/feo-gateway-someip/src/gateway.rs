@@ -0,0 +1,238 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Gateway activity forwarding between FEO topics and SOME/IP events
+
+use crate::buffer::CycleBuffer;
+use crate::config::{Direction, GatewayConfig, GatewayError};
+use crate::message::{MessageType, SomeIpHeader};
+use crate::payload::SomeIpPayload;
+use feo::activity::Activity;
+use feo::error::ActivityError;
+use feo::ids::ActivityId;
+use feo_com::interface::{ActivityInput, ActivityOutput, FeoComData};
+use score_log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Maximum size of a received datagram
+const MAX_DATAGRAM_SIZE: usize = 65535;
+
+/// Interval in which the receiver thread checks for shutdown
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Function publishing a received payload on a topic, returning an error description on failure
+type Publisher = Box<dyn FnMut(&[u8]) -> Result<(), &'static str>>;
+
+/// Function reading the current sample of a topic as payload, if any
+type Reader = Box<dyn FnMut() -> Option<Vec<u8>>>;
+
+/// Topic forwarded as notifications
+struct Notifier {
+    topic: String,
+    service: u16,
+    event: u16,
+    interface_version: u8,
+    destination: SocketAddr,
+    read: Reader,
+}
+
+/// Activity forwarding between FEO topics and SOME/IP events as configured in a [GatewayConfig]
+///
+/// Each mapped topic has to be registered with its type before startup. Events are received by a
+/// background thread started on startup and published on the next step, see [CycleBuffer].
+pub struct SomeIpGateway {
+    id: ActivityId,
+    config: GatewayConfig,
+    buffer: CycleBuffer,
+    /// Publishers per service and event ID
+    publishers: HashMap<(u16, u16), (String, Publisher)>,
+    notifiers: Vec<Notifier>,
+    socket: Option<UdpSocket>,
+    receiver: Option<JoinHandle<()>>,
+    running: Arc<AtomicBool>,
+    /// Session ID of the next notification
+    session: u16,
+}
+
+impl SomeIpGateway {
+    /// Create a gateway without registered topics
+    pub fn new(id: ActivityId, config: GatewayConfig) -> Self {
+        Self {
+            id,
+            config,
+            buffer: CycleBuffer::new(),
+            publishers: HashMap::new(),
+            notifiers: Vec::new(),
+            socket: None,
+            receiver: None,
+            running: Arc::new(AtomicBool::new(false)),
+            session: 1,
+        }
+    }
+
+    /// Publish the events mapped to the given topic on the given output
+    pub fn bridge_to_feo<T>(&mut self, topic: &str, mut output: Box<dyn ActivityOutput<T>>) -> Result<(), GatewayError>
+    where
+        T: FeoComData + SomeIpPayload + 'static,
+    {
+        let mapping = self.config.mapping(topic, Direction::ToFeo)?;
+        let publisher = move |payload: &[u8]| -> Result<(), &'static str> {
+            let sample = T::from_payload(payload).ok_or("malformed payload")?;
+            output
+                .write_uninit()
+                .map_err(|_| "failed to get output buffer")?
+                .write_payload(sample)
+                .send()
+                .map_err(|_| "failed to send sample")
+        };
+        self.buffer.add_key(mapping.service, mapping.event, mapping.buffering);
+        self.publishers.insert(
+            (mapping.service, mapping.event),
+            (topic.to_string(), Box::new(publisher)),
+        );
+        Ok(())
+    }
+
+    /// Send the samples of the given topic read from the given input as notifications of the mapped event
+    pub fn bridge_to_someip<T>(&mut self, topic: &str, input: Box<dyn ActivityInput<T>>) -> Result<(), GatewayError>
+    where
+        T: FeoComData + SomeIpPayload + 'static,
+    {
+        let mapping = self.config.mapping(topic, Direction::ToSomeIp)?;
+        let destination = mapping
+            .destination
+            .ok_or_else(|| GatewayError::MissingDestination(topic.to_string()))?;
+        let read = move || input.read().ok().map(|sample| sample.to_payload());
+        self.notifiers.push(Notifier {
+            topic: topic.to_string(),
+            service: mapping.service,
+            event: mapping.event,
+            interface_version: mapping.interface_version,
+            destination,
+            read: Box::new(read),
+        });
+        Ok(())
+    }
+
+    /// Publish the events received since the previous step
+    fn publish_received(&mut self) {
+        let publishers = &mut self.publishers;
+        let overwritten = self.buffer.take_cycle(|service, event, payload| {
+            let Some((topic, publish)) = publishers.get_mut(&(service, event)) else {
+                return;
+            };
+            if let Err(e) = publish(payload) {
+                warn!("Failed to publish SOME/IP event on topic {}: {}", topic.as_str(), e);
+            }
+        });
+        if overwritten > 0 {
+            debug!("Gateway {} dropped {} events overwritten within a cycle", self.id, overwritten);
+        }
+    }
+
+    /// Send the current samples of the forwarded topics as notifications
+    fn send_notifications(&mut self) -> Result<(), ActivityError> {
+        let socket = self.socket.as_ref().ok_or(ActivityError::Step)?;
+        for notifier in self.notifiers.iter_mut() {
+            let Some(payload) = (notifier.read)() else {
+                continue;
+            };
+            let header = SomeIpHeader::notification(
+                notifier.service,
+                notifier.event,
+                self.config.client_id,
+                self.session,
+                notifier.interface_version,
+            );
+            // Session IDs wrap around to 1, 0 denotes disabled session handling
+            self.session = self.session.checked_add(1).unwrap_or(1);
+            if socket.send_to(&header.encode(&payload), notifier.destination).is_err() {
+                warn!("Failed to send SOME/IP notification of topic {}", notifier.topic.as_str());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Activity for SomeIpGateway {
+    fn id(&self) -> ActivityId {
+        self.id
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        let socket = UdpSocket::bind(self.config.bind).map_err(|_| {
+            error!("Gateway {} failed to bind {}", self.id, self.config.bind.to_string().as_str());
+            ActivityError::Startup
+        })?;
+        let receiving = socket.try_clone().map_err(|_| ActivityError::Startup)?;
+        receiving
+            .set_read_timeout(Some(RECEIVE_TIMEOUT))
+            .map_err(|_| ActivityError::Startup)?;
+
+        self.running.store(true, Ordering::Relaxed);
+        let running = self.running.clone();
+        let buffer = self.buffer.clone();
+        let receiver = thread::Builder::new()
+            .name("someip_gateway".to_string())
+            .spawn(move || receive(receiving, buffer, running))
+            .map_err(|_| ActivityError::Startup)?;
+
+        self.socket = Some(socket);
+        self.receiver = Some(receiver);
+        info!(
+            "Gateway {} forwarding {} events and {} topics on {}",
+            self.id,
+            self.publishers.len(),
+            self.notifiers.len(),
+            self.config.bind.to_string().as_str()
+        );
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        self.publish_received();
+        self.send_notifications()
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(receiver) = self.receiver.take() {
+            receiver.join().map_err(|_| ActivityError::Shutdown)?;
+        }
+        self.socket = None;
+        Ok(())
+    }
+}
+
+/// Receive events into the buffer until the gateway shuts down
+fn receive(socket: UdpSocket, buffer: CycleBuffer, running: Arc<AtomicBool>) {
+    let mut datagram = vec![0; MAX_DATAGRAM_SIZE];
+    while running.load(Ordering::Relaxed) {
+        let Ok(len) = socket.recv(&mut datagram) else {
+            // Timed out, check for shutdown
+            continue;
+        };
+        match SomeIpHeader::decode(&datagram[..len]) {
+            Some((header, payload)) if header.message_type == MessageType::Notification => {
+                buffer.put(header.service, header.method, payload);
+            },
+            Some(_) => {},
+            None => debug!("Dropping malformed SOME/IP message of {} bytes", len),
+        }
+    }
+}
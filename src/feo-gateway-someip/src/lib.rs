@@ -0,0 +1,67 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Building blocks for activities bridging FEO topics and SOME/IP events
+//!
+//! A [SomeIpGateway] is an activity forwarding SOME/IP events received via UDP to FEO topics and
+//! FEO topics to SOME/IP notifications. Which topic maps to which event of which service is configured
+//! in a [GatewayConfig], parsed from TOML or JSON, so that a new signal only needs a configuration entry
+//! and a typed registration instead of a hand-written bridge:
+//!
+//! ```toml
+//! bind = "0.0.0.0:30490"
+//! client_id = 0x10
+//!
+//! [[mappings]]
+//! topic = "feo/com/vehicle/speed"
+//! service = 0x1234
+//! event = 0x8001
+//! direction = "to_feo"
+//! buffering = "hold"
+//!
+//! [[mappings]]
+//! topic = "feo/com/vehicle/brake"
+//! service = 0x1235
+//! event = 0x8002
+//! direction = "to_someip"
+//! destination = "192.168.0.2:30501"
+//! ```
+//!
+//! ```ignore
+//! let config = GatewayConfig::load("gateway.toml")?;
+//! let mut gateway = SomeIpGateway::new(id, config);
+//! gateway.bridge_to_feo::<Speed>("feo/com/vehicle/speed", activity_output(TOPIC_SPEED))?;
+//! gateway.bridge_to_someip::<BrakeInstruction>("feo/com/vehicle/brake", activity_input(TOPIC_BRAKE))?;
+//! ```
+//!
+//! The samples are converted with the [SomeIpPayload] serialization adapter of their type, implemented
+//! for the basic types of the SOME/IP serialization and to be implemented for the structs of an
+//! application by serializing their fields in order.
+//!
+//! Events are received in the background and collected in a [CycleBuffer] until the next step of
+//! the gateway, which publishes the latest event received per mapping, so that all activities of a
+//! cycle see the same data. Notifications are sent on each step for the topics with a sample.
+//!
+//! Service discovery is not part of the gateway: the remote endpoints are configured statically.
+
+mod buffer;
+mod config;
+mod gateway;
+mod message;
+mod payload;
+
+pub use buffer::CycleBuffer;
+pub use config::{Buffering, Direction, GatewayConfig, GatewayError, Mapping};
+pub use gateway::SomeIpGateway;
+pub use message::{MessageType, SomeIpHeader};
+pub use payload::SomeIpPayload;
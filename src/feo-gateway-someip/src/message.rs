@@ -0,0 +1,142 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Header of SOME/IP messages
+
+/// Protocol version of SOME/IP
+pub const PROTOCOL_VERSION: u8 = 0x01;
+
+/// Type of a SOME/IP message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    Request,
+    RequestNoReturn,
+    Notification,
+    Response,
+    Error,
+}
+
+impl MessageType {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x00 => Some(MessageType::Request),
+            0x01 => Some(MessageType::RequestNoReturn),
+            0x02 => Some(MessageType::Notification),
+            0x80 => Some(MessageType::Response),
+            0x81 => Some(MessageType::Error),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            MessageType::Request => 0x00,
+            MessageType::RequestNoReturn => 0x01,
+            MessageType::Notification => 0x02,
+            MessageType::Response => 0x80,
+            MessageType::Error => 0x81,
+        }
+    }
+}
+
+/// Header of a SOME/IP message, big endian on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SomeIpHeader {
+    /// ID of the service
+    pub service: u16,
+    /// ID of the method or event
+    pub method: u16,
+    /// ID of the client
+    pub client: u16,
+    /// ID of the session, counting the messages of the client
+    pub session: u16,
+    /// Interface version of the service
+    pub interface_version: u8,
+    /// Type of the message
+    pub message_type: MessageType,
+    /// Return code, 0 for notifications and successful responses
+    pub return_code: u8,
+}
+
+impl SomeIpHeader {
+    /// Size of the serialized header in bytes
+    pub const SIZE: usize = 16;
+
+    /// Header of a notification of the given event
+    pub fn notification(service: u16, event: u16, client: u16, session: u16, interface_version: u8) -> Self {
+        Self {
+            service,
+            method: event,
+            client,
+            session,
+            interface_version,
+            message_type: MessageType::Notification,
+            return_code: 0,
+        }
+    }
+
+    /// Serialize a message with this header and the given payload
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        // The length covers the payload and the part of the header following the length field
+        let length = (payload.len() + 8) as u32;
+        let mut message = Vec::with_capacity(Self::SIZE + payload.len());
+        message.extend_from_slice(&self.service.to_be_bytes());
+        message.extend_from_slice(&self.method.to_be_bytes());
+        message.extend_from_slice(&length.to_be_bytes());
+        message.extend_from_slice(&self.client.to_be_bytes());
+        message.extend_from_slice(&self.session.to_be_bytes());
+        message.push(PROTOCOL_VERSION);
+        message.push(self.interface_version);
+        message.push(self.message_type.to_byte());
+        message.push(self.return_code);
+        message.extend_from_slice(payload);
+        message
+    }
+
+    /// Split a received message into its header and payload
+    ///
+    /// Returns `None` if the message is truncated, of another protocol version or of an unknown type.
+    pub fn decode(message: &[u8]) -> Option<(Self, &[u8])> {
+        if message.len() < Self::SIZE || message[12] != PROTOCOL_VERSION {
+            return None;
+        }
+        let u16_at = |offset: usize| u16::from_be_bytes([message[offset], message[offset + 1]]);
+        let length = u32::from_be_bytes([message[4], message[5], message[6], message[7]]) as usize;
+        let end = length.checked_add(8).filter(|end| *end >= Self::SIZE && *end <= message.len())?;
+        let header = Self {
+            service: u16_at(0),
+            method: u16_at(2),
+            client: u16_at(8),
+            session: u16_at(10),
+            interface_version: message[13],
+            message_type: MessageType::from_byte(message[14])?,
+            return_code: message[15],
+        };
+        Some((header, &message[Self::SIZE..end]))
+    }
+}
+
+#[test]
+fn header_roundtrip() {
+    let header = SomeIpHeader::notification(0x1234, 0x8001, 0x10, 7, 2);
+    let message = header.encode(&[1, 2, 3]);
+    assert_eq!(message.len(), SomeIpHeader::SIZE + 3);
+    assert_eq!(&message[4..8], &[0, 0, 0, 11]);
+    assert_eq!(SomeIpHeader::decode(&message), Some((header, &[1u8, 2, 3][..])));
+
+    // Truncated messages and other protocol versions are rejected
+    assert_eq!(SomeIpHeader::decode(&message[..message.len() - 1]), None);
+    let mut other_version = message.clone();
+    other_version[12] = 2;
+    assert_eq!(SomeIpHeader::decode(&other_version), None);
+}
@@ -0,0 +1,154 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Serialization adapters between FEO samples and SOME/IP payloads
+
+/// Conversion of a type from and to the SOME/IP serialization
+///
+/// Implemented for the basic types, big endian without padding, for fixed-length arrays and for
+/// dynamic-length vectors, preceded by a 32 bit length field counting their bytes. Structs are
+/// serialized as the sequence of their fields:
+///
+/// ```ignore
+/// impl SomeIpPayload for Speed {
+///     fn serialize(&self, payload: &mut Vec<u8>) {
+///         self.timestamp_ms.serialize(payload);
+///         self.speed_kmh.serialize(payload);
+///     }
+///
+///     fn deserialize(payload: &mut &[u8]) -> Option<Self> {
+///         Some(Self {
+///             timestamp_ms: u64::deserialize(payload)?,
+///             speed_kmh: f32::deserialize(payload)?,
+///         })
+///     }
+/// }
+/// ```
+pub trait SomeIpPayload: Sized {
+    /// Append the serialized value to the payload
+    fn serialize(&self, payload: &mut Vec<u8>);
+
+    /// Deserialize a value from the start of the payload, advancing it past the value
+    ///
+    /// Returns `None` if the payload is too short or malformed.
+    fn deserialize(payload: &mut &[u8]) -> Option<Self>;
+
+    /// Serialize the value into a payload of its own
+    fn to_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.serialize(&mut payload);
+        payload
+    }
+
+    /// Deserialize a value from a payload, which must not hold further bytes
+    fn from_payload(mut payload: &[u8]) -> Option<Self> {
+        let value = Self::deserialize(&mut payload)?;
+        payload.is_empty().then_some(value)
+    }
+}
+
+/// Take the given number of bytes from the start of the payload
+fn take<'a>(payload: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if payload.len() < len {
+        return None;
+    }
+    let (bytes, rest) = payload.split_at(len);
+    *payload = rest;
+    Some(bytes)
+}
+
+macro_rules! impl_numeric {
+    ($($ty:ty),*) => {
+        $(
+            impl SomeIpPayload for $ty {
+                fn serialize(&self, payload: &mut Vec<u8>) {
+                    payload.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn deserialize(payload: &mut &[u8]) -> Option<Self> {
+                    let bytes = take(payload, size_of::<$ty>())?;
+                    Some(<$ty>::from_be_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl SomeIpPayload for bool {
+    fn serialize(&self, payload: &mut Vec<u8>) {
+        payload.push(u8::from(*self));
+    }
+
+    fn deserialize(payload: &mut &[u8]) -> Option<Self> {
+        match u8::deserialize(payload)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl<T: SomeIpPayload, const N: usize> SomeIpPayload for [T; N] {
+    fn serialize(&self, payload: &mut Vec<u8>) {
+        for element in self.iter() {
+            element.serialize(payload);
+        }
+    }
+
+    fn deserialize(payload: &mut &[u8]) -> Option<Self> {
+        let elements: Vec<T> = (0..N).map(|_| T::deserialize(payload)).collect::<Option<_>>()?;
+        elements.try_into().ok()
+    }
+}
+
+impl<T: SomeIpPayload> SomeIpPayload for Vec<T> {
+    fn serialize(&self, payload: &mut Vec<u8>) {
+        let length_at = payload.len();
+        payload.extend_from_slice(&[0; 4]);
+        for element in self.iter() {
+            element.serialize(payload);
+        }
+        let length = (payload.len() - length_at - 4) as u32;
+        payload[length_at..length_at + 4].copy_from_slice(&length.to_be_bytes());
+    }
+
+    fn deserialize(payload: &mut &[u8]) -> Option<Self> {
+        let length = u32::deserialize(payload)? as usize;
+        let mut elements = take(payload, length)?;
+        let mut vec = Vec::new();
+        while !elements.is_empty() {
+            vec.push(T::deserialize(&mut elements)?);
+        }
+        Some(vec)
+    }
+}
+
+#[test]
+fn payloads_roundtrip() {
+    assert_eq!(0x1234u16.to_payload(), [0x12, 0x34]);
+    assert_eq!(f32::from_payload(&1.5f32.to_payload()), Some(1.5));
+    assert_eq!(bool::from_payload(&[2]), None);
+
+    let array = [-1i16, 2, 3];
+    assert_eq!(<[i16; 3]>::from_payload(&array.to_payload()), Some(array));
+
+    let vec = vec![1u32, 2];
+    let payload = vec.to_payload();
+    assert_eq!(&payload[..4], &[0, 0, 0, 8]);
+    assert_eq!(Vec::<u32>::from_payload(&payload), Some(vec));
+    // Trailing and missing bytes are rejected
+    assert_eq!(u8::from_payload(&[1, 2]), None);
+    assert_eq!(Vec::<u32>::from_payload(&payload[..payload.len() - 1]), None);
+}
@@ -0,0 +1,573 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Command line interface of the recording tool
+
+use crate::export::{write_csv, write_json};
+use crate::registry::TypeRegistry;
+use crate::select::Selection;
+use anyhow::{anyhow, bail, Context, Error};
+use argh::FromArgs;
+use core::str::FromStr;
+use feo::recording::encryption::RecordingKey;
+use feo::recording::header::RecordingHeader;
+use feo::recording::timeline::Timeline;
+use feo::recording::Record;
+use feo_recording_reader::{RecordEntry, RecordIter};
+use feo_time::Duration;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::iter;
+use std::path::PathBuf;
+use std::thread;
+use std::time;
+
+/// Interval of reading the end of a followed recording again
+const FOLLOW_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+#[derive(FromArgs)]
+#[argh(help_triggers("-h", "--help", "help"))]
+/// Inspect and convert FEO recordings
+pub struct Args {
+    #[argh(subcommand)]
+    command: Command,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    List(ListArgs),
+    Info(InfoArgs),
+    Export(ExportArgs),
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "list")]
+/// List the selected records, one per line
+struct ListArgs {
+    #[argh(positional, description = "recording files, in order")]
+    paths: Vec<PathBuf>,
+
+    #[argh(option, long = "type", description = "type name to select, can be repeated")]
+    type_name: Vec<String>,
+
+    #[argh(option, description = "topic to select, can be repeated")]
+    topic: Vec<String>,
+
+    #[argh(option, description = "earliest timestamp to select in milliseconds")]
+    from: Option<u64>,
+
+    #[argh(option, description = "latest timestamp to select in milliseconds")]
+    to: Option<u64>,
+
+    #[argh(option, description = "file holding the key of encrypted recordings as 64 hex digits")]
+    key_file: Option<PathBuf>,
+
+    #[argh(switch, description = "keep listing the records appended to a recording while it is written")]
+    follow: bool,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "info")]
+/// Print a summary of the recorded cycles and topics
+struct InfoArgs {
+    #[argh(positional, description = "recording files, in order")]
+    paths: Vec<PathBuf>,
+
+    #[argh(option, description = "file holding the key of encrypted recordings as 64 hex digits")]
+    key_file: Option<PathBuf>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "export")]
+/// Export the selected records to JSON or CSV
+struct ExportArgs {
+    #[argh(positional, description = "recording files, in order")]
+    paths: Vec<PathBuf>,
+
+    #[argh(
+        option,
+        short = 'f',
+        default = "Format::Json",
+        description = "output format: json or csv"
+    )]
+    format: Format,
+
+    #[argh(option, short = 'o', description = "output path, defaults to stdout")]
+    out: Option<PathBuf>,
+
+    #[argh(option, long = "type", description = "type name to select, can be repeated")]
+    type_name: Vec<String>,
+
+    #[argh(option, description = "topic to select, can be repeated")]
+    topic: Vec<String>,
+
+    #[argh(option, description = "earliest timestamp to select in milliseconds")]
+    from: Option<u64>,
+
+    #[argh(option, description = "latest timestamp to select in milliseconds")]
+    to: Option<u64>,
+
+    #[argh(option, description = "file holding the key of encrypted recordings as 64 hex digits")]
+    key_file: Option<PathBuf>,
+}
+
+/// Export format
+enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!("unknown format {other}, expected json or csv")),
+        }
+    }
+}
+
+/// Parse the command line and run the selected command, decoding samples with the given registry
+pub fn run(registry: &TypeRegistry) -> Result<(), Error> {
+    let Args { command } = argh::from_env();
+    match command {
+        Command::List(args) => {
+            let selection = selection(args.type_name, args.topic, args.from, args.to);
+            let key = load_key(args.key_file.as_ref(), &args.paths)?;
+            if args.follow {
+                follow(&args.paths, &selection, key.as_ref(), registry)
+            } else {
+                list(&args.paths, &selection, key.as_ref(), registry)
+            }
+        },
+        Command::Info(args) => {
+            let key = load_key(args.key_file.as_ref(), &args.paths)?;
+            info(&args.paths, key.as_ref(), registry)
+        },
+        Command::Export(args) => {
+            let selection = selection(args.type_name, args.topic, args.from, args.to);
+            let key = load_key(args.key_file.as_ref(), &args.paths)?;
+            let records = read_records(&args.paths, selection.from, key.as_ref(), registry)?;
+            let records = records.filter(move |record| match record {
+                Ok(record) => selection.matches(record),
+                Err(_) => true,
+            });
+            // Rotated recordings note the encoding in the header of their first file only
+            let encoding = args
+                .paths
+                .first()
+                .map(read_header)
+                .transpose()?
+                .flatten()
+                .map(|header| header.encoding)
+                .unwrap_or_default();
+            let mut writer: Box<dyn Write> = match &args.out {
+                Some(path) => Box::new(BufWriter::new(
+                    File::create(path).with_context(|| format!("failed to create {}", path.display()))?,
+                )),
+                None => Box::new(BufWriter::new(io::stdout().lock())),
+            };
+            match args.format {
+                Format::Json => write_json(&mut writer, records, registry, encoding)?,
+                Format::Csv => write_csv(&mut writer, records, registry, encoding)?,
+            }
+            writer.flush()?;
+            Ok(())
+        },
+    }
+}
+
+/// Build a selection from the command line options
+fn selection(types: Vec<String>, topics: Vec<String>, from: Option<u64>, to: Option<u64>) -> Selection {
+    Selection {
+        types,
+        topics,
+        from: from.map(Duration::from_millis),
+        to: to.map(Duration::from_millis),
+    }
+}
+
+/// Load the key of encrypted recordings from the given file, if any
+///
+/// The key gets the id noted in the header of the first recording file, so that only the key itself
+/// has to be kept in the file.
+fn load_key(key_file: Option<&PathBuf>, paths: &[PathBuf]) -> Result<Option<RecordingKey>, Error> {
+    let Some(key_file) = key_file else {
+        return Ok(None);
+    };
+    let hex = fs::read_to_string(key_file).with_context(|| format!("failed to read {}", key_file.display()))?;
+    let key_id = paths
+        .first()
+        .map(read_header)
+        .transpose()?
+        .flatten()
+        .and_then(|header| header.key_id)
+        .unwrap_or_default();
+    let key =
+        RecordingKey::from_hex(key_id, &hex).map_err(|e| anyhow!("invalid key in {}: {e}", key_file.display()))?;
+    Ok(Some(key))
+}
+
+/// Read the records of all given files in order, decrypting their samples with the given key, if any
+///
+/// If `from` is given, files with a sidecar index are read starting at the last cycle before `from`.
+/// Files ending with an incomplete record, e.g. written by a killed agent, are read up to that record.
+fn read_records(
+    paths: &[PathBuf],
+    from: Option<Duration>,
+    key: Option<&RecordingKey>,
+    registry: &TypeRegistry,
+) -> Result<impl Iterator<Item = Result<Record, Error>>, Error> {
+    if paths.is_empty() {
+        bail!("no recording files given");
+    }
+    let readers = paths
+        .iter()
+        .map(|path| {
+            let mut records =
+                RecordIter::open(path).map_err(|e| anyhow!("failed to open {}: {e}", path.display()))?;
+            if let Some(key) = key {
+                records = records.with_key(key.clone());
+            }
+            let header = records
+                .read_header()
+                .map_err(|e| anyhow!("failed to read header of {}: {e}", path.display()))?;
+            if let Some(header) = header {
+                check_schemas(header, registry).with_context(|| format!("cannot decode {}", path.display()))?;
+            }
+            if let Some(from) = from {
+                records
+                    .seek_to_time(from)
+                    .map_err(|e| anyhow!("failed to seek in {}: {e}", path.display()))?;
+            }
+            Ok((path.clone(), records))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(readers.into_iter().flat_map(|(path, mut records)| {
+        iter::from_fn(move || match records.next() {
+            Some(entry) => Some(
+                entry
+                    .map(RecordEntry::into_record)
+                    .map_err(|e| anyhow!("failed to read {}: {e}", path.display())),
+            ),
+            None => {
+                if let Some(offset) = records.truncated() {
+                    eprintln!(
+                        "warning: {} ends with an incomplete record at byte {offset}",
+                        path.display()
+                    );
+                }
+                None
+            },
+        })
+    }))
+}
+
+/// Check that the registered types match the schemas of the recorded types
+fn check_schemas(header: &RecordingHeader, registry: &TypeRegistry) -> Result<(), Error> {
+    for recorded in header.types.iter() {
+        match registry.schema_hash(&recorded.type_name) {
+            Some(schema_hash) if schema_hash != recorded.schema_hash => {
+                bail!(
+                    "registered type {} does not match the recorded schema",
+                    recorded.type_name
+                )
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Read the header of a recording file, if any
+fn read_header(path: &PathBuf) -> Result<Option<RecordingHeader>, Error> {
+    let mut records = RecordIter::open(path).map_err(|e| anyhow!("failed to open {}: {e}", path.display()))?;
+    let header = records
+        .read_header()
+        .map_err(|e| anyhow!("failed to read header of {}: {e}", path.display()))?;
+    Ok(header.cloned())
+}
+
+/// Print the selected records, one per line
+fn list(
+    paths: &[PathBuf],
+    selection: &Selection,
+    key: Option<&RecordingKey>,
+    registry: &TypeRegistry,
+) -> Result<(), Error> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    for record in read_records(paths, selection.from, key, registry)? {
+        let record = record?;
+        if selection.matches(&record) {
+            write_record(&mut out, record, registry)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print the selected records of a recording while it is written, until interrupted or past the selected time
+///
+/// Complete records are printed as they are appended, an incomplete record at the end of the file
+/// is read again after [FOLLOW_INTERVAL].
+fn follow(
+    paths: &[PathBuf],
+    selection: &Selection,
+    key: Option<&RecordingKey>,
+    registry: &TypeRegistry,
+) -> Result<(), Error> {
+    let [path] = paths else {
+        bail!("--follow takes a single recording file");
+    };
+    let mut records = RecordIter::open(path).map_err(|e| anyhow!("failed to open {}: {e}", path.display()))?;
+    if let Some(key) = key {
+        records = records.with_key(key.clone());
+    }
+    let mut checked = false;
+    let mut out = BufWriter::new(io::stdout().lock());
+    loop {
+        let entry = records
+            .follow_entry()
+            .map_err(|e| anyhow!("failed to read {}: {e}", path.display()))?;
+        let Some(entry) = entry else {
+            out.flush()?;
+            thread::sleep(FOLLOW_INTERVAL);
+            continue;
+        };
+        // The header is the first frame of a recording, read before the first record
+        if !checked {
+            if let Some(header) = records.header() {
+                check_schemas(header, registry).with_context(|| format!("cannot decode {}", path.display()))?;
+            }
+            checked = true;
+        }
+        let record = entry.into_record();
+        if selection.to.is_some_and(|to| record.timestamp() > to) {
+            break;
+        }
+        if selection.matches(&record) {
+            write_record(&mut out, record, registry)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print a record on one line
+fn write_record(out: &mut impl Write, record: Record, registry: &TypeRegistry) -> Result<(), Error> {
+    let timestamp = record.timestamp().as_secs_f64();
+    match record {
+        Record::Cycle { .. } => writeln!(out, "{timestamp:>12.6}  cycle")?,
+        Record::Data {
+            topic, type_name, data, ..
+        } => {
+            let decoded = if registry.contains(&type_name) {
+                ""
+            } else {
+                " (unregistered)"
+            };
+            writeln!(
+                out,
+                "{timestamp:>12.6}  data   {topic}  {type_name}{decoded}  {} bytes",
+                data.len()
+            )?;
+        },
+        Record::Signal {
+            activity, cycle, kind, ..
+        } => writeln!(
+            out,
+            "{timestamp:>12.6}  signal A{activity}  cycle {cycle}  {}",
+            kind.name()
+        )?,
+        Record::State {
+            activity, cycle, state, ..
+        } => writeln!(
+            out,
+            "{timestamp:>12.6}  state  A{activity}  cycle {cycle}  {} bytes",
+            state.len()
+        )?,
+        Record::Dropped { topic, count, .. } => writeln!(out, "{timestamp:>12.6}  drop   {topic}  {count} samples")?,
+        Record::Trigger { event, .. } => writeln!(out, "{timestamp:>12.6}  trig   {event}")?,
+        Record::SpeedChange { old, new, .. } => {
+            writeln!(out, "{timestamp:>12.6}  speed  {} -> {}", speed_name(old), speed_name(new))?
+        },
+        Record::Panic {
+            activity,
+            message,
+            backtrace,
+            ..
+        } => {
+            writeln!(out, "{timestamp:>12.6}  panic  A{activity}  {message}")?;
+            if !backtrace.is_empty() {
+                writeln!(out, "{:>12}  in     {backtrace}", "")?;
+            }
+        },
+        Record::Overrun {
+            chain,
+            cycle,
+            duration,
+            outcome,
+            ..
+        } => writeln!(
+            out,
+            "{timestamp:>12.6}  overr  C{chain}  cycle {cycle}  {:.3}ms  {outcome}",
+            duration.as_secs_f64() * 1000.0
+        )?,
+        Record::ClockMapping {
+            clock,
+            external,
+            drift_ppb,
+            ..
+        } => writeln!(
+            out,
+            "{timestamp:>12.6}  clock  {clock}  at {:.6}s  drift {drift_ppb} ppb",
+            external.as_secs_f64()
+        )?,
+    }
+    Ok(())
+}
+
+/// Statistics of a recorded topic
+#[derive(Default)]
+struct TopicInfo {
+    samples: u64,
+    bytes: u64,
+}
+
+/// Print a summary of the recording
+fn info(paths: &[PathBuf], key: Option<&RecordingKey>, registry: &TypeRegistry) -> Result<(), Error> {
+    let mut cycles = 0u64;
+    let mut signals = 0u64;
+    let mut states = 0u64;
+    let mut triggers = 0u64;
+    let mut speed_changes = 0u64;
+    let mut panics = 0u64;
+    let mut overruns = 0u64;
+    let mut clocks: BTreeSet<String> = BTreeSet::new();
+    let mut dropped: BTreeMap<String, u64> = BTreeMap::new();
+    let mut first: Option<Duration> = None;
+    let mut last: Option<Duration> = None;
+    let mut topics: BTreeMap<(String, String), TopicInfo> = BTreeMap::new();
+    let header = paths.first().map(read_header).transpose()?.flatten();
+    let mut timeline = header.as_ref().map(Timeline::new);
+
+    for record in read_records(paths, None, key, registry)? {
+        let record = record?;
+        let timestamp = record.timestamp();
+        first.get_or_insert(timestamp);
+        last = Some(timestamp);
+        if let Some(timeline) = timeline.as_mut() {
+            timeline.push(&record);
+        }
+        match record {
+            Record::Cycle { .. } => cycles += 1,
+            Record::Signal { .. } => signals += 1,
+            Record::State { .. } => states += 1,
+            Record::Trigger { .. } => triggers += 1,
+            Record::SpeedChange { .. } => speed_changes += 1,
+            Record::Panic { .. } => panics += 1,
+            Record::Overrun { .. } => overruns += 1,
+            Record::ClockMapping { clock, .. } => {
+                clocks.insert(clock);
+            },
+            Record::Dropped { topic, count, .. } => *dropped.entry(topic).or_default() += count,
+            Record::Data {
+                topic, type_name, data, ..
+            } => {
+                let info = topics.entry((topic, type_name)).or_default();
+                info.samples += 1;
+                info.bytes += data.len() as u64;
+            },
+        }
+    }
+
+    if let Some(header) = header {
+        println!("format:   {} (feo {})", header.format_version, header.feo_version);
+        println!("start:    {:.6}s since epoch", header.start_time.as_secs_f64());
+        if let Some(speed) = header.speed {
+            println!("speed:    {speed}");
+        }
+        println!("codec:    {:?}", header.compression);
+        println!("encoding: {:?}", header.encoding);
+        if let Some(key_id) = header.key_id.as_ref() {
+            println!("key:      {key_id}");
+        }
+        if let Some(cycle) = header.join_cycle {
+            println!("joined:   after cycle {cycle}");
+        }
+    }
+    println!("cycles:   {cycles}");
+    if signals > 0 {
+        println!("signals:  {signals}");
+    }
+    if states > 0 {
+        println!("states:   {states}");
+    }
+    if triggers > 0 {
+        println!("triggers: {triggers}");
+    }
+    if speed_changes > 0 {
+        println!("changes:  {speed_changes} speed changes");
+    }
+    if panics > 0 {
+        println!("panics:   {panics}");
+    }
+    if overruns > 0 {
+        println!("overruns: {overruns}");
+    }
+    if !clocks.is_empty() {
+        let clocks: Vec<&str> = clocks.iter().map(String::as_str).collect();
+        println!("clocks:   {}", clocks.join(", "));
+    }
+    match (first, last) {
+        (Some(first), Some(last)) => println!(
+            "time:     {:.6}s - {:.6}s ({:.3}s)",
+            first.as_secs_f64(),
+            last.as_secs_f64(),
+            (last - first).as_secs_f64()
+        ),
+        _ => println!("time:     -"),
+    }
+    if let (Some(timeline), Some(first), Some(last)) = (timeline, first, last) {
+        let real = timeline.real_elapsed(last).saturating_sub(timeline.real_elapsed(first));
+        println!("real:     {:.3}s", real.as_secs_f64());
+    }
+    println!("topics:");
+    for ((topic, type_name), info) in topics.iter() {
+        let decoded = if registry.contains(type_name) {
+            ""
+        } else {
+            " (unregistered)"
+        };
+        println!(
+            "  {topic}  {type_name}{decoded}  {} samples, {} bytes",
+            info.samples, info.bytes
+        );
+    }
+    if !dropped.is_empty() {
+        println!("dropped:");
+        for (topic, count) in dropped.iter() {
+            println!("  {topic}  {count} samples");
+        }
+    }
+    Ok(())
+}
+
+/// Name of a speed factor noted in a recording
+fn speed_name(factor: Option<i32>) -> String {
+    factor.map_or_else(|| "1".to_string(), |factor| factor.to_string())
+}
@@ -0,0 +1,361 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Conversion of records to JSON and CSV
+
+use crate::registry::TypeRegistry;
+use anyhow::Error;
+use core::fmt::Write as _;
+use feo::recording::codec::Encoding;
+use feo::recording::Record;
+use serde_json::{json, Value};
+use std::io::Write;
+
+/// Header line of the CSV export
+pub const CSV_HEADER: &str = "timestamp_ns,kind,topic,type,size,data";
+
+/// Convert a record to a JSON object
+///
+/// Samples of registered types are decoded, other samples are given as hex string in `raw`.
+pub fn record_to_json(record: &Record, registry: &TypeRegistry, encoding: Encoding) -> Value {
+    match record {
+        Record::Cycle { timestamp } => json!({
+            "kind": "cycle",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+        }),
+        Record::Data {
+            timestamp,
+            topic,
+            type_name,
+            data,
+        } => {
+            let mut object = json!({
+                "kind": "data",
+                "timestamp_ns": timestamp.as_nanos() as u64,
+                "topic": topic,
+                "type": type_name,
+                "size": data.len(),
+            });
+            match registry.decode(type_name, encoding, data) {
+                Some(Ok(value)) => object["data"] = value,
+                Some(Err(e)) => {
+                    object["error"] = Value::String(format!("failed to decode sample: {e}"));
+                    object["raw"] = Value::String(hex(data));
+                },
+                None => object["raw"] = Value::String(hex(data)),
+            }
+            object
+        },
+        Record::Signal {
+            timestamp,
+            activity,
+            cycle,
+            kind,
+        } => json!({
+            "kind": "signal",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "activity": activity,
+            "cycle": cycle,
+            "signal": kind.name(),
+        }),
+        Record::State {
+            timestamp,
+            activity,
+            cycle,
+            state,
+        } => json!({
+            "kind": "state",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "activity": activity,
+            "cycle": cycle,
+            "size": state.len(),
+            "raw": hex(state),
+        }),
+        Record::Dropped {
+            timestamp,
+            topic,
+            count,
+        } => json!({
+            "kind": "dropped",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "topic": topic,
+            "count": count,
+        }),
+        Record::Trigger { timestamp, event } => json!({
+            "kind": "trigger",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "event": event,
+        }),
+        Record::SpeedChange {
+            timestamp,
+            old,
+            new,
+            real_time,
+        } => json!({
+            "kind": "speed_change",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "old": old,
+            "new": new,
+            "real_time_ns": real_time.as_nanos() as u64,
+        }),
+        Record::Panic {
+            timestamp,
+            activity,
+            message,
+            backtrace,
+        } => json!({
+            "kind": "panic",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "activity": activity,
+            "message": message,
+            "backtrace": backtrace,
+        }),
+        Record::Overrun {
+            timestamp,
+            chain,
+            cycle,
+            duration,
+            outcome,
+        } => json!({
+            "kind": "overrun",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "chain": chain,
+            "cycle": cycle,
+            "duration_ns": duration.as_nanos() as u64,
+            "outcome": outcome,
+        }),
+        Record::ClockMapping {
+            timestamp,
+            clock,
+            external,
+            drift_ppb,
+        } => json!({
+            "kind": "clock_mapping",
+            "timestamp_ns": timestamp.as_nanos() as u64,
+            "clock": clock,
+            "external_ns": external.as_nanos() as u64,
+            "drift_ppb": drift_ppb,
+        }),
+    }
+}
+
+/// Write the given records as a JSON array, one record per line
+///
+/// Samples are decoded according to the given encoding of the recording.
+pub fn write_json<W: Write>(
+    writer: &mut W,
+    records: impl Iterator<Item = Result<Record, Error>>,
+    registry: &TypeRegistry,
+    encoding: Encoding,
+) -> Result<(), Error> {
+    writeln!(writer, "[")?;
+    for (index, record) in records.enumerate() {
+        if index > 0 {
+            writeln!(writer, ",")?;
+        }
+        serde_json::to_writer(&mut *writer, &record_to_json(&record?, registry, encoding))?;
+    }
+    writeln!(writer, "\n]")?;
+    Ok(())
+}
+
+/// Write the given records as CSV with a header line
+///
+/// The `data` column contains the decoded sample as compact JSON,
+/// or the hex string of the raw bytes for unregistered types. Samples are decoded according to
+/// the given encoding of the recording.
+/// For signal records, it contains the activity, cycle and kind of the signal as compact JSON.
+/// For dropped records, it contains the number of samples dropped by the recorder.
+/// For trigger records, it contains the event the trigger was fired for.
+pub fn write_csv<W: Write>(
+    writer: &mut W,
+    records: impl Iterator<Item = Result<Record, Error>>,
+    registry: &TypeRegistry,
+    encoding: Encoding,
+) -> Result<(), Error> {
+    writeln!(writer, "{CSV_HEADER}")?;
+    for record in records {
+        match record? {
+            Record::Cycle { timestamp } => writeln!(writer, "{},cycle,,,,", timestamp.as_nanos())?,
+            Record::Data {
+                timestamp,
+                topic,
+                type_name,
+                data,
+            } => {
+                let sample = match registry.decode(&type_name, encoding, &data) {
+                    Some(Ok(value)) => value.to_string(),
+                    Some(Err(_)) | None => hex(&data),
+                };
+                writeln!(
+                    writer,
+                    "{},data,{},{},{},{}",
+                    timestamp.as_nanos(),
+                    csv_field(&topic),
+                    csv_field(&type_name),
+                    data.len(),
+                    csv_field(&sample)
+                )?;
+            },
+            Record::Signal {
+                timestamp,
+                activity,
+                cycle,
+                kind,
+            } => {
+                let signal = json!({ "activity": activity, "cycle": cycle, "signal": kind.name() });
+                writeln!(
+                    writer,
+                    "{},signal,,,,{}",
+                    timestamp.as_nanos(),
+                    csv_field(&signal.to_string())
+                )?;
+            },
+            Record::State {
+                timestamp,
+                activity,
+                cycle,
+                state,
+            } => {
+                let checkpoint = json!({ "activity": activity, "cycle": cycle, "raw": hex(&state) });
+                writeln!(
+                    writer,
+                    "{},state,,,{},{}",
+                    timestamp.as_nanos(),
+                    state.len(),
+                    csv_field(&checkpoint.to_string())
+                )?;
+            },
+            Record::Dropped {
+                timestamp,
+                topic,
+                count,
+            } => writeln!(
+                writer,
+                "{},dropped,{},,,{}",
+                timestamp.as_nanos(),
+                csv_field(&topic),
+                count
+            )?,
+            Record::Trigger { timestamp, event } => {
+                writeln!(writer, "{},trigger,,,,{}", timestamp.as_nanos(), csv_field(&event))?
+            },
+            Record::SpeedChange {
+                timestamp,
+                old,
+                new,
+                real_time,
+            } => {
+                let change = json!({ "old": old, "new": new, "real_time_ns": real_time.as_nanos() as u64 });
+                writeln!(
+                    writer,
+                    "{},speed_change,,,,{}",
+                    timestamp.as_nanos(),
+                    csv_field(&change.to_string())
+                )?;
+            },
+            Record::Panic {
+                timestamp,
+                activity,
+                message,
+                backtrace,
+            } => {
+                let panic = json!({ "activity": activity, "message": message, "backtrace": backtrace });
+                writeln!(writer, "{},panic,,,,{}", timestamp.as_nanos(), csv_field(&panic.to_string()))?;
+            },
+            Record::Overrun {
+                timestamp,
+                chain,
+                cycle,
+                duration,
+                outcome,
+            } => {
+                let overrun = json!({
+                    "chain": chain,
+                    "cycle": cycle,
+                    "duration_ns": duration.as_nanos() as u64,
+                    "outcome": outcome,
+                });
+                writeln!(writer, "{},overrun,,,,{}", timestamp.as_nanos(), csv_field(&overrun.to_string()))?;
+            },
+            Record::ClockMapping {
+                timestamp,
+                clock,
+                external,
+                drift_ppb,
+            } => {
+                let mapping = json!({ "external_ns": external.as_nanos() as u64, "drift_ppb": drift_ppb });
+                writeln!(
+                    writer,
+                    "{},clock_mapping,{},,,{}",
+                    timestamp.as_nanos(),
+                    csv_field(&clock),
+                    csv_field(&mapping.to_string())
+                )?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if necessary
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Format bytes as lowercase hex string
+fn hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+#[test]
+fn records_are_exported_as_csv() {
+    use feo_time::Duration;
+
+    let registry = TypeRegistry::new().with_type::<(u8, String)>();
+    let records = [
+        Record::Cycle {
+            timestamp: Duration::from_millis(1),
+        },
+        Record::Data {
+            timestamp: Duration::from_millis(1),
+            topic: "feo/com/vehicle/scene".to_string(),
+            type_name: core::any::type_name::<(u8, String)>().to_string(),
+            data: postcard::to_allocvec(&(7u8, "a,b".to_string())).unwrap(),
+        },
+        Record::Data {
+            timestamp: Duration::from_millis(2),
+            topic: "feo/com/vehicle/camera".to_string(),
+            type_name: "CameraImage".to_string(),
+            data: vec![0xab, 0x01],
+        },
+    ];
+
+    let mut csv = Vec::new();
+    write_csv(&mut csv, records.into_iter().map(Ok), &registry, Encoding::Postcard).unwrap();
+    let expected = format!(
+        "{CSV_HEADER}\n\
+         1000000,cycle,,,,\n\
+         1000000,data,feo/com/vehicle/scene,\"(u8, alloc::string::String)\",5,\"[7,\"\"a,b\"\"]\"\n\
+         2000000,data,feo/com/vehicle/camera,CameraImage,2,ab01\n"
+    );
+    assert_eq!(String::from_utf8(csv).unwrap(), expected);
+}
@@ -0,0 +1,33 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Inspection and conversion of FEO recordings
+//!
+//! The `feo_rec` binary lists the records of a recording, prints a summary of its contents
+//! and exports it to JSON or CSV. Recorded samples are postcard-serialized by default and can only be decoded
+//! with knowledge of their types. Applications therefore build their own variant of the tool
+//! by registering their message types in a [registry::TypeRegistry] and calling [cli::run]:
+//!
+//! ```ignore
+//! fn main() -> Result<(), anyhow::Error> {
+//!     let registry = TypeRegistry::new().with_type::<CameraImage>().with_type::<Scene>();
+//!     feo_rec::cli::run(&registry)
+//! }
+//! ```
+//!
+//! Samples of unregistered types are exported as hex strings of their raw bytes.
+
+pub mod cli;
+pub mod export;
+pub mod registry;
+pub mod select;
@@ -0,0 +1,21 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Inspect and convert recordings of FEO applications without application specific message types
+
+use anyhow::Error;
+use feo_rec::registry::TypeRegistry;
+
+fn main() -> Result<(), Error> {
+    feo_rec::cli::run(&TypeRegistry::new())
+}
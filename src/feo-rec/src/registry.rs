@@ -0,0 +1,85 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Registry of message types for decoding recorded samples
+
+use anyhow::{anyhow, Error};
+use core::any::type_name;
+use feo::recording::codec::Encoding;
+use feo::recording::header::schema_hash;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Function decoding a sample of the given encoding into a JSON value
+pub type Decoder = Box<dyn Fn(Encoding, &[u8]) -> Result<Value, Error> + Send + Sync>;
+
+/// Registry of decoders per recorded type name
+#[derive(Default)]
+pub struct TypeRegistry {
+    decoders: HashMap<String, Decoder>,
+    /// Schema hashes of the types registered with [TypeRegistry::with_type]
+    schemas: HashMap<String, u64>,
+}
+
+impl TypeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the given message type under its type name, as written by the recorder
+    pub fn with_type<T>(mut self) -> Self
+    where
+        T: DeserializeOwned + Serialize + 'static,
+    {
+        self.schemas.insert(type_name::<T>().to_string(), schema_hash::<T>());
+        self.decoders.insert(
+            type_name::<T>().to_string(),
+            Box::new(|encoding, data| {
+                let sample: T = encoding.decode(data).map_err(|e| anyhow!("{e}"))?;
+                Ok(serde_json::to_value(&sample)?)
+            }),
+        );
+        self
+    }
+
+    /// Register a custom decoder for the given type name
+    ///
+    /// The decoder gets the samples as recorded, regardless of the encoding of the recording.
+    pub fn with_decoder(
+        mut self,
+        type_name: &str,
+        decoder: impl Fn(&[u8]) -> Result<Value, Error> + Send + Sync + 'static,
+    ) -> Self {
+        self.decoders
+            .insert(type_name.to_string(), Box::new(move |_, data| decoder(data)));
+        self
+    }
+
+    /// Check whether a decoder for the given type name is registered
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.decoders.contains_key(type_name)
+    }
+
+    /// Get the schema hash of the given type name, if registered with [TypeRegistry::with_type]
+    pub fn schema_hash(&self, type_name: &str) -> Option<u64> {
+        self.schemas.get(type_name).copied()
+    }
+
+    /// Decode a sample of the given type and encoding, returning `None` if the type is not registered
+    pub fn decode(&self, type_name: &str, encoding: Encoding, data: &[u8]) -> Option<Result<Value, Error>> {
+        self.decoders.get(type_name).map(|decoder| decoder(encoding, data))
+    }
+}
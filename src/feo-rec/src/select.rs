@@ -0,0 +1,65 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Selection of records by type, topic and time range
+
+use feo::recording::Record;
+use feo_time::Duration;
+
+/// Selection of records
+///
+/// Data records are selected if they match any of the given types and topics, if any,
+/// and lie within the time range. Cycle and signal records are selected by their time only and are
+/// omitted if types or topics are given.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Type names to select. All types are selected if empty.
+    pub types: Vec<String>,
+    /// Topics to select. All topics are selected if empty.
+    pub topics: Vec<String>,
+    /// Earliest timestamp to select
+    pub from: Option<Duration>,
+    /// Latest timestamp to select
+    pub to: Option<Duration>,
+}
+
+impl Selection {
+    /// Check whether the given record is selected
+    pub fn matches(&self, record: &Record) -> bool {
+        let timestamp = record.timestamp();
+        if self.from.is_some_and(|from| timestamp < from) || self.to.is_some_and(|to| timestamp > to) {
+            return false;
+        }
+        match record {
+            Record::Cycle { .. }
+            | Record::Signal { .. }
+            | Record::State { .. }
+            | Record::Trigger { .. }
+            | Record::Panic { .. }
+            | Record::Overrun { .. } => {
+                self.types.is_empty() && self.topics.is_empty()
+            },
+            // Kept in any selection to map the selected timestamps to real time
+            Record::SpeedChange { .. } => true,
+            // Kept in any selection to map the sensor timestamps of the selected samples
+            Record::ClockMapping { .. } => true,
+            Record::Data { topic, type_name, .. } => {
+                (self.types.is_empty() || self.types.contains(type_name))
+                    && (self.topics.is_empty() || self.topics.contains(topic))
+            },
+            Record::Dropped { topic, .. } => {
+                self.types.is_empty() && (self.topics.is_empty() || self.topics.contains(topic))
+            },
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Reading FEO recordings from tools
+//!
+//! A [RecordIter] iterates over the records of a recording read from any `Read + Seek` input,
+//! typically a file:
+//!
+//! ```ignore
+//! let mut records = RecordIter::open(Path::new("/tmp/rec/rec_0001.bin"))?;
+//! for entry in &mut records {
+//!     let entry = entry?;
+//!     if entry.type_name() == Some(type_name::<CameraImage>()) {
+//!         let image: CameraImage = entry.decode()?;
+//!     }
+//! }
+//! if let Some(offset) = records.truncated() {
+//!     eprintln!("recording ends with an incomplete record at byte {offset}");
+//! }
+//! ```
+//!
+//! Samples are kept as recorded until accessed: [RecordEntry::raw_bytes] borrows the bytes as written,
+//! [RecordEntry::decode] deserializes them with the encoding of the recording, so tools only pay for
+//! decoding the samples they look at.
+//!
+//! A recording of an agent which was killed while writing ends with an incomplete record. Instead of
+//! failing on it, the iterator ends before the incomplete record and notes its byte offset, see
+//! [RecordIter::truncated]. Errors in the middle of a recording are still returned and end the iteration.
+//!
+//! Recordings still being written are followed with [RecordIter::follow_entry], which keeps an
+//! incomplete tail to be completed by later calls instead of ending before it.
+
+use feo::error::{Error, RecordingError};
+use feo::recording::codec::Encoding;
+use feo::recording::encryption::RecordingKey;
+use feo::recording::header::RecordingHeader;
+use feo::recording::index::RecordingIndex;
+use feo::recording::reader::RecordReader;
+use feo::recording::Record;
+use feo_time::Duration;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Record of a recording with lazy access to its payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordEntry {
+    record: Record,
+    encoding: Encoding,
+}
+
+impl RecordEntry {
+    /// Get the record
+    pub fn record(&self) -> &Record {
+        &self.record
+    }
+
+    /// Take the record
+    pub fn into_record(self) -> Record {
+        self.record
+    }
+
+    /// Get the timestamp of the record
+    pub fn timestamp(&self) -> Duration {
+        self.record.timestamp()
+    }
+
+    /// Get the topic of a data record
+    pub fn topic(&self) -> Option<&str> {
+        match &self.record {
+            Record::Data { topic, .. } => Some(topic),
+            _ => None,
+        }
+    }
+
+    /// Get the type name of the sample of a data record
+    pub fn type_name(&self) -> Option<&str> {
+        match &self.record {
+            Record::Data { type_name, .. } => Some(type_name),
+            _ => None,
+        }
+    }
+
+    /// Get the payload as recorded: the sample of a data record or the state of a state record
+    pub fn raw_bytes(&self) -> Option<&[u8]> {
+        match &self.record {
+            Record::Data { data, .. } => Some(data),
+            Record::State { state, .. } => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Deserialize the sample of a data record with the encoding of the recording
+    ///
+    /// The type is not checked against the recorded type name, see [RecordEntry::type_name].
+    pub fn decode<T: DeserializeOwned + 'static>(&self) -> Result<T, Error> {
+        match &self.record {
+            Record::Data { data, .. } => self.encoding.decode(data),
+            _ => Err(Error::Recording(RecordingError::Failed("record without sample"))),
+        }
+    }
+}
+
+/// Iterator over the records of a recording
+///
+/// Stops at the first error, or before an incomplete record at the end of the input.
+pub struct RecordIter<R: Read + Seek> {
+    /// Reader of the records
+    records: RecordReader<BufReader<R>>,
+    /// Index of the recording, if any
+    index: RecordingIndex,
+    /// Encoding of the samples if not noted in a header
+    encoding: Option<Encoding>,
+    /// Length of the input in bytes
+    len: u64,
+    /// Byte offset of an incomplete record at the end of the input, if found
+    truncated: Option<u64>,
+    /// Whether the iteration ended with an error or at an incomplete record
+    done: bool,
+}
+
+impl RecordIter<File> {
+    /// Open a recording file with its sidecar index, if any
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let index = RecordingIndex::load(path).unwrap_or_default();
+        Ok(Self::new(File::open(path)?)?.with_index(index))
+    }
+}
+
+impl<R: Read + Seek> RecordIter<R> {
+    /// Create an iterator over the recording read from the given input, starting at its beginning
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        Ok(Self {
+            records: RecordReader::new(BufReader::new(reader)),
+            index: RecordingIndex::default(),
+            encoding: None,
+            len,
+            truncated: None,
+            done: false,
+        })
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [RecordReader::with_key]
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.records = self.records.with_key(key);
+        self
+    }
+
+    /// Seek with the given index of the recording, see [RecordIter::seek_to_time]
+    pub fn with_index(mut self, index: RecordingIndex) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Decode the samples with the given encoding instead of the one noted in the header
+    ///
+    /// Needed for the files following the first one of a rotated recording, which have no header.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Read the header at the start of the recording, see [RecordReader::read_header]
+    pub fn read_header(&mut self) -> Result<Option<&RecordingHeader>, Error> {
+        self.records.read_header()
+    }
+
+    /// Get the header of the recording, if read so far
+    pub fn header(&self) -> Option<&RecordingHeader> {
+        self.records.header()
+    }
+
+    /// Get the encoding of the samples
+    pub fn encoding(&self) -> Encoding {
+        self.encoding.unwrap_or_else(|| self.records.encoding())
+    }
+
+    /// Continue reading at the last cycle starting at or before the given time
+    ///
+    /// Without an index, or if the time is before the first indexed cycle, reading continues
+    /// at the start of the recording.
+    pub fn seek_to_time(&mut self, timestamp: Duration) -> Result<(), Error> {
+        let offset = self.index.find_time(timestamp).map(|entry| entry.offset).unwrap_or_default();
+        self.records.seek(offset)?;
+        Ok(())
+    }
+
+    /// Get the byte offset of the incomplete record the recording ends with, if reached
+    pub fn truncated(&self) -> Option<u64> {
+        self.truncated
+    }
+
+    /// Read the next record, returning `None` at the end of the recording
+    pub fn read_entry(&mut self) -> Result<Option<RecordEntry>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        match self.records.read_record() {
+            Ok(Some(record)) => Ok(Some(RecordEntry {
+                record,
+                encoding: self.encoding(),
+            })),
+            Ok(None) => Ok(None),
+            // A record failing to read up to the end of the input is the incomplete tail of the recording
+            Err(_) if self.records.offset() >= self.len => {
+                self.done = true;
+                self.truncated = Some(self.records.record_offset());
+                Ok(None)
+            },
+            Err(e) => {
+                self.done = true;
+                Err(e)
+            },
+        }
+    }
+
+    /// Read the next record of a recording which is still being written, see [RecordReader::follow_record]
+    ///
+    /// Returns `None` while the next record is not written completely, to be called again later.
+    pub fn follow_entry(&mut self) -> Result<Option<RecordEntry>, Error> {
+        let record = self.records.follow_record()?;
+        Ok(record.map(|record| RecordEntry {
+            record,
+            encoding: self.encoding(),
+        }))
+    }
+}
+
+impl<R: Read + Seek> Iterator for RecordIter<R> {
+    type Item = Result<RecordEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry().transpose()
+    }
+}
+
+#[test]
+fn records_are_read_up_to_truncated_tail() {
+    use feo::recording::recorder::write_record;
+    use std::io::Cursor;
+
+    let records = [
+        Record::Cycle {
+            timestamp: Duration::from_millis(10),
+        },
+        Record::Data {
+            timestamp: Duration::from_millis(10),
+            topic: "feo/com/vehicle/speed".to_string(),
+            type_name: "u32".to_string(),
+            data: Encoding::Postcard.encode(&42u32).unwrap(),
+        },
+    ];
+    let mut recording = Vec::new();
+    for record in records.iter() {
+        write_record(&mut recording, record).unwrap();
+    }
+
+    let entries: Vec<RecordEntry> = RecordIter::new(Cursor::new(&recording))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].raw_bytes().is_none());
+    assert_eq!(entries[1].topic(), Some("feo/com/vehicle/speed"));
+    assert_eq!(entries[1].decode::<u32>().unwrap(), 42);
+
+    // A record cut off while writing ends the iteration without an error
+    let cycle_len = recording.iter().position(|byte| *byte == 0).unwrap() as u64 + 1;
+    let mut iter = RecordIter::new(Cursor::new(&recording[..recording.len() - 2])).unwrap();
+    assert_eq!(iter.next().unwrap().unwrap().record(), &records[0]);
+    assert!(iter.next().is_none());
+    assert_eq!(iter.truncated(), Some(cycle_len));
+}
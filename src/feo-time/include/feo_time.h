@@ -30,6 +30,26 @@ struct feo_timespec {
 // Set the clock speed factor
 void feo_clock_speed(int factor);
 
+// Switch to the virtual clock, which only advances on feo_clock_advance
+void feo_clock_virtual(void);
+
+// Advance the virtual clock by the given number of nanoseconds
+void feo_clock_advance(uint64_t nanos);
+
+// Pause the clock, e.g. while a debugger is attached
+void feo_clock_pause(void);
+
+// Resume the paused clock
+void feo_clock_resume(void);
+
+// OS clocks backing the FEO clock
+#define FEO_CLOCK_MONOTONIC 0
+#define FEO_CLOCK_MONOTONIC_RAW 1
+#define FEO_CLOCK_BOOTTIME 2
+
+// Select the OS clock backing the FEO clock at startup, returns 0 on success and -1 if not supported
+int feo_clock_source(uint32_t source);
+
 // Get the current realtime
 void feo_clock_gettime(struct feo_timespec* ts);
 
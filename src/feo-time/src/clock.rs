@@ -0,0 +1,191 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! OS clock backing the FEO clock
+//!
+//! By default, [Instant](crate::Instant) is backed by `CLOCK_MONOTONIC` like [std::time::Instant].
+//! For timing analysis, [set_clock_source] selects another OS clock at startup:
+//! `CLOCK_MONOTONIC_RAW` is not subject to the frequency adjustments of NTP and `CLOCK_BOOTTIME`
+//! keeps counting while the system is suspended. Both are available on Linux only.
+//!
+//! Readings of the selected clock are mapped onto the instants of the standard library by their offset
+//! at the time of selection, so speed factors, pauses and the virtual clock apply unchanged.
+//! [calibrate] measures the resolution and the cost of reading the selected clock.
+
+use crate::Duration;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time;
+
+/// Number of clock readings taken by [calibrate]
+const CALIBRATION_SAMPLES: u32 = 10_000;
+
+/// OS clock backing [Instant](crate::Instant)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockSource {
+    /// `CLOCK_MONOTONIC`, the clock of [std::time::Instant]
+    #[default]
+    Monotonic,
+    /// `CLOCK_MONOTONIC_RAW`, not adjusted by NTP
+    MonotonicRaw,
+    /// `CLOCK_BOOTTIME`, including the time the system is suspended
+    Boottime,
+}
+
+impl ClockSource {
+    /// Get the id of the OS clock, if available on this platform
+    fn clock_id(self) -> Option<libc::clockid_t> {
+        match self {
+            ClockSource::Monotonic => Some(libc::CLOCK_MONOTONIC),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ClockSource::MonotonicRaw => Some(libc::CLOCK_MONOTONIC_RAW),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ClockSource::Boottime => Some(libc::CLOCK_BOOTTIME),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+/// Error selecting a clock source which is not available on this platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedClock(pub ClockSource);
+
+impl fmt::Display for UnsupportedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "clock source {:?} is not supported on this platform", self.0)
+    }
+}
+
+impl core::error::Error for UnsupportedClock {}
+
+/// Selected clock source with its reading at the time of selection
+struct Base {
+    source: ClockSource,
+    clock_id: libc::clockid_t,
+    instant: time::Instant,
+    reading: core::time::Duration,
+}
+
+/// Clock source set via `set_clock_source`, `CLOCK_MONOTONIC` of the standard library if not set
+static BASE: OnceLock<Base> = OnceLock::new();
+
+/// Select the OS clock backing [Instant](crate::Instant).
+///
+/// Call at startup before taking any timestamps, since instants taken before are not
+/// comparable to those of another clock in the long run.
+///
+/// # Panics
+///
+/// Panics if the clock source has already been set.
+pub fn set_clock_source(source: ClockSource) -> Result<(), UnsupportedClock> {
+    let (clock_id, reading) = source
+        .clock_id()
+        .and_then(|clock_id| Some((clock_id, read(clock_id)?)))
+        .ok_or(UnsupportedClock(source))?;
+    let base = Base {
+        source,
+        clock_id,
+        instant: time::Instant::now(),
+        reading,
+    };
+    assert!(BASE.set(base).is_ok(), "clock source can be set only once");
+    Ok(())
+}
+
+/// Get the OS clock backing [Instant](crate::Instant)
+pub fn clock_source() -> ClockSource {
+    BASE.get().map_or(ClockSource::Monotonic, |base| base.source)
+}
+
+/// Current instant of the selected OS clock, mapped onto the instants of the standard library
+pub(crate) fn os_instant() -> time::Instant {
+    match BASE.get() {
+        None => time::Instant::now(),
+        Some(base) => {
+            let reading = read(base.clock_id).expect("clock error");
+            base.instant + reading.saturating_sub(base.reading)
+        },
+    }
+}
+
+/// Read the given OS clock
+fn read(clock_id: libc::clockid_t) -> Option<core::time::Duration> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // Safety: the timespec is valid for the call
+    (unsafe { libc::clock_gettime(clock_id, &mut ts) } == 0)
+        .then(|| core::time::Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Get the resolution of the given OS clock as reported by the OS
+fn resolution(clock_id: libc::clockid_t) -> Option<core::time::Duration> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // Safety: the timespec is valid for the call
+    (unsafe { libc::clock_getres(clock_id, &mut ts) } == 0)
+        .then(|| core::time::Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Result of the calibration of the selected clock, see [calibrate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calibration {
+    /// Calibrated clock source
+    pub source: ClockSource,
+    /// Resolution reported by the OS
+    pub resolution: Duration,
+    /// Smallest difference observed between two consecutive differing readings,
+    /// zero if the clock did not advance during the calibration
+    pub measured_resolution: Duration,
+    /// Average duration of reading the clock
+    pub read_cost: Duration,
+}
+
+impl fmt::Display for Calibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "clock {:?}: resolution {:?} (measured {:?}), read cost {:?}",
+            self.source, self.resolution.0, self.measured_resolution.0, self.read_cost.0
+        )
+    }
+}
+
+/// Measure the resolution and the cost of reading the selected OS clock
+///
+/// Takes a few thousand readings of the clock, which usually takes well below a millisecond.
+/// Not affected by speed factors, pauses or the virtual clock.
+pub fn calibrate() -> Calibration {
+    let source = clock_source();
+    let resolution = source.clock_id().and_then(resolution).unwrap_or_default();
+
+    let start = os_instant();
+    let mut previous = start;
+    let mut measured_resolution: Option<core::time::Duration> = None;
+    for _ in 0..CALIBRATION_SAMPLES {
+        let now = os_instant();
+        if now > previous {
+            let step = now - previous;
+            measured_resolution = Some(measured_resolution.map_or(step, |min| min.min(step)));
+        }
+        previous = now;
+    }
+    let read_cost = (previous - start) / CALIBRATION_SAMPLES;
+
+    Calibration {
+        source,
+        resolution: Duration(resolution),
+        measured_resolution: Duration(measured_resolution.unwrap_or_default()),
+        read_cost: Duration(read_cost),
+    }
+}
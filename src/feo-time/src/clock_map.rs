@@ -0,0 +1,241 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Translation of timestamps of external clocks
+//!
+//! Sensors stamp their data with the time of their own clock, e.g. the hardware timestamps of a camera
+//! or GNSS time. A [ClockMap] estimates the offset and drift of such an external clock against [Instant]
+//! from observations, i.e. pairs of an external timestamp and the instant taken at the same time, such as
+//! the hardware timestamp of a frame and the instant of its reception:
+//!
+//! ```ignore
+//! let mut camera_clock = ClockMap::new("camera/front");
+//!
+//! // In the step of the activity receiving the frames
+//! camera_clock.observe(frame.hw_timestamp, Instant::now());
+//! let captured_at = camera_clock.to_instant(frame.hw_timestamp).unwrap();
+//! ```
+//!
+//! The estimate is a least squares fit over the latest observations, see [ClockMap::with_window],
+//! so that jitter of single observations, e.g. by transmission delays, averages out.
+//!
+//! Each observation publishes the updated estimate of its clock as [ClockMapping], see [clock_mappings].
+//! Recorders note the mappings in their recordings, so that recorded sensor timestamps can be
+//! translated offline as well.
+
+use crate::{Duration, Instant, SystemTime, START, UNIX_EPOCH};
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+/// Default number of observations an estimate is fitted to
+const DEFAULT_WINDOW: usize = 32;
+
+/// Latest mapping of each clock in the order of the first observation of the clock
+static CLOCK_MAPPINGS: Mutex<Vec<ClockMapping>> = Mutex::new(Vec::new());
+
+/// Estimate of the relation of an external clock to [Instant]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    /// Timestamp of the external clock at the reference point
+    pub external: Duration,
+    /// Instant at the reference point
+    pub local: Instant,
+    /// Elapsed time of [Instant] per elapsed time of the external clock, 1.0 without drift
+    pub rate: f64,
+}
+
+impl ClockEstimate {
+    /// Get the drift of the external clock against [Instant] in parts per billion
+    ///
+    /// A positive drift means that the external clock runs slow.
+    pub fn drift_ppb(&self) -> i64 {
+        ((self.rate - 1.0) * 1e9).round() as i64
+    }
+}
+
+/// Estimated mapping of an external clock to the FEO clock, as noted in recordings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockMapping {
+    /// Name of the external clock
+    pub clock: String,
+    /// Number of observations of the clock so far
+    pub sequence: u64,
+    /// Timestamp of the external clock at the reference point
+    pub external: Duration,
+    /// FEO time at the reference point as duration since the UNIX epoch
+    pub at: Duration,
+    /// Drift of the external clock against the FEO clock in parts per billion
+    pub drift_ppb: i64,
+}
+
+/// Offset and drift estimation of an external clock against [Instant]
+#[derive(Debug, Clone)]
+pub struct ClockMap {
+    /// Name of the clock, as published in its mappings
+    name: String,
+    /// Maximum number of observations kept
+    window: usize,
+    /// Latest observations, the oldest first
+    observations: VecDeque<(Duration, Instant)>,
+    /// Number of observations so far
+    sequence: u64,
+    /// Estimate fitted to the observations, if any
+    estimate: Option<ClockEstimate>,
+}
+
+impl ClockMap {
+    /// Create a map of the external clock with the given name without observations
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            window: DEFAULT_WINDOW,
+            observations: VecDeque::new(),
+            sequence: 0,
+            estimate: None,
+        }
+    }
+
+    /// Fit the estimate to the given number of latest observations, at least two, 32 by default
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(2);
+        self
+    }
+
+    /// Get the name of the clock
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Note that the external clock read `external` at the given instant and update the estimate
+    ///
+    /// The updated estimate is published as [ClockMapping] of the clock, see [clock_mappings].
+    pub fn observe(&mut self, external: Duration, local: Instant) {
+        if self.observations.len() == self.window {
+            self.observations.pop_front();
+        }
+        self.observations.push_back((external, local));
+        self.sequence += 1;
+        let estimate = fit(&self.observations);
+        self.estimate = Some(estimate);
+        publish(ClockMapping {
+            clock: self.name.clone(),
+            sequence: self.sequence,
+            external: estimate.external,
+            at: since_epoch(estimate.local),
+            drift_ppb: estimate.drift_ppb(),
+        });
+    }
+
+    /// Get the current estimate, if the clock has been observed
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.estimate
+    }
+
+    /// Convert a timestamp of the external clock into an instant
+    ///
+    /// Returns `None` if the clock has not been observed yet or the instant is not representable.
+    pub fn to_instant(&self, external: Duration) -> Option<Instant> {
+        let estimate = self.estimate?;
+        let elapsed = signed_nanos(external, estimate.external) * estimate.rate;
+        offset(estimate.local, elapsed)
+    }
+
+    /// Convert an instant into a timestamp of the external clock
+    ///
+    /// Returns `None` if the clock has not been observed yet or the instant precedes the epoch of the
+    /// external clock.
+    pub fn to_external(&self, local: Instant) -> Option<Duration> {
+        let estimate = self.estimate?;
+        let elapsed = signed_nanos_between(local, estimate.local) / estimate.rate;
+        let nanos = estimate.external.as_nanos() as f64 + elapsed;
+        (nanos >= 0.0).then(|| Duration::from_nanos(nanos.round() as u64))
+    }
+}
+
+/// Get the latest mapping of each clock observed in this process
+///
+/// Mappings are listed in the order of the first observation of their clocks.
+pub fn clock_mappings() -> Vec<ClockMapping> {
+    CLOCK_MAPPINGS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the mapping of its clock with the given mapping
+fn publish(mapping: ClockMapping) {
+    let mut mappings = CLOCK_MAPPINGS.lock().unwrap_or_else(|e| e.into_inner());
+    match mappings.iter_mut().find(|latest| latest.clock == mapping.clock) {
+        Some(latest) => *latest = mapping,
+        None => mappings.push(mapping),
+    }
+}
+
+/// Fit an estimate to the given observations by least squares, relative to the latest observation
+fn fit(observations: &VecDeque<(Duration, Instant)>) -> ClockEstimate {
+    let (external, local) = *observations.back().expect("no observations");
+    let deltas: Vec<(f64, f64)> = observations
+        .iter()
+        .map(|(x, y)| (signed_nanos(*x, external), signed_nanos_between(*y, local)))
+        .collect();
+    let count = deltas.len() as f64;
+    let mean_x = deltas.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = deltas.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let var_x: f64 = deltas.iter().map(|(x, _)| (x - mean_x) * (x - mean_x)).sum();
+    let cov: f64 = deltas.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let rate = if var_x > 0.0 { cov / var_x } else { 1.0 };
+
+    // Fitted instant at the external timestamp of the latest observation
+    let intercept = mean_y - rate * mean_x;
+    ClockEstimate {
+        external,
+        local: offset(local, intercept).unwrap_or(local),
+        rate,
+    }
+}
+
+/// Get the signed difference `a - b` of two timestamps in nanoseconds
+fn signed_nanos(a: Duration, b: Duration) -> f64 {
+    if a >= b {
+        (a - b).as_nanos() as f64
+    } else {
+        -((b - a).as_nanos() as f64)
+    }
+}
+
+/// Get the signed difference `a - b` of two instants in nanoseconds
+fn signed_nanos_between(a: Instant, b: Instant) -> f64 {
+    match a.checked_duration_since(b) {
+        Some(elapsed) => elapsed.as_nanos() as f64,
+        None => -(b.duration_since(a).as_nanos() as f64),
+    }
+}
+
+/// Shift an instant by the given signed number of nanoseconds
+fn offset(instant: Instant, nanos: f64) -> Option<Instant> {
+    let shift = Duration::from_nanos(nanos.abs().round() as u64);
+    if nanos >= 0.0 {
+        instant.checked_add(shift)
+    } else {
+        instant.checked_sub(shift)
+    }
+}
+
+/// Get the FEO time of an instant as duration since the UNIX epoch
+fn since_epoch(instant: Instant) -> Duration {
+    let (start_time, start_instant): (SystemTime, Instant) = *START;
+    let time = match instant.checked_duration_since(start_instant) {
+        Some(elapsed) => start_time + elapsed,
+        None => start_time - start_instant.duration_since(instant),
+    };
+    time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
@@ -26,6 +26,47 @@ extern "C" fn feo_clock_speed(factor: i32) {
     crate::speed(factor);
 }
 
+/// Switch to the virtual clock.
+#[unsafe(no_mangle)]
+extern "C" fn feo_clock_virtual() {
+    crate::virtual_clock();
+}
+
+/// Advance the virtual clock by the given number of nanoseconds.
+#[unsafe(no_mangle)]
+extern "C" fn feo_clock_advance(nanos: u64) {
+    crate::advance(crate::Duration::from_nanos(nanos));
+}
+
+/// Pause the clock.
+#[unsafe(no_mangle)]
+extern "C" fn feo_clock_pause() {
+    crate::pause();
+}
+
+/// Resume the paused clock.
+#[unsafe(no_mangle)]
+extern "C" fn feo_clock_resume() {
+    crate::resume();
+}
+
+/// Select the OS clock backing the FEO clock: 0 monotonic, 1 monotonic raw, 2 boottime.
+///
+/// Returns 0 on success and -1 if the clock is unknown or not supported on this platform.
+#[unsafe(no_mangle)]
+extern "C" fn feo_clock_source(source: u32) -> i32 {
+    let source = match source {
+        0 => crate::ClockSource::Monotonic,
+        1 => crate::ClockSource::MonotonicRaw,
+        2 => crate::ClockSource::Boottime,
+        _ => return -1,
+    };
+    match crate::set_clock_source(source) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Get the current time.
 #[unsafe(no_mangle)]
 extern "C" fn feo_clock_gettime(ts: *mut FeoTimeSpec) {
@@ -55,17 +55,27 @@
 extern crate alloc;
 extern crate std;
 
+mod clock;
+mod clock_map;
 mod ffi;
+mod sync;
 #[cfg(test)]
 mod tests;
+mod timer;
 
+pub use clock::{calibrate, clock_source, set_clock_source, Calibration, ClockSource, UnsupportedClock};
+pub use clock_map::{clock_mappings, ClockEstimate, ClockMap, ClockMapping};
+pub use sync::{park_timeout, park_until, wait_timeout, wait_timeout_while, WaitTimeoutResult};
+pub use timer::{Deadline, Timer};
+
+use alloc::vec::Vec;
 use core::error::Error;
 use core::fmt;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
-use core::sync::atomic::{AtomicI32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use score_log::fmt::ScoreDebug;
 use serde::{Deserialize, Serialize};
-use std::sync::{LazyLock, Once};
+use std::sync::{LazyLock, Mutex, Once, RwLock};
 use std::time;
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, PartialOrd, Hash, Ord, Eq, Serialize, Deserialize)]
@@ -226,12 +236,69 @@ pub struct Instant(time::Instant);
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SystemTime(time::SystemTime);
 
-/// Initialization synchronization. Ensures that `speed` can be set only once.
+/// Initialization synchronization. Ensures that `speed` or `virtual_clock` can be set only once.
 static INIT: Once = Once::new();
 /// Time scaling start timestamps
 static START: LazyLock<(SystemTime, Instant)> = LazyLock::new(|| (SystemTime::now(), Instant::now()));
-/// Factor on systemtime and instant if set via `speed`
+/// Factor on systemtime and instant if set via `speed` or `change_speed`
 static FACTOR: AtomicI32 = AtomicI32::new(0);
+/// Whether the virtual clock is enabled via `virtual_clock`
+static VIRTUAL: AtomicBool = AtomicBool::new(false);
+/// Virtual time elapsed since the start timestamps in nanoseconds, advanced via `advance`
+static VIRTUAL_ELAPSED: AtomicU64 = AtomicU64::new(0);
+/// Whether the clock is paused via `pause`. Avoids locking `PAUSE` while running.
+static PAUSED: AtomicBool = AtomicBool::new(false);
+/// Total duration of all finished pauses in nanoseconds, updated via `resume`
+static PAUSED_TOTAL: AtomicU64 = AtomicU64::new(0);
+/// Pause bookkeeping of `pause` and `resume`
+static PAUSE: Mutex<Pause> = Mutex::new(Pause { paused_at: None });
+
+/// Whether the speed has ever been changed via `change_speed`. Avoids locking `REBASE` otherwise.
+static SPEED_CHANGED: AtomicBool = AtomicBool::new(false);
+/// Scaling of the elapsed time since the last speed change via `change_speed`
+static REBASE: RwLock<Rebase> = RwLock::new(Rebase {
+    factor: 0,
+    os: Duration::ZERO,
+    feo: Duration::ZERO,
+});
+/// Speed changes via `change_speed` in the order of their occurrence
+static SPEED_CHANGES: Mutex<Vec<SpeedChange>> = Mutex::new(Vec::new());
+
+/// Pause state of the clock
+struct Pause {
+    /// OS timestamps at which the clock has been paused, if currently paused
+    paused_at: Option<(time::SystemTime, time::Instant)>,
+}
+
+/// Scaling of the clock since the last speed change
+struct Rebase {
+    /// Factor set by the last speed change
+    factor: i32,
+    /// OS time elapsed since the start timestamps at the last speed change
+    os: Duration,
+    /// FEO time elapsed since the start timestamps at the last speed change
+    feo: Duration,
+}
+
+impl Rebase {
+    /// FEO time elapsed since the start timestamps at the given elapsed OS time
+    fn feo_elapsed(&self, os_elapsed: Duration) -> Duration {
+        self.feo + scale_elapsed(os_elapsed.saturating_sub(self.os), self.factor)
+    }
+}
+
+/// Change of the speed factor during a run via [change_speed]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpeedChange {
+    /// Factor before the change, if set
+    pub old: Option<i32>,
+    /// Factor after the change, if set
+    pub new: Option<i32>,
+    /// FEO time of the change as duration since the UNIX epoch
+    pub at: Duration,
+    /// OS wall clock time of the change as duration since the UNIX epoch
+    pub real: Duration,
+}
 
 /// A trait for scaling durations based on the factor set by `speed`.
 pub trait Scaled {
@@ -243,6 +310,8 @@ pub trait Scaled {
 }
 
 /// Set a speedup or down factor on the system time.
+///
+/// To change the factor during a run, use [change_speed].
 pub fn speed(factor: i32) {
     // Ensure that speed can be set only once
     assert!(!INIT.is_completed(), "speed can be set only once");
@@ -261,6 +330,172 @@ pub fn get_speed() -> Option<i32> {
     (factor != 0).then_some(factor)
 }
 
+/// Change the speed factor on the system time during a run.
+///
+/// Unlike [speed], the factor can be changed any number of times. The FEO time continues
+/// from the time of the change, i.e. only the time elapsed afterwards is scaled with the new factor.
+/// A factor of 0 switches back to the unscaled speed. Each change is noted, see [speed_changes].
+///
+/// # Panics
+///
+/// Panics if the virtual clock is enabled via [virtual_clock].
+pub fn change_speed(factor: i32) {
+    assert!(!is_virtual(), "the speed of the virtual clock cannot be changed");
+    INIT.call_once(|| ());
+
+    // Initialize the start timestamps
+    let start = *START;
+
+    let (system_time, instant) = os_now();
+    let os_elapsed = Instant(instant).duration_since(start.1);
+    let mut rebase = REBASE.write().expect("speed state poisoned");
+    let feo_elapsed = if SPEED_CHANGED.load(Ordering::Relaxed) {
+        rebase.feo_elapsed(os_elapsed)
+    } else {
+        scale_elapsed(os_elapsed, FACTOR.load(Ordering::Relaxed))
+    };
+    *rebase = Rebase {
+        factor,
+        os: os_elapsed,
+        feo: feo_elapsed,
+    };
+    // Readers block on the rebase until the factor is updated
+    SPEED_CHANGED.store(true, Ordering::Release);
+    let old = FACTOR.swap(factor, Ordering::Relaxed);
+    drop(rebase);
+
+    let change = SpeedChange {
+        old: (old != 0).then_some(old),
+        new: (factor != 0).then_some(factor),
+        at: (start.0 + feo_elapsed).duration_since(UNIX_EPOCH).unwrap_or_default(),
+        real: SystemTime(system_time).duration_since(UNIX_EPOCH).unwrap_or_default(),
+    };
+    SPEED_CHANGES.lock().expect("speed state poisoned").push(change);
+}
+
+/// Get the speed changes via [change_speed], skipping the given number of earliest changes
+///
+/// Pass the number of changes seen so far to only get the changes since then.
+pub fn speed_changes(skip: usize) -> Vec<SpeedChange> {
+    let changes = SPEED_CHANGES.lock().expect("speed state poisoned");
+    changes.get(skip..).unwrap_or_default().to_vec()
+}
+
+/// Switch to a virtual clock which only advances on calls to [advance].
+///
+/// [Instant::now] and [SystemTime::now] are frozen at the time of this call
+/// until the clock is explicitly advanced. This allows deterministic tests
+/// of cycle scheduling and timeouts without real sleeping.
+/// The virtual clock cannot be combined with [speed] and durations are not [Scaled].
+pub fn virtual_clock() {
+    // Ensure that the clock mode can be set only once
+    assert!(!INIT.is_completed(), "clock mode can be set only once");
+    INIT.call_once(|| ());
+
+    // Initialize the start timestamps
+    let _ = &*START;
+
+    VIRTUAL.store(true, Ordering::Release);
+}
+
+/// Check whether the virtual clock is enabled
+pub fn is_virtual() -> bool {
+    VIRTUAL.load(Ordering::Acquire)
+}
+
+/// Advance the virtual clock by the given duration.
+///
+/// # Panics
+///
+/// Panics if the virtual clock is not enabled via [virtual_clock].
+pub fn advance(duration: Duration) {
+    assert!(is_virtual(), "advance requires the virtual clock");
+    let nanos = u64::try_from(duration.as_nanos()).expect("duration too large to advance the clock");
+    VIRTUAL_ELAPSED.fetch_add(nanos, Ordering::AcqRel);
+}
+
+/// Pause the clock, e.g. while a debugger is attached or a breakpoint is hit.
+///
+/// [Instant::now] and [SystemTime::now] are frozen until [resume] is called, so that
+/// timeouts do not expire while stepping through code. After resuming, the clock continues
+/// from the time of the pause, i.e. the whole pause is cut out of the FEO time.
+/// Pausing a paused clock has no effect. The virtual clock is not affected.
+pub fn pause() {
+    let mut pause = PAUSE.lock().expect("pause state poisoned");
+    if pause.paused_at.is_none() {
+        pause.paused_at = Some((time::SystemTime::now(), clock::os_instant()));
+        PAUSED.store(true, Ordering::Release);
+    }
+}
+
+/// Resume the clock paused via [pause]. Resuming a running clock has no effect.
+pub fn resume() {
+    let mut pause = PAUSE.lock().expect("pause state poisoned");
+    if let Some((_, instant)) = pause.paused_at.take() {
+        let paused = clock::os_instant().saturating_duration_since(instant);
+        let paused = u64::try_from(paused.as_nanos()).unwrap_or(u64::MAX);
+        let paused_total = PAUSED_TOTAL.load(Ordering::Relaxed).saturating_add(paused);
+        // The total is updated before the clock runs again, see `os_now`
+        PAUSED_TOTAL.store(paused_total, Ordering::Release);
+        PAUSED.store(false, Ordering::Release);
+    }
+}
+
+/// Check whether the clock is paused
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Acquire)
+}
+
+/// Current OS time with all pauses cut out, frozen while paused
+fn os_now() -> (time::SystemTime, time::Instant) {
+    loop {
+        if PAUSED.load(Ordering::Acquire) {
+            let pause = PAUSE.lock().expect("pause state poisoned");
+            if let Some((system_time, instant)) = pause.paused_at {
+                let paused_total = core::time::Duration::from_nanos(PAUSED_TOTAL.load(Ordering::Acquire));
+                return (system_time - paused_total, instant - paused_total);
+            }
+            // Resumed meanwhile
+            continue;
+        }
+
+        let paused_total = PAUSED_TOTAL.load(Ordering::Acquire);
+        let (system_time, instant) = (time::SystemTime::now(), clock::os_instant());
+        // Retry if the clock has been paused meanwhile, the time would otherwise run past the pause
+        if !PAUSED.load(Ordering::Acquire) && PAUSED_TOTAL.load(Ordering::Acquire) == paused_total {
+            let paused_total = core::time::Duration::from_nanos(paused_total);
+            return (system_time - paused_total, instant - paused_total);
+        }
+    }
+}
+
+/// FEO time elapsed since the start timestamps at the given elapsed OS time
+fn feo_elapsed(os_elapsed: Duration) -> Duration {
+    if SPEED_CHANGED.load(Ordering::Acquire) {
+        REBASE.read().expect("speed state poisoned").feo_elapsed(os_elapsed)
+    } else {
+        scale_elapsed(os_elapsed, FACTOR.load(Ordering::Relaxed))
+    }
+}
+
+/// Scale the given elapsed OS time with the given factor
+fn scale_elapsed(elapsed: Duration, factor: i32) -> Duration {
+    if factor.is_positive() {
+        // Factor is greater than 0, so we speed up time by multiplying the elapsed time by factor
+        elapsed * factor.unsigned_abs()
+    } else if factor.is_negative() {
+        // Factor is less than 0, so we slow down time by dividing the elapsed time by factor
+        elapsed / factor.unsigned_abs()
+    } else {
+        elapsed
+    }
+}
+
+/// Virtual time elapsed since the start timestamps, if the virtual clock is enabled
+fn virtual_elapsed() -> Option<Duration> {
+    is_virtual().then(|| Duration::from_nanos(VIRTUAL_ELAPSED.load(Ordering::Acquire)))
+}
+
 impl Instant {
     /// Returns an instant corresponding to "now".
     ///
@@ -273,30 +508,19 @@ impl Instant {
     /// ```
     #[must_use]
     pub fn now() -> Instant {
+        // The virtual clock only advances explicitly
+        if let Some(elapsed) = virtual_elapsed() {
+            return START.1.checked_add(elapsed).expect("clock error");
+        }
+
         // Get current system time unscaled from the os
-        let now = Instant(time::Instant::now());
+        let now = Instant(os_now().1);
 
-        // Load the factor set by `SystemTime::speed`
-        let factor = FACTOR.load(Ordering::Relaxed);
-        if factor != 0 {
-            // Load start timestamp
+        // Scale the elapsed time since the start timestamp, if a factor has ever been set
+        if FACTOR.load(Ordering::Relaxed) != 0 || SPEED_CHANGED.load(Ordering::Acquire) {
             let start = START.1;
-
-            // Calculate elapsed time since start timestamp
-            let duration_since_start = now.duration_since(start);
-
-            // Calculate new "feo" time
-            if factor.is_positive() {
-                // Factor is greater than 0, so we speed up time by multiplying
-                // the elapsed time by factor add add to the start time
-                let elapsed = duration_since_start * factor.unsigned_abs();
-                start.checked_add(elapsed).expect("clock error")
-            } else {
-                // Factor is less than 0, so we slow down time by dividing
-                // the elapsed time by factor add add to the start time
-                let elapsed = duration_since_start / factor.unsigned_abs();
-                start.checked_add(elapsed).expect("clock error")
-            }
+            let elapsed = feo_elapsed(now.duration_since(start));
+            start.checked_add(elapsed).expect("clock error")
         } else {
             now
         }
@@ -480,31 +704,19 @@ impl SystemTime {
     pub const UNIX_EPOCH: SystemTime = UNIX_EPOCH;
 
     pub fn now() -> SystemTime {
-        // Get current system time unscaled from the os
-        let now = SystemTime(time::SystemTime::now());
+        // The virtual clock only advances explicitly
+        if let Some(elapsed) = virtual_elapsed() {
+            return START.0.checked_add(elapsed).expect("clock error");
+        }
 
-        // Load the factor set by `SystemTime::speed`
-        let factor = FACTOR.load(Ordering::Relaxed);
+        // Get current system time unscaled from the os
+        let now = SystemTime(os_now().0);
 
-        if factor != 0 {
-            // Load start timestamp
+        // Scale the elapsed time since the start timestamp, if a factor has ever been set
+        if FACTOR.load(Ordering::Relaxed) != 0 || SPEED_CHANGED.load(Ordering::Acquire) {
             let start = START.0;
-
-            // Calculate elapsed "real" time since start timestamp
-            let duration_since_start = now.duration_since(start).unwrap();
-
-            // Calculate new "feo" time
-            if factor.is_positive() {
-                // Factor is greater than 0, so we speed up time by multiplying
-                // the elapsed time by factor add add to the start time
-                let elapsed = duration_since_start * factor.unsigned_abs();
-                start.checked_add(elapsed).expect("clock error")
-            } else {
-                // Factor is less than 0, so we slow down time by dividing
-                // the elapsed time by factor add add to the start time
-                let elapsed = duration_since_start / factor.unsigned_abs();
-                start.checked_add(elapsed).expect("clock error")
-            }
+            let elapsed = feo_elapsed(now.duration_since(start).unwrap());
+            start.checked_add(elapsed).expect("clock error")
         } else {
             now
         }
@@ -0,0 +1,132 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Thread parking and condition variables on the FEO clock
+//!
+//! [park_timeout] and [wait_timeout] are drop-in replacements of [thread::park_timeout] and
+//! [Condvar::wait_timeout] taking FEO durations. Like a [Deadline], they wait in slices of OS time,
+//! see [wait_slice], so that changes of the speed factor, pauses of the clock and advances of the
+//! virtual clock during a wait take effect within a slice instead of after the timeout scaled at the
+//! start of the wait.
+//!
+//! As with the standard library, waits may return early without being unparked or notified.
+//! Callers waiting for a condition should loop with a fixed [Deadline], see [park_until], or use
+//! [wait_timeout_while].
+
+use crate::timer::wait_slice;
+use crate::{Deadline, Duration};
+use std::sync::{Condvar, LockResult, MutexGuard, PoisonError};
+use std::thread;
+use std::time;
+
+/// Whether a wait on a condition variable returned because its timeout elapsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Whether the wait timed out on the FEO clock
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// Block the calling thread until it is unparked or the given FEO duration has elapsed
+pub fn park_timeout(timeout: Duration) {
+    park_until(Deadline::after(timeout));
+}
+
+/// Block the calling thread until it is unparked or the given deadline has expired
+pub fn park_until(deadline: Deadline) {
+    loop {
+        let remaining = deadline.remaining();
+        if remaining.is_zero() {
+            return;
+        }
+        let slice = wait_slice(remaining);
+        let parked_at = time::Instant::now();
+        thread::park_timeout(slice);
+        // Returning before the end of the slice means the thread has been unparked
+        if parked_at.elapsed() < slice {
+            return;
+        }
+    }
+}
+
+/// Wait on the given condition variable until notified or the given FEO duration has elapsed
+///
+/// Poisoning of the mutex is reported like by [Condvar::wait_timeout].
+pub fn wait_timeout<'a, T>(
+    condvar: &Condvar,
+    mut guard: MutexGuard<'a, T>,
+    timeout: Duration,
+) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+    let deadline = Deadline::after(timeout);
+    let mut poisoned = false;
+    loop {
+        let remaining = deadline.remaining();
+        if remaining.is_zero() {
+            return lock_result((guard, WaitTimeoutResult(true)), poisoned);
+        }
+        let (next, result) = condvar
+            .wait_timeout(guard, wait_slice(remaining))
+            .unwrap_or_else(|e| {
+                poisoned = true;
+                e.into_inner()
+            });
+        guard = next;
+        if !result.timed_out() {
+            return lock_result((guard, WaitTimeoutResult(false)), poisoned);
+        }
+    }
+}
+
+/// Wait on the given condition variable while the given condition holds, at most for the given FEO duration
+///
+/// Returns whether the wait timed out with the condition still holding.
+pub fn wait_timeout_while<'a, T, F>(
+    condvar: &Condvar,
+    mut guard: MutexGuard<'a, T>,
+    timeout: Duration,
+    mut condition: F,
+) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    let deadline = Deadline::after(timeout);
+    let mut poisoned = false;
+    loop {
+        if !condition(&mut *guard) {
+            return lock_result((guard, WaitTimeoutResult(false)), poisoned);
+        }
+        let remaining = deadline.remaining();
+        if remaining.is_zero() {
+            return lock_result((guard, WaitTimeoutResult(true)), poisoned);
+        }
+        let (next, _) = condvar
+            .wait_timeout(guard, wait_slice(remaining))
+            .unwrap_or_else(|e| {
+                poisoned = true;
+                e.into_inner()
+            });
+        guard = next;
+    }
+}
+
+/// Wrap the given result of a wait, reporting poisoning if the mutex has been poisoned during the wait
+fn lock_result<G>(result: G, poisoned: bool) -> LockResult<G> {
+    if poisoned {
+        Err(PoisonError::new(result))
+    } else {
+        Ok(result)
+    }
+}
@@ -264,3 +264,212 @@ fn set_speed_twice() {
     assert_eq!(crate::get_speed(), Some(2));
     crate::speed(3);
 }
+
+#[test]
+fn pause_freezes_clock() {
+    let pause = Duration::from_millis(50);
+    crate::pause();
+    assert!(crate::is_paused());
+    let (instant, system_time) = (Instant::now(), SystemTime::now());
+    std::thread::sleep(pause.into());
+    assert_eq!(Instant::now(), instant);
+    assert_eq!(SystemTime::now(), system_time);
+
+    // The clock continues from the time of the pause
+    crate::resume();
+    assert!(!crate::is_paused());
+    assert!(Instant::now() >= instant);
+    assert!(Instant::now().duration_since(instant) < pause);
+}
+
+#[test]
+fn deadline_waits_on_feo_clock() {
+    let deadline = crate::Deadline::after(Duration::from_millis(20));
+    assert!(!deadline.poll());
+    assert!(deadline.remaining() <= Duration::from_millis(20));
+    deadline.wait();
+    assert!(deadline.poll());
+    assert!(Instant::now() >= deadline.instant());
+    assert!(deadline.remaining().is_zero());
+}
+
+#[test]
+fn timer_expires_periodically() {
+    let period = Duration::from_millis(10);
+    let mut timer = crate::Timer::new(period);
+    let first = timer.next();
+    assert!(!timer.poll());
+    timer.wait();
+    assert!(Instant::now() >= first.instant());
+    assert!(timer.next().instant() >= first.instant() + period);
+
+    // Missed expirations are skipped
+    crate::Deadline::after(period * 3).wait();
+    assert!(timer.poll());
+    assert!(!timer.poll());
+}
+
+#[test]
+fn park_returns_when_unparked() {
+    let start = std::time::Instant::now();
+    let parked = std::thread::spawn(|| crate::park_timeout(Duration::from_secs(10)));
+    parked.thread().unpark();
+    parked.join().unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    let deadline = crate::Deadline::after(Duration::from_secs(10));
+    let parked = std::thread::spawn(move || crate::park_until(deadline));
+    parked.thread().unpark();
+    parked.join().unwrap();
+    assert!(!deadline.poll());
+}
+
+#[test]
+fn park_times_out_on_feo_clock() {
+    let start = Instant::now();
+    crate::park_timeout(Duration::from_millis(20));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    let deadline = crate::Deadline::after(Duration::from_millis(20));
+    crate::park_until(deadline);
+    assert!(deadline.poll());
+}
+
+#[test]
+fn wait_returns_when_notified() {
+    use std::sync::{Arc, Condvar, Mutex};
+
+    let shared = Arc::new((Mutex::new(false), Condvar::new()));
+    let notifier = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            *shared.0.lock().unwrap() = true;
+            shared.1.notify_all();
+        })
+    };
+
+    let (mutex, condvar) = &*shared;
+    let mut ready = mutex.lock().unwrap();
+    while !*ready {
+        let (guard, result) = crate::wait_timeout(condvar, ready, Duration::from_secs(10)).unwrap();
+        assert!(!result.timed_out());
+        ready = guard;
+    }
+    drop(ready);
+
+    let ready = mutex.lock().unwrap();
+    let (ready, result) = crate::wait_timeout_while(condvar, ready, Duration::from_secs(10), |ready| !*ready).unwrap();
+    assert!(!result.timed_out());
+    assert!(*ready);
+    notifier.join().unwrap();
+}
+
+#[test]
+fn wait_times_out_on_feo_clock() {
+    use std::sync::{Condvar, Mutex};
+
+    let (mutex, condvar) = (Mutex::new(()), Condvar::new());
+    let start = Instant::now();
+    let (guard, result) = crate::wait_timeout(&condvar, mutex.lock().unwrap(), Duration::from_millis(20)).unwrap();
+    assert!(result.timed_out());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    drop(guard);
+
+    let start = Instant::now();
+    let (_guard, result) =
+        crate::wait_timeout_while(&condvar, mutex.lock().unwrap(), Duration::from_millis(20), |_| true).unwrap();
+    assert!(result.timed_out());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test]
+fn wait_reports_poisoned_mutex() {
+    use std::sync::{Arc, Condvar, Mutex};
+
+    let mutex = Arc::new(Mutex::new(0));
+    let poisoner = Arc::clone(&mutex);
+    let _ = std::thread::spawn(move || {
+        let _guard = poisoner.lock().unwrap();
+        panic!("poisoning the mutex");
+    })
+    .join();
+    assert!(mutex.is_poisoned());
+
+    let condvar = Condvar::new();
+    let guard = mutex.lock().unwrap_or_else(|e| e.into_inner());
+    let (guard, result) = crate::wait_timeout(&condvar, guard, Duration::from_millis(10))
+        .unwrap_err()
+        .into_inner();
+    assert!(result.timed_out());
+    drop(guard);
+
+    // The guard is handed back along with the error, the wait itself completes
+    let guard = mutex.lock().unwrap_or_else(|e| e.into_inner());
+    let (guard, result) = crate::wait_timeout_while(&condvar, guard, Duration::from_millis(10), |_| true)
+        .unwrap_err()
+        .into_inner();
+    assert!(result.timed_out());
+    assert_eq!(*guard, 0);
+}
+
+#[test]
+fn clock_map_estimates_offset_and_drift() {
+    // External clock starting at 5s and running slow by 100ppm against the FEO clock
+    let mut clock_map = crate::ClockMap::new("tests/camera").with_window(16);
+    assert!(clock_map.estimate().is_none());
+    assert!(clock_map.to_instant(Duration::from_secs(5)).is_none());
+
+    let base = Instant::now();
+    let external_start = Duration::from_secs(5);
+    for step in 0..40 {
+        let external = external_start + Duration::from_micros(10_000 * step);
+        let local = base + Duration::from_nanos(10_001_000 * step);
+        clock_map.observe(external, local);
+    }
+
+    let estimate = clock_map.estimate().unwrap();
+    assert!((estimate.rate - 1.0001).abs() < 1e-9, "rate {}", estimate.rate);
+    assert!((estimate.drift_ppb() - 100_000).abs() <= 1, "drift {}", estimate.drift_ppb());
+    assert_eq!(estimate.external, external_start + Duration::from_millis(390));
+
+    // Conversions round trip, up to rounding to nanoseconds
+    let distance = |a: Duration, b: Duration| a.saturating_sub(b) + b.saturating_sub(a);
+    let between = |a: Instant, b: Instant| a.saturating_duration_since(b) + b.saturating_duration_since(a);
+    let external = external_start + Duration::from_secs(1);
+    let local = clock_map.to_instant(external).unwrap();
+    assert!(between(local, base + Duration::from_micros(1_000_100)) <= Duration::from_micros(1));
+    assert!(distance(clock_map.to_external(local).unwrap(), external) <= Duration::from_micros(1));
+    assert!(distance(clock_map.to_external(base).unwrap(), external_start) <= Duration::from_micros(1));
+
+    let mapping = crate::clock_mappings()
+        .into_iter()
+        .find(|mapping| mapping.clock == "tests/camera")
+        .unwrap();
+    assert_eq!(mapping.sequence, 40);
+    assert_eq!(mapping.external, estimate.external);
+    assert_eq!(mapping.drift_ppb, estimate.drift_ppb());
+
+    // Only the observations in the window are fitted, so the estimate follows a change of the drift
+    let (external, local) = (external_start + Duration::from_millis(400), base + Duration::from_micros(400_040));
+    for step in 0..16 {
+        clock_map.observe(external + Duration::from_millis(10 * step), local + Duration::from_millis(10 * step));
+    }
+    assert!((clock_map.estimate().unwrap().rate - 1.0).abs() < 1e-9);
+    assert_eq!(clock_map.estimate().unwrap().drift_ppb(), 0);
+}
+
+#[test]
+fn clock_source_is_calibrated() {
+    // Changing the clock source is tested in a test binary of its own, see tests/clock_source.rs
+    let calibration = crate::calibrate();
+    assert_eq!(calibration.source, crate::clock_source());
+    assert!(calibration.resolution > Duration::ZERO);
+    assert!(calibration.measured_resolution >= calibration.resolution);
+    assert!(calibration.read_cost < Duration::from_millis(1));
+    let description = alloc::format!("{calibration}");
+    assert!(description.starts_with(&alloc::format!("clock {:?}: resolution", calibration.source)));
+
+    let a = Instant::now();
+    let b = Instant::now();
+    assert!(b >= a);
+}
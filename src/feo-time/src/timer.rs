@@ -0,0 +1,154 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Waiting on the FEO clock
+//!
+//! A [Deadline] is a point in FEO time and a [Timer] expires periodically. Both wait on the FEO clock,
+//! i.e. they respect the factor set with [speed](crate::speed), pauses of the clock and the
+//! [virtual clock](crate::virtual_clock), so that callers never scale sleep durations themselves.
+//! For parking threads and waiting on condition variables on the FEO clock,
+//! see [park_timeout](crate::park_timeout) and [wait_timeout](crate::wait_timeout).
+
+use crate::{is_virtual, Duration, Instant, Scaled};
+use std::thread;
+
+/// Interval at which waits on the virtual clock check whether the clock has been advanced
+const VIRTUAL_POLL_INTERVAL: core::time::Duration = core::time::Duration::from_millis(1);
+
+/// Maximum OS time of a single wait, bounding the delay until a speed change or pause takes effect
+const MAX_WAIT_SLICE: core::time::Duration = core::time::Duration::from_millis(10);
+
+/// OS time to wait at most before checking the FEO clock again, given the remaining FEO time of a wait
+///
+/// The virtual clock does not advance while waiting, and a paused clock continues with the remaining
+/// time after waking up. The speed factor may change during the wait, so that the remaining time is
+/// scaled anew after each slice.
+pub(crate) fn wait_slice(remaining: Duration) -> core::time::Duration {
+    if is_virtual() {
+        VIRTUAL_POLL_INTERVAL
+    } else {
+        core::time::Duration::from(remaining.scaled()).min(MAX_WAIT_SLICE)
+    }
+}
+
+/// A point in FEO time to wait for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Create a deadline expiring after the given FEO duration from now
+    pub fn after(duration: Duration) -> Self {
+        Self(Instant::now() + duration)
+    }
+
+    /// Create a deadline expiring at the given instant
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// Get the instant at which the deadline expires
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Get the FEO time left until the deadline expires, zero if expired
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Check whether the deadline has expired
+    pub fn poll(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Block the calling thread until the deadline has expired
+    ///
+    /// With the virtual clock, the thread waits until the clock has been advanced past the deadline.
+    pub fn wait(&self) {
+        loop {
+            let remaining = self.remaining();
+            if remaining.is_zero() {
+                return;
+            }
+            thread::sleep(wait_slice(remaining));
+        }
+    }
+}
+
+/// Timer expiring periodically on the FEO clock
+///
+/// Expirations are planned relative to the previous expiration, so that the period does not drift
+/// with the time taken by the caller. Expirations missed by more than a period are skipped.
+#[derive(Debug, Clone)]
+pub struct Timer {
+    /// Period of the timer
+    period: Duration,
+    /// Next expiration
+    next: Deadline,
+}
+
+impl Timer {
+    /// Create a timer first expiring one period from now
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is zero.
+    pub fn new(period: Duration) -> Self {
+        assert!(!period.is_zero(), "timer period must not be zero");
+        Self {
+            period,
+            next: Deadline::after(period),
+        }
+    }
+
+    /// Get the period of the timer
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Get the next expiration of the timer
+    pub fn next(&self) -> Deadline {
+        self.next
+    }
+
+    /// Restart the timer, next expiring one period from now
+    pub fn reset(&mut self) {
+        self.next = Deadline::after(self.period);
+    }
+
+    /// Check whether the timer has expired, planning the next expiration if so
+    pub fn poll(&mut self) -> bool {
+        if !self.next.poll() {
+            return false;
+        }
+        self.plan_next();
+        true
+    }
+
+    /// Block the calling thread until the timer expires and plan the next expiration
+    pub fn wait(&mut self) {
+        self.next.wait();
+        self.plan_next();
+    }
+
+    /// Plan the next expiration after an expiration
+    fn plan_next(&mut self) {
+        let now = Instant::now();
+        let mut next = self.next.instant() + self.period;
+        if next <= now {
+            // Skip missed expirations
+            next = now + self.period;
+        }
+        self.next = Deadline::at(next);
+    }
+}
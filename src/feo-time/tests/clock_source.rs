@@ -0,0 +1,38 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Tests of changing the clock source
+//!
+//! The clock source is used by the whole process, so these tests are run in a test binary of their own,
+//! separate from the unit tests of the library.
+
+#[test]
+#[cfg(target_os = "linux")]
+fn changed_clock_source_is_calibrated() {
+    use feo_time::{ClockSource, Duration, Instant};
+
+    feo_time::set_clock_source(ClockSource::MonotonicRaw).unwrap();
+    assert_eq!(feo_time::clock_source(), ClockSource::MonotonicRaw);
+
+    let calibration = feo_time::calibrate();
+    assert_eq!(calibration.source, ClockSource::MonotonicRaw);
+    assert!(calibration.resolution > Duration::ZERO);
+    assert!(calibration.measured_resolution >= calibration.resolution);
+    assert!(calibration.read_cost < Duration::from_millis(1));
+    let description = format!("{calibration}");
+    assert!(description.starts_with(&format!("clock {:?}: resolution", calibration.source)));
+
+    let a = Instant::now();
+    let b = Instant::now();
+    assert!(b >= a);
+}
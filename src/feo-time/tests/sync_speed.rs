@@ -0,0 +1,52 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Tests of parking and condition variables under a speed factor
+//!
+//! The speed factor can be set only once per process, so these tests are run in a test binary of
+//! their own, separate from the unit tests of the library.
+
+use feo_time::{Deadline, Duration, Instant};
+use std::sync::{Condvar, Mutex};
+
+#[test]
+fn waits_time_out_on_scaled_clock() {
+    feo_time::speed(4);
+    let timeout = Duration::from_millis(400);
+    // Four times faster, the timeouts elapse after 100ms of OS time
+    let max_real = std::time::Duration::from_millis(300);
+
+    let (start, real_start) = (Instant::now(), std::time::Instant::now());
+    feo_time::park_timeout(timeout);
+    assert!(start.elapsed() >= timeout);
+    assert!(real_start.elapsed() < max_real);
+
+    let (deadline, real_start) = (Deadline::after(timeout), std::time::Instant::now());
+    feo_time::park_until(deadline);
+    assert!(deadline.poll());
+    assert!(real_start.elapsed() < max_real);
+
+    let (mutex, condvar) = (Mutex::new(()), Condvar::new());
+    let (start, real_start) = (Instant::now(), std::time::Instant::now());
+    let (guard, result) = feo_time::wait_timeout(&condvar, mutex.lock().unwrap(), timeout).unwrap();
+    assert!(result.timed_out());
+    assert!(start.elapsed() >= timeout);
+    assert!(real_start.elapsed() < max_real);
+    drop(guard);
+
+    let (start, real_start) = (Instant::now(), std::time::Instant::now());
+    let (_guard, result) = feo_time::wait_timeout_while(&condvar, mutex.lock().unwrap(), timeout, |_| true).unwrap();
+    assert!(result.timed_out());
+    assert!(start.elapsed() >= timeout);
+    assert!(real_start.elapsed() < max_real);
+}
@@ -0,0 +1,49 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Tests of the virtual clock
+//!
+//! The virtual clock freezes the clock of the whole process, so these tests are run in a test binary of
+//! their own, separate from the unit tests of the library.
+
+use feo_time::{Duration, Instant, SystemTime};
+
+#[test]
+fn virtual_clock_only_advances_explicitly() {
+    feo_time::virtual_clock();
+    assert!(feo_time::is_virtual());
+
+    let (instant, system_time) = (Instant::now(), SystemTime::now());
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert_eq!(Instant::now(), instant);
+    assert_eq!(SystemTime::now(), system_time);
+
+    let step = Duration::from_millis(250);
+    feo_time::advance(step);
+    assert_eq!(Instant::now(), instant + step);
+    assert_eq!(SystemTime::now(), system_time + step);
+    assert_eq!(instant.elapsed(), step);
+
+    feo_time::advance(Duration::from_secs(3600));
+    assert_eq!(Instant::now().duration_since(instant), step + Duration::from_secs(3600));
+    assert_eq!(
+        SystemTime::now().duration_since(system_time).unwrap(),
+        step + Duration::from_secs(3600)
+    );
+
+    // Deadlines expire once the clock is advanced past them
+    let deadline = feo_time::Deadline::after(Duration::from_millis(10));
+    assert!(!deadline.poll());
+    feo_time::advance(Duration::from_millis(10));
+    assert!(deadline.poll());
+}
@@ -14,7 +14,7 @@
 use crate::io::ThreadNameCache;
 use anyhow::Error;
 use feo_tracing::protocol;
-use feo_tracing::protocol::EventInfo;
+use feo_tracing::protocol::{ClockTimestamp, CounterValue, EventInfo, FieldValue};
 use std::time;
 use std::time::SystemTime;
 
@@ -51,6 +51,8 @@ pub struct TraceRecord {
     pub thread: Option<Thread>,
     /// Trace data
     pub data: RecordData,
+    /// Time of the traced event on a secondary clock domain, if given
+    pub clock: Option<ClockTimestamp>,
 }
 
 impl TraceRecord {
@@ -61,6 +63,7 @@ impl TraceRecord {
             process,
             thread,
             data,
+            clock: None,
         }
     }
 }
@@ -91,21 +94,22 @@ pub enum RecordData {
     EnterSpan { id: Id },
     /// Span exited
     ExitSpan { id: Id },
+    /// Counter set
+    Counter { name: String, value: CounterValue },
+    /// Reading of a secondary clock domain at the time of the record
+    ClockSync(ClockTimestamp),
+    /// Context switches on a CPU, captured by the [sched](crate::sched) capture
+    Sched(SchedSwitches),
 }
 
 impl From<protocol::TraceData> for RecordData {
     fn from(trace_data: protocol::TraceData) -> Self {
         match trace_data {
-            protocol::TraceData::NewSpan {
-                id,
-                name,
-                name_len,
-                info,
-            } => {
+            protocol::TraceData::NewSpan { id, name, info } => {
                 let record_info: RecordEventInfo = info.into();
                 RecordData::NewSpan {
                     id,
-                    name: String::from_utf8_lossy(&name[0..name_len]).to_string(),
+                    name: name.into_owned(),
                     info: record_info,
                 }
             },
@@ -113,34 +117,115 @@ impl From<protocol::TraceData> for RecordData {
             protocol::TraceData::Event {
                 parent_span,
                 name,
-                name_len,
                 info,
             } => {
                 let record_info: RecordEventInfo = info.into();
                 RecordData::Event {
                     parent_span,
-                    name: String::from_utf8_lossy(&name[0..name_len]).to_string(),
+                    name: name.into_owned(),
                     info: record_info,
                 }
             },
             protocol::TraceData::Enter { span } => RecordData::EnterSpan { id: span },
             protocol::TraceData::Exit { span } => RecordData::ExitSpan { id: span },
+            protocol::TraceData::Counter { name, value } => RecordData::Counter { name, value },
+            protocol::TraceData::ClockSync(clock) => RecordData::ClockSync(clock),
+            protocol::TraceData::Hello { .. } => unreachable!("hello packets are checked on receipt"),
         }
     }
 }
 
+/// Context switches captured on one CPU, in the order of their timestamps
+#[derive(Debug, Clone)]
+pub struct SchedSwitches {
+    /// CPU the switches happened on
+    pub cpu: u32,
+    pub switches: Vec<SchedSwitch>,
+}
+
+/// Switch of a CPU from one thread to the next
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedSwitch {
+    /// Time of the switch based on UNIX epoch in nanoseconds
+    pub timestamp: u64,
+    /// Thread leaving the CPU
+    pub prev: SchedThread,
+    /// Kernel priority of the thread leaving the CPU
+    pub prev_prio: i32,
+    /// Task state bits of the kernel of the thread leaving the CPU, 0 if it is still runnable
+    pub prev_state: i64,
+    /// Thread entering the CPU
+    pub next: SchedThread,
+    /// Kernel priority of the thread entering the CPU
+    pub next_prio: i32,
+}
+
+/// Thread taking part in a context switch
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedThread {
+    /// Thread ID, 0 for the idle task
+    pub tid: ThreadId,
+    /// Process of the thread, if known
+    pub pid: Option<ProcessId>,
+    /// Command name of the thread
+    pub comm: String,
+}
+
+/// Target, source location and fields of a span or event
 #[derive(Debug, Default)]
 pub struct RecordEventInfo {
-    pub name: Option<String>,
-    pub value: String,
+    /// Target, usually the module path
+    pub target: String,
+    /// Source file and line, if known
+    pub location: Option<(String, u32)>,
+    pub fields: Vec<RecordField>,
+}
+
+/// Named field of a span or event
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordField {
+    pub name: String,
+    pub value: RecordFieldValue,
+}
+
+/// Value of a field of a span or event
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordFieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
 }
 
 impl From<EventInfo> for RecordEventInfo {
     fn from(info: EventInfo) -> Self {
-        let name_len = info.name_len;
-        let name = name_len.map(|len| String::from_utf8_lossy(&info.name[0..len]).to_string());
-        let value = String::from_utf8_lossy(&info.value[0..info.value_len]).to_string();
-        RecordEventInfo { name, value }
+        let fields = info
+            .fields
+            .into_iter()
+            .flatten()
+            .map(|field| RecordField {
+                name: field.name.into_owned(),
+                value: field.value.into(),
+            })
+            .collect();
+        RecordEventInfo {
+            target: info.target.into_owned(),
+            location: info.location.map(|(file, line)| (file.into_owned(), line)),
+            fields,
+        }
+    }
+}
+
+impl From<FieldValue> for RecordFieldValue {
+    fn from(value: FieldValue) -> Self {
+        match value {
+            FieldValue::Str(value) => RecordFieldValue::Str(value),
+            FieldValue::I64(value) => RecordFieldValue::I64(value),
+            FieldValue::U64(value) => RecordFieldValue::U64(value),
+            FieldValue::F64(value) => RecordFieldValue::F64(value),
+            FieldValue::Bool(value) => RecordFieldValue::Bool(value),
+        }
     }
 }
 
@@ -164,7 +249,10 @@ pub(crate) fn decode_packet(
         name: thread_cache.get(p.tid).map(|s| s.to_string()),
     });
 
-    let packet = TraceRecord::new(timestamp, process, thread, data);
+    let packet = TraceRecord {
+        clock: trace_packet.clock,
+        ..TraceRecord::new(timestamp, process, thread, data)
+    };
 
     Ok(packet)
 }
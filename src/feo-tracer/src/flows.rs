@@ -0,0 +1,107 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Data flow between activities
+//!
+//! The com layer emits an event for every sample sent or received, see `feo_com::trace`.
+//! Each received sample is connected by a Perfetto flow to the latest sample sent on its topic,
+//! so that timelines show arrows from the publishing to the receiving activities.
+//! Samples received by several activities are connected in the order of reception.
+//!
+//! Latencies measured by the com layer, see `feo_com::latency`, are shown on a counter track
+//! per process and topic.
+
+use crate::data::{ProcessId, RecordEventInfo, RecordFieldValue, ThreadId};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Name of the event of a sent sample
+pub const PUBLISH_EVENT: &str = "com_publish";
+/// Name of the event of a received sample
+pub const RECEIVE_EVENT: &str = "com_receive";
+/// Field name of the topic of a sample
+pub const TOPIC_FIELD: &str = "topic";
+/// Field name of the sequence number of a sent sample
+pub const SEQUENCE_FIELD: &str = "seq";
+/// Name of the event of a measured latency
+pub const LATENCY_EVENT: &str = "com_latency";
+/// Field name of a measured latency in nanoseconds
+pub const LATENCY_FIELD: &str = "latency_ns";
+
+/// Flow of a sample from its publisher to its receivers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flow {
+    /// Perfetto flow id
+    pub id: u64,
+    /// Sequence number of the sample at its publisher
+    pub sequence: u64,
+}
+
+/// Latest samples sent per topic
+#[derive(Debug, Default)]
+pub struct DataFlows {
+    latest: HashMap<String, Flow>,
+}
+
+impl DataFlows {
+    /// Get the flow of the sample of a com event emitted by the given process and thread.
+    ///
+    /// Returns `None` for other events and for received samples of topics without sent samples.
+    pub fn observe(&mut self, pid: ProcessId, tid: ThreadId, name: &str, info: &RecordEventInfo) -> Option<Flow> {
+        let topic = info
+            .fields
+            .iter()
+            .find_map(|field| match (&field.name[..], &field.value) {
+                (TOPIC_FIELD, RecordFieldValue::Str(topic)) => Some(topic),
+                _ => None,
+            })?;
+        match name {
+            PUBLISH_EVENT => {
+                let sequence = info
+                    .fields
+                    .iter()
+                    .find_map(|field| match (&field.name[..], &field.value) {
+                        (SEQUENCE_FIELD, RecordFieldValue::U64(sequence)) => Some(*sequence),
+                        _ => None,
+                    })?;
+                let mut hasher = DefaultHasher::new();
+                (pid, tid, topic, sequence).hash(&mut hasher);
+                let flow = Flow {
+                    id: hasher.finish(),
+                    sequence,
+                };
+                self.latest.insert(topic.clone(), flow);
+                Some(flow)
+            },
+            RECEIVE_EVENT => self.latest.get(topic).copied(),
+            _ => None,
+        }
+    }
+}
+
+/// Get the topic and the latency in nanoseconds of a latency event, `None` for other events
+pub fn latency<'a>(name: &str, info: &'a RecordEventInfo) -> Option<(&'a str, u64)> {
+    if name != LATENCY_EVENT {
+        return None;
+    }
+    let mut topic = None;
+    let mut latency = None;
+    for field in info.fields.iter() {
+        match (&field.name[..], &field.value) {
+            (TOPIC_FIELD, RecordFieldValue::Str(value)) => topic = Some(value.as_str()),
+            (LATENCY_FIELD, RecordFieldValue::U64(value)) => latency = Some(*value),
+            _ => {},
+        }
+    }
+    Some((topic?, latency?))
+}
@@ -15,22 +15,58 @@
 
 use crate::data;
 use anyhow::{Context, Error};
-use feo_tracing::protocol;
+use feo_tracing::local::{self, LocalAddress};
+use feo_tracing::protocol::{self, CounterValue};
+#[cfg(unix)]
+use feo_tracing::shm::{self, ShmRing};
+use feo_tracing::udp::DatagramHeader;
 use postcard::accumulator::{CobsAccumulator, FeedResult};
 use score_log::{debug, info, warn};
 use std::collections::HashMap;
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(unix)]
 use std::path::Path;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+#[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::mpsc;
-use tokio::task;
+use tokio::{task, time};
 
 /// Size of the buffer (bytes) used for deserializing incoming trace packets
-const READ_BUFFER_SIZE: usize = 32 * protocol::MAX_PACKET_SIZE;
+///
+/// Holds at least one packet of the largest size configurable in the subscriber.
+const READ_BUFFER_SIZE: usize = 4 * protocol::MAX_PACKET_SIZE;
 
-pub async fn listen(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+/// Size of the buffer (bytes) used for receiving wakeups of the shared memory transport
+#[cfg(unix)]
+const WAKEUP_BUFFER_SIZE: usize = 64;
+
+/// Size of the buffer (bytes) used for receiving datagrams of the UDP transport
+///
+/// Holds a datagram with a single packet of the largest size configurable in the subscriber.
+const DATAGRAM_BUFFER_SIZE: usize = DatagramHeader::SIZE + protocol::MAX_PACKET_SIZE;
+
+/// Time after which a process sending over UDP is considered gone if no datagram was received
+const UDP_PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Name of the counter of the datagrams lost per process sending over UDP
+const DATAGRAMS_LOST_COUNTER: &str = "datagrams_lost";
+
+/// Listen for processes streaming their packets to the given local address, see [local]
+pub async fn listen(address: &LocalAddress, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+    match address {
+        #[cfg(unix)]
+        LocalAddress::Unix(path) => listen_unix(path, sink).await,
+        LocalAddress::Tcp(address) => listen_tcp(*address, sink).await,
+    }
+}
+
+/// Listen for processes connecting to the Unix socket at the given path
+#[cfg(unix)]
+async fn listen_unix(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
     // Bind
     info!("Binding to {}", format!("{path:?}"));
     let listener = UnixListener::bind(path)?;
@@ -41,51 +77,182 @@ pub async fn listen(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Resul
         let (socket, _) = listener.accept().await.context("failed to accept connection")?;
 
         debug!("Accepted connection");
-        task::spawn(connection(socket, sink.clone()));
+        let sink = sink.clone();
+        task::spawn(async move {
+            let peer = Peer::connect(&socket, sink).await;
+            connection(Stream::Unix(socket), peer).await;
+        });
     }
 }
 
-async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
-    // Retrieve the PID of the peer
-    let pid = socket.peer_cred().unwrap().pid().unwrap() as u32;
+/// Listen for processes connecting over TCP, announcing their PID in a preamble
+async fn listen_tcp(address: SocketAddr, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+    // Bind
+    info!("Binding to {}", format!("{address:?}"));
+    let listener = TcpListener::bind(address).await?;
 
-    // Capture the process name for the peer
-    let process_name = fs::read_to_string(format!("/proc/{}/comm", pid))
-        .map(|name| name.trim_end().to_string())
-        .ok();
+    // Listen
+    info!("Listening on {}", format!("{address:?}"));
+    loop {
+        let (socket, source) = listener.accept().await.context("failed to accept connection")?;
 
-    info!(
-        "Processing messages from {:x} ({})",
-        pid,
-        process_name.as_deref().unwrap_or("")
-    );
+        debug!("Accepted connection from {}", format!("{source:?}"));
+        let sink = sink.clone();
+        task::spawn(async move {
+            let pid = match read_preamble(&socket).await {
+                Ok(pid) => pid,
+                Err(e) => {
+                    warn!("Failed to receive preamble from {}: {}", format!("{source:?}"), format!("{e:?}"));
+                    return;
+                },
+            };
+            let peer = Peer::local(pid, sink).await;
+            connection(Stream::Tcp(socket), peer).await;
+        });
+    }
+}
 
-    // Send a process exec event
-    sink.send(data::TraceRecord {
-        timestamp: SystemTime::now(),
-        process: data::Process {
-            id: pid,
-            name: process_name.clone(),
-        },
-        thread: None,
-        data: data::RecordData::Exec,
-    })
-    .await
-    .expect("channel error");
+/// Read the preamble of a TCP connection and get the PID announced by the process
+async fn read_preamble(socket: &TcpStream) -> io::Result<u32> {
+    let mut preamble = [0u8; local::PREAMBLE_SIZE];
+    let mut len = 0;
+    while len < preamble.len() {
+        socket.readable().await?;
+        match socket.try_read(&mut preamble[len..]) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(read) => len += read,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(local::preamble_pid(preamble))
+}
+
+/// Listen for processes using the shared memory transport, see [shm](feo_tracing::shm)
+#[cfg(unix)]
+pub async fn listen_shm(path: &Path, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+    // Bind
+    info!("Binding to {}", format!("{path:?}"));
+    let listener = UnixListener::bind(path)?;
+
+    // Listen
+    info!("Listening on {}", format!("{path:?}"));
+    loop {
+        let (socket, _) = listener.accept().await.context("failed to accept connection")?;
 
-    // Create a cache for the thread names in order to avoid frequent reads of procfs entries
-    let mut thread_name_cache = ThreadNameCache::new(pid);
+        debug!("Accepted shared memory connection");
+        task::spawn(shm_connection(socket, sink.clone()));
+    }
+}
 
-    // Buffers for incoming packets and postcard deserialization
+/// Receive the datagrams of processes using the UDP transport, see [udp](feo_tracing::udp)
+///
+/// If the given address is a multicast group, the group is joined on all interfaces and the
+/// datagrams sent to its port are received. Processes are identified by source address and PID.
+pub async fn listen_udp(address: SocketAddr, sink: mpsc::Sender<data::TraceRecord>) -> Result<(), Error> {
+    // Bind
+    let socket = if address.ip().is_multicast() {
+        let any: IpAddr = match address.ip() {
+            IpAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+            IpAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+        };
+        info!("Binding to {}", format!("{:?}", SocketAddr::new(any, address.port())));
+        let socket = UdpSocket::bind(SocketAddr::new(any, address.port())).await?;
+        match address.ip() {
+            IpAddr::V4(group) => socket.join_multicast_v4(group, Ipv4Addr::UNSPECIFIED)?,
+            IpAddr::V6(group) => socket.join_multicast_v6(&group, 0)?,
+        }
+        socket
+    } else {
+        info!("Binding to {}", format!("{address:?}"));
+        UdpSocket::bind(address).await?
+    };
+
+    // Receive
+    info!("Receiving datagrams on {}", format!("{address:?}"));
+    let mut peers: HashMap<(IpAddr, u32), UdpPeer> = HashMap::new();
+    let mut buffer = vec![0u8; DATAGRAM_BUFFER_SIZE];
+    loop {
+        let received = time::timeout(UDP_PEER_TIMEOUT, socket.recv_from(&mut buffer)).await;
+        if let Ok(received) = received {
+            let (len, source) = received.context("failed to receive datagram")?;
+            let Some((header, packets)) = DatagramHeader::read(&buffer[..len]) else {
+                warn!("Ignoring malformed datagram from {}", format!("{source:?}"));
+                continue;
+            };
+
+            let key = (source.ip(), header.pid);
+            if !peers.contains_key(&key) {
+                let peer = UdpPeer::connect(source.ip(), header, sink.clone()).await;
+                peers.insert(key, peer);
+            }
+            if let Some(peer) = peers.get_mut(&key) {
+                if !peer.receive(header, packets).await {
+                    if let Some(peer) = peers.remove(&key) {
+                        peer.peer.disconnect().await;
+                    }
+                }
+            }
+        }
+
+        // Processes do not disconnect over UDP, so they are considered gone once they stop sending
+        let stale: Vec<(IpAddr, u32)> = peers
+            .iter()
+            .filter(|(_, peer)| peer.last_received.elapsed() >= UDP_PEER_TIMEOUT)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in stale {
+            if let Some(peer) = peers.remove(&key) {
+                info!(
+                    "No datagrams from {} at {} for {}, closing connection",
+                    key.1,
+                    format!("{}", key.0),
+                    format!("{UDP_PEER_TIMEOUT:?}")
+                );
+                peer.peer.disconnect().await;
+            }
+        }
+    }
+}
+
+/// Stream of a process connected to the local address
+enum Stream {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    /// Wait for the stream to become readable
+    async fn readable(&self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(socket) => socket.readable().await,
+            Stream::Tcp(socket) => socket.readable().await,
+        }
+    }
+
+    /// Try to read from the stream without waiting
+    fn try_read(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Stream::Unix(socket) => socket.try_read(buffer),
+            Stream::Tcp(socket) => socket.try_read(buffer),
+        }
+    }
+}
+
+/// Receive the packets of the given peer until its stream is closed
+async fn connection(socket: Stream, mut peer: Peer) {
+    // Buffer for incoming packets
     let mut read_buffer = [0u8; READ_BUFFER_SIZE];
-    let mut cobs_buffer: CobsAccumulator<READ_BUFFER_SIZE> = CobsAccumulator::new();
 
-    'deser: loop {
+    loop {
         socket.readable().await.expect("socket error");
 
         let len = match socket.try_read(&mut read_buffer) {
             Ok(0) => {
-                info!("Connection from {} closed", pid);
+                info!("Connection from {} closed", peer.pid);
                 break;
             },
             Ok(len) => len,
@@ -95,54 +262,296 @@ async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
             Err(e) => {
                 warn!(
                     "Failed to receive data from {}: {}. Closing connection",
-                    pid,
+                    peer.pid,
                     format!("{e:?}")
                 );
                 break;
             },
         };
 
-        let buffer = &read_buffer[..len];
+        if !peer.feed(&read_buffer[..len]).await {
+            break;
+        }
+    }
+
+    peer.disconnect().await;
+}
+
+#[cfg(unix)]
+async fn shm_connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
+    let pid = peer_pid(&socket);
+    let ring = match ShmRing::open(&shm::shm_name(pid)) {
+        Ok(ring) => ring,
+        Err(e) => {
+            warn!(
+                "Failed to open shared memory of {}: {}. Closing connection",
+                pid,
+                format!("{e:?}")
+            );
+            return;
+        },
+    };
+    let mut peer = Peer::connect(&socket, sink).await;
+
+    // Buffers for packets read from the ring and wakeups received on the socket
+    let mut read_buffer = [0u8; READ_BUFFER_SIZE];
+    let mut wakeup_buffer = [0u8; WAKEUP_BUFFER_SIZE];
+    let mut closed = false;
+
+    loop {
+        let len = match ring.read(&mut read_buffer) {
+            Ok(len) => len,
+            Err(e) => {
+                warn!("Failed to read shared memory of {}: {}. Closing connection", pid, format!("{e:?}"));
+                break;
+            },
+        };
+        if len > 0 {
+            if !peer.feed(&read_buffer[..len]).await {
+                break;
+            }
+            continue;
+        }
+        // The ring has been drained after the connection was closed
+        if closed {
+            info!("Connection from {} closed", pid);
+            break;
+        }
+        if !ring.prepare_wait() {
+            continue;
+        }
+
+        socket.readable().await.expect("socket error");
+        match socket.try_read(&mut wakeup_buffer) {
+            Ok(0) => closed = true,
+            Ok(_) => {},
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {},
+            Err(e) => {
+                warn!("Failed to receive wakeup from {}: {}", pid, format!("{e:?}"));
+                closed = true;
+            },
+        }
+    }
+
+    peer.disconnect().await;
+}
+
+/// Retrieve the PID of the peer of the given socket
+#[cfg(unix)]
+fn peer_pid(socket: &UnixStream) -> u32 {
+    socket.peer_cred().unwrap().pid().unwrap() as u32
+}
+
+/// Traced process connected to the tracer
+struct Peer {
+    /// PID of the process
+    pid: u32,
+    /// Name of the process, if available
+    process_name: Option<String>,
+    /// Cache for the thread names in order to avoid frequent reads of procfs entries
+    thread_name_cache: ThreadNameCache,
+    /// Buffer for postcard deserialization
+    cobs_buffer: CobsAccumulator<READ_BUFFER_SIZE>,
+    /// Timestamp of the last packet received from the process
+    last_timestamp: SystemTime,
+    /// Sink of the decoded packets
+    sink: mpsc::Sender<data::TraceRecord>,
+}
+
+impl Peer {
+    /// Identify the peer of the given socket and send its process exec event
+    #[cfg(unix)]
+    async fn connect(socket: &UnixStream, sink: mpsc::Sender<data::TraceRecord>) -> Self {
+        Self::local(peer_pid(socket), sink).await
+    }
+
+    /// Send the process exec event of the given process on this machine
+    async fn local(pid: u32, sink: mpsc::Sender<data::TraceRecord>) -> Self {
+        // Capture the process name for the peer
+        let process_name = fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|name| name.trim_end().to_string())
+            .ok();
+
+        Self::new(pid, process_name, ThreadNameCache::new(pid), sink).await
+    }
+
+    /// Send the process exec event of the given process
+    async fn new(
+        pid: u32,
+        process_name: Option<String>,
+        thread_name_cache: ThreadNameCache,
+        sink: mpsc::Sender<data::TraceRecord>,
+    ) -> Self {
+        info!(
+            "Processing messages from {:x} ({})",
+            pid,
+            process_name.as_deref().unwrap_or("")
+        );
+
+        // Send a process exec event
+        sink.send(data::TraceRecord {
+            timestamp: SystemTime::now(),
+            process: data::Process {
+                id: pid,
+                name: process_name.clone(),
+            },
+            thread: None,
+            data: data::RecordData::Exec,
+            clock: None,
+        })
+        .await
+        .expect("channel error");
+
+        Self {
+            pid,
+            process_name,
+            thread_name_cache,
+            cobs_buffer: CobsAccumulator::new(),
+            last_timestamp: SystemTime::now(),
+            sink,
+        }
+    }
+
+    /// Decode the packets of the given bytes received from the peer and transmit them to the sink
+    ///
+    /// Returns `false` if the connection has to be closed.
+    async fn feed(&mut self, buffer: &[u8]) -> bool {
         let mut remaining = buffer;
 
         while !remaining.is_empty() {
-            remaining = match cobs_buffer.feed_ref::<protocol::TracePacket>(remaining) {
+            remaining = match self.cobs_buffer.feed_ref::<protocol::TracePacket>(remaining) {
                 FeedResult::Consumed => break,
                 FeedResult::OverFull(_) => {
-                    warn!("Deserialization buffer overflow in {}. Closing connection", pid);
-                    break 'deser;
+                    warn!("Deserialization buffer overflow in {}. Closing connection", self.pid);
+                    return false;
                 },
                 FeedResult::DeserError(remaining) => remaining,
+                FeedResult::Success {
+                    data:
+                        protocol::TracePacket {
+                            data: protocol::TraceData::Hello { version },
+                            ..
+                        },
+                    remaining,
+                } => {
+                    // Peers of another version may send packets decoding to garbage, don't trust them
+                    if version != protocol::PROTOCOL_VERSION {
+                        warn!(
+                            "Process {} speaks trace protocol version {}, expected version {}. Closing connection",
+                            self.pid,
+                            version,
+                            protocol::PROTOCOL_VERSION
+                        );
+                        return false;
+                    }
+                    debug!("Process {} speaks trace protocol version {}", self.pid, version);
+                    remaining
+                },
                 FeedResult::Success { data, remaining } => {
                     // Data successfully decoded, add thread and process info
                     // and transmit to sink
-                    let packet = match data::decode_packet(pid, data, &mut thread_name_cache, process_name.clone()) {
+                    let packet = match data::decode_packet(
+                        self.pid,
+                        data,
+                        &mut self.thread_name_cache,
+                        self.process_name.clone(),
+                    ) {
                         Ok(packet) => packet,
                         Err(e) => {
                             warn!(
                                 "Failed to decode packet from {}: {}. Closing connection",
-                                pid,
+                                self.pid,
                                 format!("{e:?}")
                             );
-                            break 'deser;
+                            return false;
                         },
                     };
-                    sink.send(packet).await.expect("channel error");
+                    self.last_timestamp = packet.timestamp;
+                    self.sink.send(packet).await.expect("channel error");
                     remaining
                 },
             };
         }
+        true
+    }
+
+    /// Send the process exit event of the peer
+    async fn disconnect(self) {
+        self.sink
+            .send(data::TraceRecord {
+                timestamp: SystemTime::now(),
+                process: data::Process {
+                    id: self.pid,
+                    name: None,
+                },
+                thread: None,
+                data: data::RecordData::Exit,
+                clock: None,
+            })
+            .await
+            .expect("channel error");
     }
+}
 
-    // Send a process exit event
-    sink.send(data::TraceRecord {
-        timestamp: SystemTime::now(),
-        process: data::Process { id: pid, name: None },
-        thread: None,
-        data: data::RecordData::Exit,
-    })
-    .await
-    .expect("channel error");
+/// Process sending over UDP, see [listen_udp]
+struct UdpPeer {
+    peer: Peer,
+    /// Sequence number of the next datagram expected from the process
+    next_sequence: u64,
+    /// Number of datagrams lost so far
+    lost: u64,
+    /// Time the last datagram was received
+    last_received: Instant,
+}
+
+impl UdpPeer {
+    /// Send the process exec event of the process sending the given first datagram
+    ///
+    /// The process runs on another machine, so its process and thread names are not available.
+    async fn connect(address: IpAddr, header: DatagramHeader, sink: mpsc::Sender<data::TraceRecord>) -> Self {
+        let thread_name_cache = ThreadNameCache::remote(header.pid);
+        let peer = Peer::new(header.pid, Some(address.to_string()), thread_name_cache, sink).await;
+        Self {
+            peer,
+            next_sequence: header.sequence,
+            lost: 0,
+            last_received: Instant::now(),
+        }
+    }
+
+    /// Decode the packets of the given datagram, counting the datagrams lost before it
+    ///
+    /// Returns `false` if the connection has to be closed.
+    async fn receive(&mut self, header: DatagramHeader, packets: &[u8]) -> bool {
+        self.last_received = Instant::now();
+        // Datagrams arriving out of order are decoded as well, without counting them as lost
+        let lost = header.sequence.saturating_sub(self.next_sequence);
+        self.next_sequence = self.next_sequence.max(header.sequence.wrapping_add(1));
+
+        let connected = self.peer.feed(packets).await;
+        if lost > 0 {
+            self.lost += lost;
+            warn!("Lost {} datagrams from {} ({} in total)", lost, self.peer.pid, self.lost);
+            self.peer
+                .sink
+                .send(data::TraceRecord {
+                    timestamp: self.peer.last_timestamp,
+                    process: data::Process {
+                        id: self.peer.pid,
+                        name: self.peer.process_name.clone(),
+                    },
+                    thread: None,
+                    data: data::RecordData::Counter {
+                        name: DATAGRAMS_LOST_COUNTER.to_string(),
+                        value: CounterValue::I64(i64::try_from(self.lost).unwrap_or(i64::MAX)),
+                    },
+                    clock: None,
+                })
+                .await
+                .expect("channel error");
+        }
+        connected
+    }
 }
 
 /// Cache for thread names in order to avoid frequent reads of procfs entries.
@@ -150,6 +559,8 @@ async fn connection(socket: UnixStream, sink: mpsc::Sender<data::TraceRecord>) {
 pub struct ThreadNameCache {
     /// PID of the process
     pid: u32,
+    /// Whether the process runs on this machine, so its thread names can be read from procfs
+    local: bool,
     /// Map of thread names indexed by their TID
     names: HashMap<u32, Option<String>>,
 }
@@ -159,12 +570,25 @@ impl<'a> ThreadNameCache {
     pub fn new(pid: u32) -> Self {
         Self {
             pid,
+            local: true,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Create a thread cache for a process on another machine, without thread names
+    pub fn remote(pid: u32) -> Self {
+        Self {
+            pid,
+            local: false,
             names: HashMap::new(),
         }
     }
 
     /// Get the name of a thread or query the kernel if not cached
     pub fn get(&'a mut self, tid: u32) -> Option<&'a str> {
+        if !self.local {
+            return None;
+        }
         self.names
             .entry(tid)
             .or_insert_with(|| {
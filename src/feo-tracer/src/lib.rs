@@ -14,5 +14,11 @@
 //! Central trace collector
 
 pub mod data;
+pub mod flows;
 pub mod io;
 pub mod perfetto;
+#[cfg(unix)]
+pub mod producer;
+#[cfg(target_os = "linux")]
+pub mod sched;
+pub mod tracks;
@@ -11,34 +11,55 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-//! Collect trace data - placeholder
+//! Collect trace data
+//!
+//! Accepts connections of any number of traced processes, over the local socket or the shared memory
+//! transport, and with `--udp` the datagrams of processes tracing over UDP from other machines.
+//! Their packets are written either into one Perfetto trace, or into a separate trace per process
+//! (`--per-process`).
+//! With `--live`, the packets are forwarded to a running Perfetto tracing service instead.
+//!
+//! On Linux, `--sched` additionally captures the context switches of the traced processes from the kernel,
+//! so that their activity spans can be correlated with the CPU scheduling, see [feo_tracer::sched].
+//! A tracing service records the scheduling itself, so `--sched` is not available with `--live`.
+//!
+//! Where Unix sockets are not available, e.g. on Windows, the local socket is a TCP socket on the
+//! loopback interface, see [feo_tracing::local]. The shared memory transport and `--live` require Unix.
 
-use anyhow::{bail, Context, Error};
+#[cfg(unix)]
+use anyhow::Context;
+use anyhow::{bail, Error};
 use argh::FromArgs;
 use core::future::pending;
-use feo_tracer::io::listen;
-use feo_tracer::perfetto;
+#[cfg(unix)]
+use feo_tracer::io::listen_shm;
+use feo_tracer::io::{listen, listen_udp};
+#[cfg(target_os = "linux")]
+use feo_tracer::sched::SchedCapture;
+use feo_tracing::local::LocalAddress;
+use feo_tracing::paths;
+
 use futures::FutureExt;
-use score_log::{debug, info, LevelFilter};
-use std::path::{Path, PathBuf};
-use std::{fs, io};
+#[cfg(unix)]
+use score_log::debug;
+use score_log::{info, LevelFilter};
+#[cfg(unix)]
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use stdout_logger::StdoutLoggerBuilder;
 use tokio::sync::mpsc;
 use tokio::{runtime, select, signal, task, time};
 
+/// Perfetto output files
+mod output;
 /// Progress bar wrapper
 mod progress;
 
-/// Path to the Unix socket
-const UNIX_PACKET_PATH: &str = "/tmp/feo-tracer.sock";
-
 /// Size of the message channel (number of messages) for transmitting decoded trace
 /// packets to the file writer
 const MESSAGE_CHANNEL_SIZE: usize = 256;
 
-/// Size of the file writing buffer (bytes)
-const FILE_BUFFER_SIZE: usize = 1024 * 1024;
-
 /// The number of Tokio worker threads to spawn
 const NUM_THREADS: usize = 4;
 
@@ -52,11 +73,39 @@ struct Args {
 
     #[argh(description = "output path")]
     #[argh(option, short = 'o')]
-    out: PathBuf,
+    out: Option<PathBuf>,
 
     #[argh(description = "log level")]
     #[argh(option, short = 'l')]
     log_level: Option<LevelFilter>,
+
+    #[argh(description = "interval in seconds to flush and fsync the output file, at least 1")]
+    #[argh(option, short = 's')]
+    sync_interval: Option<u64>,
+
+    #[argh(description = "write a separate output file per traced process, named after the output path")]
+    #[argh(switch, short = 'p')]
+    per_process: bool,
+
+    #[argh(description = "forward the packets to a running traced instead of writing a file")]
+    #[argh(switch, short = 'L')]
+    live: bool,
+
+    #[argh(description = "receive trace datagrams on the given address or multicast group, e.g. 0.0.0.0:7470")]
+    #[argh(option, short = 'u')]
+    udp: Option<SocketAddr>,
+
+    #[argh(description = "address of the socket receiving trace packets, a path or host:port, see feo_tracing::paths")]
+    #[argh(option)]
+    socket: Option<String>,
+
+    #[argh(description = "path of the socket setting up the shared memory transport, see feo_tracing::paths")]
+    #[argh(option)]
+    shm_socket: Option<PathBuf>,
+
+    #[argh(description = "capture the context switches of the traced processes, requires Linux and tracefs access")]
+    #[argh(switch)]
+    sched: bool,
 }
 
 /// Tracer main entry point
@@ -65,6 +114,13 @@ fn main() -> Result<(), Error> {
         duration,
         out,
         log_level,
+        sync_interval,
+        per_process,
+        live,
+        udp,
+        socket,
+        shm_socket,
+        sched,
     } = argh::from_env();
 
     // Initialize logging
@@ -87,41 +143,104 @@ fn main() -> Result<(), Error> {
 
     let (message_sender, mut message_receiver) = mpsc::channel(MESSAGE_CHANNEL_SIZE);
 
+    if sync_interval == Some(0) {
+        bail!("invalid sync interval 0, the interval must be at least one second");
+    }
+
     // Listen for incoming connections on a socket
     // Forward the messages to the message channel.
+    if let Some(socket) = socket.as_deref() {
+        if LocalAddress::parse(socket).is_none() {
+            bail!("invalid socket address {socket}, Unix sockets are not available");
+        }
+    }
     let fan_in_socket = {
         let message_sender = message_sender.clone();
+        let address = paths::tracer_address(socket.as_deref());
+        async move {
+            // Check if socket is present and remove if necessary
+            #[cfg(unix)]
+            if let LocalAddress::Unix(path) = &address {
+                if path.exists() {
+                    debug!("Removing stale socket at {}", format!("{path:?}"));
+                    fs::remove_file(path).with_context(|| format!("failed to remove {path:?}"))?;
+                }
+            }
+            listen(&address, message_sender).await
+        }
+    };
+
+    // Listen for incoming connections using the shared memory transport.
+    // Forward the messages to the message channel.
+    #[cfg(not(unix))]
+    if shm_socket.is_some() {
+        bail!("the shared memory transport requires Unix sockets");
+    }
+    #[cfg(unix)]
+    let fan_in_shm = {
+        let message_sender = message_sender.clone();
+        let path = paths::tracer_shm_socket(shm_socket.as_deref());
         async move {
-            let path = Path::new(UNIX_PACKET_PATH);
             // Check if socket is present and remove if necessary
             if path.exists() {
                 debug!("Removing stale socket at {}", format!("{path:?}"));
-                fs::remove_file(path).with_context(|| format!("failed to remove {path:?}"))?;
+                fs::remove_file(&path).with_context(|| format!("failed to remove {path:?}"))?;
             }
-            listen(path, message_sender).await
+            listen_shm(&path, message_sender).await
         }
     };
 
+    // Receive datagrams of processes on other machines, if configured.
+    // Forward the messages to the message channel.
+    let fan_in_udp = udp.map(|address| {
+        let message_sender = message_sender.clone();
+        async move { listen_udp(address, message_sender).await }
+    });
+
+    // Capture the context switches of the traced processes, if configured.
+    // Forward them to the message channel.
+    if sched && live {
+        bail!("--sched is not available with --live, configure the tracing service to record sched_switch");
+    }
+    #[cfg(not(target_os = "linux"))]
+    if sched {
+        bail!("--sched requires Linux");
+    }
+    #[cfg(target_os = "linux")]
+    let sched_sender = sched.then(|| message_sender.clone());
+
     // Handle incoming messages on the message channel. The channel yields
     // messages from all connected processes.
     let process_messages = {
-        // Open the output file and create a progress bar for the writes
-        let writer = io::BufWriter::with_capacity(
-            FILE_BUFFER_SIZE,
-            fs::File::create(&out).with_context(|| format!("failed to create {}", out.display()))?,
-        );
-
-        // Wrap writer in a progress bar
-        let writer = progress.add_writer(&format!("perfetto output ({})", out.display()), writer);
-
-        // Create a perfetto writer
-        let mut perfetto = perfetto::Perfetto::new(writer);
+        // Open the output file(s). Traces of individual processes are created when they connect.
+        let mut output = match (out, live) {
+            #[cfg(unix)]
+            (None, true) if !per_process => output::Output::live()?,
+            #[cfg(not(unix))]
+            (None, true) => bail!("--live requires Unix sockets"),
+            (Some(out), false) if per_process => output::Output::per_process(&out),
+            (Some(out), false) => output::Output::single(&out, &mut progress)?,
+            _ => bail!("either an output path or --live must be given, --per-process requires an output path"),
+        };
 
-        // Process messages as they arrive
+        // Process messages as they arrive.
+        // If configured, periodically flush and sync the output file(s), so that a crash of the tracer
+        // only loses the packets received since the last sync.
+        let mut sync = sync_interval.map(|interval| time::interval(time::Duration::from_secs(interval)));
         let process_packets = async move {
-            while let Some(message) = message_receiver.recv().await {
-                progress.on_packet(&message);
-                perfetto.on_packet(message)?;
+            loop {
+                select! {
+                    message = message_receiver.recv() => {
+                        let Some(message) = message else {
+                            break;
+                        };
+                        progress.on_packet(&message);
+                        output.on_packet(message, &mut progress)?;
+                    }
+                    _ = tick(&mut sync) => {
+                        task::block_in_place(|| output.sync())?;
+                    }
+                }
             }
             Ok(())
         };
@@ -149,6 +268,16 @@ fn main() -> Result<(), Error> {
     // Wait for all tasks to finish or error
     let run = async {
         tasks.spawn(fan_in_socket);
+        #[cfg(unix)]
+        tasks.spawn(fan_in_shm);
+        if let Some(fan_in_udp) = fan_in_udp {
+            tasks.spawn(fan_in_udp);
+        }
+        // The tracing instance is set up within the runtime, which polls the trace pipe
+        #[cfg(target_os = "linux")]
+        if let Some(sched_sender) = sched_sender {
+            tasks.spawn(SchedCapture::open()?.capture(sched_sender));
+        }
         tasks.spawn(process_messages);
 
         match tasks.join_next().await.expect("no tasks to join") {
@@ -165,3 +294,13 @@ fn main() -> Result<(), Error> {
         .build()?
         .block_on(run)
 }
+
+/// Wait for the next tick of the given interval, or forever if there is none
+async fn tick(interval: &mut Option<time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        },
+        None => pending::<()>().await,
+    }
+}
@@ -0,0 +1,168 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+use crate::progress::Progress;
+use anyhow::{Context, Error};
+use feo_tracer::data::{ProcessId, RecordData, TraceRecord};
+use feo_tracer::perfetto::Perfetto;
+#[cfg(unix)]
+use feo_tracer::producer::{self, ProducerWriter};
+use std::collections::hash_map::{Entry, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of the file writing buffer (bytes)
+const FILE_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Perfetto output of the tracer
+///
+/// Either multiplexes the packets of all traced processes into a single trace, writes
+/// a separate trace per process, or forwards the packets to a running Perfetto tracing service.
+pub enum Output {
+    /// Single trace containing all processes
+    Single(Trace),
+    /// Packets of all processes forwarded to the tracing service
+    #[cfg(unix)]
+    Live(Perfetto<ProducerWriter>),
+    /// One trace per process, named after the base path and the process
+    PerProcess {
+        base: PathBuf,
+        traces: HashMap<ProcessId, Trace>,
+    },
+}
+
+impl Output {
+    /// Create a single trace at `path` for all processes
+    pub fn single(path: &Path, progress: &mut Progress) -> Result<Self, Error> {
+        Trace::create(path.to_path_buf(), progress).map(Output::Single)
+    }
+
+    /// Forward the packets of all processes to the tracing service as Perfetto producer
+    #[cfg(unix)]
+    pub fn live() -> Result<Self, Error> {
+        let writer = ProducerWriter::connect(&producer::producer_socket(), "feo-tracer")?;
+        Ok(Output::Live(Perfetto::new(writer)))
+    }
+
+    /// Create a trace per process, derived from `base`
+    ///
+    /// The files are created when the processes connect.
+    pub fn per_process(base: &Path) -> Self {
+        Output::PerProcess {
+            base: base.to_path_buf(),
+            traces: HashMap::new(),
+        }
+    }
+
+    /// Write a trace packet to the trace of its process
+    pub fn on_packet(&mut self, message: TraceRecord, progress: &mut Progress) -> Result<(), Error> {
+        match self {
+            Output::Single(trace) => trace.perfetto.on_packet(message),
+            #[cfg(unix)]
+            Output::Live(perfetto) => perfetto.on_packet(message),
+            Output::PerProcess { base, traces } => {
+                // Context switches belong to no process, each trace keeps those of its process
+                if let RecordData::Sched(switches) = &message.data {
+                    for trace in traces.values_mut() {
+                        let data = RecordData::Sched(switches.clone());
+                        let record = TraceRecord::new(message.timestamp, message.process.clone(), None, data);
+                        trace.perfetto.on_packet(record)?;
+                    }
+                    return Ok(());
+                }
+                let pid = message.process.id;
+                let exit = matches!(message.data, RecordData::Exit);
+                let trace = match traces.entry(pid) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let path = process_path(base, pid, message.process.name.as_deref());
+                        entry.insert(Trace::create(path, progress)?)
+                    },
+                };
+                trace.perfetto.on_packet(message)?;
+
+                // Close the trace of a process once it disconnected
+                if exit {
+                    if let Some(mut trace) = traces.remove(&pid) {
+                        trace.perfetto.flush()?;
+                    }
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Flush and sync all open traces to disk
+    pub fn sync(&mut self) -> Result<(), Error> {
+        match self {
+            Output::Single(trace) => trace.sync(),
+            #[cfg(unix)]
+            Output::Live(perfetto) => perfetto.flush(),
+            Output::PerProcess { traces, .. } => traces.values_mut().try_for_each(Trace::sync),
+        }
+    }
+}
+
+/// Perfetto trace written to a file
+pub struct Trace {
+    path: PathBuf,
+    perfetto: Perfetto<Box<dyn Write + Send>>,
+    /// Second handle to the file for syncing it to disk while the writer is in use
+    sync_file: fs::File,
+}
+
+impl Trace {
+    fn create(path: PathBuf, progress: &mut Progress) -> Result<Self, Error> {
+        // Open the output file and create a progress bar for the writes
+        let file = fs::File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+        let sync_file = file
+            .try_clone()
+            .with_context(|| format!("failed to clone handle of {}", path.display()))?;
+        let writer = io::BufWriter::with_capacity(FILE_BUFFER_SIZE, file);
+
+        // Wrap writer in a progress bar
+        let writer = progress.add_writer(&format!("perfetto output ({})", path.display()), writer);
+        let perfetto = Perfetto::new(Box::new(writer) as Box<dyn Write + Send>);
+
+        Ok(Trace {
+            path,
+            perfetto,
+            sync_file,
+        })
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        self.perfetto.flush()?;
+        self.sync_file
+            .sync_data()
+            .with_context(|| format!("failed to sync {}", self.path.display()))
+    }
+}
+
+/// Path of the trace of a single process
+///
+/// The process name and id are inserted before the extension of `base`, e.g.
+/// `trace.pftrace` becomes `trace.adas_primary-1234.pftrace`.
+fn process_path(base: &Path, pid: ProcessId, name: Option<&str>) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let process = match name {
+        Some(name) => format!("{name}-{pid}"),
+        None => pid.to_string(),
+    };
+    let file_name = match base.extension() {
+        Some(extension) => format!("{stem}.{process}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{process}"),
+    };
+    base.with_file_name(file_name)
+}
@@ -11,13 +11,22 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::data::{RecordData, RecordEventInfo, Thread, TraceRecord};
+use crate::data::{
+    RecordData, RecordEventInfo, RecordField, RecordFieldValue, SchedSwitch, SchedSwitches, SchedThread, Thread,
+    TraceRecord,
+};
+use crate::flows::{self, DataFlows, RECEIVE_EVENT, SEQUENCE_FIELD};
+use crate::tracks::{
+    activity_track_name, activity_track_uuid, chain_track_name, chain_track_uuid, counter_track_uuid,
+    latency_track_name, latency_track_uuid, process_track_uuid, FeoMetadata, TrackNames,
+};
 use anyhow::{bail, Error};
+use feo_tracing::protocol::{ClockTimestamp, CounterValue};
 use perfetto_model as idl;
-use perfetto_model;
+use perfetto_model::builders::{self, AnnotationValue, EventBuilder, Interner, TrackBuilder};
 use prost::Message as ProstMessage;
 use score_log::info;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io;
 use std::time::UNIX_EPOCH;
 
@@ -26,6 +35,30 @@ type SequenceId = u32;
 /// Track uuid for a trace. This is unique per trace.
 type TrackUuid = u64;
 
+/// Period after which the interned strings of a sequence are emitted again, in nanoseconds
+///
+/// A tracing service in ring buffer mode overwrites the oldest packets, including those carrying
+/// the interned strings. Clearing the state periodically keeps the remaining packets readable.
+const CLEAR_PERIOD_NANOS: u64 = 10_000_000_000;
+
+/// Clock of the timestamps of packets without clock id, the default clock of traces
+///
+/// The packets are timestamped with the wall clock time of the traced processes. When forwarding the packets
+/// to a tracing service, the [producer](crate::producer) marks them as wall clock time instead.
+pub(crate) const TRACE_CLOCK_ID: u32 = idl::BuiltinClock::Boottime as u32;
+
+/// Clock id of the first secondary clock domain of a process, see [ClockTimestamp]
+///
+/// Perfetto reserves the clock ids from 64 to 127 for clocks scoped to a packet sequence, i.e. a process.
+const FIRST_DOMAIN_CLOCK_ID: u32 = 64;
+
+/// Interning state of the packet sequence of a process
+struct SequenceState {
+    interner: Interner,
+    /// Timestamp of the packet which cleared the state
+    cleared_at: u64,
+}
+
 /// Span
 #[derive(Debug, Default)]
 struct Span {
@@ -33,6 +66,8 @@ struct Span {
     pid: u32,
     /// thread in which the span was created
     thread: Thread,
+    /// Track on which the span is placed
+    track: TrackUuid,
     /// Trace of the span.
     trace: idl::Trace,
     /// Name of the span.
@@ -43,10 +78,11 @@ struct Span {
 
 impl Span {
     /// Create a new span.
-    fn new(pid: u32, thread: Thread, trace: idl::Trace, name: String, info: RecordEventInfo) -> Self {
+    fn new(pid: u32, thread: Thread, track: TrackUuid, trace: idl::Trace, name: String, info: RecordEventInfo) -> Self {
         Self {
             pid,
             thread,
+            track,
             trace,
             name,
             info,
@@ -55,11 +91,37 @@ impl Span {
 }
 
 /// Perfetto writer
+///
+/// Packets of multiple processes can be multiplexed into one trace: each process gets its own
+/// process track and packet sequence, derived from the process id of the connection the packets
+/// have been received on.
+///
+/// Event names, categories, source locations and annotation names are interned per sequence,
+/// so that each string is written once per process instead of once per packet.
+///
+/// Clock syncs of secondary clock domains, e.g. of a GPU, are written as clock snapshots relating the domain
+/// to the trace clock. Events timestamped on a domain refer to its clock, so that the trace processor
+/// converts their timestamps with the snapshots of their process.
+///
+/// Context switches captured from the kernel, see [sched](crate::sched), are written as ftrace events if a
+/// connected process takes part, so that the trace shows on which CPU the threads of the process ran.
 pub struct Perfetto<W> {
     writer: (W, u64),
     spans: HashMap<(u32, u64), Span>,
+    /// Random base of the process track uuids of this trace
     track_uuid: TrackUuid,
+    /// Random base of the packet sequence ids of this trace
     sequence_id: SequenceId,
+    /// Track names learned from feo metadata
+    names: TrackNames,
+    /// Latest samples sent per topic
+    flows: DataFlows,
+    /// Interning state of the packet sequence of each process
+    sequences: HashMap<u32, SequenceState>,
+    /// Connected processes, whose context switches are written
+    processes: HashSet<u32>,
+    /// Threads of connected processes described for their context switches, as pid and tid
+    sched_threads: HashSet<(u32, u32)>,
 }
 
 impl<W> Drop for Perfetto<W> {
@@ -80,6 +142,11 @@ impl<W: io::Write> Perfetto<W> {
             spans,
             track_uuid,
             sequence_id,
+            names: TrackNames::default(),
+            flows: DataFlows::default(),
+            sequences: HashMap::new(),
+            processes: HashSet::new(),
+            sched_threads: HashSet::new(),
         }
     }
 
@@ -88,6 +155,7 @@ impl<W: io::Write> Perfetto<W> {
         let process = message.process;
         let thread = message.thread;
         let timestamp_nanos = message.timestamp.duration_since(UNIX_EPOCH)?.as_nanos() as u64;
+        let clock = message.clock;
 
         // Map record to event. This is unfortunately not possible directly in the match
         // below because the types of the fields differ.
@@ -101,48 +169,54 @@ impl<W: io::Write> Perfetto<W> {
         };
 
         match data {
-            RecordData::Exec => (),
+            RecordData::Exec => {
+                self.processes.insert(pid);
+                // Announce the process, even if it does not emit any spans or events
+                let trace = idl::Trace {
+                    packet: vec![self.process_descriptor(pid, process.name.as_deref())],
+                };
+                self.append(trace)?;
+            },
             RecordData::Exit => {
                 // Remove all spans that belong to the process
                 self.spans.retain(|_, span| span.pid != pid);
+                self.names.remove_process(pid);
+                // A process reusing the pid starts the sequence with a cleared state
+                self.sequences.remove(&pid);
+                self.processes.remove(&pid);
+                self.sched_threads.retain(|(thread_pid, _)| *thread_pid != pid);
             },
             RecordData::NewSpan { id, name, info } => {
                 let key = (pid, id);
                 assert!(!self.spans.contains_key(&key));
 
                 let thread = thread.expect("missing thread info in new span");
+                let metadata = self.names.observe(pid, Some(thread.id), &info);
 
                 let trace = {
-                    // There's the process, thread, and the span itself
-                    let mut packet = Vec::with_capacity(5);
+                    // There's the process, thread, optionally the activity or chain, and the span itself
+                    let mut packet = Vec::with_capacity(6);
                     packet.push(self.process_descriptor(pid, process.name.as_deref()));
                     packet.push(self.thread_descriptor(pid, thread.id, thread.name.as_deref()));
+                    packet.extend(self.metadata_descriptor(pid, metadata));
                     idl::Trace { packet }
                 };
 
-                self.spans.insert(key, Span::new(pid, thread, trace, name, info));
+                let track = event_track(pid, thread.id, metadata);
+                self.spans.insert(key, Span::new(pid, thread, track, trace, name, info));
             },
             RecordData::EnterSpan { id } => {
-                let sequence_id = self.sequence_id();
+                let sequence_id = self.sequence_id(pid);
                 let Some(span) = self.spans.get_mut(&(pid, id)) else {
                     return Ok(());
                 };
 
-                let annotation = debug_annotation(span.info.name.clone(), span.info.value.clone());
-                let debug_annotations = debug_annotations(&[annotation]);
-                let thread_track_uuid = span.thread.id;
-                let event = create_event(
-                    thread_track_uuid as u64,
-                    Some(span.name.as_str()),
-                    debug_annotations,
-                    Some(idl::track_event::Type::SliceBegin),
-                );
+                let packet = with_metadata(EventBuilder::slice_begin(span.track, span.name.as_str()), &span.info)
+                    .annotations(debug_annotations(&span.info))
+                    .packet(timestamp_nanos, sequence_id);
                 let packet = idl::TracePacket {
-                    data: Some(idl::trace_packet::Data::TrackEvent(event)),
-                    timestamp: Some(timestamp_nanos),
                     trusted_pid: Some(pid as _),
-                    optional_trusted_packet_sequence_id: Some(sequence_id),
-                    ..Default::default()
+                    ..packet
                 };
 
                 span.trace.packet.push(packet);
@@ -153,29 +227,73 @@ impl<W: io::Write> Perfetto<W> {
                     return Ok(());
                 };
 
-                let thread = &span.thread;
-                let span_name = span.name.as_str();
-                let debug_annotations = None;
-                let event = create_event(
-                    thread.id as u64,
-                    Some(span_name),
-                    debug_annotations,
-                    Some(idl::track_event::Type::SliceEnd),
-                );
+                let packet = EventBuilder::slice_end(span.track)
+                    .name(span.name.as_str())
+                    .packet(timestamp_nanos, self.sequence_id(pid));
                 let packet = idl::TracePacket {
-                    data: Some(idl::trace_packet::Data::TrackEvent(event)),
-                    timestamp: Some(timestamp_nanos),
                     trusted_pid: Some(pid as _),
-                    optional_trusted_packet_sequence_id: Some(self.sequence_id()),
-                    ..Default::default()
+                    ..packet
                 };
 
                 span.trace.packet.push(packet);
 
                 // Flush
-                self.append(&span.trace)?;
+                self.append(span.trace)?;
             },
 
+            RecordData::Counter { name, value } => {
+                let track = counter_track_uuid(pid, &name);
+                let event = match value {
+                    CounterValue::I64(value) => EventBuilder::counter(track, value),
+                    CounterValue::F64(value) => EventBuilder::double_counter(track, value),
+                };
+                let trace = idl::Trace {
+                    packet: vec![
+                        self.process_descriptor(pid, process.name.as_deref()),
+                        self.counter_descriptor(pid, &name),
+                        idl::TracePacket {
+                            trusted_pid: Some(pid as _),
+                            ..event.packet(timestamp_nanos, self.sequence_id(pid))
+                        },
+                    ],
+                };
+                self.append(trace)?;
+            },
+            RecordData::ClockSync(clock) => {
+                let clocks = [(TRACE_CLOCK_ID, timestamp_nanos), (domain_clock_id(clock), clock.timestamp)];
+                let packet = idl::TracePacket {
+                    trusted_pid: Some(pid as _),
+                    ..builders::clock_snapshot(clocks, self.sequence_id(pid))
+                };
+                self.append(idl::Trace { packet: vec![packet] })?;
+            },
+            RecordData::Sched(SchedSwitches { cpu, switches }) => {
+                let mut packet = Vec::new();
+                let mut events = Vec::new();
+                for switch in switches {
+                    let connected: Vec<(u32, &SchedThread)> = [&switch.prev, &switch.next]
+                        .into_iter()
+                        .filter_map(|thread| Some((thread.pid?, thread)))
+                        .filter(|(pid, _)| self.processes.contains(pid))
+                        .collect();
+                    if connected.is_empty() {
+                        continue;
+                    }
+                    // Describe the threads once, so that they are shown as part of their process
+                    for (pid, thread) in connected {
+                        if self.sched_threads.insert((pid, thread.tid)) {
+                            packet.push(self.thread_descriptor(pid, thread.tid, Some(thread.comm.as_str())));
+                        }
+                    }
+                    events.push(sched_switch_event(&switch));
+                }
+                if events.is_empty() {
+                    return Ok(());
+                }
+                // The kernel gets a packet sequence of its own
+                packet.push(builders::ftrace_events(cpu, events, self.sequence_id(0)));
+                self.append(idl::Trace { packet })?;
+            },
             RecordData::Record { .. } => unreachable!(),
             RecordData::Event {
                 parent_span,
@@ -185,21 +303,39 @@ impl<W: io::Write> Perfetto<W> {
                 let Some(tid) = thread.as_ref().map(|t| t.id) else {
                     bail!("missing thread info in exit span");
                 };
-                let annotation = debug_annotation(info.name, info.value);
-                let debug_annotations = debug_annotations(&[annotation]);
-                let track_event = create_event(
-                    tid as u64,
-                    Some(name.as_str()),
-                    debug_annotations,
-                    Some(idl::track_event::Type::Instant),
-                );
-                let packet = perfetto_model::TracePacket {
-                    data: Some(idl::trace_packet::Data::TrackEvent(track_event)),
+                if let Some((topic, latency)) = flows::latency(&name, &info) {
+                    let trace = idl::Trace {
+                        packet: vec![
+                            self.process_descriptor(pid, process.name.as_deref()),
+                            self.latency_descriptor(pid, topic),
+                            idl::TracePacket {
+                                trusted_pid: Some(pid as _),
+                                ..EventBuilder::counter(latency_track_uuid(pid, topic), latency as i64)
+                                    .packet(timestamp_nanos, self.sequence_id(pid))
+                            },
+                        ],
+                    };
+                    return self.append(trace);
+                }
+                let metadata = self.names.observe(pid, Some(tid), &info);
+                let flow = self.flows.observe(pid, tid, &name, &info);
+                let event = EventBuilder::instant(event_track(pid, tid, metadata), name.as_str());
+                let mut event = with_metadata(event, &info).annotations(debug_annotations(&info));
+                if let Some(flow) = flow {
+                    event = event.flow(flow.id);
+                    // Received samples carry the sequence number of the sample they are connected to
+                    if name == RECEIVE_EVENT {
+                        event = event.annotation(SEQUENCE_FIELD, flow.sequence);
+                    }
+                }
+                let mut packet = idl::TracePacket {
                     trusted_pid: Some(pid as _),
-                    timestamp: Some(timestamp_nanos),
-                    optional_trusted_packet_sequence_id: Some(self.sequence_id()),
-                    ..Default::default()
+                    ..event.packet(timestamp_nanos, self.sequence_id(pid))
                 };
+                if let Some(clock) = clock {
+                    packet.timestamp = Some(clock.timestamp);
+                    packet.timestamp_clock_id = Some(domain_clock_id(clock));
+                }
 
                 // If the event is associated with a span, append to the span.
                 if let Some(span) = parent_span.and_then(|id| self.spans.get_mut(&(pid, id))) {
@@ -208,17 +344,18 @@ impl<W: io::Write> Perfetto<W> {
                 } else {
                     let process_name = process.name.as_deref();
                     let thread_name = thread.and_then(|t| t.name);
-                    let trace = idl::Trace {
+                    let mut trace = idl::Trace {
                         // Not in a span.
                         // Process and thread track *must* be present *before* the event
                         // Create the trace *after* the process and thread track give vec! a hint about the size.
                         packet: vec![
                             self.process_descriptor(pid, process_name),
                             self.thread_descriptor(pid, tid, thread_name.as_deref()),
-                            packet,
                         ],
                     };
-                    self.append(&trace)?;
+                    trace.packet.extend(self.metadata_descriptor(pid, metadata));
+                    trace.packet.push(packet);
+                    self.append(trace)?;
                 }
             },
         }
@@ -227,99 +364,162 @@ impl<W: io::Write> Perfetto<W> {
     }
 
     fn process_descriptor(&self, id: u32, name: Option<&str>) -> idl::TracePacket {
-        let mut packet = idl::TracePacket::default();
-        let process = create_process_descriptor(id, name).into();
-        let track_desc = create_track_descriptor(Some(self.track_uuid), name, process, None);
-        packet.data = Some(idl::trace_packet::Data::TrackDescriptor(track_desc));
-        packet
+        let name = self.names.process_name(id, name);
+        TrackBuilder::process(process_track_uuid(self.track_uuid, id), id, name.as_deref()).packet()
     }
 
     fn thread_descriptor(&self, tgid: u32, tid: u32, name: Option<&str>) -> idl::TracePacket {
-        let mut packet = idl::TracePacket::default();
-        let thread = create_thread_descriptor(tgid, tid).into();
-        let track_desc = create_track_descriptor(Some(tid as u64), name, None, thread);
-        packet.data = Some(idl::trace_packet::Data::TrackDescriptor(track_desc));
-        packet
+        let name = self.names.thread_name(tgid, tid, name);
+        TrackBuilder::thread(tid as u64, tgid, tid, name.as_deref()).packet()
+    }
+
+    /// Descriptor of the track of an activity, nested in the process track
+    fn activity_descriptor(&self, pid: u32, activity_id: u64) -> idl::TracePacket {
+        let name = activity_track_name(activity_id);
+        TrackBuilder::custom(activity_track_uuid(pid, activity_id), name.as_str())
+            .parent(process_track_uuid(self.track_uuid, pid))
+            .packet()
+    }
+
+    /// Descriptor of the track of a task chain, nested in the process track
+    fn chain_descriptor(&self, pid: u32, chain_id: u64) -> idl::TracePacket {
+        let name = chain_track_name(chain_id);
+        TrackBuilder::custom(chain_track_uuid(pid, chain_id), name.as_str())
+            .parent(process_track_uuid(self.track_uuid, pid))
+            .packet()
+    }
+
+    /// Descriptor of the dedicated track of the given feo metadata, if any
+    fn metadata_descriptor(&self, pid: u32, metadata: Option<FeoMetadata>) -> Option<idl::TracePacket> {
+        match metadata {
+            Some(FeoMetadata::Activity(activity_id)) => Some(self.activity_descriptor(pid, activity_id)),
+            Some(FeoMetadata::Chain(chain_id)) => Some(self.chain_descriptor(pid, chain_id)),
+            _ => None,
+        }
+    }
+
+    /// Descriptor of the latency counter track of a topic, nested in the process track
+    fn latency_descriptor(&self, pid: u32, topic: &str) -> idl::TracePacket {
+        let name = latency_track_name(topic);
+        TrackBuilder::counter(
+            latency_track_uuid(pid, topic),
+            name.as_str(),
+            idl::counter_descriptor::Unit::TimeNs,
+        )
+        .parent(process_track_uuid(self.track_uuid, pid))
+        .packet()
+    }
+
+    /// Descriptor of the track of a counter emitted by `feo_tracing::counter!`, nested in the process track
+    fn counter_descriptor(&self, pid: u32, name: &str) -> idl::TracePacket {
+        TrackBuilder::counter(
+            counter_track_uuid(pid, name),
+            name,
+            idl::counter_descriptor::Unit::Unspecified,
+        )
+        .parent(process_track_uuid(self.track_uuid, pid))
+        .packet()
+    }
+
+    /// Flush all buffered trace packets to the underlying writer.
+    ///
+    /// Packets are appended as self-contained `Trace` messages, so the output is a valid trace after each flush.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.0.flush()?;
+        Ok(())
     }
 
     /// Append a trace packet to the writer. Serialized into proto and written to the writer.
-    fn append(&mut self, packet: &idl::Trace) -> Result<(), Error> {
-        let buf = packet.encode_to_vec();
+    ///
+    /// The track events are interned here, as packets must be interned in the order they are written.
+    fn append(&mut self, mut trace: idl::Trace) -> Result<(), Error> {
+        for packet in trace.packet.iter_mut() {
+            let (Some(pid), Some(timestamp)) = (packet.trusted_pid, packet.timestamp) else {
+                continue;
+            };
+            // Timestamps on secondary clock domains are not comparable to the trace clock
+            let on_trace_clock = packet.timestamp_clock_id.is_none();
+            let state = self.sequences.entry(pid as u32).or_insert_with(|| SequenceState {
+                interner: Interner::new(),
+                cleared_at: if on_trace_clock { timestamp } else { 0 },
+            });
+            if on_trace_clock && timestamp.saturating_sub(state.cleared_at) > CLEAR_PERIOD_NANOS {
+                state.interner.reset();
+                state.cleared_at = timestamp;
+            }
+            state.interner.intern(packet);
+        }
+        let buf = trace.encode_to_vec();
         self.writer.0.write_all(&buf)?;
         self.writer.1 += buf.len() as u64;
         Ok(())
     }
 
-    fn sequence_id(&self) -> idl::trace_packet::OptionalTrustedPacketSequenceId {
-        idl::trace_packet::OptionalTrustedPacketSequenceId::TrustedPacketSequenceId(self.sequence_id)
+    /// Packet sequence of the given process
+    ///
+    /// Perfetto expects the packets of a sequence to be written by a single producer,
+    /// so each process gets its own sequence.
+    fn sequence_id(&self, pid: u32) -> SequenceId {
+        self.sequence_id ^ pid
     }
 }
 
-fn create_process_descriptor(tgid: u32, name: Option<&str>) -> idl::ProcessDescriptor {
-    perfetto_model::ProcessDescriptor {
-        pid: Some(tgid as _),
-        process_name: name.map(str::to_string),
-        ..Default::default()
-    }
+/// Clock id of the secondary clock domain of the given timestamp
+fn domain_clock_id(clock: ClockTimestamp) -> u32 {
+    FIRST_DOMAIN_CLOCK_ID + u32::from(clock.domain)
 }
 
-fn create_thread_descriptor(tgid: u32, tid: u32) -> idl::ThreadDescriptor {
-    perfetto_model::ThreadDescriptor {
-        pid: Some(tgid as _),
-        tid: Some(tid as _),
+/// Ftrace event of a context switch
+fn sched_switch_event(switch: &SchedSwitch) -> idl::FtraceEvent {
+    let event = idl::SchedSwitchFtraceEvent {
+        prev_comm: Some(switch.prev.comm.clone()),
+        prev_pid: Some(switch.prev.tid as _),
+        prev_prio: Some(switch.prev_prio),
+        prev_state: Some(switch.prev_state),
+        next_comm: Some(switch.next.comm.clone()),
+        next_pid: Some(switch.next.tid as _),
+        next_prio: Some(switch.next_prio),
+    };
+    idl::FtraceEvent {
+        timestamp: Some(switch.timestamp),
+        pid: Some(switch.prev.tid),
+        event: Some(idl::ftrace_event::Event::SchedSwitch(event)),
         ..Default::default()
     }
 }
 
-fn create_track_descriptor(
-    uuid: Option<u64>,
-    name: Option<&str>,
-    process: Option<idl::ProcessDescriptor>,
-    thread: Option<idl::ThreadDescriptor>,
-) -> idl::TrackDescriptor {
-    perfetto_model::TrackDescriptor {
-        uuid,
-        static_or_dynamic_name: name
-            .map(|s| s.to_string())
-            .map(idl::track_descriptor::StaticOrDynamicName::Name),
-        process,
-        thread,
-        ..Default::default()
+/// Track of a span or event: the activity or chain track if known, the thread track otherwise
+fn event_track(pid: u32, tid: u32, metadata: Option<FeoMetadata>) -> TrackUuid {
+    match metadata {
+        Some(FeoMetadata::Activity(activity_id)) => activity_track_uuid(pid, activity_id),
+        Some(FeoMetadata::Chain(chain_id)) => chain_track_uuid(pid, chain_id),
+        _ => tid as u64,
     }
 }
 
-fn create_event(
-    track_uuid: u64,
-    name: Option<&str>,
-    debug_annotations: Option<DebugAnnotations>,
-    r#type: Option<idl::track_event::Type>,
-) -> idl::TrackEvent {
-    perfetto_model::TrackEvent {
-        r#type: r#type.map(Into::into),
-        track_uuid: Some(track_uuid),
-        name_field: name.map(|name| idl::track_event::NameField::Name(name.to_string())),
-        debug_annotations: debug_annotations.map(|d| d.annotations).unwrap_or_default(),
-        ..Default::default()
+/// Set the target of a span or event as category, and its source location if known
+fn with_metadata(mut event: EventBuilder, info: &RecordEventInfo) -> EventBuilder {
+    if !info.target.is_empty() {
+        event = event.category(info.target.as_str());
     }
+    if let Some((file, line)) = &info.location {
+        event = event.source_location(file.as_str(), *line);
+    }
+    event
 }
 
-#[derive(Default)]
-struct DebugAnnotations {
-    annotations: Vec<idl::DebugAnnotation>,
-}
-
-fn debug_annotations(annotations: &[perfetto_model::DebugAnnotation]) -> Option<DebugAnnotations> {
-    Some(DebugAnnotations {
-        annotations: annotations.to_vec(),
-    })
+/// Debug annotations of the fields of an event
+fn debug_annotations(info: &RecordEventInfo) -> impl Iterator<Item = idl::DebugAnnotation> + '_ {
+    info.fields.iter().map(debug_annotation)
 }
 
-fn debug_annotation(name: Option<String>, value: String) -> idl::DebugAnnotation {
-    let name_field = name.map(idl::debug_annotation::NameField::Name);
-
-    idl::DebugAnnotation {
-        name_field,
-        value: Some(idl::debug_annotation::Value::StringValue(value)),
-        ..Default::default()
-    }
+fn debug_annotation(field: &RecordField) -> idl::DebugAnnotation {
+    let value = match &field.value {
+        RecordFieldValue::Str(value) => AnnotationValue::Str(value.clone()),
+        RecordFieldValue::I64(value) => AnnotationValue::I64(*value),
+        RecordFieldValue::U64(value) => AnnotationValue::U64(*value),
+        RecordFieldValue::F64(value) => AnnotationValue::F64(*value),
+        RecordFieldValue::Bool(value) => AnnotationValue::Bool(*value),
+    };
+    builders::annotation(field.name.as_str(), value)
 }
@@ -0,0 +1,1317 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Live forwarding of trace packets to the Perfetto tracing service
+//!
+//! In live mode, the tracer connects to the producer socket of a running `traced` as a Perfetto producer
+//! and registers the data source [DATA_SOURCE_NAME]. While a tracing session enables the data source, the
+//! packets are written into the shared memory buffer set up by the service and committed to the target
+//! buffer of the session. Like this, feo traces can be combined with ftrace or other system traces captured
+//! in the same session.
+//!
+//! Only the parts of the Perfetto IPC protocol and shared memory ABI needed by a single producer without
+//! patching or scraping of chunks are implemented.
+
+use crate::perfetto::TRACE_CLOCK_ID;
+use anyhow::{bail, Context, Error};
+use perfetto_model as idl;
+use prost::Message;
+use score_log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::{mem, ptr};
+
+/// Name of the data source registered with the tracing service
+pub const DATA_SOURCE_NAME: &str = "feo.trace";
+
+/// Environment variable overriding the path of the producer socket of the tracing service
+const PRODUCER_SOCKET_ENV: &str = "PERFETTO_PRODUCER_SOCK_NAME";
+
+/// Default paths of the producer socket, tried in this order
+const PRODUCER_SOCKETS: [&str; 2] = ["/run/perfetto/traced-producer.sock", "/tmp/perfetto-producer"];
+
+/// Name of the IPC service of the tracing service for producers
+const PRODUCER_PORT: &str = "ProducerPort";
+
+/// Requested page size of the shared memory buffer (bytes)
+const SMB_PAGE_SIZE_HINT: u32 = 32 * 1024;
+
+/// Requested size of the shared memory buffer (bytes)
+const SMB_SIZE_HINT: u32 = 4 * 1024 * 1024;
+
+/// Page size of the shared memory buffer if not set by the tracing service (bytes)
+const DEFAULT_PAGE_SIZE: usize = 4096;
+
+/// Size of the header of each page of the shared memory buffer (bytes)
+const PAGE_HEADER_SIZE: usize = 8;
+
+/// Size of the header of each chunk of the shared memory buffer (bytes)
+const CHUNK_HEADER_SIZE: usize = 8;
+
+/// Size of the redundant varint preceding each packet fragment in a chunk (bytes)
+const FRAGMENT_SIZE_FIELD: usize = 4;
+
+/// Maximum number of packet fragments per chunk
+const MAX_FRAGMENTS: u16 = (1 << 10) - 1;
+
+/// Position of the layout in the page header, which is followed by two bits of state per chunk
+const LAYOUT_SHIFT: u32 = 28;
+
+/// Layout of a page holding a single chunk
+const PAGE_DIV1: u32 = 1;
+
+/// Chunk states
+const CHUNK_FREE: u32 = 0;
+const CHUNK_BEING_WRITTEN: u32 = 1;
+const CHUNK_COMPLETE: u32 = 3;
+const CHUNK_STATE_MASK: u32 = 3;
+
+/// Chunk flags
+const FIRST_PACKET_CONTINUES_FROM_PREV_CHUNK: u16 = 1 << 0;
+const LAST_PACKET_CONTINUES_ON_NEXT_CHUNK: u16 = 1 << 1;
+
+/// Messages of the Perfetto IPC protocol and the producer port
+mod ipc {
+    use perfetto_model::DataSourceConfig;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Frame {
+        #[prost(uint64, optional, tag = "2")]
+        pub request_id: Option<u64>,
+        #[prost(oneof = "Msg", tags = "3, 4, 5, 6, 7")]
+        pub msg: Option<Msg>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Msg {
+        #[prost(message, tag = "3")]
+        BindService(BindService),
+        #[prost(message, tag = "4")]
+        BindServiceReply(BindServiceReply),
+        #[prost(message, tag = "5")]
+        InvokeMethod(InvokeMethod),
+        #[prost(message, tag = "6")]
+        InvokeMethodReply(InvokeMethodReply),
+        #[prost(message, tag = "7")]
+        RequestError(RequestError),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BindService {
+        #[prost(string, optional, tag = "1")]
+        pub service_name: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BindServiceReply {
+        #[prost(bool, optional, tag = "1")]
+        pub success: Option<bool>,
+        #[prost(uint32, optional, tag = "2")]
+        pub service_id: Option<u32>,
+        #[prost(message, repeated, tag = "3")]
+        pub methods: Vec<MethodInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MethodInfo {
+        #[prost(uint32, optional, tag = "1")]
+        pub id: Option<u32>,
+        #[prost(string, optional, tag = "2")]
+        pub name: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct InvokeMethod {
+        #[prost(uint32, optional, tag = "1")]
+        pub service_id: Option<u32>,
+        #[prost(uint32, optional, tag = "2")]
+        pub method_id: Option<u32>,
+        #[prost(bytes = "vec", optional, tag = "3")]
+        pub args_proto: Option<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct InvokeMethodReply {
+        #[prost(bool, optional, tag = "1")]
+        pub success: Option<bool>,
+        #[prost(bool, optional, tag = "2")]
+        pub has_more: Option<bool>,
+        #[prost(bytes = "vec", optional, tag = "3")]
+        pub reply_proto: Option<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RequestError {
+        #[prost(string, optional, tag = "1")]
+        pub error: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct InitializeConnectionRequest {
+        #[prost(uint32, optional, tag = "1")]
+        pub shared_memory_page_size_hint_bytes: Option<u32>,
+        #[prost(uint32, optional, tag = "2")]
+        pub shared_memory_size_hint_bytes: Option<u32>,
+        #[prost(string, optional, tag = "3")]
+        pub producer_name: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RegisterDataSourceRequest {
+        #[prost(message, optional, tag = "1")]
+        pub data_source_descriptor: Option<DataSourceDescriptor>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DataSourceDescriptor {
+        #[prost(string, optional, tag = "1")]
+        pub name: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetAsyncCommandRequest {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetAsyncCommandResponse {
+        #[prost(oneof = "Command", tags = "1, 2, 3, 5, 6")]
+        pub cmd: Option<Command>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Command {
+        #[prost(message, tag = "1")]
+        StartDataSource(StartDataSource),
+        #[prost(message, tag = "2")]
+        StopDataSource(StopDataSource),
+        #[prost(message, tag = "3")]
+        SetupTracing(SetupTracing),
+        #[prost(message, tag = "5")]
+        Flush(Flush),
+        #[prost(message, tag = "6")]
+        SetupDataSource(StartDataSource),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StartDataSource {
+        #[prost(uint64, optional, tag = "1")]
+        pub new_instance_id: Option<u64>,
+        #[prost(message, optional, tag = "2")]
+        pub config: Option<DataSourceConfig>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StopDataSource {
+        #[prost(uint64, optional, tag = "1")]
+        pub instance_id: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SetupTracing {
+        #[prost(uint32, optional, tag = "1")]
+        pub shared_buffer_page_size_kb: Option<u32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Flush {
+        #[prost(uint64, repeated, packed = "false", tag = "1")]
+        pub data_source_ids: Vec<u64>,
+        #[prost(uint64, optional, tag = "2")]
+        pub request_id: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommitDataRequest {
+        #[prost(message, repeated, tag = "1")]
+        pub chunks_to_move: Vec<ChunksToMove>,
+        #[prost(uint64, optional, tag = "3")]
+        pub flush_request_id: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ChunksToMove {
+        #[prost(uint32, optional, tag = "1")]
+        pub page: Option<u32>,
+        #[prost(uint32, optional, tag = "2")]
+        pub chunk: Option<u32>,
+        #[prost(uint32, optional, tag = "3")]
+        pub target_buffer: Option<u32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DataSourceRequest {
+        #[prost(uint64, optional, tag = "1")]
+        pub data_source_id: Option<u64>,
+    }
+}
+
+/// Path of the producer socket of the tracing service
+pub fn producer_socket() -> PathBuf {
+    if let Some(path) = std::env::var_os(PRODUCER_SOCKET_ENV) {
+        return PathBuf::from(path);
+    }
+    PRODUCER_SOCKETS
+        .iter()
+        .map(Path::new)
+        .find(|path| path.exists())
+        .unwrap_or(Path::new(PRODUCER_SOCKETS[0]))
+        .to_path_buf()
+}
+
+/// Writer forwarding a Perfetto trace to the tracing service
+///
+/// Takes the serialized `Trace` messages written by [Perfetto](crate::perfetto::Perfetto) and writes
+/// each packet to the shared memory buffer. Since the trusted fields of the packets are set by the
+/// tracing service, the packet sequence of each packet is mapped to a trace writer of its own instead.
+/// Packets written while the data source is not enabled by any tracing session are dropped.
+pub struct ProducerWriter {
+    /// State shared with the thread receiving the commands of the tracing service
+    session: Arc<Mutex<Session>>,
+    /// Serialized trace not split into packets yet
+    buffer: Vec<u8>,
+    /// Thread receiving the commands of the tracing service
+    receiver: Option<JoinHandle<()>>,
+}
+
+impl ProducerWriter {
+    /// Connect to the tracing service at the given producer socket and register the data source
+    pub fn connect(path: &Path, producer_name: &str) -> Result<Self, Error> {
+        info!("Connecting to tracing service at {}", format!("{path:?}"));
+        let stream = UnixStream::connect(path).with_context(|| format!("failed to connect to {path:?}"))?;
+        let mut reader = FrameReader::new(stream.try_clone()?);
+        let mut channel = Channel::bind(stream, &mut reader)?;
+
+        let request = ipc::InitializeConnectionRequest {
+            shared_memory_page_size_hint_bytes: Some(SMB_PAGE_SIZE_HINT),
+            shared_memory_size_hint_bytes: Some(SMB_SIZE_HINT),
+            producer_name: Some(producer_name.to_string()),
+        };
+        let id = channel.invoke("InitializeConnection", &request)?;
+        reader.wait_reply(id)?;
+
+        let request = ipc::RegisterDataSourceRequest {
+            data_source_descriptor: Some(ipc::DataSourceDescriptor {
+                name: Some(DATA_SOURCE_NAME.to_string()),
+            }),
+        };
+        let id = channel.invoke("RegisterDataSource", &request)?;
+        reader.wait_reply(id)?;
+
+        let commands = channel.invoke("GetAsyncCommand", &ipc::GetAsyncCommandRequest {})?;
+        info!("Registered data source {} with tracing service", DATA_SOURCE_NAME);
+
+        let session = Arc::new(Mutex::new(Session::new(channel)));
+        let receiver = {
+            let session = session.clone();
+            thread::Builder::new()
+                .name("feo-tracer-producer".to_string())
+                .spawn(move || receive_commands(reader, commands, session))?
+        };
+
+        Ok(Self {
+            session,
+            buffer: Vec::new(),
+            receiver: Some(receiver),
+        })
+    }
+
+    /// Split the buffered trace into packets and forward them
+    fn forward_packets(&mut self) -> io::Result<()> {
+        let mut consumed = 0;
+        while let Some((packet, len)) = next_packet(&self.buffer[consumed..])? {
+            let (sequence, packet) = prepare_packet(packet)?;
+            self.session
+                .lock()
+                .expect("poisoned lock")
+                .write_packet(sequence, &packet)
+                .map_err(io::Error::other)?;
+            consumed += len;
+        }
+        self.buffer.drain(..consumed);
+        Ok(())
+    }
+}
+
+impl Write for ProducerWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        self.forward_packets()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.session
+            .lock()
+            .expect("poisoned lock")
+            .commit_chunks(None)
+            .map_err(io::Error::other)
+    }
+}
+
+impl Drop for ProducerWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to commit trace data: {}", format!("{e:?}"));
+        }
+        // The receiving thread exits when the tracing service closes the connection
+        if let Ok(session) = self.session.lock() {
+            let _ = session.channel.stream.shutdown(std::net::Shutdown::Both);
+        }
+        if let Some(receiver) = self.receiver.take() {
+            let _ = receiver.join();
+        }
+    }
+}
+
+/// Get the next packet of a serialized `Trace` and the number of bytes it occupies, if complete
+fn next_packet(buffer: &[u8]) -> io::Result<Option<(&[u8], usize)>> {
+    let Some((tag, tag_len)) = read_varint(buffer) else {
+        return Ok(None);
+    };
+    // Packets are field 1 of `Trace`, length-delimited
+    if tag != 0x0a {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected field in trace"));
+    }
+    let Some((len, len_len)) = read_varint(&buffer[tag_len..]) else {
+        return Ok(None);
+    };
+    let start = tag_len + len_len;
+    let end = start + len as usize;
+    Ok(buffer.get(start..end).map(|packet| (packet, end)))
+}
+
+/// Read a varint from the start of the buffer, returning the value and its length, if complete
+fn read_varint(buffer: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (index, byte) in buffer.iter().take(10).enumerate() {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+/// Get the packet sequence of a packet and the packet as accepted by the tracing service
+///
+/// Removes the trusted fields, which are set by the tracing service, and marks the timestamp
+/// as wall clock time, so that it is converted to the clock of the other packets of the trace.
+/// Clock snapshots relate secondary clock domains to the wall clock time likewise.
+fn prepare_packet(packet: &[u8]) -> io::Result<(u32, Vec<u8>)> {
+    let mut packet = idl::TracePacket::decode(packet).map_err(io::Error::other)?;
+    let sequence = match packet.optional_trusted_packet_sequence_id.take() {
+        Some(idl::trace_packet::OptionalTrustedPacketSequenceId::TrustedPacketSequenceId(id)) => id,
+        None => 0,
+    };
+    packet.optional_trusted_uid = None;
+    packet.trusted_pid = None;
+    if packet.timestamp.is_some() && packet.timestamp_clock_id.is_none() {
+        packet.timestamp_clock_id = Some(idl::BuiltinClock::Realtime as u32);
+    }
+    if let Some(idl::trace_packet::Data::ClockSnapshot(snapshot)) = packet.data.as_mut() {
+        for clock in snapshot.clocks.iter_mut().filter(|clock| clock.clock_id == Some(TRACE_CLOCK_ID)) {
+            clock.clock_id = Some(idl::BuiltinClock::Realtime as u32);
+        }
+    }
+    Ok((sequence, packet.encode_to_vec()))
+}
+
+/// Producer state shared between the writer and the thread receiving commands
+struct Session {
+    /// Sending side of the connection to the tracing service
+    channel: Channel,
+    /// Shared memory buffer, once set up by the tracing service
+    smb: Option<SharedMemory>,
+    /// Instance and target buffer of the data source, while enabled by a tracing session
+    instance: Option<(u64, u32)>,
+    /// Trace writer per packet sequence
+    writers: HashMap<u32, TraceWriter>,
+    /// Whether the tracing service is still connected
+    connected: bool,
+}
+
+impl Session {
+    fn new(channel: Channel) -> Self {
+        Self {
+            channel,
+            smb: None,
+            instance: None,
+            writers: HashMap::new(),
+            connected: true,
+        }
+    }
+
+    /// Write a packet of the given sequence into the shared memory buffer
+    ///
+    /// Packets not fitting into the current chunk of their writer are continued in the next chunk.
+    fn write_packet(&mut self, sequence: u32, packet: &[u8]) -> Result<(), Error> {
+        if !self.connected {
+            bail!("connection to tracing service closed");
+        }
+        let (Some((_, target_buffer)), Some(smb)) = (self.instance, self.smb.as_mut()) else {
+            return Ok(());
+        };
+        let next_writer_id = self.writers.len() as u16 + 1;
+        let writer = self
+            .writers
+            .entry(sequence)
+            .or_insert_with(|| TraceWriter::new(next_writer_id));
+
+        let mut remaining = packet;
+        let mut flags = 0;
+        while !remaining.is_empty() {
+            if writer.chunk.is_none() {
+                let Some(page) = smb.acquire_page() else {
+                    warn!("Shared memory buffer full, dropping trace packet");
+                    return Ok(());
+                };
+                writer.chunk = Some(writer.open_chunk(smb, page, flags));
+            }
+            let chunk = writer.chunk.as_mut().expect("missing chunk");
+
+            let free = smb.chunk_size() - CHUNK_HEADER_SIZE - chunk.offset;
+            if free <= FRAGMENT_SIZE_FIELD || chunk.fragments == MAX_FRAGMENTS {
+                let chunk = writer.chunk.take().expect("missing chunk");
+                self.channel.commit(smb, &[chunk], target_buffer, None)?;
+                continue;
+            }
+            let len = remaining.len().min(free - FRAGMENT_SIZE_FIELD);
+            smb.write_fragment(chunk, &remaining[..len]);
+            remaining = &remaining[len..];
+
+            if !remaining.is_empty() {
+                // Continue the packet in the next chunk
+                chunk.flags |= LAST_PACKET_CONTINUES_ON_NEXT_CHUNK;
+                flags = FIRST_PACKET_CONTINUES_FROM_PREV_CHUNK;
+                let chunk = writer.chunk.take().expect("missing chunk");
+                self.channel.commit(smb, &[chunk], target_buffer, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Complete the chunks of all writers and commit them to the target buffer
+    ///
+    /// Acknowledges the given flush request of the tracing service, if any.
+    fn commit_chunks(&mut self, flush_request_id: Option<u64>) -> Result<(), Error> {
+        let chunks: Vec<_> = self
+            .writers
+            .values_mut()
+            .filter_map(|writer| writer.chunk.take())
+            .collect();
+        match (self.instance, self.smb.as_ref()) {
+            (Some((_, target_buffer)), Some(smb)) => self.channel.commit(smb, &chunks, target_buffer, flush_request_id),
+            _ if flush_request_id.is_some() => self.channel.commit_empty(flush_request_id),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle a command of the tracing service
+    fn on_command(&mut self, command: ipc::Command, fd: Option<OwnedFd>) -> Result<(), Error> {
+        match command {
+            ipc::Command::SetupTracing(setup) => {
+                let Some(fd) = fd else {
+                    bail!("tracing service did not pass a shared memory buffer");
+                };
+                let page_size = match setup.shared_buffer_page_size_kb.unwrap_or_default() {
+                    0 => DEFAULT_PAGE_SIZE,
+                    kb => kb as usize * 1024,
+                };
+                debug!("Mapping shared memory buffer with pages of {} bytes", page_size);
+                self.smb = Some(SharedMemory::map(fd, page_size)?);
+            },
+            ipc::Command::SetupDataSource(_) => {},
+            ipc::Command::StartDataSource(start) => {
+                let config = start.config.unwrap_or_default();
+                if config.name.as_deref() != Some(DATA_SOURCE_NAME) {
+                    return Ok(());
+                }
+                let instance = start.new_instance_id.unwrap_or_default();
+                let target_buffer = config.target_buffer.unwrap_or_default();
+                info!("Data source started, writing to target buffer {}", target_buffer);
+                self.instance = Some((instance, target_buffer));
+                self.writers.clear();
+                let request = ipc::DataSourceRequest {
+                    data_source_id: Some(instance),
+                };
+                self.channel.invoke("NotifyDataSourceStarted", &request)?;
+            },
+            ipc::Command::StopDataSource(stop) => {
+                let instance = stop.instance_id.unwrap_or_default();
+                if self.instance.map(|(id, _)| id) != Some(instance) {
+                    return Ok(());
+                }
+                self.commit_chunks(None)?;
+                info!("Data source stopped");
+                self.instance = None;
+                let request = ipc::DataSourceRequest {
+                    data_source_id: Some(instance),
+                };
+                self.channel.invoke("NotifyDataSourceStopped", &request)?;
+            },
+            ipc::Command::Flush(flush) => self.commit_chunks(flush.request_id)?,
+        }
+        Ok(())
+    }
+}
+
+/// Receive the commands of the tracing service until the connection is closed
+fn receive_commands(mut reader: FrameReader, commands: u64, session: Arc<Mutex<Session>>) {
+    loop {
+        let frame = match reader.read_frame() {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to receive from tracing service: {}", format!("{e:?}"));
+                break;
+            },
+        };
+        let reply = match frame.msg {
+            Some(ipc::Msg::InvokeMethodReply(reply)) if frame.request_id == Some(commands) => reply,
+            Some(ipc::Msg::InvokeMethodReply(reply)) => {
+                if reply.success != Some(true) {
+                    warn!(
+                        "Tracing service rejected request {}",
+                        frame.request_id.unwrap_or_default()
+                    );
+                }
+                continue;
+            },
+            Some(ipc::Msg::RequestError(error)) => {
+                warn!("Tracing service reported error: {}", error.error.unwrap_or_default());
+                continue;
+            },
+            _ => continue,
+        };
+        let response = reply
+            .reply_proto
+            .as_deref()
+            .map(ipc::GetAsyncCommandResponse::decode)
+            .transpose();
+        let Ok(Some(ipc::GetAsyncCommandResponse { cmd: Some(command) })) = response else {
+            continue;
+        };
+        let fd = reader.fd.take();
+        if let Err(e) = session.lock().expect("poisoned lock").on_command(command, fd) {
+            warn!("Failed to handle command of tracing service: {}", format!("{e:?}"));
+        }
+    }
+    info!("Connection to tracing service closed");
+    session.lock().expect("poisoned lock").connected = false;
+}
+
+/// Sending side of the IPC connection to the producer port of the tracing service
+struct Channel {
+    stream: UnixStream,
+    service_id: u32,
+    methods: HashMap<String, u32>,
+    next_request_id: u64,
+}
+
+impl Channel {
+    /// Bind to the producer port
+    fn bind(mut stream: UnixStream, reader: &mut FrameReader) -> Result<Self, Error> {
+        let frame = ipc::Frame {
+            request_id: Some(0),
+            msg: Some(ipc::Msg::BindService(ipc::BindService {
+                service_name: Some(PRODUCER_PORT.to_string()),
+            })),
+        };
+        send_frame(&mut stream, &frame)?;
+        let reply = loop {
+            let Some(frame) = reader.read_frame()? else {
+                bail!("tracing service closed the connection");
+            };
+            match frame.msg {
+                Some(ipc::Msg::BindServiceReply(reply)) if frame.request_id == Some(0) => break reply,
+                Some(ipc::Msg::RequestError(error)) => {
+                    bail!("failed to bind producer port: {}", error.error.unwrap_or_default())
+                },
+                _ => continue,
+            }
+        };
+        if reply.success != Some(true) {
+            bail!("tracing service refused binding the producer port");
+        }
+        let methods = reply
+            .methods
+            .into_iter()
+            .filter_map(|method| Some((method.name?, method.id?)))
+            .collect();
+
+        Ok(Self {
+            stream,
+            service_id: reply.service_id.unwrap_or_default(),
+            methods,
+            next_request_id: 1,
+        })
+    }
+
+    /// Invoke a method of the producer port, returning the request id
+    fn invoke(&mut self, method: &str, args: &impl Message) -> Result<u64, Error> {
+        let Some(method_id) = self.methods.get(method).copied() else {
+            bail!("tracing service does not provide method {method}");
+        };
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let frame = ipc::Frame {
+            request_id: Some(request_id),
+            msg: Some(ipc::Msg::InvokeMethod(ipc::InvokeMethod {
+                service_id: Some(self.service_id),
+                method_id: Some(method_id),
+                args_proto: Some(args.encode_to_vec()),
+            })),
+        };
+        send_frame(&mut self.stream, &frame)?;
+        Ok(request_id)
+    }
+
+    /// Complete the given chunks and move them to the target buffer
+    fn commit(
+        &mut self,
+        smb: &SharedMemory,
+        chunks: &[Chunk],
+        target_buffer: u32,
+        flush_request_id: Option<u64>,
+    ) -> Result<(), Error> {
+        if chunks.is_empty() && flush_request_id.is_none() {
+            return Ok(());
+        }
+        let chunks_to_move = chunks
+            .iter()
+            .map(|chunk| {
+                smb.complete(chunk);
+                ipc::ChunksToMove {
+                    page: Some(chunk.page as u32),
+                    chunk: Some(0),
+                    target_buffer: Some(target_buffer),
+                }
+            })
+            .collect();
+        let request = ipc::CommitDataRequest {
+            chunks_to_move,
+            flush_request_id,
+        };
+        self.invoke("CommitData", &request).map(|_| ())
+    }
+
+    /// Acknowledge a flush request without data
+    fn commit_empty(&mut self, flush_request_id: Option<u64>) -> Result<(), Error> {
+        let request = ipc::CommitDataRequest {
+            chunks_to_move: Vec::new(),
+            flush_request_id,
+        };
+        self.invoke("CommitData", &request).map(|_| ())
+    }
+}
+
+/// Send a frame, prefixed by its length
+fn send_frame(stream: &mut UnixStream, frame: &ipc::Frame) -> Result<(), Error> {
+    let frame = frame.encode_to_vec();
+    stream.write_all(&(frame.len() as u32).to_le_bytes())?;
+    stream.write_all(&frame)?;
+    Ok(())
+}
+
+/// Receiving side of the IPC connection to the producer port of the tracing service
+struct FrameReader {
+    stream: UnixStream,
+    /// Received bytes not decoded yet
+    buffer: Vec<u8>,
+    /// File descriptor received with the last frames
+    fd: Option<OwnedFd>,
+}
+
+impl FrameReader {
+    fn new(stream: UnixStream) -> Self {
+        Self {
+            stream,
+            buffer: Vec::new(),
+            fd: None,
+        }
+    }
+
+    /// Read the next frame, returning `None` if the connection has been closed
+    fn read_frame(&mut self) -> Result<Option<ipc::Frame>, Error> {
+        loop {
+            if let Some(len) = self.buffer.get(..4) {
+                let len = u32::from_le_bytes(len.try_into().expect("invalid length")) as usize;
+                if self.buffer.len() >= 4 + len {
+                    let frame = ipc::Frame::decode(&self.buffer[4..4 + len])?;
+                    self.buffer.drain(..4 + len);
+                    return Ok(Some(frame));
+                }
+            }
+            if self.receive()? == 0 {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Wait for the reply to the given request, failing if it was not successful
+    fn wait_reply(&mut self, request_id: u64) -> Result<(), Error> {
+        loop {
+            let Some(frame) = self.read_frame()? else {
+                bail!("tracing service closed the connection");
+            };
+            if frame.request_id != Some(request_id) {
+                continue;
+            }
+            match frame.msg {
+                Some(ipc::Msg::InvokeMethodReply(reply)) if reply.success == Some(true) => return Ok(()),
+                Some(ipc::Msg::RequestError(error)) => bail!("request failed: {}", error.error.unwrap_or_default()),
+                _ => bail!("request {request_id} failed"),
+            }
+        }
+    }
+
+    /// Receive bytes and file descriptors from the socket, returning the number of bytes
+    fn receive(&mut self) -> io::Result<usize> {
+        let mut data = [0u8; 4096];
+        let mut control = [0u64; 8];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr().cast(),
+            iov_len: data.len(),
+        };
+        // Safety: msghdr is a plain struct for which all zeros is a valid value
+        let mut message: libc::msghdr = unsafe { mem::zeroed() };
+        message.msg_iov = &mut iov;
+        message.msg_iovlen = 1;
+        message.msg_control = control.as_mut_ptr().cast();
+        message.msg_controllen = mem::size_of_val(&control) as _;
+
+        // Safety: the message refers to buffers valid for the duration of the call
+        let len = unsafe { libc::recvmsg(self.stream.as_raw_fd(), &mut message, libc::MSG_CMSG_CLOEXEC) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Safety: recvmsg filled in the control buffer and set its length, CMSG_FIRSTHDR checks the length
+        let mut header = unsafe { libc::CMSG_FIRSTHDR(&message) };
+        while !header.is_null() {
+            // Safety: the header is non-null, aligned and within the control buffer, which outlives the reference
+            let cmsg = unsafe { &*header };
+            if cmsg.cmsg_level == libc::SOL_SOCKET && cmsg.cmsg_type == libc::SCM_RIGHTS {
+                // Safety: the data of SCM_RIGHTS messages holds at least one file descriptor,
+                // possibly unaligned within the control buffer
+                let fd = unsafe { ptr::read_unaligned(libc::CMSG_DATA(header) as *const RawFd) };
+                // Safety: descriptors received with SCM_RIGHTS are installed in this process and owned by nobody else
+                self.fd = Some(unsafe { OwnedFd::from_raw_fd(fd) });
+            }
+            // Safety: the header is a control message of this message, CMSG_NXTHDR checks the remaining length
+            header = unsafe { libc::CMSG_NXTHDR(&message, header) };
+        }
+
+        let len = len as usize;
+        self.buffer.extend_from_slice(&data[..len]);
+        Ok(len)
+    }
+}
+
+/// Trace writer of a single packet sequence
+struct TraceWriter {
+    id: u16,
+    next_chunk_id: u32,
+    /// Chunk currently being written
+    chunk: Option<Chunk>,
+}
+
+impl TraceWriter {
+    fn new(id: u16) -> Self {
+        Self {
+            id,
+            next_chunk_id: 0,
+            chunk: None,
+        }
+    }
+
+    /// Start writing the single chunk of the given page
+    fn open_chunk(&mut self, smb: &mut SharedMemory, page: usize, flags: u16) -> Chunk {
+        let chunk = Chunk {
+            page,
+            offset: 0,
+            fragments: 0,
+            flags,
+        };
+        let header = smb.chunk_mut(page);
+        header[0..4].copy_from_slice(&self.next_chunk_id.to_le_bytes());
+        header[4..6].copy_from_slice(&self.id.to_le_bytes());
+        header[6..8].copy_from_slice(&0u16.to_le_bytes());
+        self.next_chunk_id = self.next_chunk_id.wrapping_add(1);
+        chunk
+    }
+}
+
+/// Chunk being written
+struct Chunk {
+    /// Page holding the chunk
+    page: usize,
+    /// Number of payload bytes written
+    offset: usize,
+    /// Number of packet fragments written
+    fragments: u16,
+    /// Chunk flags
+    flags: u16,
+}
+
+/// Shared memory buffer of the producer, divided into pages holding a single chunk each
+struct SharedMemory {
+    base: *mut u8,
+    size: usize,
+    page_size: usize,
+    /// Page to try first when acquiring a page
+    next_page: usize,
+}
+
+// Safety: the mapping is owned by the shared memory buffer and only accessed through it, the tracing service
+// accesses it through atomics of the page headers and completed chunks only
+unsafe impl Send for SharedMemory {}
+
+impl SharedMemory {
+    /// Map the shared memory buffer passed by the tracing service
+    fn map(fd: OwnedFd, page_size: usize) -> Result<Self, Error> {
+        let file = File::from(fd);
+        let size = file.metadata()?.len() as usize;
+        if page_size < PAGE_HEADER_SIZE + CHUNK_HEADER_SIZE || size < page_size {
+            bail!("invalid shared memory buffer of {size} bytes with pages of {page_size} bytes");
+        }
+        // Safety: the file descriptor is valid for the duration of the call and refers to a file of `size` bytes,
+        // the result is checked for errors before it is used
+        let base = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error()).context("failed to map shared memory buffer");
+        }
+        Ok(Self {
+            base: base.cast(),
+            size,
+            page_size,
+            next_page: 0,
+        })
+    }
+
+    /// Size of a chunk, including its header
+    fn chunk_size(&self) -> usize {
+        (self.page_size - PAGE_HEADER_SIZE) & !3
+    }
+
+    /// Layout and chunk states in the header of the given page
+    fn layout(&self, page: usize) -> &AtomicU32 {
+        // Safety: the page is within the mapping, as `page < size / page_size`. Page headers are aligned to 4 bytes,
+        // as the mapping is page aligned and the page size is a multiple of 1 KiB or the default page size.
+        // They are only accessed atomically, by both this producer and the tracing service.
+        unsafe { AtomicU32::from_ptr(self.base.add(page * self.page_size).cast()) }
+    }
+
+    /// Acquire a free page for writing its single chunk
+    fn acquire_page(&mut self) -> Option<usize> {
+        let pages = self.size / self.page_size;
+        for index in 0..pages {
+            let page = (self.next_page + index) % pages;
+            let layout = self.layout(page);
+            let current = layout.load(Ordering::Acquire);
+            let free =
+                current == 0 || (current >> LAYOUT_SHIFT == PAGE_DIV1 && current & CHUNK_STATE_MASK == CHUNK_FREE);
+            let writing = (PAGE_DIV1 << LAYOUT_SHIFT) | CHUNK_BEING_WRITTEN;
+            if free
+                && layout
+                    .compare_exchange(current, writing, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            {
+                self.next_page = page + 1;
+                return Some(page);
+            }
+        }
+        None
+    }
+
+    /// Chunk of the given page, including its header
+    fn chunk_mut(&mut self, page: usize) -> &mut [u8] {
+        let chunk_size = self.chunk_size();
+        // Safety: the chunk is within the page, as `PAGE_HEADER_SIZE + chunk_size <= page_size`, and the page is
+        // within the mapping. The page has been acquired, so it is written by this producer only until completed,
+        // and the mutable borrow of the buffer prevents other references to it.
+        unsafe { std::slice::from_raw_parts_mut(self.base.add(page * self.page_size + PAGE_HEADER_SIZE), chunk_size) }
+    }
+
+    /// Append a packet fragment preceded by its size to the chunk
+    fn write_fragment(&mut self, chunk: &mut Chunk, fragment: &[u8]) {
+        let start = CHUNK_HEADER_SIZE + chunk.offset;
+        let data = self.chunk_mut(chunk.page);
+        let len = fragment.len() as u32;
+        // Redundant varint of fixed size, as used by Perfetto for size fields reserved in advance
+        for (index, byte) in data[start..start + FRAGMENT_SIZE_FIELD].iter_mut().enumerate() {
+            *byte = (len >> (7 * index)) as u8 & 0x7f;
+            if index < FRAGMENT_SIZE_FIELD - 1 {
+                *byte |= 0x80;
+            }
+        }
+        data[start + FRAGMENT_SIZE_FIELD..start + FRAGMENT_SIZE_FIELD + fragment.len()].copy_from_slice(fragment);
+        chunk.offset += FRAGMENT_SIZE_FIELD + fragment.len();
+        chunk.fragments += 1;
+    }
+
+    /// Write the final header of the chunk and mark it as complete
+    fn complete(&self, chunk: &Chunk) {
+        let packets = chunk.fragments | (chunk.flags << 10);
+        // Safety: the packet count at offset 6 of the chunk header is within the page, which has been acquired
+        // and is written by this producer only until marked as complete below
+        unsafe {
+            let header = self.base.add(chunk.page * self.page_size + PAGE_HEADER_SIZE);
+            ptr::copy_nonoverlapping(packets.to_le_bytes().as_ptr(), header.add(6), 2);
+        }
+        let complete = (PAGE_DIV1 << LAYOUT_SHIFT) | CHUNK_COMPLETE;
+        self.layout(chunk.page).store(complete, Ordering::Release);
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        // Safety: the mapping has been created with this address and size and is not referenced anymore
+        unsafe { libc::munmap(self.base.cast(), self.size) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Target buffer of the data source instance enabled in the tests
+    const TARGET_BUFFER: u32 = 7;
+
+    /// ID of the `CommitData` method of the producer port in the tests
+    const COMMIT_DATA_METHOD: u32 = 2;
+
+    /// Descriptor of an unlinked file of the given size, standing in for the shared memory buffer
+    fn buffer_fd(test: &str, size: usize) -> OwnedFd {
+        let path = std::env::temp_dir().join(format!("feo-tracer-smb-{}-{test}", std::process::id()));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        file.set_len(size as u64).unwrap();
+        file.into()
+    }
+
+    /// Session connected to a fake tracing service, returning the receiving side of the service
+    fn connected_session() -> (Session, FrameReader) {
+        let (producer, service) = UnixStream::pair().unwrap();
+        let channel = Channel {
+            stream: producer,
+            service_id: 1,
+            methods: HashMap::from([("CommitData".to_string(), COMMIT_DATA_METHOD)]),
+            next_request_id: 1,
+        };
+        (Session::new(channel), FrameReader::new(service))
+    }
+
+    /// Session with an enabled data source writing into a shared memory buffer of the given pages
+    fn enabled_session(test: &str, pages: usize, page_size: usize) -> (Session, FrameReader) {
+        let (mut session, service) = connected_session();
+        session.smb = Some(SharedMemory::map(buffer_fd(test, pages * page_size), page_size).unwrap());
+        session.instance = Some((1, TARGET_BUFFER));
+        (session, service)
+    }
+
+    /// Pages of the chunks committed by the next `CommitData` request received by the service
+    fn committed_pages(service: &mut FrameReader) -> Vec<u32> {
+        let frame = service.read_frame().unwrap().unwrap();
+        let Some(ipc::Msg::InvokeMethod(invoke)) = frame.msg else {
+            panic!("expected method invocation");
+        };
+        assert_eq!(invoke.method_id, Some(COMMIT_DATA_METHOD));
+        let request = ipc::CommitDataRequest::decode(invoke.args_proto.unwrap_or_default().as_slice()).unwrap();
+        request
+            .chunks_to_move
+            .iter()
+            .map(|chunk| {
+                assert_eq!(chunk.target_buffer, Some(TARGET_BUFFER));
+                chunk.page.unwrap()
+            })
+            .collect()
+    }
+
+    /// Completed chunk of the given page as read by the tracing service: chunk id, writer id, flags and fragments
+    fn read_chunk(smb: &mut SharedMemory, page: usize) -> (u32, u16, u16, Vec<Vec<u8>>) {
+        let complete = (PAGE_DIV1 << LAYOUT_SHIFT) | CHUNK_COMPLETE;
+        assert_eq!(smb.layout(page).load(Ordering::Acquire), complete);
+
+        let chunk = smb.chunk_mut(page);
+        let chunk_id = u32::from_le_bytes(chunk[0..4].try_into().unwrap());
+        let writer_id = u16::from_le_bytes(chunk[4..6].try_into().unwrap());
+        let packets = u16::from_le_bytes(chunk[6..8].try_into().unwrap());
+
+        let mut fragments = Vec::new();
+        let mut offset = CHUNK_HEADER_SIZE;
+        for _ in 0..packets & MAX_FRAGMENTS {
+            let size_field = &chunk[offset..offset + FRAGMENT_SIZE_FIELD];
+            let (len, _) = read_varint(size_field).unwrap();
+            let start = offset + FRAGMENT_SIZE_FIELD;
+            fragments.push(chunk[start..start + len as usize].to_vec());
+            offset = start + len as usize;
+        }
+        (chunk_id, writer_id, packets >> 10, fragments)
+    }
+
+    /// Send the given bytes together with a file descriptor, like the tracing service passes its buffer
+    fn send_with_fd(stream: &UnixStream, data: &[u8], fd: RawFd) {
+        let mut iov = libc::iovec {
+            iov_base: data.as_ptr().cast_mut().cast(),
+            iov_len: data.len(),
+        };
+        let mut control = [0u64; 8];
+        // Safety: msghdr is a plain struct for which all zeros is a valid value. The control buffer is aligned
+        // and large enough for a single control message with one descriptor, the message refers to buffers
+        // valid for the duration of the call.
+        let len = unsafe {
+            let mut message: libc::msghdr = mem::zeroed();
+            message.msg_iov = &mut iov;
+            message.msg_iovlen = 1;
+            message.msg_control = control.as_mut_ptr().cast();
+            message.msg_controllen = libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) as _;
+            let header = libc::CMSG_FIRSTHDR(&message);
+            (*header).cmsg_level = libc::SOL_SOCKET;
+            (*header).cmsg_type = libc::SCM_RIGHTS;
+            (*header).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(header) as *mut RawFd, fd);
+            libc::sendmsg(stream.as_raw_fd(), &message, 0)
+        };
+        assert_eq!(len, data.len() as isize);
+    }
+
+    /// Encode a frame, prefixed by its length
+    fn encode_frame(frame: &ipc::Frame) -> Vec<u8> {
+        let frame = frame.encode_to_vec();
+        let mut bytes = (frame.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&frame);
+        bytes
+    }
+
+    #[test]
+    fn trace_is_split_into_packets() {
+        let trace = [0x0a, 3, 1, 2, 3, 0x0a, 0, 0x0a, 2, 4];
+        let (packet, len) = next_packet(&trace).unwrap().unwrap();
+        assert_eq!((packet, len), (&[1, 2, 3][..], 5));
+        let (packet, len) = next_packet(&trace[5..]).unwrap().unwrap();
+        assert_eq!((packet, len), (&[][..], 2));
+
+        // Incomplete packets are completed by later writes
+        assert!(next_packet(&trace[7..]).unwrap().is_none());
+        assert!(next_packet(&[0x0a]).unwrap().is_none());
+        assert!(next_packet(&[0x0a, 0x80]).unwrap().is_none());
+        assert!(next_packet(&[]).unwrap().is_none());
+
+        // Other fields than packets are rejected
+        assert!(next_packet(&[0x12, 1, 0]).is_err());
+    }
+
+    #[test]
+    fn varints_are_read() {
+        assert_eq!(read_varint(&[0x05]), Some((5, 1)));
+        assert_eq!(read_varint(&[0xac, 0x02, 0xff]), Some((300, 2)));
+        // Redundant encoding of the fragment size fields
+        assert_eq!(read_varint(&[0x81, 0x80, 0x80, 0x00]), Some((1, 4)));
+        assert_eq!(read_varint(&[0x80, 0x80]), None);
+        assert_eq!(read_varint(&[0x80; 11]), None);
+    }
+
+    #[test]
+    fn trusted_fields_are_removed_from_packets() {
+        let mut packet = idl::builders::clock_snapshot([(TRACE_CLOCK_ID, 10), (64, 20)], 3);
+        packet.timestamp = Some(10);
+        packet.trusted_pid = Some(42);
+
+        let (sequence, prepared) = prepare_packet(&packet.encode_to_vec()).unwrap();
+        assert_eq!(sequence, 3);
+        let prepared = idl::TracePacket::decode(prepared.as_slice()).unwrap();
+        assert!(prepared.optional_trusted_packet_sequence_id.is_none());
+        assert!(prepared.trusted_pid.is_none());
+        let realtime = idl::BuiltinClock::Realtime as u32;
+        assert_eq!(prepared.timestamp_clock_id, Some(realtime));
+        let Some(idl::trace_packet::Data::ClockSnapshot(snapshot)) = prepared.data else {
+            panic!("expected clock snapshot");
+        };
+        let clock_ids: Vec<_> = snapshot.clocks.iter().map(|clock| clock.clock_id).collect();
+        assert_eq!(clock_ids, [Some(realtime), Some(64)]);
+
+        assert!(prepare_packet(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn packets_are_continued_in_the_next_chunk() {
+        // 48 bytes of fragments per chunk, i.e. 44 bytes of packet data
+        let (mut session, mut service) = enabled_session("chunks", 4, 64);
+        let packet: Vec<u8> = (0..100).collect();
+        session.write_packet(5, &packet).unwrap();
+        session.write_packet(5, &[0xaa; 8]).unwrap();
+        session.commit_chunks(None).unwrap();
+
+        assert_eq!(committed_pages(&mut service), [0]);
+        assert_eq!(committed_pages(&mut service), [1]);
+        assert_eq!(committed_pages(&mut service), [2]);
+
+        let smb = session.smb.as_mut().unwrap();
+        let both = FIRST_PACKET_CONTINUES_FROM_PREV_CHUNK | LAST_PACKET_CONTINUES_ON_NEXT_CHUNK;
+        let first = read_chunk(smb, 0);
+        let second = read_chunk(smb, 1);
+        let last = read_chunk(smb, 2);
+        assert_eq!([first.0, second.0, last.0], [0, 1, 2]);
+        assert_eq!([first.1, second.1, last.1], [1; 3]);
+        assert_eq!(
+            [first.2, second.2, last.2],
+            [LAST_PACKET_CONTINUES_ON_NEXT_CHUNK, both, FIRST_PACKET_CONTINUES_FROM_PREV_CHUNK]
+        );
+
+        // The fragments reassemble to the packet, followed by the next packet
+        let fragments: Vec<_> = [first.3, second.3, last.3].into_iter().flatten().collect();
+        assert_eq!(fragments.len(), 4);
+        assert_eq!(fragments[..3].concat(), packet);
+        assert_eq!(fragments[3], [0xaa; 8]);
+    }
+
+    #[test]
+    fn packet_sequences_are_written_by_separate_writers() {
+        let (mut session, mut service) = enabled_session("writers", 4, 64);
+        session.write_packet(1, &[1; 4]).unwrap();
+        session.write_packet(2, &[2; 4]).unwrap();
+        session.commit_chunks(None).unwrap();
+
+        let mut pages = committed_pages(&mut service);
+        pages.sort();
+        assert_eq!(pages, [0, 1]);
+        let smb = session.smb.as_mut().unwrap();
+        let mut chunks = [read_chunk(smb, 0), read_chunk(smb, 1)];
+        chunks.sort_by_key(|chunk| chunk.1);
+        assert_eq!((chunks[0].1, chunks[1].1), (1, 2));
+        assert_eq!(chunks[0].3, [[1; 4]]);
+        assert_eq!(chunks[1].3, [[2; 4]]);
+    }
+
+    #[test]
+    fn full_buffer_drops_packets_until_pages_are_freed() {
+        let (mut session, mut service) = enabled_session("full", 2, 64);
+        let packet = [3; 100];
+        session.write_packet(1, &packet).unwrap();
+        assert_eq!(committed_pages(&mut service), [0]);
+        assert_eq!(committed_pages(&mut service), [1]);
+
+        let smb = session.smb.as_mut().unwrap();
+        assert!(smb.acquire_page().is_none());
+
+        // The tracing service frees the pages after moving the chunks to the target buffer
+        smb.layout(0).store(0, Ordering::Release);
+        assert_eq!(smb.acquire_page(), Some(0));
+    }
+
+    #[test]
+    fn packets_are_dropped_while_the_data_source_is_disabled() {
+        let (mut session, _service) = enabled_session("disabled", 2, 64);
+        session.instance = None;
+        session.write_packet(1, &[1; 4]).unwrap();
+        assert!(session.writers.is_empty());
+
+        session.connected = false;
+        assert!(session.write_packet(1, &[1; 4]).is_err());
+    }
+
+    #[test]
+    fn invalid_shared_memory_buffers_are_rejected() {
+        assert!(SharedMemory::map(buffer_fd("small_page", 4096), 8).is_err());
+        assert!(SharedMemory::map(buffer_fd("small_buffer", 1024), 4096).is_err());
+        let smb = SharedMemory::map(buffer_fd("valid", 4096), 1024).unwrap();
+        assert_eq!(smb.chunk_size(), 1016);
+    }
+
+    #[test]
+    fn shared_memory_buffer_is_received_with_the_setup_command() {
+        let (service, producer) = UnixStream::pair().unwrap();
+        let response = ipc::GetAsyncCommandResponse {
+            cmd: Some(ipc::Command::SetupTracing(ipc::SetupTracing {
+                shared_buffer_page_size_kb: Some(1),
+            })),
+        };
+        let frame = ipc::Frame {
+            request_id: Some(3),
+            msg: Some(ipc::Msg::InvokeMethodReply(ipc::InvokeMethodReply {
+                success: Some(true),
+                has_more: Some(true),
+                reply_proto: Some(response.encode_to_vec()),
+            })),
+        };
+        let smb = buffer_fd("setup", 4096);
+        send_with_fd(&service, &encode_frame(&frame), smb.as_raw_fd());
+
+        let mut reader = FrameReader::new(producer);
+        assert_eq!(reader.read_frame().unwrap().unwrap(), frame);
+        let fd = reader.fd.take();
+        assert!(fd.is_some());
+
+        let (mut session, _service) = connected_session();
+        let command = response.cmd.unwrap();
+        session.on_command(command.clone(), fd).unwrap();
+        let smb = session.smb.as_ref().unwrap();
+        assert_eq!((smb.size, smb.page_size), (4096, 1024));
+
+        // A setup without a buffer fails
+        let (mut session, _service) = connected_session();
+        assert!(session.on_command(command, None).is_err());
+        assert!(session.smb.is_none());
+    }
+
+    #[test]
+    fn truncated_and_invalid_frames_are_handled() {
+        let (mut service, producer) = UnixStream::pair().unwrap();
+        let mut reader = FrameReader::new(producer);
+        let frame = encode_frame(&ipc::Frame {
+            request_id: Some(1),
+            msg: None,
+        });
+
+        // A frame split across writes is reassembled, without a descriptor
+        service.write_all(&frame[..3]).unwrap();
+        let writer = thread::spawn(move || {
+            service.write_all(&frame[3..]).unwrap();
+            // Invalid frame contents
+            service.write_all(&[2, 0, 0, 0, 0xff, 0xff]).unwrap();
+            service
+        });
+        assert_eq!(reader.read_frame().unwrap().unwrap().request_id, Some(1));
+        assert!(reader.fd.is_none());
+        assert!(reader.read_frame().is_err());
+
+        // A frame truncated by closing the connection
+        let mut service = writer.join().unwrap();
+        reader.buffer.clear();
+        service.write_all(&[10, 0, 0, 0, 1]).unwrap();
+        drop(service);
+        assert!(reader.read_frame().unwrap().is_none());
+    }
+}
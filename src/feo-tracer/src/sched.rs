@@ -0,0 +1,290 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Capture of the CPU scheduling of the traced processes
+//!
+//! A [SchedCapture] enables the `sched_switch` event of the kernel in a tracing instance of its own, so that
+//! other users of ftrace are not disturbed, and reads the context switches from the `trace_pipe` of the
+//! instance. The switches are sent per CPU as [RecordData::Sched], together with the processes of the
+//! threads taking part. The [Perfetto](crate::perfetto::Perfetto) writer keeps the switches from or to a
+//! thread of a connected process and writes them as ftrace events, so that the CPU tracks and thread states
+//! of the traced processes show up next to their activity spans.
+//!
+//! The text format of the trace pipe limits the resolution of the switch timestamps to microseconds.
+//! Capturing requires write access to tracefs, usually root.
+
+use crate::data::{Process, ProcessId, RecordData, SchedSwitch, SchedSwitches, SchedThread, ThreadId, TraceRecord};
+use anyhow::{bail, Context, Error};
+use score_log::{debug, info, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::unix::AsyncFd;
+use tokio::sync::mpsc;
+
+/// Mount points of tracefs, in the order of preference
+const TRACEFS_MOUNTS: [&str; 2] = ["/sys/kernel/tracing", "/sys/kernel/debug/tracing"];
+
+/// Enable switch of the `sched_switch` event, relative to the tracing instance
+const SCHED_SWITCH_ENABLE: &str = "events/sched/sched_switch/enable";
+
+/// Size of the buffer (bytes) used for reading the trace pipe
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Number of threads whose process is cached before the cache is cleared
+const MAX_CACHED_THREADS: usize = 4096;
+
+/// Task state of a thread preempted while runnable, `R+` in the trace pipe
+const TASK_STATE_PREEMPTED: i64 = 0x100;
+
+/// Capture of the context switches of all CPUs
+///
+/// The tracing instance is removed when the capture is dropped.
+pub struct SchedCapture {
+    /// Trace pipe of the instance, opened non-blocking
+    ///
+    /// Declared before the instance, as an instance with an open trace pipe cannot be removed.
+    pipe: AsyncFd<fs::File>,
+    instance: Instance,
+}
+
+impl SchedCapture {
+    /// Create a tracing instance recording the `sched_switch` event and open its trace pipe
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn open() -> Result<Self, Error> {
+        let Some(tracefs) = TRACEFS_MOUNTS
+            .iter()
+            .map(Path::new)
+            .find(|path| path.join("instances").is_dir())
+        else {
+            bail!("tracefs is not mounted at any of {TRACEFS_MOUNTS:?}");
+        };
+        let path = tracefs.join("instances").join(format!("feo-tracer-{}", process::id()));
+        fs::create_dir(&path).with_context(|| format!("failed to create tracing instance {}", path.display()))?;
+        let instance = Instance(path);
+
+        // Timestamps on the boot clock are converted to wall clock time, the clock of the trace
+        instance.write("trace_clock", "boot")?;
+        instance.write(SCHED_SWITCH_ENABLE, "1")?;
+        let pipe = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(instance.0.join("trace_pipe"))
+            .with_context(|| format!("failed to open trace pipe of {}", instance.0.display()))?;
+        let pipe = AsyncFd::new(pipe).context("failed to register trace pipe")?;
+        info!("Capturing context switches with tracing instance {}", instance.0.display().to_string().as_str());
+
+        Ok(Self { pipe, instance })
+    }
+
+    /// Read the context switches and forward them to the message channel
+    pub async fn capture(self, sink: mpsc::Sender<TraceRecord>) -> Result<(), Error> {
+        let mut buffer = vec![0; READ_BUFFER_SIZE];
+        // Trailing part of a line not read completely yet
+        let mut pending = Vec::new();
+        let mut threads = ThreadProcesses::default();
+
+        loop {
+            let mut guard = self.pipe.readable().await?;
+            let len = match guard.try_io(|pipe| {
+                let mut file = pipe.get_ref();
+                file.read(&mut buffer)
+            }) {
+                Ok(result) => result.context("failed to read trace pipe")?,
+                Err(_would_block) => continue,
+            };
+            pending.extend_from_slice(&buffer[..len]);
+            let Some(end) = pending.iter().rposition(|byte| *byte == b'\n') else {
+                continue;
+            };
+            let lines: Vec<u8> = pending.drain(..=end).collect();
+
+            let offset = boot_offset()?;
+            let mut cpus: BTreeMap<u32, Vec<SchedSwitch>> = BTreeMap::new();
+            for line in String::from_utf8_lossy(&lines).lines() {
+                let Some((cpu, mut switch)) = parse_switch(line) else {
+                    debug!("Skipping trace pipe line {}", line);
+                    continue;
+                };
+                switch.timestamp += offset;
+                switch.prev.pid = threads.process(switch.prev.tid);
+                switch.next.pid = threads.process(switch.next.tid);
+                cpus.entry(cpu).or_default().push(switch);
+            }
+
+            for (cpu, switches) in cpus {
+                let record = TraceRecord::new(
+                    UNIX_EPOCH + Duration::from_nanos(switches[0].timestamp),
+                    Process { id: 0, name: None },
+                    None,
+                    RecordData::Sched(SchedSwitches { cpu, switches }),
+                );
+                sink.send(record).await.expect("channel error");
+            }
+        }
+    }
+}
+
+/// Tracing instance of the capture
+struct Instance(PathBuf);
+
+impl Instance {
+    /// Write to a file of the instance
+    fn write(&self, file: &str, value: &str) -> Result<(), Error> {
+        let path = self.0.join(file);
+        fs::write(&path, value).with_context(|| format!("failed to write {value} to {}", path.display()))
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        // Events must be disabled before the instance can be removed
+        let _ = self.write(SCHED_SWITCH_ENABLE, "0");
+        if let Err(e) = fs::remove_dir(&self.0) {
+            warn!(
+                "Failed to remove tracing instance {}: {}",
+                self.0.display().to_string().as_str(),
+                e.to_string().as_str()
+            );
+        }
+    }
+}
+
+/// Processes of the threads seen in context switches
+#[derive(Debug, Default)]
+struct ThreadProcesses(HashMap<ThreadId, Option<ProcessId>>);
+
+impl ThreadProcesses {
+    /// Get the process of the given thread, if it still exists
+    fn process(&mut self, tid: ThreadId) -> Option<ProcessId> {
+        // The idle tasks of all CPUs share the thread ID 0
+        if tid == 0 {
+            return None;
+        }
+        // Thread IDs are reused, so the cache is not kept forever
+        if self.0.len() >= MAX_CACHED_THREADS {
+            self.0.clear();
+        }
+        *self.0.entry(tid).or_insert_with(|| read_process(tid))
+    }
+}
+
+/// Read the process of a thread from procfs
+fn read_process(tid: ThreadId) -> Option<ProcessId> {
+    let status = fs::read_to_string(format!("/proc/{tid}/status")).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Tgid:"))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Offset of the wall clock time to the boot clock in nanoseconds
+///
+/// Taken anew for each read, so that adjustments of the wall clock are followed.
+fn boot_offset() -> Result<u64, Error> {
+    let mut boot = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // Safety: clock_gettime only writes the given timespec
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut boot) } != 0 {
+        return Err(io::Error::last_os_error()).context("failed to read boot clock");
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as u64;
+    Ok(now.saturating_sub(boot.tv_sec as u64 * 1_000_000_000 + boot.tv_nsec as u64))
+}
+
+/// Parse a `sched_switch` line of the trace pipe into the CPU and the switch, with the timestamp on the boot clock
+///
+/// Lines look like
+/// `worker-1234 [003] d..2. 5123.456789: sched_switch: prev_comm=worker prev_pid=1234 prev_prio=120 prev_state=S
+/// ==> next_comm=swapper/3 next_pid=0 next_prio=120`, where the command names may contain spaces.
+fn parse_switch(line: &str) -> Option<(u32, SchedSwitch)> {
+    let (header, fields) = line.split_once(": sched_switch: ")?;
+    let mut tokens = header.split_whitespace().rev();
+    let timestamp = parse_timestamp(tokens.next()?)?;
+    let cpu = tokens
+        .find_map(|token| token.strip_prefix('[')?.strip_suffix(']'))?
+        .parse()
+        .ok()?;
+
+    let (prev, next) = fields.split_once(" ==> ")?;
+    let [prev_comm, prev_pid, prev_prio, prev_state] =
+        values(prev, ["prev_comm", "prev_pid", "prev_prio", "prev_state"])?;
+    let [next_comm, next_pid, next_prio] = values(next, ["next_comm", "next_pid", "next_prio"])?;
+    let switch = SchedSwitch {
+        timestamp,
+        prev: SchedThread {
+            tid: prev_pid.parse().ok()?,
+            pid: None,
+            comm: prev_comm.to_string(),
+        },
+        prev_prio: prev_prio.parse().ok()?,
+        prev_state: task_state(prev_state),
+        next: SchedThread {
+            tid: next_pid.parse().ok()?,
+            pid: None,
+            comm: next_comm.to_string(),
+        },
+        next_prio: next_prio.parse().ok()?,
+    };
+    Some((cpu, switch))
+}
+
+/// Parse a timestamp in seconds with fractional digits, e.g. `5123.456789`, into nanoseconds
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (secs, fraction) = timestamp.split_once('.')?;
+    if fraction.len() > 9 {
+        return None;
+    }
+    let nanos: u64 = format!("{fraction:0<9}").parse().ok()?;
+    Some(secs.parse::<u64>().ok()? * 1_000_000_000 + nanos)
+}
+
+/// Get the values of the given keys from `key=value` pairs separated by spaces, where values may contain spaces
+fn values<'a, const N: usize>(text: &'a str, keys: [&str; N]) -> Option<[&'a str; N]> {
+    let mut values = [""; N];
+    let mut rest = text;
+    // Split off the pairs from the end, so that spaces end up in the value of the first key
+    for (index, key) in keys.iter().enumerate().rev() {
+        let start = rest.rfind(&format!("{key}="))?;
+        values[index] = &rest[start + key.len() + 1..];
+        rest = rest[..start].trim_end();
+    }
+    Some(values)
+}
+
+/// Convert the state of a thread leaving the CPU, e.g. `S` or `D|K`, into the task state bits of the kernel
+fn task_state(state: &str) -> i64 {
+    if state == "R+" {
+        return TASK_STATE_PREEMPTED;
+    }
+    state
+        .split('|')
+        .map(|flag| match flag {
+            "S" => 0x1,
+            "D" => 0x2,
+            "T" => 0x4,
+            "t" => 0x8,
+            "X" => 0x10,
+            "Z" => 0x20,
+            "P" => 0x40,
+            "I" => 0x80,
+            _ => 0,
+        })
+        .fold(0, |state, flag| state | flag)
+}
@@ -0,0 +1,175 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Mapping of feo metadata to named Perfetto tracks
+//!
+//! Spans and events carrying an `activity_id`, `chain_id`, `agent_id` or `worker_id` field are
+//! recognized as feo metadata:
+//! - Spans and events with an `activity_id` are placed on a dedicated track per activity.
+//! - Spans and events with a `chain_id`, e.g. the cycle spans of the scheduler, are placed on a
+//!   dedicated track per task chain, so cycles of different chains overlapping in time do not nest.
+//! - A `worker_id` names the thread track of the emitting thread after the worker.
+//! - An `agent_id` names the process track of the emitting process after the agent.
+
+use crate::data::{ProcessId, RecordEventInfo, RecordField, RecordFieldValue, ThreadId};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Field name of activity ids
+pub const ACTIVITY_ID_FIELD: &str = "activity_id";
+/// Field name of task chain ids
+pub const CHAIN_ID_FIELD: &str = "chain_id";
+/// Field name of agent ids
+pub const AGENT_ID_FIELD: &str = "agent_id";
+/// Field name of worker ids
+pub const WORKER_ID_FIELD: &str = "worker_id";
+
+/// Feo metadata found in the info of a span or event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeoMetadata {
+    Activity(u64),
+    Chain(u64),
+    Agent(u64),
+    Worker(u64),
+}
+
+impl FeoMetadata {
+    /// Extract feo metadata from the given field, if any
+    pub fn from_field(field: &RecordField) -> Option<Self> {
+        let id = match &field.value {
+            RecordFieldValue::U64(id) => *id,
+            RecordFieldValue::I64(id) => u64::try_from(*id).ok()?,
+            RecordFieldValue::Str(id) => id.parse().ok()?,
+            RecordFieldValue::F64(_) | RecordFieldValue::Bool(_) => return None,
+        };
+        match field.name.as_str() {
+            ACTIVITY_ID_FIELD => Some(Self::Activity(id)),
+            CHAIN_ID_FIELD => Some(Self::Chain(id)),
+            AGENT_ID_FIELD => Some(Self::Agent(id)),
+            WORKER_ID_FIELD => Some(Self::Worker(id)),
+            _ => None,
+        }
+    }
+}
+
+/// Names of process and thread tracks learned from feo metadata
+#[derive(Debug, Default)]
+pub struct TrackNames {
+    /// Agent ids per process
+    agents: HashMap<ProcessId, u64>,
+    /// Worker ids per thread
+    workers: HashMap<(ProcessId, ThreadId), u64>,
+}
+
+impl TrackNames {
+    /// Remember the feo metadata in `info` emitted by the given process and thread.
+    ///
+    /// Returns the recognized metadata, if any, preferring an activity id over a chain id.
+    pub fn observe(&mut self, pid: ProcessId, tid: Option<ThreadId>, info: &RecordEventInfo) -> Option<FeoMetadata> {
+        let mut recognized = None;
+        for metadata in info.fields.iter().filter_map(FeoMetadata::from_field) {
+            match metadata {
+                FeoMetadata::Agent(id) => {
+                    self.agents.insert(pid, id);
+                },
+                FeoMetadata::Worker(id) => {
+                    if let Some(tid) = tid {
+                        self.workers.insert((pid, tid), id);
+                    }
+                },
+                FeoMetadata::Activity(_) | FeoMetadata::Chain(_) => (),
+            }
+            recognized = match (recognized, metadata) {
+                (Some(FeoMetadata::Activity(_)), _) => recognized,
+                (Some(FeoMetadata::Chain(_)), FeoMetadata::Agent(_) | FeoMetadata::Worker(_)) => recognized,
+                _ => Some(metadata),
+            };
+        }
+        recognized
+    }
+
+    /// Forget all names of the given process
+    pub fn remove_process(&mut self, pid: ProcessId) {
+        self.agents.remove(&pid);
+        self.workers.retain(|(p, _), _| *p != pid);
+    }
+
+    /// Name of the process track, prefixed with the agent id if known
+    pub fn process_name(&self, pid: ProcessId, name: Option<&str>) -> Option<String> {
+        named("Agent", self.agents.get(&pid), name)
+    }
+
+    /// Name of the thread track, prefixed with the worker id if known
+    pub fn thread_name(&self, pid: ProcessId, tid: ThreadId, name: Option<&str>) -> Option<String> {
+        named("Worker", self.workers.get(&(pid, tid)), name)
+    }
+}
+
+/// Uuid of the track of a process, unique per trace
+pub fn process_track_uuid(trace_uuid: u64, pid: ProcessId) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (trace_uuid, pid).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Uuid of the track of an activity in a process
+pub fn activity_track_uuid(pid: ProcessId, activity_id: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (ACTIVITY_ID_FIELD, pid, activity_id).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Name of the track of an activity
+pub fn activity_track_name(activity_id: u64) -> String {
+    format!("Activity {activity_id}")
+}
+
+/// Uuid of the track of a task chain in a process
+pub fn chain_track_uuid(pid: ProcessId, chain_id: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (CHAIN_ID_FIELD, pid, chain_id).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Name of the track of a task chain
+pub fn chain_track_name(chain_id: u64) -> String {
+    format!("Task chain {chain_id}")
+}
+
+/// Uuid of the latency counter track of a topic in a process
+pub fn latency_track_uuid(pid: ProcessId, topic: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ("latency", pid, topic).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Name of the latency counter track of a topic
+pub fn latency_track_name(topic: &str) -> String {
+    format!("Latency {topic}")
+}
+
+/// Uuid of the track of a counter in a process
+pub fn counter_track_uuid(pid: ProcessId, name: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ("counter", pid, name).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Combine a feo id with the name given by the OS, if any
+fn named(kind: &str, id: Option<&u64>, name: Option<&str>) -> Option<String> {
+    match (id, name) {
+        (Some(id), Some(name)) => Some(format!("{kind} {id} ({name})")),
+        (Some(id), None) => Some(format!("{kind} {id}")),
+        (None, name) => name.map(str::to_string),
+    }
+}
@@ -71,6 +71,9 @@ fn iteration(n: u32) {
     }
 
     sleep_rand_millis(100..130);
+
+    // Set a counter, shown as counter track of the process
+    feo_tracing::counter!("last iteration", n);
 }
 
 #[instrument]
@@ -0,0 +1,104 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Tracing in the context of activities
+//!
+//! The FEO workers [enter] the context of an activity while running its lifecycle methods. The
+//! [span_activity](crate::span_activity) and [event_activity](crate::event_activity) macros attach
+//! the activity, worker and cycle of the current context as fields `activity`, `worker` and `cycle`,
+//! which are left out outside of an activity.
+//!
+//! Each span and event of these macros belongs to a [Category], passed as its first argument, and
+//! is traced with the target `feo_activity::<category>`. Categories are stripped at compile time
+//! with the crate features `strip_sensor`, `strip_fusion` and `strip_control`, e.g. to remove
+//! verbose sensor traces from release builds entirely:
+//!
+//! ```ignore
+//! let _span = span_activity!(sensor, Level::DEBUG, "decode_frame", bytes = frame.len()).entered();
+//! event_activity!(control, Level::INFO, torque = command.torque, "command sent");
+//! ```
+
+use core::cell::Cell;
+
+/// Category of the spans and events of activities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Acquisition and preprocessing of sensor data
+    Sensor,
+    /// Fusion of sensor data into an environment model
+    Fusion,
+    /// Planning and control of actuators
+    Control,
+}
+
+impl Category {
+    /// Whether the spans and events of this category are compiled in
+    pub const fn enabled(self) -> bool {
+        match self {
+            Category::Sensor => !cfg!(feature = "strip_sensor"),
+            Category::Fusion => !cfg!(feature = "strip_fusion"),
+            Category::Control => !cfg!(feature = "strip_control"),
+        }
+    }
+}
+
+/// Activity running on the current thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityContext {
+    /// ID of the activity
+    pub activity: u64,
+    /// ID of the worker running the activity
+    pub worker: u64,
+    /// Index of the cycle of the activity, counting its steps from zero
+    pub cycle: u64,
+}
+
+std::thread_local! {
+    /// Context of the activity running on this thread, if any
+    static CURRENT: Cell<Option<ActivityContext>> = const { Cell::new(None) };
+}
+
+/// Get the context of the activity running on the current thread, if any
+pub fn current() -> Option<ActivityContext> {
+    CURRENT.get()
+}
+
+/// Enter the context of the given activity on the current thread until the returned guard is dropped
+pub fn enter(context: ActivityContext) -> ContextGuard {
+    ContextGuard(CURRENT.replace(Some(context)))
+}
+
+/// Guard restoring the previous activity context of the thread when dropped
+#[must_use]
+pub struct ContextGuard(Option<ActivityContext>);
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
+
+/// Map a category name of the activity tracing macros to its [Category]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __activity_category {
+    (sensor) => {
+        $crate::activity::Category::Sensor
+    };
+    (fusion) => {
+        $crate::activity::Category::Fusion
+    };
+    (control) => {
+        $crate::activity::Category::Control
+    };
+}
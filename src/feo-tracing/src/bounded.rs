@@ -0,0 +1,415 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Strings and bytes of a fixed capacity
+//!
+//! Packets and signals of a bounded size carry text and bytes inline instead of allocating them.
+//! A [BoundedStr] holds up to `N` bytes of UTF-8 text, a [BoundedBytes] up to `N` bytes, each
+//! together with its length. Longer input is either truncated, text at a character boundary like
+//! [truncate] does for trace packets, or rejected.
+//!
+//! Both serialize like `str` and `[u8]` respectively, and fail to deserialize input exceeding their capacity.
+
+use crate::protocol::truncate;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::{Serialize, Serializer};
+
+/// Text of at most `N` bytes, stored inline
+#[derive(Clone, Copy)]
+pub struct BoundedStr<const N: usize> {
+    /// Length of the text in bytes
+    len: u16,
+    /// Text, followed by zeros
+    bytes: [u8; N],
+}
+
+impl<const N: usize> BoundedStr<N> {
+    /// Capacity in bytes
+    pub const CAPACITY: usize = N;
+
+    /// Fails to compile for capacities exceeding the range of the length
+    const VALID: () = assert!(N <= u16::MAX as usize, "the capacity is limited to u16::MAX bytes");
+
+    /// Create an empty text
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self { len: 0, bytes: [0; N] }
+    }
+
+    /// Create a text from the given one, truncated to `N` bytes at a character boundary
+    pub fn truncated(text: &str) -> Self {
+        let mut bounded = Self::new();
+        bounded.push_str(text);
+        bounded
+    }
+
+    /// Create a text from the given one, returns `None` if it exceeds `N` bytes
+    pub fn try_new(text: &str) -> Option<Self> {
+        (text.len() <= N).then(|| Self::truncated(text))
+    }
+
+    /// Create a text from UTF-8 encoded bytes, returns `None` if they are invalid or exceed `N` bytes
+    pub fn from_utf8(bytes: &[u8]) -> Option<Self> {
+        Self::try_new(core::str::from_utf8(bytes).ok()?)
+    }
+
+    /// Append as much of the given text as fits, returning whether it has been appended completely
+    pub fn push_str(&mut self, text: &str) -> bool {
+        let len = self.len as usize;
+        let appended = truncate(text, N - len);
+        self.bytes[len..len + appended.len()].copy_from_slice(appended.as_bytes());
+        self.len = (len + appended.len()) as u16;
+        appended.len() == text.len()
+    }
+
+    /// Get the text
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are stored, see [BoundedStr::push_str]
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+
+    /// Length of the text in bytes
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Whether the text is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for BoundedStr<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PartialEq for BoundedStr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for BoundedStr<N> {}
+
+impl<const N: usize> Hash for BoundedStr<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Write for BoundedStr<N> {
+    /// Append the text, failing if it is truncated
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.push_str(s) {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for BoundedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for BoundedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> ScoreDebug for BoundedStr<N> {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str(self.as_str(), spec)
+    }
+}
+
+impl<const N: usize> Serialize for BoundedStr<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for BoundedStr<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(BoundedStrVisitor::<N>)
+    }
+}
+
+struct BoundedStrVisitor<const N: usize>;
+
+impl<const N: usize> Visitor<'_> for BoundedStrVisitor<N> {
+    type Value = BoundedStr<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string of at most {N} bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        BoundedStr::try_new(value).ok_or_else(|| E::invalid_length(value.len(), &self))
+    }
+}
+
+/// Bytes of at most `N`, stored inline
+#[derive(Clone, Copy)]
+pub struct BoundedBytes<const N: usize> {
+    /// Number of bytes
+    len: u16,
+    /// Bytes, followed by zeros
+    bytes: [u8; N],
+}
+
+impl<const N: usize> BoundedBytes<N> {
+    /// Capacity in bytes
+    pub const CAPACITY: usize = N;
+
+    /// Fails to compile for capacities exceeding the range of the length
+    const VALID: () = assert!(N <= u16::MAX as usize, "the capacity is limited to u16::MAX bytes");
+
+    /// Create empty bytes
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self { len: 0, bytes: [0; N] }
+    }
+
+    /// Create bytes from the given ones, truncated to `N` bytes
+    pub fn truncated(bytes: &[u8]) -> Self {
+        let mut bounded = Self::new();
+        bounded.extend_from_slice(bytes);
+        bounded
+    }
+
+    /// Create bytes from the given ones, returns `None` if they exceed `N` bytes
+    pub fn try_new(bytes: &[u8]) -> Option<Self> {
+        (bytes.len() <= N).then(|| Self::truncated(bytes))
+    }
+
+    /// Append as many of the given bytes as fit, returning whether they have been appended completely
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) -> bool {
+        let len = self.len as usize;
+        let appended = &bytes[..bytes.len().min(N - len)];
+        self.bytes[len..len + appended.len()].copy_from_slice(appended);
+        self.len = (len + appended.len()) as u16;
+        appended.len() == bytes.len()
+    }
+
+    /// Get the bytes
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Number of bytes
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Whether there are no bytes
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for BoundedBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PartialEq for BoundedBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for BoundedBytes<N> {}
+
+impl<const N: usize> Hash for BoundedBytes<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Debug for BoundedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<const N: usize> ScoreDebug for BoundedBytes<N> {
+    /// Formats the bytes like [fmt::Debug], as a list
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("[", spec)?;
+        for (index, byte) in self.as_slice().iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ", spec)?;
+            }
+            ScoreDebug::fmt(byte, f, spec)?;
+        }
+        f.write_str("]", spec)
+    }
+}
+
+impl<const N: usize> Serialize for BoundedBytes<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for BoundedBytes<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(BoundedBytesVisitor::<N>)
+    }
+}
+
+struct BoundedBytesVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for BoundedBytesVisitor<N> {
+    type Value = BoundedBytes<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at most {N} bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        BoundedBytes::try_new(value).ok_or_else(|| E::invalid_length(value.len(), &self))
+    }
+
+    /// Formats without a notion of bytes, e.g. JSON, encode them as sequence
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = BoundedBytes::new();
+        while let Some(byte) = seq.next_element::<u8>()? {
+            if !bytes.extend_from_slice(&[byte]) {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_truncated_at_character_boundaries() {
+        // Three bytes per character, the fourth character does not fit completely
+        let text = BoundedStr::<10>::truncated("€€€€");
+        assert_eq!(text.as_str(), "€€€");
+        assert_eq!(text.len(), 9);
+
+        let mut text = BoundedStr::<4>::new();
+        assert!(text.is_empty());
+        assert!(text.push_str("ab"));
+        assert!(!text.push_str("cä"));
+        assert_eq!(text.as_str(), "abc");
+        assert!(text.push_str("d"));
+        assert!(!text.push_str("e"));
+        assert_eq!(text.as_str(), "abcd");
+        assert!(text.push_str(""));
+    }
+
+    #[test]
+    fn oversize_text_is_rejected() {
+        assert_eq!(BoundedStr::<4>::try_new("abcd").unwrap().as_str(), "abcd");
+        assert!(BoundedStr::<4>::try_new("abcde").is_none());
+        assert!(BoundedStr::<4>::try_new("abcä").is_none());
+        assert!(BoundedStr::<4>::from_utf8(&[0xff]).is_none());
+
+        let mut text = BoundedStr::<4>::new();
+        assert!(fmt::Write::write_str(&mut text, "abcd").is_ok());
+        assert!(fmt::Write::write_str(&mut text, "e").is_err());
+    }
+
+    #[test]
+    fn text_roundtrips_through_serde() {
+        let text = BoundedStr::<8>::truncated("feo äö");
+        let bytes = postcard::to_allocvec(&text).unwrap();
+        assert_eq!(bytes, postcard::to_allocvec("feo äö").unwrap());
+        assert_eq!(postcard::from_bytes::<BoundedStr<8>>(&bytes).unwrap(), text);
+
+        // Text exceeding the capacity of the receiving side is rejected instead of truncated
+        assert!(postcard::from_bytes::<BoundedStr<4>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn bytes_are_truncated_or_rejected() {
+        let bytes = BoundedBytes::<4>::truncated(&[1, 2, 3, 4, 5]);
+        assert_eq!(bytes.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(BoundedBytes::<4>::try_new(&[1, 2, 3, 4]), Some(bytes));
+        assert!(BoundedBytes::<4>::try_new(&[1, 2, 3, 4, 5]).is_none());
+
+        let mut bytes = BoundedBytes::<4>::new();
+        assert!(bytes.is_empty());
+        assert!(bytes.extend_from_slice(&[1, 2]));
+        assert!(!bytes.extend_from_slice(&[3, 4, 5]));
+        assert_eq!(bytes.as_slice(), &[1, 2, 3, 4]);
+        assert!(bytes.extend_from_slice(&[]));
+    }
+
+    #[test]
+    fn bytes_roundtrip_through_serde() {
+        let bytes = BoundedBytes::<8>::truncated(&[0, 1, 0xff]);
+        let serialized = postcard::to_allocvec(&bytes).unwrap();
+        assert_eq!(serialized, postcard::to_allocvec(&[0u8, 1, 0xff][..]).unwrap());
+        assert_eq!(postcard::from_bytes::<BoundedBytes<8>>(&serialized).unwrap(), bytes);
+
+        // Bytes exceeding the capacity of the receiving side are rejected instead of truncated
+        assert!(postcard::from_bytes::<BoundedBytes<2>>(&serialized).is_err());
+    }
+
+    #[test]
+    fn bytes_are_formatted_as_list() {
+        /// Writer collecting the formatted text
+        #[derive(Default)]
+        struct Text(String);
+
+        /// Implement the typed write methods of [ScoreWrite] with [fmt::Display]
+        macro_rules! write_display {
+            ($($method:ident: $type:ty),*) => {
+                $(
+                    fn $method(&mut self, value: &$type, _spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+                        self.0.push_str(&value.to_string());
+                        Ok(())
+                    }
+                )*
+            };
+        }
+
+        impl ScoreWrite for Text {
+            write_display!(
+                write_bool: bool, write_f32: f32, write_f64: f64,
+                write_i8: i8, write_i16: i16, write_i32: i32, write_i64: i64,
+                write_u8: u8, write_u16: u16, write_u32: u32, write_u64: u64
+            );
+
+            fn write_str(&mut self, value: &str, _spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+                self.0.push_str(value);
+                Ok(())
+            }
+        }
+
+        for bytes in [BoundedBytes::<4>::new(), BoundedBytes::<4>::truncated(&[1, 22, 255])] {
+            let mut text = Text::default();
+            ScoreDebug::fmt(&bytes, &mut text, &FormatSpec::new()).unwrap();
+            assert_eq!(text.0, format!("{bytes:?}"));
+        }
+    }
+}
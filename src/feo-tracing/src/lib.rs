@@ -17,10 +17,108 @@
 /// The tracing data is forward to `feo-tracer`
 #[path = "subscriber.rs"]
 mod feo_subscriber;
+pub mod activity;
+pub mod bounded;
+pub mod local;
+pub mod paths;
 pub mod protocol;
+#[cfg(unix)]
+pub mod shm;
+pub mod tap;
+pub mod udp;
 
 /// Initialize tracing
 pub use feo_subscriber::init;
 pub use feo_subscriber::ScoreDebugIoError;
+/// Initialize tracing with a given configuration, e.g. the behavior on overflow, the transport to feo-tracer,
+/// the limits of the packet sizes and the flushing and buffering of packets
+pub use feo_subscriber::{
+    init_with, Config, FlushMode, OverflowMode, Transport, DEFAULT_BUFFER_SIZE, DEFAULT_CHANNEL_BOUND,
+    DEFAULT_FLUSH_INTERVAL,
+};
+pub use protocol::TraceLimits;
+/// Number of trace packets dropped in this process, e.g. for metrics
+pub use feo_subscriber::dropped_packets;
+/// Emit the current value of a counter, shown by feo-tracer as a counter track of the emitting process
+///
+/// The value may be any integer or floating point number, e.g. `counter!("queue_depth", queue.len())`.
+/// Counters are emitted at info level.
+#[macro_export]
+macro_rules! counter {
+    ($name:expr, $value:expr) => {
+        $crate::tracing::event!(
+            target: $crate::protocol::COUNTER_TARGET,
+            $crate::Level::INFO,
+            counter = $name,
+            value = $value
+        )
+    };
+}
+
+/// Emit a reading of a secondary clock domain, e.g. the clock of a GPU or of an external sensor
+///
+/// The timestamp in nanoseconds on the clock domain with the given number is taken at the time of the call,
+/// e.g. `clock_sync!(GPU_CLOCK, gpu.timestamp_ns())`. feo-tracer aligns the domain with the other clocks of
+/// the trace from these readings, so events measured on the domain are shown at the right time. Such events
+/// carry their time on the domain in the fields `clock_domain` and `clock_timestamp`, e.g.
+/// `event!(Level::INFO, clock_domain = GPU_CLOCK, clock_timestamp = kernel.end_ns(), "kernel done")`.
+/// Clock syncs are emitted at info level.
+#[macro_export]
+macro_rules! clock_sync {
+    ($domain:expr, $timestamp:expr) => {
+        $crate::tracing::event!(
+            target: $crate::protocol::CLOCK_TARGET,
+            $crate::Level::INFO,
+            clock_domain = $domain,
+            clock_timestamp = $timestamp
+        )
+    };
+}
+
+/// Create a span in the context of the current activity, see [activity]
+///
+/// The first argument is the [category](activity::Category) of the span, `sensor`, `fusion` or `control`,
+/// followed by the arguments of [span!]. Spans of stripped categories are disabled spans.
+#[macro_export]
+macro_rules! span_activity {
+    ($category:ident, $level:expr, $name:expr $(, $($fields:tt)*)?) => {
+        if $crate::__activity_category!($category).enabled() {
+            let context = $crate::activity::current();
+            $crate::tracing::span!(
+                target: concat!("feo_activity::", stringify!($category)),
+                $level,
+                $name,
+                activity = context.map(|c| c.activity),
+                worker = context.map(|c| c.worker),
+                cycle = context.map(|c| c.cycle)
+                $(, $($fields)*)?
+            )
+        } else {
+            $crate::tracing::Span::none()
+        }
+    };
+}
+
+/// Emit an event in the context of the current activity, see [activity]
+///
+/// The first argument is the [category](activity::Category) of the event, `sensor`, `fusion` or `control`,
+/// followed by the arguments of [event!]. Events of stripped categories are not emitted.
+#[macro_export]
+macro_rules! event_activity {
+    ($category:ident, $level:expr, $($fields:tt)+) => {
+        if $crate::__activity_category!($category).enabled() {
+            let context = $crate::activity::current();
+            $crate::tracing::event!(
+                target: concat!("feo_activity::", stringify!($category)),
+                $level,
+                activity = context.map(|c| c.activity),
+                worker = context.map(|c| c.worker),
+                cycle = context.map(|c| c.cycle),
+                $($fields)+
+            )
+        }
+    };
+}
+
 /// Re-export of the `tracing` crate.
 pub use tracing::{self, event, instrument, level_filters::LevelFilter, span, Level};
@@ -0,0 +1,101 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Local stream transport of trace packets to feo-tracer
+//!
+//! Traced processes on the same machine as feo-tracer stream their packets to a [LocalAddress]:
+//! a Unix socket, or a TCP connection on the loopback interface where Unix sockets are not available,
+//! e.g. on Windows. Addresses given as `host:port` select TCP, any other address is the path of a
+//! Unix socket.
+//!
+//! The peer of a Unix socket is identified by its credentials. Over TCP, feo-tracer cannot identify
+//! the connected process, so it announces its PID in a preamble of [PREAMBLE_SIZE] bytes, little
+//! endian, ahead of the packets.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Port of feo-tracer on the loopback interface if Unix sockets are not available
+pub const DEFAULT_TRACER_PORT: u16 = 7471;
+
+/// Size of the preamble announcing the PID of a process connected over TCP
+pub const PREAMBLE_SIZE: usize = 4;
+
+/// Stream of trace packets to feo-tracer
+pub trait LocalStream: Read + Write + Send {}
+
+impl<S: Read + Write + Send> LocalStream for S {}
+
+/// Address of feo-tracer on the local machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalAddress {
+    /// Path of a Unix socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// TCP address, usually on the loopback interface
+    Tcp(SocketAddr),
+}
+
+impl LocalAddress {
+    /// Parse an address given as `host:port` or as the path of a Unix socket
+    ///
+    /// Returns `None` for paths where Unix sockets are not available.
+    pub fn parse(address: &str) -> Option<Self> {
+        match address.parse::<SocketAddr>() {
+            Ok(address) => Some(LocalAddress::Tcp(address)),
+            #[cfg(unix)]
+            Err(_) => Some(LocalAddress::Unix(PathBuf::from(address))),
+            #[cfg(not(unix))]
+            Err(_) => None,
+        }
+    }
+
+    /// Get the address of feo-tracer on the loopback interface with the default port
+    pub fn loopback() -> Self {
+        LocalAddress::Tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_TRACER_PORT)))
+    }
+
+    /// Connect to feo-tracer, announcing the PID of this process over TCP
+    pub fn connect(&self) -> io::Result<Box<dyn LocalStream>> {
+        match self {
+            #[cfg(unix)]
+            LocalAddress::Unix(path) => Ok(Box::new(UnixStream::connect(path)?)),
+            LocalAddress::Tcp(address) => {
+                let mut stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                stream.write_all(&std::process::id().to_le_bytes())?;
+                Ok(Box::new(stream))
+            },
+        }
+    }
+}
+
+impl fmt::Display for LocalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(unix)]
+            LocalAddress::Unix(path) => write!(f, "{}", path.display()),
+            LocalAddress::Tcp(address) => write!(f, "{address}"),
+        }
+    }
+}
+
+/// Get the PID announced in the given preamble of a TCP connection
+pub fn preamble_pid(preamble: [u8; PREAMBLE_SIZE]) -> u32 {
+    u32::from_le_bytes(preamble)
+}
@@ -0,0 +1,102 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Resolution of runtime paths
+//!
+//! Runtime files such as sockets are resolved in the order
+//!
+//! 1. the path given on the command line, if any,
+//! 2. the path given by the environment variable of the file,
+//! 3. the default file name in the runtime directory.
+//!
+//! The runtime directory is taken from [RUNTIME_DIR_ENV], `XDG_RUNTIME_DIR` or `TMPDIR`, in this order,
+//! falling back to `/tmp`. Setting [RUNTIME_DIR_ENV] to a directory of its own per instance separates
+//! instances running on the same machine, e.g. tests running in parallel in CI sandboxes.
+//!
+//! The paths of feo-tracer are resolved here, as they are shared by the subscriber and feo-tracer,
+//! see `feo::paths` for the paths of FEO applications. The address receiving trace packets may also be
+//! a TCP address, which is the default where Unix sockets are not available, see [local](crate::local).
+
+use crate::local::LocalAddress;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Environment variable of the runtime directory shared by all FEO processes of an instance
+pub const RUNTIME_DIR_ENV: &str = "FEO_RUNTIME_DIR";
+
+/// Environment variable of the address of feo-tracer receiving trace packets, a path or `host:port`
+pub const TRACER_SOCKET_ENV: &str = "FEO_TRACER_SOCKET";
+
+/// Environment variable of the socket of feo-tracer setting up the shared memory transport
+pub const TRACER_SHM_SOCKET_ENV: &str = "FEO_TRACER_SHM_SOCKET";
+
+/// Default file name of the socket of feo-tracer receiving trace packets
+#[cfg(unix)]
+const TRACER_SOCKET_NAME: &str = "feo-tracer.sock";
+
+/// Default file name of the socket of feo-tracer setting up the shared memory transport
+const TRACER_SHM_SOCKET_NAME: &str = "feo-tracer-shm.sock";
+
+/// Directory of runtime files if none is configured
+const FALLBACK_RUNTIME_DIR: &str = "/tmp";
+
+/// Get the runtime directory
+pub fn runtime_dir() -> PathBuf {
+    [RUNTIME_DIR_ENV, "XDG_RUNTIME_DIR", "TMPDIR"]
+        .into_iter()
+        .find_map(env_path)
+        .unwrap_or_else(|| PathBuf::from(FALLBACK_RUNTIME_DIR))
+}
+
+/// Resolve the path of a runtime file from the given command line flag, environment variable
+/// and default file name in the runtime directory
+pub fn resolve(flag: Option<&Path>, env: &str, file_name: &str) -> PathBuf {
+    flag.map(Path::to_path_buf)
+        .or_else(|| env_path(env))
+        .unwrap_or_else(|| runtime_dir().join(file_name))
+}
+
+/// Get the address of feo-tracer receiving trace packets from the given command line flag, if valid
+///
+/// Defaults to the socket in the runtime directory, or to the loopback interface where Unix sockets are
+/// not available.
+pub fn tracer_address(flag: Option<&str>) -> LocalAddress {
+    flag.map(str::to_string)
+        .or_else(|| env::var(TRACER_SOCKET_ENV).ok().filter(|value| !value.is_empty()))
+        .and_then(|address| LocalAddress::parse(&address))
+        .unwrap_or_else(default_tracer_address)
+}
+
+/// Get the path of the socket of feo-tracer setting up the shared memory transport
+pub fn tracer_shm_socket(flag: Option<&Path>) -> PathBuf {
+    resolve(flag, TRACER_SHM_SOCKET_ENV, TRACER_SHM_SOCKET_NAME)
+}
+
+/// Get the default address of feo-tracer receiving trace packets
+#[cfg(unix)]
+fn default_tracer_address() -> LocalAddress {
+    LocalAddress::Unix(runtime_dir().join(TRACER_SOCKET_NAME))
+}
+
+/// Get the default address of feo-tracer receiving trace packets
+#[cfg(not(unix))]
+fn default_tracer_address() -> LocalAddress {
+    LocalAddress::loopback()
+}
+
+/// Get the path given by the environment variable of the given name, if set and not empty
+fn env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
@@ -13,20 +13,108 @@
 
 use core::fmt::Debug;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::process;
 use std::time::{self, UNIX_EPOCH};
 use tracing::field::Field;
+use tracing::Metadata;
 use tracing_subscriber::field::Visit;
 
-pub const MAX_INFO_SIZE: usize = 30;
+/// The default maximal size of names and string values in bytes, see [TraceLimits]
+pub const DEFAULT_INFO_SIZE: usize = 30;
 
-/// The maximal allowed size of serialized packet data
+/// The upper bound of the configurable size of names and string values in bytes
+pub const MAX_INFO_SIZE: usize = 1024;
+
+/// The maximal number of fields recorded per span or event
+///
+/// Further fields are dropped. Spans and events of [activities](crate::activity) use three fields for their context.
+pub const MAX_FIELDS: usize = 8;
+
+/// The default maximal allowed size of serialized packet data, see [TraceLimits]
+pub const DEFAULT_PACKET_SIZE: usize = STRINGS_PER_PACKET * DEFAULT_INFO_SIZE + PACKET_OVERHEAD;
+
+/// The upper bound of the configurable packet size, i.e. the largest packet accepted by feo-tracer
+pub const MAX_PACKET_SIZE: usize = 16 * 1024;
+
+/// Version of the trace protocol
 ///
-/// Packets exceeding this size will be dropped with an error message
-pub const MAX_PACKET_SIZE: usize = 148;
+/// The subscriber announces its version with a [TraceData::Hello] packet before any other packet on each
+/// connection. Bump the version on every incompatible change of the packet types below; appending variants
+/// to [TraceData] is compatible, as feo-tracer skips packets it fails to deserialize.
+pub const PROTOCOL_VERSION: u16 = 3;
+
+/// Number of names and string values per packet: the name, target and source file of the span or event
+/// and the names and values of its fields
+const STRINGS_PER_PACKET: usize = 3 + 2 * MAX_FIELDS;
+
+/// Size of a packet in bytes besides the names and string values, including their length prefixes
+const PACKET_OVERHEAD: usize = 42 + 12 * MAX_FIELDS + 2 * STRINGS_PER_PACKET;
+
+/// Target of the events emitted by [counter](crate::counter)
+pub const COUNTER_TARGET: &str = "feo_counter";
+
+/// Field name of the name of a counter
+pub const COUNTER_NAME_FIELD: &str = "counter";
+
+/// Field name of the value of a counter
+pub const COUNTER_VALUE_FIELD: &str = "value";
+
+/// Target of the events emitted by [clock_sync](crate::clock_sync)
+pub const CLOCK_TARGET: &str = "feo_clock";
+
+/// Field name of the secondary clock domain of an event or clock sync
+pub const CLOCK_DOMAIN_FIELD: &str = "clock_domain";
+
+/// Field name of the timestamp of an event or clock sync on its secondary clock domain, in nanoseconds
+pub const CLOCK_TIMESTAMP_FIELD: &str = "clock_timestamp";
+
+/// Number of secondary clock domains, numbered from zero
+pub const MAX_CLOCK_DOMAINS: u8 = 64;
 
 type Id = u64;
 
+/// Limits of the sizes of trace packets, configured when initializing the subscriber
+///
+/// Longer names and string values are truncated, larger packets are dropped with an error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceLimits {
+    /// Maximal size of the names of spans, events, fields and counters and of string values in bytes
+    pub info_size: usize,
+    /// Maximal size of a serialized packet in bytes
+    pub packet_size: usize,
+}
+
+impl Default for TraceLimits {
+    fn default() -> Self {
+        Self {
+            info_size: DEFAULT_INFO_SIZE,
+            packet_size: DEFAULT_PACKET_SIZE,
+        }
+    }
+}
+
+impl TraceLimits {
+    /// Limits of the given size of names and string values, with packets large enough for all fields
+    pub fn with_info_size(info_size: usize) -> Self {
+        Self {
+            info_size,
+            packet_size: STRINGS_PER_PACKET * info_size + PACKET_OVERHEAD,
+        }
+        .bounded()
+    }
+
+    /// Clamp the limits to the bounds supported by feo-tracer, [MAX_INFO_SIZE] and [MAX_PACKET_SIZE]
+    ///
+    /// Packets are never limited to less than [DEFAULT_PACKET_SIZE] bytes.
+    pub fn bounded(self) -> Self {
+        Self {
+            info_size: self.info_size.clamp(1, MAX_INFO_SIZE),
+            packet_size: self.packet_size.clamp(DEFAULT_PACKET_SIZE, MAX_PACKET_SIZE),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Process {
     pub pid: u32,
@@ -46,8 +134,7 @@ impl Process {
 pub enum TraceData {
     NewSpan {
         id: Id,
-        name: [u8; MAX_INFO_SIZE],
-        name_len: usize,
+        name: Cow<'static, str>,
         info: EventInfo,
     },
     Record {
@@ -55,8 +142,7 @@ pub enum TraceData {
     },
     Event {
         parent_span: Option<Id>,
-        name: [u8; MAX_INFO_SIZE],
-        name_len: usize,
+        name: Cow<'static, str>,
         info: EventInfo,
     },
     Enter {
@@ -65,32 +151,256 @@ pub enum TraceData {
     Exit {
         span: Id,
     },
+    /// Current value of a counter, see [counter](crate::counter)
+    Counter { name: String, value: CounterValue },
+    /// Protocol version of the subscriber, see [PROTOCOL_VERSION]
+    Hello { version: u16 },
+    /// Reading of a secondary clock domain at the time of the packet, see [clock_sync](crate::clock_sync)
+    ClockSync(ClockTimestamp),
 }
 
-/// Additional info that can be attached to an event
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Timestamp on a secondary clock domain, e.g. the clock of a GPU or of an external sensor
+///
+/// The domains are numbered by the application, below [MAX_CLOCK_DOMAINS], and scoped to the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockTimestamp {
+    /// Number of the clock domain
+    pub domain: u8,
+    /// Timestamp in nanoseconds
+    pub timestamp: u64,
+}
+
+/// Secondary clock domain and timestamp, recorded from the fields of an event
+#[derive(Debug, Default)]
+pub struct ClockInfo {
+    pub domain: Option<u8>,
+    pub timestamp: Option<u64>,
+}
+
+impl ClockInfo {
+    /// Get the timestamp, if both the domain and the timestamp have been recorded
+    pub fn into_timestamp(self) -> Option<ClockTimestamp> {
+        Some(ClockTimestamp {
+            domain: self.domain.filter(|domain| *domain < MAX_CLOCK_DOMAINS)?,
+            timestamp: self.timestamp?,
+        })
+    }
+}
+
+impl Visit for ClockInfo {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if let Ok(value) = u64::try_from(value) {
+            self.record_u64(field, value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            CLOCK_DOMAIN_FIELD => self.domain = u8::try_from(value).ok(),
+            CLOCK_TIMESTAMP_FIELD => self.timestamp = Some(value),
+            _ => {},
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+}
+
+/// Value of a counter
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CounterValue {
+    I64(i64),
+    F64(f64),
+}
+
+/// Name and value of a counter, recorded from the fields of a counter event
+#[derive(Debug)]
+pub struct CounterInfo {
+    pub name: String,
+    pub value: Option<CounterValue>,
+    /// Maximal size of the name in bytes
+    info_size: usize,
+}
+
+impl CounterInfo {
+    /// Create an empty counter whose name is truncated to `info_size` bytes
+    pub fn new(info_size: usize) -> Self {
+        Self {
+            name: String::new(),
+            value: None,
+            info_size,
+        }
+    }
+
+    /// Get the trace data of the counter, if its value has been recorded
+    pub fn into_trace_data(self) -> Option<TraceData> {
+        Some(TraceData::Counter {
+            name: self.name,
+            value: self.value?,
+        })
+    }
+}
+
+impl Default for CounterInfo {
+    fn default() -> Self {
+        Self::new(DEFAULT_INFO_SIZE)
+    }
+}
+
+impl Visit for CounterInfo {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == COUNTER_NAME_FIELD {
+            self.name = truncate(value, self.info_size).to_string();
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == COUNTER_VALUE_FIELD {
+            self.value = Some(CounterValue::I64(value));
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == COUNTER_VALUE_FIELD {
+            // Values beyond the range of i64 are clamped, Perfetto counters are signed
+            self.value = Some(CounterValue::I64(value.min(i64::MAX as u64) as i64));
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == COUNTER_VALUE_FIELD {
+            self.value = Some(CounterValue::F64(value));
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+}
+
+/// Additional info that can be attached to an event: its target, source location and up to [MAX_FIELDS] named fields
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EventInfo {
-    pub name: [u8; MAX_INFO_SIZE],
-    pub name_len: Option<usize>,
-    pub value: [u8; MAX_INFO_SIZE],
-    pub value_len: usize,
+    /// Target of the span or event, usually its module path
+    pub target: Cow<'static, str>,
+    /// Source file and line of the span or event, if known
+    pub location: Option<(Cow<'static, str>, u32)>,
+    /// Recorded fields, filled from the start
+    pub fields: [Option<EventField>; MAX_FIELDS],
+    /// Maximal size of the names and string values of recorded fields in bytes, not transmitted
+    #[serde(skip)]
+    info_size: usize,
+}
+
+impl EventInfo {
+    /// Create an info without fields, truncating names and string values of fields to `info_size` bytes
+    pub fn new(info_size: usize) -> Self {
+        Self {
+            target: Cow::Borrowed(""),
+            location: None,
+            fields: Default::default(),
+            info_size,
+        }
+    }
+
+    /// Set the target and source location from the metadata of the span or event
+    ///
+    /// Source files are truncated from the start, keeping the file name.
+    pub fn set_metadata(&mut self, metadata: &'static Metadata<'static>) {
+        self.target = Cow::Borrowed(truncate(metadata.target(), self.info_size));
+        self.location = metadata
+            .file()
+            .zip(metadata.line())
+            .map(|(file, line)| (Cow::Borrowed(truncate_start(file, self.info_size)), line));
+    }
+
+    /// Add a field, dropping it if [MAX_FIELDS] fields are already recorded
+    pub fn push(&mut self, name: &'static str, value: FieldValue) {
+        if let Some(slot) = self.fields.iter_mut().find(|field| field.is_none()) {
+            *slot = Some(EventField {
+                name: Cow::Borrowed(truncate(name, self.info_size)),
+                value,
+            });
+        }
+    }
+
+    /// Iterate over the recorded fields
+    pub fn fields(&self) -> impl Iterator<Item = &EventField> {
+        self.fields.iter().flatten()
+    }
+
+    /// Remove the fields of a secondary clock timestamp, see [ClockTimestamp]
+    pub fn remove_clock_fields(&mut self) {
+        for slot in self.fields.iter_mut() {
+            if slot
+                .as_ref()
+                .is_some_and(|field| field.name == CLOCK_DOMAIN_FIELD || field.name == CLOCK_TIMESTAMP_FIELD)
+            {
+                *slot = None;
+            }
+        }
+    }
+}
+
+impl Default for EventInfo {
+    fn default() -> Self {
+        Self::new(DEFAULT_INFO_SIZE)
+    }
 }
 
 impl Visit for EventInfo {
     fn record_str(&mut self, field: &Field, value: &str) {
-        self.name_len = Some(truncate(field.name(), &mut self.name));
-        self.value_len = truncate(value, &mut self.value);
+        self.push(field.name(), FieldValue::str(value, self.info_size));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field.name(), FieldValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field.name(), FieldValue::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field.name(), FieldValue::F64(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field.name(), FieldValue::Bool(value));
     }
 
     fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
 }
 
+/// Named field of a span or event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventField {
+    pub name: Cow<'static, str>,
+    pub value: FieldValue,
+}
+
+/// Value of a field of a span or event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+impl FieldValue {
+    /// Create a string value, truncated to `info_size` bytes
+    pub fn str(slice: &str, info_size: usize) -> Self {
+        FieldValue::Str(truncate(slice, info_size).to_string())
+    }
+}
+
 /// A trace packet
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TracePacket {
     pub timestamp: u64, // nanoseconds
     pub process: Option<Process>,
     pub data: TraceData,
+    /// Time of the traced event on a secondary clock domain, if given by the application
+    pub clock: Option<ClockTimestamp>,
 }
 
 impl TracePacket {
@@ -99,14 +409,22 @@ impl TracePacket {
             timestamp,
             process,
             data,
+            clock: None,
         }
     }
 
+    /// Set the time of the traced event on a secondary clock domain
+    pub fn with_clock(mut self, clock: Option<ClockTimestamp>) -> TracePacket {
+        self.clock = clock;
+        self
+    }
+
     pub fn now_with_data(data: TraceData) -> TracePacket {
         TracePacket {
             timestamp: timestamp(),
             process: Some(Process::this()),
             data,
+            clock: None,
         }
     }
 
@@ -116,17 +434,23 @@ impl TracePacket {
             timestamp: timestamp(),
             process: None,
             data,
+            clock: None,
         }
     }
 }
 
-/// Truncate the given string slice into the given output byte buffer
-///
-/// Returns the number of valid bytes in the buffer
-pub fn truncate(slice: &str, buffer: &mut [u8]) -> usize {
-    let len = trunc_len(slice, buffer.len());
-    buffer[0..len].clone_from_slice(&slice.as_bytes()[0..len]);
-    len
+/// Truncate the given string slice to at most `max_byte_len` bytes without splitting a character
+pub fn truncate(slice: &str, max_byte_len: usize) -> &str {
+    &slice[..trunc_len(slice, max_byte_len)]
+}
+
+/// Truncate the given utf-8 encoded string slice from the start, keeping at most the given number of bytes
+pub fn truncate_start(slice: &str, max_byte_len: usize) -> &str {
+    let mut start = slice.len().saturating_sub(max_byte_len);
+    while !slice.is_char_boundary(start) {
+        start += 1;
+    }
+    &slice[start..]
 }
 
 /// Now epoch in nanoseconds
@@ -137,16 +461,13 @@ fn timestamp() -> u64 {
 /// Return the byte length of the given utf-8 encoded string slice
 /// truncated to fit into the specified maximal length in bytes
 fn trunc_len(slice: &str, max_byte_len: usize) -> usize {
-    let mut len: usize = 0;
-    for (pos, _) in slice.char_indices() {
-        if pos >= max_byte_len {
-            break;
-        }
-        if pos + 1 > len && pos < max_byte_len {
-            len = pos + 1;
-        }
+    if slice.len() <= max_byte_len {
+        return slice.len();
     }
-    len
+    (0..=max_byte_len)
+        .rev()
+        .find(|len| slice.is_char_boundary(*len))
+        .unwrap_or(0)
 }
 
 mod thread {
@@ -154,8 +475,21 @@ mod thread {
     pub(crate) type ThreadId = u32;
 
     /// Get the current thread id
+    #[cfg(unix)]
     pub(crate) fn id() -> ThreadId {
         // Safety: gettid(2) says this never fails
         unsafe { libc::gettid() as u32 }
     }
+
+    /// Get an id of the current thread, unique within the process
+    #[cfg(not(unix))]
+    pub(crate) fn id() -> ThreadId {
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+        std::thread_local! {
+            static ID: ThreadId = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        }
+        ID.with(|id| *id)
+    }
 }
@@ -0,0 +1,343 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Shared memory transport of trace packets
+//!
+//! Instead of writing each serialized packet to the Unix socket, the subscriber writes the packets
+//! into a single-producer single-consumer ring buffer in POSIX shared memory named [shm_name].
+//! The ring carries the same stream of COBS framed packets as the socket.
+//!
+//! The subscriber creates the ring before connecting to the [shm socket](crate::paths::tracer_shm_socket)
+//! of feo-tracer. feo-tracer derives the name of the ring from the PID of the peer, maps and unlinks it.
+//! Afterwards, the socket is only used for wakeups: when feo-tracer drained the ring, it announces to
+//! wait in the ring header, and the subscriber writes a single byte to the socket after its next write
+//! into the ring.
+//! Closing the socket ends the transport.
+
+use core::mem::size_of;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::ffi::CString;
+use std::io;
+
+/// Default size of the data area of a ring in bytes
+pub const DEFAULT_CAPACITY: usize = 1 << 20;
+
+/// Name of the shared memory object of the ring of the process with the given PID
+pub fn shm_name(pid: u32) -> String {
+    format!("/feo-tracer-{pid}")
+}
+
+/// Value aligned to its own cache line, so that the writer and reader do not share cache lines
+#[repr(C, align(64))]
+struct CacheLine<T>(T);
+
+/// Header of a ring at the start of the shared memory
+#[repr(C)]
+struct RingHeader {
+    /// Total number of bytes written, only modified by the writer
+    head: CacheLine<AtomicU64>,
+    /// Total number of bytes read, only modified by the reader
+    tail: CacheLine<AtomicU64>,
+    /// Whether the reader waits for a wakeup on the socket
+    reader_waiting: CacheLine<AtomicBool>,
+}
+
+/// Single-producer single-consumer byte ring in shared memory
+///
+/// One process writes with [ShmRing::write], another process reads with [ShmRing::read].
+pub struct ShmRing {
+    /// Start of the mapping
+    mapping: *mut u8,
+    /// Length of the mapping in bytes
+    len: usize,
+    /// Name of the shared memory object, if it is unlinked when dropped
+    owned_name: Option<CString>,
+}
+
+// Safety: The ring is only accessed through atomics and the single writer and reader protocol
+unsafe impl Send for ShmRing {}
+
+impl ShmRing {
+    /// Create the ring with the given name and data capacity in bytes, replacing a stale one
+    ///
+    /// The shared memory object is unlinked when the ring is dropped, unless the reader unlinked it before.
+    pub fn create(name: &str, capacity: usize) -> io::Result<Self> {
+        let c_name = c_name(name)?;
+        let len = size_of::<RingHeader>() + capacity;
+        // Safety: Plain libc calls with a valid, null terminated name; the descriptor is closed below
+        let mapping = unsafe {
+            libc::shm_unlink(c_name.as_ptr());
+            let fd = libc::shm_open(c_name.as_ptr(), libc::O_CREAT | libc::O_EXCL | libc::O_RDWR, 0o600);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let result = if libc::ftruncate(fd, len as libc::off_t) < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                map(fd, len)
+            };
+            libc::close(fd);
+            if result.is_err() {
+                libc::shm_unlink(c_name.as_ptr());
+            }
+            result?
+        };
+        // The mapping of a new shared memory object is zeroed, i.e. the header is initialized
+        Ok(Self {
+            mapping,
+            len,
+            owned_name: Some(c_name),
+        })
+    }
+
+    /// Open the ring with the given name created by the writer and unlink its name
+    pub fn open(name: &str) -> io::Result<Self> {
+        let c_name = c_name(name)?;
+        // Safety: Plain libc calls with a valid, null terminated name; the descriptor is closed below
+        let (mapping, len) = unsafe {
+            let fd = libc::shm_open(c_name.as_ptr(), libc::O_RDWR, 0);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut stat: libc::stat = core::mem::zeroed();
+            let result = if libc::fstat(fd, &mut stat) < 0 {
+                Err(io::Error::last_os_error())
+            } else if (stat.st_size as usize) <= size_of::<RingHeader>() {
+                Err(io::ErrorKind::InvalidData.into())
+            } else {
+                map(fd, stat.st_size as usize).map(|mapping| (mapping, stat.st_size as usize))
+            };
+            libc::close(fd);
+            libc::shm_unlink(c_name.as_ptr());
+            result?
+        };
+        Ok(Self {
+            mapping,
+            len,
+            owned_name: None,
+        })
+    }
+
+    /// Write the given bytes into the ring
+    ///
+    /// Returns `false` without writing anything if there is not enough free space.
+    /// Fails if the reader corrupted the ring header.
+    /// Must only be called by the writer.
+    pub fn write(&self, data: &[u8]) -> io::Result<bool> {
+        let header = self.header();
+        let capacity = self.capacity() as u64;
+        let head = header.head.0.load(Ordering::Relaxed);
+        let tail = header.tail.0.load(Ordering::Acquire);
+        if capacity - self.used(head, tail)? < data.len() as u64 {
+            return Ok(false);
+        }
+
+        let start = (head % capacity) as usize;
+        let first = data.len().min(self.capacity() - start);
+        // Safety: Both ranges are within the data area and not accessed by the reader until published
+        unsafe {
+            ptr::copy_nonoverlapping(data.as_ptr(), self.data().add(start), first);
+            ptr::copy_nonoverlapping(data[first..].as_ptr(), self.data(), data.len() - first);
+        }
+        // Publish the bytes, sequentially consistent with announcing a wait in the reader
+        header.head.0.store(head + data.len() as u64, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    /// Take the wait announced by the reader, returning whether it has to be woken up
+    ///
+    /// Must only be called by the writer, after [ShmRing::write].
+    pub fn take_reader_waiting(&self) -> bool {
+        self.header().reader_waiting.0.swap(false, Ordering::SeqCst)
+    }
+
+    /// Read the available bytes into the given buffer, returning the number of bytes read
+    ///
+    /// Fails if the writer corrupted the ring header.
+    /// Must only be called by the reader.
+    pub fn read(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        let header = self.header();
+        let capacity = self.capacity() as u64;
+        let tail = header.tail.0.load(Ordering::Relaxed);
+        let head = header.head.0.load(Ordering::Acquire);
+        let len = (self.used(head, tail)? as usize).min(buffer.len());
+
+        let start = (tail % capacity) as usize;
+        let first = len.min(self.capacity() - start);
+        // Safety: Both ranges are within the data area and have been published by the writer
+        unsafe {
+            ptr::copy_nonoverlapping(self.data().add(start), buffer.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(self.data(), buffer[first..].as_mut_ptr(), len - first);
+        }
+        header.tail.0.store(tail + len as u64, Ordering::Release);
+        Ok(len)
+    }
+
+    /// Announce that the reader waits for a wakeup
+    ///
+    /// Returns `false` if bytes arrived meanwhile, in which case the reader must not wait.
+    /// Must only be called by the reader.
+    pub fn prepare_wait(&self) -> bool {
+        let header = self.header();
+        header.reader_waiting.0.store(true, Ordering::SeqCst);
+        if header.head.0.load(Ordering::SeqCst) != header.tail.0.load(Ordering::Relaxed) {
+            header.reader_waiting.0.store(false, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    /// Capacity of the data area in bytes
+    pub fn capacity(&self) -> usize {
+        self.len - size_of::<RingHeader>()
+    }
+
+    /// Number of bytes written and not read yet, failing if the positions are inconsistent
+    ///
+    /// Each position is written by another process, which must not be trusted to keep them consistent.
+    fn used(&self, head: u64, tail: u64) -> io::Result<u64> {
+        match head.checked_sub(tail) {
+            Some(used) if used <= self.capacity() as u64 => Ok(used),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt shared memory ring positions")),
+        }
+    }
+
+    fn header(&self) -> &RingHeader {
+        // Safety: The mapping starts with the header, is page aligned and outlives the reference
+        unsafe { &*(self.mapping as *const RingHeader) }
+    }
+
+    fn data(&self) -> *mut u8 {
+        // Safety: The data area follows the header within the mapping
+        unsafe { self.mapping.add(size_of::<RingHeader>()) }
+    }
+}
+
+impl Drop for ShmRing {
+    fn drop(&mut self) {
+        // Safety: The mapping is not used anymore; unlinking a name removed by the reader is harmless
+        unsafe {
+            libc::munmap(self.mapping.cast(), self.len);
+            if let Some(name) = self.owned_name.as_ref() {
+                libc::shm_unlink(name.as_ptr());
+            }
+        }
+    }
+}
+
+fn c_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|_| io::ErrorKind::InvalidInput.into())
+}
+
+/// Map the shared memory object of the given descriptor
+///
+/// # Safety
+///
+/// `fd` must be a valid descriptor of a shared memory object of at least `len` bytes.
+unsafe fn map(fd: libc::c_int, len: usize) -> io::Result<*mut u8> {
+    let mapping = libc::mmap(
+        ptr::null_mut(),
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+        fd,
+        0,
+    );
+    if mapping == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(mapping.cast())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a ring of the given capacity and open it as its reader
+    fn ring_pair(test: &str, capacity: usize) -> (ShmRing, ShmRing) {
+        let name = format!("/feo-tracing-test-{}-{test}", std::process::id());
+        let writer = ShmRing::create(&name, capacity).unwrap();
+        let reader = ShmRing::open(&name).unwrap();
+        (writer, reader)
+    }
+
+    #[test]
+    fn written_bytes_are_read() {
+        let (writer, reader) = ring_pair("read", 64);
+        let mut buffer = [0u8; 64];
+        assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+
+        assert!(writer.write(b"hello").unwrap());
+        assert!(writer.write(b" ring").unwrap());
+        assert_eq!(reader.read(&mut buffer).unwrap(), 10);
+        assert_eq!(&buffer[..10], b"hello ring");
+        assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+
+        // A short buffer leaves the remaining bytes in the ring
+        assert!(writer.write(b"abcdef").unwrap());
+        assert_eq!(reader.read(&mut buffer[..4]).unwrap(), 4);
+        assert_eq!(&buffer[..4], b"abcd");
+        assert_eq!(reader.read(&mut buffer).unwrap(), 2);
+        assert_eq!(&buffer[..2], b"ef");
+    }
+
+    #[test]
+    fn writes_wrap_around() {
+        let (writer, reader) = ring_pair("wrap", 16);
+        let mut buffer = [0u8; 16];
+
+        for round in 0..5u8 {
+            let data = [round; 10];
+            assert!(writer.write(&data).unwrap());
+            assert_eq!(reader.read(&mut buffer).unwrap(), 10);
+            assert_eq!(buffer[..10], data);
+        }
+        assert_eq!(writer.header().head.0.load(Ordering::Relaxed), 50);
+    }
+
+    #[test]
+    fn full_ring_rejects_writes() {
+        let (writer, reader) = ring_pair("full", 16);
+        let mut buffer = [0u8; 16];
+
+        assert!(writer.write(&[1; 12]).unwrap());
+        assert!(!writer.write(&[2; 5]).unwrap());
+        assert!(writer.write(&[2; 4]).unwrap());
+        assert!(!writer.write(&[3]).unwrap());
+
+        // Reading makes room again
+        assert_eq!(reader.read(&mut buffer[..8]).unwrap(), 8);
+        assert!(writer.write(&[3; 8]).unwrap());
+        assert_eq!(reader.read(&mut buffer).unwrap(), 16);
+        assert_eq!(buffer[..4], [1; 4]);
+        assert_eq!(buffer[4..8], [2; 4]);
+        assert_eq!(buffer[8..], [3; 8]);
+    }
+
+    #[test]
+    fn corrupt_positions_are_rejected() {
+        let (writer, reader) = ring_pair("corrupt", 16);
+        let mut buffer = [0u8; 16];
+
+        // The writer claims more bytes than fit into the ring
+        writer.header().head.0.store(17, Ordering::Relaxed);
+        assert!(reader.read(&mut buffer).is_err());
+
+        // The reader claims to have read bytes not written yet
+        writer.header().head.0.store(4, Ordering::Relaxed);
+        reader.header().tail.0.store(8, Ordering::Relaxed);
+        assert!(writer.write(&[0]).is_err());
+        assert!(reader.read(&mut buffer).is_err());
+    }
+}
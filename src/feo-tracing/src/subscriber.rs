@@ -11,54 +11,265 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::protocol::{truncate, EventInfo, TraceData, TracePacket, MAX_INFO_SIZE, MAX_PACKET_SIZE};
+use crate::protocol::{
+    truncate, ClockInfo, CounterInfo, EventInfo, FieldValue, TraceData, TraceLimits, TracePacket, CLOCK_DOMAIN_FIELD,
+    CLOCK_TARGET, COUNTER_TARGET, DEFAULT_INFO_SIZE, DEFAULT_PACKET_SIZE, PROTOCOL_VERSION,
+};
+use crate::paths;
+#[cfg(unix)]
+use crate::shm::{shm_name, ShmRing, DEFAULT_CAPACITY};
+use crate::tap;
+use crate::udp::UdpWriter;
 use core::sync::atomic;
-use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicBool, AtomicU64};
 use core::time::Duration;
-use score_log::error;
 use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use score_log::{debug, error, info, warn};
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::io::Write;
+use std::net::SocketAddr;
+#[cfg(unix)]
 use std::os::unix::net::UnixStream;
-use std::sync::mpsc::SendError;
+use std::sync::mpsc::{RecvTimeoutError, SendError, TrySendError};
 use std::sync::{mpsc, Arc};
 use std::thread::JoinHandle;
 use std::{io, thread};
 use tracing::level_filters::LevelFilter;
 use tracing::span;
 use tracing::subscriber::set_global_default;
+use tracing::Level;
 
-/// The unix socket path used by the tracing daemon to receive trace packets
-pub const UNIX_PACKET_PATH: &str = "/tmp/feo-tracer.sock";
+/// Default size of the channel (number of packets) for transmitting trace packets to the serializing thread
+pub const DEFAULT_CHANNEL_BOUND: usize = 512;
 
-/// Size of the channel (number of packets) for transmitting trace packets to the serializing thread
-const MPSC_CHANNEL_BOUND: usize = 512;
+/// Default size of the buffer (bytes) for transmitting serialized packets to the trace daemon
+pub const DEFAULT_BUFFER_SIZE: usize = 512 * DEFAULT_PACKET_SIZE;
 
-/// Size of the buffer (bytes) for transmitting serialized packets to the trace daemon
-const BUFWRITER_SIZE: usize = 512 * MAX_PACKET_SIZE;
+/// Default maximal time interval after which to flush packets to the daemon
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
 
-/// Size of the maximal time interval after which to flush packets to the daemon
-const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+/// Size of the buffer (bytes) for serializing the hello packet announcing the protocol version
+const HELLO_PACKET_SIZE: usize = 64;
+
+/// Interval between two attempts to (re)connect to the trace daemon
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of packets buffered while not connected to the trace daemon per packet of the channel bound.
+///
+/// If the buffer is full, the oldest packets are dropped.
+const RECONNECT_BUFFER_FACTOR: usize = 8;
+
+/// Interval between two reports of packets dropped in [OverflowMode::Drop]
+const DROP_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval between two attempts to write into a full shared memory ring
+#[cfg(unix)]
+const SHM_FULL_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Number of trace packets dropped by the subscriber of this process since startup
+static DROPPED_PACKETS: AtomicU64 = AtomicU64::new(0);
+
+/// Severities for which dropped packets are counted.
+///
+/// Span enter, exit and record packets have no severity and are counted separately.
+const SEVERITIES: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Behavior of the subscriber if the channel to the serializing thread is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    /// Block the traced thread until the packet can be queued
+    #[default]
+    Block,
+    /// Drop the packet without blocking.
+    ///
+    /// Dropped packets are counted per severity and periodically reported in the trace
+    /// as synthetic "N packets dropped" events, so that gaps in the trace are visible.
+    Drop,
+}
+
+/// Transport of the serialized packets to the trace daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transport {
+    /// Write the packets to the local address of the trace daemon, a Unix socket or a TCP connection
+    /// on the loopback interface, see [local](crate::local)
+    #[default]
+    Socket,
+    /// Write the packets into a ring buffer in shared memory, using the socket for setup and wakeups only.
+    ///
+    /// Avoids a system call per written buffer at high event rates, see `shm`. Only available on Unix systems.
+    SharedMemory,
+    /// Send the packets as UDP datagrams to a feo-tracer on another machine, or a multicast group
+    /// joined by it, see [udp](crate::udp).
+    ///
+    /// For targets without a local trace daemon. Datagrams lost in the network are not retransmitted.
+    Udp(SocketAddr),
+}
+
+/// Strategy of flushing the serialized packets buffered by the subscriber to the trace daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Flush at most after the given interval, keeping the number of writes to the trace daemon low
+    Interval(Duration),
+    /// Flush after every packet, e.g. for debugging short-lived processes whose final packets
+    /// would otherwise be lost in the buffer when the process exits
+    EveryPacket,
+}
+
+impl Default for FlushMode {
+    fn default() -> Self {
+        FlushMode::Interval(DEFAULT_FLUSH_INTERVAL)
+    }
+}
+
+/// Configuration of the tracing subscriber, see [init_with]
+///
+/// ```ignore
+/// feo_tracing::init_with(Config {
+///     flush: FlushMode::EveryPacket,
+///     ..Config::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Most verbose level traced
+    pub level: LevelFilter,
+    /// Behavior if the channel to the serializing thread is full
+    pub overflow: OverflowMode,
+    /// Transport of the packets to the trace daemon
+    pub transport: Transport,
+    /// Limits of the sizes of the trace packets, clamped to the bounds supported by feo-tracer
+    pub limits: TraceLimits,
+    /// Strategy of flushing buffered packets to the trace daemon
+    pub flush: FlushMode,
+    /// Size of the buffer (bytes) for writing serialized packets to the socket of the trace daemon
+    pub buffer_size: usize,
+    /// Size of the channel (number of packets) to the serializing thread
+    ///
+    /// Up to eight times as many packets are buffered while not connected to the trace daemon.
+    pub channel_bound: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::TRACE,
+            overflow: OverflowMode::default(),
+            transport: Transport::default(),
+            limits: TraceLimits::default(),
+            flush: FlushMode::default(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            channel_bound: DEFAULT_CHANNEL_BOUND,
+        }
+    }
+}
+
+/// Get the number of trace packets dropped in this process since startup
+///
+/// Counts the packets dropped on overflow in [OverflowMode::Drop], the packets dropped from the full
+/// buffer while disconnected from feo-tracer and the packets written but not flushed when the connection failed.
+pub fn dropped_packets() -> u64 {
+    DROPPED_PACKETS.load(atomic::Ordering::Relaxed)
+}
 
 /// Initialize the tracing subscriber with the given level
+///
+/// Tracing blocks if the serializing thread cannot keep up. See [init_with] for other settings.
 pub fn init(level: LevelFilter) {
-    let (sender, receiver) = mpsc::sync_channel::<TracePacket>(MPSC_CHANNEL_BOUND);
+    init_with(Config {
+        level,
+        ..Config::default()
+    });
+}
+
+/// Initialize the tracing subscriber with the given configuration
+pub fn init_with(config: Config) {
+    let config = Config {
+        limits: config.limits.bounded(),
+        buffer_size: config.buffer_size.max(config.limits.packet_size),
+        channel_bound: config.channel_bound.max(1),
+        ..config
+    };
+    let (sender, receiver) = mpsc::sync_channel::<TracePacket>(config.channel_bound);
     let enabled = Arc::new(AtomicBool::new(true));
+    let dropped = match config.overflow {
+        OverflowMode::Block => None,
+        OverflowMode::Drop => Some(Arc::new(DropCounters::new())),
+    };
 
     // Spawn thread for serializing trace packets and sending to the trace daemon
-    let _thread = {
-        let enabled = Arc::clone(&enabled);
-        thread::spawn(|| Subscriber::thread_main(receiver, enabled))
-    };
+    let thread_dropped = dropped.clone();
+    let _thread = thread::spawn(move || Subscriber::thread_main(receiver, thread_dropped, config));
 
     let subscriber = Subscriber {
-        max_level: level,
+        max_level: config.level,
+        limits: config.limits,
         enabled,
         _thread,
         sender,
+        dropped,
     };
     set_global_default(subscriber).expect("setting tracing default failed");
 }
 
+/// Counters of packets dropped because the channel to the serializing thread was full
+struct DropCounters {
+    /// Dropped packets per entry of [SEVERITIES], followed by the packets without severity
+    counts: [AtomicU64; SEVERITIES.len() + 1],
+    /// Timestamp of the first packet dropped since the last report, `u64::MAX` if none
+    first_timestamp: AtomicU64,
+}
+
+impl DropCounters {
+    fn new() -> Self {
+        Self {
+            counts: Default::default(),
+            first_timestamp: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// Count a dropped packet with the given severity
+    fn count(&self, packet: &TracePacket, level: Option<Level>) {
+        let index = level
+            .and_then(|level| SEVERITIES.iter().position(|severity| *severity == level))
+            .unwrap_or(SEVERITIES.len());
+        self.first_timestamp
+            .fetch_min(packet.timestamp, atomic::Ordering::Relaxed);
+        self.counts[index].fetch_add(1, atomic::Ordering::Relaxed);
+        DROPPED_PACKETS.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    /// Take the counts since the last report as synthetic events, one per severity
+    fn take_reports(&self) -> Vec<TracePacket> {
+        let timestamp = self.first_timestamp.swap(u64::MAX, atomic::Ordering::Relaxed);
+        let severities = SEVERITIES.iter().map(Level::as_str).chain(["SPAN"]);
+        severities
+            .zip(self.counts.iter())
+            .filter_map(|(severity, count)| {
+                let count = count.swap(0, atomic::Ordering::Relaxed);
+                (count > 0).then(|| Self::report(timestamp, severity, count))
+            })
+            .collect()
+    }
+
+    /// Create a synthetic event reporting `count` dropped packets with the given severity
+    fn report(timestamp: u64, severity: &str, count: u64) -> TracePacket {
+        let mut info = EventInfo::default();
+        info.push("severity", FieldValue::str(severity, DEFAULT_INFO_SIZE));
+        let trace_data = TraceData::Event {
+            parent_span: None,
+            name: Cow::Owned(format!("{count} packets dropped")),
+            info,
+        };
+        let mut packet = TracePacket::now_with_data(trace_data);
+        // Place the report at the start of the gap, if known
+        if timestamp != u64::MAX {
+            packet.timestamp = timestamp;
+        }
+        packet
+    }
+}
+
 /// ScoreDebug support for std::io::Error
 #[derive(Debug)]
 pub struct ScoreDebugIoError(pub std::io::Error);
@@ -166,14 +377,19 @@ impl ScoreDebug for ScoreDebugSendError {
     }
 }
 
-/// A subscriber sending trace data to the feo-tracer via unix socket and postcard serialized data.
+/// A subscriber sending trace data to the feo-tracer via unix socket, shared memory or UDP
+/// and postcard serialized data.
 ///
 /// See the `TraceData` and `TracePacket` types for the data format.
 struct Subscriber {
     max_level: LevelFilter,
+    /// Limits of the sizes of the trace packets
+    limits: TraceLimits,
     enabled: Arc<AtomicBool>,
     _thread: JoinHandle<()>,
     sender: mpsc::SyncSender<TracePacket>,
+    /// Counters of dropped packets, if the subscriber drops packets on overflow
+    dropped: Option<Arc<DropCounters>>,
 }
 
 impl Subscriber {
@@ -188,56 +404,200 @@ impl Subscriber {
         span::Id::from_u64(id)
     }
 
-    fn thread_main(receiver: mpsc::Receiver<TracePacket>, enabled: Arc<AtomicBool>) {
-        let connection = match UnixStream::connect(UNIX_PACKET_PATH) {
-            Ok(connection) => connection,
-            Err(e) => {
-                error!("Failed to connect to feo-tracer: {:?}, aborting", ScoreDebugIoError(e));
-                // disable further tracing (TODO: add a time period of retrying)
-                enabled.store(false, atomic::Ordering::Relaxed);
-                return;
-            },
-        };
-
+    fn thread_main(
+        receiver: mpsc::Receiver<TracePacket>,
+        overflow: Option<Arc<DropCounters>>,
+        config: Config,
+    ) {
         // Create buffer for serialization
-        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let mut buffer = vec![0u8; config.limits.packet_size];
+
+        // Packets not written to the trace daemon yet, buffered while not connected
+        let reconnect_buffer_size = RECONNECT_BUFFER_FACTOR * config.channel_bound;
+        let mut pending = VecDeque::with_capacity(reconnect_buffer_size);
+        let mut dropped = 0usize;
 
-        // Create BufferedWriter for socket
-        let mut socket_writer = io::BufWriter::with_capacity(BUFWRITER_SIZE, connection);
+        // Writer to the trace daemon, if connected
+        let mut socket_writer = Self::connect(config.transport, config.buffer_size);
+        if socket_writer.is_none() {
+            warn!("feo-tracer not available, buffering trace packets until connected");
+        }
+        let mut last_connect_attempt = std::time::Instant::now();
         let mut last_flush = std::time::Instant::now();
+        let mut last_report = std::time::Instant::now();
+        // Number of packets written since the last flush, lost if the connection fails before the next one
+        let mut unflushed = 0usize;
 
         loop {
-            let packet = receiver.recv().expect("trace subscriber failed to receive, aborting");
-
-            let serialized = match postcard::to_slice_cobs(&packet, &mut buffer[..]) {
-                Ok(serialized) => serialized,
-                Err(e) => {
-                    error!("Failed to serialize trace packet: {:?}", ScoreDebugPostcardError(e));
-                    continue;
+            // While disconnected, wake up periodically to retry connecting.
+            // When dropping packets on overflow, wake up periodically to report them.
+            // With unflushed packets, wake up to flush them even if no more packets arrive.
+            let flush_timeout = match config.flush {
+                FlushMode::Interval(interval) if unflushed > 0 => Some(interval),
+                _ => None,
+            };
+            let packet = match (&socket_writer, &overflow, flush_timeout) {
+                (None, _, _) => receiver.recv_timeout(RECONNECT_INTERVAL),
+                (Some(_), Some(_), timeout) => {
+                    receiver.recv_timeout(timeout.map_or(DROP_REPORT_INTERVAL, |t| t.min(DROP_REPORT_INTERVAL)))
                 },
+                (Some(_), None, Some(timeout)) => receiver.recv_timeout(timeout),
+                (Some(_), None, None) => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let packet = match packet {
+                Ok(packet) => Some(packet),
+                Err(RecvTimeoutError::Timeout) => None,
+                // All senders are gone, i.e. the subscriber has been dropped
+                Err(RecvTimeoutError::Disconnected) => return,
             };
 
-            let ret = socket_writer.write_all(serialized);
-            if let Err(error) = ret {
-                error!("Failed to send to feo-tracer: {:?}, aborting", ScoreDebugIoError(error));
-                enabled.store(false, atomic::Ordering::Relaxed);
-                return;
+            // Queue the reports of dropped packets before the received packet to preserve the order
+            if let Some(overflow) = overflow.as_ref() {
+                if last_report.elapsed() >= DROP_REPORT_INTERVAL {
+                    last_report = std::time::Instant::now();
+                    for report in overflow.take_reports() {
+                        Self::buffer_packet(&mut pending, report, &mut dropped, reconnect_buffer_size);
+                    }
+                }
+            }
+
+            if socket_writer.is_none() && last_connect_attempt.elapsed() >= RECONNECT_INTERVAL {
+                last_connect_attempt = std::time::Instant::now();
+                socket_writer = Self::connect(config.transport, config.buffer_size);
+                if socket_writer.is_some() && dropped > 0 {
+                    warn!("Dropped {} trace packets while disconnected from feo-tracer", dropped);
+                    dropped = 0;
+                }
+            }
+
+            // Queue the received packet behind the buffered ones to preserve the order
+            if let Some(packet) = packet {
+                Self::buffer_packet(&mut pending, packet, &mut dropped, reconnect_buffer_size);
             }
+            let Some(writer) = socket_writer.as_mut() else {
+                continue;
+            };
 
-            // Flush, if pre-defined time interval elapsed or insufficient spare capacity
-            if last_flush.elapsed() > FLUSH_INTERVAL {
-                socket_writer.flush().expect("failed to flush");
-                last_flush = std::time::Instant::now();
+            // Packets are only removed from the buffer once written, so that a packet failing to be
+            // written is sent again after reconnecting
+            let mut result = Ok(());
+            while let Some(packet) = pending.front() {
+                result = Self::write_packet(writer, packet, &mut buffer);
+                if result.is_err() {
+                    break;
+                }
+                pending.pop_front();
+                unflushed += 1;
+            }
+            let result = result.and_then(|_| {
+                // Flush after every packet or if the configured interval elapsed
+                let due = match config.flush {
+                    FlushMode::EveryPacket => true,
+                    FlushMode::Interval(interval) => last_flush.elapsed() >= interval,
+                };
+                if unflushed > 0 && due {
+                    last_flush = std::time::Instant::now();
+                    writer.flush()?;
+                    unflushed = 0;
+                }
+                Ok(())
+            });
+
+            if let Err(e) = result {
+                warn!(
+                    "Lost connection to feo-tracer: {:?}, trying to reconnect",
+                    ScoreDebugIoError(e)
+                );
+                // Packets written but not flushed yet may not have reached the trace daemon
+                dropped += unflushed;
+                DROPPED_PACKETS.fetch_add(unflushed as u64, atomic::Ordering::Relaxed);
+                unflushed = 0;
+                socket_writer = None;
+                last_connect_attempt = std::time::Instant::now();
             }
         }
     }
 
+    /// Buffer a packet until it is written, dropping the oldest one if the buffer is full
+    fn buffer_packet(pending: &mut VecDeque<TracePacket>, packet: TracePacket, dropped: &mut usize, capacity: usize) {
+        if pending.len() >= capacity {
+            pending.pop_front();
+            *dropped += 1;
+            DROPPED_PACKETS.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        pending.push_back(packet);
+    }
+
+    /// Try to connect to the trace daemon with the given transport, announcing our protocol version
+    ///
+    /// Packets written to the socket are buffered with the given size.
+    fn connect(transport: Transport, buffer_size: usize) -> Option<Box<dyn Write>> {
+        let result = match transport {
+            Transport::Socket => paths::tracer_address(None).connect().map(|connection| {
+                Box::new(io::BufWriter::with_capacity(buffer_size, connection)) as Box<dyn Write>
+            }),
+            #[cfg(unix)]
+            Transport::SharedMemory => ShmWriter::connect().map(|writer| Box::new(writer) as Box<dyn Write>),
+            #[cfg(not(unix))]
+            Transport::SharedMemory => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "shared memory transport requires a Unix system",
+            )),
+            Transport::Udp(destination) => {
+                UdpWriter::connect(destination).map(|writer| Box::new(writer) as Box<dyn Write>)
+            },
+        };
+        let result = result.and_then(|mut writer| {
+            let hello = TracePacket::now_with_data(TraceData::Hello {
+                version: PROTOCOL_VERSION,
+            });
+            Self::write_packet(&mut writer, &hello, &mut [0u8; HELLO_PACKET_SIZE])?;
+            Ok(writer)
+        });
+        match result {
+            Ok(writer) => {
+                info!("Connected to feo-tracer");
+                Some(writer)
+            },
+            Err(e) => {
+                debug!("Failed to connect to feo-tracer: {:?}", ScoreDebugIoError(e));
+                None
+            },
+        }
+    }
+
+    /// Serialize a packet and write it to the given writer.
+    ///
+    /// Packets failing to serialize are dropped with an error message.
+    fn write_packet(writer: &mut impl Write, packet: &TracePacket, buffer: &mut [u8]) -> io::Result<()> {
+        match postcard::to_slice_cobs(packet, buffer) {
+            Ok(serialized) => writer.write_all(serialized),
+            Err(e) => {
+                error!("Failed to serialize trace packet: {:?}", ScoreDebugPostcardError(e));
+                Ok(())
+            },
+        }
+    }
+
     // Send a value to the tracer
-    fn send(&self, packet: TracePacket) {
+    //
+    // The level of the traced span or event, if any, is used to count the packet if it is dropped.
+    fn send(&self, packet: TracePacket, level: Option<Level>) {
         if !self.enabled.load(atomic::Ordering::Relaxed) {
             return;
         }
-        if let Err(e) = self.sender.send(packet) {
+        let result = match self.dropped.as_ref() {
+            None => self.sender.send(packet),
+            Some(dropped) => match self.sender.try_send(packet) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(packet)) => {
+                    dropped.count(&packet, level);
+                    Ok(())
+                },
+                Err(TrySendError::Disconnected(packet)) => Err(SendError(packet)),
+            },
+        };
+        if let Err(e) = result {
             error!(
                 "Failed to connect to feo-tracer: {:?}, aborting",
                 ScoreDebugSendError(e)
@@ -247,6 +607,51 @@ impl Subscriber {
     }
 }
 
+/// Writer of serialized packets into the shared memory ring read by the trace daemon
+#[cfg(unix)]
+struct ShmWriter {
+    ring: ShmRing,
+    /// Socket to the trace daemon, used for wakeups only
+    socket: UnixStream,
+}
+
+#[cfg(unix)]
+impl ShmWriter {
+    /// Create the ring of this process and connect to the trace daemon
+    fn connect() -> io::Result<Self> {
+        let ring = ShmRing::create(&shm_name(std::process::id()), DEFAULT_CAPACITY)?;
+        let socket = UnixStream::connect(paths::tracer_shm_socket(None))?;
+        Ok(Self { ring, socket })
+    }
+
+    /// Wake up the trace daemon, if it waits for data
+    fn wake(&mut self) -> io::Result<()> {
+        if self.ring.take_reader_waiting() {
+            self.socket.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl Write for ShmWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Packets are written as a whole, waiting for the trace daemon to make room if necessary
+        let len = buf.len().min(self.ring.capacity());
+        while !self.ring.write(&buf[..len])? {
+            self.wake()?;
+            thread::sleep(SHM_FULL_RETRY_INTERVAL);
+        }
+        self.wake()?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Written packets are visible to the trace daemon immediately
+        Ok(())
+    }
+}
+
 impl tracing::Subscriber for Subscriber {
     fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
         // A span or event is enabled if it is at or below the configured
@@ -260,53 +665,79 @@ impl tracing::Subscriber for Subscriber {
 
     fn new_span(&self, span: &span::Attributes) -> span::Id {
         let id = self.new_span_id();
-        let mut name = [0u8; MAX_INFO_SIZE];
-        let name_len = truncate(span.metadata().name(), &mut name);
-        let mut info = EventInfo::default();
+        let name = Cow::Borrowed(truncate(span.metadata().name(), self.limits.info_size));
+        let mut info = EventInfo::new(self.limits.info_size);
+        info.set_metadata(span.metadata());
         span.record(&mut info);
         let trace_data = TraceData::NewSpan {
             id: id.into_u64(),
             name,
-            name_len,
             info,
         };
         let trace_packet = TracePacket::now_with_data(trace_data);
-        self.send(trace_packet);
+        self.send(trace_packet, Some(*span.metadata().level()));
         id
     }
 
     fn record(&self, span: &span::Id, _: &span::Record) {
         let trace_data = TraceData::Record { span: span.into_u64() };
         let trace_packet = TracePacket::now_with_data(trace_data);
-        self.send(trace_packet);
+        self.send(trace_packet, None);
     }
 
     fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
 
     fn event(&self, event: &tracing::Event) {
-        let mut name = [0u8; MAX_INFO_SIZE];
-        let name_len = truncate(event.metadata().name(), &mut name);
-        let mut info = EventInfo::default();
+        if event.metadata().target() == COUNTER_TARGET {
+            let mut counter = CounterInfo::new(self.limits.info_size);
+            event.record(&mut counter);
+            if let Some(trace_data) = counter.into_trace_data() {
+                let trace_packet = TracePacket::now_with_data(trace_data);
+                self.send(trace_packet, Some(*event.metadata().level()));
+            }
+            return;
+        }
+        if event.metadata().target() == CLOCK_TARGET {
+            let mut clock = ClockInfo::default();
+            event.record(&mut clock);
+            if let Some(clock) = clock.into_timestamp() {
+                let trace_packet = TracePacket::now_with_data(TraceData::ClockSync(clock));
+                self.send(trace_packet, Some(*event.metadata().level()));
+            }
+            return;
+        }
+        tap::forward(event);
+
+        let name = Cow::Borrowed(truncate(event.metadata().name(), self.limits.info_size));
+        let mut info = EventInfo::new(self.limits.info_size);
+        info.set_metadata(event.metadata());
         event.record(&mut info);
+        // The time of the event on a secondary clock domain is sent with the packet instead of as fields
+        let mut clock = None;
+        if info.fields().any(|field| field.name == CLOCK_DOMAIN_FIELD) {
+            let mut clock_info = ClockInfo::default();
+            event.record(&mut clock_info);
+            clock = clock_info.into_timestamp();
+            info.remove_clock_fields();
+        }
         let trace_data = TraceData::Event {
             parent_span: self.current_span().id().map(|id| id.into_u64()),
             name,
-            name_len,
             info,
         };
-        let trace_packet = TracePacket::now_with_data(trace_data);
-        self.send(trace_packet);
+        let trace_packet = TracePacket::now_with_data(trace_data).with_clock(clock);
+        self.send(trace_packet, Some(*event.metadata().level()));
     }
 
     fn enter(&self, span: &span::Id) {
         let trace_data = TraceData::Enter { span: span.into_u64() };
         let trace_packet = TracePacket::now_without_process(trace_data);
-        self.send(trace_packet);
+        self.send(trace_packet, None);
     }
 
     fn exit(&self, span: &span::Id) {
         let trace_data = TraceData::Exit { span: span.into_u64() };
         let trace_packet = TracePacket::now_without_process(trace_data);
-        self.send(trace_packet);
+        self.send(trace_packet, None);
     }
 }
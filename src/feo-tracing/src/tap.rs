@@ -0,0 +1,38 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Tap on the trace events of the process
+//!
+//! Besides sending them to feo-tracer, the subscriber passes each enabled trace event to a tap installed
+//! with [set_event_tap], e.g. to forward selected events to a logging backend. Counters and spans are
+//! not passed. The tap is called on the traced thread and should return quickly.
+
+use std::sync::OnceLock;
+use tracing::Event;
+
+/// Function called with each trace event
+pub type EventTap = Box<dyn Fn(&Event<'_>) + Send + Sync>;
+
+static TAP: OnceLock<EventTap> = OnceLock::new();
+
+/// Install the tap of this process, returning `false` if a tap is installed already
+pub fn set_event_tap(tap: EventTap) -> bool {
+    TAP.set(tap).is_ok()
+}
+
+/// Pass the given event to the tap, if installed
+pub(crate) fn forward(event: &Event<'_>) {
+    if let Some(tap) = TAP.get() {
+        tap(event);
+    }
+}
@@ -0,0 +1,145 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! UDP transport of trace packets to a feo-tracer on another machine
+//!
+//! Targets without a writable file system or a local trace daemon stream their trace packets as UDP
+//! datagrams to a feo-tracer running on a developer machine, see [Transport::Udp](crate::Transport::Udp).
+//! The destination is either the unicast address of the developer machine or a multicast group
+//! joined by the tracer, so that several tracers can capture the same stream.
+//!
+//! Each datagram starts with a [DatagramHeader] holding the PID of the traced process and a sequence
+//! number counting the datagrams sent by it, followed by complete COBS frames of packets as on the
+//! other transports. Datagrams are not retransmitted: the tracer counts the datagrams lost in the
+//! network from gaps in the sequence numbers, while the subscriber counts the datagrams it failed to send.
+
+use score_log::warn;
+use std::io::{self, Write};
+use std::net::{SocketAddr, UdpSocket};
+
+/// Default UDP port of the tracer
+pub const DEFAULT_UDP_PORT: u16 = 7470;
+
+/// Size of the datagrams in bytes, fitting into an Ethernet frame without fragmentation
+///
+/// Packets larger than a datagram are sent in a datagram of their own.
+pub const MAX_DATAGRAM_SIZE: usize = 1400;
+
+/// Header of each datagram, little endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatagramHeader {
+    /// Number of the datagram, counting the datagrams sent by the process
+    pub sequence: u64,
+    /// PID of the traced process
+    pub pid: u32,
+}
+
+impl DatagramHeader {
+    /// Size of the serialized header in bytes
+    pub const SIZE: usize = 12;
+
+    /// Serialize the header into the first [Self::SIZE] bytes of the given buffer
+    pub fn write(&self, buffer: &mut [u8]) {
+        buffer[..8].copy_from_slice(&self.sequence.to_le_bytes());
+        buffer[8..Self::SIZE].copy_from_slice(&self.pid.to_le_bytes());
+    }
+
+    /// Split a received datagram into its header and packets, `None` if too short
+    pub fn read(datagram: &[u8]) -> Option<(Self, &[u8])> {
+        if datagram.len() < Self::SIZE {
+            return None;
+        }
+        let (header, packets) = datagram.split_at(Self::SIZE);
+        let sequence = u64::from_le_bytes(header[..8].try_into().ok()?);
+        let pid = u32::from_le_bytes(header[8..].try_into().ok()?);
+        Some((Self { sequence, pid }, packets))
+    }
+}
+
+/// Writer of serialized packets into datagrams sent to the tracer
+///
+/// Packets are collected into a datagram until it is full or flushed. Each call of `write` must pass
+/// one complete COBS frame, so that every datagram can be decoded on its own.
+pub(crate) struct UdpWriter {
+    socket: UdpSocket,
+    /// Address of the tracer or multicast group
+    destination: SocketAddr,
+    /// Datagram being filled, starting with room for the header
+    datagram: Vec<u8>,
+    /// Header of the next datagram
+    header: DatagramHeader,
+    /// Datagrams failed to send since the last successful send
+    failed: u64,
+}
+
+impl UdpWriter {
+    /// Create a socket sending to the given address
+    pub(crate) fn connect(destination: SocketAddr) -> io::Result<Self> {
+        let local: SocketAddr = match destination {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        let mut datagram = Vec::with_capacity(MAX_DATAGRAM_SIZE);
+        datagram.resize(DatagramHeader::SIZE, 0);
+        Ok(Self {
+            socket,
+            destination,
+            datagram,
+            header: DatagramHeader {
+                sequence: 0,
+                pid: std::process::id(),
+            },
+            failed: 0,
+        })
+    }
+
+    /// Send the collected packets, if any
+    ///
+    /// Datagrams failing to send are counted and skipped in the sequence, so that the tracer notices
+    /// the gap, and reported with the next datagram sent.
+    fn send(&mut self) {
+        if self.datagram.len() == DatagramHeader::SIZE {
+            return;
+        }
+        self.header.write(&mut self.datagram);
+        self.header.sequence = self.header.sequence.wrapping_add(1);
+        match self.socket.send_to(&self.datagram, self.destination) {
+            Ok(_) if self.failed > 0 => {
+                warn!("Failed to send {} trace datagrams to feo-tracer", self.failed);
+                self.failed = 0;
+            },
+            Ok(_) => {},
+            Err(_) => self.failed += 1,
+        }
+        self.datagram.truncate(DatagramHeader::SIZE);
+    }
+}
+
+impl Write for UdpWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.datagram.len() + buf.len() > MAX_DATAGRAM_SIZE {
+            self.send();
+        }
+        self.datagram.extend_from_slice(buf);
+        if self.datagram.len() >= MAX_DATAGRAM_SIZE {
+            self.send();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send();
+        Ok(())
+    }
+}
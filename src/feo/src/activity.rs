@@ -15,6 +15,7 @@
 use crate::error::ActivityError;
 use crate::ids::ActivityId;
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 /// Activity trait, to be implemented by any activity intended to run in a WorkerPool
 pub trait Activity {
@@ -29,6 +30,26 @@ pub trait Activity {
 
     /// Called upon shutdown
     fn shutdown(&mut self) -> Result<(), ActivityError>;
+
+    /// Called right before a step for each activity this activity depends on which was skipped in the current cycle
+    ///
+    /// The inputs written by the skipped activity, e.g. because it is disabled or degraded, have not been
+    /// updated for this step and are stale. See [control](crate::control).
+    fn input_stale(&mut self, _producer: ActivityId) {}
+
+    /// Save the state of the activity for a checkpoint, see [checkpoint](crate::recording::checkpoint)
+    ///
+    /// Called between steps. An empty state, as returned by default, is not checkpointed.
+    fn save_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Restore the state of the activity from a checkpoint saved with [Activity::save_state]
+    ///
+    /// Called right after the startup of the activity.
+    fn restore_state(&mut self, _state: &[u8]) -> Result<(), ActivityError> {
+        Ok(())
+    }
 }
 
 /// Activity Builder trait.
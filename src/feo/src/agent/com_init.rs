@@ -12,6 +12,7 @@
 // *******************************************************************************
 
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::recording::join;
 use crate::topicspec::{Direction, TopicSpecification};
 use alloc::vec::Vec;
 use feo_com::interface::{
@@ -28,7 +29,8 @@ use std::collections::{HashMap, HashSet};
 /// * topics_specs: Specifications of all topics used in the application
 ///   (i.e., primary and secondary agents)
 /// * agent_assignments: Map from agent ids to lists of workers with their activities
-/// * max_additional_readers: The maximum number of optional additional readers on a topic
+/// * max_additional_readers: The maximum number of optional additional readers on a topic,
+///   e.g. a [late-joining recorder](crate::recording::join)
 pub fn initialize_com_primary(
     backend: ComBackend,
     agent_id: AgentId,
@@ -65,6 +67,7 @@ pub fn initialize_com_primary(
             is_local_write,
         );
 
+        join::register_topic(spec.topic, spec.type_name);
         let handle = (spec.init_primary_fn)(&init_params);
         handles.push(handle);
     }
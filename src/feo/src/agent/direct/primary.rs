@@ -14,19 +14,43 @@
 //! Implementation of the primary agent for direct scheduler-to-worker signalling
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::register_sigterm_handler;
-use crate::agent::NodeAddress;
-use crate::debug_fmt::ScoreDebugDebug;
-use crate::error::Error;
+use crate::agent::{join_workers, register_sigterm_handler, WorkerThread};
+use crate::agent::{ConnectionPolicy, NodeAddress, DEFAULT_CYCLE_TIME, DEFAULT_ENDPOINT, DEFAULT_TIMEOUT};
+use crate::chain::TaskChains;
+use crate::control::ActivityControl;
+use crate::deadline::StepDeadlines;
+use crate::debugger::CycleDebugger;
+use crate::dependency;
+use crate::error::{ConfigError, Error};
+use crate::error_policy::ErrorPolicies;
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::introspection;
+use crate::launcher::{Launcher, SecondaryProcess};
+use crate::log_relay::{MergedLog, MergedLogConfig};
+use crate::on_demand::CycleTrigger;
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::recording::signals::SignalCapture;
+use crate::reload::ConfigReload;
 use crate::scheduler::Scheduler;
+use crate::signal_forwarding::{self, ForwardedSignal};
 use crate::signalling::common::interface::{ConnectScheduler, ConnectWorker};
 use crate::signalling::direct::mw_com::scheduler_connector::MwComSchedulerConnector;
 use crate::signalling::direct::mw_com::worker_connector::agent_output;
 use crate::signalling::direct::mw_com::worker_connector::MwComWorkerConnector;
+#[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+use crate::signalling::direct::qnx::scheduler::QnxSchedulerConnector;
+#[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+use crate::signalling::direct::qnx::worker::QnxWorkerConnector;
 use crate::signalling::direct::scheduler::{TcpSchedulerConnector, UnixSchedulerConnector};
+use crate::signalling::direct::shm::scheduler::ShmSchedulerConnector;
+use crate::signalling::direct::shm::worker::ShmWorkerConnector;
 use crate::signalling::direct::worker::{TcpWorkerConnector, UnixWorkerConnector};
-use crate::timestamp;
+use crate::standby::{MirroredState, StandbyMirror};
+use crate::startup::StartupOrder;
+use crate::statistics::CycleStatistics;
+use crate::supervision::{AgentSupervisor, Supervision};
+use crate::timestamp::{self, SyncInfo};
+use crate::watchdog::Watchdog;
 use crate::worker::Worker;
 use crate::TOKIO_RT;
 use alloc::boxed::Box;
@@ -36,10 +60,11 @@ use com_api::LolaRuntimeImpl;
 use core::sync::atomic::AtomicBool;
 use feo_time::Duration;
 use score_log::debug;
-use score_log::error;
+use score_log::warn;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Barrier;
-use std::thread::{self, JoinHandle};
+use std::thread;
 
 type WorkerWithActivities = (WorkerId, Vec<ActivityId>);
 
@@ -47,7 +72,7 @@ type WorkerWithActivities = (WorkerId, Vec<ActivityId>);
 pub struct PrimaryConfig {
     /// Id of the primary agent
     pub id: AgentId,
-    /// Cycle time of the step loop
+    /// Cycle time of the main task chain
     pub cycle_time: Duration,
     /// Dependencies per activity
     pub activity_dependencies: HashMap<ActivityId, Vec<ActivityId>>,
@@ -61,18 +86,98 @@ pub struct PrimaryConfig {
     pub connection_timeout: Duration,
     /// Timeout for waiting on activities to become ready during startup.
     pub startup_timeout: Duration,
+    /// Startup dependencies of the activities, see [startup](crate::startup)
+    pub startup_order: StartupOrder,
+    /// Step deadlines of the monitored activities
+    pub step_deadlines: StepDeadlines,
+    /// Task chains and the activities belonging to them
+    pub task_chains: TaskChains,
+    /// Reaction to failed steps per activity
+    pub error_policies: ErrorPolicies,
+    /// Heartbeat supervision of the secondary agents
+    pub supervision: Supervision,
+    /// External watchdog notified by the scheduler once per completed cycle, if any
+    pub watchdog: Option<Watchdog>,
+    /// Unix socket path of the introspection server, if any
+    pub introspection: Option<PathBuf>,
+    /// Cycle statistics collected by the scheduler, if any
+    pub statistics: Option<CycleStatistics>,
+    /// Capture of the step signals of the scheduler for a recorder in this agent, if any
+    pub signal_capture: Option<SignalCapture>,
+    /// Checkpoints of the states of the activities run in this agent, if any
+    pub checkpoints: Option<StateCheckpoints>,
+    /// Merged log of the records of all agents written by this agent, if any
+    pub merged_log: Option<MergedLogConfig>,
+    /// Signals forwarded to the secondary agents, see [signal_forwarding](crate::signal_forwarding)
+    pub forward_signals: Vec<ForwardedSignal>,
+    /// Secondary agent processes started and supervised by this agent, see [launcher](crate::launcher)
+    pub secondary_processes: Vec<SecondaryProcess>,
     /// Endpoint on which the connector of the scheduler waits for connections
     pub endpoint: NodeAddress,
     /// Map of all activities to agent ids
     pub activity_agent_map: HashMap<ActivityId, AgentId>,
+    /// Endpoint of a standby primary agent to which the scheduler state is mirrored, if any
+    pub standby: Option<NodeAddress>,
+    /// State mirrored from a failed primary agent to continue from, if taking over as standby
+    pub resume: Option<MirroredState>,
+    /// Keep running when a secondary agent is lost and let it rejoin once restarted, see [rejoin](crate::rejoin)
+    ///
+    /// Supported for the TCP and Unix socket endpoints.
+    pub rejoin: bool,
+}
+
+impl Default for PrimaryConfig {
+    /// Run no activities, listening on [DEFAULT_ENDPOINT] with the default cycle time and timeouts
+    fn default() -> Self {
+        Self {
+            id: AgentId::new(0),
+            cycle_time: DEFAULT_CYCLE_TIME,
+            activity_dependencies: Default::default(),
+            all_agent_assignments: Default::default(),
+            worker_assignments: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            connection_timeout: DEFAULT_TIMEOUT,
+            startup_timeout: DEFAULT_TIMEOUT,
+            startup_order: Default::default(),
+            step_deadlines: Default::default(),
+            task_chains: Default::default(),
+            error_policies: Default::default(),
+            supervision: Default::default(),
+            watchdog: Default::default(),
+            introspection: Default::default(),
+            statistics: Default::default(),
+            signal_capture: Default::default(),
+            checkpoints: Default::default(),
+            merged_log: Default::default(),
+            forward_signals: Default::default(),
+            secondary_processes: Default::default(),
+            endpoint: NodeAddress::Tcp(DEFAULT_ENDPOINT),
+            activity_agent_map: Default::default(),
+            standby: Default::default(),
+            resume: Default::default(),
+            rejoin: Default::default(),
+        }
+    }
 }
 
 /// Primary agent
 pub struct Primary {
     /// Scheduler
     scheduler: Scheduler,
+    /// Handle disabling and re-enabling activities of the scheduler
+    control: ActivityControl,
+    /// Handle pausing and stepping the cycles of the scheduler
+    debugger: CycleDebugger,
+    /// Handle reloading the non-structural parameters of the configuration
+    reload: ConfigReload,
+    /// Handle triggering cycles of on-demand task chains
+    trigger: CycleTrigger,
     /// Handles to the worker threads
-    worker_threads: Vec<JoinHandle<()>>,
+    worker_threads: Vec<WorkerThread>,
+    /// Supervision of the started secondary agent processes
+    launcher: Launcher,
+    /// Synchronization with the failed primary agent taken over from, if any
+    resumed_sync: Option<SyncInfo>,
 }
 
 impl Primary {
@@ -86,17 +191,50 @@ impl Primary {
             timeout,
             connection_timeout,
             startup_timeout,
+            startup_order,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            supervision,
+            watchdog,
+            introspection,
+            statistics,
+            signal_capture,
+            checkpoints,
+            merged_log,
+            forward_signals,
+            secondary_processes,
             activity_agent_map,
             worker_assignments,
             all_agent_assignments,
+            standby,
+            resume,
+            rejoin,
             ..
         } = config;
 
-        if let &NodeAddress::MwCom = &endpoint {
-            assert!(
-                worker_assignments.is_empty(),
-                "mw com backend doesn't work with workers being in the same process as the scheduler"
-            );
+        dependency::validate(&activity_dependencies, &[])?;
+        task_chains.validate(&activity_dependencies)?;
+        startup_order.validate(&activity_dependencies)?;
+        let merged_log = merged_log.map(MergedLog::new).transpose()?;
+        let introspection = introspection.as_deref().map(introspection::serve).transpose()?;
+        let control = introspection
+            .as_ref()
+            .map(introspection::Introspection::control)
+            .unwrap_or_default();
+        let debugger = introspection
+            .as_ref()
+            .map(introspection::Introspection::debugger)
+            .unwrap_or_default();
+        let reload = introspection
+            .as_ref()
+            .map(introspection::Introspection::reload)
+            .unwrap_or_default();
+        let trigger = CycleTrigger::new();
+
+        // The mw com backend doesn't work with workers being in the same process as the scheduler
+        if matches!(endpoint, NodeAddress::MwCom) && !worker_assignments.is_empty() {
+            return Err(Error::Config(ConfigError::LocalWorkers));
         }
 
         let _guard = TOKIO_RT.enter();
@@ -113,7 +251,8 @@ impl Primary {
                 let agent_id = config.id;
                 let barrier_clone = barrier.clone();
                 let agent_output = agent_output.clone();
-                thread::spawn(move || match endpoint {
+                let checkpoints = checkpoints.clone();
+                let handle = thread::spawn(move || match endpoint {
                     NodeAddress::MwCom => {
                         let mut connector = MwComWorkerConnector::new(
                             barrier_clone,
@@ -122,59 +261,138 @@ impl Primary {
                             agent_output,
                             runtime,
                         );
-                        connector.connect_remote().expect("failed to connect");
+                        connector.connect_remote()?;
 
                         let activity_builders = activities;
-                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout);
-
-                        worker.run().expect("failed to run worker");
+                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout, None)
+                            .with_checkpoints(checkpoints);
+                        worker.run()
                     },
                     NodeAddress::Tcp(addr) => {
-                        let mut connector = TcpWorkerConnector::new(addr, activities.iter().map(|(id, _)| *id));
-                        connector.connect_remote().expect("failed to connect");
+                        let mut connector = TcpWorkerConnector::new(
+                            addr,
+                            activities.iter().map(|(id, _)| *id),
+                            ConnectionPolicy::default(),
+                        );
+                        connector.connect_remote()?;
 
                         let activity_builders = activities;
-                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout);
+                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout, None)
+                            .with_checkpoints(checkpoints);
 
-                        if let Err(e) = worker.run() {
-                            error!("Worker {} in primary agent failed: {:?}", worker_id, e);
-                        }
+                        worker.run()
                     },
                     NodeAddress::UnixSocket(path) => {
-                        let mut connector = UnixWorkerConnector::new(path, activities.iter().map(|(id, _)| *id));
-                        connector.connect_remote().expect("failed to connect");
+                        let mut connector = UnixWorkerConnector::new(
+                            path,
+                            activities.iter().map(|(id, _)| *id),
+                            ConnectionPolicy::default(),
+                        );
+                        connector.connect_remote()?;
+
+                        let activity_builders = activities;
+                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout, None)
+                            .with_checkpoints(checkpoints);
+
+                        worker.run()
+                    },
+                    NodeAddress::SharedMemory(path) => {
+                        let mut connector = ShmWorkerConnector::new(
+                            path,
+                            activities.iter().map(|(id, _)| *id),
+                            ConnectionPolicy::default(),
+                        );
+                        connector.connect_remote()?;
+
+                        let activity_builders = activities;
+                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout, None)
+                            .with_checkpoints(checkpoints);
+
+                        worker.run()
+                    },
+                    #[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+                    NodeAddress::QnxChannel(name) => {
+                        let mut connector = QnxWorkerConnector::new(
+                            name,
+                            activities.iter().map(|(id, _)| *id),
+                            ConnectionPolicy::default(),
+                        );
+                        connector.connect_remote()?;
 
                         let activity_builders = activities;
-                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout);
+                        let worker = Worker::new(worker_id, agent_id, activity_builders, connector, timeout, None)
+                            .with_checkpoints(checkpoints);
 
-                        if let Err(e) = worker.run() {
-                            error!("Worker {} in primary agent failed: {:?}", worker_id, e);
-                        }
+                        worker.run()
                     },
-                })
+                });
+                (worker_id, handle)
             })
             .collect();
 
+        let activity_workers: HashMap<ActivityId, WorkerId> = all_agent_assignments
+            .iter()
+            .flat_map(|(_, workers)| workers.iter())
+            .flat_map(|(worker_id, activities)| activities.iter().map(move |id| (*id, *worker_id)))
+            .collect();
+
+        let supervisor = AgentSupervisor::new(
+            supervision,
+            activity_agent_map
+                .iter()
+                .map(|(activity_id, agent_id)| (*activity_id, *agent_id)),
+        );
+
+        if rejoin && !matches!(endpoint, NodeAddress::Tcp(_) | NodeAddress::UnixSocket(_)) {
+            warn!("Rejoining of secondary agents is not supported by the configured endpoint");
+        }
         let mut connector = match endpoint {
             NodeAddress::MwCom => Box::new(MwComSchedulerConnector::new(id, all_agent_assignments, runtime)),
-            NodeAddress::Tcp(addr) => Box::new(TcpSchedulerConnector::new(
-                addr,
+            NodeAddress::Tcp(addr) => Box::new(
+                TcpSchedulerConnector::new(
+                    addr,
+                    activity_dependencies.keys().cloned(),
+                    activity_agent_map,
+                    connection_timeout,
+                )
+                .with_rejoin(rejoin),
+            ) as Box<dyn ConnectScheduler>,
+            NodeAddress::UnixSocket(path) => Box::new(
+                UnixSchedulerConnector::new(
+                    &path,
+                    activity_dependencies.keys().cloned(),
+                    activity_agent_map,
+                    connection_timeout,
+                )
+                .with_rejoin(rejoin),
+            ) as Box<dyn ConnectScheduler>,
+            NodeAddress::SharedMemory(path) => Box::new(ShmSchedulerConnector::new(
+                &path,
                 activity_dependencies.keys().cloned(),
                 activity_agent_map,
                 connection_timeout,
             )) as Box<dyn ConnectScheduler>,
-            NodeAddress::UnixSocket(path) => Box::new(UnixSchedulerConnector::new(
-                &path,
+            #[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+            NodeAddress::QnxChannel(name) => Box::new(QnxSchedulerConnector::new(
+                &name,
                 activity_dependencies.keys().cloned(),
                 activity_agent_map,
                 connection_timeout,
             )) as Box<dyn ConnectScheduler>,
         };
+        // Start the secondary agents once the connector listens for their connections
+        let launcher = Launcher::launch(secondary_processes)?;
         connector.connect_remotes()?;
 
         // Create a shared flag to signal shutdown from an OS signal (e.g., Ctrl-C).
         let shutdown_requested = Arc::new(AtomicBool::new(false));
-        register_sigterm_handler(shutdown_requested.clone());
+        register_sigterm_handler(shutdown_requested.clone())?;
+        signal_forwarding::enable(&forward_signals)?;
+
+        // Keep the time base of the failed primary agent, which the secondary agents are synchronized to
+        let resumed_sync = resume
+            .as_ref()
+            .map(|state| SyncInfo::from_since_epoch(Duration::from_nanos(state.startup_ns)));
 
         debug!("Creating scheduler...");
         let scheduler = Scheduler::new(
@@ -182,39 +400,84 @@ impl Primary {
             cycle_time,
             timeout,
             startup_timeout,
+            startup_order,
             activity_dependencies,
+            activity_workers,
             connector,
             shutdown_requested,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            supervisor,
+            watchdog,
+            introspection,
+            statistics,
+            signal_capture,
+            merged_log,
+            control.clone(),
+            debugger.clone(),
+            reload.clone(),
+            trigger.clone(),
+            standby.map(StandbyMirror::new),
+            resume,
         );
 
         Ok(Self {
             scheduler,
+            control,
+            debugger,
+            reload,
+            trigger,
             worker_threads,
+            launcher,
+            resumed_sync,
         })
     }
 
+    /// Get the handle disabling and re-enabling activities at the cycle boundaries, see [control](crate::control)
+    pub fn activity_control(&self) -> ActivityControl {
+        self.control.clone()
+    }
+
+    /// Get the handle pausing and stepping the cycles of the scheduler, see [debugger](crate::debugger)
+    ///
+    /// Pause it before running the agent to stop the scheduler before the first cycle.
+    pub fn cycle_debugger(&self) -> CycleDebugger {
+        self.debugger.clone()
+    }
+
+    /// Get the handle reloading the non-structural parameters of the configuration, see [reload](crate::reload)
+    ///
+    /// Pass the configuration file to [ConfigReload::watch] to reload it on SIGHUP.
+    pub fn config_reload(&self) -> ConfigReload {
+        self.reload.clone()
+    }
+
+    /// Get the handle triggering cycles of on-demand task chains, see [on_demand](crate::on_demand)
+    pub fn cycle_trigger(&self) -> CycleTrigger {
+        self.trigger.clone()
+    }
+
     /// Run the agent
     pub fn run(&mut self) -> Result<(), Error> {
         // Initialize local time
-        timestamp::initialize();
+        match self.resumed_sync {
+            Some(sync_info) => timestamp::initialize_from(sync_info),
+            None => timestamp::initialize(),
+        }
 
         // Sync time on remotes
         self.scheduler.sync_remotes()?;
 
         // This will block until the scheduler decides to shut down.
-        self.scheduler.run();
+        let result = self.scheduler.run();
 
         // After the scheduler returns, we know the shutdown sequence has completed.
         // We can now safely join our local worker threads.
-        for th in self.worker_threads.drain(..) {
-            if let Err(e) = th.join() {
-                error!(
-                    "A local worker thread in the primary agent panicked: {:?}",
-                    ScoreDebugDebug::<_, 1024>(&e)
-                );
-            }
-        }
+        let workers = join_workers(self.worker_threads.drain(..));
+        // The secondary agents exit after the termination sent by the scheduler
+        self.launcher.stop();
 
-        Ok(())
+        result.and(workers)
     }
 }
@@ -14,13 +14,26 @@
 //! Implementation of the primary agent for mpsc-only signalling
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::register_sigterm_handler;
-use crate::debug_fmt::ScoreDebugDebug;
+use crate::agent::{join_workers, register_sigterm_handler, WorkerThread, DEFAULT_CYCLE_TIME, DEFAULT_TIMEOUT};
+use crate::chain::TaskChains;
+use crate::control::ActivityControl;
+use crate::deadline::StepDeadlines;
+use crate::debugger::CycleDebugger;
+use crate::dependency;
 use crate::error::Error;
+use crate::error_policy::ErrorPolicies;
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::log_relay::{MergedLog, MergedLogConfig};
+use crate::on_demand::CycleTrigger;
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::recording::signals::SignalCapture;
+use crate::reload::ConfigReload;
 use crate::scheduler::Scheduler;
 use crate::signalling::common::interface::{ConnectScheduler, ConnectWorker};
 use crate::signalling::direct::mpsc::scheduler::SchedulerConnector;
+use crate::startup::StartupOrder;
+use crate::statistics::CycleStatistics;
+use crate::supervision::{AgentSupervisor, Supervision};
 use crate::timestamp;
 use crate::worker::Worker;
 use alloc::boxed::Box;
@@ -28,15 +41,15 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::sync::atomic::AtomicBool;
 use feo_time::Duration;
-use score_log::{debug, error};
+use score_log::debug;
 use std::collections::HashMap;
-use std::thread::{self, JoinHandle};
+use std::thread;
 
 /// Configuration of the primary agent
 pub struct PrimaryConfig {
     /// Id of the primary agent
     pub id: AgentId,
-    /// Cycle time of the step loop
+    /// Cycle time of the main task chain
     pub cycle_time: Duration,
     /// Dependencies per activity
     pub activity_dependencies: HashMap<ActivityId, Vec<ActivityId>>,
@@ -46,14 +59,60 @@ pub struct PrimaryConfig {
     pub timeout: Duration,
     /// Timeout for waiting on activities to become ready during startup.
     pub startup_timeout: Duration,
+    /// Startup dependencies of the activities, see [startup](crate::startup)
+    pub startup_order: StartupOrder,
+    /// Step deadlines of the monitored activities
+    pub step_deadlines: StepDeadlines,
+    /// Task chains and the activities belonging to them
+    pub task_chains: TaskChains,
+    /// Reaction to failed steps per activity
+    pub error_policies: ErrorPolicies,
+    /// Cycle statistics collected by the scheduler, if any
+    pub statistics: Option<CycleStatistics>,
+    /// Capture of the step signals of the scheduler for a recorder in this agent, if any
+    pub signal_capture: Option<SignalCapture>,
+    /// Checkpoints of the states of the activities run in this agent, if any
+    pub checkpoints: Option<StateCheckpoints>,
+    /// Merged log of the records of all agents written by this agent, if any
+    pub merged_log: Option<MergedLogConfig>,
+}
+
+impl Default for PrimaryConfig {
+    /// Run no activities with the default cycle time and timeouts
+    fn default() -> Self {
+        Self {
+            id: AgentId::new(0),
+            cycle_time: DEFAULT_CYCLE_TIME,
+            activity_dependencies: Default::default(),
+            worker_assignments: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            startup_timeout: DEFAULT_TIMEOUT,
+            startup_order: Default::default(),
+            step_deadlines: Default::default(),
+            task_chains: Default::default(),
+            error_policies: Default::default(),
+            statistics: Default::default(),
+            signal_capture: Default::default(),
+            checkpoints: Default::default(),
+            merged_log: Default::default(),
+        }
+    }
 }
 
 /// Primary agent
 pub struct Primary {
     /// Scheduler
     scheduler: Scheduler,
+    /// Handle disabling and re-enabling activities of the scheduler
+    control: ActivityControl,
+    /// Handle pausing and stepping the cycles of the scheduler
+    debugger: CycleDebugger,
+    /// Handle reloading the non-structural parameters of the configuration
+    reload: ConfigReload,
+    /// Handle triggering cycles of on-demand task chains
+    trigger: CycleTrigger,
     /// Handles to the worker threads
-    worker_threads: Vec<JoinHandle<()>>,
+    worker_threads: Vec<WorkerThread>,
 }
 
 impl Primary {
@@ -64,9 +123,26 @@ impl Primary {
             activity_dependencies,
             timeout,
             startup_timeout,
+            startup_order,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            statistics,
+            signal_capture,
+            checkpoints,
+            merged_log,
             ..
         } = config;
 
+        dependency::validate(&activity_dependencies, &[])?;
+        task_chains.validate(&activity_dependencies)?;
+        startup_order.validate(&activity_dependencies)?;
+        let merged_log = merged_log.map(MergedLog::new).transpose()?;
+        let control = ActivityControl::new();
+        let debugger = CycleDebugger::new();
+        let reload = ConfigReload::new();
+        let trigger = CycleTrigger::new();
+
         let activity_worker_map: HashMap<ActivityId, WorkerId> = config
             .worker_assignments
             .iter()
@@ -74,7 +150,7 @@ impl Primary {
             .collect();
 
         // Create scheduler connector
-        let mut connector = Box::new(SchedulerConnector::new(activity_worker_map));
+        let mut connector = Box::new(SchedulerConnector::new(activity_worker_map.clone()));
 
         // Get worker connector builders to be moved into worker threads
         let mut connector_builders = connector.worker_connector_builders();
@@ -84,41 +160,88 @@ impl Primary {
             .worker_assignments
             .into_iter()
             .map(|(id, activities)| {
-                let connector_builder = connector_builders.remove(&id).expect("missing connector builder");
+                let connector_builder = connector_builders.remove(&id).ok_or(Error::WorkerNotFound(id))?;
                 let agent_id = config.id;
-                thread::spawn(move || {
+                let checkpoints = checkpoints.clone();
+                Ok((id, thread::spawn(move || {
                     let mut connector = connector_builder();
-                    connector.connect_remote().expect("failed to connect");
+                    connector.connect_remote()?;
 
                     let activity_builders = activities;
-                    let worker = Worker::new(id, agent_id, activity_builders, connector, timeout);
-                    worker.run().expect("failed to run worker");
-                })
+                    let worker = Worker::new(id, agent_id, activity_builders, connector, timeout, None)
+                        .with_checkpoints(checkpoints);
+                    worker.run()
+                })))
             })
-            .collect();
+            .collect::<Result<Vec<_>, Error>>()?;
 
-        connector.connect_remotes().expect("failed to connect");
+        connector.connect_remotes()?;
 
         // Create a shared flag to signal shutdown from an OS signal (e.g., Ctrl-C).
         let shutdown_requested = Arc::new(AtomicBool::new(false));
-        register_sigterm_handler(shutdown_requested.clone());
+        register_sigterm_handler(shutdown_requested.clone())?;
 
         let scheduler = Scheduler::new(
             config.id,
             cycle_time,
             timeout,
             startup_timeout,
+            startup_order,
             activity_dependencies,
+            activity_worker_map,
             connector,
             shutdown_requested,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            AgentSupervisor::new(Supervision::default(), []),
+            None,
+            None,
+            statistics,
+            signal_capture,
+            merged_log,
+            control.clone(),
+            debugger.clone(),
+            reload.clone(),
+            trigger.clone(),
+            None,
+            None,
         );
 
         Ok(Self {
             scheduler,
+            control,
+            debugger,
+            reload,
+            trigger,
             worker_threads,
         })
     }
 
+    /// Get the handle disabling and re-enabling activities at the cycle boundaries, see [control](crate::control)
+    pub fn activity_control(&self) -> ActivityControl {
+        self.control.clone()
+    }
+
+    /// Get the handle pausing and stepping the cycles of the scheduler, see [debugger](crate::debugger)
+    ///
+    /// Pause it before running the agent to stop the scheduler before the first cycle.
+    pub fn cycle_debugger(&self) -> CycleDebugger {
+        self.debugger.clone()
+    }
+
+    /// Get the handle reloading the non-structural parameters of the configuration, see [reload](crate::reload)
+    ///
+    /// Pass the configuration file to [ConfigReload::watch] to reload it on SIGHUP.
+    pub fn config_reload(&self) -> ConfigReload {
+        self.reload.clone()
+    }
+
+    /// Get the handle triggering cycles of on-demand task chains, see [on_demand](crate::on_demand)
+    pub fn cycle_trigger(&self) -> CycleTrigger {
+        self.trigger.clone()
+    }
+
     /// Run the agent
     pub fn run(&mut self) -> Result<(), Error> {
         // Initialize local time
@@ -127,18 +250,9 @@ impl Primary {
         // Sync time on remotes
         self.scheduler.sync_remotes()?;
 
-        // TODO: Bubble up errors
-        self.scheduler.run();
-
-        for th in self.worker_threads.drain(..) {
-            if let Err(e) = th.join() {
-                error!(
-                    "A local worker thread in the primary agent panicked: {:?}",
-                    ScoreDebugDebug::<_, 1024>(&e)
-                );
-            }
-        }
+        let result = self.scheduler.run();
+        let workers = join_workers(self.worker_threads.drain(..));
         debug!("Primary agent finished");
-        Ok(())
+        result.and(workers)
     }
 }
@@ -14,22 +14,31 @@
 //! Implementation of a secondary agent for direct scheduler-to-worker signalling
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{join_workers, ConnectionPolicy, NodeAddress, WorkerThread, DEFAULT_ENDPOINT, DEFAULT_TIMEOUT};
+use crate::deadline::HardDeadlines;
 use crate::debug_fmt::ScoreDebugDebug;
-use crate::ids::{AgentId, WorkerId};
+use crate::error::Error;
+use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::log_relay::LogRelay;
+use crate::panic_report;
+use crate::recording::checkpoint::StateCheckpoints;
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::direct::mw_com::worker_connector::agent_output;
 use crate::signalling::direct::mw_com::worker_connector::MwComWorkerConnector;
+#[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+use crate::signalling::direct::qnx::worker::QnxWorkerConnector;
+use crate::signalling::direct::shm::worker::ShmWorkerConnector;
 use crate::signalling::direct::worker::{TcpWorkerConnector, UnixWorkerConnector};
+use crate::thread_config::{ThreadConfigs, WorkerThreadConfigs};
 use crate::worker::Worker;
 use crate::TOKIO_RT;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use com_api::LolaRuntimeImpl;
 use feo_time::Duration;
-use score_log::{debug, error};
+use score_log::{debug, warn};
 use std::sync::Barrier;
-use std::thread::{self, JoinHandle};
+use std::thread;
 
 /// Configuration of a secondary agent
 pub struct SecondaryConfig {
@@ -39,8 +48,44 @@ pub struct SecondaryConfig {
     pub worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
     /// Maximum time for a worker to make no progress without panicking
     pub timeout: Duration,
+    /// Interval of heartbeats sent by each worker to the primary agent for supervision, if any
+    pub heartbeat_interval: Option<Duration>,
+    /// CPU affinity and scheduling policy per worker thread and activity
+    pub thread_configs: ThreadConfigs,
+    /// Hard step deadlines enforced on the activities of this agent
+    pub hard_deadlines: HardDeadlines,
+    /// Checkpoints of the states of the activities run in this agent, if any
+    pub checkpoints: Option<StateCheckpoints>,
+    /// Relay of the log records of this agent to the primary agent, if any
+    pub log_relay: Option<LogRelay>,
     /// Endpoint on which the scheduler connector is listening
     pub endpoint: NodeAddress,
+    /// Policy for connecting to the scheduler connector
+    pub connection_policy: ConnectionPolicy,
+    /// Endpoint of a standby primary agent to connect to when the primary agent fails, if any
+    ///
+    /// Must use the same signalling as [SecondaryConfig::endpoint], either TCP or Unix sockets,
+    /// see [standby](crate::standby).
+    pub failover_endpoint: Option<NodeAddress>,
+}
+
+impl Default for SecondaryConfig {
+    /// Run no activities, connecting to [DEFAULT_ENDPOINT] with the default timeout and connection policy
+    fn default() -> Self {
+        Self {
+            id: AgentId::new(0),
+            worker_assignments: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            heartbeat_interval: Default::default(),
+            thread_configs: Default::default(),
+            hard_deadlines: Default::default(),
+            checkpoints: Default::default(),
+            log_relay: Default::default(),
+            endpoint: NodeAddress::Tcp(DEFAULT_ENDPOINT),
+            connection_policy: Default::default(),
+            failover_endpoint: Default::default(),
+        }
+    }
 }
 
 /// Secondary agent
@@ -48,7 +93,7 @@ pub struct Secondary {
     /// ID
     id: AgentId,
     /// Handles to the worker threads
-    worker_threads: Vec<JoinHandle<()>>,
+    worker_threads: Vec<WorkerThread>,
 }
 
 impl Secondary {
@@ -58,7 +103,14 @@ impl Secondary {
             id: _,
             worker_assignments,
             timeout,
+            heartbeat_interval,
+            thread_configs,
+            hard_deadlines,
+            checkpoints,
+            log_relay,
             endpoint,
+            connection_policy,
+            failover_endpoint,
         } = config;
 
         let _guard = TOKIO_RT.enter();
@@ -67,51 +119,71 @@ impl Secondary {
         let barrier = Arc::new(Barrier::new(worker_assignments.len()));
         let agent_output = agent_output(runtime, config.id);
 
+        // Report panics of activities to the primary agent before their failures
+        panic_report::install();
+
         let worker_threads = worker_assignments
             .into_iter()
             .map(|(worker_id, activities)| {
                 let endpoint = endpoint.clone();
-                let agent_id = config.id; // Use the correct AgentId from the config.
+                let failover_endpoint = failover_endpoint.clone();
                 let barrier_clone = barrier.clone();
                 let agent_output = agent_output.clone();
-                thread::spawn(move || match endpoint {
+                let activity_ids: Vec<ActivityId> = activities.iter().map(|(id, _)| *id).collect();
+                let settings = WorkerSettings {
+                    worker_id,
+                    agent_id: config.id, // Use the correct AgentId from the config.
+                    activities,
+                    timeout,
+                    heartbeat_interval,
+                    thread_configs: thread_configs.for_worker(worker_id, activity_ids.iter().copied()),
+                    hard_deadlines: hard_deadlines.for_activities(activity_ids.iter().copied()),
+                    checkpoints: checkpoints.clone(),
+                    log_relay: log_relay.clone(),
+                };
+                let handle = thread::spawn(move || match endpoint {
                     NodeAddress::MwCom => {
                         let _guard = TOKIO_RT.enter();
-                        let mut connector = MwComWorkerConnector::new(
-                            barrier_clone,
-                            activities.iter().map(|(a, _)| *a).collect(),
-                            worker_id,
-                            agent_output,
-                            runtime,
-                        );
-                        connector.connect_remote().expect("failed to connect");
-                        let worker = Worker::new(worker_id, agent_id, activities, connector, timeout);
-
-                        worker.run().expect("failed to run worker");
+                        let mut connector =
+                            MwComWorkerConnector::new(barrier_clone, activity_ids, worker_id, agent_output, runtime);
+                        connector.connect_remote()?;
+                        settings.run(connector, None)
                     },
                     NodeAddress::Tcp(addr) => {
-                        let mut connector = TcpWorkerConnector::new(addr, activities.iter().map(|(id, _)| *id));
-                        if let Err(e) = connector.connect_remote() {
-                            error!("Worker {} failed to connect to primary: {:?}", worker_id, e);
-                            return;
-                        }
-                        let worker = Worker::new(worker_id, agent_id, activities, connector, timeout);
-                        if let Err(e) = worker.run() {
-                            error!("Worker {} failed with error: {:?}", worker_id, e);
-                        }
+                        let mut connector = TcpWorkerConnector::new(addr, activity_ids.clone(), connection_policy);
+                        connector.connect_remote()?;
+                        let failover = match failover_endpoint {
+                            Some(NodeAddress::Tcp(addr)) => {
+                                Some(TcpWorkerConnector::new(addr, activity_ids, connection_policy))
+                            },
+                            other => unsupported_failover(other),
+                        };
+                        settings.run(connector, failover)
                     },
                     NodeAddress::UnixSocket(path) => {
-                        let mut connector = UnixWorkerConnector::new(path, activities.iter().map(|(id, _)| *id));
-                        if let Err(e) = connector.connect_remote() {
-                            error!("Worker {} failed to connect to primary: {:?}", worker_id, e);
-                            return;
-                        }
-                        let worker = Worker::new(worker_id, agent_id, activities, connector, timeout);
-                        if let Err(e) = worker.run() {
-                            error!("Worker {} failed with error: {:?}", worker_id, e);
-                        }
+                        let mut connector = UnixWorkerConnector::new(path, activity_ids.clone(), connection_policy);
+                        connector.connect_remote()?;
+                        let failover = match failover_endpoint {
+                            Some(NodeAddress::UnixSocket(path)) => {
+                                Some(UnixWorkerConnector::new(path, activity_ids, connection_policy))
+                            },
+                            other => unsupported_failover(other),
+                        };
+                        settings.run(connector, failover)
+                    },
+                    NodeAddress::SharedMemory(path) => {
+                        let mut connector = ShmWorkerConnector::new(path, activity_ids, connection_policy);
+                        connector.connect_remote()?;
+                        settings.run(connector, None)
                     },
-                })
+                    #[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+                    NodeAddress::QnxChannel(name) => {
+                        let mut connector = QnxWorkerConnector::new(name, activity_ids, connection_policy);
+                        connector.connect_remote()?;
+                        settings.run(connector, None)
+                    },
+                });
+                (worker_id, handle)
             })
             .collect();
 
@@ -122,19 +194,55 @@ impl Secondary {
     }
 
     /// Run the agent
-    pub fn run(self) {
+    pub fn run(self) -> Result<(), Error> {
         debug!("Running secondary with ID {:?}", self.id);
 
-        for th in self.worker_threads {
-            if let Err(e) = th.join() {
-                error!(
-                    "Worker thread for agent {:?} panicked: {:?}",
-                    self.id,
-                    ScoreDebugDebug::<_, 1024>(&e)
-                );
-            }
-        }
-
+        let result = join_workers(self.worker_threads);
         debug!("Secondary with ID {:?} finished", self.id);
+        result
+    }
+}
+
+/// Settings of a worker of a secondary agent, independent of its connector
+struct WorkerSettings {
+    worker_id: WorkerId,
+    agent_id: AgentId,
+    activities: Vec<ActivityIdAndBuilder>,
+    timeout: Duration,
+    heartbeat_interval: Option<Duration>,
+    thread_configs: WorkerThreadConfigs,
+    hard_deadlines: HardDeadlines,
+    checkpoints: Option<StateCheckpoints>,
+    log_relay: Option<LogRelay>,
+}
+
+impl WorkerSettings {
+    /// Run a worker connected to the scheduler with the given connector, failing over to the given one, if any
+    fn run<T: ConnectWorker>(self, connector: T, failover: Option<T>) -> Result<(), Error> {
+        Worker::new(
+            self.worker_id,
+            self.agent_id,
+            self.activities,
+            connector,
+            self.timeout,
+            self.heartbeat_interval,
+        )
+        .with_thread_configs(self.thread_configs)
+        .with_hard_deadlines(self.hard_deadlines)
+        .with_checkpoints(self.checkpoints)
+        .with_log_relay(self.log_relay)
+        .with_failover(failover)
+        .run()
+    }
+}
+
+/// Warn about a failover endpoint not matching the signalling of the primary agent
+fn unsupported_failover<T>(endpoint: Option<NodeAddress>) -> Option<T> {
+    if let Some(endpoint) = endpoint {
+        warn!(
+            "Ignoring failover endpoint {:?} not matching the endpoint of the primary agent",
+            ScoreDebugDebug::<_, 128>(&endpoint)
+        );
     }
+    None
 }
@@ -17,12 +17,22 @@
 //! agents. The primary agent is responsible for triggering the execution of all activities distributed
 //! across all agents.
 
+use crate::error::{ConnectionError, Error, SignallingError};
+use crate::ids::WorkerId;
 use alloc::sync::Arc;
-use core::net::SocketAddr;
+#[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+use alloc::string::{String, ToString};
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+use core::str::FromStr;
 use core::sync::atomic::AtomicBool;
 use core::sync::atomic::Ordering;
-use score_log::info;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, error, info};
+use std::io;
 use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
 pub mod com_init;
 pub mod direct;
@@ -33,18 +43,215 @@ pub mod relayed;
 pub enum NodeAddress {
     Tcp(SocketAddr),
     UnixSocket(PathBuf),
+    /// Path of a shared memory file, only supported for direct signalling between agents on the same host
+    SharedMemory(PathBuf),
+    /// Name of a QNX channel in the path name space, only supported for direct signalling on QNX targets
+    #[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+    QnxChannel(String),
     MwCom,
 }
 
-fn register_sigterm_handler(shutdown: Arc<AtomicBool>) {
+impl FromStr for NodeAddress {
+    type Err = Error;
+
+    /// Parse an endpoint written as `tcp:<address>:<port>`, `unix:<path>`, `shm:<path>`, `qnx:<name>` or `mwcom`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "mwcom" {
+            return Ok(NodeAddress::MwCom);
+        }
+        match s.split_once(':') {
+            Some(("tcp", addr)) => addr
+                .parse()
+                .map(NodeAddress::Tcp)
+                .map_err(|_| Error::Connection(ConnectionError::Discovery("invalid TCP endpoint address"))),
+            Some(("unix", path)) if !path.is_empty() => Ok(NodeAddress::UnixSocket(PathBuf::from(path))),
+            Some(("shm", path)) if !path.is_empty() => Ok(NodeAddress::SharedMemory(PathBuf::from(path))),
+            #[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+            Some(("qnx", name)) if !name.is_empty() => Ok(NodeAddress::QnxChannel(name.to_string())),
+            _ => Err(Error::Connection(ConnectionError::Discovery(
+                "invalid endpoint, expected tcp:, unix:, shm:, qnx: or mwcom",
+            ))),
+        }
+    }
+}
+
+/// Cycle time of the main task chain in the default configurations of the primary agents
+pub const DEFAULT_CYCLE_TIME: Duration = Duration::from_millis(100);
+
+/// Receive, connection and startup timeouts in the default configurations of the agents
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Endpoint of the scheduler in the default configurations of the agents, of the senders for relayed signalling
+pub const DEFAULT_ENDPOINT: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8081);
+
+/// Address of the receivers in the default configurations of the agents using relayed signalling
+pub const DEFAULT_RECEIVERS_ENDPOINT: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8082);
+
+/// Maximum factor by which the retry interval of a [ConnectionPolicy] grows
+const MAX_RETRY_BACKOFF: u32 = 8;
+
+/// Policy for connecting to a remote agent which may not be listening yet
+///
+/// A failed connection attempt is retried until the number of retries exceeds `max_retries`
+/// or the time since the first attempt exceeds `timeout`.
+/// The interval between two attempts starts at `retry_interval` and doubles after each
+/// failed attempt, up to eight times `retry_interval`.
+/// The interval and the timeout are measured in real time, unaffected by the speed factor
+/// and the virtual clock of [feo_time], so that connecting does not wait for the clock to be advanced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionPolicy {
+    /// Maximum time to keep trying, `None` to try indefinitely
+    pub timeout: Option<Duration>,
+    /// Initial interval between two connection attempts
+    pub retry_interval: Duration,
+    /// Maximum number of retries after the first failed attempt, `None` for unlimited retries
+    pub max_retries: Option<u32>,
+}
+
+impl Default for ConnectionPolicy {
+    /// Retry indefinitely, starting with an interval of 300ms
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            retry_interval: Duration::from_millis(300),
+            max_retries: None,
+        }
+    }
+}
+
+impl ConnectionPolicy {
+    /// Call `connect` until it succeeds or this policy gives up
+    pub(crate) fn connect<T>(&self, mut connect: impl FnMut() -> io::Result<T>) -> Result<T, Error> {
+        let start = Instant::now();
+        let mut retry_interval = self.retry_interval;
+        let mut retries = 0;
+
+        loop {
+            let error = match connect() {
+                Ok(connection) => return Ok(connection),
+                Err(e) => e,
+            };
+
+            if self.max_retries.is_some_and(|max_retries| retries >= max_retries) {
+                return Err(Error::Connection(ConnectionError::Failed((ScoreDebugIoError(error), retries + 1))));
+            }
+            if let Some(timeout) = self.timeout {
+                let elapsed = Duration::from(start.elapsed());
+                if elapsed + retry_interval > timeout {
+                    return Err(Error::Timeout(Some(timeout), "connecting to remote agent"));
+                }
+            }
+
+            debug!(
+                "Connection attempt {} failed: {:?}, retrying in {:?}",
+                retries + 1,
+                ScoreDebugIoError(error),
+                retry_interval
+            );
+            thread::sleep(retry_interval.into());
+            retries += 1;
+            retry_interval = core::cmp::min(retry_interval * 2, self.retry_interval * MAX_RETRY_BACKOFF);
+        }
+    }
+}
+
+/// Request a graceful shutdown on SIGINT and SIGTERM
+///
+/// The first signal sets the `shutdown` flag, letting the scheduler finish the current cycle
+/// and run the shutdown sequence. A second signal exits immediately, after forwarding SIGTERM to the
+/// secondary agents if configured, see [signal_forwarding](crate::signal_forwarding).
+/// SIGTERM is handled through the `termination` feature of the `ctrlc` crate.
+fn register_sigterm_handler(shutdown: Arc<AtomicBool>) -> Result<(), Error> {
     ctrlc::set_handler(move || {
         if shutdown.load(Ordering::Relaxed) {
             info!("Terminate triggered, exiting...");
+            crate::signal_forwarding::terminate_all();
             std::process::exit(1);
         } else {
-            info!("Ctrl-C detected. Requesting graceful shutdown...");
+            info!("Termination signal received. Requesting graceful shutdown...");
             shutdown.store(true, core::sync::atomic::Ordering::Relaxed);
         }
     })
-    .expect("Error setting Ctrl-C handler")
+    .map_err(|e| Error::Io((ScoreDebugIoError(io::Error::other(e)), "failed to set termination signal handler")))
+}
+
+/// Handle to the thread of a worker of an agent
+type WorkerThread = (WorkerId, JoinHandle<Result<(), Error>>);
+
+/// Wait for the worker threads of an agent to finish, returning the first failure
+///
+/// Failures of all workers are logged. A worker thread which panicked outside of its activities
+/// fails with [SignallingError::WorkerTerminated].
+fn join_workers(threads: impl IntoIterator<Item = WorkerThread>) -> Result<(), Error> {
+    let mut result = Ok(());
+    for (id, thread) in threads {
+        let outcome = thread
+            .join()
+            .unwrap_or(Err(Error::Signalling(SignallingError::WorkerTerminated)));
+        if let Err(e) = outcome {
+            error!("Worker {} failed: {:?}", id, e);
+            result = result.and(Err(e));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_is_retried_until_it_succeeds() {
+        let policy = ConnectionPolicy {
+            timeout: None,
+            retry_interval: Duration::from_millis(1),
+            max_retries: Some(5),
+        };
+        let mut attempts = 0;
+        let result = policy.connect(|| {
+            attempts += 1;
+            match attempts {
+                1..=2 => Err(io::ErrorKind::ConnectionRefused.into()),
+                _ => Ok(7),
+            }
+        });
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn connection_fails_after_max_retries() {
+        let policy = ConnectionPolicy {
+            timeout: None,
+            retry_interval: Duration::from_millis(1),
+            max_retries: Some(4),
+        };
+        let start = Instant::now();
+        let mut attempts = 0;
+        let result = policy.connect(|| -> io::Result<()> {
+            attempts += 1;
+            Err(io::ErrorKind::ConnectionRefused.into())
+        });
+        assert!(matches!(result, Err(Error::Connection(ConnectionError::Failed((_, 5))))));
+        assert_eq!(attempts, 5);
+        // The interval doubles after each retry, up to eight times the initial one: 1 + 2 + 4 + 8 ms
+        assert!(start.elapsed() >= core::time::Duration::from_millis(15));
+    }
+
+    #[test]
+    fn connection_fails_after_timeout() {
+        let policy = ConnectionPolicy {
+            timeout: Some(Duration::from_millis(20)),
+            retry_interval: Duration::from_millis(2),
+            max_retries: None,
+        };
+        let mut attempts = 0;
+        let result = policy.connect(|| -> io::Result<()> {
+            attempts += 1;
+            Err(io::ErrorKind::NotFound.into())
+        });
+        assert!(matches!(result, Err(Error::Timeout(Some(timeout), _)) if timeout == Duration::from_millis(20)));
+        // Retries after 2, 4, 8 and 16 ms would exceed the timeout with the last interval
+        assert!((1..=4).contains(&attempts));
+    }
 }
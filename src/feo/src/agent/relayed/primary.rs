@@ -14,14 +14,32 @@
 //! Implementation of the primary agent for mixed signalling using sockets and mpsc channels
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::register_sigterm_handler;
-use crate::agent::NodeAddress;
-use crate::error::Error;
+use crate::agent::{join_workers, register_sigterm_handler, WorkerThread};
+use crate::agent::{NodeAddress, DEFAULT_CYCLE_TIME, DEFAULT_ENDPOINT, DEFAULT_RECEIVERS_ENDPOINT, DEFAULT_TIMEOUT};
+use crate::chain::TaskChains;
+use crate::control::ActivityControl;
+use crate::deadline::StepDeadlines;
+use crate::debugger::CycleDebugger;
+use crate::dependency;
+use crate::error::{ConfigError, Error};
+use crate::error_policy::ErrorPolicies;
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::introspection;
+use crate::launcher::{Launcher, SecondaryProcess};
+use crate::log_relay::{MergedLog, MergedLogConfig};
+use crate::on_demand::CycleTrigger;
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::recording::signals::SignalCapture;
+use crate::reload::ConfigReload;
 use crate::scheduler::Scheduler;
+use crate::signal_forwarding::{self, ForwardedSignal};
 use crate::signalling::common::interface::{ConnectScheduler, ConnectWorker};
 use crate::signalling::relayed::sockets_mpsc::{SchedulerConnectorTcp, SchedulerConnectorUnix};
+use crate::startup::StartupOrder;
+use crate::statistics::CycleStatistics;
+use crate::supervision::{AgentSupervisor, Supervision};
 use crate::timestamp;
+use crate::watchdog::Watchdog;
 use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::sync::Arc;
@@ -29,15 +47,16 @@ use alloc::vec::Vec;
 use com_api::LolaRuntimeImpl;
 use core::sync::atomic::AtomicBool;
 use feo_time::Duration;
-use score_log::debug;
+use score_log::{debug, error};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::thread::{self, JoinHandle};
 
 /// Configuration of the primary agent
 pub struct PrimaryConfig {
     /// Id of the primary agent
     pub id: AgentId,
-    /// Cycle time of the step loop
+    /// Cycle time of the main task chain
     pub cycle_time: Duration,
     /// Dependencies per activity
     pub activity_dependencies: HashMap<ActivityId, Vec<ActivityId>>,
@@ -49,6 +68,32 @@ pub struct PrimaryConfig {
     pub connection_timeout: Duration,
     /// Timeout for waiting on activities to become ready during startup.
     pub startup_timeout: Duration,
+    /// Startup dependencies of the activities, see [startup](crate::startup)
+    pub startup_order: StartupOrder,
+    /// Step deadlines of the monitored activities
+    pub step_deadlines: StepDeadlines,
+    /// Task chains and the activities belonging to them
+    pub task_chains: TaskChains,
+    /// Reaction to failed steps per activity
+    pub error_policies: ErrorPolicies,
+    /// Heartbeat supervision of the secondary agents
+    pub supervision: Supervision,
+    /// External watchdog notified by the scheduler once per completed cycle, if any
+    pub watchdog: Option<Watchdog>,
+    /// Unix socket path of the introspection server, if any
+    pub introspection: Option<PathBuf>,
+    /// Cycle statistics collected by the scheduler, if any
+    pub statistics: Option<CycleStatistics>,
+    /// Capture of the step signals of the scheduler for a recorder in this agent, if any
+    pub signal_capture: Option<SignalCapture>,
+    /// Checkpoints of the states of the activities run in this agent, if any
+    pub checkpoints: Option<StateCheckpoints>,
+    /// Merged log of the records of all agents written by this agent, if any
+    pub merged_log: Option<MergedLogConfig>,
+    /// Signals forwarded to the secondary agents, see [signal_forwarding](crate::signal_forwarding)
+    pub forward_signals: Vec<ForwardedSignal>,
+    /// Secondary agent processes started and supervised by this agent, see [launcher](crate::launcher)
+    pub secondary_processes: Vec<SecondaryProcess>,
     /// The socket address to which secondary agents' senders shall connect
     pub bind_address_senders: NodeAddress,
     /// The socket address to which secondary agents' receivers shall connect
@@ -59,12 +104,55 @@ pub struct PrimaryConfig {
     pub activity_worker_map: HashMap<ActivityId, WorkerId>,
 }
 
+impl Default for PrimaryConfig {
+    /// Run no activities, listening on [DEFAULT_ENDPOINT] and [DEFAULT_RECEIVERS_ENDPOINT] with the default cycle
+    /// time and timeouts
+    fn default() -> Self {
+        Self {
+            id: AgentId::new(0),
+            cycle_time: DEFAULT_CYCLE_TIME,
+            activity_dependencies: Default::default(),
+            worker_assignments: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            connection_timeout: DEFAULT_TIMEOUT,
+            startup_timeout: DEFAULT_TIMEOUT,
+            startup_order: Default::default(),
+            step_deadlines: Default::default(),
+            task_chains: Default::default(),
+            error_policies: Default::default(),
+            supervision: Default::default(),
+            watchdog: Default::default(),
+            introspection: Default::default(),
+            statistics: Default::default(),
+            signal_capture: Default::default(),
+            checkpoints: Default::default(),
+            merged_log: Default::default(),
+            forward_signals: Default::default(),
+            secondary_processes: Default::default(),
+            bind_address_senders: NodeAddress::Tcp(DEFAULT_ENDPOINT),
+            bind_address_receivers: NodeAddress::Tcp(DEFAULT_RECEIVERS_ENDPOINT),
+            worker_agent_map: Default::default(),
+            activity_worker_map: Default::default(),
+        }
+    }
+}
+
 /// Primary agent
 pub struct Primary {
     /// Scheduler
     scheduler: Scheduler,
+    /// Handle disabling and re-enabling activities of the scheduler
+    control: ActivityControl,
+    /// Handle pausing and stepping the cycles of the scheduler
+    debugger: CycleDebugger,
+    /// Handle reloading the non-structural parameters of the configuration
+    reload: ConfigReload,
+    /// Handle triggering cycles of on-demand task chains
+    trigger: CycleTrigger,
     /// Handles to the worker threads
-    worker_threads: Vec<JoinHandle<()>>,
+    worker_threads: Vec<WorkerThread>,
+    /// Supervision of the started secondary agent processes
+    launcher: Launcher,
     /// Handles to the relay threads
     relay_threads: Vec<JoinHandle<()>>,
 }
@@ -82,10 +170,50 @@ impl Primary {
             timeout,
             connection_timeout,
             startup_timeout,
+            startup_order,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            supervision,
+            watchdog,
+            introspection,
+            statistics,
+            signal_capture,
+            checkpoints,
+            merged_log,
+            forward_signals,
+            secondary_processes,
             worker_agent_map,
             activity_worker_map,
         } = config;
 
+        dependency::validate(&activity_dependencies, &[])?;
+        task_chains.validate(&activity_dependencies)?;
+        startup_order.validate(&activity_dependencies)?;
+        let merged_log = merged_log.map(MergedLog::new).transpose()?;
+        let introspection = introspection.as_deref().map(introspection::serve).transpose()?;
+        let control = introspection
+            .as_ref()
+            .map(introspection::Introspection::control)
+            .unwrap_or_default();
+        let debugger = introspection
+            .as_ref()
+            .map(introspection::Introspection::debugger)
+            .unwrap_or_default();
+        let reload = introspection
+            .as_ref()
+            .map(introspection::Introspection::reload)
+            .unwrap_or_default();
+        let trigger = CycleTrigger::new();
+
+        let activity_workers = activity_worker_map.clone();
+        let supervisor = AgentSupervisor::new(
+            supervision,
+            activity_worker_map
+                .iter()
+                .filter_map(|(activity_id, worker_id)| Some((*activity_id, *worker_agent_map.get(worker_id)?))),
+        );
+
         // Create scheduler connector depending on given address types and
         // get worker connector builders to be moved into worker threads
         let (mut connector, mut builders) = match (bind_address_receivers, bind_address_senders) {
@@ -113,27 +241,29 @@ impl Primary {
                 let builders = connector.worker_connector_builders();
                 (connector as Box<dyn ConnectScheduler>, builders)
             },
-            _ => {
-                panic!("bind addresses must either be both TCP socket addresses or both Unix socket paths")
-            },
+            _ => return Err(Error::Config(ConfigError::MixedBindAddresses)),
         };
 
         // Create worker threads first so that the connector of the scheduler can connect
         let worker_threads = worker_assignments
             .into_iter()
             .map(|(id, activities)| {
-                let connector_builder = builders.remove(&id).expect("missing connector builder");
-                thread::spawn(move || {
+                let connector_builder = builders.remove(&id).ok_or(Error::WorkerNotFound(id))?;
+                let checkpoints = checkpoints.clone();
+                Ok((id, thread::spawn(move || {
                     let mut connector = connector_builder();
-                    connector.connect_remote().expect("failed to connect");
+                    connector.connect_remote()?;
 
                     let activity_builders = activities;
-                    let worker = Worker::new(id, config.id, activity_builders, connector, timeout);
-                    worker.run().expect("failed to run worker");
-                })
+                    let worker = Worker::new(id, config.id, activity_builders, connector, timeout, None)
+                        .with_checkpoints(checkpoints);
+                    worker.run()
+                })))
             })
-            .collect();
+            .collect::<Result<Vec<_>, Error>>()?;
 
+        // Start the secondary agents once the connector listens for their connections
+        let launcher = Launcher::launch(secondary_processes)?;
         connector.connect_remotes()?;
 
         // Take ownership of the relay threads from the connector.
@@ -141,25 +271,72 @@ impl Primary {
 
         // Create a shared flag to signal shutdown from an OS signal (e.g., Ctrl-C).
         let shutdown_requested = Arc::new(AtomicBool::new(false));
-        register_sigterm_handler(shutdown_requested.clone());
+        register_sigterm_handler(shutdown_requested.clone())?;
+        signal_forwarding::enable(&forward_signals)?;
 
         let scheduler = Scheduler::new(
             id,
             cycle_time,
             timeout,
             startup_timeout,
+            startup_order,
             activity_dependencies,
+            activity_workers,
             connector,
             shutdown_requested,
+            step_deadlines,
+            task_chains,
+            error_policies,
+            supervisor,
+            watchdog,
+            introspection,
+            statistics,
+            signal_capture,
+            merged_log,
+            control.clone(),
+            debugger.clone(),
+            reload.clone(),
+            trigger.clone(),
+            None,
+            None,
         );
 
         Ok(Self {
             scheduler,
+            control,
+            debugger,
+            reload,
+            trigger,
             worker_threads,
+            launcher,
             relay_threads,
         })
     }
 
+    /// Get the handle disabling and re-enabling activities at the cycle boundaries, see [control](crate::control)
+    pub fn activity_control(&self) -> ActivityControl {
+        self.control.clone()
+    }
+
+    /// Get the handle pausing and stepping the cycles of the scheduler, see [debugger](crate::debugger)
+    ///
+    /// Pause it before running the agent to stop the scheduler before the first cycle.
+    pub fn cycle_debugger(&self) -> CycleDebugger {
+        self.debugger.clone()
+    }
+
+    /// Get the handle reloading the non-structural parameters of the configuration, see [reload](crate::reload)
+    ///
+    /// Pass the configuration file to [ConfigReload::watch] to reload it on SIGHUP.
+    pub fn config_reload(&self) -> ConfigReload {
+        self.reload.clone()
+    }
+
+    /// Get the handle triggering cycles of on-demand task chains, see [on_demand](crate::on_demand)
+    pub fn cycle_trigger(&self) -> CycleTrigger {
+        self.trigger.clone()
+    }
+
     /// Run the agent
     pub fn run(&mut self) -> Result<(), Error> {
         // Initialize local time
@@ -168,22 +345,23 @@ impl Primary {
         // Sync time on remotes
         self.scheduler.sync_remotes()?;
 
-        // TODO: Bubble up errors
-        self.scheduler.run();
+        let result = self.scheduler.run();
 
         debug!("Primary agent waiting for background threads to join...");
 
         // Wait for all local worker threads to complete their shutdown.
         // They will exit after receiving the `Terminate` signal from the scheduler's broadcast.
-        for th in self.worker_threads.drain(..) {
-            th.join().unwrap();
-        }
+        let workers = join_workers(self.worker_threads.drain(..));
         // Wait for the communication relay threads to complete their shutdown.
         for th in core::mem::take(&mut self.relay_threads) {
-            th.join().unwrap();
+            if th.join().is_err() {
+                error!("A communication relay thread of the primary agent panicked");
+            }
         }
+        // The secondary agents exit after the termination sent by the scheduler
+        self.launcher.stop();
         debug!("Primary finished!!");
 
-        Ok(())
+        result.and(workers)
     }
 }
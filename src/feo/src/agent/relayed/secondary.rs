@@ -14,18 +14,25 @@
 //! Implementation of a secondary agent for mixed signalling using sockets and mpsc channels
 
 use crate::activity::ActivityIdAndBuilder;
-use crate::agent::NodeAddress;
+use crate::agent::{join_workers, ConnectionPolicy, NodeAddress, WorkerThread};
+use crate::agent::{DEFAULT_ENDPOINT, DEFAULT_RECEIVERS_ENDPOINT, DEFAULT_TIMEOUT};
+use crate::deadline::HardDeadlines;
+use crate::error::{ConfigError, Error};
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::log_relay::LogRelay;
+use crate::panic_report;
+use crate::recording::checkpoint::StateCheckpoints;
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::relayed::sockets_mpsc::{SecondaryConnectorTcp, SecondaryConnectorUnix};
 use crate::signalling::relayed::ConnectSecondary;
+use crate::thread_config::ThreadConfigs;
 use crate::worker::Worker;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use feo_time::Duration;
 use score_log::debug;
 use std::collections::HashMap;
-use std::thread::{self, JoinHandle};
+use std::thread;
 
 /// Configuration of a secondary agent
 pub struct SecondaryConfig {
@@ -35,10 +42,42 @@ pub struct SecondaryConfig {
     pub worker_assignments: Vec<(WorkerId, Vec<ActivityIdAndBuilder>)>,
     /// Maximum time for a worker to make no progress without panicking
     pub timeout: Duration,
+    /// Interval of heartbeats sent by each worker to the primary agent for supervision, if any
+    pub heartbeat_interval: Option<Duration>,
+    /// CPU affinity and scheduling policy per worker thread and activity
+    pub thread_configs: ThreadConfigs,
+    /// Hard step deadlines enforced on the activities of this agent
+    pub hard_deadlines: HardDeadlines,
+    /// Checkpoints of the states of the activities run in this agent, if any
+    pub checkpoints: Option<StateCheckpoints>,
+    /// Relay of the log records of this agent to the primary agent, if any
+    pub log_relay: Option<LogRelay>,
     /// Address on which the scheduler connector is listening for sender channel connections
     pub bind_address_senders: NodeAddress,
     /// Address on which the scheduler connector is listening for receiver channel connections
     pub bind_address_receivers: NodeAddress,
+    /// Timeout and retry policy for connecting to the primary agent
+    pub connection_policy: ConnectionPolicy,
+}
+
+impl Default for SecondaryConfig {
+    /// Run no activities, connecting to [DEFAULT_ENDPOINT] and [DEFAULT_RECEIVERS_ENDPOINT] with the default
+    /// timeout and connection policy
+    fn default() -> Self {
+        Self {
+            id: AgentId::new(0),
+            worker_assignments: Default::default(),
+            timeout: DEFAULT_TIMEOUT,
+            heartbeat_interval: Default::default(),
+            thread_configs: Default::default(),
+            hard_deadlines: Default::default(),
+            checkpoints: Default::default(),
+            log_relay: Default::default(),
+            bind_address_senders: NodeAddress::Tcp(DEFAULT_ENDPOINT),
+            bind_address_receivers: NodeAddress::Tcp(DEFAULT_RECEIVERS_ENDPOINT),
+            connection_policy: Default::default(),
+        }
+    }
 }
 
 /// Secondary agent
@@ -48,18 +87,24 @@ pub struct Secondary {
     /// Connector from the secondary to to the primary process
     connector: Option<Box<dyn ConnectSecondary>>,
     /// Handles to the worker threads
-    worker_threads: Vec<JoinHandle<()>>,
+    worker_threads: Vec<WorkerThread>,
 }
 
 impl Secondary {
     /// Create a new instance
-    pub fn new(config: SecondaryConfig) -> Self {
+    pub fn new(config: SecondaryConfig) -> Result<Self, Error> {
         let SecondaryConfig {
             id,
             worker_assignments,
             timeout,
+            heartbeat_interval,
+            thread_configs,
+            hard_deadlines,
+            checkpoints,
+            log_relay,
             bind_address_senders,
             bind_address_receivers,
+            connection_policy,
         } = config;
 
         let activity_worker_map: HashMap<ActivityId, WorkerId> = worker_assignments
@@ -70,50 +115,72 @@ impl Secondary {
         // Create SecondaryConnector and builders of WorkerConnectors
         let (connector, mut connector_builders) = match (bind_address_receivers, bind_address_senders) {
             (NodeAddress::Tcp(bind_receivers), NodeAddress::Tcp(bind_senders)) => {
-                let (connector, builders) =
-                    SecondaryConnectorTcp::create(id, activity_worker_map, bind_senders, bind_receivers, timeout);
+                let (connector, builders) = SecondaryConnectorTcp::create(
+                    id,
+                    activity_worker_map,
+                    bind_senders,
+                    bind_receivers,
+                    timeout,
+                    connection_policy,
+                );
                 (Box::new(connector) as Box<dyn ConnectSecondary>, builders)
             },
             (NodeAddress::UnixSocket(bind_receivers), NodeAddress::UnixSocket(bind_senders)) => {
-                let (connector, builders) =
-                    SecondaryConnectorUnix::create(id, activity_worker_map, bind_senders, bind_receivers, timeout);
+                let (connector, builders) = SecondaryConnectorUnix::create(
+                    id,
+                    activity_worker_map,
+                    bind_senders,
+                    bind_receivers,
+                    timeout,
+                    connection_policy,
+                );
                 (Box::new(connector) as Box<dyn ConnectSecondary>, builders)
             },
-            _ => {
-                panic!("bind addresses must either be both TCP socket addresses or both Unix socket paths")
-            },
+            _ => return Err(Error::Config(ConfigError::MixedBindAddresses)),
         };
 
+        // Report panics of activities to the primary agent before their failures
+        panic_report::install();
+
         let worker_threads = worker_assignments
             .into_iter()
             .map(|(id, activities)| {
-                let connector_builder = connector_builders.remove(&id).expect("missing connector builder");
-                thread::spawn(move || {
+                let connector_builder = connector_builders.remove(&id).ok_or(Error::WorkerNotFound(id))?;
+                let thread_configs = thread_configs.for_worker(id, activities.iter().map(|(id, _)| *id));
+                let hard_deadlines = hard_deadlines.for_activities(activities.iter().map(|(id, _)| *id));
+                let checkpoints = checkpoints.clone();
+                let log_relay = log_relay.clone();
+                Ok((id, thread::spawn(move || {
                     let mut connector = connector_builder();
-                    connector.connect_remote().expect("failed to connect");
-                    let worker = Worker::new(id, config.id, activities, connector, timeout);
+                    connector.connect_remote()?;
+                    let worker = Worker::new(id, config.id, activities, connector, timeout, heartbeat_interval)
+                        .with_thread_configs(thread_configs)
+                        .with_hard_deadlines(hard_deadlines)
+                        .with_checkpoints(checkpoints)
+                        .with_log_relay(log_relay);
 
-                    worker.run().expect("failed to run worker");
-                })
+                    worker.run()
+                })))
             })
-            .collect();
+            .collect::<Result<Vec<_>, Error>>()?;
 
-        Self {
+        Ok(Self {
             id,
             connector: Some(connector),
             worker_threads,
-        }
+        })
     }
 
     /// Run the agent
-    pub fn run(mut self) {
+    pub fn run(mut self) -> Result<(), Error> {
         debug!("Running secondary with ID {:?}", self.id);
 
-        self.connector.take().unwrap().run_and_connect();
-
-        for th in self.worker_threads {
-            th.join().unwrap();
+        if let Some(mut connector) = self.connector.take() {
+            connector.run_and_connect();
         }
+
+        let result = join_workers(self.worker_threads);
         debug!("Secondary with ID {:?} finished", self.id);
+        result
     }
 }
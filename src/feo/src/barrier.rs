@@ -0,0 +1,205 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! End-of-cycle barrier of task chains
+//!
+//! Each cycle of a task chain opens a [CycleBarrier] for all activities of the chain. An activity arrives
+//! at the barrier once it signalled ready after its step, or once its step has been skipped, e.g. because
+//! it is degraded or its agent has been lost. The cycle is complete when all activities have arrived.
+//!
+//! If no ready signal is received within the receive timeout of the scheduler, the scheduler shuts down
+//! with [Error::CycleTimeout](crate::error::Error::CycleTimeout), reporting the [Straggler]s of all
+//! running cycles: the activities which have not arrived yet, the agents they run on, and whether they
+//! are still stepping or have not been stepped yet.
+
+use crate::ids::{ActivityId, AgentId, ChainId};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt;
+use feo_time::Duration;
+use score_log::fmt::{DebugSet, FormatSpec, ScoreDebug, ScoreWrite};
+
+/// Barrier at the end of a cycle of a task chain
+#[derive(Debug, Default)]
+pub(crate) struct CycleBarrier {
+    /// Activities which have not arrived at the barrier yet
+    pending: BTreeSet<ActivityId>,
+}
+
+impl CycleBarrier {
+    /// Open the barrier of a new cycle for the given activities
+    pub(crate) fn open<'a>(&mut self, activities: impl IntoIterator<Item = &'a ActivityId>) {
+        self.pending = activities.into_iter().copied().collect();
+    }
+
+    /// Note the arrival of the given activity, returning whether it was pending
+    pub(crate) fn arrive(&mut self, id: &ActivityId) -> bool {
+        self.pending.remove(id)
+    }
+
+    /// Whether all activities have arrived
+    pub(crate) fn is_complete(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Get the activities which have not arrived yet, in the order of their ids
+    pub(crate) fn pending(&self) -> impl Iterator<Item = &ActivityId> {
+        self.pending.iter()
+    }
+}
+
+/// Activity which has not arrived at the barrier of a running cycle
+#[derive(Debug, Clone, PartialEq, Eq, ScoreDebug)]
+pub struct Straggler {
+    /// Task chain of the activity
+    pub chain: ChainId,
+    /// Number of the running cycle
+    pub cycle: u64,
+    /// ID of the activity
+    pub activity: ActivityId,
+    /// Agent running the activity, if known to the scheduler
+    pub agent: Option<AgentId>,
+    /// What the activity is doing
+    pub state: StragglerState,
+}
+
+impl fmt::Display for Straggler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "activity {}", self.activity)?;
+        if let Some(agent) = self.agent {
+            write!(f, " on agent {agent}")?;
+        }
+        write!(
+            f,
+            " in cycle {} of task chain {} {}",
+            self.cycle, self.chain, self.state
+        )
+    }
+}
+
+/// State of an activity which has not arrived at the barrier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScoreDebug)]
+pub enum StragglerState {
+    /// The step has been triggered the given time ago without a ready signal since
+    Stepping(Duration),
+    /// The step is due, but held back until the prioritized worker of the activity is free
+    Held,
+    /// Dependencies of the activity have not arrived yet, so the step has not been triggered
+    Blocked,
+}
+
+impl fmt::Display for StragglerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StragglerState::Stepping(elapsed) => write!(f, "stepping for {:0.3}s", elapsed.as_secs_f64()),
+            StragglerState::Held => write!(f, "held until its worker is free"),
+            StragglerState::Blocked => write!(f, "waiting for its dependencies"),
+        }
+    }
+}
+
+/// Expiry of the receive timeout while cycles were running, with the activities holding them up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleTimeout {
+    /// Receive timeout of the scheduler
+    pub timeout: Duration,
+    /// Activities which have not arrived at the barriers of the running cycles,
+    /// sorted by task chain and activity
+    pub stragglers: Vec<Straggler>,
+}
+
+impl CycleTimeout {
+    /// Get the stragglers which are still stepping, i.e. the likely cause of the stall
+    pub fn stepping(&self) -> impl Iterator<Item = &Straggler> {
+        self.stragglers
+            .iter()
+            .filter(|straggler| matches!(straggler.state, StragglerState::Stepping(_)))
+    }
+}
+
+impl fmt::Display for CycleTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timeout reached ({:0.3}s) while waiting for the end of cycle, pending: ",
+            self.timeout.as_secs_f64()
+        )?;
+        for (index, straggler) in self.stragglers.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{straggler}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ScoreDebug for CycleTimeout {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("CycleTimeout", spec)?;
+        DebugSet::new(f, spec).entries(self.stragglers.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barrier_reports_pending_activities() {
+        let activities = [ActivityId::new(3), ActivityId::new(1), ActivityId::new(2)];
+        let mut barrier = CycleBarrier::default();
+        assert!(barrier.is_complete());
+
+        barrier.open(activities.iter());
+        assert!(barrier.arrive(&ActivityId::new(1)));
+        assert!(!barrier.arrive(&ActivityId::new(1)));
+        assert!(!barrier.arrive(&ActivityId::new(7)));
+        assert!(!barrier.is_complete());
+        assert_eq!(
+            barrier.pending().copied().collect::<Vec<_>>(),
+            [ActivityId::new(2), ActivityId::new(3)]
+        );
+
+        barrier.arrive(&ActivityId::new(2));
+        barrier.arrive(&ActivityId::new(3));
+        assert!(barrier.is_complete());
+
+        let timeout = CycleTimeout {
+            timeout: Duration::from_millis(500),
+            stragglers: alloc::vec![
+                Straggler {
+                    chain: ChainId::new(0),
+                    cycle: 7,
+                    activity: ActivityId::new(2),
+                    agent: Some(AgentId::new(100)),
+                    state: StragglerState::Stepping(Duration::from_millis(480)),
+                },
+                Straggler {
+                    chain: ChainId::new(0),
+                    cycle: 7,
+                    activity: ActivityId::new(3),
+                    agent: None,
+                    state: StragglerState::Blocked,
+                },
+            ],
+        };
+        assert_eq!(timeout.stepping().count(), 1);
+        assert_eq!(
+            alloc::format!("{timeout}"),
+            "timeout reached (0.500s) while waiting for the end of cycle, pending: \
+             activity A2 on agent Agt-100 in cycle 7 of task chain Ch0 stepping for 0.480s; \
+             activity A3 in cycle 7 of task chain Ch0 waiting for its dependencies"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Independent task chains of the primary agent
+
+use crate::error::Error;
+use crate::ids::{ActivityId, ChainId};
+use crate::overrun::OverrunPolicy;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use std::collections::{HashMap, HashSet};
+
+/// ID of the main task chain, running with the cycle time of the primary agent
+pub const MAIN_CHAIN: ChainId = ChainId::new(0);
+
+/// Task chain configuration of the primary agent
+///
+/// By default, all activities belong to the [MAIN_CHAIN], which runs with the cycle time
+/// configured for the primary agent. Additional chains run independently with their own cycle time,
+/// e.g. a 10ms control chain next to a 100ms diagnostics chain.
+/// Chains marked as on demand run one cycle per trigger instead, see [on_demand](crate::on_demand).
+/// An activity must not depend on activities of a different chain.
+///
+/// Activities of different chains may share a worker. By default, the steps of all chains are sent to
+/// the worker as soon as they are due and run in that order. With chain priorities, a worker shared by
+/// chains of different priorities gets one step at a time: the scheduler holds back further due steps
+/// and, whenever the worker is free, sends the held step of the highest priority. So a cycle of a high
+/// priority chain overtakes the queued steps of lower priority chains, while a running step is always
+/// completed.
+///
+/// A periodic chain whose cycle overruns its cycle time starts the next cycle immediately, unless
+/// configured otherwise with an [OverrunPolicy], see [overrun](crate::overrun).
+#[derive(Debug, Clone, Default)]
+pub struct TaskChains {
+    /// Cycle time per additional task chain
+    pub cycle_times: HashMap<ChainId, Duration>,
+    /// Task chain per activity. Activities without an entry belong to the [MAIN_CHAIN].
+    pub activity_chains: HashMap<ActivityId, ChainId>,
+    /// Task chains cycled on demand, using their cycle time as the maximum duration of a cycle
+    pub on_demand: HashSet<ChainId>,
+    /// Priority per task chain, higher values first. Chains without an entry have priority 0.
+    pub priorities: HashMap<ChainId, u8>,
+    /// Reaction to overrunning cycles per task chain. Chains without an entry run the next cycle immediately.
+    pub overrun_policies: HashMap<ChainId, OverrunPolicy>,
+}
+
+impl TaskChains {
+    /// Add a task chain with the given cycle time and activities
+    pub fn with_chain(
+        mut self,
+        id: ChainId,
+        cycle_time: Duration,
+        activities: impl IntoIterator<Item = ActivityId>,
+    ) -> Self {
+        self.cycle_times.insert(id, cycle_time);
+        self.activity_chains
+            .extend(activities.into_iter().map(|activity_id| (activity_id, id)));
+        self
+    }
+
+    /// Cycle the given task chain on demand instead of periodically, see [on_demand](crate::on_demand)
+    pub fn with_on_demand(mut self, id: ChainId) -> Self {
+        self.on_demand.insert(id);
+        self
+    }
+
+    /// Give the steps of the given task chain the given priority on workers shared with other chains
+    pub fn with_priority(mut self, id: ChainId, priority: u8) -> Self {
+        self.priorities.insert(id, priority);
+        self
+    }
+
+    /// React to overrunning cycles of the given task chain with the given policy
+    pub fn with_overrun_policy(mut self, id: ChainId, policy: OverrunPolicy) -> Self {
+        self.overrun_policies.insert(id, policy);
+        self
+    }
+
+    /// Get the priority of the given task chain
+    pub(crate) fn priority_of(&self, id: &ChainId) -> u8 {
+        self.priorities.get(id).copied().unwrap_or_default()
+    }
+
+    /// Get the overrun policy of the given task chain
+    pub(crate) fn overrun_policy_of(&self, id: &ChainId) -> OverrunPolicy {
+        self.overrun_policies.get(id).copied().unwrap_or_default()
+    }
+
+    /// Get the task chain of the given activity
+    pub(crate) fn chain_of(&self, id: &ActivityId) -> ChainId {
+        self.activity_chains.get(id).copied().unwrap_or(MAIN_CHAIN)
+    }
+
+    /// Check that all chains are defined and no activity depends on an activity of a different chain
+    pub(crate) fn validate(&self, activity_depends: &HashMap<ActivityId, Vec<ActivityId>>) -> Result<(), Error> {
+        if let Some(chain_id) = self
+            .activity_chains
+            .values()
+            .chain(self.on_demand.iter())
+            .chain(self.priorities.keys())
+            .chain(self.overrun_policies.keys())
+            .find(|id| **id != MAIN_CHAIN && !self.cycle_times.contains_key(id))
+        {
+            return Err(Error::ChainNotFound(*chain_id));
+        }
+
+        for (id, dependencies) in activity_depends.iter() {
+            let chain_id = self.chain_of(id);
+            if let Some(dependency) = dependencies.iter().find(|dep| self.chain_of(dep) != chain_id) {
+                return Err(Error::ChainDependency(*id, *dependency));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cross_chain_dependencies_are_rejected() {
+        use alloc::vec;
+
+        let diagnostics = ChainId::new(1);
+        let chains = TaskChains::default().with_chain(diagnostics, Duration::from_millis(100), [ActivityId::new(3)]);
+
+        let deps = HashMap::from([
+            (ActivityId::new(1), vec![]),
+            (ActivityId::new(2), vec![ActivityId::new(1)]),
+            (ActivityId::new(3), vec![]),
+        ]);
+        assert!(chains.validate(&deps).is_ok());
+
+        let deps = HashMap::from([(ActivityId::new(3), vec![ActivityId::new(1)])]);
+        assert!(matches!(chains.validate(&deps), Err(Error::ChainDependency(..))));
+
+        let chains = TaskChains {
+            activity_chains: HashMap::from([(ActivityId::new(3), ChainId::new(2))]),
+            ..Default::default()
+        };
+        assert!(matches!(chains.validate(&deps), Err(Error::ChainNotFound(..))));
+
+        let chains = TaskChains::default().with_on_demand(ChainId::new(2));
+        assert!(matches!(chains.validate(&HashMap::new()), Err(Error::ChainNotFound(..))));
+
+        let chains = TaskChains::default().with_priority(ChainId::new(2), 1);
+        assert!(matches!(chains.validate(&HashMap::new()), Err(Error::ChainNotFound(..))));
+
+        let chains = TaskChains::default().with_overrun_policy(ChainId::new(2), OverrunPolicy::SkipNext);
+        assert!(matches!(chains.validate(&HashMap::new()), Err(Error::ChainNotFound(..))));
+    }
+}
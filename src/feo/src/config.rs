@@ -0,0 +1,850 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Declarative configuration of FEO applications
+//!
+//! An [AppConfig] describes the agents, workers, activities and their dependencies,
+//! task chains and topics of an application. It is parsed from TOML or JSON, validated
+//! and provides the inputs for the configuration of the primary and secondary agents:
+//!
+//! ```toml
+//! primary_agent = 100
+//! cycle_time_ms = 50
+//! clock = "monotonic_raw"
+//!
+//! [[agents]]
+//! id = 100
+//! workers = [40, 41]
+//!
+//! [[agents]]
+//! id = 101
+//! workers = [42]
+//! command = ["/usr/bin/adas_secondary", "1"]
+//! max_restarts = 3
+//!
+//! [[chains]]
+//! id = 1
+//! cycle_time_ms = 200
+//! overrun = { degrade = 4 }
+//!
+//! [[activities]]
+//! id = 0
+//! name = "camera"
+//! worker = 40
+//!
+//! [[activities]]
+//! id = 1
+//! name = "diagnostics"
+//! worker = 41
+//! chain = 1
+//! depends_on = []
+//! starts_after = [0]
+//! thread = { cpus = [3] }
+//!
+//! [[workers]]
+//! id = 40
+//! thread = { cpus = [2], policy = "fifo", priority = 80 }
+//!
+//! [[topics]]
+//! name = "feo/com/vehicle/camera"
+//! writers = [0]
+//! readers = []
+//! ```
+//!
+//! The cycle times, the receive timeout of the scheduler (`timeout_ms`), the log level (`log_level`),
+//! the filter of recorded topics (`record_filter`) and whether debug topics are enabled (`debug_topics`,
+//! see [debug_topic](crate::debug_topic)) can be reloaded while the application is running,
+//! see [reload](crate::reload). All other entries describe the structure of the application.
+//!
+//! The reaction of a task chain to overrunning cycles is configured with `overrun`, at the top level for
+//! the main chain: `"run_immediately"` (default), `"skip_next"` or `{ degrade = <factor> }`,
+//! see [OverrunPolicy].
+
+use crate::activity::{ActivityBuilder, ActivityIdAndBuilder};
+use crate::chain::{TaskChains, MAIN_CHAIN};
+use crate::ids::{ActivityId, AgentId, ChainId, WorkerId};
+use crate::launcher::SecondaryProcess;
+use crate::overrun::OverrunPolicy;
+use crate::recording::filter::RecordFilter;
+use crate::startup::StartupOrder;
+use crate::thread_config::{ThreadConfig, ThreadConfigs};
+use crate::topicspec::Direction;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use feo_time::{ClockSource, Duration};
+use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use score_log::LevelFilter;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Declarative description of a FEO application
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppConfig {
+    /// ID of the primary agent
+    pub primary_agent: u64,
+    /// Cycle time of the main task chain in milliseconds
+    pub cycle_time_ms: u64,
+    /// OS clock backing the FEO clock, to be selected with [feo_time::set_clock_source] at startup
+    #[serde(default)]
+    pub clock: ClockSource,
+    /// Reaction of the main task chain to overrunning cycles, see [overrun](crate::overrun)
+    #[serde(default)]
+    pub overrun: OverrunPolicy,
+    /// Agents and the workers running on them
+    pub agents: Vec<AgentConfig>,
+    /// Thread configurations of workers
+    #[serde(default)]
+    pub workers: Vec<WorkerConfig>,
+    /// Additional task chains
+    #[serde(default)]
+    pub chains: Vec<ChainConfig>,
+    /// Activities
+    pub activities: Vec<ActivityConfig>,
+    /// Topics and the activities writing and reading them
+    #[serde(default)]
+    pub topics: Vec<TopicConfig>,
+    /// Receive timeout of the scheduler in milliseconds
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Maximum level of the log records of the primary agent
+    #[serde(default)]
+    pub log_level: Option<LogLevel>,
+    /// Filter of the topics recorded by a recorder in the primary agent
+    #[serde(default)]
+    pub record_filter: Option<RecordFilterConfig>,
+    /// Whether debug topics are enabled, to be applied with [debug_topic::set_enabled](crate::debug_topic::set_enabled)
+    /// at startup
+    #[serde(default)]
+    pub debug_topics: bool,
+}
+
+/// Maximum log level of an [AppConfig]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Record filter entry of an [AppConfig], see [RecordFilter]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordFilterConfig {
+    /// Topics to record. All topics are included if empty.
+    #[serde(default)]
+    pub include_topics: Vec<String>,
+    /// Topics not to record
+    #[serde(default)]
+    pub exclude_topics: Vec<String>,
+    /// Type names to record. All types are included if empty.
+    #[serde(default)]
+    pub include_types: Vec<String>,
+    /// Type names not to record
+    #[serde(default)]
+    pub exclude_types: Vec<String>,
+    /// IDs of the producing activities of the topics to record. All topics are included if empty.
+    #[serde(default)]
+    pub include_producers: Vec<u64>,
+    /// IDs of the producing activities of the topics not to record
+    #[serde(default)]
+    pub exclude_producers: Vec<u64>,
+    /// Sampling ratio per topic: only every n-th available sample of the topic is recorded
+    #[serde(default)]
+    pub sampling: HashMap<String, u32>,
+}
+
+impl RecordFilterConfig {
+    /// Create the described filter
+    pub fn filter(&self) -> RecordFilter {
+        RecordFilter {
+            include_topics: self.include_topics.iter().cloned().collect(),
+            exclude_topics: self.exclude_topics.iter().cloned().collect(),
+            include_types: self.include_types.iter().cloned().collect(),
+            exclude_types: self.exclude_types.iter().cloned().collect(),
+            include_producers: self.include_producers.iter().map(|id| ActivityId::new(*id)).collect(),
+            exclude_producers: self.exclude_producers.iter().map(|id| ActivityId::new(*id)).collect(),
+            sampling: self.sampling.clone(),
+        }
+    }
+}
+
+/// Agent entry of an [AppConfig]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentConfig {
+    /// ID of the agent
+    pub id: u64,
+    /// IDs of the workers running on the agent
+    pub workers: Vec<u64>,
+    /// Program and arguments of a secondary agent started by the primary agent, see [launcher](crate::launcher)
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Maximum number of restarts of the started agent after crashes
+    #[serde(default)]
+    pub max_restarts: u32,
+}
+
+/// Worker entry of an [AppConfig]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkerConfig {
+    /// ID of the worker
+    pub id: u64,
+    /// CPU affinity and scheduling policy of the worker thread
+    pub thread: ThreadConfig,
+}
+
+/// Task chain entry of an [AppConfig]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainConfig {
+    /// ID of the chain
+    pub id: u64,
+    /// Cycle time of the chain in milliseconds, the maximum duration of a cycle if cycled on demand
+    pub cycle_time_ms: u64,
+    /// Whether the chain is cycled on demand instead of periodically, see [on_demand](crate::on_demand)
+    #[serde(default)]
+    pub on_demand: bool,
+    /// Priority of the steps of the chain on workers shared with other chains, see [TaskChains]
+    #[serde(default)]
+    pub priority: u8,
+    /// Reaction of the chain to overrunning cycles, see [overrun](crate::overrun)
+    #[serde(default)]
+    pub overrun: OverrunPolicy,
+}
+
+/// Activity entry of an [AppConfig]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActivityConfig {
+    /// ID of the activity
+    pub id: u64,
+    /// Optional name, e.g. to select the activity builder
+    #[serde(default)]
+    pub name: Option<String>,
+    /// ID of the worker running the activity
+    pub worker: u64,
+    /// IDs of the activities this activity depends on
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
+    /// ID of the task chain of the activity. Defaults to the main chain.
+    #[serde(default)]
+    pub chain: Option<u64>,
+    /// IDs of the activities started up before this activity, see [startup](crate::startup)
+    #[serde(default)]
+    pub starts_after: Vec<u64>,
+    /// CPU affinity and scheduling policy applied to the worker thread while running the activity
+    #[serde(default)]
+    pub thread: Option<ThreadConfig>,
+}
+
+/// Topic entry of an [AppConfig]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TopicConfig {
+    /// Name of the topic
+    pub name: String,
+    /// IDs of the activities writing the topic
+    #[serde(default)]
+    pub writers: Vec<u64>,
+    /// IDs of the activities reading the topic
+    #[serde(default)]
+    pub readers: Vec<u64>,
+}
+
+/// Error loading or validating an [AppConfig]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read
+    Io(std::io::Error),
+    /// The configuration could not be parsed
+    Parse(String),
+    /// The file extension is neither `toml` nor `json`
+    UnknownFormat,
+    DuplicateAgent(AgentId),
+    DuplicateWorker(WorkerId),
+    UndefinedWorker(WorkerId),
+    DuplicateActivity(ActivityId),
+    DuplicateChain(ChainId),
+    DuplicateTopic(String),
+    PrimaryAgentNotFound(AgentId),
+    WorkerNotFound {
+        activity: ActivityId,
+        worker: WorkerId,
+    },
+    DependencyNotFound {
+        activity: ActivityId,
+        dependency: ActivityId,
+    },
+    ChainNotFound {
+        activity: ActivityId,
+        chain: ChainId,
+    },
+    ChainDependency {
+        activity: ActivityId,
+        dependency: ActivityId,
+    },
+    CyclicDependency(ActivityId),
+    TopicPeerNotFound {
+        topic: String,
+        activity: ActivityId,
+    },
+    /// No configuration file is watched for reloading, see [reload](crate::reload)
+    NotWatched,
+    /// A reloaded configuration changes the given structural entries, which requires a restart
+    StructuralChange(&'static str),
+    /// The bind addresses of an agent mix TCP socket addresses and Unix socket paths
+    MixedBindAddresses,
+    /// Workers are assigned to the primary agent, whose signalling requires all workers in secondary agents
+    LocalWorkers,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read configuration: {e}"),
+            ConfigError::Parse(e) => write!(f, "failed to parse configuration: {e}"),
+            ConfigError::UnknownFormat => write!(f, "unknown configuration format, expected .toml or .json"),
+            ConfigError::DuplicateAgent(id) => write!(f, "agent {id} is defined more than once"),
+            ConfigError::DuplicateWorker(id) => write!(f, "worker {id} is assigned to more than one agent"),
+            ConfigError::UndefinedWorker(id) => write!(f, "worker {id} is not assigned to any agent"),
+            ConfigError::DuplicateActivity(id) => write!(f, "activity {id} is defined more than once"),
+            ConfigError::DuplicateChain(id) => write!(f, "task chain {id} is defined more than once"),
+            ConfigError::DuplicateTopic(topic) => write!(f, "topic {topic} is defined more than once"),
+            ConfigError::PrimaryAgentNotFound(id) => write!(f, "primary agent {id} is not defined in agents"),
+            ConfigError::WorkerNotFound { activity, worker } => {
+                write!(f, "activity {activity}: worker {worker} is not assigned to any agent")
+            },
+            ConfigError::DependencyNotFound { activity, dependency } => {
+                write!(f, "activity {activity}: dependency {dependency} is not defined")
+            },
+            ConfigError::ChainNotFound { activity, chain } => {
+                write!(f, "activity {activity}: task chain {chain} is not defined")
+            },
+            ConfigError::ChainDependency { activity, dependency } => {
+                write!(
+                    f,
+                    "activity {activity}: dependency {dependency} belongs to a different task chain"
+                )
+            },
+            ConfigError::CyclicDependency(id) => write!(f, "activity {id} is part of a dependency cycle"),
+            ConfigError::TopicPeerNotFound { topic, activity } => {
+                write!(f, "topic {topic}: activity {activity} is not defined")
+            },
+            ConfigError::NotWatched => write!(f, "no configuration file is watched for reloading"),
+            ConfigError::StructuralChange(entries) => {
+                write!(f, "changing {entries} requires a restart")
+            },
+            ConfigError::MixedBindAddresses => {
+                write!(f, "bind addresses must either be both TCP socket addresses or both Unix socket paths")
+            },
+            ConfigError::LocalWorkers => write!(f, "signalling does not support workers in the primary agent"),
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+impl ScoreDebug for ConfigError {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("ConfigError(", spec)?;
+        f.write_str(&self.to_string(), spec)?;
+        f.write_str(")", spec)
+    }
+}
+
+impl AppConfig {
+    /// Load and validate a configuration file, choosing the format by its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => Err(ConfigError::UnknownFormat),
+        }
+    }
+
+    /// Parse and validate a configuration in TOML format
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(content).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse and validate a configuration in JSON format
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(content).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the consistency of all entries
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut agents = HashSet::new();
+        let mut workers = HashSet::new();
+        for agent in self.agents.iter() {
+            if !agents.insert(agent.id) {
+                return Err(ConfigError::DuplicateAgent(AgentId::new(agent.id)));
+            }
+            for worker in agent.workers.iter() {
+                if !workers.insert(*worker) {
+                    return Err(ConfigError::DuplicateWorker(WorkerId::new(*worker)));
+                }
+            }
+        }
+        if !agents.contains(&self.primary_agent) {
+            return Err(ConfigError::PrimaryAgentNotFound(AgentId::new(self.primary_agent)));
+        }
+        if let Some(worker) = self.workers.iter().find(|worker| !workers.contains(&worker.id)) {
+            return Err(ConfigError::UndefinedWorker(WorkerId::new(worker.id)));
+        }
+
+        let mut chains = HashSet::new();
+        for chain in self.chains.iter() {
+            if chain.id == u64::from(MAIN_CHAIN) || !chains.insert(chain.id) {
+                return Err(ConfigError::DuplicateChain(ChainId::new(chain.id)));
+            }
+        }
+
+        let mut activities = HashMap::new();
+        for activity in self.activities.iter() {
+            if activities.insert(activity.id, activity).is_some() {
+                return Err(ConfigError::DuplicateActivity(ActivityId::new(activity.id)));
+            }
+        }
+
+        for activity in self.activities.iter() {
+            let id = ActivityId::new(activity.id);
+            if !workers.contains(&activity.worker) {
+                return Err(ConfigError::WorkerNotFound {
+                    activity: id,
+                    worker: WorkerId::new(activity.worker),
+                });
+            }
+            if let Some(chain) = activity.chain.filter(|chain| !chains.contains(chain)) {
+                return Err(ConfigError::ChainNotFound {
+                    activity: id,
+                    chain: ChainId::new(chain),
+                });
+            }
+            for dependency in activity.depends_on.iter() {
+                let Some(dependency_config) = activities.get(dependency) else {
+                    return Err(ConfigError::DependencyNotFound {
+                        activity: id,
+                        dependency: ActivityId::new(*dependency),
+                    });
+                };
+                if dependency_config.chain != activity.chain {
+                    return Err(ConfigError::ChainDependency {
+                        activity: id,
+                        dependency: ActivityId::new(*dependency),
+                    });
+                }
+            }
+            if let Some(dependency) = activity.starts_after.iter().find(|dep| !activities.contains_key(dep)) {
+                return Err(ConfigError::DependencyNotFound {
+                    activity: id,
+                    dependency: ActivityId::new(*dependency),
+                });
+            }
+        }
+
+        self.check_acyclic()?;
+
+        let mut topics = HashSet::new();
+        for topic in self.topics.iter() {
+            if !topics.insert(topic.name.as_str()) {
+                return Err(ConfigError::DuplicateTopic(topic.name.clone()));
+            }
+            if let Some(peer) = topic
+                .writers
+                .iter()
+                .chain(topic.readers.iter())
+                .find(|peer| !activities.contains_key(peer))
+            {
+                return Err(ConfigError::TopicPeerNotFound {
+                    topic: topic.name.clone(),
+                    activity: ActivityId::new(*peer),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the activity dependencies do not contain cycles
+    fn check_acyclic(&self) -> Result<(), ConfigError> {
+        // Repeatedly remove activities without unresolved dependencies
+        let mut pending: HashMap<u64, usize> = self
+            .activities
+            .iter()
+            .map(|activity| (activity.id, activity.depends_on.len()))
+            .collect();
+        let mut resolved: Vec<u64> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        while let Some(id) = resolved.pop() {
+            pending.remove(&id);
+            for activity in self.activities.iter() {
+                let count = activity.depends_on.iter().filter(|dep| **dep == id).count();
+                if count == 0 {
+                    continue;
+                }
+                if let Some(unresolved) = pending.get_mut(&activity.id) {
+                    *unresolved -= count;
+                    if *unresolved == 0 {
+                        resolved.push(activity.id);
+                    }
+                }
+            }
+        }
+
+        match pending.keys().min() {
+            Some(id) => Err(ConfigError::CyclicDependency(ActivityId::new(*id))),
+            None => Ok(()),
+        }
+    }
+
+    /// ID of the primary agent
+    pub fn primary_agent(&self) -> AgentId {
+        AgentId::new(self.primary_agent)
+    }
+
+    /// IDs of all secondary agents
+    pub fn secondary_agents(&self) -> Vec<AgentId> {
+        self.agents
+            .iter()
+            .filter(|agent| agent.id != self.primary_agent)
+            .map(|agent| AgentId::new(agent.id))
+            .collect()
+    }
+
+    /// Secondary agents started by the primary agent, i.e. those with a command
+    pub fn secondary_processes(&self) -> Vec<SecondaryProcess> {
+        self.agents
+            .iter()
+            .filter(|agent| agent.id != self.primary_agent && !agent.command.is_empty())
+            .map(|agent| {
+                SecondaryProcess::new(AgentId::new(agent.id), agent.command.iter().cloned())
+                    .with_max_restarts(agent.max_restarts)
+            })
+            .collect()
+    }
+
+    /// Cycle time of the main task chain
+    pub fn cycle_time(&self) -> Duration {
+        Duration::from_millis(self.cycle_time_ms)
+    }
+
+    /// Receive timeout of the scheduler, if configured
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// Check that the given configuration only differs from this one in reloadable entries
+    ///
+    /// Reloadable are the cycle times, the receive timeout, the log level, the record filter and
+    /// whether debug topics are enabled.
+    pub fn check_reloadable(&self, other: &AppConfig) -> Result<(), ConfigError> {
+        let structural_change = if self.primary_agent != other.primary_agent || self.agents != other.agents {
+            Some("agents")
+        } else if self.clock != other.clock {
+            Some("clock")
+        } else if self.overrun != other.overrun {
+            Some("overrun")
+        } else if self.workers != other.workers {
+            Some("workers")
+        } else if self.chains.len() != other.chains.len()
+            || self
+                .chains
+                .iter()
+                .zip(other.chains.iter())
+                .any(|(chain, other)| {
+                    chain.id != other.id
+                        || chain.on_demand != other.on_demand
+                        || chain.priority != other.priority
+                        || chain.overrun != other.overrun
+                })
+        {
+            Some("task chains")
+        } else if self.activities != other.activities {
+            Some("activities")
+        } else if self.topics != other.topics {
+            Some("topics")
+        } else {
+            None
+        };
+        match structural_change {
+            Some(entries) => Err(ConfigError::StructuralChange(entries)),
+            None => Ok(()),
+        }
+    }
+
+    /// Dependencies per activity
+    pub fn activity_dependencies(&self) -> HashMap<ActivityId, Vec<ActivityId>> {
+        self.activities
+            .iter()
+            .map(|activity| {
+                let dependencies = activity.depends_on.iter().map(|id| ActivityId::new(*id)).collect();
+                (ActivityId::new(activity.id), dependencies)
+            })
+            .collect()
+    }
+
+    /// Startup dependencies of the activities
+    pub fn startup_order(&self) -> StartupOrder {
+        let mut startup_order = StartupOrder::new();
+        for activity in self.activities.iter() {
+            let id = ActivityId::new(activity.id);
+            for dependency in activity.starts_after.iter() {
+                startup_order = startup_order.with_dependency(id, ActivityId::new(*dependency));
+            }
+        }
+        startup_order
+    }
+
+    /// Task chains and the activities belonging to them
+    pub fn task_chains(&self) -> TaskChains {
+        let mut task_chains = TaskChains::default();
+        if self.overrun != OverrunPolicy::default() {
+            task_chains = task_chains.with_overrun_policy(MAIN_CHAIN, self.overrun);
+        }
+        for chain in self.chains.iter() {
+            let activities = self
+                .activities
+                .iter()
+                .filter(|activity| activity.chain == Some(chain.id))
+                .map(|activity| ActivityId::new(activity.id));
+            task_chains = task_chains.with_chain(
+                ChainId::new(chain.id),
+                Duration::from_millis(chain.cycle_time_ms),
+                activities,
+            );
+            if chain.on_demand {
+                task_chains = task_chains.with_on_demand(ChainId::new(chain.id));
+            }
+            if chain.priority > 0 {
+                task_chains = task_chains.with_priority(ChainId::new(chain.id), chain.priority);
+            }
+            if chain.overrun != OverrunPolicy::default() {
+                task_chains = task_chains.with_overrun_policy(ChainId::new(chain.id), chain.overrun);
+            }
+        }
+        task_chains
+    }
+
+    /// Workers and their activities per agent
+    pub fn agent_assignments(&self) -> Vec<(AgentId, Vec<(WorkerId, Vec<ActivityId>)>)> {
+        self.agents
+            .iter()
+            .map(|agent| {
+                let workers = agent
+                    .workers
+                    .iter()
+                    .map(|worker| (WorkerId::new(*worker), self.worker_activities(*worker).collect()))
+                    .collect();
+                (AgentId::new(agent.id), workers)
+            })
+            .collect()
+    }
+
+    /// Workers of the given agent with the builders of their activities
+    ///
+    /// The builder of each activity is created by calling `builder` with its entry.
+    pub fn worker_assignments(
+        &self,
+        agent: AgentId,
+        mut builder: impl FnMut(&ActivityConfig) -> Box<dyn ActivityBuilder>,
+    ) -> Vec<(WorkerId, Vec<ActivityIdAndBuilder>)> {
+        let Some(agent) = self.agents.iter().find(|a| a.id == u64::from(agent)) else {
+            return Vec::new();
+        };
+        agent
+            .workers
+            .iter()
+            .map(|worker| {
+                let activities = self
+                    .activities
+                    .iter()
+                    .filter(|activity| activity.worker == *worker)
+                    .map(|activity| (ActivityId::new(activity.id), builder(activity)))
+                    .collect();
+                (WorkerId::new(*worker), activities)
+            })
+            .collect()
+    }
+
+    /// CPU affinity and scheduling policy per worker thread and activity
+    pub fn thread_configs(&self) -> ThreadConfigs {
+        let workers = self
+            .workers
+            .iter()
+            .map(|worker| (WorkerId::new(worker.id), worker.thread.clone()))
+            .collect();
+        let activities = self
+            .activities
+            .iter()
+            .filter_map(|activity| Some((ActivityId::new(activity.id), activity.thread.clone()?)))
+            .collect();
+        ThreadConfigs { workers, activities }
+    }
+
+    /// Agent running each activity
+    pub fn activity_agent_map(&self) -> HashMap<ActivityId, AgentId> {
+        self.agents
+            .iter()
+            .flat_map(|agent| {
+                agent.workers.iter().flat_map(move |worker| {
+                    self.worker_activities(*worker)
+                        .map(move |activity| (activity, AgentId::new(agent.id)))
+                })
+            })
+            .collect()
+    }
+
+    /// Peers of the given topic, e.g. to create its [TopicSpecification](crate::topicspec::TopicSpecification)
+    pub fn topic_peers(&self, topic: &str) -> Vec<(ActivityId, Direction)> {
+        let Some(topic) = self.topics.iter().find(|t| t.name == topic) else {
+            return Vec::new();
+        };
+        let writers = topic
+            .writers
+            .iter()
+            .map(|id| (ActivityId::new(*id), Direction::Outgoing));
+        let readers = topic
+            .readers
+            .iter()
+            .map(|id| (ActivityId::new(*id), Direction::Incoming));
+        writers.chain(readers).collect()
+    }
+
+    /// IDs of the activities running on the given worker
+    fn worker_activities(&self, worker: u64) -> impl Iterator<Item = ActivityId> + '_ {
+        self.activities
+            .iter()
+            .filter(move |activity| activity.worker == worker)
+            .map(|activity| ActivityId::new(activity.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_is_parsed_and_validated() {
+        use crate::thread_config::SchedPolicy;
+
+        let toml = r#"
+            primary_agent = 100
+            cycle_time_ms = 50
+            clock = "monotonic_raw"
+            overrun = "skip_next"
+
+            [[agents]]
+            id = 100
+            workers = [40, 41]
+
+            [[agents]]
+            id = 101
+            workers = [42]
+            command = ["adas_secondary", "1"]
+
+            [[activities]]
+            id = 0
+            worker = 40
+
+            [[activities]]
+            id = 1
+            worker = 42
+            depends_on = [0]
+            starts_after = [0]
+            thread = { cpus = [1] }
+
+            [[workers]]
+            id = 42
+            thread = { cpus = [2], policy = "round_robin", priority = 10 }
+
+            [[topics]]
+            name = "feo/com/vehicle/camera"
+            writers = [0]
+            readers = [1]
+        "#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        assert_eq!(config.clock, ClockSource::MonotonicRaw);
+        assert_eq!(config.task_chains().overrun_policy_of(&MAIN_CHAIN), OverrunPolicy::SkipNext);
+        assert_eq!(config.secondary_agents(), [AgentId::new(101)]);
+        assert_eq!(
+            config.secondary_processes(),
+            [SecondaryProcess::new(AgentId::new(101), ["adas_secondary", "1"])]
+        );
+        assert_eq!(config.activity_agent_map()[&ActivityId::new(1)], AgentId::new(101));
+        assert_eq!(config.topic_peers("feo/com/vehicle/camera").len(), 2);
+        let thread_configs = config.thread_configs();
+        assert_eq!(
+            thread_configs.workers[&WorkerId::new(42)],
+            ThreadConfig::default()
+                .with_cpus([2])
+                .with_policy(SchedPolicy::RoundRobin, 10)
+        );
+        assert_eq!(thread_configs.activities[&ActivityId::new(1)].cpus, [1]);
+        assert_eq!(
+            config.startup_order().dependencies[&ActivityId::new(1)],
+            [ActivityId::new(0)]
+        );
+
+        let json = r#"{
+            "primary_agent": 100,
+            "cycle_time_ms": 50,
+            "agents": [{ "id": 100, "workers": [40] }],
+            "activities": [
+                { "id": 0, "worker": 40, "depends_on": [1] },
+                { "id": 1, "worker": 40, "depends_on": [0] }
+            ]
+        }"#;
+        assert!(matches!(
+            AppConfig::from_json(json),
+            Err(ConfigError::CyclicDependency(..))
+        ));
+
+        let unknown_worker = toml.replace("worker = 42", "worker = 43");
+        assert!(matches!(
+            AppConfig::from_toml(&unknown_worker),
+            Err(ConfigError::WorkerNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Disabling and re-enabling activities at runtime
+//!
+//! An [ActivityControl] obtained from the primary agent, or the `disable <id>` and `enable <id>`
+//! commands of the [introspection](crate::introspection) server, request individual activities to be
+//! disabled or re-enabled, e.g. to degrade a running system on purpose.
+//!
+//! Requests are applied by the scheduler at the next cycle boundary of the task chain of the activity,
+//! so a running cycle is never changed. A disabled activity is not stepped, but marked ready like a
+//! degraded activity. Each activity depending on a skipped activity is informed about its stale inputs
+//! with [Activity::input_stale](crate::activity::Activity::input_stale) right before its step.
+
+use crate::ids::ActivityId;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Handle requesting activities to be disabled or re-enabled, shared with the scheduler
+#[derive(Debug, Clone, Default)]
+pub struct ActivityControl {
+    /// Requested state per activity not applied yet, `true` meaning disabled
+    requests: Arc<Mutex<BTreeMap<ActivityId, bool>>>,
+}
+
+impl ActivityControl {
+    /// Create a handle without any requests
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the given activity to be disabled from the next cycle of its task chain on
+    pub fn disable(&self, id: ActivityId) {
+        self.request(id, true);
+    }
+
+    /// Request the given activity to be stepped again from the next cycle of its task chain on
+    pub fn enable(&self, id: ActivityId) {
+        self.request(id, false);
+    }
+
+    /// Take the pending requests of the given activities, `true` meaning disabled
+    ///
+    /// Requests of other activities stay pending.
+    pub(crate) fn take(&self, activities: &[ActivityId]) -> Vec<(ActivityId, bool)> {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        if requests.is_empty() {
+            return Vec::new();
+        }
+        activities
+            .iter()
+            .filter_map(|id| requests.remove(id).map(|disabled| (*id, disabled)))
+            .collect()
+    }
+
+    /// Take the pending requests of activities not contained in `known`
+    pub(crate) fn take_unknown(&self, known: impl Fn(&ActivityId) -> bool) -> Vec<ActivityId> {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        let unknown: Vec<ActivityId> = requests.keys().filter(|id| !known(id)).copied().collect();
+        for id in unknown.iter() {
+            requests.remove(id);
+        }
+        unknown
+    }
+
+    fn request(&self, id: ActivityId, disabled: bool) {
+        // A later request replaces an earlier one not applied yet
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, disabled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_taken_per_activity() {
+        let control = ActivityControl::new();
+        control.disable(ActivityId::from(1));
+        control.disable(ActivityId::from(2));
+        control.enable(ActivityId::from(2));
+        control.disable(ActivityId::from(7));
+
+        assert_eq!(
+            control.take(&[ActivityId::from(2), ActivityId::from(3)]),
+            alloc::vec![(ActivityId::from(2), false)]
+        );
+        assert_eq!(
+            control.take_unknown(|id| *id != ActivityId::from(7)),
+            alloc::vec![ActivityId::from(7)]
+        );
+        assert_eq!(
+            control.take(&[ActivityId::from(1), ActivityId::from(2)]),
+            alloc::vec![(ActivityId::from(1), true)]
+        );
+    }
+}
@@ -84,21 +84,21 @@ macro_rules! cpp_activity {
                     self.activity_id
                 }
 
-                #[instrument(name = "startup")]
+                #[instrument(name = "startup", skip(self), fields(activity_id = self.activity_id.id()))]
                 fn startup(&mut self) -> Result<(), ActivityError> {
                     // Safety: Call of external C functions belonging to C++ activitiy, to be reviewed
                     unsafe { make_fn_call!($name, _startup, (self.cpp_activity)) };
                     Ok(())
                 }
 
-                #[instrument(name = "step")]
+                #[instrument(name = "step", skip(self), fields(activity_id = self.activity_id.id()))]
                 fn step(&mut self) -> Result<(), ActivityError> {
                     // Safety: Call of external C functions belonging to C++ activitiy, to be reviewed
                     unsafe { make_fn_call!($name, _step, (self.cpp_activity)) };
                     Ok(())
                 }
 
-                #[instrument(name = "shutdown")]
+                #[instrument(name = "shutdown", skip(self), fields(activity_id = self.activity_id.id()))]
                 fn shutdown(&mut self) -> Result<(), ActivityError> {
                     // Safety: Call of external C functions belonging to C++ activitiy, to be reviewed
                     unsafe { make_fn_call!($name, _shutdown, (self.cpp_activity)) };
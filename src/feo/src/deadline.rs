@@ -0,0 +1,138 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Deadline monitoring of activity steps
+
+use crate::ids::ActivityId;
+use alloc::boxed::Box;
+use feo_time::Duration;
+use score_log::{error, ScoreDebug};
+use std::collections::HashMap;
+
+/// Handler called by the scheduler for each detected deadline overrun
+pub type OverrunHandler = Box<dyn FnMut(&DeadlineOverrun) + Send>;
+
+/// Step deadline configuration of the primary agent
+#[derive(Default)]
+pub struct StepDeadlines {
+    /// Maximum duration of a single step per activity.
+    ///
+    /// Activities without an entry are not monitored.
+    pub budgets: HashMap<ActivityId, Duration>,
+    /// Optional handler called for each detected overrun in addition to logging it
+    pub on_overrun: Option<OverrunHandler>,
+}
+
+impl StepDeadlines {
+    /// Create a deadline configuration from the given per-activity step budgets
+    pub fn new(budgets: HashMap<ActivityId, Duration>) -> Self {
+        Self {
+            budgets,
+            on_overrun: None,
+        }
+    }
+
+    /// Set the handler called for each detected overrun
+    pub fn with_overrun_handler(mut self, handler: impl FnMut(&DeadlineOverrun) + Send + 'static) -> Self {
+        self.on_overrun = Some(Box::new(handler));
+        self
+    }
+
+    /// Get the step budget of the given activity, if any
+    pub(crate) fn budget(&self, id: &ActivityId) -> Option<Duration> {
+        self.budgets.get(id).copied()
+    }
+
+    /// Log the given overrun and forward it to the overrun handler, if any
+    pub(crate) fn report(&mut self, overrun: DeadlineOverrun) {
+        if overrun.finished {
+            error!(
+                "Activity {} overran its step deadline in cycle {}: took {:?}, budget {:?}",
+                overrun.activity_id, overrun.cycle, overrun.elapsed, overrun.budget
+            );
+        } else {
+            error!(
+                "Activity {} overran its step deadline in cycle {}: still running after {:?}, budget {:?}",
+                overrun.activity_id, overrun.cycle, overrun.elapsed, overrun.budget
+            );
+        }
+
+        if let Some(handler) = self.on_overrun.as_mut() {
+            handler(&overrun);
+        }
+    }
+}
+
+/// Deadline overrun of a single activity step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScoreDebug)]
+pub struct DeadlineOverrun {
+    /// Number of the task chain cycle in which the overrun occurred, starting at zero
+    pub cycle: u64,
+    /// Activity which overran its deadline
+    pub activity_id: ActivityId,
+    /// Configured step budget of the activity
+    pub budget: Duration,
+    /// Time elapsed since the step was triggered when the overrun was detected
+    pub elapsed: Duration,
+    /// Whether the step had already finished when the overrun was detected.
+    ///
+    /// A hung activity is reported with `finished == false` as soon as its budget is exceeded.
+    /// If it finishes later in the same cycle, it is not reported again.
+    pub finished: bool,
+}
+
+/// Reaction of a secondary agent to an activity step exceeding its hard deadline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ScoreDebug)]
+pub enum HardDeadlineAction {
+    /// Report the step as failed and park the thread of the hung activity.
+    ///
+    /// The activity is not run anymore, all further signals to it are answered as failed.
+    #[default]
+    Park,
+    /// Report the step as failed and abort the process of the secondary agent
+    Abort,
+}
+
+/// Hard step deadlines enforced by a secondary agent
+///
+/// Each activity with a hard deadline is run on a thread of its own supervised by its worker,
+/// so that a hung step does not stall the worker and the task chain indefinitely.
+#[derive(Debug, Clone, Default)]
+pub struct HardDeadlines {
+    /// Maximum duration of a single step per activity.
+    ///
+    /// Activities without an entry are run on their worker thread without enforcement.
+    pub budgets: HashMap<ActivityId, Duration>,
+    /// Reaction to a step exceeding its hard deadline
+    pub action: HardDeadlineAction,
+}
+
+impl HardDeadlines {
+    /// Create a hard deadline configuration from the given per-activity step budgets
+    pub fn new(budgets: HashMap<ActivityId, Duration>, action: HardDeadlineAction) -> Self {
+        Self { budgets, action }
+    }
+
+    /// Get the hard deadline of the given activity, if any
+    pub(crate) fn budget(&self, id: &ActivityId) -> Option<Duration> {
+        self.budgets.get(id).copied()
+    }
+
+    /// Get the hard deadlines of the given activities only
+    pub(crate) fn for_activities(&self, ids: impl IntoIterator<Item = ActivityId>) -> Self {
+        Self {
+            budgets: ids.into_iter().filter_map(|id| Some((id, self.budget(&id)?))).collect(),
+            action: self.action,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Read-only debug topics of intermediate values
+//!
+//! Activities publish intermediate values of their steps with [debug_publish](crate::debug_publish),
+//! e.g. the residual of a lane fit, which would otherwise require a topic of its own:
+//!
+//! ```ignore
+//! debug_publish!("lane_fit/residual", residual);
+//! ```
+//!
+//! Each name is registered as a debug topic `feo/debug/<name>` on its first publication. Debug topics
+//! are no [feo_com] topics: they have no writers and readers in the topic graph of the application and
+//! keep the latest value only. They are consumed by
+//!
+//! - recorders configured with [Recorder::with_debug_topics](crate::recording::recorder::Recorder::with_debug_topics),
+//!   recording them like topics,
+//! - the `debug` command of the [introspection](crate::introspection) server, answering the latest values.
+//!
+//! Debug topics are disabled by default, so that the macro only checks a flag and does not evaluate the
+//! value. They are enabled with [set_enabled] at startup, usually from the `debug_topics` entry of the
+//! [AppConfig](crate::config::AppConfig), which is taken over on reloads as well.
+//!
+//! Values are kept in the process publishing them, so only the values published by the activities of the
+//! agent running the recorder or the introspection server are exposed.
+
+use crate::error::Error;
+use crate::ids::ActivityId;
+use crate::recording::codec::Encoding;
+use crate::recording::header::RecordedType;
+use crate::timestamp::timestamp;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use feo_time::Duration;
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+
+/// Prefix of the names of debug topics
+pub const DEBUG_TOPIC_PREFIX: &str = "feo/debug/";
+
+/// Whether debug topics are enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Debug topics of this process in the order of their registration
+static TOPICS: Mutex<Vec<DebugSample>> = Mutex::new(Vec::new());
+
+/// Publish an intermediate value of an activity on a debug topic, see [debug_topic](crate::debug_topic)
+///
+/// The value must implement `Serialize + Send + Sync + 'static` and is only evaluated while
+/// debug topics are enabled.
+#[macro_export]
+macro_rules! debug_publish {
+    ($name:expr, $value:expr) => {
+        if $crate::debug_topic::enabled() {
+            $crate::debug_topic::publish($name, $value);
+        }
+    };
+}
+
+/// Enable or disable debug topics
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether debug topics are enabled
+#[inline]
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Publish the given value on the debug topic of the given name, registering the topic if new
+///
+/// Use [debug_publish](crate::debug_publish) instead, which skips disabled debug topics.
+#[doc(hidden)]
+pub fn publish<T: Serialize + Send + Sync + 'static>(name: &str, value: T) {
+    let activity = crate::dlt::ActivityScope::current();
+    let timestamp = timestamp().0;
+    let value: Arc<dyn DebugValue> = Arc::new(value);
+    let mut topics = lock();
+    match topics.iter_mut().find(|sample| sample.name() == name) {
+        Some(sample) => {
+            sample.sequence += 1;
+            sample.activity = activity;
+            sample.timestamp = timestamp;
+            sample.value = value;
+        },
+        None => topics.push(DebugSample {
+            topic: format!("{DEBUG_TOPIC_PREFIX}{name}"),
+            sequence: 1,
+            activity,
+            timestamp,
+            value,
+        }),
+    }
+}
+
+/// Get the number of debug topics registered so far
+pub(crate) fn count() -> usize {
+    lock().len()
+}
+
+/// Get the latest sample of the debug topic with the given index in the order of registration
+pub(crate) fn sample(index: usize) -> Option<DebugSample> {
+    lock().get(index).cloned()
+}
+
+/// Get the latest samples of all debug topics
+pub(crate) fn samples() -> Vec<DebugSample> {
+    lock().clone()
+}
+
+fn lock() -> MutexGuard<'static, Vec<DebugSample>> {
+    TOPICS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Latest sample of a debug topic
+#[derive(Clone)]
+pub(crate) struct DebugSample {
+    /// Name of the debug topic, including the prefix
+    pub(crate) topic: String,
+    /// Number of samples published on the topic so far
+    pub(crate) sequence: u64,
+    /// Activity which published the sample, if published in the scope of an activity
+    pub(crate) activity: Option<ActivityId>,
+    /// Time since startup of the primary agent
+    pub(crate) timestamp: Duration,
+    /// Published value
+    pub(crate) value: Arc<dyn DebugValue>,
+}
+
+impl DebugSample {
+    /// Name of the debug topic without the prefix
+    fn name(&self) -> &str {
+        &self.topic[DEBUG_TOPIC_PREFIX.len()..]
+    }
+}
+
+impl fmt::Debug for DebugSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugSample")
+            .field("topic", &self.topic)
+            .field("sequence", &self.sequence)
+            .field("type_name", &self.value.type_name())
+            .finish()
+    }
+}
+
+/// Value of a debug topic with its type erased
+pub(crate) trait DebugValue: Send + Sync {
+    /// Get the name of the type of the value
+    fn type_name(&self) -> &'static str;
+
+    /// Get the type of the value as noted in recordings
+    fn recorded_type(&self) -> RecordedType;
+
+    /// Encode the value with the given encoding
+    fn encode(&self, encoding: Encoding) -> Result<Vec<u8>, Error>;
+
+    /// Get the value as JSON, `null` if it cannot be represented
+    fn to_json(&self) -> serde_json::Value;
+}
+
+impl<T: Serialize + Send + Sync + 'static> DebugValue for T {
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn recorded_type(&self) -> RecordedType {
+        RecordedType::of::<T>()
+    }
+
+    fn encode(&self, encoding: Encoding) -> Result<Vec<u8>, Error> {
+        encoding.encode(self)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_values_are_published() {
+        let residual = 0.25f32;
+        debug_publish!("test/residual", residual);
+        assert!(samples().iter().all(|sample| sample.topic != "feo/debug/test/residual"));
+
+        // Published directly, as enabling debug topics would affect the tests running in parallel
+        publish("test/residual", residual);
+        publish("test/residual", 0.5f32);
+        publish("test/inliers", [3u8, 4]);
+
+        let samples = samples();
+        let residual = samples
+            .iter()
+            .find(|sample| sample.topic == "feo/debug/test/residual")
+            .unwrap();
+        assert_eq!(residual.sequence, 2);
+        assert_eq!(residual.value.to_json(), serde_json::json!(0.5));
+        assert_eq!(residual.value.encode(Encoding::Postcard).unwrap(), postcard::to_allocvec(&0.5f32).unwrap());
+        let inliers = samples
+            .iter()
+            .find(|sample| sample.topic == "feo/debug/test/inliers")
+            .unwrap();
+        assert_eq!(inliers.value.to_json(), serde_json::json!([3, 4]));
+        assert_eq!(inliers.value.type_name(), "[u8; 2]");
+    }
+}
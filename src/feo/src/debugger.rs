@@ -0,0 +1,153 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Stepping through the cycles of the scheduler
+//!
+//! A [CycleDebugger] obtained from the primary agent, or the `pause`, `step [n]` and `continue`
+//! commands of the [introspection](crate::introspection) server, stop the scheduler before the start
+//! of the next cycle, so developers can single-step the task chains and inspect the samples of the
+//! topics between cycles. Pausing the debugger before running the primary agent stops the scheduler
+//! before the first cycle.
+//!
+//! While paused, cycles already running are finished, but no further cycle is started. Once all
+//! task chains are idle, the FEO clock of the primary agent is paused with [feo_time::pause], so no
+//! cycle is overdue and no timeout expires until the scheduler continues. The clocks of the secondary
+//! agents keep running. Each cycle of a task chain counts as one step.
+
+use alloc::sync::Arc;
+use core::time::Duration;
+use score_log::info;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Handle pausing and stepping the cycles of the scheduler, shared with the scheduler
+#[derive(Debug, Clone, Default)]
+pub struct CycleDebugger(Arc<DebuggerState>);
+
+#[derive(Debug, Default)]
+struct DebuggerState {
+    state: Mutex<Stepping>,
+    /// Notified on each command
+    command: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Stepping {
+    /// Number of cycles to be started before pausing, none while running freely
+    steps_left: Option<u64>,
+    /// Whether the scheduler paused the clock while waiting for a command
+    clock_paused: bool,
+}
+
+impl CycleDebugger {
+    /// Create a handle of a freely running scheduler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause the scheduler before the start of the next cycle
+    pub fn pause(&self) {
+        self.command(Some(0));
+    }
+
+    /// Start the given number of cycles, then pause again
+    pub fn step(&self, cycles: u64) {
+        self.command(Some(cycles));
+    }
+
+    /// Continue to cycle freely
+    pub fn resume(&self) {
+        self.command(None);
+    }
+
+    /// Whether the scheduler is paused, or will pause before the next cycle
+    pub fn is_paused(&self) -> bool {
+        self.lock().steps_left == Some(0)
+    }
+
+    /// Take a step before the start of a cycle, returning whether the cycle may be started
+    ///
+    /// Resumes the clock paused while waiting for the step.
+    pub(crate) fn take_step(&self) -> bool {
+        let mut state = self.lock();
+        match state.steps_left {
+            Some(0) => return false,
+            Some(steps_left) => state.steps_left = Some(steps_left - 1),
+            None => {},
+        }
+        if state.clock_paused {
+            state.clock_paused = false;
+            feo_time::resume();
+        }
+        true
+    }
+
+    /// Wait for a command, at most for the given OS time, pausing the clock if still paused
+    ///
+    /// Must be called while all task chains are idle.
+    pub(crate) fn wait(&self, timeout: Duration) {
+        let mut state = self.lock();
+        if state.steps_left != Some(0) {
+            return;
+        }
+        if !state.clock_paused {
+            info!("Scheduler paused, waiting for a step or continue command");
+            state.clock_paused = true;
+            feo_time::pause();
+        }
+        let _ = self.0.command.wait_timeout(state, timeout);
+    }
+
+    /// Resume the clock paused while waiting for a command, e.g. before shutting down
+    pub(crate) fn release_clock(&self) {
+        let mut state = self.lock();
+        if state.clock_paused {
+            state.clock_paused = false;
+            feo_time::resume();
+        }
+    }
+
+    fn command(&self, steps_left: Option<u64>) {
+        self.lock().steps_left = steps_left;
+        self.0.command.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Stepping> {
+        self.0.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_taken_until_paused() {
+        let debugger = CycleDebugger::new();
+        assert!(debugger.take_step());
+        assert!(!debugger.is_paused());
+
+        debugger.pause();
+        assert!(debugger.is_paused());
+        assert!(!debugger.take_step());
+
+        debugger.step(2);
+        assert!(!debugger.is_paused());
+        assert!(debugger.take_step());
+        assert!(debugger.take_step());
+        assert!(debugger.is_paused());
+        assert!(!debugger.take_step());
+
+        debugger.resume();
+        assert!((0..5).all(|_| debugger.take_step()));
+    }
+}
@@ -0,0 +1,300 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Validation of the activity dependency graph
+//!
+//! A misconfigured dependency graph does not fail on its own, but stalls the scheduler:
+//! activities in a dependency cycle or depending on an unknown activity are never stepped,
+//! and activities reading a topic nobody writes wait for data forever.
+//! [validate] checks the declared dependencies, and optionally the topics connecting
+//! the activities, up front and reports all offending activities at once.
+
+use crate::ids::ActivityId;
+use crate::topicspec::{Direction, TopicSpecification};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use score_log::fmt::{DebugSet, FormatSpec, ScoreDebug, ScoreWrite};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Misconfiguration of the activity dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyIssue {
+    /// The activities form a dependency cycle, each depending on the next and the last on the first
+    Cycle(Vec<ActivityId>),
+    /// An activity depends on an activity which is not part of the application
+    UnknownDependency {
+        activity: ActivityId,
+        dependency: ActivityId,
+    },
+    /// An activity reads a topic which is not written by any activity
+    UnconnectedInput { topic: String, activity: ActivityId },
+    /// A single-writer topic is written by more than one activity
+    DuplicateWriters { topic: String, writers: Vec<ActivityId> },
+}
+
+impl fmt::Display for DependencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyIssue::Cycle(activities) => {
+                write!(f, "dependency cycle ")?;
+                for activity in activities.iter() {
+                    write!(f, "{activity} -> ")?;
+                }
+                write!(f, "{}", activities[0])
+            },
+            DependencyIssue::UnknownDependency { activity, dependency } => {
+                write!(f, "activity {activity} depends on unknown activity {dependency}")
+            },
+            DependencyIssue::UnconnectedInput { topic, activity } => {
+                write!(f, "activity {activity} reads topic {topic} which has no writer")
+            },
+            DependencyIssue::DuplicateWriters { topic, writers } => {
+                write!(f, "single-writer topic {topic} is written by activities")?;
+                for writer in writers.iter() {
+                    write!(f, " {writer}")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl ScoreDebug for DependencyIssue {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        match self {
+            DependencyIssue::Cycle(activities) => {
+                f.write_str("Cycle", spec)?;
+                DebugSet::new(f, spec).entries(activities.iter()).finish()
+            },
+            DependencyIssue::UnknownDependency { activity, dependency } => {
+                f.write_str("UnknownDependency", spec)?;
+                DebugSet::new(f, spec).entries([activity, dependency]).finish()
+            },
+            DependencyIssue::UnconnectedInput { topic, activity } => {
+                f.write_str("UnconnectedInput(", spec)?;
+                f.write_str(topic, spec)?;
+                f.write_str(")", spec)?;
+                DebugSet::new(f, spec).entries([activity]).finish()
+            },
+            DependencyIssue::DuplicateWriters { topic, writers } => {
+                f.write_str("DuplicateWriters(", spec)?;
+                f.write_str(topic, spec)?;
+                f.write_str(")", spec)?;
+                DebugSet::new(f, spec).entries(writers.iter()).finish()
+            },
+        }
+    }
+}
+
+/// All misconfigurations found in an activity dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyErrors {
+    pub issues: Vec<DependencyIssue>,
+}
+
+impl fmt::Display for DependencyErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, issue) in self.issues.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ScoreDebug for DependencyErrors {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        DebugSet::new(f, spec).entries(self.issues.iter()).finish()
+    }
+}
+
+/// Check the activity dependencies and the topics connecting the activities
+///
+/// Pass an empty slice of topics to check the dependencies only.
+pub fn validate(
+    activity_depends: &HashMap<ActivityId, Vec<ActivityId>>,
+    topics: &[TopicSpecification<'_>],
+) -> Result<(), DependencyErrors> {
+    // Sort the graph for a deterministic order of the reported issues
+    let graph: BTreeMap<ActivityId, BTreeSet<ActivityId>> = activity_depends
+        .iter()
+        .map(|(id, dependencies)| (*id, dependencies.iter().copied().collect()))
+        .collect();
+
+    let mut issues = Vec::new();
+    for (activity, dependencies) in graph.iter() {
+        for dependency in dependencies.iter().filter(|dep| !graph.contains_key(dep)) {
+            issues.push(DependencyIssue::UnknownDependency {
+                activity: *activity,
+                dependency: *dependency,
+            });
+        }
+    }
+    issues.extend(find_cycles(&graph).into_iter().map(DependencyIssue::Cycle));
+
+    for topic in topics.iter() {
+        check_topic(topic.topic, &topic.peers, topic.multiple_writers, &mut issues);
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(DependencyErrors { issues })
+    }
+}
+
+/// Check that a topic is written and, unless it allows multiple writers, written by a single activity
+fn check_topic(
+    topic: &str,
+    peers: &[(ActivityId, Direction)],
+    multiple_writers: bool,
+    issues: &mut Vec<DependencyIssue>,
+) {
+    let writers: Vec<ActivityId> = peers
+        .iter()
+        .filter(|(_, direction)| *direction == Direction::Outgoing)
+        .map(|(id, _)| *id)
+        .collect();
+    if writers.is_empty() {
+        issues.extend(
+            peers
+                .iter()
+                .filter(|(_, direction)| *direction == Direction::Incoming)
+                .map(|(activity, _)| DependencyIssue::UnconnectedInput {
+                    topic: topic.to_string(),
+                    activity: *activity,
+                }),
+        );
+    } else if writers.len() > 1 && !multiple_writers {
+        issues.push(DependencyIssue::DuplicateWriters {
+            topic: topic.to_string(),
+            writers,
+        });
+    }
+}
+
+/// Visiting state of an activity during the search for cycles
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    /// The activity is on the current search path
+    Active,
+    /// All dependencies of the activity have been searched
+    Done,
+}
+
+/// Find the dependency cycles of the graph
+///
+/// Each cycle is reported once, starting with its smallest activity id.
+fn find_cycles(graph: &BTreeMap<ActivityId, BTreeSet<ActivityId>>) -> Vec<Vec<ActivityId>> {
+    let mut visits = HashMap::new();
+    let mut path = Vec::new();
+    let mut cycles = Vec::new();
+    for activity in graph.keys() {
+        search_cycles(*activity, graph, &mut visits, &mut path, &mut cycles);
+    }
+    cycles
+}
+
+/// Depth-first search for cycles through the dependencies of `activity`
+fn search_cycles(
+    activity: ActivityId,
+    graph: &BTreeMap<ActivityId, BTreeSet<ActivityId>>,
+    visits: &mut HashMap<ActivityId, Visit>,
+    path: &mut Vec<ActivityId>,
+    cycles: &mut Vec<Vec<ActivityId>>,
+) {
+    match visits.get(&activity) {
+        Some(Visit::Done) => return,
+        Some(Visit::Active) => {
+            // Found a dependency back to an activity on the current path
+            let start = path
+                .iter()
+                .position(|id| *id == activity)
+                .expect("active activity not on path");
+            let mut cycle = path[start..].to_vec();
+            let smallest = cycle
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, id)| **id)
+                .map_or(0, |(i, _)| i);
+            cycle.rotate_left(smallest);
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+            return;
+        },
+        None => (),
+    }
+
+    visits.insert(activity, Visit::Active);
+    path.push(activity);
+    for dependency in graph.get(&activity).into_iter().flatten() {
+        search_cycles(*dependency, graph, visits, path, cycles);
+    }
+    path.pop();
+    visits.insert(activity, Visit::Done);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn misconfigured_dependencies_are_reported() {
+        use alloc::vec;
+
+        let id = ActivityId::new;
+        let deps = HashMap::from([(id(0), vec![]), (id(1), vec![id(0)]), (id(2), vec![id(0), id(1)])]);
+        assert_eq!(validate(&deps, &[]), Ok(()));
+
+        let deps = HashMap::from([
+            (id(0), vec![id(2)]),
+            (id(1), vec![id(0)]),
+            (id(2), vec![id(1)]),
+            (id(3), vec![id(4)]),
+        ]);
+        let errors = validate(&deps, &[]).unwrap_err();
+        assert_eq!(
+            errors.issues,
+            [
+                DependencyIssue::UnknownDependency {
+                    activity: id(3),
+                    dependency: id(4)
+                },
+                DependencyIssue::Cycle(vec![id(0), id(2), id(1)]),
+            ]
+        );
+
+        let writers = [(id(0), Direction::Outgoing), (id(1), Direction::Outgoing)];
+        let mut issues = Vec::new();
+        check_topic("a", &writers, false, &mut issues);
+        check_topic("b", &[(id(2), Direction::Incoming)], false, &mut issues);
+        check_topic("c", &writers, true, &mut issues);
+        assert_eq!(
+            issues,
+            [
+                DependencyIssue::DuplicateWriters {
+                    topic: "a".into(),
+                    writers: vec![id(0), id(1)]
+                },
+                DependencyIssue::UnconnectedInput {
+                    topic: "b".into(),
+                    activity: id(2)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Discovery of the endpoints of the primary agent
+//!
+//! Instead of hardcoding the socket paths or addresses of the primary agent, agents look them up with
+//! [PrimaryEndpoints::lookup], so that deployments can move them without rebuilding the agents.
+//! Each endpoint is taken from the first of
+//!
+//! 1. the environment variables `FEO_PRIMARY_ENDPOINT` and `FEO_PRIMARY_RECEIVERS`,
+//! 2. the discovery file given by the environment variable `FEO_DISCOVERY_FILE`,
+//! 3. the default passed by the agent.
+//!
+//! Endpoints are written as `tcp:<address>:<port>`, `unix:<path>`, `shm:<path>` or `mwcom`.
+//! The discovery file is in TOML format:
+//!
+//! ```toml
+//! # Endpoint of direct signalling, or of the senders of relayed signalling
+//! endpoint = "unix:/run/feo/primary.sock"
+//! # Endpoint of the receivers of relayed signalling
+//! receivers = "unix:/run/feo/primary_receivers.sock"
+//! ```
+//!
+//! The primary agent looks up the endpoints it binds to the same way, so that all agents of
+//! a deployment share a single discovery file.
+
+use crate::agent::NodeAddress;
+use crate::debug_fmt::ScoreDebugDebug;
+use crate::error::{ConnectionError, Error};
+use alloc::string::String;
+use feo_tracing::ScoreDebugIoError;
+use score_log::debug;
+use serde::Deserialize;
+use std::env;
+use std::path::Path;
+
+/// Environment variable with the endpoint of direct signalling, or of the senders of relayed signalling
+pub const ENDPOINT_VAR: &str = "FEO_PRIMARY_ENDPOINT";
+
+/// Environment variable with the endpoint of the receivers of relayed signalling
+pub const RECEIVERS_VAR: &str = "FEO_PRIMARY_RECEIVERS";
+
+/// Environment variable with the path of the discovery file
+pub const DISCOVERY_FILE_VAR: &str = "FEO_DISCOVERY_FILE";
+
+/// Endpoints of the primary agent
+#[derive(Debug, Clone, Default)]
+pub struct PrimaryEndpoints {
+    /// Endpoint of direct signalling, or of the senders of relayed signalling
+    pub endpoint: Option<NodeAddress>,
+    /// Endpoint of the receivers of relayed signalling
+    pub receivers: Option<NodeAddress>,
+}
+
+/// Content of a discovery file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DiscoveryFile {
+    endpoint: Option<String>,
+    receivers: Option<String>,
+}
+
+impl PrimaryEndpoints {
+    /// Look up the endpoints in the environment and the discovery file, if any
+    pub fn lookup() -> Result<Self, Error> {
+        let from_env = Self::from_env()?;
+        let from_file = match env::var_os(DISCOVERY_FILE_VAR) {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        let endpoints = Self {
+            endpoint: from_env.endpoint.or(from_file.endpoint),
+            receivers: from_env.receivers.or(from_file.receivers),
+        };
+        debug!(
+            "Discovered primary endpoints {:?}",
+            ScoreDebugDebug::<_, 256>(&endpoints)
+        );
+        Ok(endpoints)
+    }
+
+    /// Read the endpoints from the environment variables
+    pub fn from_env() -> Result<Self, Error> {
+        let var = |name| env::var(name).ok().map(|value| value.parse()).transpose();
+        Ok(Self {
+            endpoint: var(ENDPOINT_VAR)?,
+            receivers: var(RECEIVERS_VAR)?,
+        })
+    }
+
+    /// Load the endpoints from a discovery file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to read discovery file")))?;
+        Self::from_toml(&content)
+    }
+
+    /// Parse the endpoints from the content of a discovery file
+    pub fn from_toml(content: &str) -> Result<Self, Error> {
+        let file: DiscoveryFile = toml::from_str(content)
+            .map_err(|_| Error::Connection(ConnectionError::Discovery("failed to parse discovery file")))?;
+        Ok(Self {
+            endpoint: file.endpoint.as_deref().map(str::parse).transpose()?,
+            receivers: file.receivers.as_deref().map(str::parse).transpose()?,
+        })
+    }
+
+    /// Get the discovered endpoint or the given default
+    pub fn endpoint_or(&self, default: NodeAddress) -> NodeAddress {
+        self.endpoint.clone().unwrap_or(default)
+    }
+
+    /// Get the discovered endpoint of the receivers or the given default
+    pub fn receivers_or(&self, default: NodeAddress) -> NodeAddress {
+        self.receivers.clone().unwrap_or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_are_parsed_from_discovery_file() {
+        use std::path::PathBuf;
+
+        let endpoints = PrimaryEndpoints::from_toml(
+            r#"
+            endpoint = "tcp:192.168.1.10:8081"
+            receivers = "unix:/run/feo/receivers.sock"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(endpoints.endpoint, Some(NodeAddress::Tcp(addr)) if addr.port() == 8081));
+        assert!(matches!(
+            endpoints.receivers,
+            Some(NodeAddress::UnixSocket(path)) if path == PathBuf::from("/run/feo/receivers.sock")
+        ));
+
+        let endpoints = PrimaryEndpoints::from_toml("").unwrap();
+        assert!(matches!(endpoints.endpoint_or(NodeAddress::MwCom), NodeAddress::MwCom));
+        assert!(PrimaryEndpoints::from_toml(r#"endpoint = "udp:localhost""#).is_err());
+    }
+}
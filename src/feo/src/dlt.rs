@@ -0,0 +1,532 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Logging to AUTOSAR DLT
+//!
+//! Vehicle logging collects the logs of all ECUs with the AUTOSAR Diagnostic Log and Trace (DLT)
+//! infrastructure. A [DltLogger] installed as the logger of an agent with [DltLogger::install] sends
+//! the records of `score_log` to the DLT daemon of the ECU via its application socket, as verbose log
+//! messages with the text of the record as single string argument.
+//!
+//! Each agent logs as a DLT application and each activity as a DLT context of it, as configured in a
+//! [DltConfig]: records logged by a worker while running the startup, step or shutdown of an activity
+//! are logged in the context of the activity, all other records in the context [DEFAULT_CONTEXT].
+//! Agents and activities without configured ID get an ID derived from their numeric ID, e.g. `A12` for
+//! activity 12.
+//!
+//! Trace events of selected targets, e.g. `feo::scheduler`, are logged as well, in the context
+//! [TRACE_CONTEXT]. They are taken from the tracing of the agent, which must be initialized,
+//! see [feo_tracing::tap].
+//!
+//! Messages logged while the daemon is not reachable are dropped. The logger reconnects at most once
+//! per [RECONNECT_INTERVAL].
+
+use crate::ids::{ActivityId, AgentId};
+use crate::log_relay::TextBuffer;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt::{self, Write as _};
+use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use core::time::Duration;
+use feo_tracing::tracing::field::{Field, Visit};
+use feo_tracing::tracing::Event;
+use score_log::{warn, Level, LevelFilter, Log, Metadata, Record};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Default path of the application socket of the DLT daemon
+pub const DLT_SOCKET_PATH: &str = "/tmp/dlt";
+
+/// Context of the records logged outside of activities
+pub const DEFAULT_CONTEXT: DltId = DltId(*b"FEO\0");
+
+/// Context of the forwarded trace events
+pub const TRACE_CONTEXT: DltId = DltId(*b"TRC\0");
+
+/// Interval between two attempts to (re)connect to the DLT daemon
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pattern starting each message to the DLT daemon
+const USER_HEADER_PATTERN: [u8; 4] = *b"DUH\x01";
+
+/// Types of the messages to the DLT daemon
+const USER_MESSAGE_LOG: u32 = 1;
+const USER_MESSAGE_REGISTER_APPLICATION: u32 = 2;
+const USER_MESSAGE_REGISTER_CONTEXT: u32 = 4;
+
+/// Flags of the standard header: extended header, ECU ID, session ID and timestamp, protocol version 1
+const HEADER_TYPE: u8 = 0x01 | 0x04 | 0x08 | 0x10 | 0x20;
+
+/// Type info of a UTF-8 string argument
+const TYPE_INFO_UTF8_STRING: u32 = 0x0000_0200 | 0x0000_8000;
+
+std::thread_local! {
+    /// Activity run by this thread, if any
+    static CURRENT_ACTIVITY: Cell<Option<ActivityId>> = const { Cell::new(None) };
+}
+
+/// Application or context ID of DLT, up to four ASCII characters
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DltId([u8; 4]);
+
+impl DltId {
+    /// Create an ID from the first four bytes of the given ASCII string, padded with zeros
+    pub fn new(id: &str) -> Self {
+        let mut bytes = [0; 4];
+        for (byte, c) in bytes.iter_mut().zip(id.bytes()) {
+            *byte = c;
+        }
+        Self(bytes)
+    }
+
+    /// Create an ID from the given prefix and number, truncated to four characters
+    fn numbered(prefix: char, number: u64) -> Self {
+        Self::new(&format!("{prefix}{number}"))
+    }
+
+    /// Get the bytes of the ID
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for DltId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DltId({self})")
+    }
+}
+
+impl fmt::Display for DltId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter().take_while(|byte| **byte != 0) {
+            f.write_char(char::from(*byte))?;
+        }
+        Ok(())
+    }
+}
+
+/// Configuration of a [DltLogger]
+#[derive(Debug, Clone)]
+pub struct DltConfig {
+    /// Path of the application socket of the DLT daemon
+    pub socket: PathBuf,
+    /// ID of the ECU
+    pub ecu: DltId,
+    /// Maximum level of the logged records
+    pub level: LevelFilter,
+    /// Application IDs of the agents, `F<agent ID>` if not set
+    pub agents: HashMap<AgentId, DltId>,
+    /// Context IDs of the activities, `A<activity ID>` if not set
+    pub activities: HashMap<ActivityId, DltId>,
+    /// Prefixes of the targets of the trace events to log
+    pub trace_targets: Vec<String>,
+}
+
+impl Default for DltConfig {
+    fn default() -> Self {
+        Self {
+            socket: PathBuf::from(DLT_SOCKET_PATH),
+            ecu: DltId::new("ECU1"),
+            level: LevelFilter::Info,
+            agents: HashMap::new(),
+            activities: HashMap::new(),
+            trace_targets: Vec::new(),
+        }
+    }
+}
+
+impl DltConfig {
+    /// Create a configuration logging up to info level to the default socket of the daemon
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log to the daemon listening on the given socket
+    pub fn with_socket(mut self, socket: impl Into<PathBuf>) -> Self {
+        self.socket = socket.into();
+        self
+    }
+
+    /// Log with the given ECU ID
+    pub fn with_ecu(mut self, ecu: DltId) -> Self {
+        self.ecu = ecu;
+        self
+    }
+
+    /// Log the records up to the given level
+    pub fn with_level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Log the records of the given agent with the given application ID
+    pub fn with_agent(mut self, agent: AgentId, app: DltId) -> Self {
+        self.agents.insert(agent, app);
+        self
+    }
+
+    /// Log the records of the given activity with the given context ID
+    pub fn with_activity(mut self, activity: ActivityId, context: DltId) -> Self {
+        self.activities.insert(activity, context);
+        self
+    }
+
+    /// Log the trace events of the targets starting with the given prefix
+    pub fn with_trace_target(mut self, prefix: &str) -> Self {
+        self.trace_targets.push(prefix.into());
+        self
+    }
+
+    /// Get the application ID of the given agent
+    pub fn app_id(&self, agent: AgentId) -> DltId {
+        self.agents
+            .get(&agent)
+            .copied()
+            .unwrap_or_else(|| DltId::numbered('F', agent.id()))
+    }
+
+    /// Get the context ID of the given activity
+    pub fn context_id(&self, activity: ActivityId) -> DltId {
+        self.activities
+            .get(&activity)
+            .copied()
+            .unwrap_or_else(|| DltId::numbered('A', activity.id()))
+    }
+}
+
+/// Logger sending log records to the DLT daemon
+///
+/// Clones share the same connection.
+#[derive(Debug, Clone)]
+pub struct DltLogger(Arc<LoggerInner>);
+
+#[derive(Debug)]
+struct LoggerInner {
+    config: DltConfig,
+    /// Application ID of the agent
+    app: DltId,
+    /// Start of the timestamps of the messages
+    start: Instant,
+    connection: Mutex<Connection>,
+    /// Message counter, wrapping around
+    counter: AtomicU8,
+    /// Number of messages dropped because the daemon was not reachable
+    dropped: AtomicU64,
+}
+
+/// Connection to the DLT daemon
+#[derive(Debug, Default)]
+struct Connection {
+    stream: Option<UnixStream>,
+    /// Time of the last attempt to connect
+    last_attempt: Option<Instant>,
+    /// Contexts registered with the daemon on the current connection
+    registered: HashSet<DltId>,
+}
+
+impl DltLogger {
+    /// Create a logger for the records of the given agent
+    pub fn new(agent: AgentId, config: DltConfig) -> Self {
+        Self(Arc::new(LoggerInner {
+            app: config.app_id(agent),
+            config,
+            start: Instant::now(),
+            connection: Mutex::new(Connection::default()),
+            counter: AtomicU8::new(0),
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Install a clone of this logger as the logger of this process
+    ///
+    /// Also forwards the trace events of the configured targets, if any.
+    pub fn install(&self) {
+        score_log::set_global_logger(Box::new(self.clone()));
+        score_log::set_max_level(self.0.config.level);
+        if !self.0.config.trace_targets.is_empty() {
+            let logger = self.clone();
+            if !feo_tracing::tap::set_event_tap(Box::new(move |event| logger.trace_event(event))) {
+                warn!("Trace events not logged to DLT, another tap is installed");
+            }
+        }
+    }
+
+    /// Number of messages dropped so far because the DLT daemon was not reachable
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Log a trace event of a selected target
+    fn trace_event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let target = metadata.target();
+        if !self.0.config.trace_targets.iter().any(|prefix| target.starts_with(prefix.as_str())) {
+            return;
+        }
+        let mut text = TextBuffer::default();
+        let _ = write!(text, "{}", metadata.name());
+        event.record(&mut FieldWriter(&mut text));
+        let level = match *metadata.level() {
+            feo_tracing::Level::ERROR => Level::Error,
+            feo_tracing::Level::WARN => Level::Warn,
+            feo_tracing::Level::INFO => Level::Info,
+            feo_tracing::Level::DEBUG => Level::Debug,
+            _ => Level::Trace,
+        };
+        self.send(TRACE_CONTEXT, level, text.as_str());
+    }
+
+    /// Send a log message in the given context, dropping it if the daemon is not reachable
+    ///
+    /// Does not log itself to avoid feedback loops.
+    fn send(&self, context: DltId, level: Level, text: &str) {
+        let inner = &self.0;
+        let mut connection = inner.connection.lock().unwrap();
+        if connection.stream.is_none() && !connection.connect(&inner.config, inner.app) {
+            inner.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let timestamp = inner.start.elapsed();
+        let message = LogMessage {
+            ecu: inner.config.ecu,
+            app: inner.app,
+            context,
+            level,
+            counter: inner.counter.fetch_add(1, Ordering::Relaxed),
+            session: std::process::id(),
+            timestamp: (timestamp.as_micros() / 100) as u32,
+            text,
+        };
+        let registered = connection.registered.contains(&context)
+            || connection.write(&register_context(inner.app, context)).is_ok();
+        if registered {
+            connection.registered.insert(context);
+        }
+        if !registered || connection.write(&message.encode()).is_err() {
+            inner.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Connection {
+    /// Connect to the daemon and register the application, unless attempted recently
+    fn connect(&mut self, config: &DltConfig, app: DltId) -> bool {
+        if self
+            .last_attempt
+            .is_some_and(|last_attempt| last_attempt.elapsed() < RECONNECT_INTERVAL)
+        {
+            return false;
+        }
+        self.last_attempt = Some(Instant::now());
+        let Ok(stream) = UnixStream::connect(&config.socket) else {
+            return false;
+        };
+        self.stream = Some(stream);
+        self.registered.clear();
+        self.write(&register_application(app)).is_ok()
+    }
+
+    /// Write a message to the daemon, closing the connection on failure
+    fn write(&mut self, message: &[u8]) -> Result<(), ()> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Err(());
+        };
+        if stream.write_all(message).is_err() {
+            self.stream = None;
+            return Err(());
+        }
+        Ok(())
+    }
+}
+
+impl Log for DltLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.0.config.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut text = TextBuffer::default();
+        // Formatting errors only occur if the text is truncated
+        let _ = score_log::fmt::write(&mut text, *record.args());
+        let context = match CURRENT_ACTIVITY.with(Cell::get) {
+            Some(activity) => self.0.config.context_id(activity),
+            None => DEFAULT_CONTEXT,
+        };
+        self.send(context, record.level(), text.as_str());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Scope of a thread running an activity, logging in the context of the activity until dropped
+pub(crate) struct ActivityScope(Option<ActivityId>);
+
+impl ActivityScope {
+    /// Enter the scope of the given activity
+    pub(crate) fn enter(activity: ActivityId) -> Self {
+        Self(CURRENT_ACTIVITY.with(|current| current.replace(Some(activity))))
+    }
+
+    /// Get the activity whose scope the current thread is in, if any
+    pub(crate) fn current() -> Option<ActivityId> {
+        CURRENT_ACTIVITY.with(Cell::get)
+    }
+}
+
+impl Drop for ActivityScope {
+    fn drop(&mut self) {
+        CURRENT_ACTIVITY.with(|current| current.set(self.0));
+    }
+}
+
+/// Writer of the fields of a trace event into a log text
+struct FieldWriter<'a>(&'a mut TextBuffer);
+
+impl Visit for FieldWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={}", field.name(), value);
+    }
+}
+
+/// Log message of a single string argument
+struct LogMessage<'a> {
+    ecu: DltId,
+    app: DltId,
+    context: DltId,
+    level: Level,
+    counter: u8,
+    session: u32,
+    /// Time since the start of the logger in 0.1 ms
+    timestamp: u32,
+    text: &'a str,
+}
+
+impl LogMessage<'_> {
+    /// Encode the message as sent to the daemon
+    fn encode(&self) -> Vec<u8> {
+        // Standard header with ECU ID, session ID and timestamp, extended header and the argument
+        let argument_len = 4 + 2 + self.text.len() + 1;
+        let len = 4 + 12 + 10 + argument_len;
+        let mut message = user_header(USER_MESSAGE_LOG);
+        message.reserve(len);
+        message.push(HEADER_TYPE);
+        message.push(self.counter);
+        message.extend_from_slice(&(len as u16).to_be_bytes());
+        message.extend_from_slice(self.ecu.as_bytes());
+        message.extend_from_slice(&self.session.to_be_bytes());
+        message.extend_from_slice(&self.timestamp.to_be_bytes());
+        // Verbose log message of the level with a single argument
+        message.push((dlt_level(self.level) << 4) | 0x01);
+        message.push(1);
+        message.extend_from_slice(self.app.as_bytes());
+        message.extend_from_slice(self.context.as_bytes());
+        message.extend_from_slice(&TYPE_INFO_UTF8_STRING.to_le_bytes());
+        message.extend_from_slice(&((self.text.len() + 1) as u16).to_le_bytes());
+        message.extend_from_slice(self.text.as_bytes());
+        message.push(0);
+        message
+    }
+}
+
+/// Level of DLT for the given level of a record
+fn dlt_level(level: Level) -> u8 {
+    match level {
+        Level::Error => 2,
+        Level::Warn => 3,
+        Level::Info => 4,
+        Level::Debug => 5,
+        Level::Trace => 6,
+    }
+}
+
+/// Header of a message to the daemon of the given type
+fn user_header(message_type: u32) -> Vec<u8> {
+    let mut message = Vec::from(USER_HEADER_PATTERN);
+    message.extend_from_slice(&message_type.to_ne_bytes());
+    message
+}
+
+/// Message registering the application of this process without description
+fn register_application(app: DltId) -> Vec<u8> {
+    let mut message = user_header(USER_MESSAGE_REGISTER_APPLICATION);
+    message.extend_from_slice(app.as_bytes());
+    message.extend_from_slice(&(std::process::id() as i32).to_ne_bytes());
+    message.extend_from_slice(&0u32.to_ne_bytes());
+    message
+}
+
+/// Message registering a context of the application without description, with default level and trace status
+fn register_context(app: DltId, context: DltId) -> Vec<u8> {
+    let mut message = user_header(USER_MESSAGE_REGISTER_CONTEXT);
+    message.extend_from_slice(app.as_bytes());
+    message.extend_from_slice(context.as_bytes());
+    // Position of the log level in the shared table, default log level and trace status
+    message.extend_from_slice(&0i32.to_ne_bytes());
+    message.extend_from_slice(&[-1i8 as u8, -1i8 as u8]);
+    message.extend_from_slice(&(std::process::id() as i32).to_ne_bytes());
+    message.extend_from_slice(&0u32.to_ne_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_message_is_encoded() {
+        let config = DltConfig::new().with_activity(ActivityId::new(3), DltId::new("CAM"));
+        assert_eq!(config.app_id(AgentId::new(100)).as_bytes(), b"F100");
+        assert_eq!(config.context_id(ActivityId::new(3)).as_bytes(), b"CAM\0");
+        assert_eq!(config.context_id(ActivityId::new(12)).as_bytes(), b"A12\0");
+
+        let message = LogMessage {
+            ecu: config.ecu,
+            app: DltId::new("F100"),
+            context: DltId::new("CAM"),
+            level: Level::Warn,
+            counter: 7,
+            session: 42,
+            timestamp: 10,
+            text: "hi",
+        }
+        .encode();
+        let (header, dlt) = message.split_at(8);
+        assert_eq!(&header[..4], b"DUH\x01");
+        // Standard header, 16 bytes
+        assert_eq!(&dlt[..4], &[HEADER_TYPE, 7, 0, 35]);
+        assert_eq!(&dlt[4..8], b"ECU1");
+        assert_eq!(&dlt[8..12], &42u32.to_be_bytes());
+        // Extended header, 10 bytes
+        assert_eq!(&dlt[16..18], &[0x31, 1]);
+        assert_eq!(&dlt[18..26], b"F100CAM\0");
+        // Argument
+        assert_eq!(&dlt[26..32], &[0x00, 0x82, 0, 0, 3, 0]);
+        assert_eq!(&dlt[32..], b"hi\0");
+        assert_eq!(dlt.len(), 35);
+    }
+}
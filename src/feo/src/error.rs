@@ -13,28 +13,78 @@
 
 //! FEO Error implementation
 
+use crate::barrier::CycleTimeout;
+pub use crate::config::ConfigError;
 use crate::debug_fmt::ScoreDebugComApiError;
-use crate::ids::{ActivityId, ChannelId, WorkerId};
+use crate::dependency::DependencyErrors;
+use crate::ids::{ActivityId, AgentId, ChainId, ChannelId, WorkerId};
 use crate::signalling::common::signals::Signal;
 use feo_time::Duration;
 use feo_tracing::ScoreDebugIoError;
 use score_log::ScoreDebug;
 
 /// FEO Error type
+///
+/// Failures of a category shared by several modules are grouped into a nested error type,
+/// see [ConnectionError], [ConfigError], [SignallingError] and [RecordingError], so that
+/// embedding applications can handle them without matching on descriptions.
 #[non_exhaustive]
 #[derive(Debug, ScoreDebug)]
 pub enum Error {
     ActivityFailed(ActivityId, ActivityError),
     ActivityNotFound(ActivityId),
+    AgentUnhealthy(AgentId),
+    ChainDependency(ActivityId, ActivityId),
+    ChainNotFound(ChainId),
+    /// Invalid configuration of an agent
+    Config(ConfigError),
+    /// No ready signal received while cycles were running, with the activities holding them up
+    CycleTimeout(CycleTimeout),
+    /// Failure to connect agents and workers or to keep their channels open
+    Connection(ConnectionError),
+    Dependencies(DependencyErrors),
+    Harness(&'static str),
+    Io((ScoreDebugIoError, &'static str)),
+    /// Failure to write or read a recording
+    Recording(RecordingError),
+    /// Violation of the signalling protocol between scheduler and workers
+    Signalling(SignallingError),
+    Timeout(Option<Duration>, &'static str),
+    WorkerNotFound(WorkerId),
+    MwComError(ScoreDebugComApiError),
+}
+
+/// Failure to connect agents and workers or to keep their channels open
+#[non_exhaustive]
+#[derive(Debug, ScoreDebug)]
+pub enum ConnectionError {
+    /// Connecting failed after the given number of attempts
+    Failed((ScoreDebugIoError, u32)),
     Channel(&'static str),
     ChannelClosed,
     ChannelNotFound(ChannelId),
-    Io((ScoreDebugIoError, &'static str)),
-    Timeout(Option<Duration>, &'static str),
+    Discovery(&'static str),
+    /// Protocol version of a peer differing from ours (expected, found), `None` if the peer announced none
+    ProtocolVersion(u16, Option<u16>),
+}
+
+/// Violation of the signalling protocol between scheduler and workers
+#[non_exhaustive]
+#[derive(Debug, ScoreDebug)]
+pub enum SignallingError {
     UnexpectedProtocolSignal,
     UnexpectedSignal(Signal),
-    WorkerNotFound(WorkerId),
-    MwComError(ScoreDebugComApiError),
+    /// A worker thread of this agent terminated without a result, e.g. by a panic outside of activities
+    WorkerTerminated,
+}
+
+/// Failure to write or read a recording
+#[non_exhaustive]
+#[derive(Debug, ScoreDebug)]
+pub enum RecordingError {
+    /// The recorder was started up before
+    AlreadyStarted,
+    Failed(&'static str),
 }
 
 impl From<com_api::Error> for Error {
@@ -63,10 +113,22 @@ impl core::fmt::Display for Error {
                 write!(f, "activity {id} reported a failure: {err:?}")
             },
             Error::ActivityNotFound(id) => write!(f, "failed to find activity with ID {id}"),
-            Error::Channel(description) => write!(f, "channel error: {description}"),
-            Error::ChannelClosed => write!(f, "channel closed by peer"),
-            Error::ChannelNotFound(id) => write!(f, "failed to find channel with ID {id}"),
+            Error::AgentUnhealthy(id) => write!(f, "agent {id} missed its heartbeat"),
+            Error::ChainDependency(id, dependency) => {
+                write!(
+                    f,
+                    "activity {id} depends on activity {dependency} of another task chain"
+                )
+            },
+            Error::ChainNotFound(id) => write!(f, "failed to find task chain with ID {id}"),
+            Error::Config(e) => write!(f, "invalid configuration: {e}"),
+            Error::CycleTimeout(e) => write!(f, "{e}"),
+            Error::Connection(e) => write!(f, "{e}"),
+            Error::Dependencies(errors) => write!(f, "invalid activity dependencies: {errors}"),
+            Error::Harness(description) => write!(f, "harness error: {description}"),
             Error::Io((e, description)) => write!(f, "{description}: io error: {e}"),
+            Error::Recording(e) => write!(f, "recording error: {e}"),
+            Error::Signalling(e) => write!(f, "{e}"),
             Error::Timeout(duration, action) => {
                 if let Some(duration) = duration {
                     write!(f, "timeout reached ({:0.3}s) while {action}", duration.as_secs_f64())
@@ -74,14 +136,87 @@ impl core::fmt::Display for Error {
                     write!(f, "timeout reached while {action}")
                 }
             },
-            Error::UnexpectedProtocolSignal => write!(f, "received unexpected protocol signal"),
-            Error::UnexpectedSignal(signal) => write!(f, "received unexpected signal {signal}"),
             Error::WorkerNotFound(id) => write!(f, "failed to find worker with ID {id}"),
             Error::MwComError(e) => write!(f, "mw com error: {e:?}"),
         }
     }
 }
 
+impl core::error::Error for ConnectionError {}
+
+impl core::fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ConnectionError::Failed((e, attempts)) => {
+                write!(f, "failed to connect after {attempts} attempts: io error: {e}")
+            },
+            ConnectionError::Channel(description) => write!(f, "channel error: {description}"),
+            ConnectionError::ChannelClosed => write!(f, "channel closed by peer"),
+            ConnectionError::ChannelNotFound(id) => write!(f, "failed to find channel with ID {id}"),
+            ConnectionError::Discovery(description) => write!(f, "discovery error: {description}"),
+            ConnectionError::ProtocolVersion(expected, Some(found)) => {
+                write!(f, "peer speaks protocol version {found}, expected version {expected}")
+            },
+            ConnectionError::ProtocolVersion(expected, None) => {
+                write!(f, "peer did not announce its protocol version, expected version {expected}")
+            },
+        }
+    }
+}
+
+impl core::error::Error for SignallingError {}
+
+impl core::fmt::Display for SignallingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SignallingError::UnexpectedProtocolSignal => write!(f, "received unexpected protocol signal"),
+            SignallingError::UnexpectedSignal(signal) => write!(f, "received unexpected signal {signal}"),
+            SignallingError::WorkerTerminated => write!(f, "worker thread terminated without a result"),
+        }
+    }
+}
+
+impl core::error::Error for RecordingError {}
+
+impl core::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RecordingError::AlreadyStarted => write!(f, "recorder already started"),
+            RecordingError::Failed(description) => write!(f, "{description}"),
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
+
+impl From<ConnectionError> for Error {
+    fn from(e: ConnectionError) -> Self {
+        Error::Connection(e)
+    }
+}
+
+impl From<SignallingError> for Error {
+    fn from(e: SignallingError) -> Self {
+        Error::Signalling(e)
+    }
+}
+
+impl From<RecordingError> for Error {
+    fn from(e: RecordingError) -> Self {
+        Error::Recording(e)
+    }
+}
+
+impl From<DependencyErrors> for Error {
+    fn from(errors: DependencyErrors) -> Self {
+        Error::Dependencies(errors)
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Error::Io((ScoreDebugIoError(err), "failed"))
@@ -0,0 +1,72 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Reaction of the primary agent to failing activities
+
+use crate::ids::ActivityId;
+use score_log::ScoreDebug;
+use std::collections::HashMap;
+
+/// Reaction of the scheduler to a failed step of an activity
+///
+/// Failures during startup always lead to a shutdown of all agents,
+/// failures during shutdown are logged and otherwise ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ScoreDebug)]
+pub enum ErrorPolicy {
+    /// Shut down all activities and agents
+    #[default]
+    Abort,
+    /// Treat the failed step as finished and continue the task chain
+    Skip,
+    /// Step the activity again, up to the given number of times per cycle, before aborting
+    Retry(u32),
+    /// Treat the failed step as finished and stop stepping the activity for the rest of the run.
+    ///
+    /// Dependent activities are stepped as if the degraded activity had finished its steps.
+    Degrade,
+    /// Abort the current cycle of the activity's task chain.
+    ///
+    /// Running steps of the chain are awaited, but no further activities of the chain are stepped
+    /// in this cycle. The next cycle of the chain starts as usual.
+    AbortChain,
+}
+
+/// Error policy configuration of the primary agent
+#[derive(Debug, Clone, Default)]
+pub struct ErrorPolicies {
+    /// Error policy per activity
+    pub policies: HashMap<ActivityId, ErrorPolicy>,
+    /// Error policy of activities without an entry in `policies`
+    pub default: ErrorPolicy,
+}
+
+impl ErrorPolicies {
+    /// Create an error policy configuration applying the given policy to all activities
+    pub fn new(default: ErrorPolicy) -> Self {
+        Self {
+            policies: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Set the error policy of the given activity
+    pub fn with_policy(mut self, id: ActivityId, policy: ErrorPolicy) -> Self {
+        self.policies.insert(id, policy);
+        self
+    }
+
+    /// Get the error policy of the given activity
+    pub(crate) fn policy(&self, id: &ActivityId) -> ErrorPolicy {
+        self.policies.get(id).copied().unwrap_or(self.default)
+    }
+}
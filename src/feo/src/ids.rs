@@ -11,7 +11,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-//! IDs activities, agents, channels, relays, and workers
+//! IDs activities, agents, chains, channels, relays, and workers
 
 use core::fmt;
 use core::hash::Hash;
@@ -43,6 +43,18 @@ impl GetPrefix for AgentIdMarker {
     }
 }
 
+/// Identifies a task chain
+pub type ChainId = GenericId<ChainIdMarker>;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChainIdMarker;
+
+impl GetPrefix for ChainIdMarker {
+    fn prefix() -> &'static str {
+        "Ch"
+    }
+}
+
 /// Identifies a communication channel peer
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, ScoreDebug)]
 #[non_exhaustive]
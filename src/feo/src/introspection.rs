@@ -0,0 +1,443 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Introspection of a running primary agent
+//!
+//! The scheduler of the primary agent publishes a [Status] snapshot after each completed cycle
+//! of a task chain. An introspection server started with [serve] answers queries for this snapshot
+//! on a Unix socket, so operators can inspect a running system without attaching a tracer,
+//! e.g. with `echo status | nc -U /tmp/feo_introspection.sock`.
+//!
+//! The protocol is line based: each line sent by a client is a command, answered with a single line.
+//!
+//! - `status`: JSON of the complete [Status]
+//! - `chains`: JSON of the [ChainStatus] of all task chains
+//! - `activities`: JSON of the [ActivityStatus] of all activities
+//! - `agents`: JSON of the [AgentStatus] of all connected remote agents
+//! - `workers`: JSON of the [WorkerStatus] of all workers having reported their utilization
+//! - `latencies`: JSON of the [TopicLatencyStatus] of all topics with measured latencies,
+//!   see [latency](feo_com::latency)
+//! - `debug`: JSON of the [DebugTopicStatus] of all debug topics, see [debug_topic](crate::debug_topic)
+//! - `disable <id>`: request the activity with the given ID to be disabled, answered with `ok`
+//! - `enable <id>`: request the disabled activity with the given ID to be re-enabled, answered with `ok`
+//! - `pause`: pause the scheduler before the next cycle, answered with `ok`
+//! - `step [n]`: start the next `n` cycles, one by default, and pause again, answered with `ok`
+//! - `continue`: continue to cycle freely, answered with `ok`
+//! - `reload`: reload the non-structural parameters of the watched configuration file, answered with `ok`
+//!   or the reason of the rejection
+//! - `join`: JSON of the [JoinInfo] needed by a recorder process to join, see [join](crate::recording::join)
+//! - `cycle`: wait for the next completed cycle of the main task chain, answered with its number
+//! - `help`: list of the commands
+//!
+//! Requests to disable or re-enable activities are applied at the next cycle boundary, see [control](crate::control).
+//! Pausing and stepping is described in [debugger](crate::debugger), reloading in [reload](crate::reload).
+
+use crate::chain::MAIN_CHAIN;
+use crate::control::ActivityControl;
+use crate::debug_topic;
+use crate::debugger::CycleDebugger;
+use crate::error::Error;
+use crate::ids::ActivityId;
+use crate::recording::join::JoinInfo;
+use crate::reload::ConfigReload;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use feo_com::latency;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, error, info};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// Response to the `help` command
+const HELP: &str = concat!(
+    "commands: status, chains, activities, agents, workers, latencies, debug, disable <id>, enable <id>, ",
+    "pause, step [n], continue, reload, join, cycle, help"
+);
+
+/// Snapshot of the state of the scheduler
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Status {
+    /// Whether the scheduler is cycling the task chains, i.e. startup is done and no shutdown requested
+    pub running: bool,
+    /// Whether the scheduler is paused before the next cycle, see [debugger](crate::debugger)
+    pub paused: bool,
+    /// State of the task chains
+    pub chains: Vec<ChainStatus>,
+    /// State of the activities
+    pub activities: Vec<ActivityStatus>,
+    /// State of the connected remote agents
+    pub agents: Vec<AgentStatus>,
+    /// Utilization of the workers
+    pub workers: Vec<WorkerStatus>,
+}
+
+/// State of a task chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainStatus {
+    /// ID of the chain
+    pub id: u64,
+    /// Number of completed cycles
+    pub cycle: u64,
+    /// Duration of the last completed cycle in microseconds
+    pub last_cycle_us: Option<u64>,
+}
+
+/// State of an activity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActivityStatus {
+    /// ID of the activity
+    pub id: u64,
+    /// ID of the task chain of the activity
+    pub chain: u64,
+    /// Duration of the last finished step in microseconds
+    pub last_step_us: Option<u64>,
+    /// Whether the activity is no longer stepped due to a failure or an unhealthy agent
+    pub degraded: bool,
+    /// Whether the activity is not stepped on request
+    pub disabled: bool,
+    /// Bytes allocated and not freed by the last step, if accounted, see [memory](crate::memory)
+    pub last_step_retained_bytes: Option<i64>,
+    /// Bytes allocated and not freed by all steps since startup, if accounted
+    pub retained_bytes: Option<i64>,
+}
+
+/// State of a connected remote agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgentStatus {
+    /// ID of the agent
+    pub id: u64,
+    /// Whether the agent sends its heartbeats in time, always true without supervision
+    pub healthy: bool,
+}
+
+/// Utilization of a worker, see [worker](crate::worker)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkerStatus {
+    /// ID of the worker
+    pub id: u64,
+    /// Share of its last cycle the worker spent executing its activities, in percent
+    pub utilization_percent: u8,
+}
+
+/// Latencies of the samples of a topic from publish to first read, measured in the primary agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopicLatencyStatus {
+    /// Name of the topic
+    pub topic: String,
+    /// Number of measured latencies
+    pub count: u64,
+    /// Mean latency in microseconds
+    pub mean_us: u64,
+    /// Median latency in microseconds, upper bound of its histogram bucket
+    pub p50_us: u64,
+    /// 99th percentile latency in microseconds, upper bound of its histogram bucket
+    pub p99_us: u64,
+    /// Maximum latency in microseconds
+    pub max_us: u64,
+}
+
+impl TopicLatencyStatus {
+    /// Get the status of all topics with measured latencies in this process
+    pub fn all() -> Vec<Self> {
+        latency::histograms()
+            .into_iter()
+            .map(|(topic, histogram)| Self {
+                topic,
+                count: histogram.count(),
+                mean_us: histogram.mean().as_micros() as u64,
+                p50_us: histogram.quantile(0.5).as_micros() as u64,
+                p99_us: histogram.quantile(0.99).as_micros() as u64,
+                max_us: histogram.max().as_micros() as u64,
+            })
+            .collect()
+    }
+}
+
+/// Latest value of a debug topic published in the primary agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DebugTopicStatus {
+    /// Name of the debug topic
+    pub topic: String,
+    /// Name of the type of the value
+    pub type_name: String,
+    /// ID of the activity which published the value, if published by an activity
+    pub activity: Option<u64>,
+    /// Number of values published on the debug topic
+    pub count: u64,
+    /// Time of the publication since startup in microseconds
+    pub timestamp_us: u64,
+    /// Latest value, `null` if not representable as JSON
+    pub value: serde_json::Value,
+}
+
+impl DebugTopicStatus {
+    /// Get the status of all debug topics registered in this process
+    pub fn all() -> Vec<Self> {
+        debug_topic::samples()
+            .into_iter()
+            .map(|sample| Self {
+                type_name: sample.value.type_name().to_string(),
+                value: sample.value.to_json(),
+                topic: sample.topic,
+                activity: sample.activity.map(|activity| activity.id()),
+                count: sample.sequence,
+                timestamp_us: sample.timestamp.0.as_micros() as u64,
+            })
+            .collect()
+    }
+}
+
+/// Shared status of the scheduler, updated by the scheduler and read by the introspection server
+#[derive(Debug, Clone, Default)]
+pub struct Introspection {
+    status: Arc<Mutex<Status>>,
+    published: Arc<Condvar>,
+    control: ActivityControl,
+    debugger: CycleDebugger,
+    reload: ConfigReload,
+}
+
+impl Introspection {
+    /// Create a handle with an empty status
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a copy of the current status
+    pub fn status(&self) -> Status {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Get the handle disabling and re-enabling activities, shared with the `disable` and `enable` commands
+    pub fn control(&self) -> ActivityControl {
+        self.control.clone()
+    }
+
+    /// Get the handle pausing and stepping the scheduler, shared with the `pause`, `step` and `continue` commands
+    pub fn debugger(&self) -> CycleDebugger {
+        self.debugger.clone()
+    }
+
+    /// Get the handle reloading the configuration, shared with the `reload` command
+    pub fn reload(&self) -> ConfigReload {
+        self.reload.clone()
+    }
+
+    /// Replace the current status
+    pub(crate) fn publish(&self, status: Status) {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = status;
+        self.published.notify_all();
+    }
+
+    /// Wait for the next completed cycle of the main task chain and get its number
+    ///
+    /// Returns `None` if the scheduler is not running or stops before.
+    pub fn next_cycle(&self) -> Option<u64> {
+        let main_cycle = |status: &Status| {
+            status
+                .chains
+                .iter()
+                .find(|chain| chain.id == MAIN_CHAIN.id())
+                .map(|chain| chain.cycle)
+        };
+        let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        let cycle = main_cycle(&status);
+        let status = self
+            .published
+            .wait_while(status, |status| status.running && main_cycle(status) == cycle)
+            .unwrap_or_else(|e| e.into_inner());
+        if status.running {
+            main_cycle(&status)
+        } else {
+            None
+        }
+    }
+
+    /// Answer a single command of the line protocol
+    pub fn respond(&self, command: &str) -> String {
+        if let Some(cycles) = command.strip_prefix("step").filter(|rest| rest.is_empty() || rest.starts_with(' ')) {
+            let cycles = cycles.trim();
+            let Ok(cycles) = (if cycles.is_empty() { Ok(1) } else { cycles.parse::<u64>() }) else {
+                return format!("error: invalid number of cycles '{cycles}'");
+            };
+            self.debugger.step(cycles);
+            return "ok".to_string();
+        }
+        if let Some((command, id)) = command.split_once(' ') {
+            let Ok(id) = id.trim().parse::<u64>() else {
+                return format!("error: invalid activity id '{}'", id.trim());
+            };
+            match command {
+                "disable" => self.control.disable(ActivityId::from(id)),
+                "enable" => self.control.enable(ActivityId::from(id)),
+                other => return format!("error: unknown command '{other}', {HELP}"),
+            }
+            return "ok".to_string();
+        }
+
+        let status = self.status();
+        let response = match command {
+            "status" => serde_json::to_string(&status),
+            "chains" => serde_json::to_string(&status.chains),
+            "activities" => serde_json::to_string(&status.activities),
+            "agents" => serde_json::to_string(&status.agents),
+            "workers" => serde_json::to_string(&status.workers),
+            "latencies" => serde_json::to_string(&TopicLatencyStatus::all()),
+            "debug" => serde_json::to_string(&DebugTopicStatus::all()),
+            "join" => serde_json::to_string(&JoinInfo::current()),
+            "cycle" => {
+                return match self.next_cycle() {
+                    Some(cycle) => cycle.to_string(),
+                    None => "error: not running".to_string(),
+                };
+            },
+            "pause" => {
+                self.debugger.pause();
+                return "ok".to_string();
+            },
+            "continue" => {
+                self.debugger.resume();
+                return "ok".to_string();
+            },
+            "reload" => {
+                return match self.reload.reload() {
+                    Ok(_) => "ok".to_string(),
+                    Err(e) => format!("error: {e}"),
+                };
+            },
+            "help" => return HELP.to_string(),
+            other => return format!("error: unknown command '{other}', {HELP}"),
+        };
+        response.unwrap_or_else(|e| format!("error: {e}"))
+    }
+}
+
+/// Start an introspection server listening on the Unix socket at `path`
+///
+/// A stale socket file at `path` is removed. The server runs in a background thread for the
+/// lifetime of the process, serving each client in a thread of its own.
+pub fn serve(path: &Path) -> Result<Introspection, Error> {
+    let _ = fs::remove_file(path);
+    let listener = UnixListener::bind(path)
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to bind introspection socket")))?;
+    let introspection = Introspection::new();
+    let server = introspection.clone();
+    thread::Builder::new()
+        .name("feo-introspection".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        error!("Failed to accept introspection client: {:?}", ScoreDebugIoError(e));
+                        continue;
+                    },
+                };
+                let introspection = server.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_client(stream, &introspection) {
+                        debug!("Introspection client disconnected: {:?}", ScoreDebugIoError(e));
+                    }
+                });
+            }
+        })
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn introspection server")))?;
+    info!(
+        "Introspection server listening on {}",
+        path.display().to_string().as_str()
+    );
+    Ok(introspection)
+}
+
+/// Answer the commands of a client until it disconnects
+fn handle_client(stream: UnixStream, introspection: &Introspection) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let response = introspection.respond(line?.trim());
+        writer.write_all(response.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn introspection_server_answers_commands() {
+        let directory = std::env::temp_dir().join(format!("feo_introspection_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("introspection.sock");
+
+        let introspection = serve(&path).unwrap();
+        introspection.publish(Status {
+            running: true,
+            paused: false,
+            chains: alloc::vec![ChainStatus {
+                id: 0,
+                cycle: 42,
+                last_cycle_us: Some(1500),
+            }],
+            activities: Vec::new(),
+            agents: alloc::vec![AgentStatus { id: 1, healthy: false }],
+            workers: alloc::vec![WorkerStatus {
+                id: 2,
+                utilization_percent: 35,
+            }],
+        });
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .write_all(b"chains\nagents\nworkers\nlatencies\ndebug\nfoo\ndisable 3\npause\nstep x\nstep 2\nreload\n")
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"[{"id":0,"cycle":42,"last_cycle_us":1500}]"#
+        );
+        assert_eq!(lines.next().unwrap().unwrap(), r#"[{"id":1,"healthy":false}]"#);
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"[{"id":2,"utilization_percent":35}]"#
+        );
+        assert_eq!(lines.next().unwrap().unwrap(), "[]");
+        assert!(lines.next().unwrap().unwrap().starts_with('['));
+        let unknown = lines.next().unwrap().unwrap();
+        assert!(unknown.starts_with("error: unknown command 'foo'"));
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        assert_eq!(
+            introspection.control().take(&[ActivityId::from(3)]),
+            alloc::vec![(ActivityId::from(3), true)]
+        );
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        assert!(lines.next().unwrap().unwrap().starts_with("error: invalid number of cycles"));
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        let debugger = introspection.debugger();
+        assert!(debugger.take_step() && debugger.take_step());
+        assert!(!debugger.take_step());
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            "error: no configuration file is watched for reloading"
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
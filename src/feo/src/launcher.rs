@@ -0,0 +1,257 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Autostart and supervision of the secondary agent processes
+//!
+//! With [SecondaryProcess] entries in the configuration of the primary agent, e.g. created from the
+//! `command` of the agents in an [AppConfig](crate::config::AppConfig), the primary agent starts the
+//! secondary agents itself once its scheduler waits for their connections, so no external scripts
+//! are needed to launch an application. Lines written by the processes to stdout and stderr are
+//! logged by the primary agent with the ID of the agent, on info and warn level respectively.
+//!
+//! A process exiting unsuccessfully before the primary agent shuts down is restarted, up to the
+//! `max_restarts` of its entry. Unless the primary agent is configured to let restarted agents
+//! [rejoin](crate::rejoin), the scheduler takes on the connections of the secondary agents during
+//! startup only, so a secondary agent restarted later cannot rejoin the running task chains;
+//! its activities are handled by the heartbeat [supervision](crate::supervision) instead.
+//!
+//! On shutdown, the primary agent waits for the processes to exit after their termination and
+//! kills the processes still running after [STOP_GRACE].
+
+use crate::error::Error;
+use crate::ids::AgentId;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{error, info, warn};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// Time the processes are given to exit on shutdown before they are killed
+pub const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Interval of checking the processes for their exit
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Delay before restarting a crashed process
+const RESTART_DELAY: Duration = Duration::from_millis(500);
+
+/// Secondary agent process started and supervised by the primary agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecondaryProcess {
+    /// ID of the agent run by the process
+    pub agent: AgentId,
+    /// Program and arguments starting the process
+    pub command: Vec<String>,
+    /// Maximum number of restarts after crashes
+    pub max_restarts: u32,
+}
+
+impl SecondaryProcess {
+    /// Create an entry starting the given agent with the given program and arguments, never restarted
+    pub fn new(agent: AgentId, command: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            agent,
+            command: command.into_iter().map(Into::into).collect(),
+            max_restarts: 0,
+        }
+    }
+
+    /// Restart the process after crashes, at most the given number of times
+    pub fn with_max_restarts(mut self, max_restarts: u32) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Start the process, forwarding its output to the log
+    fn spawn(&self) -> io::Result<Child> {
+        let Some((program, args)) = self.command.split_first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command line"));
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(stdout) = child.stdout.take() {
+            forward_output(self.agent, stdout, false);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_output(self.agent, stderr, true);
+        }
+        info!("Started agent {} (PID {})", self.agent, child.id());
+        Ok(child)
+    }
+}
+
+/// Supervisor threads of the started secondary agent processes
+///
+/// Dropping the launcher stops the supervision, see [Launcher::stop].
+#[derive(Debug, Default)]
+pub(crate) struct Launcher {
+    /// Whether the primary agent shuts down, so exited processes are not restarted
+    stopping: Arc<AtomicBool>,
+    /// Supervisor thread per process
+    supervisors: Vec<JoinHandle<()>>,
+}
+
+impl Launcher {
+    /// Start the given processes, each supervised by a thread of its own
+    pub(crate) fn launch(processes: Vec<SecondaryProcess>) -> Result<Self, Error> {
+        let mut launcher = Self::default();
+        for process in processes {
+            let child = process
+                .spawn()
+                .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to start secondary agent")))?;
+            let stopping = launcher.stopping.clone();
+            let supervisor = thread::Builder::new()
+                .name(format!("feo-launcher-{}", process.agent))
+                .spawn(move || supervise(process, child, &stopping))
+                .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn supervisor of secondary agent")))?;
+            launcher.supervisors.push(supervisor);
+        }
+        Ok(launcher)
+    }
+
+    /// Wait for the processes to exit without restarting them, killing them after [STOP_GRACE]
+    pub(crate) fn stop(&mut self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        for supervisor in self.supervisors.drain(..) {
+            let _ = supervisor.join();
+        }
+    }
+}
+
+impl Drop for Launcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Supervise the given process, restarting it on crashes until stopping
+fn supervise(process: SecondaryProcess, mut child: Child, stopping: &AtomicBool) {
+    let mut restarts = 0;
+    let mut kill_at = None;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() || stopping.load(Ordering::Relaxed) => {
+                info!("Agent {} exited ({})", process.agent, status.to_string().as_str());
+                return;
+            },
+            Ok(Some(status)) if restarts >= process.max_restarts => {
+                error!(
+                    "Agent {} crashed ({}), giving up after {} restarts",
+                    process.agent,
+                    status.to_string().as_str(),
+                    restarts
+                );
+                return;
+            },
+            Ok(Some(status)) => {
+                restarts += 1;
+                warn!(
+                    "Agent {} crashed ({}), restarting it ({} of {})",
+                    process.agent,
+                    status.to_string().as_str(),
+                    restarts,
+                    process.max_restarts
+                );
+                thread::sleep(RESTART_DELAY);
+                child = match process.spawn() {
+                    Ok(child) => child,
+                    Err(e) => {
+                        error!("Failed to restart agent {}: {:?}", process.agent, ScoreDebugIoError(e));
+                        return;
+                    },
+                };
+            },
+            Ok(None) => {},
+            Err(e) => {
+                error!("Failed to supervise agent {}: {:?}", process.agent, ScoreDebugIoError(e));
+                return;
+            },
+        }
+
+        if stopping.load(Ordering::Relaxed) {
+            let kill_at = *kill_at.get_or_insert_with(|| Instant::now() + STOP_GRACE);
+            if Instant::now() >= kill_at {
+                warn!(
+                    "Killing agent {}, still running {} s after shutdown",
+                    process.agent,
+                    STOP_GRACE.as_secs()
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Log the lines of the given output of a process in a thread of its own
+fn forward_output(agent: AgentId, output: impl Read + Send + 'static, is_stderr: bool) {
+    let forwarder = thread::Builder::new()
+        .name(format!("feo-output-{agent}"))
+        .spawn(move || {
+            for line in BufReader::new(output).split(b'\n') {
+                let Ok(line) = line else {
+                    break;
+                };
+                let line = String::from_utf8_lossy(&line);
+                if is_stderr {
+                    warn!("[agent {}] {}", agent, line.trim_end());
+                } else {
+                    info!("[agent {}] {}", agent, line.trim_end());
+                }
+            }
+        });
+    if let Err(e) = forwarder {
+        warn!("Not logging the output of agent {}: {:?}", agent, ScoreDebugIoError(e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crashed_processes_are_restarted() {
+        let directory = std::env::temp_dir().join(format!("feo_launcher_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let starts = directory.join("starts");
+        let script = format!("echo started >> {}; exit 3", starts.display());
+
+        let process = SecondaryProcess::new(AgentId::new(101), ["sh", "-c", script.as_str()]).with_max_restarts(2);
+        let mut launcher = Launcher::launch(alloc::vec![process]).unwrap();
+        // Wait for the supervisor to give up instead of stopping it
+        for supervisor in launcher.supervisors.drain(..) {
+            supervisor.join().unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&starts).unwrap().lines().count(), 3);
+
+        let process = SecondaryProcess::new(AgentId::new(102), ["sleep", "60"]);
+        let started = Instant::now();
+        Launcher::launch(alloc::vec![process]).unwrap().stop();
+        assert!(started.elapsed() < STOP_GRACE + Duration::from_secs(2));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
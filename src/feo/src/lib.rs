@@ -41,17 +41,54 @@
 extern crate alloc;
 extern crate std;
 
+// Signalling, signal forwarding, shared memory and the local services of the agents rely on Unix sockets,
+// signals and memory mappings. Only the tracing transport of feo-tracing supports other targets so far.
+#[cfg(not(unix))]
+compile_error!("FEO requires a Unix target");
+
 pub mod activity;
 pub mod agent;
+pub mod barrier;
+pub mod chain;
+pub mod config;
+pub mod control;
 pub mod cpp;
+pub mod deadline;
 pub mod debug_fmt;
+pub mod debug_topic;
+pub mod debugger;
+pub mod dependency;
+pub mod discovery;
+pub mod dlt;
 pub mod error;
+pub mod error_policy;
 pub mod ids;
+pub mod introspection;
+pub mod launcher;
+pub mod log_relay;
+pub mod memory;
+pub mod metrics;
+pub mod on_demand;
+pub mod overrun;
+pub mod panic_report;
+pub mod paths;
+pub mod recording;
+pub mod rejoin;
+pub mod reload;
 pub mod scheduler;
+pub mod signal_forwarding;
 pub mod signalling;
+pub mod standby;
+pub mod startup;
+pub mod statistics;
+pub mod supervision;
+pub mod testing;
+pub mod thread_config;
 mod timestamp;
 pub mod topicspec;
+pub mod watchdog;
 pub mod worker;
+pub mod worker_local;
 
 pub(crate) static TOKIO_RT: std::sync::LazyLock<tokio::runtime::Runtime> =
     std::sync::LazyLock::new(|| tokio::runtime::Runtime::new().unwrap());
@@ -0,0 +1,460 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Relay of log records from secondary agents to the primary agent
+//!
+//! On targets where secondary agents have no console, each secondary agent installs a [LogRelay]
+//! as its logger with [LogRelay::install] and passes a clone to its configuration. Log records are
+//! queued as [LogLine]s and sent by the workers of the agent to the primary agent over the existing
+//! signalling connection, whenever a worker is not busy running an activity.
+//!
+//! The primary agent configured with a [MergedLogConfig] writes the relayed records, together with
+//! the records of its own [LogRelay] if given, into a single log. Records are held back for
+//! [MERGE_WINDOW] to write them in the order of their timestamps. The timestamps are taken from the
+//! system clock, assuming the system clocks of all agents are synchronized.
+//!
+//! Log lines do not fit into the messages of the shared memory signalling and are dropped there.
+
+use crate::error::Error;
+use crate::ids::AgentId;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use core::cell::Cell;
+use core::fmt::{self, Write as _};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use feo_tracing::bounded::BoundedStr;
+use feo_tracing::ScoreDebugIoError;
+use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use score_log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum length of the text of a [LogLine] in bytes, longer texts are truncated
+pub const LOG_LINE_CAPACITY: usize = 160;
+
+/// Time for which the primary agent holds back log lines to write them in order
+pub const MERGE_WINDOW: Duration = Duration::from_millis(200);
+
+/// Maximum number of log lines queued by a [LogRelay] until sent
+///
+/// The oldest lines are dropped if the workers do not keep up.
+const MAX_QUEUED: usize = 1024;
+
+std::thread_local! {
+    /// Whether the current thread is forwarding log lines, dropping records logged meanwhile
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Log record relayed to the primary agent
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogLine {
+    /// Agent which logged the record
+    pub agent: AgentId,
+    /// Time since the unix epoch at which the record was logged
+    pub timestamp: Duration,
+    /// Level of the record, see [LogLine::level]
+    level: u8,
+    /// Text of the record
+    text: BoundedStr<LOG_LINE_CAPACITY>,
+}
+
+impl LogLine {
+    /// Create a log line, truncating the text to [LOG_LINE_CAPACITY] bytes
+    pub fn new(agent: AgentId, timestamp: Duration, level: Level, text: &str) -> Self {
+        Self {
+            agent,
+            timestamp,
+            level: level_to_u8(level),
+            text: BoundedStr::truncated(text),
+        }
+    }
+
+    /// Create a log line from its encoded parts, returns `None` if they are invalid
+    pub(crate) fn from_parts(agent: AgentId, timestamp: Duration, level: u8, text: &[u8]) -> Option<Self> {
+        level_from_u8(level)?;
+        Some(Self {
+            agent,
+            timestamp,
+            level,
+            text: BoundedStr::from_utf8(text)?,
+        })
+    }
+
+    /// Level of the record
+    pub fn level(&self) -> Level {
+        level_from_u8(self.level).unwrap_or(Level::Error)
+    }
+
+    /// Level of the record as encoded in signals
+    pub(crate) fn level_u8(&self) -> u8 {
+        self.level
+    }
+
+    /// Text of the record
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+}
+
+impl fmt::Debug for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogLine")
+            .field("agent", &self.agent)
+            .field("timestamp", &self.timestamp)
+            .field("level", &self.level())
+            .field("text", &self.text())
+            .finish()
+    }
+}
+
+impl ScoreDebug for LogLine {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("LogLine(", spec)?;
+        ScoreDebug::fmt(&self.agent, f, spec)?;
+        f.write_str(", ", spec)?;
+        f.write_str(self.text(), spec)?;
+        f.write_str(")", spec)
+    }
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level() {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        write!(
+            f,
+            "{}.{:06} [{}] {level} {}",
+            self.timestamp.as_secs(),
+            self.timestamp.subsec_micros(),
+            self.agent,
+            self.text()
+        )
+    }
+}
+
+fn level_to_u8(level: Level) -> u8 {
+    match level {
+        Level::Error => 1,
+        Level::Warn => 2,
+        Level::Info => 3,
+        Level::Debug => 4,
+        Level::Trace => 5,
+    }
+}
+
+fn level_from_u8(level: u8) -> Option<Level> {
+    match level {
+        1 => Some(Level::Error),
+        2 => Some(Level::Warn),
+        3 => Some(Level::Info),
+        4 => Some(Level::Debug),
+        5 => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+/// Logger queueing log records to be relayed to the primary agent
+///
+/// Clones share the same queue.
+#[derive(Debug, Clone)]
+pub struct LogRelay(Arc<RelayInner>);
+
+#[derive(Debug)]
+struct RelayInner {
+    /// Agent logging the records
+    agent: AgentId,
+    /// Maximum level of the relayed records
+    level: LevelFilter,
+    /// Queued log lines
+    queue: Mutex<VecDeque<LogLine>>,
+    /// Number of log lines dropped because the queue was full
+    dropped: AtomicU64,
+}
+
+impl LogRelay {
+    /// Create a relay for the records of the given agent up to the given level
+    pub fn new(agent: AgentId, level: LevelFilter) -> Self {
+        Self(Arc::new(RelayInner {
+            agent,
+            level,
+            queue: Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Install a clone of this relay as the logger of this process
+    pub fn install(&self) {
+        score_log::set_global_logger(Box::new(self.clone()));
+        score_log::set_max_level(self.0.level);
+    }
+
+    /// Queue a log line, dropping the oldest one if the queue is full
+    pub fn push(&self, line: LogLine) {
+        let mut queue = self.0.queue.lock().unwrap();
+        if queue.len() >= MAX_QUEUED {
+            queue.pop_front();
+            self.0.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queue.push_back(line);
+    }
+
+    /// Number of log lines dropped so far because they could not be sent in time
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Take all queued log lines
+    pub(crate) fn take(&self) -> VecDeque<LogLine> {
+        core::mem::take(&mut *self.0.queue.lock().unwrap())
+    }
+
+    /// Send all queued log lines with the given function, dropping those failing to be sent
+    ///
+    /// Records logged while sending, e.g. by the signalling layer, are dropped to avoid feedback loops.
+    pub(crate) fn forward(&self, mut send: impl FnMut(LogLine) -> Result<(), Error>) {
+        let lines = self.take();
+        if lines.is_empty() {
+            return;
+        }
+        FORWARDING.with(|forwarding| forwarding.set(true));
+        for line in lines {
+            if send(line).is_err() {
+                self.0.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        FORWARDING.with(|forwarding| forwarding.set(false));
+    }
+}
+
+impl Log for LogRelay {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.0.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) || FORWARDING.with(Cell::get) {
+            return;
+        }
+        let mut text = TextBuffer::default();
+        // Formatting errors only occur if the text is truncated
+        let _ = score_log::fmt::write(&mut text, *record.args());
+        self.push(LogLine::new(self.0.agent, now(), record.level(), text.as_str()));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Time since the unix epoch
+fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Buffer formatting the text of a log record, truncated to [LOG_LINE_CAPACITY] bytes
+#[derive(Default)]
+pub(crate) struct TextBuffer(BoundedStr<LOG_LINE_CAPACITY>);
+
+impl TextBuffer {
+    pub(crate) fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Write for TextBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s)
+    }
+}
+
+/// Implement the typed write methods of [ScoreWrite] with [fmt::Display]
+macro_rules! write_display {
+    ($($method:ident: $type:ty),*) => {
+        $(
+            fn $method(&mut self, value: &$type, _spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+                write!(self, "{value}").map_err(|_| score_log::fmt::Error)
+            }
+        )*
+    };
+}
+
+impl ScoreWrite for TextBuffer {
+    write_display!(
+        write_bool: bool, write_f32: f32, write_f64: f64,
+        write_i8: i8, write_i16: i16, write_i32: i32, write_i64: i64,
+        write_u8: u8, write_u16: u16, write_u32: u32, write_u64: u64
+    );
+
+    fn write_str(&mut self, value: &str, _spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        fmt::Write::write_str(self, value).map_err(|_| score_log::fmt::Error)
+    }
+}
+
+/// Destination of the merged log of the primary agent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LogSink {
+    /// Write to the standard output
+    #[default]
+    Stdout,
+    /// Write to the given file, replacing an existing one
+    File(PathBuf),
+}
+
+/// Configuration of the merged log of the primary agent
+#[derive(Debug, Clone, Default)]
+pub struct MergedLogConfig {
+    /// Destination of the merged log
+    pub sink: LogSink,
+    /// Relay installed as logger of the primary agent, if its own records shall be merged as well
+    pub relay: Option<LogRelay>,
+}
+
+/// Log of the primary agent merging the log lines of all agents in the order of their timestamps
+pub(crate) struct MergedLog {
+    /// Destination of the log
+    writer: Box<dyn Write + Send>,
+    /// Relay of the primary agent, if any
+    relay: Option<LogRelay>,
+    /// Log lines held back, by timestamp and order of arrival
+    pending: BTreeMap<(Duration, u64), LogLine>,
+    /// Number of log lines received so far
+    received: u64,
+}
+
+impl MergedLog {
+    /// Open the merged log with the given configuration
+    pub(crate) fn new(config: MergedLogConfig) -> Result<Self, Error> {
+        let writer: Box<dyn Write + Send> = match config.sink {
+            LogSink::Stdout => Box::new(io::stdout()),
+            LogSink::File(path) => {
+                Box::new(BufWriter::new(File::create(path).map_err(|e| {
+                    Error::Io((ScoreDebugIoError(e), "failed to create merged log"))
+                })?))
+            },
+        };
+        Ok(Self {
+            writer,
+            relay: config.relay,
+            pending: BTreeMap::new(),
+            received: 0,
+        })
+    }
+
+    /// Add a log line relayed by a secondary agent
+    pub(crate) fn push(&mut self, line: LogLine) {
+        self.pending.insert((line.timestamp, self.received), line);
+        self.received += 1;
+    }
+
+    /// Take the log lines of the primary agent and write all lines older than [MERGE_WINDOW]
+    pub(crate) fn poll(&mut self) {
+        if let Some(relay) = self.relay.clone() {
+            relay.take().into_iter().for_each(|line| self.push(line));
+        }
+        let until = now().saturating_sub(MERGE_WINDOW);
+        self.write_until(until);
+    }
+
+    /// Write all log lines with a timestamp up to the given one
+    fn write_until(&mut self, until: Duration) {
+        while let Some(entry) = self.pending.first_entry() {
+            if entry.key().0 > until {
+                break;
+            }
+            let line = entry.remove();
+            // Failures to write the log cannot be logged themselves
+            let _ = writeln!(self.writer, "{line}");
+        }
+        let _ = self.writer.flush();
+    }
+}
+
+impl Drop for MergedLog {
+    fn drop(&mut self) {
+        if let Some(relay) = self.relay.clone() {
+            relay.take().into_iter().for_each(|line| self.push(line));
+        }
+        self.write_until(Duration::MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_lines_are_merged_in_order() {
+        let path = std::env::temp_dir().join(alloc::format!("feo_merged_log_{}.log", std::process::id()));
+        let relay = LogRelay::new(AgentId::from(100), LevelFilter::Info);
+        let config = MergedLogConfig {
+            sink: LogSink::File(path.clone()),
+            relay: Some(relay.clone()),
+        };
+        let mut log = MergedLog::new(config).unwrap();
+
+        let base = now();
+        log.push(LogLine::new(
+            AgentId::from(101),
+            base + Duration::from_millis(2),
+            Level::Warn,
+            "late",
+        ));
+        relay.push(LogLine::new(
+            AgentId::from(100),
+            base + Duration::from_millis(1),
+            Level::Info,
+            "early",
+        ));
+        let long = "ä".repeat(LOG_LINE_CAPACITY);
+        let truncated = LogLine::new(AgentId::from(102), base + Duration::from_millis(3), Level::Error, &long);
+        assert_eq!(truncated.text().len(), LOG_LINE_CAPACITY);
+        log.push(truncated);
+        drop(log);
+
+        let merged = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: alloc::vec::Vec<&str> = merged.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("INFO early"));
+        assert!(lines[1].ends_with("WARN late"));
+        assert!(lines[2].contains("ERROR ä"));
+    }
+
+    #[test]
+    fn texts_are_truncated_at_character_boundaries() {
+        let mut text = TextBuffer::default();
+        assert!(write!(text, "{}", "€".repeat(LOG_LINE_CAPACITY)).is_err());
+        assert_eq!(text.as_str(), "€".repeat(LOG_LINE_CAPACITY / 3));
+
+        // Encoded texts exceeding the capacity or not in UTF-8 are rejected instead
+        let agent = AgentId::from(100);
+        let line = LogLine::from_parts(agent, Duration::ZERO, 3, text.as_str().as_bytes()).unwrap();
+        assert_eq!(line.text(), text.as_str());
+        assert!(LogLine::from_parts(agent, Duration::ZERO, 3, &[b'a'; LOG_LINE_CAPACITY + 1]).is_none());
+        assert!(LogLine::from_parts(agent, Duration::ZERO, 3, &[0xff]).is_none());
+        assert!(LogLine::from_parts(agent, Duration::ZERO, 0, b"level").is_none());
+
+        // Bounded texts are serialized like strings, deserializing fails beyond the capacity
+        let bytes = postcard::to_allocvec(&text.0).unwrap();
+        assert_eq!(postcard::from_bytes::<BoundedStr<LOG_LINE_CAPACITY>>(&bytes).unwrap(), text.0);
+        assert!(postcard::from_bytes::<BoundedStr<8>>(&bytes).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Accounting of the memory retained by the steps of the activities
+//!
+//! With a [CountingAllocator] installed as global allocator of an agent, the workers measure the memory
+//! allocated and not freed by each step of an activity and report it to the scheduler:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: feo::memory::CountingAllocator = feo::memory::CountingAllocator::system();
+//! ```
+//!
+//! The scheduler publishes the memory retained by the last step and the total retained since startup
+//! per activity via [introspection](crate::introspection). A total growing over many cycles points to
+//! an activity leaking memory. Agents without the allocator do not report anything.
+//!
+//! The allocator counts the bytes allocated and freed per thread, so memory allocated in a step and
+//! freed on another thread, e.g. by a thread of a library, is accounted as retained by the step,
+//! while memory allocated on another thread and freed in a step is accounted as released by the step.
+
+use crate::ids::ActivityId;
+use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::alloc::System;
+use std::sync::Mutex;
+
+/// Whether a [CountingAllocator] is installed, i.e. has served an allocation
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+/// Memory retained by the steps measured in this process, not yet reported to the scheduler
+static RETAINED: Mutex<Vec<(ActivityId, i64)>> = Mutex::new(Vec::new());
+
+std::thread_local! {
+    /// Bytes allocated minus bytes freed on this thread
+    static BALANCE: Cell<i64> = const { Cell::new(0) };
+}
+
+/// Global allocator counting the bytes allocated and freed per thread, wrapping another allocator
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator {
+    /// Create a counting allocator wrapping the system allocator
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Create a counting allocator wrapping the given allocator
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+// Safety: All allocations are served by the wrapped allocator; counting does not allocate
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            count(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            count(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        count(-(layout.size() as i64));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            count(new_size as i64 - layout.size() as i64);
+        }
+        new_ptr
+    }
+}
+
+/// Add the given number of bytes to the balance of the current thread
+fn count(bytes: i64) {
+    if !COUNTING.load(Ordering::Relaxed) {
+        COUNTING.store(true, Ordering::Relaxed);
+    }
+    // Not available while the thread is torn down
+    let _ = BALANCE.try_with(|balance| balance.set(balance.get() + bytes));
+}
+
+/// Whether a [CountingAllocator] is installed as global allocator of this process
+pub fn is_counting() -> bool {
+    COUNTING.load(Ordering::Relaxed)
+}
+
+/// Run the given step of an activity, measuring the memory it retains if counting
+pub(crate) fn measure_step<R>(id: ActivityId, step: impl FnOnce() -> R) -> R {
+    if !is_counting() {
+        return step();
+    }
+    let before = BALANCE.with(Cell::get);
+    let result = step();
+    let retained = BALANCE.with(Cell::get) - before;
+    RETAINED.lock().unwrap_or_else(|e| e.into_inner()).push((id, retained));
+    result
+}
+
+/// Take the measured memory retained by steps, to be reported to the scheduler
+pub(crate) fn take_retained() -> Vec<(ActivityId, i64)> {
+    let mut retained = RETAINED.lock().unwrap_or_else(|e| e.into_inner());
+    if retained.is_empty() {
+        return Vec::new();
+    }
+    core::mem::take(&mut *retained)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_retained_by_steps_is_measured() {
+        let allocator = CountingAllocator::system();
+        let layout = Layout::from_size_align(64, 8).unwrap();
+        let id = ActivityId::from(9001);
+
+        // Safety: The layouts have a non-zero size, memory is freed with the layout it was allocated with
+        unsafe { allocator.dealloc(allocator.alloc(layout), layout) };
+        assert!(is_counting());
+        let ptr = measure_step(id, || unsafe { allocator.alloc(layout) });
+        let ptr = measure_step(id, || unsafe { allocator.realloc(ptr, layout, 96) });
+        measure_step(id, || unsafe { allocator.dealloc(ptr, Layout::from_size_align(96, 8).unwrap()) });
+
+        let retained: Vec<i64> = take_retained()
+            .into_iter()
+            .filter(|(activity, _)| *activity == id)
+            .map(|(_, bytes)| bytes)
+            .collect();
+        assert_eq!(retained, [64, 32, -96]);
+    }
+}
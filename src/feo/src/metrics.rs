@@ -0,0 +1,265 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Metrics of the primary agent in the Prometheus text format
+//!
+//! For long-running setups such as benchmarks, where traces are too heavyweight, a metrics server started
+//! with [serve] exposes the counters of the primary agent to Prometheus, and thus to Grafana dashboards:
+//!
+//! ```ignore
+//! let statistics = CycleStatistics::new();
+//! let metrics = Metrics::new(statistics.clone()).with_dropped_samples(recorder.id(), recorder.dropped_samples());
+//! metrics::serve("0.0.0.0:9464", metrics)?;
+//! // Pass `statistics` in the configuration of the primary agent
+//! ```
+//!
+//! The server answers `GET /metrics` with the current values of
+//!
+//! - `feo_cycle_duration_seconds`: summary of the cycle durations per task chain
+//! - `feo_cycle_start_jitter_seconds`: summary of the delays of the cycle starts per task chain
+//! - `feo_cycle_overruns_total`: number of overrunning cycles per task chain, see [overrun](crate::overrun)
+//! - `feo_step_duration_seconds`: summary of the step durations per activity
+//! - `feo_recorder_dropped_samples_total`: number of samples dropped by queued recorders per recorder
+//!   and topic, see [backpressure](crate::recording::backpressure)
+//! - `feo_trace_dropped_packets_total`: number of trace packets dropped in this process
+//!
+//! Task chains and activities are labelled with their numeric IDs. The values are taken from the
+//! [CycleStatistics] passed to the primary agent, so metrics are only collected if statistics are.
+
+use crate::error::Error;
+use crate::ids::ActivityId;
+use crate::recording::backpressure::DroppedSamples;
+use crate::statistics::{CycleStatistics, Histogram};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, error, info};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+
+/// Content type of the Prometheus text format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Quantiles of the summaries, in percent
+const QUANTILES: [u8; 3] = [50, 90, 99];
+
+/// Time after which a client not sending its request is disconnected
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sources of the metrics exposed by a metrics server
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    /// Cycle statistics collected by the scheduler
+    statistics: CycleStatistics,
+    /// Dropped samples of queued recorders
+    dropped_samples: Vec<(ActivityId, DroppedSamples)>,
+}
+
+impl Metrics {
+    /// Expose the given cycle statistics, which must be passed to the primary agent as well
+    pub fn new(statistics: CycleStatistics) -> Self {
+        Self {
+            statistics,
+            dropped_samples: Vec::new(),
+        }
+    }
+
+    /// Expose the samples dropped by the queue of the recorder with the given ID
+    pub fn with_dropped_samples(mut self, recorder: ActivityId, dropped: DroppedSamples) -> Self {
+        self.dropped_samples.push((recorder, dropped));
+        self
+    }
+
+    /// Render the current values of the metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let stats = self.statistics.snapshot();
+        let mut text = String::new();
+
+        header(&mut text, "feo_cycle_duration_seconds", "summary", "Durations of the cycles of the task chains");
+        for (id, chain) in stats.chains.iter() {
+            summary(&mut text, "feo_cycle_duration_seconds", &format!("chain=\"{}\"", id.id()), &chain.duration);
+        }
+        header(
+            &mut text,
+            "feo_cycle_start_jitter_seconds",
+            "summary",
+            "Delays of the cycle starts of the task chains after their planned start",
+        );
+        for (id, chain) in stats.chains.iter() {
+            let labels = format!("chain=\"{}\"", id.id());
+            summary(&mut text, "feo_cycle_start_jitter_seconds", &labels, &chain.start_jitter);
+        }
+        header(&mut text, "feo_cycle_overruns_total", "counter", "Overrunning cycles of the task chains");
+        for (id, chain) in stats.chains.iter() {
+            let _ = writeln!(text, "feo_cycle_overruns_total{{chain=\"{}\"}} {}", id.id(), chain.overruns);
+        }
+        header(&mut text, "feo_step_duration_seconds", "summary", "Durations of the steps of the activities");
+        for (id, steps) in stats.activities.iter() {
+            summary(&mut text, "feo_step_duration_seconds", &format!("activity=\"{}\"", id.id()), steps);
+        }
+        header(
+            &mut text,
+            "feo_recorder_dropped_samples_total",
+            "counter",
+            "Samples dropped by the queues of the recorders",
+        );
+        for (recorder, dropped) in self.dropped_samples.iter() {
+            for (topic, count) in dropped.counts() {
+                let _ = writeln!(
+                    text,
+                    "feo_recorder_dropped_samples_total{{recorder=\"{}\",topic=\"{}\"}} {count}",
+                    recorder.id(),
+                    escape(&topic)
+                );
+            }
+        }
+        header(&mut text, "feo_trace_dropped_packets_total", "counter", "Trace packets dropped in this process");
+        let _ = writeln!(text, "feo_trace_dropped_packets_total {}", feo_tracing::dropped_packets());
+        text
+    }
+}
+
+/// Serve the given metrics over HTTP on the given address, e.g. `0.0.0.0:9464`
+///
+/// Returns the address the server listens on, which tells the assigned port if port 0 is given.
+/// Clients are served one after the other by a background thread.
+pub fn serve(address: impl ToSocketAddrs, metrics: Metrics) -> Result<SocketAddr, Error> {
+    let listener =
+        TcpListener::bind(address).map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to bind metrics listener")))?;
+    let address = listener
+        .local_addr()
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to get metrics listener address")))?;
+    thread::Builder::new()
+        .name("feo-metrics".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        error!("Failed to accept metrics client: {:?}", ScoreDebugIoError(e));
+                        continue;
+                    },
+                };
+                if let Err(e) = handle_client(stream, &metrics) {
+                    debug!("Failed to serve metrics client: {:?}", ScoreDebugIoError(e));
+                }
+            }
+        })
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn metrics server")))?;
+    info!("Metrics server listening on {}", address.to_string().as_str());
+    Ok(address)
+}
+
+/// Answer the HTTP request of a client
+fn handle_client(stream: TcpStream, metrics: &Metrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT.into()))?;
+    let mut writer = stream.try_clone()?;
+    let mut lines = BufReader::new(stream).lines();
+    let request = lines.next().transpose()?.unwrap_or_default();
+    // Skip the headers of the request
+    for line in lines.by_ref() {
+        if line?.is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        (Some("GET"), _) => ("404 Not Found", "not found, see /metrics\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    writer.write_all(body.as_bytes())?;
+    writer.flush()
+}
+
+/// Write the help and type lines of a metric
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {name} {help}");
+    let _ = writeln!(text, "# TYPE {name} {kind}");
+}
+
+/// Write the quantiles, sum and count of a histogram as summary
+fn summary(text: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    for percent in QUANTILES {
+        if let Some(value) = histogram.percentile(percent) {
+            let quantile = f64::from(percent) / 100.0;
+            let _ = writeln!(text, "{name}{{{labels},quantile=\"{quantile}\"}} {}", value.as_secs_f64());
+        }
+    }
+    let _ = writeln!(text, "{name}_sum{{{labels}}} {}", histogram.sum().as_secs_f64());
+    let _ = writeln!(text, "{name}_count{{{labels}}} {}", histogram.count());
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_served_in_prometheus_text_format() {
+        use crate::ids::ChainId;
+        use std::io::Read;
+
+        let statistics = CycleStatistics::new();
+        statistics.record_cycle(ChainId::new(0), Duration::from_micros(20), Duration::from_millis(4));
+        statistics.record_cycle(ChainId::new(0), Duration::from_micros(40), Duration::from_millis(12));
+        statistics.record_overrun(ChainId::new(0));
+        statistics.record_step(ActivityId::new(3), Duration::from_micros(1500));
+        let dropped = DroppedSamples::default();
+        dropped.add("camera/\"front\"");
+        let metrics = Metrics::new(statistics).with_dropped_samples(ActivityId::new(9), dropped);
+
+        let text = metrics.render();
+        for line in [
+            "# TYPE feo_cycle_duration_seconds summary",
+            "feo_cycle_duration_seconds_sum{chain=\"0\"} 0.016",
+            "feo_cycle_duration_seconds_count{chain=\"0\"} 2",
+            "feo_cycle_duration_seconds{chain=\"0\",quantile=\"0.99\"} 0.012",
+            "feo_cycle_start_jitter_seconds_count{chain=\"0\"} 2",
+            "feo_cycle_overruns_total{chain=\"0\"} 1",
+            "feo_step_duration_seconds_count{activity=\"3\"} 1",
+            "feo_recorder_dropped_samples_total{recorder=\"9\",topic=\"camera/\\\"front\\\"\"} 1",
+            "# TYPE feo_trace_dropped_packets_total counter",
+        ] {
+            assert!(text.lines().any(|rendered| rendered == line), "missing {line} in\n{text}");
+        }
+
+        let address = serve("127.0.0.1:0", metrics).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.ends_with(&text), "{response}");
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"), "{response}");
+    }
+}
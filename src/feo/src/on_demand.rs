@@ -0,0 +1,155 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Task chains cycled on demand
+//!
+//! A task chain marked with [TaskChains::with_on_demand](crate::chain::TaskChains::with_on_demand)
+//! does not cycle periodically. Instead, it runs one cycle per trigger fired on the [CycleTrigger]
+//! obtained from the primary agent, e.g. from [Primary::cycle_trigger](crate::agent::direct::primary::Primary::cycle_trigger).
+//! A chain can also be triggered by each sample arriving on a topic with [CycleTrigger::on_arrival],
+//! e.g. to process every frame of a camera instead of free-running.
+//!
+//! Triggers fired while a cycle of the chain is running are queued and start the next cycles back to back,
+//! up to [MAX_PENDING] queued triggers per chain. The cycle time configured for the chain is the maximum
+//! duration of a cycle, an exceeded cycle is reported like for periodic chains. The start jitter recorded
+//! in the [statistics](crate::statistics) is the delay of the start after the trigger.
+//!
+//! Waiting for a trigger never raises the receive timeout of the scheduler, which only applies while
+//! activities are stepped. A [Recorder](crate::recording::recorder::Recorder) in an on-demand chain
+//! records one cycle per trigger, so [Replayer::run_triggered](crate::recording::replayer::Replayer::run_triggered)
+//! reproduces the triggers with their recorded timing.
+
+use crate::chain::MAIN_CHAIN;
+use crate::ids::ChainId;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use feo_com::topic::Topic;
+use feo_time::{Duration, Instant};
+use score_log::warn;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Maximum number of queued triggers per task chain
+///
+/// Further triggers are dropped until the chain catches up.
+pub const MAX_PENDING: usize = 16;
+
+/// Handle triggering cycles of on-demand task chains, shared with the scheduler
+#[derive(Debug, Clone, Default)]
+pub struct CycleTrigger(Arc<TriggerState>);
+
+#[derive(Debug, Default)]
+struct TriggerState {
+    /// Times of the queued triggers per task chain, oldest first
+    pending: Mutex<BTreeMap<ChainId, VecDeque<Instant>>>,
+    /// Notified on each fired trigger
+    fired: Condvar,
+}
+
+impl CycleTrigger {
+    /// Create a handle without any triggers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trigger a cycle of the [MAIN_CHAIN]
+    pub fn trigger_cycle(&self) {
+        self.trigger_chain(MAIN_CHAIN);
+    }
+
+    /// Trigger a cycle of the given task chain
+    ///
+    /// Triggers of chains not cycled on demand are ignored.
+    pub fn trigger_chain(&self, chain: ChainId) {
+        let mut pending = self.lock();
+        let queue = pending.entry(chain).or_default();
+        if queue.len() >= MAX_PENDING {
+            warn!("Dropping trigger of task chain {}, {} triggers pending", chain, MAX_PENDING);
+            return;
+        }
+        queue.push_back(Instant::now());
+        self.0.fired.notify_all();
+    }
+
+    /// Trigger a cycle of the given task chain on each sample arriving on the given topic
+    ///
+    /// Only samples sent in this process are observed, see [feo_com::arrival].
+    pub fn on_arrival<T>(&self, topic: Topic<'_, T>, chain: ChainId) {
+        let trigger = self.clone();
+        feo_com::arrival::on_arrival(topic, move || trigger.trigger_chain(chain));
+    }
+
+    /// Take the oldest queued trigger of the given task chain, returning the time it was fired
+    pub(crate) fn take(&self, chain: ChainId) -> Option<Instant> {
+        self.lock().get_mut(&chain).and_then(VecDeque::pop_front)
+    }
+
+    /// Whether a trigger of any of the given task chains is queued
+    pub(crate) fn is_pending(&self, chains: &[ChainId]) -> bool {
+        Self::any_pending(&self.lock(), chains)
+    }
+
+    /// Wait until a trigger of any of the given task chains is queued, at most for the given duration
+    pub(crate) fn wait(&self, chains: &[ChainId], timeout: Duration) {
+        let pending = self.lock();
+        let _ = feo_time::wait_timeout_while(&self.0.fired, pending, timeout, |pending| {
+            !Self::any_pending(pending, chains)
+        });
+    }
+
+    fn any_pending(pending: &BTreeMap<ChainId, VecDeque<Instant>>, chains: &[ChainId]) -> bool {
+        chains
+            .iter()
+            .any(|chain| pending.get(chain).is_some_and(|queue| !queue.is_empty()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<ChainId, VecDeque<Instant>>> {
+        self.0.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggers_are_queued_per_chain() {
+        let trigger = CycleTrigger::new();
+        let camera = ChainId::new(1);
+        assert!(!trigger.is_pending(&[MAIN_CHAIN, camera]));
+
+        trigger.trigger_cycle();
+        trigger.trigger_cycle();
+        assert!(trigger.is_pending(&[MAIN_CHAIN]));
+        assert!(!trigger.is_pending(&[camera]));
+        assert!(trigger.take(camera).is_none());
+        assert!(trigger.take(MAIN_CHAIN).is_some());
+        assert!(trigger.take(MAIN_CHAIN).is_some());
+        assert!(trigger.take(MAIN_CHAIN).is_none());
+
+        // Triggers beyond the maximum are dropped
+        for _ in 0..MAX_PENDING + 3 {
+            trigger.trigger_chain(camera);
+        }
+        assert_eq!((0..).take_while(|_| trigger.take(camera).is_some()).count(), MAX_PENDING);
+
+        // A trigger fired from another thread ends the wait
+        let remote = trigger.clone();
+        let waiter = std::thread::spawn(move || {
+            remote.wait(&[camera], Duration::from_millis(10_000));
+            remote.take(camera).is_some()
+        });
+        std::thread::sleep(core::time::Duration::from_millis(10));
+        trigger.trigger_chain(camera);
+        assert!(waiter.join().unwrap());
+    }
+}
@@ -0,0 +1,140 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Reaction of task chains to overrunning cycles
+//!
+//! A cycle of a periodic task chain overruns if it takes at least the cycle time of the chain. By default,
+//! the next cycle starts immediately, catching up with the missed period. With
+//! [TaskChains::with_overrun_policy](crate::chain::TaskChains::with_overrun_policy), a chain instead skips
+//! the cycles whose start has passed, or drops to a degraded rate until its cycles fit into the cycle time
+//! again, see [OverrunPolicy].
+//!
+//! The [OverrunOutcome] of each overrunning cycle is logged, emitted as `overrun` event in the span of the
+//! cycle in traces, and captured as [Record::Overrun](crate::recording::Record::Overrun) for recorders
+//! configured with [signals](crate::recording::signals).
+//!
+//! On-demand task chains start their cycles on triggers, so their overruns are reported without a policy
+//! being applied.
+
+use core::fmt;
+use feo_time::{Duration, Instant};
+use score_log::ScoreDebug;
+use serde::{Deserialize, Serialize};
+
+/// Reaction of a periodic task chain to an overrunning cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ScoreDebug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrunPolicy {
+    /// Start the next cycle immediately, catching up with the missed period
+    #[default]
+    RunImmediately,
+    /// Skip the cycles whose start has passed and start at the next period boundary, keeping the phase
+    SkipNext,
+    /// Run with the given multiple of the cycle time until a cycle fits into the cycle time again
+    Degrade(u32),
+}
+
+/// Outcome of an overrunning cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScoreDebug, Serialize, Deserialize)]
+pub enum OverrunOutcome {
+    /// The next cycle started immediately
+    CaughtUp,
+    /// The given number of cycles were skipped
+    Skipped(u32),
+    /// The chain runs with the given multiple of its cycle time
+    Degraded(u32),
+}
+
+impl OverrunOutcome {
+    /// Get the name of the outcome as used in exports and traces
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverrunOutcome::CaughtUp => "caught_up",
+            OverrunOutcome::Skipped(_) => "skipped",
+            OverrunOutcome::Degraded(_) => "degraded",
+        }
+    }
+}
+
+impl fmt::Display for OverrunOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrunOutcome::CaughtUp => write!(f, "caught up"),
+            OverrunOutcome::Skipped(cycles) => write!(f, "skipped {cycles} cycles"),
+            OverrunOutcome::Degraded(factor) => write!(f, "degraded to {factor}x cycle time"),
+        }
+    }
+}
+
+/// Plan the next start of a periodic task chain after the cycle started at `cycle_start` finished at `now`
+///
+/// Returns the next start and, if the cycle overran, the outcome of the given policy.
+pub(crate) fn plan(
+    policy: OverrunPolicy,
+    cycle_start: Instant,
+    cycle_time: Duration,
+    now: Instant,
+) -> (Instant, Option<OverrunOutcome>) {
+    let duration = now.saturating_duration_since(cycle_start);
+    if duration < cycle_time {
+        return (cycle_start + cycle_time, None);
+    }
+
+    match policy {
+        OverrunPolicy::RunImmediately => (now, Some(OverrunOutcome::CaughtUp)),
+        OverrunPolicy::SkipNext => {
+            let periods = duration.as_nanos() / cycle_time.as_nanos().max(1) + 1;
+            let periods = u32::try_from(periods).unwrap_or(u32::MAX);
+            (cycle_start + cycle_time * periods, Some(OverrunOutcome::Skipped(periods - 1)))
+        },
+        OverrunPolicy::Degrade(factor) => {
+            let factor = factor.max(1);
+            let start = cycle_start + cycle_time * factor;
+            (if start > now { start } else { now }, Some(OverrunOutcome::Degraded(factor)))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrun_policies_plan_the_next_cycle() {
+        let start = Instant::now();
+        let cycle_time = Duration::from_millis(10);
+
+        let on_time = start + Duration::from_millis(4);
+        for policy in [OverrunPolicy::RunImmediately, OverrunPolicy::SkipNext, OverrunPolicy::Degrade(4)] {
+            assert_eq!(plan(policy, start, cycle_time, on_time), (start + cycle_time, None));
+        }
+
+        let overrun = start + Duration::from_millis(25);
+        assert_eq!(
+            plan(OverrunPolicy::RunImmediately, start, cycle_time, overrun),
+            (overrun, Some(OverrunOutcome::CaughtUp))
+        );
+        assert_eq!(
+            plan(OverrunPolicy::SkipNext, start, cycle_time, overrun),
+            (start + Duration::from_millis(30), Some(OverrunOutcome::Skipped(2)))
+        );
+        assert_eq!(
+            plan(OverrunPolicy::Degrade(4), start, cycle_time, overrun),
+            (start + Duration::from_millis(40), Some(OverrunOutcome::Degraded(4)))
+        );
+        assert_eq!(
+            plan(OverrunPolicy::Degrade(2), start, cycle_time, overrun),
+            (overrun, Some(OverrunOutcome::Degraded(2)))
+        );
+    }
+}
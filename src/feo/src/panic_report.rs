@@ -0,0 +1,217 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Capture of activity panics and their forwarding to the primary agent
+//!
+//! Workers catch panics in the lifecycle methods of their activities and report the method as failed,
+//! so the error policy of the primary agent applies to panics like to returned errors. Secondary agents
+//! additionally install a panic hook with [install], which notes the message, the location and a
+//! condensed backtrace of each panic of an activity as a [PanicReport]. The workers send the reports to
+//! the primary agent as [Signal::ActivityPanicked](crate::signalling::common::signals::Signal) before
+//! the failure of the method, so the root cause is logged by the primary agent and noted in recordings
+//! capturing the scheduling signals, see [Record::Panic](crate::recording::Record::Panic).
+//!
+//! Reports are truncated to fit into a signal. Backtraces are only captured if enabled with
+//! `RUST_BACKTRACE`, see [Backtrace::capture].
+
+use crate::error::ActivityError;
+use crate::ids::ActivityId;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::panic::AssertUnwindSafe;
+use feo_tracing::bounded::BoundedStr;
+use score_log::fmt::{FormatSpec, ScoreDebug, ScoreWrite};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic;
+use std::sync::{Mutex, Once};
+
+/// Maximum length of the message of a [PanicReport] in bytes, longer messages are truncated
+pub const PANIC_MESSAGE_CAPACITY: usize = 96;
+
+/// Maximum length of the condensed backtrace of a [PanicReport] in bytes, longer backtraces are truncated
+pub const PANIC_BACKTRACE_CAPACITY: usize = 144;
+
+/// Prefixes of the frames of the panic machinery left out of condensed backtraces
+const SKIPPED_FRAMES: [&str; 6] = ["std::", "core::", "alloc::", "rust_begin_unwind", "__rust", "feo::panic_report"];
+
+/// Panics of activities in this process, not yet reported to the scheduler
+static REPORTS: Mutex<Vec<PanicReport>> = Mutex::new(Vec::new());
+
+/// Panic of an activity reported to the primary agent
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PanicReport {
+    /// Activity which panicked
+    pub activity: ActivityId,
+    /// Message and location of the panic
+    message: BoundedStr<PANIC_MESSAGE_CAPACITY>,
+    /// Condensed backtrace, empty if not captured
+    backtrace: BoundedStr<PANIC_BACKTRACE_CAPACITY>,
+}
+
+impl PanicReport {
+    /// Create a report, truncating the message and backtrace to their capacities
+    pub fn new(activity: ActivityId, message: &str, backtrace: &str) -> Self {
+        Self {
+            activity,
+            message: BoundedStr::truncated(message),
+            backtrace: BoundedStr::truncated(backtrace),
+        }
+    }
+
+    /// Create a report from its encoded parts, returns `None` if they are invalid
+    pub(crate) fn from_parts(activity: ActivityId, message: &[u8], backtrace: &[u8]) -> Option<Self> {
+        Some(Self {
+            activity,
+            message: BoundedStr::from_utf8(message)?,
+            backtrace: BoundedStr::from_utf8(backtrace)?,
+        })
+    }
+
+    /// Message and location of the panic
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    /// Condensed backtrace of the panic, innermost frame first, empty if not captured
+    pub fn backtrace(&self) -> &str {
+        self.backtrace.as_str()
+    }
+}
+
+impl fmt::Debug for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicReport")
+            .field("activity", &self.activity)
+            .field("message", &self.message())
+            .field("backtrace", &self.backtrace())
+            .finish()
+    }
+}
+
+impl ScoreDebug for PanicReport {
+    fn fmt(&self, f: &mut dyn ScoreWrite, spec: &FormatSpec) -> Result<(), score_log::fmt::Error> {
+        f.write_str("PanicReport(", spec)?;
+        ScoreDebug::fmt(&self.activity, f, spec)?;
+        f.write_str(", ", spec)?;
+        f.write_str(self.message(), spec)?;
+        f.write_str(")", spec)
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "activity {} panicked: {}", self.activity, self.message())?;
+        if !self.backtrace().is_empty() {
+            write!(f, " in {}", self.backtrace())?;
+        }
+        Ok(())
+    }
+}
+
+/// Install the panic hook noting the panics of activities for the workers of this process
+///
+/// The previously installed hook, by default printing the panic to stderr, is still called.
+/// The hook is installed once per process, further calls have no effect.
+pub fn install() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(install_hook);
+}
+
+/// Install the panic hook, chaining the previously installed hook
+fn install_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Some(activity) = crate::dlt::ActivityScope::current() {
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("panic without message");
+            let message = match info.location() {
+                Some(location) => format!("{message} at {}:{}", location.file(), location.line()),
+                None => String::from(message),
+            };
+            let backtrace = condense(&Backtrace::capture());
+            let mut reports = REPORTS.lock().unwrap_or_else(|e| e.into_inner());
+            reports.push(PanicReport::new(activity, &message, &backtrace));
+        }
+        previous(info);
+    }));
+}
+
+/// Run a lifecycle method of an activity, failing with the given error if the method panics
+pub(crate) fn catch(
+    error: ActivityError,
+    method: impl FnOnce() -> Result<(), ActivityError>,
+) -> Result<(), ActivityError> {
+    panic::catch_unwind(AssertUnwindSafe(method)).unwrap_or(Err(error))
+}
+
+/// Take the panic reports noted in this process since the last call
+pub(crate) fn take() -> Vec<PanicReport> {
+    let mut reports = REPORTS.lock().unwrap_or_else(|e| e.into_inner());
+    if reports.is_empty() {
+        return Vec::new();
+    }
+    core::mem::take(&mut *reports)
+}
+
+/// Condense a backtrace into the names of its frames outside of the panic machinery, innermost first
+fn condense(backtrace: &Backtrace) -> String {
+    if backtrace.status() != BacktraceStatus::Captured {
+        return String::new();
+    }
+    // Frames are printed as `<index>: <function>`, followed by their location on a line of its own
+    let frames = format!("{backtrace}");
+    let names: Vec<&str> = frames
+        .lines()
+        .filter_map(|line| line.trim().split_once(": "))
+        .filter(|(index, _)| index.bytes().all(|byte| byte.is_ascii_digit()))
+        .map(|(_, name)| name)
+        .filter(|name| !SKIPPED_FRAMES.iter().any(|prefix| name.starts_with(prefix)))
+        .collect();
+    names.join(" < ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_of_activities_are_reported() {
+        use crate::dlt::ActivityScope;
+
+        install();
+        let activity = ActivityId::new(7);
+        let result = {
+            let _scope = ActivityScope::enter(activity);
+            catch(ActivityError::Step, || panic!("sensor {} disconnected", 3))
+        };
+        assert_eq!(result, Err(ActivityError::Step));
+        assert_eq!(catch(ActivityError::Step, || Ok(())), Ok(()));
+
+        let reports = take();
+        let report = reports.iter().find(|report| report.activity == activity).unwrap();
+        assert!(report.message().starts_with("sensor 3 disconnected at "));
+        assert!(report.message().contains("panic_report.rs"));
+
+        let long = "ü".repeat(PANIC_MESSAGE_CAPACITY);
+        let report = PanicReport::new(activity, &long, "");
+        assert_eq!(report.message().len(), PANIC_MESSAGE_CAPACITY);
+        assert_eq!(PanicReport::from_parts(activity, report.message().as_bytes(), b""), Some(report));
+    }
+}
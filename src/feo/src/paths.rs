@@ -0,0 +1,72 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Runtime paths of FEO applications
+//!
+//! All runtime files are resolved in the order command line flag, environment variable and default
+//! in the runtime directory, see [feo_tracing::paths]. Under Bazel, `bazel test` sets `TMPDIR` to
+//! a sandbox directory per test, so tests running in parallel do not share their sockets unless
+//! configured to.
+
+use alloc::format;
+use std::env;
+use std::path::{Path, PathBuf};
+
+pub use feo_tracing::paths::{resolve, runtime_dir, tracer_address, tracer_shm_socket, RUNTIME_DIR_ENV};
+
+/// Environment variable of the directory of the signalling sockets
+pub const SOCKET_DIR_ENV: &str = "FEO_SOCKET_DIR";
+
+/// Environment variable of the directory of recordings
+pub const RECORDING_DIR_ENV: &str = "FEO_RECORDING_DIR";
+
+/// Get the path of the signalling socket of the given name, e.g. `listener1`
+///
+/// Without a flag, the socket `feo_<name>.socket` is placed in the directory given by [SOCKET_DIR_ENV]
+/// or in the runtime directory.
+pub fn signalling_socket(flag: Option<&Path>, name: &str) -> PathBuf {
+    in_dir(flag, SOCKET_DIR_ENV, &format!("feo_{name}.socket"))
+}
+
+/// Get the path of the recording of the given name, e.g. `rec_0001.bin`
+///
+/// Without a flag, the recording is placed in the directory given by [RECORDING_DIR_ENV]
+/// or in the runtime directory.
+pub fn recording(flag: Option<&Path>, name: &str) -> PathBuf {
+    in_dir(flag, RECORDING_DIR_ENV, name)
+}
+
+/// Resolve the path of a file in the directory given by the environment variable of the given name
+fn in_dir(flag: Option<&Path>, dir_env: &str, file_name: &str) -> PathBuf {
+    match flag {
+        Some(path) => path.to_path_buf(),
+        None => env::var_os(dir_env)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(runtime_dir)
+            .join(file_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_take_precedence() {
+        let flag = Path::new("/run/feo/custom.socket");
+        assert_eq!(signalling_socket(Some(flag), "listener1"), flag);
+        assert_eq!(recording(Some(flag), "rec_0001.bin"), flag);
+        assert_eq!(signalling_socket(None, "listener1").file_name().unwrap(), "feo_listener1.socket");
+    }
+}
@@ -0,0 +1,347 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Bounded queue between the recorder and its output
+//!
+//! By default, the [Recorder](crate::recording::recorder::Recorder) writes each cycle to its output within
+//! its step, so a slow output delays the step of the recorder and thereby its task chain.
+//! With [Recorder::with_queue](crate::recording::recorder::Recorder::with_queue), the cycles are passed to
+//! a background thread writing them to the output instead. The queue holds a bounded number of samples.
+//! When it is full, the [BackpressurePolicy] of the topic of each new sample decides:
+//!
+//! - [BackpressurePolicy::Block]: wait until the output has caught up, as without a queue
+//! - [BackpressurePolicy::DropNewest]: drop the new sample
+//! - [BackpressurePolicy::DropOldest]: drop the oldest queued sample of the same topic to make room
+//!
+//! Cycle records, signals and states are never dropped. Dropped samples are counted per topic in
+//! [DroppedSamples], logged, and noted in the recording as [Record::Dropped]s.
+
+use crate::error::{Error, RecordingError};
+use crate::recording::recorder::CycleWriter;
+use crate::recording::sink::RecordSink;
+use crate::recording::Record;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+/// Reaction of the recorder to a new sample of a topic while its queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait until there is room in the queue, delaying the task chain of the recorder
+    #[default]
+    Block,
+    /// Drop the new sample
+    DropNewest,
+    /// Drop the oldest queued sample of the same topic, or the new sample if none is queued
+    DropOldest,
+}
+
+/// Numbers of samples dropped per topic, shared between the recorder and the application
+#[derive(Debug, Clone, Default)]
+pub struct DroppedSamples {
+    counts: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl DroppedSamples {
+    /// Get the number of dropped samples of the given topic
+    pub fn count(&self, topic: &str) -> u64 {
+        self.lock().get(topic).copied().unwrap_or_default()
+    }
+
+    /// Get the numbers of dropped samples of all topics with dropped samples
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.lock().clone()
+    }
+
+    /// Count a dropped sample of the given topic, returning the number of dropped samples of the topic
+    pub(crate) fn add(&self, topic: &str) -> u64 {
+        let mut counts = self.lock();
+        let count = counts.entry(topic.to_string()).or_default();
+        *count += 1;
+        *count
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, u64>> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sample of a topic queued for writing
+#[derive(Debug)]
+pub(crate) struct QueuedSample {
+    /// Index of the topic in the recorder
+    pub(crate) topic: usize,
+    /// Data record of the sample
+    pub(crate) record: Record,
+}
+
+/// Records of a single cycle queued for writing
+#[derive(Debug, Default)]
+pub(crate) struct QueuedCycle {
+    /// Time since startup of the primary agent
+    pub(crate) timestamp: Duration,
+    /// Samples of the recorded topics
+    pub(crate) samples: Vec<QueuedSample>,
+    /// Records following the samples, i.e. signals, states and dropped samples
+    pub(crate) trailer: Vec<Record>,
+}
+
+/// Queue of cycles written by a background thread
+pub(crate) struct RecordQueue {
+    /// State shared with the writer thread
+    shared: Arc<Shared>,
+    /// Maximum number of queued samples
+    capacity: usize,
+    /// Writer thread, returning the result of writing the recording
+    thread: Option<JoinHandle<Result<(), Error>>>,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    /// Notified whenever a cycle is queued, a cycle is taken or the queue is closed
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    cycles: VecDeque<QueuedCycle>,
+    /// Number of samples in the queued cycles
+    samples: usize,
+    /// Whether no more cycles are queued
+    closed: bool,
+    /// Whether the writer thread stopped after failing to write
+    failed: bool,
+}
+
+impl RecordQueue {
+    /// Start the thread writing the queued cycles to the given writer
+    pub(crate) fn spawn<W: RecordSink + Send + 'static>(
+        writer: CycleWriter<W>,
+        capacity: usize,
+    ) -> Result<Self, Error> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState::default()),
+            changed: Condvar::new(),
+        });
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("feo-recorder".to_string())
+            .spawn(move || write_queued(writer, &thread_shared))
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn recorder thread")))?;
+        Ok(Self {
+            shared,
+            capacity: capacity.max(1),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue the given cycle, applying the policies of the topics of its samples while the queue is full
+    ///
+    /// `dropped` is called with the topic index of each dropped sample.
+    pub(crate) fn push(
+        &mut self,
+        cycle: QueuedCycle,
+        policy: impl Fn(usize) -> BackpressurePolicy,
+        mut dropped: impl FnMut(usize),
+    ) -> Result<(), Error> {
+        let QueuedCycle {
+            timestamp,
+            samples,
+            trailer,
+        } = cycle;
+        let mut state = self.shared.lock();
+        let mut kept = Vec::with_capacity(samples.len());
+        for sample in samples {
+            loop {
+                if state.failed {
+                    return Err(Error::Recording(RecordingError::Failed("recorder thread failed to write recording")));
+                }
+                if state.samples + kept.len() < self.capacity {
+                    kept.push(sample);
+                    break;
+                }
+                match policy(sample.topic) {
+                    BackpressurePolicy::Block => {
+                        state = self.shared.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+                    },
+                    BackpressurePolicy::DropNewest => {
+                        dropped(sample.topic);
+                        break;
+                    },
+                    BackpressurePolicy::DropOldest => {
+                        dropped(sample.topic);
+                        if !state.remove_oldest(sample.topic) {
+                            break;
+                        }
+                    },
+                }
+            }
+        }
+        state.samples += kept.len();
+        state.cycles.push_back(QueuedCycle {
+            timestamp,
+            samples: kept,
+            trailer,
+        });
+        self.shared.changed.notify_all();
+        Ok(())
+    }
+
+    /// Close the queue and wait until all queued cycles have been written and the output flushed
+    pub(crate) fn close(mut self) -> Result<(), Error> {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_all();
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(Error::Recording(RecordingError::Failed("recorder thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for RecordQueue {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.shared.lock().closed = true;
+            self.shared.changed.notify_all();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl QueueState {
+    /// Remove the oldest queued sample of the given topic, returning whether there was one
+    fn remove_oldest(&mut self, topic: usize) -> bool {
+        for cycle in self.cycles.iter_mut() {
+            if let Some(index) = cycle.samples.iter().position(|sample| sample.topic == topic) {
+                cycle.samples.remove(index);
+                self.samples -= 1;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Main function of the writer thread
+fn write_queued<W: RecordSink>(mut writer: CycleWriter<W>, shared: &Shared) -> Result<(), Error> {
+    loop {
+        let cycle = {
+            let mut state = shared.lock();
+            while state.cycles.is_empty() && !state.closed {
+                state = shared.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            let Some(cycle) = state.cycles.pop_front() else {
+                break;
+            };
+            state.samples -= cycle.samples.len();
+            shared.changed.notify_all();
+            cycle
+        };
+        if let Err(e) = writer.write_cycle(cycle) {
+            shared.lock().failed = true;
+            shared.changed.notify_all();
+            return Err(e);
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_queue_applies_policies() {
+        use crate::recording::compression::Compression;
+        use std::io::Write;
+        use std::sync::mpsc;
+
+        /// Output blocking each write until released
+        struct Gated(mpsc::Receiver<()>);
+
+        impl Write for Gated {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let _ = self.0.recv();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let sample = |topic: usize| QueuedSample {
+            topic,
+            record: Record::Data {
+                timestamp: Duration::ZERO,
+                topic: alloc::format!("topic{topic}"),
+                type_name: "u8".to_string(),
+                data: alloc::vec![topic as u8],
+            },
+        };
+        let cycle = |samples: Vec<QueuedSample>| QueuedCycle {
+            samples,
+            ..Default::default()
+        };
+        let policy = |topic: usize| match topic {
+            0 => BackpressurePolicy::DropNewest,
+            _ => BackpressurePolicy::DropOldest,
+        };
+
+        let (release, gate) = mpsc::channel();
+        let writer = CycleWriter::new(Gated(gate), Compression::None);
+        let mut queue = RecordQueue::spawn(writer, 2).unwrap();
+        let mut dropped = Vec::new();
+
+        // The first cycle is taken by the writer thread, which then waits for the gate
+        queue.push(cycle(alloc::vec![]), policy, |_| {}).unwrap();
+        while !queue.shared.lock().cycles.is_empty() {
+            thread::yield_now();
+        }
+        queue
+            .push(cycle(alloc::vec![sample(0), sample(1)]), policy, |topic| {
+                dropped.push(topic)
+            })
+            .unwrap();
+        queue
+            .push(cycle(alloc::vec![sample(0), sample(1)]), policy, |topic| {
+                dropped.push(topic)
+            })
+            .unwrap();
+        assert_eq!(dropped, alloc::vec![0, 1]);
+        {
+            let state = queue.shared.lock();
+            assert_eq!(state.samples, 2);
+            let topics: Vec<Vec<usize>> = state
+                .cycles
+                .iter()
+                .map(|cycle| cycle.samples.iter().map(|sample| sample.topic).collect())
+                .collect();
+            assert_eq!(topics, alloc::vec![alloc::vec![0], alloc::vec![1]]);
+        }
+
+        drop(release);
+        queue.close().unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Checkpoints of the states of activities in recordings
+//!
+//! Activities implementing [Activity::save_state] and [Activity::restore_state] can be checkpointed.
+//! With [StateCheckpoints] passed to the configuration of an agent, its workers save the states
+//! of their activities after every `interval`-th step of each activity. A [Recorder](crate::recording::recorder::Recorder)
+//! holding a clone of the checkpoints writes the saved states as [Record::State]s following the [Record::Cycle]
+//! of its step.
+//!
+//! To replay a recording from the middle instead of from its first cycle, skip the replayer to the cycle to start from
+//! with [Replayer::skip_to](crate::recording::replayer::Replayer::skip_to), which returns the latest states recorded
+//! before. Passed to the agents with [StateCheckpoints::restoring], the workers restore these states right after
+//! the startup of the activities.
+//!
+//! The checkpoints are shared in memory, so the recorder must run in the same agent as the checkpointed activities.
+
+use crate::activity::Activity;
+use crate::error::ActivityError;
+use crate::ids::ActivityId;
+use crate::recording::Record;
+use crate::timestamp::timestamp;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use score_log::{debug, error};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Maximum number of saved states kept until taken by the recorder
+///
+/// The oldest states are dropped if the recorder does not keep up or is not running.
+const MAX_PENDING: usize = 1024;
+
+/// States of activities at a point of a recording
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Time of the recorded cycle the checkpoint was taken for
+    pub timestamp: Duration,
+    /// Latest recorded step count and state per activity
+    pub states: HashMap<ActivityId, (u64, Vec<u8>)>,
+}
+
+impl Checkpoint {
+    /// Update the checkpoint with a record, keeping the latest state per activity
+    pub fn update(&mut self, record: &Record) {
+        if let Record::State {
+            activity, cycle, state, ..
+        } = record
+        {
+            self.states.insert(ActivityId::new(*activity), (*cycle, state.clone()));
+        }
+    }
+}
+
+/// Handle to the checkpoints of the activities of an agent
+///
+/// Clones share the same checkpoints.
+#[derive(Debug, Clone, Default)]
+pub struct StateCheckpoints {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Number of steps between two saved states of an activity, not saving any states if zero
+    interval: u64,
+    /// Number of steps per activity since its startup
+    steps: HashMap<ActivityId, u64>,
+    /// Saved states not yet taken by the recorder
+    pending: VecDeque<Record>,
+    /// States to restore after the startup of the activities
+    restore: HashMap<ActivityId, Vec<u8>>,
+}
+
+impl StateCheckpoints {
+    /// Save the states of the activities after every `interval`-th step
+    pub fn new(interval: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                interval,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Restore the states of the given checkpoint after the startup of the activities
+    ///
+    /// The step counts of the restored activities continue from the checkpoint.
+    pub fn restoring(self, checkpoint: Checkpoint) -> Self {
+        let mut inner = self.inner.lock().expect("poisoned lock");
+        for (activity, (steps, state)) in checkpoint.states {
+            inner.steps.insert(activity, steps);
+            inner.restore.insert(activity, state);
+        }
+        drop(inner);
+        self
+    }
+
+    /// Restore the state of the given activity after its startup, if any
+    pub(crate) fn restore(&self, id: ActivityId, activity: &mut dyn Activity) -> Result<(), ActivityError> {
+        let Some(state) = self.inner.lock().expect("poisoned lock").restore.remove(&id) else {
+            return Ok(());
+        };
+        debug!("Restoring state of activity {} ({} bytes)", id, state.len());
+        activity.restore_state(&state).inspect_err(|e| {
+            error!("Activity {} failed to restore its state: {:?}", id, e);
+        })
+    }
+
+    /// Count a step of the given activity and save its state if due
+    pub(crate) fn stepped(&self, id: ActivityId, activity: &dyn Activity) {
+        let mut inner = self.inner.lock().expect("poisoned lock");
+        let interval = inner.interval;
+        let steps = inner.steps.entry(id).or_default();
+        *steps += 1;
+        let cycle = *steps;
+        if interval == 0 || cycle % interval != 0 {
+            return;
+        }
+        // Do not hold the lock while saving, other workers may step their activities meanwhile
+        drop(inner);
+
+        let state = activity.save_state();
+        if state.is_empty() {
+            return;
+        }
+        let mut inner = self.inner.lock().expect("poisoned lock");
+        if inner.pending.len() == MAX_PENDING {
+            inner.pending.pop_front();
+        }
+        inner.pending.push_back(Record::State {
+            timestamp: timestamp().0,
+            activity: id.id(),
+            cycle,
+            state,
+        });
+    }
+
+    /// Take all states saved so far
+    pub(crate) fn take(&self) -> Vec<Record> {
+        self.inner.lock().expect("poisoned lock").pending.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_saved_and_restored() {
+        struct Counter(u64);
+
+        impl Activity for Counter {
+            fn id(&self) -> ActivityId {
+                ActivityId::new(1)
+            }
+
+            fn startup(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+
+            fn step(&mut self) -> Result<(), ActivityError> {
+                self.0 += 1;
+                Ok(())
+            }
+
+            fn shutdown(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+
+            fn save_state(&self) -> Vec<u8> {
+                self.0.to_le_bytes().to_vec()
+            }
+
+            fn restore_state(&mut self, state: &[u8]) -> Result<(), ActivityError> {
+                self.0 = u64::from_le_bytes(state.try_into().map_err(|_| ActivityError::Startup)?);
+                Ok(())
+            }
+        }
+
+        let id = ActivityId::new(1);
+        let checkpoints = StateCheckpoints::new(2);
+        let mut activity = Counter(0);
+        for _ in 0..5 {
+            activity.step().unwrap();
+            checkpoints.stepped(id, &activity);
+        }
+        let records = checkpoints.take();
+        assert_eq!(records.len(), 2);
+
+        let mut checkpoint = Checkpoint::default();
+        records.iter().for_each(|record| checkpoint.update(record));
+        assert_eq!(checkpoint.states[&id], (4, 4u64.to_le_bytes().to_vec()));
+
+        let restored = StateCheckpoints::new(2).restoring(checkpoint);
+        let mut activity = Counter(0);
+        restored.restore(id, &mut activity).unwrap();
+        assert_eq!(activity.0, 4);
+
+        // Step counts continue from the checkpoint
+        activity.step().unwrap();
+        restored.stepped(id, &activity);
+        activity.step().unwrap();
+        restored.stepped(id, &activity);
+        assert!(matches!(restored.take()[..], [Record::State { cycle: 6, .. }]));
+    }
+}
@@ -0,0 +1,172 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Encodings of recorded samples
+//!
+//! The recorder serializes the samples of its topics with the [RecordCodec] of the [Encoding] selected
+//! per recorder. The encoding of a recording is noted in its [header](crate::recording::header), so that
+//! readers decode the samples accordingly. Independent of the encoding, the records themselves remain
+//! COBS-framed postcard.
+//!
+//! - [Postcard] is the compact default, which can only be decoded with the recorded Rust types.
+//! - [Cbor] writes self-describing samples, which tools in other languages can decode without the types.
+//! - [RawBytes] copies the in-memory representation of the samples, which matches the layout of the
+//!   `#[repr(C)]` types shared with C++ through the com layer. It is restricted to plain data types.
+
+use crate::error::{Error, RecordingError};
+use alloc::vec::Vec;
+use core::mem::{needs_drop, size_of};
+use core::ptr;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Serialization of recorded samples
+pub trait RecordCodec {
+    /// Encode a sample
+    fn encode<T: Serialize + 'static>(sample: &T) -> Result<Vec<u8>, Error>;
+
+    /// Decode a sample encoded with [RecordCodec::encode]
+    fn decode<T: DeserializeOwned + 'static>(data: &[u8]) -> Result<T, Error>;
+}
+
+/// Codec of the [postcard] wire format
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Postcard;
+
+impl RecordCodec for Postcard {
+    fn encode<T: Serialize + 'static>(sample: &T) -> Result<Vec<u8>, Error> {
+        postcard::to_allocvec(sample)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to serialize sample")))
+    }
+
+    fn decode<T: DeserializeOwned + 'static>(data: &[u8]) -> Result<T, Error> {
+        postcard::from_bytes(data)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize sample")))
+    }
+}
+
+/// Codec of the CBOR format (RFC 8949)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cbor;
+
+impl RecordCodec for Cbor {
+    fn encode<T: Serialize + 'static>(sample: &T) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        ciborium::into_writer(sample, &mut data)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to serialize sample")))?;
+        Ok(data)
+    }
+
+    fn decode<T: DeserializeOwned + 'static>(data: &[u8]) -> Result<T, Error> {
+        ciborium::from_reader(data)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize sample")))
+    }
+}
+
+/// Codec copying the in-memory representation of samples
+///
+/// Only types without drop glue are accepted, which excludes types owning heap memory.
+/// Samples are decoded by copying the bytes back, so recordings must only be decoded with the type
+/// they were recorded with, as checked with the schema hashes of the [header](crate::recording::header).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawBytes;
+
+impl RecordCodec for RawBytes {
+    fn encode<T: Serialize + 'static>(sample: &T) -> Result<Vec<u8>, Error> {
+        if needs_drop::<T>() {
+            return Err(Error::Recording(RecordingError::Failed("raw bytes encoding requires a plain data type")));
+        }
+        let mut data = Vec::with_capacity(size_of::<T>());
+        // Safety: the sample is valid for reads of its size and the buffer has been allocated with that size.
+        // Plain data types are copied bytewise by the com layer as well.
+        unsafe {
+            ptr::copy_nonoverlapping(ptr::from_ref(sample).cast::<u8>(), data.as_mut_ptr(), size_of::<T>());
+            data.set_len(size_of::<T>());
+        }
+        Ok(data)
+    }
+
+    fn decode<T: DeserializeOwned + 'static>(data: &[u8]) -> Result<T, Error> {
+        if needs_drop::<T>() {
+            return Err(Error::Recording(RecordingError::Failed("raw bytes encoding requires a plain data type")));
+        }
+        if data.len() != size_of::<T>() {
+            return Err(Error::Recording(RecordingError::Failed("size of raw sample does not match its type")));
+        }
+        // Safety: the size has been checked and the bytes have been copied from a sample of the same type
+        Ok(unsafe { ptr::read_unaligned(data.as_ptr().cast::<T>()) })
+    }
+}
+
+/// Encoding of the samples of a recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Encoding {
+    /// Samples are encoded with [Postcard]
+    #[default]
+    Postcard,
+    /// Samples are encoded with [Cbor]
+    Cbor,
+    /// Samples are encoded with [RawBytes]
+    RawBytes,
+}
+
+impl Encoding {
+    /// Encode a sample with the codec of this encoding
+    pub fn encode<T: Serialize + 'static>(&self, sample: &T) -> Result<Vec<u8>, Error> {
+        match self {
+            Encoding::Postcard => Postcard::encode(sample),
+            Encoding::Cbor => Cbor::encode(sample),
+            Encoding::RawBytes => RawBytes::encode(sample),
+        }
+    }
+
+    /// Decode a sample with the codec of this encoding
+    pub fn decode<T: DeserializeOwned + 'static>(&self, data: &[u8]) -> Result<T, Error> {
+        match self {
+            Encoding::Postcard => Postcard::decode(data),
+            Encoding::Cbor => Cbor::decode(data),
+            Encoding::RawBytes => RawBytes::decode(data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_roundtrip_in_all_encodings() {
+        use alloc::string::String;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+        #[repr(C)]
+        struct Sample {
+            id: u32,
+            position: [f32; 3],
+        }
+
+        let sample = Sample {
+            id: 7,
+            position: [1.0, -2.5, 0.0],
+        };
+        for encoding in [Encoding::Postcard, Encoding::Cbor, Encoding::RawBytes] {
+            let data = encoding.encode(&sample).unwrap();
+            assert_eq!(encoding.decode::<Sample>(&data).unwrap(), sample);
+        }
+        assert_eq!(Encoding::RawBytes.encode(&sample).unwrap().len(), size_of::<Sample>());
+
+        // Raw bytes are rejected for types owning heap memory
+        assert!(Encoding::RawBytes.encode(&String::from("feo")).is_err());
+        assert!(Encoding::RawBytes.decode::<Sample>(&[0; 3]).is_err());
+    }
+}
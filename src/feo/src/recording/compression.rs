@@ -0,0 +1,121 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Compression of recorded samples
+//!
+//! With compression enabled, the recorder compresses the [Record::Data](crate::recording::Record::Data)
+//! records of each cycle into a single frame following the [Record::Cycle](crate::recording::Record::Cycle)
+//! record. Every frame names its codec and is compressed on its own, so readers decompress a recording
+//! frame by frame and can start reading at any cycle. The codec of a recording is also noted in its
+//! [header](crate::recording::header).
+
+use crate::error::{Error, RecordingError};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Compression level used for zstd
+const ZSTD_LEVEL: i32 = 3;
+
+/// Codec compressing the samples of a recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compression {
+    /// Samples are stored uncompressed
+    #[default]
+    None,
+    /// Fast compression for high-bandwidth topics such as camera images
+    Lz4,
+    /// Higher compression ratio at a higher CPU cost
+    Zstd,
+}
+
+impl Compression {
+    /// Compress a frame
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL)
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to compress frame"))),
+        }
+    }
+
+    /// Decompress a frame
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(data)
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to decompress frame"))),
+            Compression::Zstd => zstd::decode_all(data)
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to decompress frame"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_frames_roundtrip() {
+        use crate::recording::header::RecordingHeader;
+        use crate::recording::reader::RecordReader;
+        use crate::recording::recorder::{write_frame, write_record};
+        use crate::recording::{Frame, Record};
+        use alloc::string::ToString;
+        use alloc::vec;
+        use feo_time::Duration;
+
+        let cycle = Record::Cycle {
+            timestamp: Duration::from_millis(10),
+        };
+        let records = [
+            Record::Data {
+                timestamp: Duration::from_millis(10),
+                topic: "feo/com/vehicle/camera".to_string(),
+                type_name: "CameraImage".to_string(),
+                data: vec![0; 4096],
+            },
+            Record::Data {
+                timestamp: Duration::from_millis(10),
+                topic: "feo/com/vehicle/radar".to_string(),
+                type_name: "RadarScan".to_string(),
+                data: vec![0, 1, 2, 0, 3],
+            },
+        ];
+
+        for compression in [Compression::None, Compression::Lz4, Compression::Zstd] {
+            let mut data = Vec::new();
+            for record in records.iter() {
+                write_record(&mut data, record).unwrap();
+            }
+            let frames = compression.compress(&data).unwrap();
+            assert_eq!(compression.decompress(&frames).unwrap(), data);
+
+            let mut recording = Vec::new();
+            let header = RecordingHeader::new(Duration::from_secs(1), compression);
+            write_frame(&mut recording, &Frame::Header(header)).unwrap();
+            let cycle_offset = recording.len() as u64;
+            write_record(&mut recording, &cycle).unwrap();
+            write_frame(&mut recording, &Frame::Compressed { compression, frames }).unwrap();
+
+            let mut reader = RecordReader::new(&recording[..]);
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+            assert_eq!(reader.compression(), compression);
+            assert_eq!(reader.record_offset(), cycle_offset);
+            for record in records.iter() {
+                assert_eq!(reader.read_record().unwrap().as_ref(), Some(record));
+            }
+            assert!(reader.read_record().unwrap().is_none());
+        }
+    }
+}
@@ -0,0 +1,224 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Encryption of recorded samples at rest
+//!
+//! Recorded samples may be personal data, e.g. camera images, which must not be stored in plaintext.
+//! With a [RecordingKey] passed to [Recorder::with_encryption](crate::recording::recorder::Recorder::with_encryption),
+//! the recorder encrypts the [Record::Data](crate::recording::Record::Data) records of each cycle into
+//! a single frame with AES-256-GCM, after compressing them if enabled, see [compression](crate::recording::compression).
+//! The states of the activities may be derived from personal data as well, so each
+//! [Record::State](crate::recording::Record::State) is encrypted into a frame of its own.
+//! Every frame is encrypted with a random nonce of its own, so readers decrypt a recording frame by frame
+//! and can start reading at any cycle.
+//!
+//! The authentication tag of each frame detects modified samples. It also covers the id of the key and
+//! the timestamp of the cycle of the frame, so that neither the key id noted in the header can be changed
+//! nor encrypted frames can be moved to other cycles unnoticed.
+//!
+//! The [header](crate::recording::header) notes the id of the key, but never the key itself. Readers need
+//! the same key, see [RecordReader::with_key](crate::recording::reader::RecordReader::with_key).
+//! All other records, e.g. cycles and signals, are stored in plaintext, so that recordings
+//! can be indexed and inspected without the key.
+
+use crate::error::{Error, RecordingError};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use feo_time::Duration;
+
+/// Size of a key in bytes
+pub const KEY_SIZE: usize = 32;
+
+/// Size of the nonce of an encrypted frame in bytes
+pub(crate) const NONCE_SIZE: usize = 12;
+
+/// Key encrypting the samples of a recording
+#[derive(Clone)]
+pub struct RecordingKey {
+    /// Id of the key noted in the header of the recording
+    id: String,
+    /// Cipher initialized with the key
+    cipher: Aes256Gcm,
+}
+
+impl RecordingKey {
+    /// Create a key with the given id, e.g. the name of the key in the key store of the vehicle
+    pub fn new(id: impl Into<String>, key: &[u8; KEY_SIZE]) -> Self {
+        Self {
+            id: id.into(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Create a key with the given id from the key written as 64 hex digits
+    pub fn from_hex(id: impl Into<String>, hex: &str) -> Result<Self, Error> {
+        let hex = hex.trim();
+        let mut key = [0u8; KEY_SIZE];
+        if hex.len() != 2 * KEY_SIZE || !hex.is_ascii() {
+            return Err(Error::Recording(RecordingError::Failed("invalid recording key, expected 64 hex digits")));
+        }
+        for (byte, digits) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let digits = core::str::from_utf8(digits).unwrap_or_default();
+            *byte = u8::from_str_radix(digits, 16).map_err(|_| {
+                Error::Recording(RecordingError::Failed("invalid recording key, expected 64 hex digits"))
+            })?;
+        }
+        Ok(Self::new(id, &key))
+    }
+
+    /// Get the id of the key
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Encrypt a frame of the cycle with the given timestamp, returning the random nonce and the encrypted data
+    pub(crate) fn encrypt(&self, timestamp: Duration, data: &[u8]) -> Result<([u8; NONCE_SIZE], Vec<u8>), Error> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: data,
+            aad: &self.associated_data(timestamp),
+        };
+        let frames = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to encrypt frame")))?;
+        let mut nonce_bytes = [0; NONCE_SIZE];
+        nonce_bytes.copy_from_slice(&nonce);
+        Ok((nonce_bytes, frames))
+    }
+
+    /// Decrypt a frame of the cycle with the given timestamp encrypted with the given nonce
+    pub(crate) fn decrypt(&self, timestamp: Duration, nonce: &[u8; NONCE_SIZE], data: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = Payload {
+            msg: data,
+            aad: &self.associated_data(timestamp),
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| {
+                Error::Recording(RecordingError::Failed(
+                    "failed to decrypt frame, wrong key or modified recording",
+                ))
+            })
+    }
+
+    /// Data authenticated along with a frame, the id of the key and the timestamp of the cycle
+    fn associated_data(&self, timestamp: Duration) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.id.len() + 12);
+        data.extend_from_slice(self.id.as_bytes());
+        data.extend_from_slice(&timestamp.as_secs().to_le_bytes());
+        data.extend_from_slice(&timestamp.subsec_nanos().to_le_bytes());
+        data
+    }
+}
+
+// Implemented manually to never print the key
+impl fmt::Debug for RecordingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_frames_roundtrip() {
+        use crate::recording::compression::Compression;
+        use crate::recording::header::RecordingHeader;
+        use crate::recording::reader::RecordReader;
+        use crate::recording::recorder::{write_frame, write_record};
+        use crate::recording::{Frame, Record};
+        use alloc::string::ToString;
+        use alloc::vec;
+
+        let key = RecordingKey::from_hex("vehicle-1", &"0f".repeat(KEY_SIZE)).unwrap();
+        let other_key = RecordingKey::new("vehicle-1", &[1; KEY_SIZE]);
+        assert!(RecordingKey::from_hex("vehicle-1", "0f0f").is_err());
+
+        let cycle = Record::Cycle {
+            timestamp: Duration::from_millis(10),
+        };
+        let sample = Record::Data {
+            timestamp: Duration::from_millis(10),
+            topic: "feo/com/vehicle/camera".to_string(),
+            type_name: "CameraImage".to_string(),
+            data: vec![7; 512],
+        };
+
+        for compression in [Compression::None, Compression::Lz4] {
+            // Compressed frames are encrypted as a whole
+            let mut data = Vec::new();
+            write_record(&mut data, &sample).unwrap();
+            if compression != Compression::None {
+                let frames = compression.compress(&data).unwrap();
+                data.clear();
+                write_frame(&mut data, &Frame::Compressed { compression, frames }).unwrap();
+            }
+            let (nonce, frames) = key.encrypt(Duration::from_millis(10), &data).unwrap();
+            assert!(!frames.windows(data.len()).any(|window| window == data));
+            let encrypted = Frame::Encrypted { nonce, frames };
+
+            let mut recording = Vec::new();
+            let header = RecordingHeader::new(Duration::from_secs(1), compression).with_key_id(key.id());
+            write_frame(&mut recording, &Frame::Header(header)).unwrap();
+            write_record(&mut recording, &cycle).unwrap();
+            write_frame(&mut recording, &encrypted).unwrap();
+
+            let mut reader = RecordReader::new(&recording[..]).with_key(key.clone());
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&sample));
+            assert!(reader.read_record().unwrap().is_none());
+
+            // Without the key, only the plaintext records can be read
+            let mut reader = RecordReader::new(&recording[..]);
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+            assert!(reader.read_record().is_err());
+
+            let mut reader = RecordReader::new(&recording[..]).with_key(other_key.clone());
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+            assert!(reader.read_record().is_err());
+
+            let unknown_key = RecordingKey::new("vehicle-2", &[0x0f; KEY_SIZE]);
+            let mut reader = RecordReader::new(&recording[..]).with_key(unknown_key.clone());
+            assert!(reader.read_header().is_err());
+
+            // The key id is authenticated, changing it in the header is detected even with the same key material
+            let mut changed = Vec::new();
+            let header = RecordingHeader::new(Duration::from_secs(1), compression).with_key_id(unknown_key.id());
+            write_frame(&mut changed, &Frame::Header(header)).unwrap();
+            write_record(&mut changed, &cycle).unwrap();
+            write_frame(&mut changed, &encrypted).unwrap();
+            let mut reader = RecordReader::new(&changed[..]).with_key(unknown_key);
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+            assert!(reader.read_record().is_err());
+
+            // Encrypted frames moved to another cycle are detected
+            let mut moved = Vec::new();
+            let header = RecordingHeader::new(Duration::from_secs(1), compression).with_key_id(key.id());
+            write_frame(&mut moved, &Frame::Header(header)).unwrap();
+            let other_cycle = Record::Cycle {
+                timestamp: Duration::from_millis(20),
+            };
+            write_record(&mut moved, &other_cycle).unwrap();
+            write_frame(&mut moved, &encrypted).unwrap();
+            let mut reader = RecordReader::new(&moved[..]).with_key(key.clone());
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(&other_cycle));
+            assert!(reader.read_record().is_err());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Filtering of recorded topics
+//!
+//! A [RecordFilter] selects the topics written by the [Recorder](crate::recording::recorder::Recorder)
+//! by topic name, type name or producing activity, and down-samples individual topics.
+//! The filter of a running recorder can be replaced at any time through a [RecordFilterHandle].
+
+use crate::ids::ActivityId;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+/// Selection of recorded topics
+///
+/// A topic is recorded if it matches every non-empty include set and none of the exclude sets.
+/// The default filter records all samples of all topics.
+#[derive(Debug, Clone, Default)]
+pub struct RecordFilter {
+    /// Topics to record. All topics are included if empty.
+    pub include_topics: HashSet<String>,
+    /// Topics not to record
+    pub exclude_topics: HashSet<String>,
+    /// Type names, as given by [core::any::type_name], to record. All types are included if empty.
+    pub include_types: HashSet<String>,
+    /// Type names not to record
+    pub exclude_types: HashSet<String>,
+    /// Producing activities of the topics to record. All topics are included if empty.
+    ///
+    /// Topics recorded without a producer are not included if this set is not empty.
+    pub include_producers: HashSet<ActivityId>,
+    /// Producing activities of the topics not to record
+    pub exclude_producers: HashSet<ActivityId>,
+    /// Sampling ratio per topic: only every n-th available sample of the topic is recorded.
+    ///
+    /// Topics without an entry are fully recorded.
+    pub sampling: HashMap<String, u32>,
+}
+
+impl RecordFilter {
+    /// Record the given topic only
+    pub fn include_topic(mut self, topic: &str) -> Self {
+        self.include_topics.insert(topic.to_string());
+        self
+    }
+
+    /// Do not record the given topic
+    pub fn exclude_topic(mut self, topic: &str) -> Self {
+        self.exclude_topics.insert(topic.to_string());
+        self
+    }
+
+    /// Record topics of the given type only
+    pub fn include_type(mut self, type_name: &str) -> Self {
+        self.include_types.insert(type_name.to_string());
+        self
+    }
+
+    /// Do not record topics of the given type
+    pub fn exclude_type(mut self, type_name: &str) -> Self {
+        self.exclude_types.insert(type_name.to_string());
+        self
+    }
+
+    /// Record topics produced by the given activity only
+    pub fn include_producer(mut self, id: ActivityId) -> Self {
+        self.include_producers.insert(id);
+        self
+    }
+
+    /// Do not record topics produced by the given activity
+    pub fn exclude_producer(mut self, id: ActivityId) -> Self {
+        self.exclude_producers.insert(id);
+        self
+    }
+
+    /// Record only every n-th available sample of the given topic
+    pub fn sample_every(mut self, topic: &str, n: u32) -> Self {
+        self.sampling.insert(topic.to_string(), n);
+        self
+    }
+
+    /// Check whether the given topic is selected for recording
+    pub fn matches(&self, topic: &str, type_name: &str, producer: Option<ActivityId>) -> bool {
+        let included = (self.include_topics.is_empty() || self.include_topics.contains(topic))
+            && (self.include_types.is_empty() || self.include_types.contains(type_name))
+            && (self.include_producers.is_empty() || producer.is_some_and(|id| self.include_producers.contains(&id)));
+        let excluded = self.exclude_topics.contains(topic)
+            || self.exclude_types.contains(type_name)
+            || producer.is_some_and(|id| self.exclude_producers.contains(&id));
+        included && !excluded
+    }
+
+    /// Check whether the sample with the given index, counting from zero, of the given topic is to be recorded
+    pub fn sampled(&self, topic: &str, index: u64) -> bool {
+        match self.sampling.get(topic) {
+            Some(n) if *n > 1 => index % u64::from(*n) == 0,
+            _ => true,
+        }
+    }
+}
+
+/// Shared handle to the filter of a recorder, allowing to change it at runtime
+///
+/// Changes take effect with the next recorded cycle.
+#[derive(Debug, Clone, Default)]
+pub struct RecordFilterHandle(Arc<Mutex<RecordFilter>>);
+
+impl RecordFilterHandle {
+    /// Create a handle to the given filter
+    pub fn new(filter: RecordFilter) -> Self {
+        Self(Arc::new(Mutex::new(filter)))
+    }
+
+    /// Replace the filter
+    pub fn set(&self, filter: RecordFilter) {
+        *self.lock() = filter;
+    }
+
+    /// Modify the filter in place
+    pub fn update(&self, f: impl FnOnce(&mut RecordFilter)) {
+        f(&mut self.lock());
+    }
+
+    /// Get a copy of the current filter
+    pub fn get(&self) -> RecordFilter {
+        self.lock().clone()
+    }
+
+    /// Lock the filter, ignoring poisoning by a panicking user of the handle
+    pub(crate) fn lock(&self) -> MutexGuard<'_, RecordFilter> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_selects_and_samples_topics() {
+        let camera = "feo/com/vehicle/camera";
+        let control = "feo/com/vehicle/control";
+        let producer = ActivityId::new(1);
+
+        let filter = RecordFilter::default()
+            .exclude_producer(ActivityId::new(2))
+            .sample_every(camera, 3);
+        assert!(filter.matches(camera, "Image", Some(producer)));
+        assert!(filter.matches(control, "Control", None));
+        assert!(!filter.matches(control, "Control", Some(ActivityId::new(2))));
+        let sampled: alloc::vec::Vec<bool> = (0..4).map(|index| filter.sampled(camera, index)).collect();
+        assert_eq!(sampled, [true, false, false, true]);
+        assert!(filter.sampled(control, 1));
+
+        let filter = RecordFilter::default().include_producer(producer).exclude_type("Image");
+        assert!(!filter.matches(camera, "Image", Some(producer)));
+        assert!(!filter.matches(control, "Control", None));
+        assert!(filter.matches(control, "Control", Some(producer)));
+    }
+}
@@ -0,0 +1,193 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Header of a recording
+//!
+//! The recorder writes a [RecordingHeader] as the first frame of each recording. Readers use it to
+//! reject recordings of an unsupported format and samples of types changed since recording, instead of
+//! failing on the first sample which cannot be deserialized. Recorded types may carry a descriptor of their
+//! schema, which allows replaying samples of types evolved since recording, see [schema](crate::recording::schema).
+
+use crate::error::{Error, RecordingError};
+use crate::recording::codec::Encoding;
+use crate::recording::compression::Compression;
+use crate::recording::schema::SchemaDescriptor;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::mem::{align_of, size_of};
+use feo_time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Version of the recording format written by this version of FEO
+///
+/// Increment on every incompatible change of the recording format.
+pub const FORMAT_VERSION: u32 = 9;
+
+/// Header at the start of a recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    /// Version of the recording format, see [FORMAT_VERSION]
+    pub format_version: u32,
+    /// Version of FEO the recording was written with
+    pub feo_version: String,
+    /// Startup time of the primary agent as duration since the UNIX epoch
+    ///
+    /// The timestamps of the records are relative to this time.
+    pub start_time: Duration,
+    /// Speed factor of the clock while recording, if set with [feo_time::speed]
+    pub speed: Option<i32>,
+    /// Codec of the compressed frames of the recording
+    pub compression: Compression,
+    /// Encoding of the recorded samples
+    pub encoding: Encoding,
+    /// Id of the key the samples are encrypted with, if encrypted, see [encryption](crate::recording::encryption)
+    pub key_id: Option<String>,
+    /// Types of the recorded topics
+    pub types: Vec<RecordedType>,
+    /// Cycle of the main task chain after which a late-joining recorder started, see [join](crate::recording::join)
+    pub join_cycle: Option<u64>,
+}
+
+impl RecordingHeader {
+    /// Create a header of the current format version with samples encoded with postcard and
+    /// without recorded types
+    pub fn new(start_time: Duration, compression: Compression) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            feo_version: env!("CARGO_PKG_VERSION").to_string(),
+            start_time,
+            speed: feo_time::get_speed(),
+            compression,
+            encoding: Encoding::default(),
+            key_id: None,
+            types: Vec::new(),
+            join_cycle: None,
+        }
+    }
+
+    /// Note the given encoding of the recorded samples
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Note the id of the key the samples are encrypted with
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    /// Note the cycle of the main task chain after which the recorder joined
+    pub fn with_join_cycle(mut self, cycle: u64) -> Self {
+        self.join_cycle = Some(cycle);
+        self
+    }
+
+    /// Add the given types to the recorded types, skipping duplicates
+    pub fn with_types(mut self, types: impl IntoIterator<Item = RecordedType>) -> Self {
+        for recorded in types {
+            if !self.types.contains(&recorded) {
+                self.types.push(recorded);
+            }
+        }
+        self
+    }
+
+    /// Get the schema hash of the recorded type with the given name, if any
+    pub fn schema_hash(&self, type_name: &str) -> Option<u64> {
+        self.recorded_type(type_name).map(|recorded| recorded.schema_hash)
+    }
+
+    /// Get the entry of the recorded type with the given name, if any
+    pub fn recorded_type(&self, type_name: &str) -> Option<&RecordedType> {
+        self.types.iter().find(|recorded| recorded.type_name == type_name)
+    }
+
+    /// Check that the recording can be read by this version of FEO
+    pub fn check_version(&self) -> Result<(), Error> {
+        if self.format_version > FORMAT_VERSION {
+            return Err(Error::Recording(RecordingError::Failed("unsupported recording format version")));
+        }
+        Ok(())
+    }
+}
+
+/// Recorded type and hash of its schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedType {
+    /// Name of the type as written to the data records
+    pub type_name: String,
+    /// Hash of the schema of the type, see [schema_hash]
+    pub schema_hash: u64,
+    /// Descriptor of the schema of the type, if registered
+    pub schema: Option<SchemaDescriptor>,
+}
+
+impl RecordedType {
+    /// Get the entry of the given type without schema descriptor
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            type_name: type_name::<T>().to_string(),
+            schema_hash: schema_hash::<T>(),
+            schema: None,
+        }
+    }
+
+    /// Note the given descriptor of the schema of the type
+    pub fn with_schema(mut self, schema: Option<SchemaDescriptor>) -> Self {
+        self.schema = schema;
+        self
+    }
+}
+
+/// Hash of the schema of a recorded type
+///
+/// Postcard encodes neither field names nor field types, so samples can only be decoded with the
+/// type they were recorded with. The hash is a FNV-1a hash of the name, size and alignment of the type.
+/// Recorded types are plain data types, so most incompatible changes of their serialized form
+/// change their memory layout and thus the hash.
+pub fn schema_hash<T: 'static>() -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let size = (size_of::<T>() as u64).to_le_bytes();
+    let align = (align_of::<T>() as u64).to_le_bytes();
+    type_name::<T>()
+        .as_bytes()
+        .iter()
+        .chain(size.iter())
+        .chain(align.iter())
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_lists_recorded_types() {
+        assert_eq!(schema_hash::<u32>(), schema_hash::<u32>());
+        assert_ne!(schema_hash::<u32>(), schema_hash::<i32>());
+        assert_ne!(schema_hash::<[u8; 4]>(), schema_hash::<[u8; 8]>());
+
+        let header = RecordingHeader::new(Duration::from_secs(1), Compression::None).with_types([
+            RecordedType::of::<u32>(),
+            RecordedType::of::<[u8; 4]>(),
+            RecordedType::of::<u32>(),
+        ]);
+        assert_eq!(header.types.len(), 2);
+        assert_eq!(header.schema_hash(type_name::<u32>()), Some(schema_hash::<u32>()));
+        assert!(header.check_version().is_ok());
+    }
+}
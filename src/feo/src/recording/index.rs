@@ -0,0 +1,208 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Index of recordings for seeking
+//!
+//! An index maps the timestamps and record numbers of the [Record::Cycle]s of a recording
+//! to their byte offsets, allowing a [RecordingReader](crate::recording::reader::RecordingReader)
+//! to seek in O(log n) instead of scanning the recording.
+//!
+//! The index is stored in a sidecar file next to the recording, see [index_path], as a stream
+//! of COBS-framed postcard-serialized [IndexEntry]s. The [Recorder](crate::recording::recorder::Recorder)
+//! writes the index while recording if configured with an index output. For existing recordings,
+//! the index can be built with [RecordingIndex::build].
+
+use crate::error::{Error, RecordingError};
+use crate::recording::reader::RecordReader;
+use crate::recording::Record;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Index entry of a recorded cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Timestamp of the cycle
+    pub timestamp: Duration,
+    /// Number of the [Record::Cycle] in the recording, counting from zero
+    pub record: u64,
+    /// Byte offset of the [Record::Cycle] in the recording
+    pub offset: u64,
+}
+
+/// Serialize an index entry and write it as a single COBS frame
+pub fn write_index_entry<W: Write>(writer: &mut W, entry: &IndexEntry) -> Result<(), Error> {
+    let frame = postcard::to_allocvec_cobs(entry)
+        .map_err(|_| Error::Recording(RecordingError::Failed("failed to serialize index entry")))?;
+    writer.write_all(&frame)?;
+    Ok(())
+}
+
+/// Get the path of the sidecar index of the given recording, i.e. the path with `.idx` appended
+pub fn index_path(recording: &Path) -> PathBuf {
+    let mut path = OsString::from(recording.as_os_str());
+    path.push(".idx");
+    PathBuf::from(path)
+}
+
+/// Index of the cycles of a recording, ordered by timestamp
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordingIndex {
+    entries: Vec<IndexEntry>,
+}
+
+impl RecordingIndex {
+    /// Build the index by scanning a recording
+    pub fn build<R: BufRead>(reader: R) -> Result<Self, Error> {
+        Self::build_from(RecordReader::new(reader))
+    }
+
+    /// Build the index by scanning a recording with the given reader, e.g. one with the key of an encrypted recording
+    pub fn build_from<R: BufRead>(mut reader: RecordReader<R>) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        let mut record = 0;
+        loop {
+            match reader.read_record()? {
+                Some(Record::Cycle { timestamp }) => entries.push(IndexEntry {
+                    timestamp,
+                    record,
+                    offset: reader.record_offset(),
+                }),
+                Some(
+                    Record::Data { .. }
+                    | Record::Signal { .. }
+                    | Record::State { .. }
+                    | Record::Dropped { .. }
+                    | Record::Trigger { .. }
+                    | Record::SpeedChange { .. }
+                    | Record::Panic { .. }
+                    | Record::Overrun { .. }
+                    | Record::ClockMapping { .. },
+                ) => {},
+                None => break,
+            }
+            record += 1;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Read an index written as a stream of index entries
+    ///
+    /// An incomplete entry at the end, e.g. after a crash of the recorder, is ignored.
+    pub fn read<R: BufRead>(mut reader: R) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        let mut frame = Vec::new();
+        loop {
+            frame.clear();
+            if reader.read_until(0, &mut frame)? == 0 || frame.last() != Some(&0) {
+                break;
+            }
+            let entry = postcard::from_bytes_cobs(&mut frame)
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize index entry")))?;
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Write the index as a stream of index entries
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.entries
+            .iter()
+            .try_for_each(|entry| write_index_entry(writer, entry))
+    }
+
+    /// Load the sidecar index of the given recording
+    pub fn load(recording: &Path) -> Result<Self, Error> {
+        let file = File::open(index_path(recording))?;
+        Self::read(std::io::BufReader::new(file))
+    }
+
+    /// Save the index as sidecar of the given recording
+    pub fn save(&self, recording: &Path) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(index_path(recording))?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Get all entries
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Find the last cycle starting at or before the given time
+    ///
+    /// Returns the first cycle if the time is before the start of the recording.
+    pub fn find_time(&self, timestamp: Duration) -> Option<&IndexEntry> {
+        let pos = self.entries.partition_point(|entry| entry.timestamp <= timestamp);
+        self.entries.get(pos.saturating_sub(1))
+    }
+
+    /// Find the last cycle starting at or before the given record number
+    pub fn find_record(&self, record: u64) -> Option<&IndexEntry> {
+        let pos = self.entries.partition_point(|entry| entry.record <= record);
+        self.entries.get(pos.checked_sub(1)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_seeks_through_index() {
+        use crate::recording::reader::RecordingReader;
+        use crate::recording::recorder::write_record;
+        use alloc::string::ToString;
+        use alloc::vec;
+        use std::io::Cursor;
+
+        let mut recording = Vec::new();
+        for cycle in 0..10 {
+            let timestamp = Duration::from_millis(cycle * 10);
+            write_record(&mut recording, &Record::Cycle { timestamp }).unwrap();
+            let data = Record::Data {
+                timestamp,
+                topic: "feo/com/vehicle/camera".to_string(),
+                type_name: "CameraImage".to_string(),
+                data: vec![cycle as u8],
+            };
+            write_record(&mut recording, &data).unwrap();
+        }
+
+        let index = RecordingIndex::build(&recording[..]).unwrap();
+        assert_eq!(index.entries().len(), 10);
+        let mut serialized = Vec::new();
+        index.write(&mut serialized).unwrap();
+        assert_eq!(RecordingIndex::read(&serialized[..]).unwrap(), index);
+
+        let mut reader = RecordingReader::new(Cursor::new(recording), index);
+        reader.seek_to_time(Duration::from_millis(35)).unwrap();
+        assert_eq!(reader.next_record(), 6);
+        assert_eq!(
+            reader.read_record().unwrap(),
+            Some(Record::Cycle {
+                timestamp: Duration::from_millis(30)
+            })
+        );
+
+        reader.seek_to_record(13).unwrap();
+        let record = reader.read_record().unwrap().unwrap();
+        assert!(matches!(record, Record::Data { data, .. } if data == [6]));
+        assert!(reader.seek_to_record(21).is_err());
+    }
+}
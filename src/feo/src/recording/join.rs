@@ -0,0 +1,190 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Recorders joining a running primary agent
+//!
+//! Recorders are usually activities configured at startup. A recorder process started while the application
+//! is already running joins it through the [introspection](crate::introspection) server of the primary agent
+//! instead:
+//!
+//! 1. [PrimaryLink::join] fetches the [JoinInfo] of the primary agent, i.e. its startup time, with which the
+//!    timestamps of the recorder are synchronized, and the topics initialized with the types of their samples.
+//! 2. The recorder process subscribes to the topics it knows the types of, see [JoinInfo::offers], as one of
+//!    the additional readers of the topics configured for the primary agent.
+//! 3. [record_late] starts the recorder at the next cycle boundary of the main task chain, noting the cycle
+//!    in the header of the recording, and steps it after each following cycle until shutdown.
+//!
+//! The recorder reads the latest samples after each cycle of the main chain like a recorder at the end of
+//! the chain, but is not synchronized with it: samples written while the recorder is stepped may be recorded
+//! in the following cycle.
+
+use crate::activity::Activity;
+use crate::chain::MAIN_CHAIN;
+use crate::error::{Error, RecordingError};
+use crate::recording::recorder::Recorder;
+use crate::recording::sink::RecordSink;
+use crate::timestamp::{self, sync_info, SyncInfo};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::sync::atomic::{AtomicBool, Ordering};
+use feo_tracing::ScoreDebugIoError;
+use score_log::info;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Topics initialized in the primary agent of this process
+static TOPICS: Mutex<Vec<JoinableTopic>> = Mutex::new(Vec::new());
+
+/// Configuration of a running primary agent needed to join it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinInfo {
+    /// Startup time of the primary agent as nanoseconds since the UNIX epoch
+    pub startup_ns: u64,
+    /// Topics of the application
+    pub topics: Vec<JoinableTopic>,
+}
+
+impl JoinInfo {
+    /// Get the configuration of the primary agent running in this process
+    pub fn current() -> Self {
+        Self {
+            startup_ns: u64::from(sync_info()),
+            topics: TOPICS.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    /// Whether the given topic is offered with samples of type `T`
+    pub fn offers<T: 'static>(&self, topic: &str) -> bool {
+        self.topics
+            .iter()
+            .any(|joinable| joinable.topic == topic && joinable.type_name == type_name::<T>())
+    }
+}
+
+/// Topic of a running application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinableTopic {
+    /// Name of the topic
+    pub topic: String,
+    /// Name of the type of its samples
+    pub type_name: String,
+}
+
+/// Note a topic initialized by the primary agent
+pub(crate) fn register_topic(topic: &str, type_name: &str) {
+    let mut topics = TOPICS.lock().unwrap_or_else(|e| e.into_inner());
+    if !topics.iter().any(|joinable| joinable.topic == topic) {
+        topics.push(JoinableTopic {
+            topic: topic.to_string(),
+            type_name: type_name.to_string(),
+        });
+    }
+}
+
+/// Connection to the introspection server of a running primary agent
+pub struct PrimaryLink {
+    writer: UnixStream,
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl PrimaryLink {
+    /// Connect to the introspection server listening on the Unix socket at `path`
+    pub fn connect(path: &Path) -> Result<Self, Error> {
+        let writer = UnixStream::connect(path)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to connect to introspection server")))?;
+        let reader = writer
+            .try_clone()
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to clone introspection socket")))?;
+        Ok(Self {
+            writer,
+            lines: BufReader::new(reader).lines(),
+        })
+    }
+
+    /// Fetch the configuration of the primary agent and synchronize the timestamps of this process with it
+    pub fn join(&mut self) -> Result<JoinInfo, Error> {
+        let response = self.request("join")?;
+        let info: JoinInfo = serde_json::from_str(&response)
+            .map_err(|_| Error::Recording(RecordingError::Failed("invalid join response")))?;
+        timestamp::initialize_from(SyncInfo::from(info.startup_ns));
+        Ok(info)
+    }
+
+    /// Wait for the next completed cycle of the main task chain and get its number
+    pub fn next_cycle(&mut self) -> Result<u64, Error> {
+        let response = self.request("cycle")?;
+        response
+            .parse()
+            .map_err(|_| Error::Recording(RecordingError::Failed("primary agent is no longer cycling")))
+    }
+
+    /// Send a command and receive its response
+    fn request(&mut self, command: &str) -> Result<String, Error> {
+        self.writer
+            .write_all(alloc::format!("{command}\n").as_bytes())
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to send introspection command")))?;
+        match self.lines.next() {
+            Some(Ok(line)) => Ok(line),
+            Some(Err(e)) => Err(Error::Io((ScoreDebugIoError(e), "failed to receive introspection response"))),
+            None => Err(Error::Recording(RecordingError::Failed("introspection server disconnected"))),
+        }
+    }
+}
+
+/// Record from the next cycle boundary of the main task chain until `shutdown` is set
+///
+/// The recorder is started up after the next completed cycle, noting its number in the header,
+/// and stepped after each following cycle.
+pub fn record_late<W: RecordSink>(
+    recorder: Recorder<W>,
+    link: &mut PrimaryLink,
+    shutdown: &AtomicBool,
+) -> Result<(), Error> {
+    let cycle = link.next_cycle()?;
+    info!("Joining task chain {} after cycle {}", MAIN_CHAIN, cycle);
+    let mut recorder = recorder.with_join_cycle(cycle);
+    let id = recorder.id();
+    recorder.startup().map_err(|e| Error::ActivityFailed(id, e))?;
+    while !shutdown.load(Ordering::Relaxed) {
+        if link.next_cycle().is_err() {
+            info!("Primary agent stopped cycling, finishing recording");
+            break;
+        }
+        recorder.step().map_err(|e| Error::ActivityFailed(id, e))?;
+    }
+    recorder.shutdown().map_err(|e| Error::ActivityFailed(id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joinable_topics_are_offered_by_type() {
+        timestamp::initialize();
+        register_topic("test/join/camera", type_name::<[u8; 16]>());
+        register_topic("test/join/camera", type_name::<u32>());
+
+        let info = JoinInfo::current();
+        assert!(info.offers::<[u8; 16]>("test/join/camera"));
+        assert!(!info.offers::<u32>("test/join/camera"));
+        assert!(!info.offers::<[u8; 16]>("test/join/unknown"));
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<JoinInfo>(&json).unwrap(), info);
+    }
+}
@@ -0,0 +1,214 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Keyframe recording of slowly changing topics
+//!
+//! Topics with large samples whose content rarely changes, e.g. maps or calibration data, can be
+//! recorded with keyframes: every `interval` samples, the full encoded sample is recorded as a keyframe,
+//! while the samples in between are recorded as a [SampleDelta] holding the bytes changed since the
+//! keyframe. A delta not smaller than its sample is replaced by a new keyframe.
+//!
+//! Deltas refer to the latest keyframe of their topic instead of the previous sample, so readers
+//! only keep the latest keyframe per topic and reconstruct the samples transparently into
+//! [Record::Data] records. Readers starting in the middle of a recording, e.g. after seeking,
+//! cannot reconstruct the deltas before the next keyframe and report them as a [Record::Dropped] instead.
+
+use crate::error::{Error, RecordingError};
+use crate::recording::{Frame, Record};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Number of unchanged bytes up to which adjacent changed ranges are merged
+const MERGE_GAP: usize = 8;
+
+/// Changes of an encoded sample relative to the keyframe of its topic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleDelta {
+    /// Length of the encoded sample
+    pub len: u32,
+    /// Changed ranges of bytes as offset and new content
+    pub changes: Vec<(u32, Vec<u8>)>,
+}
+
+impl SampleDelta {
+    /// Get the changes of the given sample relative to the given keyframe
+    pub fn between(keyframe: &[u8], sample: &[u8]) -> Self {
+        let mut changes = Vec::new();
+        let mut offset = 0;
+        while offset < sample.len() {
+            if keyframe.get(offset) == Some(&sample[offset]) {
+                offset += 1;
+                continue;
+            }
+            // Extend the changed range over short runs of unchanged bytes
+            let start = offset;
+            let mut end = offset + 1;
+            let mut unchanged = 0;
+            offset += 1;
+            while offset < sample.len() && unchanged < MERGE_GAP {
+                if keyframe.get(offset) == Some(&sample[offset]) {
+                    unchanged += 1;
+                } else {
+                    unchanged = 0;
+                    end = offset + 1;
+                }
+                offset += 1;
+            }
+            changes.push((start as u32, sample[start..end].to_vec()));
+        }
+        Self {
+            len: sample.len() as u32,
+            changes,
+        }
+    }
+
+    /// Reconstruct the sample from the given keyframe
+    pub fn apply(&self, keyframe: &[u8]) -> Result<Vec<u8>, Error> {
+        let len = self.len as usize;
+        let mut sample = keyframe[..len.min(keyframe.len())].to_vec();
+        sample.resize(len, 0);
+        for (offset, bytes) in self.changes.iter() {
+            let start = *offset as usize;
+            let range = sample
+                .get_mut(start..start + bytes.len())
+                .ok_or(Error::Recording(RecordingError::Failed("delta exceeds its sample")))?;
+            range.copy_from_slice(bytes);
+        }
+        Ok(sample)
+    }
+
+    /// Get the number of changed bytes
+    fn changed_bytes(&self) -> usize {
+        self.changes.iter().map(|(_, bytes)| bytes.len()).sum()
+    }
+}
+
+/// Keyframe state of a topic recorded with keyframes
+#[derive(Debug)]
+struct KeyframeTopic {
+    /// Number of samples from one keyframe to the next
+    interval: u64,
+    /// Number of samples recorded since the latest keyframe
+    since_keyframe: u64,
+    /// Latest keyframe, if any
+    keyframe: Option<Vec<u8>>,
+}
+
+/// Writer side of keyframe recording, turning the data records of the configured topics into
+/// keyframes and deltas
+#[derive(Debug, Default)]
+pub(crate) struct KeyframeWriter {
+    topics: HashMap<String, KeyframeTopic>,
+}
+
+impl KeyframeWriter {
+    /// Record the given topic with a keyframe every `interval` samples
+    pub(crate) fn add_topic(&mut self, topic: String, interval: u64) {
+        let state = KeyframeTopic {
+            interval: interval.max(1),
+            since_keyframe: 0,
+            keyframe: None,
+        };
+        self.topics.insert(topic, state);
+    }
+
+    /// Get the frame to write for the given record, or `None` if the record is written as it is
+    pub(crate) fn frame(&mut self, record: &Record) -> Option<Frame> {
+        let Record::Data {
+            timestamp,
+            topic,
+            type_name,
+            data,
+        } = record
+        else {
+            return None;
+        };
+        let state = self.topics.get_mut(topic)?;
+        if let Some(keyframe) = state.keyframe.as_deref().filter(|_| state.since_keyframe < state.interval) {
+            let delta = SampleDelta::between(keyframe, data);
+            // Deltas must be smaller than the sample including the offsets of the changes
+            if delta.changed_bytes() + delta.changes.len() * 8 < data.len() {
+                state.since_keyframe += 1;
+                return Some(Frame::Delta {
+                    timestamp: *timestamp,
+                    topic: topic.clone(),
+                    type_name: type_name.clone(),
+                    delta,
+                });
+            }
+        }
+        state.keyframe = Some(data.clone());
+        state.since_keyframe = 1;
+        Some(Frame::Keyframe {
+            timestamp: *timestamp,
+            topic: topic.clone(),
+            type_name: type_name.clone(),
+            data: data.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas_are_reconstructed_from_keyframes() {
+        use crate::recording::reader::RecordReader;
+        use crate::recording::recorder::write_frame;
+        use alloc::string::ToString;
+        use alloc::vec;
+        use feo_time::Duration;
+
+        let keyframe: Vec<u8> = (0..64).collect();
+        let mut sample = keyframe.clone();
+        sample[3] = 0xff;
+        sample[5] = 0xff;
+        sample[40] = 0xff;
+        sample.push(1);
+        let delta = SampleDelta::between(&keyframe, &sample);
+        assert_eq!(delta.changes, [(3, vec![0xff, 4, 0xff]), (40, vec![0xff]), (64, vec![1])]);
+        assert_eq!(delta.apply(&keyframe).unwrap(), sample);
+        assert_eq!(SampleDelta::between(&sample, &keyframe).apply(&sample).unwrap(), keyframe);
+
+        let data = |timestamp: u64, data: &Vec<u8>| Record::Data {
+            timestamp: Duration::from_millis(timestamp),
+            topic: "feo/com/map".to_string(),
+            type_name: "Map".to_string(),
+            data: data.clone(),
+        };
+        let records = [data(0, &keyframe), data(10, &sample), data(20, &sample), data(30, &keyframe)];
+        let mut writer = KeyframeWriter::default();
+        writer.add_topic("feo/com/map".to_string(), 3);
+        let mut recording = Vec::new();
+        let mut offsets = Vec::new();
+        for record in records.iter() {
+            offsets.push(recording.len() as u64);
+            write_frame(&mut recording, &writer.frame(record).unwrap()).unwrap();
+        }
+        // Every third sample is a keyframe
+        assert!(recording.len() < 3 * keyframe.len());
+
+        let mut reader = RecordReader::new(&recording[..]);
+        for record in records.iter() {
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(record));
+        }
+        assert!(reader.read_record().unwrap().is_none());
+
+        // Deltas before the first keyframe read are reported as dropped
+        let mut reader = RecordReader::new(&recording[offsets[1] as usize..]);
+        assert!(matches!(reader.read_record().unwrap(), Some(Record::Dropped { count: 1, .. })));
+    }
+}
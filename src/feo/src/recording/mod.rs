@@ -0,0 +1,298 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Recording and replay of topic data
+//!
+//! A recording is a stream of COBS-framed postcard-serialized [Record]s,
+//! starting with a [header::RecordingHeader].
+//! The [recorder::Recorder] is an activity which reads its configured topics on each step
+//! and writes their samples to the stream. Place it at the end of the task chain by letting it depend
+//! on all producers of the recorded topics.
+//! The [replayer::Replayer] reads such a stream and re-publishes the recorded samples into the com layer.
+//! To bound the disk usage of long recordings, use a [rotation::RotatingFileWriter] as output of the recorder.
+//! The recorded topics can be selected and down-sampled with a [filter::RecordFilter].
+//! For seeking in long recordings, the recorder can write an [index::RecordingIndex] used by
+//! a [reader::RecordingReader].
+//! Recorded samples can be compressed on the fly, see [compression], encrypted at rest, see [encryption],
+//! and encoded in other formats than postcard for tools in other languages, see [codec].
+//! Optionally, the recorder also captures the step signals of the scheduler, see [signals],
+//! and checkpoints of the states of activities to replay from the middle of a recording, see [checkpoint].
+//! To keep a slow output from delaying the task chain, the recorder can write through a bounded queue,
+//! dropping samples according to per-topic policies, see [backpressure].
+//! To capture rare events without recording continuously, the recorder can keep the last cycles in memory
+//! and only write them when the application fires a trigger, see [trigger].
+//! Changes of the clock speed during a recording are recorded as well, so that the recorded timestamps
+//! can be mapped back to real time, see [timeline].
+//! To develop downstream activities against real data, selected activities can be replaced by stubs
+//! replaying their recorded outputs, see [stub].
+//! Samples of types which gained fields since recording are still replayed, see [schema].
+//! Large samples of slowly changing topics can be recorded as keyframes and deltas, see [keyframe].
+//! Instead of a file, the recorder can write to any [sink::RecordSink], e.g. an iceoryx2 service
+//! streamed off-board by an uploader process, see [sink].
+//! A recorder process can also join an application which is already running, see [join].
+//! For regression tests, the outputs of a task chain replayed from a recording can be compared with the
+//! recorded outputs, see [verify].
+
+pub mod backpressure;
+pub mod checkpoint;
+pub mod codec;
+pub mod compression;
+pub mod encryption;
+pub mod filter;
+pub mod header;
+pub mod index;
+pub mod join;
+pub mod keyframe;
+pub mod reader;
+pub mod recorder;
+pub mod replayer;
+pub mod rotation;
+pub mod schema;
+pub mod signals;
+pub mod sink;
+pub mod stub;
+pub mod timeline;
+pub mod trigger;
+pub mod verify;
+
+use crate::overrun::OverrunOutcome;
+use crate::recording::compression::Compression;
+use crate::recording::header::RecordingHeader;
+use crate::recording::keyframe::SampleDelta;
+use crate::recording::signals::SignalKind;
+use alloc::string::String;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// A single entry of a recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Record {
+    /// Begin of a recorded task chain cycle
+    Cycle {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+    },
+    /// Sample of a topic
+    Data {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Topic the sample was read from
+        topic: String,
+        /// Name of the sample type
+        type_name: String,
+        /// Postcard-serialized sample
+        data: Vec<u8>,
+    },
+    /// Step signal exchanged between the scheduler and an activity
+    Signal {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Id of the activity
+        activity: u64,
+        /// Cycle of the task chain of the activity
+        cycle: u64,
+        /// Kind of the signal
+        kind: SignalKind,
+    },
+    /// Saved state of an activity
+    State {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Id of the activity
+        activity: u64,
+        /// Number of steps of the activity since its startup
+        cycle: u64,
+        /// State saved by the activity
+        state: Vec<u8>,
+    },
+    /// Samples of a topic dropped by the recorder, see [backpressure]
+    Dropped {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Topic the samples were read from
+        topic: String,
+        /// Number of samples dropped since the previous record of this kind for the topic
+        count: u64,
+    },
+    /// Trigger fired by the application, see [trigger]
+    Trigger {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Event the trigger was fired for
+        event: String,
+    },
+    /// Change of the clock speed during the recording, see [timeline]
+    SpeedChange {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Speed factor before the change, if set
+        old: Option<i32>,
+        /// Speed factor after the change, if set
+        new: Option<i32>,
+        /// OS wall clock time of the change as duration since the UNIX epoch
+        real_time: Duration,
+    },
+    /// Panic of an activity reported to the primary agent, see [panic_report](crate::panic_report)
+    Panic {
+        /// Time since startup of the primary agent
+        timestamp: Duration,
+        /// Id of the activity
+        activity: u64,
+        /// Message and location of the panic
+        message: String,
+        /// Condensed backtrace of the panic, empty if not captured
+        backtrace: String,
+    },
+    /// Overrunning cycle of a task chain, see [overrun](crate::overrun)
+    Overrun {
+        /// Time since startup of the primary agent at the end of the cycle
+        timestamp: Duration,
+        /// Id of the task chain
+        chain: u64,
+        /// Number of the overrunning cycle of the task chain
+        cycle: u64,
+        /// Duration of the cycle
+        duration: Duration,
+        /// Outcome of the overrun policy of the task chain
+        outcome: OverrunOutcome,
+    },
+    /// Estimated mapping of an external clock, e.g. of a sensor, see [feo_time::ClockMap]
+    ClockMapping {
+        /// Time since startup of the primary agent at the reference point of the mapping
+        timestamp: Duration,
+        /// Name of the external clock
+        clock: String,
+        /// Timestamp of the external clock at the reference point
+        external: Duration,
+        /// Drift of the external clock against the FEO clock in parts per billion
+        drift_ppb: i64,
+    },
+}
+
+impl Record {
+    /// Get the timestamp of the record
+    pub fn timestamp(&self) -> Duration {
+        match self {
+            Record::Cycle { timestamp } => *timestamp,
+            Record::Data { timestamp, .. } => *timestamp,
+            Record::Signal { timestamp, .. } => *timestamp,
+            Record::State { timestamp, .. } => *timestamp,
+            Record::Dropped { timestamp, .. } => *timestamp,
+            Record::Trigger { timestamp, .. } => *timestamp,
+            Record::SpeedChange { timestamp, .. } => *timestamp,
+            Record::Panic { timestamp, .. } => *timestamp,
+            Record::Overrun { timestamp, .. } => *timestamp,
+            Record::ClockMapping { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// Frame of a recording stream
+///
+/// The first variants match [Record::Cycle] and [Record::Data], so that these records serialized
+/// are frames as well. Later variants are appended to keep existing recordings readable.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum Frame {
+    /// See [Record::Cycle]
+    Cycle { timestamp: Duration },
+    /// See [Record::Data]
+    Data {
+        timestamp: Duration,
+        topic: String,
+        type_name: String,
+        data: Vec<u8>,
+    },
+    /// Header at the start of a recording
+    Header(RecordingHeader),
+    /// Compressed [Frame::Data], [Frame::Keyframe] and [Frame::Delta] frames of a cycle
+    Compressed {
+        /// Codec of this frame
+        compression: Compression,
+        /// Compressed COBS frames
+        frames: Vec<u8>,
+    },
+    /// See [Record::Signal]
+    Signal {
+        timestamp: Duration,
+        activity: u64,
+        cycle: u64,
+        kind: SignalKind,
+    },
+    /// See [Record::State]
+    State {
+        timestamp: Duration,
+        activity: u64,
+        cycle: u64,
+        state: Vec<u8>,
+    },
+    /// See [Record::Dropped]
+    Dropped {
+        timestamp: Duration,
+        topic: String,
+        count: u64,
+    },
+    /// See [Record::Trigger]
+    Trigger { timestamp: Duration, event: String },
+    /// See [Record::SpeedChange]
+    SpeedChange {
+        timestamp: Duration,
+        old: Option<i32>,
+        new: Option<i32>,
+        real_time: Duration,
+    },
+    /// Encrypted [Frame::Data], [Frame::Keyframe] and [Frame::Delta] frames or [Frame::Compressed] frame
+    /// of a cycle, or an encrypted [Frame::State], see [encryption]
+    Encrypted {
+        /// Random nonce of this frame
+        nonce: [u8; encryption::NONCE_SIZE],
+        /// Encrypted COBS frames followed by the authentication tag
+        frames: Vec<u8>,
+    },
+    /// [Record::Data] of a topic recorded with keyframes, holding the full sample, see [keyframe]
+    Keyframe {
+        timestamp: Duration,
+        topic: String,
+        type_name: String,
+        data: Vec<u8>,
+    },
+    /// [Record::Data] of a topic recorded with keyframes, holding the changes since the latest keyframe
+    Delta {
+        timestamp: Duration,
+        topic: String,
+        type_name: String,
+        delta: SampleDelta,
+    },
+    /// See [Record::Panic]
+    Panic {
+        timestamp: Duration,
+        activity: u64,
+        message: String,
+        backtrace: String,
+    },
+    /// See [Record::Overrun]
+    Overrun {
+        timestamp: Duration,
+        chain: u64,
+        cycle: u64,
+        duration: Duration,
+        outcome: OverrunOutcome,
+    },
+    /// See [Record::ClockMapping]
+    ClockMapping {
+        timestamp: Duration,
+        clock: String,
+        external: Duration,
+        drift_ppb: i64,
+    },
+}
@@ -0,0 +1,582 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Reader of recordings
+
+use crate::error::{Error, RecordingError};
+use crate::recording::codec::Encoding;
+use crate::recording::compression::Compression;
+use crate::recording::encryption::{RecordingKey, NONCE_SIZE};
+use crate::recording::header::RecordingHeader;
+use crate::recording::index::RecordingIndex;
+use crate::recording::{Frame, Record};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use score_log::debug;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+/// Iterator over the records of a recording
+///
+/// Compressed frames are decompressed one at a time while reading, and encrypted frames are decrypted
+/// with the key given with [RecordReader::with_key]. Samples of topics recorded with keyframes are
+/// reconstructed from the latest keyframe read, see [keyframe](crate::recording::keyframe).
+pub struct RecordReader<R: BufRead> {
+    /// Input of the recording
+    reader: R,
+    /// Buffer holding the current COBS frame
+    frame: Vec<u8>,
+    /// Byte offset of the next frame
+    offset: u64,
+    /// Byte offset of the frame holding the last record read
+    record_offset: u64,
+    /// Header of the recording, if read
+    header: Option<RecordingHeader>,
+    /// Records of a decompressed frame not read yet
+    pending: VecDeque<Record>,
+    /// Key of encrypted recordings, if any
+    key: Option<RecordingKey>,
+    /// Timestamp of the cycle of the frames read last, authenticated along with encrypted frames
+    cycle: Option<Duration>,
+    /// Latest keyframe per topic recorded with keyframes
+    keyframes: HashMap<String, Vec<u8>>,
+    /// Whether the frame buffer holds the start of a frame still being written, see [RecordReader::follow_record]
+    partial: bool,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    /// Create a new reader reading records from the given input
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            frame: Vec::new(),
+            offset: 0,
+            record_offset: 0,
+            header: None,
+            pending: VecDeque::new(),
+            key: None,
+            cycle: None,
+            keyframes: HashMap::new(),
+            partial: false,
+        }
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [encryption](crate::recording::encryption)
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Get the byte offset of the next frame relative to the start of the input
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Get the byte offset of the frame holding the last record read
+    pub fn record_offset(&self) -> u64 {
+        self.record_offset
+    }
+
+    /// Get the header of the recording, if read so far
+    pub fn header(&self) -> Option<&RecordingHeader> {
+        self.header.as_ref()
+    }
+
+    /// Get the codec noted in the header of the recording
+    pub fn compression(&self) -> Compression {
+        self.header
+            .as_ref()
+            .map(|header| header.compression)
+            .unwrap_or_default()
+    }
+
+    /// Get the encoding of the samples noted in the header of the recording
+    pub fn encoding(&self) -> Encoding {
+        self.header.as_ref().map(|header| header.encoding).unwrap_or_default()
+    }
+
+    /// Read the header at the start of the recording
+    ///
+    /// Returns `None` for recordings without header, e.g. the files following the first one
+    /// of a rotated recording. Must be called before reading any records.
+    pub fn read_header(&mut self) -> Result<Option<&RecordingHeader>, Error> {
+        if self.offset == 0 {
+            if let Some(frame) = self.read_frame()? {
+                self.push_frame(frame)?;
+            }
+        }
+        Ok(self.header.as_ref())
+    }
+
+    /// Read the next record, returning `None` at the end of the recording
+    pub fn read_record(&mut self) -> Result<Option<Record>, Error> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(Some(record));
+            }
+            let Some(frame) = self.read_frame()? else {
+                return Ok(None);
+            };
+            self.push_frame(frame)?;
+        }
+    }
+
+    /// Read the next record of a recording which is still being written
+    ///
+    /// Returns `None` if the next record has not been written completely yet. Unlike [RecordReader::read_record],
+    /// an incomplete record at the end of the input is kept and completed by the following calls, which read
+    /// the input again, e.g. after waiting for the writer to append to the file.
+    pub fn follow_record(&mut self) -> Result<Option<Record>, Error> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(Some(record));
+            }
+            let Some(frame) = self.follow_frame()? else {
+                return Ok(None);
+            };
+            self.push_frame(frame)?;
+        }
+    }
+
+    /// Handle a frame, queuing its records as pending
+    fn push_frame(&mut self, frame: Frame) -> Result<(), Error> {
+        match frame {
+            Frame::Cycle { timestamp } => {
+                self.cycle = Some(timestamp);
+                self.pending.push_back(Record::Cycle { timestamp });
+            },
+            Frame::Data {
+                timestamp,
+                topic,
+                type_name,
+                data,
+            } => self.pending.push_back(Record::Data {
+                timestamp,
+                topic,
+                type_name,
+                data,
+            }),
+            Frame::Header(header) => {
+                header.check_version()?;
+                if let (Some(key_id), Some(key)) = (header.key_id.as_deref(), self.key.as_ref()) {
+                    if key_id != key.id() {
+                        return Err(Error::Recording(RecordingError::Failed("recording encrypted with another key")));
+                    }
+                }
+                self.header = Some(header);
+            },
+            Frame::Compressed { compression, frames } => self.decompress(compression, &frames)?,
+            Frame::Signal {
+                timestamp,
+                activity,
+                cycle,
+                kind,
+            } => self.pending.push_back(Record::Signal {
+                timestamp,
+                activity,
+                cycle,
+                kind,
+            }),
+            Frame::State {
+                timestamp,
+                activity,
+                cycle,
+                state,
+            } => self.pending.push_back(Record::State {
+                timestamp,
+                activity,
+                cycle,
+                state,
+            }),
+            Frame::Dropped {
+                timestamp,
+                topic,
+                count,
+            } => self.pending.push_back(Record::Dropped {
+                timestamp,
+                topic,
+                count,
+            }),
+            Frame::Trigger { timestamp, event } => self.pending.push_back(Record::Trigger { timestamp, event }),
+            Frame::SpeedChange {
+                timestamp,
+                old,
+                new,
+                real_time,
+            } => self.pending.push_back(Record::SpeedChange {
+                timestamp,
+                old,
+                new,
+                real_time,
+            }),
+            Frame::Panic {
+                timestamp,
+                activity,
+                message,
+                backtrace,
+            } => self.pending.push_back(Record::Panic {
+                timestamp,
+                activity,
+                message,
+                backtrace,
+            }),
+            Frame::Overrun {
+                timestamp,
+                chain,
+                cycle,
+                duration,
+                outcome,
+            } => self.pending.push_back(Record::Overrun {
+                timestamp,
+                chain,
+                cycle,
+                duration,
+                outcome,
+            }),
+            Frame::ClockMapping {
+                timestamp,
+                clock,
+                external,
+                drift_ppb,
+            } => self.pending.push_back(Record::ClockMapping {
+                timestamp,
+                clock,
+                external,
+                drift_ppb,
+            }),
+            Frame::Encrypted { nonce, frames } => self.decrypt(&nonce, &frames)?,
+            Frame::Keyframe {
+                timestamp,
+                topic,
+                type_name,
+                data,
+            } => {
+                self.keyframes.insert(topic.clone(), data.clone());
+                self.pending.push_back(Record::Data {
+                    timestamp,
+                    topic,
+                    type_name,
+                    data,
+                });
+            },
+            Frame::Delta {
+                timestamp,
+                topic,
+                type_name,
+                delta,
+            } => match self.keyframes.get(&topic) {
+                Some(keyframe) => {
+                    let data = delta.apply(keyframe)?;
+                    self.pending.push_back(Record::Data {
+                        timestamp,
+                        topic,
+                        type_name,
+                        data,
+                    });
+                },
+                None => {
+                    debug!("Missing keyframe of topic {}, dropping sample", topic.as_str());
+                    self.pending.push_back(Record::Dropped {
+                        timestamp,
+                        topic,
+                        count: 1,
+                    });
+                },
+            },
+        }
+        Ok(())
+    }
+
+    /// Read the next frame, returning `None` at the end of the recording
+    fn read_frame(&mut self) -> Result<Option<Frame>, Error> {
+        self.frame.clear();
+        let len = self.reader.read_until(0, &mut self.frame)?;
+        if len == 0 {
+            return Ok(None);
+        }
+        self.record_offset = self.offset;
+        self.offset += len as u64;
+        if self.frame.last() != Some(&0) {
+            return Err(Error::Recording(RecordingError::Failed("truncated record at end of recording")));
+        }
+        postcard::from_bytes_cobs(&mut self.frame)
+            .map(Some)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize record")))
+    }
+
+    /// Read the next frame of a recording still being written, returning `None` until it is complete
+    fn follow_frame(&mut self) -> Result<Option<Frame>, Error> {
+        if !self.partial {
+            self.frame.clear();
+            self.record_offset = self.offset;
+        }
+        let len = self.reader.read_until(0, &mut self.frame)?;
+        self.offset += len as u64;
+        self.partial = self.frame.last() != Some(&0);
+        if self.partial {
+            return Ok(None);
+        }
+        postcard::from_bytes_cobs(&mut self.frame)
+            .map(Some)
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize record")))
+    }
+
+    /// Decompress the data records of a compressed frame into the pending records
+    fn decompress(&mut self, compression: Compression, frames: &[u8]) -> Result<(), Error> {
+        let mut frames = compression.decompress(frames)?;
+        for frame in frames.split_inclusive_mut(|byte| *byte == 0) {
+            match postcard::from_bytes_cobs(frame) {
+                Ok(frame @ (Frame::Data { .. } | Frame::Keyframe { .. } | Frame::Delta { .. })) => {
+                    self.push_frame(frame)?
+                },
+                _ => return Err(Error::Recording(RecordingError::Failed("invalid record in compressed frame"))),
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt the data records or the state of an encrypted frame into the pending records
+    fn decrypt(&mut self, nonce: &[u8; NONCE_SIZE], frames: &[u8]) -> Result<(), Error> {
+        let Some(key) = self.key.as_ref() else {
+            return Err(Error::Recording(RecordingError::Failed("encrypted recording, no key given")));
+        };
+        let Some(timestamp) = self.cycle else {
+            return Err(Error::Recording(RecordingError::Failed("encrypted frame outside of a cycle")));
+        };
+        let mut frames = key.decrypt(timestamp, nonce, frames)?;
+        for frame in frames.split_inclusive_mut(|byte| *byte == 0) {
+            match postcard::from_bytes_cobs(frame) {
+                Ok(
+                    frame @ (Frame::Data { .. }
+                    | Frame::Keyframe { .. }
+                    | Frame::Delta { .. }
+                    | Frame::Compressed { .. }
+                    | Frame::State { .. }),
+                ) => self.push_frame(frame)?,
+                _ => return Err(Error::Recording(RecordingError::Failed("invalid record in encrypted frame"))),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead + Seek> RecordReader<R> {
+    /// Continue reading at the given byte offset, which must be the start of a frame
+    pub fn seek(&mut self, offset: u64) -> Result<(), Error> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.offset = offset;
+        self.pending.clear();
+        self.partial = false;
+        self.cycle = None;
+        // Keyframes read so far may be newer than the samples at the given offset
+        self.keyframes.clear();
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Reader of a recording seeking by time or record number through a [RecordingIndex]
+pub struct RecordingReader<R: BufRead + Seek> {
+    /// Reader of the records
+    records: RecordReader<R>,
+    /// Index of the recording
+    index: RecordingIndex,
+    /// Number of the next record
+    next_record: u64,
+}
+
+impl RecordingReader<BufReader<File>> {
+    /// Open a recording file with its sidecar index and read its header
+    ///
+    /// If the recording has no sidecar index, the index is built by scanning the recording once.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with_key(path, None)
+    }
+
+    /// Open a recording file like [Self::open], decrypting its samples with the given key, if any
+    pub fn open_with_key(path: &Path, key: Option<RecordingKey>) -> Result<Self, Error> {
+        let index = match RecordingIndex::load(path) {
+            Ok(index) => index,
+            Err(_) => {
+                let mut records = RecordReader::new(BufReader::new(File::open(path)?));
+                records.key = key.clone();
+                RecordingIndex::build_from(records)?
+            },
+        };
+        let mut reader = Self::new(BufReader::new(File::open(path)?), index);
+        reader.records.key = key;
+        reader.read_header()?;
+        Ok(reader)
+    }
+}
+
+impl<R: BufRead + Seek> RecordingReader<R> {
+    /// Create a new reader of the given recording and its index
+    pub fn new(reader: R, index: RecordingIndex) -> Self {
+        Self {
+            records: RecordReader::new(reader),
+            index,
+            next_record: 0,
+        }
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [RecordReader::with_key]
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.records.key = Some(key);
+        self
+    }
+
+    /// Get the index of the recording
+    pub fn index(&self) -> &RecordingIndex {
+        &self.index
+    }
+
+    /// Get the header of the recording, if read so far
+    pub fn header(&self) -> Option<&RecordingHeader> {
+        self.records.header()
+    }
+
+    /// Read the header at the start of the recording, see [RecordReader::read_header]
+    pub fn read_header(&mut self) -> Result<Option<&RecordingHeader>, Error> {
+        self.records.read_header()
+    }
+
+    /// Get the number of the next record, counting from zero
+    pub fn next_record(&self) -> u64 {
+        self.next_record
+    }
+
+    /// Read the next record, returning `None` at the end of the recording
+    pub fn read_record(&mut self) -> Result<Option<Record>, Error> {
+        let record = self.records.read_record()?;
+        if record.is_some() {
+            self.next_record += 1;
+        }
+        Ok(record)
+    }
+
+    /// Continue reading at the last cycle starting at or before the given time
+    ///
+    /// Continues at the start of the recording if the time is before its first cycle.
+    pub fn seek_to_time(&mut self, timestamp: Duration) -> Result<(), Error> {
+        let (offset, record) = self
+            .index
+            .find_time(timestamp)
+            .map(|entry| (entry.offset, entry.record))
+            .unwrap_or_default();
+        self.records.seek(offset)?;
+        self.next_record = record;
+        Ok(())
+    }
+
+    /// Continue reading at the given record number
+    pub fn seek_to_record(&mut self, record: u64) -> Result<(), Error> {
+        let (offset, start) = self
+            .index
+            .find_record(record)
+            .map(|entry| (entry.offset, entry.record))
+            .unwrap_or_default();
+        self.records.seek(offset)?;
+        self.next_record = start;
+        while self.next_record < record {
+            if self.read_record()?.is_none() {
+                return Err(Error::Recording(RecordingError::Failed("record number beyond end of recording")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead + Seek> Iterator for RecordingReader<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_roundtrip() {
+        use crate::recording::recorder::write_record;
+        use alloc::string::ToString;
+        use alloc::vec;
+        use feo_time::Duration;
+
+        let records = [
+            Record::Cycle {
+                timestamp: Duration::from_millis(10),
+            },
+            Record::Data {
+                timestamp: Duration::from_millis(10),
+                topic: "feo/com/vehicle/camera".to_string(),
+                type_name: "CameraImage".to_string(),
+                data: vec![0, 1, 2, 0, 3],
+            },
+        ];
+
+        let mut recording = Vec::new();
+        for record in records.iter() {
+            write_record(&mut recording, record).unwrap();
+        }
+
+        let mut reader = RecordReader::new(&recording[..]);
+        for record in records.iter() {
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(record));
+        }
+        assert!(reader.read_record().unwrap().is_none());
+
+        // A truncated tail is reported as an error
+        let mut reader = RecordReader::new(&recording[..recording.len() - 1]);
+        assert!(reader.read_record().unwrap().is_some());
+        assert!(reader.read_record().is_err());
+    }
+
+    #[test]
+    fn records_are_followed_while_written() {
+        use crate::recording::recorder::write_record;
+        use std::io::Cursor;
+
+        let cycle = |timestamp: u64| Record::Cycle {
+            timestamp: Duration::from_millis(timestamp),
+        };
+        let mut frames = Vec::new();
+        write_record(&mut frames, &cycle(10)).unwrap();
+        let first_len = frames.len();
+        write_record(&mut frames, &cycle(20)).unwrap();
+
+        // The second record is written in two parts
+        let mut reader = RecordReader::new(Cursor::new(frames[..first_len + 2].to_vec()));
+        assert_eq!(reader.follow_record().unwrap(), Some(cycle(10)));
+        assert_eq!(reader.follow_record().unwrap(), None);
+        assert_eq!(reader.follow_record().unwrap(), None);
+        reader.reader.get_mut().extend_from_slice(&frames[first_len + 2..]);
+        assert_eq!(reader.follow_record().unwrap(), Some(cycle(20)));
+        assert_eq!(reader.record_offset(), first_len as u64);
+        assert_eq!(reader.follow_record().unwrap(), None);
+    }
+}
@@ -0,0 +1,896 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Recorder activity writing topic samples to a recording
+
+use crate::activity::Activity;
+use crate::debug_topic;
+use crate::error::{ActivityError, Error, RecordingError};
+use crate::ids::ActivityId;
+use crate::recording::backpressure::{BackpressurePolicy, DroppedSamples, QueuedCycle, QueuedSample, RecordQueue};
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::recording::codec::Encoding;
+use crate::recording::compression::Compression;
+use crate::recording::encryption::RecordingKey;
+use crate::recording::filter::{RecordFilter, RecordFilterHandle};
+use crate::recording::header::{RecordedType, RecordingHeader};
+use crate::recording::index::{write_index_entry, IndexEntry};
+use crate::recording::keyframe::KeyframeWriter;
+use crate::recording::schema::SchemaRegistry;
+use crate::recording::signals::SignalCapture;
+use crate::recording::sink::RecordSink;
+use crate::recording::trigger::{note_triggers, PreTriggerBuffer, RecordTrigger, TriggerWindow};
+use crate::recording::{Frame, Record};
+use crate::timestamp::{sync_info, timestamp};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::mem;
+use feo_com::interface::{ActivityInput, FeoComData};
+use feo_time::Duration;
+use score_log::{debug, error, info, trace, warn};
+use serde::Serialize;
+use std::io::Write;
+
+/// Serialize a record and write it as a single COBS frame
+pub fn write_record<W: Write>(writer: &mut W, record: &Record) -> Result<(), Error> {
+    let frame = match record {
+        Record::Signal {
+            timestamp,
+            activity,
+            cycle,
+            kind,
+        } => postcard::to_allocvec_cobs(&Frame::Signal {
+            timestamp: *timestamp,
+            activity: *activity,
+            cycle: *cycle,
+            kind: *kind,
+        }),
+        Record::State {
+            timestamp,
+            activity,
+            cycle,
+            state,
+        } => postcard::to_allocvec_cobs(&Frame::State {
+            timestamp: *timestamp,
+            activity: *activity,
+            cycle: *cycle,
+            state: state.clone(),
+        }),
+        Record::Dropped {
+            timestamp,
+            topic,
+            count,
+        } => postcard::to_allocvec_cobs(&Frame::Dropped {
+            timestamp: *timestamp,
+            topic: topic.clone(),
+            count: *count,
+        }),
+        Record::Trigger { timestamp, event } => postcard::to_allocvec_cobs(&Frame::Trigger {
+            timestamp: *timestamp,
+            event: event.clone(),
+        }),
+        Record::SpeedChange {
+            timestamp,
+            old,
+            new,
+            real_time,
+        } => postcard::to_allocvec_cobs(&Frame::SpeedChange {
+            timestamp: *timestamp,
+            old: *old,
+            new: *new,
+            real_time: *real_time,
+        }),
+        Record::Panic {
+            timestamp,
+            activity,
+            message,
+            backtrace,
+        } => postcard::to_allocvec_cobs(&Frame::Panic {
+            timestamp: *timestamp,
+            activity: *activity,
+            message: message.clone(),
+            backtrace: backtrace.clone(),
+        }),
+        Record::Overrun {
+            timestamp,
+            chain,
+            cycle,
+            duration,
+            outcome,
+        } => postcard::to_allocvec_cobs(&Frame::Overrun {
+            timestamp: *timestamp,
+            chain: *chain,
+            cycle: *cycle,
+            duration: *duration,
+            outcome: *outcome,
+        }),
+        Record::ClockMapping {
+            timestamp,
+            clock,
+            external,
+            drift_ppb,
+        } => postcard::to_allocvec_cobs(&Frame::ClockMapping {
+            timestamp: *timestamp,
+            clock: clock.clone(),
+            external: *external,
+            drift_ppb: *drift_ppb,
+        }),
+        _ => postcard::to_allocvec_cobs(record),
+    }
+    .map_err(|_| Error::Recording(RecordingError::Failed("failed to serialize record")))?;
+    writer.write_all(&frame)?;
+    Ok(())
+}
+
+/// Serialize a frame and write it as a single COBS frame
+pub(crate) fn write_frame<W: Write>(writer: &mut W, frame: &Frame) -> Result<(), Error> {
+    let frame = postcard::to_allocvec_cobs(frame)
+        .map_err(|_| Error::Recording(RecordingError::Failed("failed to serialize frame")))?;
+    writer.write_all(&frame)?;
+    Ok(())
+}
+
+/// Recorder activity
+///
+/// On each step, the recorder writes a [Record::Cycle] followed by one [Record::Data]
+/// for each configured topic with a sample available and selected by the [RecordFilter].
+/// With a [SignalCapture], the data records are followed by the [Record::Signal]s captured
+/// since the previous step, see [signals](crate::recording::signals).
+/// All records of a cycle are passed to the output in a single write,
+/// which allows a [RotatingFileWriter](crate::recording::rotation::RotatingFileWriter)
+/// to rotate files at cycle boundaries only. Besides any [Write], the output may be another
+/// [RecordSink], e.g. an iceoryx2 service, see [sink](crate::recording::sink).
+///
+/// On startup, the recorder writes a [RecordingHeader] listing the recorded types
+/// and noting the [Compression] of the recording and the [Encoding] of its samples.
+/// The types registered in a [SchemaRegistry] are listed with the descriptors of their schemas,
+/// see [schema](crate::recording::schema).
+/// With compression enabled, the data records of a cycle are compressed into a single frame,
+/// see [compression](crate::recording::compression).
+/// With a [RecordingKey], the data records of a cycle are encrypted into a single frame as well,
+/// and each state into a frame of its own, see [encryption](crate::recording::encryption).
+/// Topics with large, slowly changing samples can be recorded as keyframes and deltas,
+/// see [keyframe](crate::recording::keyframe).
+///
+/// Optionally, the recorder writes an index of the recorded cycles, see [index](crate::recording::index).
+/// The byte offsets of the index count all bytes passed to the output, so an index is only valid
+/// for outputs writing a single file.
+///
+/// With a queue, the cycles are written by a background thread and samples may be dropped
+/// while the output cannot keep up, see [backpressure](crate::recording::backpressure).
+///
+/// In flight recorder mode, the cycles are kept in memory and only written around the triggers
+/// fired by the application, see [trigger](crate::recording::trigger).
+///
+/// Changes of the clock speed via [feo_time::change_speed] are noted as [Record::SpeedChange]s
+/// following the cycle they occurred in, see [timeline](crate::recording::timeline).
+/// Likewise, updated estimates of the external clocks mapped with [feo_time::ClockMap] are noted as
+/// [Record::ClockMapping]s.
+///
+/// Optionally, the recorder records the debug topics of its process like topics, registering them
+/// as they appear, see [debug_topic]. Only the debug topics registered before startup are noted in the header.
+pub struct Recorder<W: RecordSink> {
+    /// ID of the recorder activity
+    id: ActivityId,
+    /// Output of the recording
+    output: Output<W>,
+    /// Encoding of the recorded samples
+    encoding: Encoding,
+    /// Schemas of the recorded types to note in the header
+    schemas: SchemaRegistry,
+    /// Topics to record
+    topics: Vec<Box<dyn RecordTopic>>,
+    /// Selection of the recorded topics
+    filter: RecordFilterHandle,
+    /// Signals of the scheduler to record, if any
+    signals: Option<SignalCapture>,
+    /// Checkpoints of activity states to record, if any
+    checkpoints: Option<StateCheckpoints>,
+    /// Capacity of the queue and function starting its writer thread on startup, if queued
+    queue: Option<(usize, SpawnQueue<W>)>,
+    /// Reaction to a full queue per topic, blocking if not configured
+    policies: BTreeMap<String, BackpressurePolicy>,
+    /// Numbers of dropped samples per topic
+    dropped: DroppedSamples,
+    /// Numbers of dropped samples per topic already noted in the recording
+    reported: Vec<u64>,
+    /// Trigger fired by the application
+    trigger: RecordTrigger,
+    /// Cycles before the next trigger, if in flight recorder mode
+    pre_trigger: Option<PreTriggerBuffer>,
+    /// Number of clock speed changes already noted in the recording
+    speed_changes: usize,
+    /// Sequences of the clock mappings already noted in the recording, see [feo_time::clock_mappings]
+    clock_mappings: Vec<u64>,
+    /// Number of debug topics recorded so far, if recording debug topics
+    debug_topics: Option<usize>,
+    /// Cycle of the main task chain after which the recorder joined, if joined late
+    join_cycle: Option<u64>,
+}
+
+/// Function starting the writer thread of a queue
+type SpawnQueue<W> = fn(CycleWriter<W>, usize) -> Result<RecordQueue, Error>;
+
+/// Output of a recorder
+enum Output<W: RecordSink> {
+    /// Cycles are written within the step of the recorder
+    Direct(CycleWriter<W>),
+    /// Cycles are written by the writer thread of a queue
+    Queued(RecordQueue),
+    /// The recorder has been shut down
+    Closed,
+}
+
+impl<W: RecordSink> Recorder<W> {
+    /// Create a new recorder writing to the given output, any [Write] or other [RecordSink]
+    pub fn new(id: ActivityId, writer: W) -> Self {
+        Self {
+            id,
+            output: Output::Direct(CycleWriter::new(writer, Compression::None)),
+            encoding: Encoding::default(),
+            schemas: SchemaRegistry::default(),
+            topics: Vec::new(),
+            filter: RecordFilterHandle::default(),
+            signals: None,
+            checkpoints: None,
+            queue: None,
+            policies: BTreeMap::new(),
+            dropped: DroppedSamples::default(),
+            reported: Vec::new(),
+            trigger: RecordTrigger::default(),
+            pre_trigger: None,
+            speed_changes: 0,
+            clock_mappings: Vec::new(),
+            debug_topics: None,
+            join_cycle: None,
+        }
+    }
+
+    /// Write an index of the recorded cycles to the given output, e.g. the sidecar file
+    /// at [index_path](crate::recording::index::index_path) of the recording
+    pub fn with_index(mut self, index: impl Write + Send + 'static) -> Self {
+        self.writer().index = Some(Box::new(index));
+        self
+    }
+
+    /// Record the step signals captured by the scheduler
+    ///
+    /// The same capture must be passed to the configuration of the primary agent running the recorder.
+    pub fn with_signals(mut self, signals: SignalCapture) -> Self {
+        self.signals = Some(signals);
+        self
+    }
+
+    /// Record the states saved by the checkpointed activities
+    ///
+    /// The same checkpoints must be passed to the configuration of the agent running the recorder.
+    pub fn with_checkpoints(mut self, checkpoints: StateCheckpoints) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// Record the debug topics published in this process, see [debug_topic]
+    pub fn with_debug_topics(mut self) -> Self {
+        self.debug_topics = Some(0);
+        self
+    }
+
+    /// Note in the header that the recorder joined a running primary agent after the given cycle,
+    /// see [join](crate::recording::join)
+    pub fn with_join_cycle(mut self, cycle: u64) -> Self {
+        self.join_cycle = Some(cycle);
+        self
+    }
+
+    /// Compress the recorded samples with the given codec
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.writer().compression = compression;
+        self
+    }
+
+    /// Record the given topic with a full sample every `interval` samples and the changes
+    /// since the latest full sample in between
+    pub fn with_keyframes(mut self, topic: &str, interval: u64) -> Self {
+        self.writer().keyframes.add_topic(topic.to_string(), interval);
+        self
+    }
+
+    /// Encrypt the recorded samples and states with the given key, noting the id of the key in the header
+    pub fn with_encryption(mut self, key: RecordingKey) -> Self {
+        self.writer().encryption = Some(key);
+        self
+    }
+
+    /// Encode the recorded samples with the given encoding instead of postcard
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Note the schemas of the recorded types registered in the given registry in the header
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Set the initial filter of the recorded topics
+    pub fn with_filter(self, filter: RecordFilter) -> Self {
+        self.filter.set(filter);
+        self
+    }
+
+    /// Write the cycles from a background thread through a queue holding up to `capacity` samples
+    ///
+    /// While the queue is full, the [BackpressurePolicy] of each topic applies, see [Self::with_policy].
+    pub fn with_queue(mut self, capacity: usize) -> Self
+    where
+        W: Send + 'static,
+    {
+        self.queue = Some((capacity, RecordQueue::spawn::<W>));
+        self
+    }
+
+    /// Set the reaction to new samples of the given topic while the queue is full
+    pub fn with_policy(mut self, topic: &str, policy: BackpressurePolicy) -> Self {
+        self.policies.insert(topic.to_string(), policy);
+        self
+    }
+
+    /// Only write the cycles within the given windows around the triggers fired through [Self::trigger_handle]
+    ///
+    /// The cycles of the pre-trigger window are kept in memory until a trigger fires.
+    pub fn with_trigger(mut self, window: TriggerWindow) -> Self {
+        self.pre_trigger = Some(PreTriggerBuffer::new(window));
+        self
+    }
+
+    /// Get a handle to fire the trigger of the recorder
+    pub fn trigger_handle(&self) -> RecordTrigger {
+        self.trigger.clone()
+    }
+
+    /// Get a handle to change the filter of the recorded topics at runtime
+    pub fn filter_handle(&self) -> RecordFilterHandle {
+        self.filter.clone()
+    }
+
+    /// Get a handle to the numbers of samples dropped per topic while the queue was full
+    pub fn dropped_samples(&self) -> DroppedSamples {
+        self.dropped.clone()
+    }
+
+    /// Record the given topic, reading its samples from the given input
+    pub fn record_topic<T>(&mut self, topic: &str, input: Box<dyn ActivityInput<T>>)
+    where
+        T: FeoComData + Serialize + 'static,
+    {
+        self.push_topic(topic, None, input);
+    }
+
+    /// Record the given topic produced by the given activity, reading its samples from the given input
+    ///
+    /// The producer allows to select the topic by its producing activity in the [RecordFilter].
+    pub fn record_topic_from<T>(&mut self, topic: &str, producer: ActivityId, input: Box<dyn ActivityInput<T>>)
+    where
+        T: FeoComData + Serialize + 'static,
+    {
+        self.push_topic(topic, Some(producer), input);
+    }
+
+    fn push_topic<T>(&mut self, topic: &str, producer: Option<ActivityId>, input: Box<dyn ActivityInput<T>>)
+    where
+        T: FeoComData + Serialize + 'static,
+    {
+        self.topics.push(Box::new(TopicReader {
+            topic: topic.to_string(),
+            producer,
+            input,
+            samples: 0,
+        }));
+        self.reported.push(0);
+    }
+
+    /// Record the debug topics registered since the previous call, if recording debug topics
+    fn push_debug_topics(&mut self) {
+        let Some(recorded) = self.debug_topics else {
+            return;
+        };
+        let count = debug_topic::count();
+        for index in recorded..count {
+            let Some(sample) = debug_topic::sample(index) else {
+                break;
+            };
+            debug!("Recorder {} recording debug topic {}", self.id, sample.topic.as_str());
+            self.topics.push(Box::new(DebugTopicReader {
+                index,
+                recorded_type: sample.value.recorded_type(),
+                topic: sample.topic,
+                sequence: 0,
+                samples: 0,
+            }));
+            self.reported.push(0);
+        }
+        self.debug_topics = Some(count);
+    }
+
+    /// Get the writer of the output, which is only available before startup
+    fn writer(&mut self) -> &mut CycleWriter<W> {
+        match &mut self.output {
+            Output::Direct(writer) => writer,
+            _ => unreachable!("recorder configured after startup"),
+        }
+    }
+
+    /// Write the header of the recording and start the writer thread of the queue, if any
+    fn record_header(&mut self) -> Result<(), Error> {
+        self.push_debug_topics();
+        let encoding = self.encoding;
+        let join_cycle = self.join_cycle;
+        let types: Vec<RecordedType> = self
+            .topics
+            .iter()
+            .map(|topic| topic.recorded_type())
+            .map(|recorded| {
+                let schema = self.schemas.get(&recorded.type_name).cloned();
+                recorded.with_schema(schema)
+            })
+            .collect();
+        let Output::Direct(writer) = &mut self.output else {
+            return Err(Error::Recording(RecordingError::AlreadyStarted));
+        };
+        let mut header = RecordingHeader::new(sync_info().since_epoch(), writer.compression)
+            .with_encoding(encoding)
+            .with_types(types);
+        if let Some(key) = writer.encryption.as_ref() {
+            header = header.with_key_id(key.id());
+        }
+        if let Some(cycle) = join_cycle {
+            header = header.with_join_cycle(cycle);
+        }
+        writer.write_header(header)?;
+        // The header notes the speed at startup, earlier changes are not recorded
+        self.speed_changes = feo_time::speed_changes(0).len();
+
+        if let Some((capacity, spawn)) = self.queue {
+            let Output::Direct(writer) = mem::replace(&mut self.output, Output::Closed) else {
+                return Err(Error::Recording(RecordingError::AlreadyStarted));
+            };
+            self.output = Output::Queued(spawn(writer, capacity)?);
+        }
+        Ok(())
+    }
+
+    /// Write the records of a single cycle
+    fn record_cycle(&mut self) -> Result<(), Error> {
+        self.push_debug_topics();
+        let mut cycle = QueuedCycle {
+            timestamp: timestamp().0,
+            ..Default::default()
+        };
+        let filter = self.filter.lock();
+        for (index, topic) in self.topics.iter_mut().enumerate() {
+            if let Some(record) = topic.read(cycle.timestamp, &filter, self.encoding)? {
+                cycle.samples.push(QueuedSample { topic: index, record });
+            }
+        }
+        drop(filter);
+        if let Some(signals) = self.signals.as_ref() {
+            cycle.trailer.extend(signals.take());
+        }
+        if let Some(checkpoints) = self.checkpoints.as_ref() {
+            cycle.trailer.extend(checkpoints.take());
+        }
+        self.report_dropped(cycle.timestamp, &mut cycle.trailer);
+        self.report_speed_changes(&mut cycle.trailer);
+        self.report_clock_mappings(&mut cycle.trailer);
+
+        let events = self.trigger.take();
+        let triggered = !events.is_empty();
+        if triggered {
+            info!("Recorder {} triggered by {}", self.id, events[0].as_str());
+        }
+        note_triggers(&mut cycle, events);
+        let cycles = match self.pre_trigger.as_mut() {
+            Some(buffer) => buffer.admit(cycle, triggered),
+            None => alloc::vec![cycle],
+        };
+
+        for cycle in cycles {
+            self.write_cycle(cycle)?;
+        }
+        Ok(())
+    }
+
+    /// Pass the records of a single cycle to the output
+    fn write_cycle(&mut self, cycle: QueuedCycle) -> Result<(), Error> {
+        match &mut self.output {
+            Output::Direct(writer) => writer.write_cycle(cycle),
+            Output::Queued(queue) => {
+                let (id, topics, policies, dropped) = (self.id, &self.topics, &self.policies, &self.dropped);
+                let policy = |index: usize| policies.get(topics[index].topic()).copied().unwrap_or_default();
+                queue.push(cycle, policy, |index| {
+                    let topic = topics[index].topic();
+                    if dropped.add(topic) == 1 {
+                        warn!("Recorder {} cannot keep up, dropping samples of topic {}", id, topic);
+                    }
+                })
+            },
+            Output::Closed => Err(Error::Recording(RecordingError::Failed("recorder has been shut down"))),
+        }
+    }
+
+    /// Note the samples dropped since the previous cycle in the given records
+    fn report_dropped(&mut self, timestamp: Duration, records: &mut Vec<Record>) {
+        for (topic, reported) in self.topics.iter().zip(self.reported.iter_mut()) {
+            let count = self.dropped.count(topic.topic());
+            if count > *reported {
+                records.push(Record::Dropped {
+                    timestamp,
+                    topic: topic.topic().to_string(),
+                    count: count - *reported,
+                });
+                *reported = count;
+            }
+        }
+    }
+
+    /// Note the clock speed changes since the previous cycle in the given records
+    fn report_speed_changes(&mut self, records: &mut Vec<Record>) {
+        let changes = feo_time::speed_changes(self.speed_changes);
+        self.speed_changes += changes.len();
+        let startup = sync_info().since_epoch();
+        records.extend(changes.into_iter().map(|change| Record::SpeedChange {
+            timestamp: change.at.saturating_sub(startup),
+            old: change.old,
+            new: change.new,
+            real_time: change.real,
+        }));
+    }
+
+    /// Note the clock mappings updated since the previous cycle in the given records
+    fn report_clock_mappings(&mut self, records: &mut Vec<Record>) {
+        let mappings = feo_time::clock_mappings();
+        if mappings.is_empty() {
+            return;
+        }
+        let startup = sync_info().since_epoch();
+        self.clock_mappings.resize(mappings.len(), 0);
+        for (mapping, reported) in mappings.into_iter().zip(self.clock_mappings.iter_mut()) {
+            if mapping.sequence == *reported {
+                continue;
+            }
+            *reported = mapping.sequence;
+            records.push(Record::ClockMapping {
+                timestamp: mapping.at.saturating_sub(startup),
+                clock: mapping.clock,
+                external: mapping.external,
+                drift_ppb: mapping.drift_ppb,
+            });
+        }
+    }
+
+    /// Write all pending cycles and flush the output
+    fn close(&mut self) -> Result<(), Error> {
+        let result = match mem::replace(&mut self.output, Output::Closed) {
+            Output::Direct(mut writer) => writer.flush(),
+            Output::Queued(queue) => queue.close(),
+            Output::Closed => Ok(()),
+        };
+        for (topic, count) in self.dropped.counts() {
+            warn!(
+                "Recorder {} dropped {} samples of topic {}",
+                self.id,
+                count,
+                topic.as_str()
+            );
+        }
+        result
+    }
+}
+
+impl<W: RecordSink> Activity for Recorder<W> {
+    fn id(&self) -> ActivityId {
+        self.id
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        debug!("Recorder {} recording {} topics", self.id, self.topics.len());
+        self.record_header().map_err(|e| {
+            error!("Recorder {} failed to write recording header: {:?}", self.id, e);
+            ActivityError::Startup
+        })
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        self.record_cycle().map_err(|e| {
+            error!("Recorder {} failed to record cycle: {:?}", self.id, e);
+            ActivityError::Step
+        })
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        self.close().map_err(|e| {
+            error!("Recorder {} failed to flush recording: {:?}", self.id, e);
+            ActivityError::Shutdown
+        })
+    }
+}
+
+/// Writer of the records of the cycles to the output of a recorder
+pub(crate) struct CycleWriter<W: RecordSink> {
+    /// Output of the recording
+    writer: W,
+    /// Buffer collecting the records of a cycle
+    cycle: Vec<u8>,
+    /// Buffer collecting the data records of a cycle before compression
+    data: Vec<u8>,
+    /// Compression of the data records
+    compression: Compression,
+    /// Key encrypting the data records and the states, if any
+    encryption: Option<RecordingKey>,
+    /// Keyframe state of the topics recorded with keyframes
+    keyframes: KeyframeWriter,
+    /// Output of the index, if any
+    index: Option<Box<dyn Write + Send>>,
+    /// Number of records written so far
+    records: u64,
+    /// Number of bytes written so far
+    offset: u64,
+}
+
+impl<W: RecordSink> CycleWriter<W> {
+    /// Create a writer to the given output
+    pub(crate) fn new(writer: W, compression: Compression) -> Self {
+        Self {
+            writer,
+            cycle: Vec::new(),
+            data: Vec::new(),
+            compression,
+            encryption: None,
+            keyframes: KeyframeWriter::default(),
+            index: None,
+            records: 0,
+            offset: 0,
+        }
+    }
+
+    /// Write the header of the recording
+    fn write_header(&mut self, header: RecordingHeader) -> Result<(), Error> {
+        self.cycle.clear();
+        write_frame(&mut self.cycle, &Frame::Header(header))?;
+        self.writer.write_frames(&self.cycle)?;
+        self.offset += self.cycle.len() as u64;
+        Ok(())
+    }
+
+    /// Write the records of a single cycle
+    pub(crate) fn write_cycle(&mut self, cycle: QueuedCycle) -> Result<(), Error> {
+        let QueuedCycle {
+            timestamp,
+            samples,
+            trailer,
+        } = cycle;
+        self.cycle.clear();
+        self.data.clear();
+        write_record(&mut self.cycle, &Record::Cycle { timestamp })?;
+        let records = 1 + samples.len() + trailer.len();
+        for sample in samples.iter() {
+            match self.keyframes.frame(&sample.record) {
+                Some(frame) => write_frame(&mut self.data, &frame)?,
+                None => write_record(&mut self.data, &sample.record)?,
+            }
+        }
+        if self.compression != Compression::None && !self.data.is_empty() {
+            let compression = self.compression;
+            let frames = compression.compress(&self.data)?;
+            self.data.clear();
+            write_frame(&mut self.data, &Frame::Compressed { compression, frames })?;
+        }
+        match self.encryption.as_ref() {
+            Some(key) if !self.data.is_empty() => {
+                let (nonce, frames) = key.encrypt(timestamp, &self.data)?;
+                write_frame(&mut self.cycle, &Frame::Encrypted { nonce, frames })?;
+            },
+            _ => self.cycle.extend_from_slice(&self.data),
+        }
+        for record in trailer.iter() {
+            match (self.encryption.as_ref(), record) {
+                (Some(key), Record::State { .. }) => {
+                    self.data.clear();
+                    write_record(&mut self.data, record)?;
+                    let (nonce, frames) = key.encrypt(timestamp, &self.data)?;
+                    write_frame(&mut self.cycle, &Frame::Encrypted { nonce, frames })?;
+                },
+                _ => write_record(&mut self.cycle, record)?,
+            }
+        }
+        self.writer.write_frames(&self.cycle)?;
+
+        if let Some(index) = self.index.as_mut() {
+            let entry = IndexEntry {
+                timestamp,
+                record: self.records,
+                offset: self.offset,
+            };
+            write_index_entry(index, &entry)?;
+        }
+        self.records += records as u64;
+        self.offset += self.cycle.len() as u64;
+        Ok(())
+    }
+
+    /// Flush the index, if any, and the output
+    pub(crate) fn flush(&mut self) -> Result<(), Error> {
+        if let Some(index) = self.index.as_mut() {
+            index.flush()?;
+        }
+        self.writer.flush_frames()?;
+        Ok(())
+    }
+}
+
+/// Type-erased source of topic samples
+trait RecordTopic {
+    /// Read the current sample of the topic, if any and selected by the filter, and encode it
+    fn read(&mut self, timestamp: Duration, filter: &RecordFilter, encoding: Encoding)
+        -> Result<Option<Record>, Error>;
+
+    /// Get the type of the samples of the topic
+    fn recorded_type(&self) -> RecordedType;
+
+    /// Get the name of the topic
+    fn topic(&self) -> &str;
+}
+
+/// Reader of samples of a specific type
+struct TopicReader<T: FeoComData> {
+    topic: String,
+    producer: Option<ActivityId>,
+    input: Box<dyn ActivityInput<T>>,
+    /// Number of samples read from the topic while selected by the filter
+    samples: u64,
+}
+
+impl<T: FeoComData + Serialize + 'static> RecordTopic for TopicReader<T> {
+    fn read(
+        &mut self,
+        timestamp: Duration,
+        filter: &RecordFilter,
+        encoding: Encoding,
+    ) -> Result<Option<Record>, Error> {
+        if !filter.matches(&self.topic, type_name::<T>(), self.producer) {
+            return Ok(None);
+        }
+        let Ok(sample) = self.input.read() else {
+            trace!("No sample available on topic {}", self.topic.as_str());
+            return Ok(None);
+        };
+        let index = self.samples;
+        self.samples += 1;
+        if !filter.sampled(&self.topic, index) {
+            trace!("Skipping sample {} of topic {}", index, self.topic.as_str());
+            return Ok(None);
+        }
+        let data = encoding.encode(&*sample)?;
+        Ok(Some(Record::Data {
+            timestamp,
+            topic: self.topic.clone(),
+            type_name: type_name::<T>().to_string(),
+            data,
+        }))
+    }
+
+    fn recorded_type(&self) -> RecordedType {
+        RecordedType::of::<T>()
+    }
+
+    fn topic(&self) -> &str {
+        &self.topic
+    }
+}
+
+/// Reader of the latest samples of a debug topic
+struct DebugTopicReader {
+    /// Index of the debug topic in the order of registration
+    index: usize,
+    topic: String,
+    recorded_type: RecordedType,
+    /// Sequence number of the latest sample read
+    sequence: u64,
+    /// Number of samples read from the topic while selected by the filter
+    samples: u64,
+}
+
+impl RecordTopic for DebugTopicReader {
+    fn read(
+        &mut self,
+        timestamp: Duration,
+        filter: &RecordFilter,
+        encoding: Encoding,
+    ) -> Result<Option<Record>, Error> {
+        let Some(sample) = debug_topic::sample(self.index).filter(|sample| sample.sequence > self.sequence) else {
+            return Ok(None);
+        };
+        self.sequence = sample.sequence;
+        if !filter.matches(&self.topic, &self.recorded_type.type_name, sample.activity) {
+            return Ok(None);
+        }
+        let index = self.samples;
+        self.samples += 1;
+        if !filter.sampled(&self.topic, index) {
+            return Ok(None);
+        }
+        Ok(Some(Record::Data {
+            timestamp,
+            topic: self.topic.clone(),
+            type_name: self.recorded_type.type_name.clone(),
+            data: sample.value.encode(encoding)?,
+        }))
+    }
+
+    fn recorded_type(&self) -> RecordedType {
+        self.recorded_type.clone()
+    }
+
+    fn topic(&self) -> &str {
+        &self.topic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_encrypted() {
+        use crate::recording::encryption::KEY_SIZE;
+        use crate::recording::reader::RecordReader;
+        use alloc::vec;
+
+        let key = RecordingKey::new("vehicle-1", &[0x0f; KEY_SIZE]);
+        let mut writer = CycleWriter::new(Vec::new(), Compression::None);
+        writer.encryption = Some(key.clone());
+        let header = RecordingHeader::new(Duration::from_secs(1), Compression::None).with_key_id(key.id());
+        writer.write_header(header).unwrap();
+
+        let state = Record::State {
+            timestamp: Duration::from_millis(10),
+            activity: 1,
+            cycle: 3,
+            state: b"position of the vehicle".to_vec(),
+        };
+        let cycle = QueuedCycle {
+            timestamp: Duration::from_millis(10),
+            trailer: vec![state.clone()],
+            ..Default::default()
+        };
+        writer.write_cycle(cycle).unwrap();
+        let recording = writer.writer;
+        assert!(!recording.windows(23).any(|window| window == b"position of the vehicle"));
+
+        let mut reader = RecordReader::new(&recording[..]).with_key(key);
+        let cycle = Record::Cycle {
+            timestamp: Duration::from_millis(10),
+        };
+        assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+        assert_eq!(reader.read_record().unwrap().as_ref(), Some(&state));
+        assert!(reader.read_record().unwrap().is_none());
+
+        let mut reader = RecordReader::new(&recording[..]);
+        assert_eq!(reader.read_record().unwrap().as_ref(), Some(&cycle));
+        assert!(reader.read_record().is_err());
+    }
+}
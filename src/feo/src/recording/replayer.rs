@@ -0,0 +1,382 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Replay of recorded topic data
+
+use crate::activity::Activity;
+use crate::error::{ActivityError, Error, RecordingError};
+use crate::ids::{ActivityId, ChainId};
+use crate::on_demand::CycleTrigger;
+use crate::recording::checkpoint::Checkpoint;
+use crate::recording::codec::Encoding;
+use crate::recording::encryption::RecordingKey;
+use crate::recording::header::RecordedType;
+use crate::recording::reader::RecordReader;
+use crate::recording::schema::{decode_evolved, Compatibility, SchemaDescriptor, SchemaRegistry};
+use crate::recording::Record;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use feo_com::interface::{ActivityOutput, FeoComData};
+use feo_time::{Deadline, Duration, Instant};
+use score_log::{debug, error, info, trace, warn};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Function publishing a sample of the given encoding on a topic, recorded with the given schema if evolved since
+type Publisher = Box<dyn FnMut(Encoding, Option<&SchemaDescriptor>, &[u8]) -> Result<(), Error>>;
+
+/// Pacing of a replay run with [Replayer::run]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    /// Replay cycles with their recorded timing.
+    ///
+    /// The timing is scaled according to the speed factor set with [feo_time::speed],
+    /// allowing for faster- or slower-than-real-time replay.
+    Recorded,
+    /// Replay cycles as fast as possible
+    Unpaced,
+}
+
+/// Replayer of recorded topic data
+///
+/// The replayer reads [Record]s from a recording and publishes the samples of
+/// all registered topics into the com layer, one recorded cycle at a time.
+///
+/// To re-execute a task chain deterministically, the replayer can be run as an activity
+/// at the start of the chain, replaying one recorded cycle per step.
+/// Alternatively, it can run standalone with [Replayer::run], or with [Replayer::run_triggered]
+/// to drive a task chain cycled on demand.
+///
+/// Samples of types which gained fields since recording are decoded by field name, if the recording
+/// lists the schemas of its types, see [schema](crate::recording::schema).
+pub struct Replayer<R: BufRead> {
+    /// ID of the replayer activity
+    id: ActivityId,
+    /// Reader of the recording
+    reader: RecordReader<R>,
+    /// Publishers and expected types per topic
+    publishers: HashMap<String, (RecordedType, Publisher)>,
+    /// Schemas of registered types overriding the ones derived from their [Deserialize](serde::Deserialize)
+    /// implementation
+    schemas: SchemaRegistry,
+    /// Recorded schemas of the topics whose types evolved since recording
+    evolved: HashMap<String, SchemaDescriptor>,
+    /// Record read ahead while looking for the end of a cycle
+    pending: Option<Record>,
+    /// Whether the header of the recording has been checked
+    checked: bool,
+    /// Whether the end of the recording has been reached
+    finished: bool,
+}
+
+impl<R: BufRead> Replayer<R> {
+    /// Create a new replayer reading from the given recording
+    pub fn new(id: ActivityId, reader: R) -> Self {
+        Self {
+            id,
+            reader: RecordReader::new(reader),
+            publishers: HashMap::new(),
+            schemas: SchemaRegistry::default(),
+            evolved: HashMap::new(),
+            pending: None,
+            checked: false,
+            finished: false,
+        }
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [encryption](crate::recording::encryption)
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.reader = self.reader.with_key(key);
+        self
+    }
+
+    /// Use the schemas registered in the given registry for types with a handwritten
+    /// [Deserialize](serde::Deserialize) implementation
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Publish the recorded samples of the given topic on the given output.
+    ///
+    /// Samples of topics not registered are skipped.
+    pub fn publish_topic<T>(&mut self, topic: &str, mut output: Box<dyn ActivityOutput<T>>)
+    where
+        T: FeoComData + DeserializeOwned + 'static,
+    {
+        let publisher = move |encoding: Encoding, schema: Option<&SchemaDescriptor>, data: &[u8]| -> Result<(), Error> {
+            let sample: T = match schema {
+                Some(recorded) => decode_evolved(encoding, recorded, data)?,
+                None => encoding.decode(data)?,
+            };
+            output
+                .write_uninit()
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to get output buffer")))?
+                .write_payload(sample)
+                .send()
+                .map_err(|_| Error::Recording(RecordingError::Failed("failed to send sample")))
+        };
+        let expected = RecordedType::of::<T>().with_schema(Some(SchemaDescriptor::of::<T>()));
+        self.publishers.insert(topic.to_string(), (expected, Box::new(publisher)));
+    }
+
+    /// Replay all remaining cycles of the recording
+    pub fn run(&mut self, pacing: Pacing) -> Result<(), Error> {
+        self.run_with(pacing, || {})
+    }
+
+    /// Replay all remaining cycles of the recording, triggering a cycle of the given on-demand
+    /// task chain after each replayed cycle, see [on_demand](crate::on_demand)
+    ///
+    /// With [Pacing::Recorded], the chain is triggered with the recorded timing of the cycles.
+    pub fn run_triggered(&mut self, pacing: Pacing, trigger: &CycleTrigger, chain: ChainId) -> Result<(), Error> {
+        self.run_with(pacing, || trigger.trigger_chain(chain))
+    }
+
+    /// Replay all remaining cycles of the recording, calling `replayed` after each cycle
+    fn run_with(&mut self, pacing: Pacing, mut replayed: impl FnMut()) -> Result<(), Error> {
+        let start = Instant::now();
+        let mut first_timestamp = None;
+
+        while let Some(timestamp) = self.peek_cycle()? {
+            if pacing == Pacing::Recorded {
+                let offset = timestamp.saturating_sub(*first_timestamp.get_or_insert(timestamp));
+                Deadline::at(start + offset).wait();
+            }
+            self.replay_cycle()?;
+            replayed();
+        }
+
+        info!("Replay finished after {:?}", start.elapsed());
+        Ok(())
+    }
+
+    /// Skip the recorded cycles before the given time without publishing their samples
+    ///
+    /// Returns the latest states of the activities recorded before the first cycle recorded at or after
+    /// the given time, to be restored with [StateCheckpoints::restoring](crate::recording::checkpoint::StateCheckpoints::restoring).
+    pub fn skip_to(&mut self, timestamp: Duration) -> Result<Checkpoint, Error> {
+        let mut checkpoint = Checkpoint::default();
+        loop {
+            match self.next_record()? {
+                None => break,
+                Some(Record::Cycle { timestamp: cycle }) if cycle >= timestamp => {
+                    self.pending = Some(Record::Cycle { timestamp: cycle });
+                    checkpoint.timestamp = cycle;
+                    break;
+                },
+                Some(record) => checkpoint.update(&record),
+            }
+        }
+        debug!(
+            "Replayer {} skipped to {:?} with {} checkpointed activities",
+            self.id,
+            checkpoint.timestamp,
+            checkpoint.states.len()
+        );
+        Ok(checkpoint)
+    }
+
+    /// Replay the next recorded cycle.
+    ///
+    /// Returns the recorded timestamp of the cycle or `None`, if the end of the recording has been reached.
+    pub fn replay_cycle(&mut self) -> Result<Option<Duration>, Error> {
+        let Some(timestamp) = self.peek_cycle()? else {
+            return Ok(None);
+        };
+        // Consume the cycle record
+        self.pending = None;
+
+        loop {
+            match self.next_record()? {
+                None => break,
+                Some(record @ Record::Cycle { .. }) => {
+                    self.pending = Some(record);
+                    break;
+                },
+                Some(record) => self.publish(&record)?,
+            }
+        }
+
+        trace!("Replayed cycle recorded at {:?}", timestamp);
+        Ok(Some(timestamp))
+    }
+
+    /// Replay the latest recorded cycle starting at or before the given time, skipping the cycles before it.
+    ///
+    /// Returns the recorded timestamp of the replayed cycle or `None`, if no further cycle was recorded
+    /// up to the given time. The samples of skipped cycles are not published.
+    pub fn replay_at(&mut self, timestamp: Duration) -> Result<Option<Duration>, Error> {
+        let mut replayed = None;
+        let mut records = Vec::new();
+        while let Some(cycle) = self.peek_cycle()? {
+            if cycle > timestamp {
+                break;
+            }
+            // Consume the cycle record and keep the records of the cycle until the next cycle is known
+            self.pending = None;
+            records.clear();
+            loop {
+                match self.next_record()? {
+                    None => break,
+                    Some(record @ Record::Cycle { .. }) => {
+                        self.pending = Some(record);
+                        break;
+                    },
+                    Some(record) => records.push(record),
+                }
+            }
+            replayed = Some(cycle);
+        }
+        for record in records.iter() {
+            self.publish(record)?;
+        }
+
+        if let Some(timestamp) = replayed {
+            trace!("Replayed cycle recorded at {:?}", timestamp);
+        }
+        Ok(replayed)
+    }
+
+    /// Get the timestamp of the next recorded cycle without consuming it.
+    ///
+    /// Samples recorded before the first cycle are published immediately.
+    pub(super) fn peek_cycle(&mut self) -> Result<Option<Duration>, Error> {
+        loop {
+            match self.next_record()? {
+                None => return Ok(None),
+                Some(Record::Cycle { timestamp }) => {
+                    self.pending = Some(Record::Cycle { timestamp });
+                    return Ok(Some(timestamp));
+                },
+                Some(record) => self.publish(&record)?,
+            }
+        }
+    }
+
+    /// Check the schemas of the registered types against the types listed in the header of the recording
+    ///
+    /// Types whose schema changed are accepted if the recording lists their schema and only fields
+    /// have been added since.
+    fn check_header(&mut self) -> Result<(), Error> {
+        let Some(header) = self.reader.read_header()? else {
+            debug!("Replayer {} reading recording without header", self.id);
+            return Ok(());
+        };
+        for (topic, (expected, _)) in self.publishers.iter() {
+            let Some(recorded) = header.recorded_type(&expected.type_name) else {
+                continue;
+            };
+            if recorded.schema_hash == expected.schema_hash {
+                continue;
+            }
+            let current = self.schemas.get(&expected.type_name).or(expected.schema.as_ref());
+            let compatibility = match (current, recorded.schema.as_ref()) {
+                (Some(current), Some(recorded)) => current.check_compatible(recorded),
+                _ => Ok(Compatibility::Identical),
+            };
+            match compatibility {
+                Ok(Compatibility::Evolved { added }) => {
+                    warn!(
+                        "Type {} of topic {} evolved since recording, filling in {} added fields with defaults",
+                        expected.type_name.as_str(),
+                        topic.as_str(),
+                        added.len()
+                    );
+                    self.evolved.insert(topic.clone(), recorded.schema.clone().unwrap_or_default());
+                },
+                // Fields removed or the layout changed with the same fields, e.g. by a changed field type
+                Ok(Compatibility::Identical) | Err(_) => {
+                    error!(
+                        "Schema of recorded type {} of topic {} does not match registered type",
+                        expected.type_name.as_str(),
+                        topic.as_str()
+                    );
+                    return Err(Error::Recording(RecordingError::Failed("incompatible schema of recorded type")));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the next record, taking a pending record first
+    fn next_record(&mut self) -> Result<Option<Record>, Error> {
+        if !self.checked {
+            self.checked = true;
+            self.check_header()?;
+        }
+        if let Some(record) = self.pending.take() {
+            return Ok(Some(record));
+        }
+        self.reader.read_record()
+    }
+
+    /// Publish the sample of a data record on its topic, if registered
+    fn publish(&mut self, record: &Record) -> Result<(), Error> {
+        let Record::Data {
+            topic, type_name, data, ..
+        } = record
+        else {
+            return Ok(());
+        };
+        let Some((expected, publisher)) = self.publishers.get_mut(topic) else {
+            trace!("Skipping sample of unregistered topic {}", topic.as_str());
+            return Ok(());
+        };
+        if expected.type_name != *type_name {
+            error!(
+                "Recorded type {} of topic {} does not match registered type {}",
+                type_name.as_str(),
+                topic.as_str(),
+                expected.type_name.as_str()
+            );
+            return Err(Error::Recording(RecordingError::Failed("type mismatch of recorded sample")));
+        }
+        publisher(self.reader.encoding(), self.evolved.get(topic), data)
+    }
+}
+
+impl<R: BufRead> Activity for Replayer<R> {
+    fn id(&self) -> ActivityId {
+        self.id
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        debug!("Replayer {} replaying {} topics", self.id, self.publishers.len());
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        if self.finished {
+            return Ok(());
+        }
+        match self.replay_cycle() {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => {
+                info!("Replayer {} reached the end of the recording", self.id);
+                self.finished = true;
+                Ok(())
+            },
+            Err(e) => {
+                error!("Replayer {} failed to replay cycle: {:?}", self.id, e);
+                Err(ActivityError::Step)
+            },
+        }
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        Ok(())
+    }
+}
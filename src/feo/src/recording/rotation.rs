@@ -0,0 +1,200 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Rotation of recording files
+//!
+//! A [RotatingFileWriter] splits a recording into numbered files (`rec_0001.bin`, `rec_0002.bin`, ...)
+//! in an output directory. A new file is started once the current one exceeds the configured size
+//! or age, and the oldest files are deleted to keep at most the configured number of files.
+
+use crate::error::Error;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, warn};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Default prefix of recording file names
+pub const DEFAULT_FILE_PREFIX: &str = "rec";
+
+/// Rotation policy of recording files
+///
+/// All limits are disabled by default, resulting in a single unbounded file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RotationPolicy {
+    /// Maximum size of a single file in bytes
+    pub max_file_size: Option<u64>,
+    /// Maximum duration covered by a single file
+    pub max_file_duration: Option<Duration>,
+    /// Maximum number of files to keep, deleting the oldest ones first
+    pub max_files: Option<usize>,
+}
+
+/// Writer splitting its output into rotated files
+///
+/// Rotation only happens between calls to [Write::write], so that each call
+/// is written to a single file. The [Recorder](crate::recording::recorder::Recorder)
+/// writes a complete cycle per call, so that each file starts with a cycle record
+/// and can be replayed on its own.
+///
+/// Existing files with the same names in the output directory are overwritten.
+pub struct RotatingFileWriter {
+    /// Output directory
+    directory: PathBuf,
+    /// Prefix of file names
+    prefix: String,
+    /// Rotation policy
+    policy: RotationPolicy,
+    /// Current output file
+    file: BufWriter<File>,
+    /// Index of the current file
+    index: u32,
+    /// Bytes written to the current file
+    size: u64,
+    /// Creation time of the current file
+    created: Instant,
+    /// Paths of all retained files, oldest first
+    files: VecDeque<PathBuf>,
+}
+
+impl RotatingFileWriter {
+    /// Create a new writer, creating the output directory and the first file
+    pub fn new(directory: impl Into<PathBuf>, prefix: &str, policy: RotationPolicy) -> Result<Self, Error> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create recording directory")))?;
+
+        let index = 1;
+        let path = file_path(&directory, prefix, index);
+        let file = create_file(&path)?;
+        Ok(Self {
+            directory,
+            prefix: prefix.to_string(),
+            policy,
+            file,
+            index,
+            size: 0,
+            created: Instant::now(),
+            files: VecDeque::from([path]),
+        })
+    }
+
+    /// Path of the current output file
+    pub fn current_path(&self) -> &Path {
+        self.files.back().expect("current file is always retained")
+    }
+
+    /// Check whether the current file has to be rotated before writing `len` bytes
+    fn rotation_due(&self, len: usize) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        let size_exceeded = self
+            .policy
+            .max_file_size
+            .is_some_and(|max| self.size + len as u64 > max);
+        let age_exceeded = self
+            .policy
+            .max_file_duration
+            .is_some_and(|max| self.created.elapsed() >= max);
+        size_exceeded || age_exceeded
+    }
+
+    /// Close the current file and continue with the next one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        self.index += 1;
+        let path = file_path(&self.directory, &self.prefix, self.index);
+        debug!("Rotating recording to {}", &*path.to_string_lossy());
+        self.file = BufWriter::new(File::create(&path)?);
+        self.size = 0;
+        self.created = Instant::now();
+        self.files.push_back(path);
+
+        let max_files = self.policy.max_files.unwrap_or(usize::MAX).max(1);
+        while self.files.len() > max_files {
+            let Some(oldest) = self.files.pop_front() else {
+                break;
+            };
+            if let Err(e) = fs::remove_file(&oldest) {
+                warn!(
+                    "Failed to remove recording file {}: {:?}",
+                    &*oldest.to_string_lossy(),
+                    ScoreDebugIoError(e)
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.rotation_due(buf.len()) {
+            self.rotate()?;
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Path of the recording file with the given index
+fn file_path(directory: &Path, prefix: &str, index: u32) -> PathBuf {
+    directory.join(format!("{prefix}_{index:04}.bin"))
+}
+
+/// Create a buffered recording file
+fn create_file(path: &Path) -> Result<BufWriter<File>, Error> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create recording file")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_rotated_by_size_and_retained() {
+        let directory = std::env::temp_dir().join(format!("feo_rotation_test_{}", std::process::id()));
+        let policy = RotationPolicy {
+            max_file_size: Some(8),
+            max_files: Some(2),
+            ..Default::default()
+        };
+        let mut writer = RotatingFileWriter::new(&directory, DEFAULT_FILE_PREFIX, policy).unwrap();
+
+        // Each write exceeding the size limit of the current file starts a new one
+        for cycle in [b"cycle_1", b"cycle_2", b"cycle_3"] {
+            writer.write_all(cycle).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert!(!directory.join("rec_0001.bin").exists());
+        assert_eq!(fs::read(directory.join("rec_0002.bin")).unwrap(), b"cycle_2");
+        assert_eq!(fs::read(directory.join("rec_0003.bin")).unwrap(), b"cycle_3");
+        assert_eq!(writer.current_path(), directory.join("rec_0003.bin").as_path());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
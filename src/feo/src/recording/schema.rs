@@ -0,0 +1,290 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Schemas of recorded types and their evolution
+//!
+//! The schema hash of the [header](crate::recording::header) detects recorded types which changed since
+//! recording, but cannot tell whether the samples can still be read. A [SchemaDescriptor] lists the fields of
+//! a struct type in their serialization order. It is derived from the [Deserialize] implementation of the type
+//! with [SchemaDescriptor::of] or given manually for types with a handwritten implementation.
+//!
+//! Recorders write the descriptors of the types registered in their [SchemaRegistry] to the header.
+//! When replaying, the descriptors of the recorded and the current type are compared with
+//! [SchemaDescriptor::check_compatible]. If fields have been added to the type since recording,
+//! samples are decoded by field name, filling in the added fields with their defaults. Added fields
+//! must therefore be marked with `#[serde(default)]`:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Speed {
+//!     timestamp_ms: u64,
+//!     speed_kmh: f32,
+//!     // Added after recording, zero in replayed samples of older recordings
+//!     #[serde(default)]
+//!     acceleration: f32,
+//! }
+//! ```
+//!
+//! Only added fields of the top-level struct are supported. Removed fields, changed field types and changes
+//! of nested types are not detected by the descriptors and still fail to decode.
+
+use crate::error::{Error, RecordingError};
+use crate::recording::codec::Encoding;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use score_log::error;
+use serde::de::value::StrDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, Error as _, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// Descriptor of the serialized form of a recorded type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaDescriptor {
+    /// Names of the fields of a struct type in serialization order, empty for other types
+    pub fields: Vec<String>,
+}
+
+impl SchemaDescriptor {
+    /// Derive the descriptor of the given type from its [Deserialize] implementation
+    ///
+    /// Types not deserialized as a struct get a descriptor without fields.
+    pub fn of<T: DeserializeOwned>() -> Self {
+        let mut fields = None;
+        // Tracing always ends with an error, after noting the fields of a struct
+        let _ = T::deserialize(FieldTracer { fields: &mut fields });
+        Self {
+            fields: fields
+                .unwrap_or_default()
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+        }
+    }
+
+    /// Create a descriptor of a struct type with the given fields in serialization order
+    pub fn with_fields<'a>(fields: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            fields: fields.into_iter().map(|field| field.to_string()).collect(),
+        }
+    }
+
+    /// Check whether samples recorded with the given schema can be decoded with this schema
+    pub fn check_compatible(&self, recorded: &SchemaDescriptor) -> Result<Compatibility, Error> {
+        if self == recorded {
+            return Ok(Compatibility::Identical);
+        }
+        if self.fields.is_empty() || recorded.fields.is_empty() {
+            return Err(Error::Recording(RecordingError::Failed("recorded type is not a struct")));
+        }
+        if let Some(removed) = recorded.fields.iter().find(|field| !self.fields.contains(field)) {
+            error!("Field {} of recorded type has been removed", removed.as_str());
+            return Err(Error::Recording(RecordingError::Failed("field of recorded type removed")));
+        }
+        let added = self
+            .fields
+            .iter()
+            .filter(|field| !recorded.fields.contains(field))
+            .cloned()
+            .collect();
+        Ok(Compatibility::Evolved { added })
+    }
+}
+
+/// Result of comparing the schema of a recorded type with its current schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The schemas match, samples are decoded as they are
+    Identical,
+    /// Fields have been added to the type or reordered, samples are decoded by field name
+    Evolved {
+        /// Fields not in the recording, filled in with their defaults
+        added: Vec<String>,
+    },
+}
+
+/// Schemas of recorded types by type name
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, SchemaDescriptor>,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the schema of the given type derived from its [Deserialize] implementation
+    pub fn register<T: DeserializeOwned + 'static>(mut self) -> Self {
+        self.schemas.insert(type_name::<T>().to_string(), SchemaDescriptor::of::<T>());
+        self
+    }
+
+    /// Register the given schema of the given type
+    pub fn register_schema<T: 'static>(mut self, schema: SchemaDescriptor) -> Self {
+        self.schemas.insert(type_name::<T>().to_string(), schema);
+        self
+    }
+
+    /// Get the schema of the type with the given name, if registered
+    pub fn get(&self, type_name: &str) -> Option<&SchemaDescriptor> {
+        self.schemas.get(type_name)
+    }
+}
+
+/// Decode a sample recorded with the given schema of an evolved type
+pub(crate) fn decode_evolved<T: DeserializeOwned>(
+    encoding: Encoding,
+    recorded: &SchemaDescriptor,
+    data: &[u8],
+) -> Result<T, Error> {
+    match encoding {
+        Encoding::Postcard => {
+            let mut deserializer = postcard::Deserializer::from_bytes(data);
+            T::deserialize(RecordedStruct {
+                fields: &recorded.fields,
+                deserializer: &mut deserializer,
+            })
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to deserialize sample of evolved type")))
+        },
+        // CBOR encodes field names, so missing fields are filled in by serde
+        Encoding::Cbor => encoding.decode(data),
+        Encoding::RawBytes => Err(Error::Recording(RecordingError::Failed(
+            "raw bytes encoding does not support evolved types",
+        ))),
+    }
+}
+
+/// Deserializer noting the fields of a struct type without deserializing anything
+struct FieldTracer<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de> Deserializer<'de> for FieldTracer<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Self::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(Self::Error::custom("struct traced"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Postcard deserializer of a struct recorded with the given fields, presenting the struct as a map
+///
+/// Postcard encodes the fields of a struct in order without their names. Presenting them as map entries
+/// keyed by the recorded field names lets serde match them to the current fields by name and fill in
+/// the missing fields with their defaults.
+struct RecordedStruct<'a, 'de> {
+    fields: &'a [String],
+    deserializer: &'a mut postcard::Deserializer<'de, postcard::de_flavors::Slice<'de>>,
+}
+
+impl<'de> Deserializer<'de> for RecordedStruct<'_, 'de> {
+    type Error = postcard::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Self::Error::custom("evolved type is not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for RecordedStruct<'_, 'de> {
+    type Error = postcard::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((field, rest)) = self.fields.split_first() else {
+            return Ok(None);
+        };
+        self.fields = rest;
+        let key: StrDeserializer<'_, Self::Error> = field.as_str().into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evolved_samples_are_decoded_with_defaults() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Recorded {
+            id: u32,
+            speed: f32,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Current {
+            id: u32,
+            #[serde(default)]
+            label: String,
+            speed: f32,
+        }
+
+        let recorded = SchemaDescriptor::of::<Recorded>();
+        let current = SchemaDescriptor::of::<Current>();
+        assert_eq!(recorded, SchemaDescriptor::with_fields(["id", "speed"]));
+        assert_eq!(SchemaDescriptor::of::<u32>(), SchemaDescriptor::default());
+        assert_eq!(
+            current.check_compatible(&recorded).unwrap(),
+            Compatibility::Evolved {
+                added: alloc::vec!["label".to_string()]
+            }
+        );
+        assert!(recorded.check_compatible(&current).is_err());
+
+        let sample = Recorded { id: 7, speed: 2.5 };
+        let expected = Current {
+            id: 7,
+            label: String::new(),
+            speed: 2.5,
+        };
+        for encoding in [Encoding::Postcard, Encoding::Cbor] {
+            let data = encoding.encode(&sample).unwrap();
+            assert_eq!(decode_evolved::<Current>(encoding, &recorded, &data).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Capture of the scheduling signals in recordings
+//!
+//! With a [SignalCapture] passed to the primary agent, the scheduler captures the step signals it exchanges
+//! with the activities. A [Recorder](crate::recording::recorder::Recorder) holding a clone of the capture
+//! writes the signals captured since its previous step as [Record::Signal]s following the [Record::Cycle]
+//! of its step, which allows to correlate the recorded data with the scheduling behavior.
+//!
+//! The capture is shared in memory, so the recorder must run in the primary agent. Signals of steps
+//! are captured only, startup and shutdown are not. Panics reported by activities are captured in
+//! any lifecycle method as [Record::Panic], overrunning cycles of task chains as [Record::Overrun].
+
+use crate::ids::{ActivityId, ChainId};
+use crate::overrun::OverrunOutcome;
+use crate::panic_report::PanicReport;
+use crate::recording::Record;
+use crate::timestamp::Timestamp;
+use alloc::collections::VecDeque;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Maximum number of captured signals kept until taken by the recorder
+///
+/// The oldest signals are dropped if the recorder does not keep up or is not running.
+const MAX_PENDING: usize = 65536;
+
+/// Kind of a captured signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignalKind {
+    /// The scheduler triggered a step of the activity
+    Trigger,
+    /// The activity finished its step, timestamped by the worker running the activity
+    StepComplete,
+    /// The scheduler received the ready signal of the finished step
+    Ready,
+    /// The scheduler received the failure of the step
+    Failed,
+}
+
+impl SignalKind {
+    /// Get the name of the kind as used in exports
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignalKind::Trigger => "trigger",
+            SignalKind::StepComplete => "step_complete",
+            SignalKind::Ready => "ready",
+            SignalKind::Failed => "failed",
+        }
+    }
+}
+
+/// Handle to the signals captured by the scheduler of the primary agent
+///
+/// Clones share the same captured signals.
+#[derive(Debug, Clone, Default)]
+pub struct SignalCapture {
+    signals: Arc<Mutex<VecDeque<Record>>>,
+}
+
+impl SignalCapture {
+    /// Create a new, empty capture
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture a signal of the given activity in the given cycle of its task chain
+    pub(crate) fn capture(&self, kind: SignalKind, activity: ActivityId, cycle: u64, timestamp: Timestamp) {
+        let mut signals = self.signals.lock().expect("poisoned lock");
+        if signals.len() == MAX_PENDING {
+            signals.pop_front();
+        }
+        signals.push_back(Record::Signal {
+            timestamp: timestamp.0,
+            activity: activity.id(),
+            cycle,
+            kind,
+        });
+    }
+
+    /// Capture the panic of an activity reported to the scheduler
+    pub(crate) fn capture_panic(&self, report: &PanicReport, timestamp: Timestamp) {
+        let mut signals = self.signals.lock().expect("poisoned lock");
+        if signals.len() == MAX_PENDING {
+            signals.pop_front();
+        }
+        signals.push_back(Record::Panic {
+            timestamp: timestamp.0,
+            activity: report.activity.id(),
+            message: report.message().to_string(),
+            backtrace: report.backtrace().to_string(),
+        });
+    }
+
+    /// Capture the outcome of an overrunning cycle of a task chain
+    pub(crate) fn capture_overrun(
+        &self,
+        chain: ChainId,
+        cycle: u64,
+        duration: Duration,
+        outcome: OverrunOutcome,
+        timestamp: Timestamp,
+    ) {
+        let mut signals = self.signals.lock().expect("poisoned lock");
+        if signals.len() == MAX_PENDING {
+            signals.pop_front();
+        }
+        signals.push_back(Record::Overrun {
+            timestamp: timestamp.0,
+            chain: chain.id(),
+            cycle,
+            duration,
+            outcome,
+        });
+    }
+
+    /// Take all signals captured so far
+    pub(crate) fn take(&self) -> Vec<Record> {
+        self.signals.lock().expect("poisoned lock").drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_signals_roundtrip() {
+        use crate::recording::reader::RecordReader;
+        use crate::recording::recorder::write_record;
+
+        let capture = SignalCapture::new();
+        let scheduler = capture.clone();
+        scheduler.capture(
+            SignalKind::Trigger,
+            ActivityId::new(3),
+            7,
+            Timestamp(Duration::from_micros(100)),
+        );
+        scheduler.capture(
+            SignalKind::Ready,
+            ActivityId::new(3),
+            7,
+            Timestamp(Duration::from_micros(250)),
+        );
+
+        let records = capture.take();
+        assert_eq!(records.len(), 2);
+        assert!(capture.take().is_empty());
+
+        let mut recording = Vec::new();
+        for record in records.iter() {
+            write_record(&mut recording, record).unwrap();
+        }
+        let mut reader = RecordReader::new(&recording[..]);
+        for record in records.iter() {
+            assert_eq!(reader.read_record().unwrap().as_ref(), Some(record));
+        }
+        assert!(matches!(
+            records[1],
+            Record::Signal {
+                activity: 3,
+                cycle: 7,
+                kind: SignalKind::Ready,
+                ..
+            }
+        ));
+    }
+}
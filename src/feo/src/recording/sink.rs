@@ -0,0 +1,160 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Outputs of the recorder
+//!
+//! A [Recorder](crate::recording::recorder::Recorder) passes the header and all records of each cycle
+//! to its [RecordSink] in a single call, as COBS frames exactly as stored in a recording file.
+//! Any [Write] is a sink, e.g. a file or a [RotatingFileWriter](crate::recording::rotation::RotatingFileWriter).
+//!
+//! An [Iox2RecordSink] publishes the frames instead on an iceoryx2 service, one sample per call.
+//! An uploader process receives them with an [Iox2RecordSource] and streams the recording off-board,
+//! e.g. by concatenating the samples into a file or reading them with a
+//! [RecordReader](crate::recording::reader::RecordReader), without the recorder touching a file system:
+//!
+//! ```ignore
+//! let recorder = Recorder::new(id, Iox2RecordSink::new(RECORD_SERVICE)?);
+//!
+//! // In the uploader process
+//! let source = Iox2RecordSource::new(RECORD_SERVICE)?;
+//! loop {
+//!     match source.receive()? {
+//!         Some(frames) => upload(&frames),
+//!         None => thread::sleep(POLL_INTERVAL),
+//!     }
+//! }
+//! ```
+//!
+//! The header is published once at startup of the recorder, so the uploader must be running before.
+//! A slow uploader loses the oldest cycles once [SINK_BUFFER_SIZE] cycles are pending. To keep a slow
+//! uploader from losing cycles silently, combine the sink with a [queue](crate::recording::backpressure)
+//! and watch the dropped samples it reports.
+
+use crate::error::{Error, RecordingError};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use feo_com::iox2::ipc_node;
+use iceoryx2::port::publisher::Publisher;
+use iceoryx2::port::subscriber::Subscriber;
+use iceoryx2::prelude::{AllocationStrategy, ServiceName};
+use iceoryx2::service::ipc;
+use score_log::error;
+use std::io::Write;
+
+/// Default name of the iceoryx2 service publishing recordings
+pub const RECORD_SERVICE: &str = "feo/recording";
+
+/// Number of published cycles kept per subscriber until received
+pub const SINK_BUFFER_SIZE: usize = 64;
+
+/// Initial size (bytes) of the samples of the sink, grown on demand
+const INITIAL_SAMPLE_SIZE: usize = 64 * 1024;
+
+/// Output of the records of a recorder
+pub trait RecordSink {
+    /// Pass the frames of the header or of a single cycle
+    fn write_frames(&mut self, frames: &[u8]) -> Result<(), Error>;
+
+    /// Flush the frames passed so far
+    fn flush_frames(&mut self) -> Result<(), Error>;
+}
+
+impl<W: Write> RecordSink for W {
+    fn write_frames(&mut self, frames: &[u8]) -> Result<(), Error> {
+        self.write_all(frames)?;
+        Ok(())
+    }
+
+    fn flush_frames(&mut self) -> Result<(), Error> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Sink publishing the frames of a recorder on an iceoryx2 service
+pub struct Iox2RecordSink {
+    publisher: Publisher<ipc::Service, [u8], ()>,
+}
+
+impl Iox2RecordSink {
+    /// Create a sink publishing on the iceoryx2 service with the given name, e.g. [RECORD_SERVICE]
+    pub fn new(service: &str) -> Result<Self, Error> {
+        let publisher = ipc_node()
+            .service_builder(&service_name(service)?)
+            .publish_subscribe::<[u8]>()
+            .subscriber_max_buffer_size(SINK_BUFFER_SIZE)
+            .open_or_create()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to open record service")))?
+            .publisher_builder()
+            .initial_max_slice_len(INITIAL_SAMPLE_SIZE)
+            .allocation_strategy(AllocationStrategy::PowerOfTwo)
+            .create()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to create record publisher")))?;
+        Ok(Self { publisher })
+    }
+}
+
+impl RecordSink for Iox2RecordSink {
+    fn write_frames(&mut self, frames: &[u8]) -> Result<(), Error> {
+        let sample = self.publisher.loan_slice_uninit(frames.len()).map_err(|e| {
+            error!("Failed to loan sample of {} bytes: {}", frames.len(), e.to_string().as_str());
+            Error::Recording(RecordingError::Failed("failed to loan record sample"))
+        })?;
+        sample
+            .write_from_slice(frames)
+            .send()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to publish record sample")))?;
+        Ok(())
+    }
+
+    fn flush_frames(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Convert the name of a record service into the name of its iceoryx2 service
+fn service_name(service: &str) -> Result<ServiceName, Error> {
+    service
+        .try_into()
+        .map_err(|_| Error::Recording(RecordingError::Failed("invalid record service name")))
+}
+
+/// Receiver of the frames published by an [Iox2RecordSink], e.g. in an uploader process
+pub struct Iox2RecordSource {
+    subscriber: Subscriber<ipc::Service, [u8], ()>,
+}
+
+impl Iox2RecordSource {
+    /// Subscribe to the iceoryx2 service with the given name, e.g. [RECORD_SERVICE]
+    pub fn new(service: &str) -> Result<Self, Error> {
+        let subscriber = ipc_node()
+            .service_builder(&service_name(service)?)
+            .publish_subscribe::<[u8]>()
+            .subscriber_max_buffer_size(SINK_BUFFER_SIZE)
+            .open_or_create()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to open record service")))?
+            .subscriber_builder()
+            .create()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to create record subscriber")))?;
+        Ok(Self { subscriber })
+    }
+
+    /// Receive the frames of the next published header or cycle, if any
+    pub fn receive(&self) -> Result<Option<Vec<u8>>, Error> {
+        let sample = self
+            .subscriber
+            .receive()
+            .map_err(|_| Error::Recording(RecordingError::Failed("failed to receive record sample")))?;
+        Ok(sample.map(|sample| sample.payload().to_vec()))
+    }
+}
@@ -0,0 +1,277 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Stubbing of activities by their recorded outputs
+//!
+//! To develop or test downstream activities against real data without running the upstream sensor stack,
+//! selected activities are replaced by a [RecordedStub] replaying the outputs recorded from them in an
+//! earlier run. The stub takes the place of the activity in the task chain, with the same ID and
+//! dependencies, and publishes the recorded samples of the outputs of the activity on each step:
+//!
+//! ```ignore
+//! let stubs = ActivityStubs::new().with_stub(
+//!     camera,
+//!     ActivityStub::new("/tmp/rec/rec_0001.bin", StubMatch::Timestamp, |replayer| {
+//!         replayer.publish_topic(TOPIC_CAMERA_FRONT, activity_output(TOPIC_CAMERA_FRONT));
+//!     }),
+//! );
+//! stubs.apply(&mut config.worker_assignments);
+//! ```
+//!
+//! The recorded cycles are matched to the steps of the stub either by their index, replaying one
+//! recorded cycle per step, or by their timestamp, replaying the latest cycle recorded at or before the
+//! time of the step since the startup of the primary agent. Matching by timestamp keeps the stubbed outputs
+//! in step with the recorded timing even if the cycle time differs from the recorded one.
+//!
+//! Each agent applies the stubs to the activities assigned to its own workers, so the same stubs
+//! can be applied by all agents of an application.
+
+use crate::activity::{Activity, ActivityIdAndBuilder};
+use crate::error::ActivityError;
+use crate::ids::{ActivityId, WorkerId};
+use crate::recording::encryption::RecordingKey;
+use crate::recording::replayer::Replayer;
+use crate::timestamp::timestamp;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use score_log::{debug, error, info};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+/// Matching of the recorded cycles to the steps of a [RecordedStub]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StubMatch {
+    /// Replay the next recorded cycle on each step
+    CycleIndex,
+    /// Replay the latest cycle recorded at or before the time of the step, skipping earlier cycles
+    Timestamp,
+}
+
+/// Activity replaying the recorded outputs of the activity it replaces
+pub struct RecordedStub<R: BufRead> {
+    /// Replayer of the recorded outputs, with the ID of the replaced activity
+    replayer: Replayer<R>,
+    /// Matching of the recorded cycles to the steps
+    matching: StubMatch,
+    /// Whether the end of the recording has been reached
+    finished: bool,
+}
+
+impl<R: BufRead> RecordedStub<R> {
+    /// Create a stub publishing the topics registered with the given replayer
+    pub fn new(replayer: Replayer<R>, matching: StubMatch) -> Self {
+        Self {
+            replayer,
+            matching,
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead> Activity for RecordedStub<R> {
+    fn id(&self) -> ActivityId {
+        self.replayer.id()
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        info!("Activity {} stubbed by its recorded outputs", self.id());
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        if self.finished {
+            return Ok(());
+        }
+        let replayed = match self.matching {
+            StubMatch::CycleIndex => self.replayer.replay_cycle(),
+            StubMatch::Timestamp => self.replayer.replay_at(timestamp().0),
+        };
+        match replayed.and_then(|replayed| match replayed {
+            Some(_) => Ok(true),
+            None => self.replayer.peek_cycle().map(|next| next.is_some()),
+        }) {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                info!("Stub of activity {} reached the end of the recording", self.id());
+                self.finished = true;
+                Ok(())
+            },
+            Err(e) => {
+                error!("Stub of activity {} failed to replay cycle: {:?}", self.id(), e);
+                Err(ActivityError::Step)
+            },
+        }
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        Ok(())
+    }
+}
+
+/// Function registering the recorded outputs of a stubbed activity with the replayer of its stub
+type RegisterOutputs = Box<dyn FnOnce(&mut Replayer<BufReader<File>>) + Send>;
+
+/// Replacement of an activity by a [RecordedStub] reading a recording file
+pub struct ActivityStub {
+    /// Recording of the outputs of the activity
+    path: PathBuf,
+    /// Matching of the recorded cycles to the steps
+    matching: StubMatch,
+    /// Key of an encrypted recording
+    key: Option<RecordingKey>,
+    /// Registration of the outputs to replay, called in the thread of the worker building the stub
+    outputs: RegisterOutputs,
+}
+
+impl ActivityStub {
+    /// Create a stub replaying the recording at the given path
+    ///
+    /// The outputs of the activity to replay are registered with the given function, see
+    /// [Replayer::publish_topic].
+    pub fn new(
+        path: impl Into<PathBuf>,
+        matching: StubMatch,
+        outputs: impl FnOnce(&mut Replayer<BufReader<File>>) + Send + 'static,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            matching,
+            key: None,
+            outputs: Box::new(outputs),
+        }
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [encryption](crate::recording::encryption)
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Build the stub for the activity with the given ID
+    ///
+    /// A recording failing to open fails the startup of the stub.
+    fn build(self, id: ActivityId) -> Box<dyn Activity> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => {
+                error!(
+                    "Failed to open recording {} for stub of activity {}",
+                    self.path.display().to_string().as_str(),
+                    id
+                );
+                return Box::new(FailedStub(id));
+            },
+        };
+        let mut replayer = Replayer::new(id, BufReader::new(file));
+        if let Some(key) = self.key {
+            replayer = replayer.with_key(key);
+        }
+        (self.outputs)(&mut replayer);
+        Box::new(RecordedStub::new(replayer, self.matching))
+    }
+}
+
+/// Stub of an activity whose recording failed to open
+struct FailedStub(ActivityId);
+
+impl Activity for FailedStub {
+    fn id(&self) -> ActivityId {
+        self.0
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        Err(ActivityError::Startup)
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        Err(ActivityError::Step)
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        Ok(())
+    }
+}
+
+/// Activities of an application to replace by stubs
+#[derive(Default)]
+pub struct ActivityStubs {
+    stubs: HashMap<ActivityId, ActivityStub>,
+}
+
+impl ActivityStubs {
+    /// Create an empty set of stubs, replacing no activity
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the activity with the given ID by the given stub
+    pub fn with_stub(mut self, id: ActivityId, stub: ActivityStub) -> Self {
+        self.stubs.insert(id, stub);
+        self
+    }
+
+    /// Replace the builders of the stubbed activities among the given worker assignments of an agent
+    ///
+    /// Stubs of activities not assigned to the given workers are ignored, as they belong to other agents.
+    /// Returns the IDs of the replaced activities.
+    pub fn apply(mut self, worker_assignments: &mut [(WorkerId, Vec<ActivityIdAndBuilder>)]) -> Vec<ActivityId> {
+        let mut replaced = Vec::new();
+        for (_, activities) in worker_assignments.iter_mut() {
+            for (id, builder) in activities.iter_mut() {
+                let Some(stub) = self.stubs.remove(id) else {
+                    continue;
+                };
+                *builder = Box::new(move |id| stub.build(id));
+                replaced.push(*id);
+            }
+        }
+        debug!("Replaced {} activities by stubs", replaced.len());
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_cycle_at_timestamp_is_replayed() {
+        use crate::recording::recorder::write_record;
+        use crate::recording::Record;
+        use feo_time::Duration;
+
+        let mut recording = Vec::new();
+        for millis in [10, 20, 30] {
+            let record = Record::Cycle {
+                timestamp: Duration::from_millis(millis),
+            };
+            write_record(&mut recording, &record).unwrap();
+        }
+
+        let mut replayer = Replayer::new(ActivityId::new(1), &recording[..]);
+        assert_eq!(replayer.replay_at(Duration::from_millis(5)).unwrap(), None);
+        assert_eq!(
+            replayer.replay_at(Duration::from_millis(25)).unwrap(),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(replayer.replay_at(Duration::from_millis(25)).unwrap(), None);
+        assert_eq!(
+            replayer.replay_at(Duration::from_millis(100)).unwrap(),
+            Some(Duration::from_millis(30))
+        );
+        assert_eq!(replayer.peek_cycle().unwrap(), None);
+    }
+}
@@ -0,0 +1,177 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Mapping of recorded timestamps to real time
+//!
+//! The timestamps of a recording are FEO time, which runs faster or slower than real time while
+//! a speed factor is set, see [feo_time::speed]. The [RecordingHeader] notes the factor at startup,
+//! and the recorder notes each change of the factor during the recording via [feo_time::change_speed]
+//! as a [Record::SpeedChange].
+//!
+//! A [Timeline] collects these changes while reading a recording and maps recorded timestamps
+//! to the real time elapsed since startup and to the OS wall clock, e.g. to align a recording
+//! with logs or traces of other processes.
+
+use crate::recording::header::RecordingHeader;
+use crate::recording::Record;
+use alloc::vec::Vec;
+use feo_time::Duration;
+
+/// Mapping of the recorded timestamps of a recording to real time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    /// Startup time of the primary agent from the header of the recording
+    start_time: Duration,
+    /// Segments of constant speed, starting with the speed at startup
+    segments: Vec<Segment>,
+}
+
+/// Part of a recording with a constant speed factor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    /// Recorded timestamp of the start of the segment
+    timestamp: Duration,
+    /// Speed factor within the segment, if set
+    factor: Option<i32>,
+    /// Real time elapsed since startup at the start of the segment
+    real_elapsed: Duration,
+    /// OS wall clock time of the start of the segment, unless it starts at startup
+    real_time: Option<Duration>,
+}
+
+impl Timeline {
+    /// Create a timeline of the recording with the given header, without speed changes
+    pub fn new(header: &RecordingHeader) -> Self {
+        Self {
+            start_time: header.start_time,
+            segments: alloc::vec![Segment {
+                timestamp: Duration::ZERO,
+                factor: header.speed,
+                real_elapsed: Duration::ZERO,
+                real_time: None,
+            }],
+        }
+    }
+
+    /// Note the given record read from the recording, ignoring records other than [Record::SpeedChange]
+    ///
+    /// Records must be passed in the order of the recording.
+    pub fn push(&mut self, record: &Record) {
+        let Record::SpeedChange {
+            timestamp,
+            new,
+            real_time,
+            ..
+        } = *record
+        else {
+            return;
+        };
+        let real_elapsed = self.real_elapsed(timestamp);
+        self.segments.push(Segment {
+            timestamp,
+            factor: new,
+            real_elapsed,
+            real_time: Some(real_time),
+        });
+    }
+
+    /// Get the speed factor at the given recorded timestamp, if set
+    pub fn speed_at(&self, timestamp: Duration) -> Option<i32> {
+        self.segment(timestamp).factor
+    }
+
+    /// Map the given recorded timestamp to the real time elapsed since startup
+    pub fn real_elapsed(&self, timestamp: Duration) -> Duration {
+        let segment = self.segment(timestamp);
+        segment.real_elapsed + to_real(timestamp.saturating_sub(segment.timestamp), segment.factor)
+    }
+
+    /// Map the given recorded timestamp to the OS wall clock time as duration since the UNIX epoch
+    ///
+    /// The wall clock is anchored at the latest speed change before the timestamp, or the first one
+    /// after it. Without speed changes, the startup time of the header is used as anchor, which is
+    /// ahead of the wall clock if the speed was set before startup.
+    pub fn wall_clock(&self, timestamp: Duration) -> Duration {
+        let segment = self.segment(timestamp);
+        let since_segment = to_real(timestamp.saturating_sub(segment.timestamp), segment.factor);
+        match (segment.real_time, self.segments.get(1)) {
+            (Some(real_time), _) => real_time + since_segment,
+            (None, Some(first_change)) => {
+                let until_change = first_change.real_elapsed.saturating_sub(since_segment);
+                first_change.real_time.unwrap_or_default().saturating_sub(until_change)
+            },
+            (None, None) => self.start_time + since_segment,
+        }
+    }
+
+    /// Get the segment containing the given recorded timestamp
+    fn segment(&self, timestamp: Duration) -> &Segment {
+        let index = self.segments.partition_point(|segment| segment.timestamp <= timestamp);
+        &self.segments[index.saturating_sub(1)]
+    }
+}
+
+/// Map the given FEO time elapsed with the given speed factor to real time
+fn to_real(elapsed: Duration, factor: Option<i32>) -> Duration {
+    match factor {
+        Some(factor) if factor.is_positive() => elapsed / factor.unsigned_abs(),
+        Some(factor) if factor.is_negative() => elapsed * factor.unsigned_abs(),
+        _ => elapsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_maps_timestamps_across_speed_changes() {
+        use crate::recording::compression::Compression;
+
+        let mut header = RecordingHeader::new(Duration::from_secs(90), Compression::None);
+        header.speed = Some(2);
+        let mut timeline = Timeline::new(&header);
+        assert_eq!(timeline.real_elapsed(Duration::from_secs(4)), Duration::from_secs(2));
+        assert_eq!(timeline.wall_clock(Duration::from_secs(4)), Duration::from_secs(92));
+
+        // Other records are ignored
+        timeline.push(&Record::Cycle {
+            timestamp: Duration::from_secs(5),
+        });
+        timeline.push(&Record::SpeedChange {
+            timestamp: Duration::from_secs(10),
+            old: Some(2),
+            new: None,
+            real_time: Duration::from_secs(100),
+        });
+        timeline.push(&Record::SpeedChange {
+            timestamp: Duration::from_secs(20),
+            old: None,
+            new: Some(-2),
+            real_time: Duration::from_secs(110),
+        });
+
+        assert_eq!(timeline.speed_at(Duration::from_secs(4)), Some(2));
+        assert_eq!(timeline.speed_at(Duration::from_secs(10)), None);
+        assert_eq!(timeline.speed_at(Duration::from_secs(25)), Some(-2));
+
+        assert_eq!(timeline.real_elapsed(Duration::from_secs(4)), Duration::from_secs(2));
+        assert_eq!(timeline.real_elapsed(Duration::from_secs(12)), Duration::from_secs(7));
+        assert_eq!(timeline.real_elapsed(Duration::from_secs(22)), Duration::from_secs(19));
+
+        // Anchored at the first change before it, at the latest change afterwards
+        assert_eq!(timeline.wall_clock(Duration::from_secs(4)), Duration::from_secs(97));
+        assert_eq!(timeline.wall_clock(Duration::from_secs(12)), Duration::from_secs(102));
+        assert_eq!(timeline.wall_clock(Duration::from_secs(22)), Duration::from_secs(114));
+    }
+}
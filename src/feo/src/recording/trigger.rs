@@ -0,0 +1,152 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Flight recorder mode of the recorder
+//!
+//! With [Recorder::with_trigger](crate::recording::recorder::Recorder::with_trigger), the recorder
+//! does not write its cycles continuously. Instead, it keeps the cycles of the last [TriggerWindow::pre]
+//! in a ring buffer in memory. When the application fires the [RecordTrigger] of the recorder,
+//! e.g. on an emergency brake, the buffered cycles are written, followed by the cycles of the next
+//! [TriggerWindow::post]. Firing the trigger again while the post-trigger window is open extends it.
+//!
+//! Every fired trigger is noted in the recording as a [Record::Trigger] following the cycle it fired in,
+//! also without the flight recorder mode.
+
+use crate::recording::backpressure::QueuedCycle;
+use crate::recording::Record;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem;
+use feo_time::Duration;
+use std::sync::{Mutex, MutexGuard};
+
+/// Windows around a trigger written by a recorder in flight recorder mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerWindow {
+    /// Time before the trigger kept in memory
+    pub pre: Duration,
+    /// Time after the trigger written to the output
+    pub post: Duration,
+}
+
+/// Shared handle to fire the trigger of a recorder
+///
+/// Fired triggers take effect with the next recorded cycle.
+#[derive(Debug, Clone, Default)]
+pub struct RecordTrigger(Arc<Mutex<Vec<String>>>);
+
+impl RecordTrigger {
+    /// Fire the trigger for the given event, e.g. `"emergency_brake"`
+    pub fn fire(&self, event: &str) {
+        self.lock().push(event.to_string());
+    }
+
+    /// Take the events fired since the previous call
+    pub(crate) fn take(&self) -> Vec<String> {
+        mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<String>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Ring buffer of the cycles before a trigger
+pub(crate) struct PreTriggerBuffer {
+    /// Windows around a trigger
+    window: TriggerWindow,
+    /// Cycles within the pre-trigger window, oldest first
+    cycles: VecDeque<QueuedCycle>,
+    /// End of the current post-trigger window, if open
+    until: Option<Duration>,
+}
+
+impl PreTriggerBuffer {
+    /// Create an empty buffer
+    pub(crate) fn new(window: TriggerWindow) -> Self {
+        Self {
+            window,
+            cycles: VecDeque::new(),
+            until: None,
+        }
+    }
+
+    /// Pass a recorded cycle through the buffer, returning the cycles to write, oldest first
+    ///
+    /// The cycle is buffered unless it has been triggered or falls within a post-trigger window.
+    pub(crate) fn admit(&mut self, cycle: QueuedCycle, triggered: bool) -> Vec<QueuedCycle> {
+        if triggered {
+            let until = cycle.timestamp + self.window.post;
+            self.until = Some(self.until.map_or(until, |current| current.max(until)));
+        }
+        if self.until.is_some_and(|until| cycle.timestamp <= until) {
+            let mut cycles: Vec<QueuedCycle> = self.cycles.drain(..).collect();
+            cycles.push(cycle);
+            return cycles;
+        }
+
+        self.until = None;
+        let start = cycle.timestamp.saturating_sub(self.window.pre);
+        self.cycles.push_back(cycle);
+        while self.cycles.front().is_some_and(|oldest| oldest.timestamp < start) {
+            self.cycles.pop_front();
+        }
+        Vec::new()
+    }
+}
+
+/// Note the given fired events in the trailer of the cycle
+pub(crate) fn note_triggers(cycle: &mut QueuedCycle, events: Vec<String>) {
+    let timestamp = cycle.timestamp;
+    cycle
+        .trailer
+        .extend(events.into_iter().map(|event| Record::Trigger { timestamp, event }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_around_triggers_are_written() {
+        let window = TriggerWindow {
+            pre: Duration::from_millis(20),
+            post: Duration::from_millis(10),
+        };
+        let mut buffer = PreTriggerBuffer::new(window);
+        let cycle = |ms: u64| QueuedCycle {
+            timestamp: Duration::from_millis(ms),
+            ..Default::default()
+        };
+        let timestamps = |cycles: Vec<QueuedCycle>| -> Vec<u64> {
+            cycles
+                .iter()
+                .map(|cycle| cycle.timestamp.0.as_millis() as u64)
+                .collect()
+        };
+
+        for ms in (0..100).step_by(10) {
+            assert!(buffer.admit(cycle(ms), false).is_empty());
+        }
+        assert_eq!(timestamps(buffer.admit(cycle(100), true)), [80, 90, 100]);
+        assert_eq!(timestamps(buffer.admit(cycle(110), false)), [110]);
+
+        // A trigger within the post-trigger window extends it
+        assert_eq!(timestamps(buffer.admit(cycle(120), true)), [120]);
+        assert_eq!(timestamps(buffer.admit(cycle(130), false)), [130]);
+        assert!(buffer.admit(cycle(140), false).is_empty());
+        assert_eq!(timestamps(buffer.admit(cycle(150), true)), [140, 150]);
+    }
+}
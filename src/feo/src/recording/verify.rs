@@ -0,0 +1,527 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Verification of replayed task chains against their recorded outputs
+//!
+//! To regression test changes of activities, a task chain is executed against the inputs recorded in an
+//! earlier run, and the outputs it produces are compared with the outputs recorded in the same run.
+//! A [Replayer](crate::recording::replayer::Replayer) at the start of the chain publishes the recorded inputs,
+//! one recorded cycle per step. A [ReplayVerifier] at the end of the chain, depending on all producers of the
+//! verified topics, reads the produced outputs on each step and compares them with the samples recorded in
+//! the same cycle:
+//!
+//! ```ignore
+//! let mut verifier = ReplayVerifier::new(verifier_id, BufReader::new(File::open(recording)?));
+//! verifier.verify_topic(TOPIC_LANES, activity_input(TOPIC_LANES));
+//! verifier.verify_topic_with(TOPIC_OBJECTS, activity_input(TOPIC_OBJECTS), |recorded: &Objects, produced| {
+//!     recorded.approx_eq(produced, 1e-3)
+//! });
+//! let verification = verifier.verification();
+//!
+//! // After the replay
+//! let report = verification.snapshot();
+//! assert!(report.is_clean(), "{report}");
+//! ```
+//!
+//! By default, the produced samples are encoded with the encoding of the recording and compared byte for byte
+//! with the recorded ones. Topics with tolerances, e.g. floating point results, are compared by a comparator
+//! of the decoded samples instead. Each cycle with differing outputs, or with outputs only produced or only
+//! recorded, is logged and noted as [CycleDivergence] in the [VerificationReport].
+//!
+//! The recorded cycles are matched to the steps of the verifier by their index, like the steps of the replayer.
+//! The recording must hold every sample of the verified topics, i.e. must not be down-sampled by a
+//! [RecordFilter](crate::recording::filter::RecordFilter).
+
+use crate::activity::Activity;
+use crate::error::{ActivityError, Error, RecordingError};
+use crate::ids::ActivityId;
+use crate::recording::codec::Encoding;
+use crate::recording::encryption::RecordingKey;
+use crate::recording::reader::RecordReader;
+use crate::recording::Record;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt;
+use feo_com::interface::{ActivityInput, FeoComData};
+use feo_time::Duration;
+use score_log::{debug, error, info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::BufRead;
+use std::sync::{Mutex, MutexGuard};
+
+/// Difference of the output produced on a topic from the recorded one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// The produced sample differs from the recorded one, from the given byte on if compared byte for byte
+    Mismatch(Option<usize>),
+    /// A sample was recorded, but none produced
+    Missing,
+    /// A sample was produced, but none recorded
+    Unexpected,
+}
+
+impl fmt::Display for DivergenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DivergenceKind::Mismatch(Some(offset)) => write!(f, "differs from byte {offset}"),
+            DivergenceKind::Mismatch(None) => write!(f, "differs"),
+            DivergenceKind::Missing => write!(f, "not produced"),
+            DivergenceKind::Unexpected => write!(f, "not recorded"),
+        }
+    }
+}
+
+/// Divergence of the output of a topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicDivergence {
+    /// Name of the topic
+    pub topic: String,
+    /// Difference from the recorded output
+    pub kind: DivergenceKind,
+}
+
+/// Divergences of the outputs of a replayed cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleDivergence {
+    /// Index of the cycle in the recording, starting at 0
+    pub cycle: u64,
+    /// Recorded timestamp of the cycle
+    pub timestamp: Duration,
+    /// Diverging topics in the order of their registration
+    pub topics: Vec<TopicDivergence>,
+}
+
+impl fmt::Display for CycleDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle {} at {:.6}s:", self.cycle, self.timestamp.as_secs_f64())?;
+        for (index, divergence) in self.topics.iter().enumerate() {
+            let separator = if index > 0 { "," } else { "" };
+            write!(f, "{separator} topic {} {}", divergence.topic, divergence.kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of the verification of a replay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Number of verified cycles
+    pub cycles: u64,
+    /// Cycles with diverging outputs in the order of the recording
+    pub divergences: Vec<CycleDivergence>,
+    /// Whether the end of the recording has been reached
+    pub complete: bool,
+}
+
+impl VerificationReport {
+    /// Whether all verified cycles produced the recorded outputs
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} cycles verified, {} diverged", self.cycles, self.divergences.len())?;
+        if !self.complete {
+            write!(f, ", end of recording not reached")?;
+        }
+        for divergence in self.divergences.iter() {
+            write!(f, "\n{divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Handle to the report of a [ReplayVerifier]
+///
+/// Clones share the same report, so a clone kept by the application can query the report while and after
+/// the verifier runs in its worker.
+#[derive(Debug, Clone, Default)]
+pub struct Verification {
+    report: Arc<Mutex<VerificationReport>>,
+}
+
+impl Verification {
+    /// Get a copy of the report so far
+    pub fn snapshot(&self) -> VerificationReport {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VerificationReport> {
+        self.report.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Samples of a recorded cycle
+struct RecordedCycle {
+    /// Recorded timestamp of the cycle
+    timestamp: Duration,
+    /// Type name and encoded sample per topic
+    samples: BTreeMap<String, (String, Vec<u8>)>,
+}
+
+/// Activity comparing the outputs of a replayed task chain with the recorded outputs
+pub struct ReplayVerifier<R: BufRead> {
+    /// ID of the verifier activity
+    id: ActivityId,
+    /// Reader of the recording
+    reader: RecordReader<R>,
+    /// Verified topics in the order of registration
+    topics: Vec<Box<dyn VerifyTopic>>,
+    /// Report shared with the application
+    verification: Verification,
+    /// Cycle record read ahead while looking for the end of a cycle
+    pending: Option<Record>,
+    /// Whether the header of the recording has been read
+    checked: bool,
+    /// Index of the next recorded cycle
+    cycle: u64,
+}
+
+impl<R: BufRead> ReplayVerifier<R> {
+    /// Create a new verifier comparing the outputs with the given recording
+    pub fn new(id: ActivityId, reader: R) -> Self {
+        Self {
+            id,
+            reader: RecordReader::new(reader),
+            topics: Vec::new(),
+            verification: Verification::default(),
+            pending: None,
+            checked: false,
+            cycle: 0,
+        }
+    }
+
+    /// Decrypt the samples of an encrypted recording with the given key, see [encryption](crate::recording::encryption)
+    pub fn with_key(mut self, key: RecordingKey) -> Self {
+        self.reader = self.reader.with_key(key);
+        self
+    }
+
+    /// Compare the samples of the given topic, read from the given input, byte for byte with the recorded ones
+    pub fn verify_topic<T>(&mut self, topic: &str, input: Box<dyn ActivityInput<T>>)
+    where
+        T: FeoComData + Serialize + 'static,
+    {
+        self.topics.push(Box::new(BytewiseTopic {
+            topic: topic.to_string(),
+            input,
+        }));
+    }
+
+    /// Compare the samples of the given topic, read from the given input, with the recorded ones
+    ///
+    /// The comparator is called with the decoded recorded sample and the produced sample and returns
+    /// whether they match.
+    pub fn verify_topic_with<T>(
+        &mut self,
+        topic: &str,
+        input: Box<dyn ActivityInput<T>>,
+        comparator: impl FnMut(&T, &T) -> bool + 'static,
+    ) where
+        T: FeoComData + DeserializeOwned + 'static,
+    {
+        self.topics.push(Box::new(ComparedTopic {
+            topic: topic.to_string(),
+            input,
+            comparator: Box::new(comparator),
+        }));
+    }
+
+    /// Get a handle to the report of the verification
+    pub fn verification(&self) -> Verification {
+        self.verification.clone()
+    }
+
+    /// Compare the produced outputs with the outputs of the next recorded cycle
+    ///
+    /// Returns `false` without comparing if the end of the recording has been reached.
+    pub fn verify_cycle(&mut self) -> Result<bool, Error> {
+        let Some(mut recorded) = self.read_cycle()? else {
+            self.verification.lock().complete = true;
+            return Ok(false);
+        };
+        let encoding = self.reader.encoding();
+        let mut topics = Vec::new();
+        for verified in self.topics.iter_mut() {
+            let sample = recorded.samples.remove(verified.topic());
+            if let Some((recorded_type, _)) = sample.as_ref().filter(|(name, _)| name != verified.type_name()) {
+                error!(
+                    "Recorded type {} of topic {} does not match verified type {}",
+                    recorded_type.as_str(),
+                    verified.topic(),
+                    verified.type_name()
+                );
+                return Err(Error::Recording(RecordingError::Failed("type mismatch of recorded sample")));
+            }
+            if let Some(kind) = verified.verify(encoding, sample.as_ref().map(|(_, data)| data.as_slice()))? {
+                topics.push(TopicDivergence {
+                    topic: verified.topic().to_string(),
+                    kind,
+                });
+            }
+        }
+
+        let cycle = self.cycle;
+        self.cycle += 1;
+        let mut report = self.verification.lock();
+        report.cycles += 1;
+        if !topics.is_empty() {
+            let divergence = CycleDivergence {
+                cycle,
+                timestamp: recorded.timestamp,
+                topics,
+            };
+            warn!("Replay diverged from the recording in {}", divergence.to_string().as_str());
+            report.divergences.push(divergence);
+        }
+        Ok(true)
+    }
+
+    /// Read the samples of the next recorded cycle, `None` at the end of the recording
+    ///
+    /// Records before the first cycle are skipped.
+    fn read_cycle(&mut self) -> Result<Option<RecordedCycle>, Error> {
+        let timestamp = loop {
+            match self.next_record()? {
+                None => return Ok(None),
+                Some(Record::Cycle { timestamp }) => break timestamp,
+                Some(_) => {},
+            }
+        };
+        let mut samples = BTreeMap::new();
+        loop {
+            match self.next_record()? {
+                None => break,
+                Some(record @ Record::Cycle { .. }) => {
+                    self.pending = Some(record);
+                    break;
+                },
+                Some(Record::Data {
+                    topic, type_name, data, ..
+                }) => {
+                    samples.insert(topic, (type_name, data));
+                },
+                Some(_) => {},
+            }
+        }
+        Ok(Some(RecordedCycle { timestamp, samples }))
+    }
+
+    /// Get the next record, taking a pending record first
+    fn next_record(&mut self) -> Result<Option<Record>, Error> {
+        if !self.checked {
+            self.checked = true;
+            self.reader.read_header()?;
+        }
+        if let Some(record) = self.pending.take() {
+            return Ok(Some(record));
+        }
+        self.reader.read_record()
+    }
+}
+
+impl<R: BufRead> Activity for ReplayVerifier<R> {
+    fn id(&self) -> ActivityId {
+        self.id
+    }
+
+    fn startup(&mut self) -> Result<(), ActivityError> {
+        debug!("Replay verifier {} verifying {} topics", self.id, self.topics.len());
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), ActivityError> {
+        if self.verification.lock().complete {
+            return Ok(());
+        }
+        match self.verify_cycle() {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                info!("Replay verifier {} reached the end of the recording", self.id);
+                Ok(())
+            },
+            Err(e) => {
+                error!("Replay verifier {} failed to verify cycle: {:?}", self.id, e);
+                Err(ActivityError::Step)
+            },
+        }
+    }
+
+    fn shutdown(&mut self) -> Result<(), ActivityError> {
+        let report = self.verification.snapshot();
+        info!(
+            "Replay verifier {} verified {} cycles, {} diverged",
+            self.id,
+            report.cycles,
+            report.divergences.len()
+        );
+        Ok(())
+    }
+}
+
+/// Comparison of the produced samples of a topic with the recorded ones
+trait VerifyTopic {
+    /// Get the name of the topic
+    fn topic(&self) -> &str;
+
+    /// Get the name of the type of the samples
+    fn type_name(&self) -> &'static str;
+
+    /// Read the produced sample and compare it with the recorded sample, if any
+    fn verify(&mut self, encoding: Encoding, recorded: Option<&[u8]>) -> Result<Option<DivergenceKind>, Error>;
+}
+
+/// Topic compared byte for byte in the encoding of the recording
+struct BytewiseTopic<T: FeoComData> {
+    topic: String,
+    input: Box<dyn ActivityInput<T>>,
+}
+
+impl<T: FeoComData + Serialize + 'static> VerifyTopic for BytewiseTopic<T> {
+    fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn verify(&mut self, encoding: Encoding, recorded: Option<&[u8]>) -> Result<Option<DivergenceKind>, Error> {
+        let produced = match self.input.read() {
+            Ok(sample) => Some(encoding.encode(&*sample)?),
+            Err(_) => None,
+        };
+        Ok(match (recorded, produced) {
+            (Some(recorded), Some(produced)) => {
+                first_difference(recorded, &produced).map(|offset| DivergenceKind::Mismatch(Some(offset)))
+            },
+            (Some(_), None) => Some(DivergenceKind::Missing),
+            (None, Some(_)) => Some(DivergenceKind::Unexpected),
+            (None, None) => None,
+        })
+    }
+}
+
+/// Topic compared by a comparator of the decoded samples
+struct ComparedTopic<T: FeoComData> {
+    topic: String,
+    input: Box<dyn ActivityInput<T>>,
+    comparator: Box<dyn FnMut(&T, &T) -> bool>,
+}
+
+impl<T: FeoComData + DeserializeOwned + 'static> VerifyTopic for ComparedTopic<T> {
+    fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn verify(&mut self, encoding: Encoding, recorded: Option<&[u8]>) -> Result<Option<DivergenceKind>, Error> {
+        let Ok(produced) = self.input.read() else {
+            return Ok(recorded.map(|_| DivergenceKind::Missing));
+        };
+        let Some(recorded) = recorded else {
+            return Ok(Some(DivergenceKind::Unexpected));
+        };
+        let recorded: T = encoding.decode(recorded)?;
+        Ok((!(self.comparator)(&recorded, &produced)).then_some(DivergenceKind::Mismatch(None)))
+    }
+}
+
+/// Get the offset of the first byte differing between two encoded samples, if any
+fn first_difference(recorded: &[u8], produced: &[u8]) -> Option<usize> {
+    recorded
+        .iter()
+        .zip(produced)
+        .position(|(recorded, produced)| recorded != produced)
+        .or_else(|| (recorded.len() != produced.len()).then(|| recorded.len().min(produced.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_cycles_are_compared_bytewise() {
+        use crate::recording::recorder::write_record;
+
+        let data = |timestamp, topic: &str, data: &[u8]| Record::Data {
+            timestamp: Duration::from_millis(timestamp),
+            topic: topic.to_string(),
+            type_name: type_name::<u32>().to_string(),
+            data: data.to_vec(),
+        };
+        let mut recording = Vec::new();
+        for record in [
+            data(0, "early", &[1]),
+            Record::Cycle {
+                timestamp: Duration::from_millis(10),
+            },
+            data(10, "lanes", &[1, 2, 3]),
+            data(10, "objects", &[4]),
+            Record::Cycle {
+                timestamp: Duration::from_millis(20),
+            },
+        ] {
+            write_record(&mut recording, &record).unwrap();
+        }
+
+        let mut verifier = ReplayVerifier::new(ActivityId::new(9), &recording[..]);
+        let first = verifier.read_cycle().unwrap().unwrap();
+        assert_eq!(first.timestamp, Duration::from_millis(10));
+        assert_eq!(first.samples.keys().collect::<Vec<_>>(), ["lanes", "objects"]);
+        assert_eq!(first.samples["lanes"].1, [1, 2, 3]);
+        let second = verifier.read_cycle().unwrap().unwrap();
+        assert_eq!(second.timestamp, Duration::from_millis(20));
+        assert!(second.samples.is_empty());
+        assert!(verifier.read_cycle().unwrap().is_none());
+
+        assert_eq!(first_difference(&[1, 2, 3], &[1, 2, 3]), None);
+        assert_eq!(first_difference(&[1, 2, 3], &[1, 5, 3]), Some(1));
+        assert_eq!(first_difference(&[1, 2, 3], &[1, 2]), Some(2));
+
+        let report = VerificationReport {
+            cycles: 2,
+            divergences: alloc::vec![CycleDivergence {
+                cycle: 0,
+                timestamp: Duration::from_millis(10),
+                topics: alloc::vec![
+                    TopicDivergence {
+                        topic: "lanes".to_string(),
+                        kind: DivergenceKind::Mismatch(Some(1)),
+                    },
+                    TopicDivergence {
+                        topic: "objects".to_string(),
+                        kind: DivergenceKind::Missing,
+                    },
+                ],
+            }],
+            complete: true,
+        };
+        assert!(!report.is_clean());
+        assert_eq!(
+            alloc::format!("{report}"),
+            "2 cycles verified, 1 diverged\n\
+             cycle 0 at 0.010000s: topic lanes differs from byte 1, topic objects not produced"
+        );
+    }
+}
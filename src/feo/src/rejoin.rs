@@ -0,0 +1,40 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Rejoining of restarted secondary agents
+//!
+//! By default, the primary agent shuts down once the connection to a secondary agent is lost.
+//! A primary agent configured with [rejoin](crate::agent::direct::primary::PrimaryConfig::rejoin)
+//! keeps running instead: the activities of the lost agent are degraded, i.e. no longer stepped,
+//! and their dependents are informed about their stale inputs as for any skipped step.
+//!
+//! A secondary agent restarted afterwards, e.g. by the [launcher](crate::launcher) or an external
+//! process supervisor, connects its workers to the primary agent as on a regular start. Its communication
+//! is set up again while starting the agent, so its activities subscribe to their topics anew. Once all
+//! activities of the agent are connected, the primary agent synchronizes the time of the agent and starts
+//! up its activities, which restore their state from the latest [checkpoint](crate::recording::checkpoint)
+//! if configured. Each activity is stepped again from the next cycle of its task chain on, continuing
+//! the cycle counter of the chain.
+//!
+//! Rejoining is supported for the TCP and Unix socket signalling of the direct mode.
+
+use crate::ids::AgentId;
+
+/// Change of the connection of a remote agent, reported by the connector of the scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AgentChange {
+    /// The connection to the agent has been lost
+    Lost(AgentId),
+    /// All activities of the lost agent are connected again and its time is synchronized
+    Rejoined(AgentId),
+}
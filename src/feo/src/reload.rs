@@ -0,0 +1,230 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Reloading the non-structural parameters of the configuration of a running primary agent
+//!
+//! After passing the loaded [AppConfig] and its file to [ConfigReload::watch], the file is read again
+//! on SIGHUP, on the `reload` command of the [introspection](crate::introspection) server, or on
+//! [ConfigReload::reload]. The following parameters are taken over without restarting the task chains:
+//!
+//! - the cycle times of the task chains, taking effect with the next cycle of each chain,
+//! - the receive timeout of the scheduler,
+//! - the log level of the primary agent,
+//! - the filter of a recorder registered with [ConfigReload::with_record_filter],
+//! - whether debug topics are enabled, see [debug_topic](crate::debug_topic).
+//!
+//! A reloaded configuration changing the agents, workers, task chains, activities or topics is
+//! rejected with [ConfigError::StructuralChange], keeping the current parameters. The scheduler reads
+//! the file on SIGHUP itself, between cycles, so a reload requested by a signal is noted in its log only.
+
+use crate::config::{AppConfig, ConfigError};
+use crate::debug_topic;
+use crate::error::Error;
+use crate::ids::ChainId;
+use crate::recording::filter::RecordFilterHandle;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{info, LevelFilter};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// Whether a SIGHUP was received and not yet handled
+static SIGHUP_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// Parameters taken over from a reloaded configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadedParams {
+    /// Cycle time of the main task chain
+    pub cycle_time: Duration,
+    /// Cycle times of the additional task chains
+    pub chain_cycle_times: Vec<(ChainId, Duration)>,
+    /// Receive timeout of the scheduler, if configured
+    pub timeout: Option<Duration>,
+    /// Maximum log level of the primary agent, if configured
+    pub log_level: Option<LevelFilter>,
+}
+
+impl ReloadedParams {
+    fn new(config: &AppConfig) -> Self {
+        Self {
+            cycle_time: config.cycle_time(),
+            chain_cycle_times: config
+                .chains
+                .iter()
+                .map(|chain| (ChainId::new(chain.id), Duration::from_millis(chain.cycle_time_ms)))
+                .collect(),
+            timeout: config.timeout(),
+            log_level: config.log_level.map(LevelFilter::from),
+        }
+    }
+}
+
+/// Handle reloading the configuration file, shared with the scheduler
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReload(Arc<Mutex<Reloading>>);
+
+#[derive(Debug, Default)]
+struct Reloading {
+    /// Watched configuration file and the configuration currently applied
+    watched: Option<(PathBuf, AppConfig)>,
+    /// Filter of the recorder updated on reloads, if any
+    record_filter: Option<RecordFilterHandle>,
+    /// Parameters reloaded but not yet taken by the scheduler
+    pending: Option<ReloadedParams>,
+}
+
+impl ConfigReload {
+    /// Create a handle without a watched configuration file
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the given filter of a recorder with the `record_filter` entry on reloads
+    pub fn with_record_filter(self, record_filter: RecordFilterHandle) -> Self {
+        self.lock().record_filter = Some(record_filter);
+        self
+    }
+
+    /// Watch the given configuration file, loaded into `config`, and reload it on SIGHUP
+    pub fn watch(&self, path: impl Into<PathBuf>, config: AppConfig) -> Result<(), Error> {
+        // Safety: The handler only stores to an atomic, which is async-signal-safe
+        let result = unsafe {
+            let mut action: libc::sigaction = core::mem::zeroed();
+            action.sa_sigaction = note_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(libc::SIGHUP, &action, core::ptr::null_mut())
+        };
+        if result != 0 {
+            return Err(Error::Io((
+                ScoreDebugIoError(io::Error::last_os_error()),
+                "failed to install handler of SIGHUP",
+            )));
+        }
+        self.lock().watched = Some((path.into(), config));
+        Ok(())
+    }
+
+    /// Read the watched configuration file again and apply its non-structural parameters
+    ///
+    /// The log level, the record filter and the debug topics are applied right away, the cycle times and the
+    /// receive timeout at the next cycle boundary of the scheduler.
+    pub fn reload(&self) -> Result<ReloadedParams, ConfigError> {
+        let mut reloading = self.lock();
+        let Some((path, current)) = reloading.watched.as_ref() else {
+            return Err(ConfigError::NotWatched);
+        };
+        let config = AppConfig::load(path)?;
+        current.check_reloadable(&config)?;
+
+        let params = ReloadedParams::new(&config);
+        if let Some(level) = params.log_level {
+            score_log::set_max_level(level);
+        }
+        if let (Some(handle), Some(filter)) = (reloading.record_filter.as_ref(), config.record_filter.as_ref()) {
+            handle.set(filter.filter());
+        }
+        debug_topic::set_enabled(config.debug_topics);
+        info!("Reloaded configuration, main cycle time {:?}", params.cycle_time);
+        reloading.pending = Some(params.clone());
+        if let Some((_, current)) = reloading.watched.as_mut() {
+            *current = config;
+        }
+        Ok(params)
+    }
+
+    /// Whether a SIGHUP requested a reload since the last call
+    pub(crate) fn take_sighup(&self) -> bool {
+        SIGHUP_RECEIVED.swap(false, Ordering::Relaxed)
+    }
+
+    /// Take the reloaded parameters to be applied by the scheduler, if any
+    pub(crate) fn take(&self) -> Option<ReloadedParams> {
+        self.lock().pending.take()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Reloading> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Signal handler noting the reload request, handled by the scheduler between cycles
+extern "C" fn note_sighup(_signal: libc::c_int) {
+    SIGHUP_RECEIVED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_structural_parameters_are_reloaded() {
+        use crate::recording::filter::RecordFilter;
+
+        let directory = std::env::temp_dir().join(alloc::format!("feo_reload_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("app.toml");
+        let toml = r#"
+            primary_agent = 100
+            cycle_time_ms = 50
+
+            [[agents]]
+            id = 100
+            workers = [40]
+
+            [[chains]]
+            id = 1
+            cycle_time_ms = 200
+
+            [[activities]]
+            id = 0
+            worker = 40
+
+            [[activities]]
+            id = 1
+            worker = 40
+            chain = 1
+        "#;
+        std::fs::write(&path, toml).unwrap();
+
+        let record_filter = RecordFilterHandle::new(RecordFilter::default());
+        let reload = ConfigReload::new().with_record_filter(record_filter.clone());
+        assert!(reload.reload().is_err());
+        reload.watch(&path, AppConfig::from_toml(toml).unwrap()).unwrap();
+
+        let reloaded = toml
+            .replace("cycle_time_ms = 50", "cycle_time_ms = 50\ntimeout_ms = 20")
+            .replace("cycle_time_ms = 200", "cycle_time_ms = 100")
+            + "[record_filter]\nexclude_topics = [\"feo/com/vehicle/camera\"]\n";
+        std::fs::write(&path, &reloaded).unwrap();
+        let params = reload.reload().unwrap();
+        assert_eq!(params.chain_cycle_times, [(ChainId::new(1), Duration::from_millis(100))]);
+        assert_eq!(params.timeout, Some(Duration::from_millis(20)));
+        assert_eq!(reload.take(), Some(params));
+        assert!(reload.take().is_none());
+        assert!(record_filter.get().exclude_topics.contains("feo/com/vehicle/camera"));
+
+        std::fs::write(&path, reloaded.replace("chain = 1", "")).unwrap();
+        assert!(matches!(
+            reload.reload(),
+            Err(ConfigError::StructuralChange("activities"))
+        ));
+        assert!(reload.take().is_none());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
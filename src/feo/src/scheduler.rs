@@ -13,34 +13,62 @@
 
 //! Global activity scheduler
 
+use crate::barrier::{CycleBarrier, CycleTimeout, Straggler, StragglerState};
+use crate::chain::{TaskChains, MAIN_CHAIN};
+use crate::control::ActivityControl;
+use crate::deadline::{DeadlineOverrun, StepDeadlines};
 use crate::debug_fmt::ScoreDebugBTreeSet;
-use crate::error::Error;
-use crate::ids::{ActivityId, AgentId};
+use crate::debugger::CycleDebugger;
+use crate::error::{ActivityError, Error};
+use crate::error_policy::{ErrorPolicies, ErrorPolicy};
+use crate::ids::{ActivityId, AgentId, ChainId, WorkerId};
+use crate::introspection::{ActivityStatus, AgentStatus, ChainStatus, Introspection, Status, WorkerStatus};
+use crate::log_relay::{LogLine, MergedLog, MERGE_WINDOW};
+use crate::on_demand::CycleTrigger;
+use crate::overrun::{self, OverrunOutcome, OverrunPolicy};
+use crate::panic_report::PanicReport;
+use crate::recording::signals::{SignalCapture, SignalKind};
+use crate::rejoin::AgentChange;
+use crate::reload::ConfigReload;
+use crate::signal_forwarding;
 use crate::signalling::common::interface::ConnectScheduler;
 use crate::signalling::common::signals::Signal;
-use crate::timestamp::timestamp;
+use crate::standby::{MirroredState, StandbyMirror};
+use crate::startup::StartupOrder;
+use crate::statistics::CycleStatistics;
+use crate::supervision::{AgentSupervisor, SupervisionAction};
+use crate::timestamp::{sync_info, timestamp, Timestamp};
+use crate::watchdog::Watchdog;
+use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use alloc::{boxed::Box, collections::BTreeSet};
+use core::cmp::min;
 use core::sync::atomic::{AtomicBool, Ordering};
-use feo_time::Instant;
+use feo_time::{Deadline, Instant};
+use feo_tracing::{event, span, tracing, Level};
 use score_log::ScoreDebug;
-use score_log::{debug, error, info, trace};
-use std::collections::HashMap;
-use std::thread;
+use score_log::{debug, error, info, trace, warn};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum time between two checks for triggers of idle on-demand task chains while other chains are running
+const TRIGGER_POLL_INTERVAL: feo_time::Duration = feo_time::Duration::from_millis(1);
 
 /// Global activity scheduler
 ///
 /// The scheduler (aka 'FEO Executor') executes the FEO activities according to the defined order.
+/// Each task chain is cycled independently with its own cycle time.
 pub(crate) struct Scheduler {
     /// The ID of the agent this scheduler is running on.
     agent_id: AgentId,
-    /// Target duration of a task chain cycle
-    cycle_time: feo_time::Duration,
+    /// Task chains, sorted by their IDs
+    chains: Vec<ChainState>,
     /// Timeout of receive function
     receive_timeout: feo_time::Duration,
     /// Timeout for waiting on activities to become ready during startup.
     startup_timeout: feo_time::Duration,
+    /// Startup dependencies of the activities, starting them in phases
+    startup_order: StartupOrder,
     /// For each activity: list of activities it depends on
     activity_depends: HashMap<ActivityId, Vec<ActivityId>>,
     /// Map keeping track of activity states
@@ -49,6 +77,53 @@ pub(crate) struct Scheduler {
     connector: Box<dyn ConnectScheduler>,
     /// Flag to signal a shutdown request from an external source (e.g., Ctrl-C).
     shutdown_requested: Arc<AtomicBool>,
+    /// Step deadlines of the monitored activities
+    step_deadlines: StepDeadlines,
+    /// Reaction to failed steps per activity
+    error_policies: ErrorPolicies,
+    /// Heartbeat supervision of the remote agents
+    supervisor: AgentSupervisor,
+    /// External watchdog notified once per completed cycle, if any
+    watchdog: Option<Watchdog>,
+    /// Status published to the introspection server, if any
+    introspection: Option<Introspection>,
+    /// Cycle statistics collected, if any
+    statistics: Option<CycleStatistics>,
+    /// Next time at which the cycle statistics are logged
+    next_statistics_log: Option<Instant>,
+    /// Capture of the step signals for recordings, if any
+    signals: Option<SignalCapture>,
+    /// Merged log of all agents, if any
+    merged_log: Option<MergedLog>,
+    /// Requests to disable or re-enable activities, applied at the cycle boundaries
+    control: ActivityControl,
+    /// Pausing and stepping of the cycles, checked before the start of each cycle
+    debugger: CycleDebugger,
+    /// Reloaded parameters of the configuration, applied at the cycle boundaries
+    reload: ConfigReload,
+    /// Triggers of the cycles of on-demand task chains
+    trigger: CycleTrigger,
+    /// IDs of the task chains cycled on demand
+    on_demand_chains: Vec<ChainId>,
+    /// Start of waiting for a ready signal, kept while waiting is interrupted to start other chains
+    ready_wait_start: Option<Instant>,
+    /// Remote agents connected at startup
+    remote_agents: Vec<AgentId>,
+    /// Utilization in percent of the last cycle per worker, as reported by the workers
+    worker_utilization: HashMap<WorkerId, u8>,
+    /// Activities of rejoined agents whose startup is running, see [rejoin](crate::rejoin)
+    rejoining: HashSet<ActivityId>,
+    /// Activities of rejoined agents started up and stepped again from the next cycle of their chain
+    resuming: HashSet<ActivityId>,
+    /// Worker of each activity running on a worker shared by task chains of different priorities
+    prioritized_workers: HashMap<ActivityId, WorkerId>,
+    /// Due steps on prioritized workers held back until their worker is free, in the order they became due,
+    /// with the skipped producers of each activity
+    held_steps: Vec<(ActivityId, Vec<ActivityId>)>,
+    /// Mirror of the scheduler state to a standby primary agent, if any
+    mirror: Option<StandbyMirror>,
+    /// Remote agents connected to the failed primary agent this scheduler took over from, if any
+    resumed_agents: Option<Vec<AgentId>>,
 }
 
 impl Scheduler {
@@ -58,20 +133,102 @@ impl Scheduler {
         feo_cycle_time: feo_time::Duration,
         receive_timeout: feo_time::Duration,
         startup_timeout: feo_time::Duration,
+        startup_order: StartupOrder,
         activity_depends: HashMap<ActivityId, Vec<ActivityId>>,
+        activity_workers: HashMap<ActivityId, WorkerId>,
         connector: Box<dyn ConnectScheduler>,
         shutdown_requested: Arc<AtomicBool>,
+        step_deadlines: StepDeadlines,
+        task_chains: TaskChains,
+        error_policies: ErrorPolicies,
+        supervisor: AgentSupervisor,
+        watchdog: Option<Watchdog>,
+        introspection: Option<Introspection>,
+        statistics: Option<CycleStatistics>,
+        signals: Option<SignalCapture>,
+        merged_log: Option<MergedLog>,
+        control: ActivityControl,
+        debugger: CycleDebugger,
+        reload: ConfigReload,
+        trigger: CycleTrigger,
+        mirror: Option<StandbyMirror>,
+        resume: Option<MirroredState>,
     ) -> Self {
+        #[cfg(feature = "fault_injection")]
+        let connector = crate::testing::fault_injection::wrap_scheduler(connector);
+
+        // Group activities by task chain, omitting chains without activities
+        let mut chain_activities: HashMap<ChainId, Vec<ActivityId>> = HashMap::new();
+        for id in activity_depends.keys() {
+            chain_activities.entry(task_chains.chain_of(id)).or_default().push(*id);
+        }
+        let mut chain_ids: Vec<ChainId> = chain_activities.keys().copied().collect();
+        chain_ids.sort();
+        let now = Instant::now();
+        let mut chains: Vec<ChainState> = chain_ids
+            .into_iter()
+            .map(|id| ChainState {
+                id,
+                cycle_time: task_chains.cycle_times.get(&id).copied().unwrap_or(feo_cycle_time),
+                on_demand: task_chains.on_demand.contains(&id),
+                priority: task_chains.priority_of(&id),
+                overrun_policy: task_chains.overrun_policy_of(&id),
+                degraded_rate: false,
+                activities: chain_activities.remove(&id).unwrap(),
+                barrier: CycleBarrier::default(),
+                running: false,
+                cycle_start: now,
+                next_start: now,
+                cycle: 0,
+                start_jitter: feo_time::Duration::ZERO,
+                last_duration: None,
+                span: None,
+            })
+            .collect();
+
+        let on_demand_chains = chains.iter().filter(|chain| chain.on_demand).map(|chain| chain.id).collect();
+
+        // Workers running activities of task chains of different priorities get one step at a time
+        let mut worker_priorities: HashMap<WorkerId, HashSet<u8>> = HashMap::new();
+        for (id, worker) in activity_workers.iter() {
+            let priority = task_chains.priority_of(&task_chains.chain_of(id));
+            worker_priorities.entry(*worker).or_default().insert(priority);
+        }
+        let prioritized_workers: HashMap<ActivityId, WorkerId> = activity_workers
+            .into_iter()
+            .filter(|(_, worker)| worker_priorities[worker].len() > 1)
+            .collect();
+
+        // Continue the cycle counters of a failed primary agent
+        if let Some(resume) = resume.as_ref() {
+            for chain in chains.iter_mut() {
+                if let Some((_, cycle)) = resume.cycles.iter().find(|(id, _)| *id == chain.id.id()) {
+                    chain.cycle = *cycle;
+                }
+            }
+        }
+
         // Pre-allocate state map
-        let activity_states: HashMap<ActivityId, ActivityState> = activity_depends
-            .keys()
-            .map(|k| {
+        let activity_states: HashMap<ActivityId, ActivityState> = chains
+            .iter()
+            .enumerate()
+            .flat_map(|(chain, state)| state.activities.iter().map(move |id| (*id, chain)))
+            .map(|(k, chain)| {
                 (
-                    *k,
+                    k,
                     ActivityState {
+                        chain,
                         triggered: false,
                         ready: false,
                         ever_ready: false,
+                        degraded: false,
+                        disabled: false,
+                        skipped: false,
+                        retries: 0,
+                        step_triggered_at: None,
+                        overrun_reported: false,
+                        last_step_duration: None,
+                        retained_memory: None,
                     },
                 )
             })
@@ -79,13 +236,37 @@ impl Scheduler {
 
         Self {
             agent_id,
-            cycle_time: feo_cycle_time,
+            chains,
             receive_timeout,
             startup_timeout,
+            startup_order,
             activity_depends,
             connector,
             activity_states,
             shutdown_requested,
+            step_deadlines,
+            error_policies,
+            supervisor,
+            watchdog,
+            introspection,
+            statistics,
+            next_statistics_log: None,
+            signals,
+            merged_log,
+            control,
+            debugger,
+            reload,
+            trigger,
+            on_demand_chains,
+            ready_wait_start: None,
+            remote_agents: Vec::new(),
+            worker_utilization: HashMap::new(),
+            rejoining: HashSet::new(),
+            resuming: HashSet::new(),
+            prioritized_workers,
+            held_steps: Vec::new(),
+            mirror,
+            resumed_agents: resume.map(|state| state.agents.into_iter().map(AgentId::new).collect()),
         }
     }
 
@@ -97,117 +278,632 @@ impl Scheduler {
     }
 
     /// Run the task lifecycle, i.e. startup, stepping, shutdown
-    pub(crate) fn run(&mut self) {
-        #[cfg(feature = "loop_duration_meter")]
-        let mut meter = loop_duration_meter::LoopDurationMeter::<1000>::default();
-
-        // Sort activity ids
-        let mut activity_ids: Vec<_> = self.activity_states.keys().collect();
-        activity_ids.sort();
-
-        // Call startup on all activities sorted according to their ids
-        // Note: Actual startup may occur in different order, depending on the assignment
-        // of activities to worker threads. (A worker with greater id value may start up in
-        // one thread before an activity with smaller id value in another thread.)
-        for activity_id in activity_ids {
-            Self::startup_activity(activity_id, &mut self.connector).unwrap();
-        }
-
-        // Wait until all activities have returned their ready signal, with a timeout.
-        let startup_start = Instant::now();
-        while !self.all_ready() {
-            if startup_start.elapsed() > self.startup_timeout {
+    ///
+    /// Returns the failure which led to the shutdown, if any. All agents are shut down in any case.
+    pub(crate) fn run(&mut self) -> Result<(), Error> {
+        self.startup()?;
+
+        // Supervise the heartbeats of all remote agents once they are up and running
+        self.remote_agents = self
+            .connector
+            .get_connected_agent_ids()
+            .into_iter()
+            .filter(|id| *id != self.agent_id)
+            .collect();
+        self.supervisor.start(self.remote_agents.iter().copied());
+        self.check_resumed_agents();
+        self.publish_status(true);
+
+        // Loop the FEO task chains
+        let now = Instant::now();
+        self.chains.iter_mut().for_each(|chain| chain.next_start = now);
+        self.next_statistics_log = self
+            .statistics
+            .as_ref()
+            .and_then(CycleStatistics::log_interval)
+            .map(|interval| now + interval);
+        loop {
+            // Check for an external shutdown request (e.g., from Ctrl-C).
+            // Running cycles are finished before shutting down.
+            let shutdown_requested = self.shutdown_requested.load(Ordering::Relaxed);
+            if shutdown_requested && !self.chains.iter().any(|chain| chain.running) {
+                info!("External shutdown signal received, initiating graceful shutdown.");
+                self.debugger.release_clock();
+                break;
+            }
+
+            self.apply_reload();
+
+            // Start all chains which are due for their next cycle or have been triggered,
+            // unless held by the debugger
+            let now = Instant::now();
+            let mut held = false;
+            if !shutdown_requested {
+                for chain in 0..self.chains.len() {
+                    let state = &mut self.chains[chain];
+                    if state.running {
+                        continue;
+                    }
+                    let due = if state.on_demand {
+                        self.trigger.is_pending(&[state.id])
+                    } else {
+                        state.next_start <= now
+                    };
+                    if !due {
+                        continue;
+                    }
+                    if !self.debugger.take_step() {
+                        held = true;
+                        continue;
+                    }
+                    if state.on_demand {
+                        state.next_start = self.trigger.take(state.id).unwrap_or(now);
+                    }
+                    self.start_chain(chain);
+                }
+            }
+
+            // Step all activities that have their dependencies met
+            if let Err(e) = self.step_ready_activities() {
+                error!("Failed to trigger activity steps: {:?}", e);
+                self.shutdown_gracefully("Failed to trigger activity steps.");
+                return Err(e);
+            }
+
+            // Finish all chains whose activities are done
+            for chain in 0..self.chains.len() {
+                if self.chains[chain].running && self.chain_ready(chain) {
+                    self.finish_chain(chain);
+                    self.notify_watchdog();
+                    self.mirror_state();
+                    self.publish_status(true);
+                }
+            }
+            self.log_statistics();
+
+            // Time left until the next cycle start of an idle periodic chain, none while held by the debugger
+            let mut next_start = self
+                .chains
+                .iter()
+                .filter(|chain| !held && !chain.running && !chain.on_demand)
+                .map(|chain| chain.next_start.saturating_duration_since(now))
+                .min();
+
+            if held && !self.chains.iter().any(|chain| chain.running) {
+                // Paused between cycles, waiting is limited by the receive timeout to check for shutdown requests
+                self.publish_status(true);
+                self.debugger.wait(self.receive_timeout.into());
+                continue;
+            }
+
+            if !self.chains.iter().any(|chain| chain.running) {
+                // Nothing to wait for but the next cycle start or trigger.
+                // Waiting for a trigger is limited by the receive timeout to check for shutdown requests.
+                self.ready_wait_start = None;
+                let time_left = next_start.or((!self.on_demand_chains.is_empty()).then_some(self.receive_timeout));
+                if let Some(time_left) = time_left {
+                    if let Err(e) = self.wait_idle(time_left) {
+                        error!("A failure occurred while waiting for the next cycle: {:?}", e);
+                        self.shutdown_gracefully("A failure occurred while waiting for the next cycle.");
+                        return Err(e);
+                    }
+                }
+                continue;
+            }
+
+            // Check for triggers of idle on-demand chains regularly
+            if self.chains.iter().any(|chain| !chain.running && chain.on_demand) {
+                next_start = Some(next_start.map_or(TRIGGER_POLL_INTERVAL, |time_left| {
+                    min(time_left, TRIGGER_POLL_INTERVAL)
+                }));
+            }
+
+            // Wait until a new ready signal has been received or the next cycle of an idle chain is due.
+            // If we receive an error not handled by an error policy, proceed to graceful shutdown.
+            if let Err(e) = self.wait_next_ready(next_start) {
                 error!(
-                    "Startup timeout of {:?} exceeded. Not all activities became ready.",
-                    self.startup_timeout
+                    "A failure occurred during step execution: {:?}, while waiting for activities ready signal: {:?}",
+                    e, &self.activity_states
                 );
-                self.shutdown_gracefully("Startup timeout exceeded.");
-                return;
+                self.shutdown_gracefully("A failure occurred during step execution.");
+                return Err(e);
+            }
+        } // end loop
+
+        // Once the loop is broken, always perform a graceful shutdown.
+        self.shutdown_gracefully("Main loop concluded or external signal received.");
+        Ok(())
+    }
+
+    /// Start the next cycle of the given task chain
+    ///
+    /// Pending requests to disable or re-enable activities of the chain are applied before.
+    fn start_chain(&mut self, chain: usize) {
+        self.apply_control(chain);
+        self.resume_activities(chain);
+
+        let state = &mut self.chains[chain];
+        state.running = true;
+        state.cycle_start = Instant::now();
+        state.start_jitter = state.cycle_start.saturating_duration_since(state.next_start);
+
+        // The span is entered and exited explicitly, since cycles of different chains overlap
+        let span = span!(Level::INFO, "cycle", chain_id = state.id.id(), cycle = state.cycle);
+        span.with_subscriber(|(id, subscriber)| subscriber.enter(id));
+        state.span = Some(span);
+
+        // Clear ready and triggered signals
+        for id in state.activities.iter() {
+            let activity_state = self.activity_states.get_mut(id).unwrap();
+            activity_state.ready = false;
+            activity_state.triggered = false;
+            activity_state.skipped = false;
+            activity_state.retries = 0;
+        }
+        state.barrier.open(state.activities.iter());
+
+        debug!("Starting task chain {}", state.id);
+    }
+
+    /// Apply the pending requests to disable or re-enable activities of the given task chain
+    fn apply_control(&mut self, chain: usize) {
+        for id in self.control.take_unknown(|id| self.activity_states.contains_key(id)) {
+            warn!("Ignoring request to disable or enable unknown activity {}", id);
+        }
+        for (id, disabled) in self.control.take(&self.chains[chain].activities) {
+            let state = self.activity_states.get_mut(&id).unwrap();
+            if state.disabled == disabled {
+                continue;
+            }
+            state.disabled = disabled;
+            if disabled {
+                warn!("Disabling activity {}", id);
+            } else {
+                info!("Re-enabling activity {}", id);
             }
-            if let Err(err) = self.wait_next_ready() {
-                // An error here, such as ActivityFailed or a timeout, constitutes a startup failure.
-                // Log the specific error, but pass a generic reason to shutdown_gracefully
-                error!("A failure occurred during startup: {:?}. Aborting.", err);
-                self.shutdown_gracefully("Startup failed due to an activity error.");
-                return;
+        }
+    }
+
+    /// Step the activities of the given task chain again whose agents rejoined, see [rejoin](crate::rejoin)
+    fn resume_activities(&mut self, chain: usize) {
+        let state = &self.chains[chain];
+        for id in state.activities.iter() {
+            if self.resuming.remove(id) {
+                info!(
+                    "Resuming activity {} in cycle {} of task chain {}",
+                    id, state.cycle, state.id
+                );
+                self.activity_states.get_mut(id).unwrap().degraded = false;
             }
         }
+    }
 
-        // Loop the FEO task chain
-        loop {
-            let task_chain_start = Instant::now();
-
-            // Clear ready and triggered signals
-            self.activity_states.values_mut().for_each(|v| {
-                v.ready = false;
-                v.triggered = false;
-            });
-
-            debug!("Starting task chain");
-
-            while !self.all_ready() {
-                // Step all activities that have their dependencies met
-                self.step_ready_activities();
-                // Wait until a new ready signal has been received.
-                // If we receive an error (i.e., an ActivityFailed signal), proceed to graceful shutdown.
-                if let Err(e) = self.wait_next_ready() {
-                    error!("A failure occurred during step execution: {:?}, while waiting for activities ready signal: {:?}", e, &self.activity_states);
-                    self.shutdown_gracefully("A failure occurred during step execution.");
-                    return;
+    /// Apply the parameters of a reloaded configuration, reloading it first if requested by SIGHUP
+    ///
+    /// New cycle times take effect when planning the next cycle of each chain.
+    fn apply_reload(&mut self) {
+        if self.reload.take_sighup() {
+            if let Err(e) = self.reload.reload() {
+                error!("Failed to reload configuration: {}", e.to_string().as_str());
+            }
+        }
+        let Some(params) = self.reload.take() else {
+            return;
+        };
+        for chain in self.chains.iter_mut() {
+            let cycle_time = if chain.id == MAIN_CHAIN {
+                Some(params.cycle_time)
+            } else {
+                params
+                    .chain_cycle_times
+                    .iter()
+                    .find(|(id, _)| *id == chain.id)
+                    .map(|(_, cycle_time)| *cycle_time)
+            };
+            if let Some(cycle_time) = cycle_time.filter(|cycle_time| *cycle_time != chain.cycle_time) {
+                info!("Changing cycle time of task chain {} to {:?}", chain.id, cycle_time);
+                if !chain.running && !chain.on_demand && chain.cycle > 0 {
+                    chain.next_start = chain.cycle_start + cycle_time;
                 }
+                chain.cycle_time = cycle_time;
             }
+        }
+        if let Some(timeout) = params.timeout {
+            self.receive_timeout = timeout;
+        }
+    }
 
-            let task_chain_duration = task_chain_start.elapsed();
-
-            #[cfg(feature = "loop_duration_meter")]
-            meter.track(&task_chain_duration);
+    /// Finish the current cycle of the given task chain and plan its next cycle
+    ///
+    /// The next cycle of an overrunning periodic chain is planned according to its [OverrunPolicy].
+    fn finish_chain(&mut self, chain: usize) {
+        let state = &mut self.chains[chain];
+        state.running = false;
+        let cycle = state.cycle;
+        state.cycle = state.cycle.wrapping_add(1);
+        let span = state.span.take();
 
-            let time_left = self.cycle_time.saturating_sub(task_chain_duration);
+        let now = Instant::now();
+        let task_chain_duration = now.saturating_duration_since(state.cycle_start);
+        state.last_duration = Some(task_chain_duration);
+        if let Some(statistics) = self.statistics.as_ref() {
+            statistics.record_cycle(state.id, state.start_jitter, task_chain_duration);
+        }
+        let time_left = state.cycle_time.saturating_sub(task_chain_duration);
+        if state.on_demand {
             if time_left.is_zero() {
                 error!(
-                    "Finished task chain after {:?}. Expected to be less than {:?}",
-                    task_chain_duration, self.cycle_time
+                    "Finished task chain {} after {:?}. Expected to be less than {:?}",
+                    state.id, task_chain_duration, state.cycle_time
                 );
+                if let Some(statistics) = self.statistics.as_ref() {
+                    statistics.record_overrun(state.id);
+                }
             } else {
                 debug!(
-                    "Finished task chain after {:?}. Sleeping for {:?}",
-                    task_chain_duration, time_left
+                    "Finished task chain {} after {:?}. Waiting for the next trigger",
+                    state.id, task_chain_duration
                 );
-                thread::sleep(time_left.into());
+            }
+        } else {
+            let (next_start, outcome) = overrun::plan(state.overrun_policy, state.cycle_start, state.cycle_time, now);
+            state.next_start = next_start;
+            match outcome {
+                Some(outcome) => {
+                    error!(
+                        "Finished task chain {} after {:?}. Expected to be less than {:?}, {}",
+                        state.id,
+                        task_chain_duration,
+                        state.cycle_time,
+                        outcome.to_string().as_str()
+                    );
+                    if let Some(span) = span.as_ref() {
+                        event!(
+                            parent: span,
+                            Level::WARN,
+                            overrun = outcome.name(),
+                            duration_us = task_chain_duration.0.as_micros() as u64
+                        );
+                    }
+                    if let Some(signals) = self.signals.as_ref() {
+                        signals.capture_overrun(state.id, cycle, task_chain_duration, outcome, timestamp());
+                    }
+                    if let Some(statistics) = self.statistics.as_ref() {
+                        statistics.record_overrun(state.id);
+                    }
+                },
+                None => debug!(
+                    "Finished task chain {} after {:?}. Next cycle in {:?}",
+                    state.id, task_chain_duration, time_left
+                ),
             }
 
-            // Check for an external shutdown request (e.g., from Ctrl-C).
-            if self.shutdown_requested.load(Ordering::Relaxed) {
-                info!("External shutdown signal received, initiating graceful shutdown.");
-                break;
+            // Drop to or recover from the degraded rate
+            let degraded_rate = matches!(outcome, Some(OverrunOutcome::Degraded(_)));
+            if degraded_rate && !state.degraded_rate {
+                warn!("Dropping task chain {} to its degraded rate", state.id);
+            } else if !degraded_rate && state.degraded_rate {
+                info!("Task chain {} recovered from its degraded rate", state.id);
             }
-        } // end loop
+            state.degraded_rate = degraded_rate;
+        }
 
-        // Once the loop is broken, always perform a graceful shutdown.
-        self.shutdown_gracefully("Main loop concluded or external signal received.");
+        if let Some(span) = span {
+            span.with_subscriber(|(id, subscriber)| subscriber.exit(id));
+        }
+    }
+
+    /// Notify the external watchdog about a completed cycle
+    ///
+    /// A failing notification is logged but does not stop the scheduler: the watchdog
+    /// will eventually detect the missing notifications itself.
+    fn notify_watchdog(&mut self) {
+        if let Some(watchdog) = self.watchdog.as_mut() {
+            if let Err(e) = watchdog.notify() {
+                warn!("Failed to notify watchdog: {:?}", e);
+            }
+        }
+    }
+
+    /// Mirror the current state to the standby primary agent, if any
+    fn mirror_state(&mut self) {
+        let Some(mirror) = self.mirror.as_mut() else {
+            return;
+        };
+        mirror.send(&MirroredState {
+            startup_ns: sync_info().since_epoch().0.as_nanos() as u64,
+            cycles: self.chains.iter().map(|chain| (chain.id.id(), chain.cycle)).collect(),
+            agents: self.remote_agents.iter().map(AgentId::id).collect(),
+        });
+    }
+
+    /// Warn about remote agents of the failed primary agent which did not reconnect after a takeover
+    fn check_resumed_agents(&mut self) {
+        let Some(resumed_agents) = self.resumed_agents.take() else {
+            return;
+        };
+        info!("Took over from failed primary agent");
+        for id in resumed_agents {
+            if !self.remote_agents.contains(&id) {
+                warn!("Agent {} of the failed primary agent did not reconnect", id);
+            }
+        }
+    }
+
+    /// Log the cycle statistics if due
+    fn log_statistics(&mut self) {
+        let (Some(statistics), Some(due)) = (self.statistics.as_ref(), self.next_statistics_log.as_mut()) else {
+            return;
+        };
+        let now = Instant::now();
+        if *due > now {
+            return;
+        }
+        *due = now + statistics.log_interval().unwrap_or_default();
+
+        let stats = statistics.snapshot();
+        for (id, chain) in stats.chains.iter() {
+            info!(
+                "Statistics of task chain {}: {} cycles, duration min {:?} p99 {:?} max {:?}, start jitter p99 {:?} max {:?}",
+                id,
+                chain.duration.count(),
+                chain.duration.min().unwrap_or_default(),
+                chain.duration.percentile(99).unwrap_or_default(),
+                chain.duration.max().unwrap_or_default(),
+                chain.start_jitter.percentile(99).unwrap_or_default(),
+                chain.start_jitter.max().unwrap_or_default()
+            );
+        }
+        for (id, steps) in stats.activities.iter() {
+            debug!(
+                "Statistics of activity {}: {} steps, duration min {:?} p99 {:?} max {:?}",
+                id,
+                steps.count(),
+                steps.min().unwrap_or_default(),
+                steps.percentile(99).unwrap_or_default(),
+                steps.max().unwrap_or_default()
+            );
+        }
+    }
+
+    /// Note the bytes retained by a step of the given activity, reported by its worker
+    fn record_retained_memory(&mut self, id: ActivityId, bytes: i64) {
+        let Some(state) = self.activity_states.get_mut(&id) else {
+            warn!("Ignoring memory retained by unknown activity {}", id);
+            return;
+        };
+        let total = state.retained_memory.map_or(0, |(_, total)| total);
+        state.retained_memory = Some((bytes, total.saturating_add(bytes)));
+    }
+
+    /// Log the panic of an activity reported by its worker and capture it for recordings
+    ///
+    /// The failure of the panicked method follows the report and is handled by the error policy.
+    fn report_panic(&self, report: PanicReport) {
+        error!("Activity {} panicked: {}", report.activity, report.message());
+        if !report.backtrace().is_empty() {
+            error!("Backtrace of activity {}: {}", report.activity, report.backtrace());
+        }
+        if let Some(signals) = self.signals.as_ref() {
+            signals.capture_panic(&report, timestamp());
+        }
+    }
+
+    /// Publish the current status to the introspection server, if any
+    fn publish_status(&self, running: bool) {
+        let Some(introspection) = self.introspection.as_ref() else {
+            return;
+        };
+        let chains = self
+            .chains
+            .iter()
+            .map(|chain| ChainStatus {
+                id: chain.id.id(),
+                cycle: chain.cycle,
+                last_cycle_us: chain.last_duration.map(|duration| duration.0.as_micros() as u64),
+            })
+            .collect();
+        let mut activities: Vec<ActivityStatus> = self
+            .activity_states
+            .iter()
+            .map(|(id, state)| ActivityStatus {
+                id: id.id(),
+                chain: self.chains[state.chain].id.id(),
+                last_step_us: state.last_step_duration.map(|duration| duration.0.as_micros() as u64),
+                degraded: state.degraded,
+                disabled: state.disabled,
+                last_step_retained_bytes: state.retained_memory.map(|(last, _)| last),
+                retained_bytes: state.retained_memory.map(|(_, total)| total),
+            })
+            .collect();
+        activities.sort_by_key(|activity| activity.id);
+        let agents = self
+            .remote_agents
+            .iter()
+            .map(|id| AgentStatus {
+                id: id.id(),
+                healthy: self.supervisor.is_healthy(id),
+            })
+            .collect();
+        let mut workers: Vec<WorkerStatus> = self
+            .worker_utilization
+            .iter()
+            .map(|(id, percent)| WorkerStatus {
+                id: id.id(),
+                utilization_percent: *percent,
+            })
+            .collect();
+        workers.sort_by_key(|worker| worker.id);
+        introspection.publish(Status {
+            running,
+            paused: self.debugger.is_paused(),
+            chains,
+            activities,
+            agents,
+            workers,
+        });
+    }
+
+    /// Check if all activities of the given task chain have arrived at the barrier of its cycle
+    fn chain_ready(&self, chain: usize) -> bool {
+        self.chains[chain].barrier.is_complete()
     }
 
     /// Step all activities whose dependencies have signalled ready
-    fn step_ready_activities(&mut self) {
-        // Get data from activity_depends in self so that we can iterate over it
-        // and at the same time modify another member of self
-        for (act_id, dependencies) in self.activity_depends.iter() {
-            // skip activity if already triggered
-            if self.activity_states[act_id].triggered {
-                continue;
+    ///
+    /// Degraded and disabled activities are not stepped but immediately marked ready,
+    /// which may in turn fulfill the dependencies of further activities. Activities depending
+    /// on a skipped activity are informed about their stale inputs before their step.
+    /// Steps on prioritized workers are held back until their worker is free, see [TaskChains].
+    fn step_ready_activities(&mut self) -> Result<(), Error> {
+        let mut due = Vec::new();
+        loop {
+            let mut skipped_any = false;
+
+            // Get data from activity_depends in self so that we can iterate over it
+            // and at the same time modify another member of self
+            for (act_id, dependencies) in self.activity_depends.iter() {
+                // skip activity if already triggered or its task chain is not running
+                let state = &self.activity_states[act_id];
+                if state.triggered || !self.chains[state.chain].running {
+                    continue;
+                }
+
+                // If dependencies are fulfilled
+                let is_ready = self
+                    .activity_states
+                    .iter()
+                    .filter(|(id, _)| dependencies.contains(id))
+                    .all(|(_, state)| state.ready);
+                if !is_ready {
+                    continue;
+                }
+
+                let stale: Vec<ActivityId> = dependencies
+                    .iter()
+                    .filter(|id| self.activity_states.get(*id).is_some_and(|state| state.skipped))
+                    .copied()
+                    .collect();
+                let state = self.activity_states.get_mut(act_id).unwrap();
+                state.triggered = true;
+                if state.degraded || state.disabled {
+                    trace!("Skipping step of degraded or disabled activity {}", act_id);
+                    state.ready = true;
+                    state.skipped = true;
+                    skipped_any = true;
+                    self.chains[state.chain].barrier.arrive(act_id);
+                } else {
+                    due.push((*act_id, stale));
+                }
             }
 
-            // If dependencies are fulfilled
-            let is_ready = self
-                .activity_states
-                .iter()
-                .filter(|(id, _)| dependencies.contains(id))
-                .all(|(_, state)| state.ready);
-            if is_ready {
-                Self::step_activity(act_id, &mut self.connector).expect("failed to step activity");
-                self.activity_states.get_mut(act_id).unwrap().triggered = true;
+            for (id, stale) in due.drain(..) {
+                if self.prioritized_workers.contains_key(&id) {
+                    self.held_steps.push((id, stale));
+                } else {
+                    self.trigger_step(id, stale)?;
+                }
+            }
+
+            if !skipped_any {
+                break;
             }
         }
+        self.dispatch_held_steps()
+    }
+
+    /// Step the held activities whose worker is free, those of the highest chain priority first
+    ///
+    /// A worker is free while none of its activities is stepping. Held steps of the same priority
+    /// are sent in the order they became due.
+    fn dispatch_held_steps(&mut self) -> Result<(), Error> {
+        if self.held_steps.is_empty() {
+            return Ok(());
+        }
+        // Drop held steps finished without running, e.g. of an aborted cycle or a lost agent
+        self.held_steps.retain(|(id, _)| !self.activity_states[id].ready);
+
+        let mut busy: HashSet<WorkerId> = self
+            .prioritized_workers
+            .iter()
+            .filter(|(id, _)| self.activity_states[*id].step_triggered_at.is_some())
+            .map(|(_, worker)| *worker)
+            .collect();
+        let mut order: Vec<usize> = (0..self.held_steps.len()).collect();
+        order.sort_by_key(|index| {
+            let id = &self.held_steps[*index].0;
+            core::cmp::Reverse(self.chains[self.activity_states[id].chain].priority)
+        });
+        let mut dispatched: Vec<usize> = order
+            .into_iter()
+            .filter(|index| busy.insert(self.prioritized_workers[&self.held_steps[*index].0]))
+            .collect();
+        dispatched.sort_unstable();
+        for index in dispatched.into_iter().rev() {
+            let (id, stale) = self.held_steps.remove(index);
+            self.trigger_step(id, stale)?;
+        }
+        Ok(())
+    }
+
+    /// Send the stale inputs and the step signal to the given activity
+    fn trigger_step(&mut self, id: ActivityId, stale: Vec<ActivityId>) -> Result<(), Error> {
+        for producer in stale {
+            Self::stale_input(&id, producer, &mut self.connector)?;
+        }
+        Self::step_activity(&id, &mut self.connector)?;
+        let triggered_at = timestamp();
+        let state = self.activity_states.get_mut(&id).ok_or(Error::ActivityNotFound(id))?;
+        state.step_triggered_at = Some(triggered_at);
+        state.overrun_reported = false;
+        self.capture_signal(id, SignalKind::Trigger, triggered_at);
+        Ok(())
+    }
+
+    /// Start up all activities phase by phase, failing unless all of them became ready
+    ///
+    /// The activities of a phase are started in the order of their ids. Actual startup may occur
+    /// in different order, depending on the assignment of activities to worker threads. (A worker
+    /// with greater id value may start up in one thread before an activity with smaller id value
+    /// in another thread.) On failure, all agents are shut down.
+    fn startup(&mut self) -> Result<(), Error> {
+        let phases = self.startup_order.phases(self.activity_states.keys().copied());
+        let phase_timeout = self.startup_order.phase_timeout.unwrap_or(self.startup_timeout);
+        for (phase, activities) in phases.iter().enumerate() {
+            if phases.len() > 1 {
+                debug!("Starting up phase {} of {} activities", phase, activities.len());
+            }
+            for activity_id in activities.iter() {
+                if let Err(err) = Self::startup_activity(activity_id, &mut self.connector) {
+                    error!("Failed to trigger startup of activity {}: {:?}. Aborting.", activity_id, err);
+                    self.shutdown_gracefully("Startup failed due to an activity error.");
+                    return Err(err);
+                }
+            }
+
+            // Wait until all activities of the phase have returned their ready signal, with a timeout.
+            let phase_start = Instant::now();
+            while !activities.iter().all(|id| self.activity_states[id].ready) {
+                if phase_start.elapsed() > phase_timeout {
+                    for id in activities.iter().filter(|id| !self.activity_states[*id].ready) {
+                        error!(
+                            "Startup phase {} blocked by activity {}, not ready after {:?}",
+                            phase, id, phase_timeout
+                        );
+                    }
+                    self.shutdown_gracefully("Startup timeout exceeded.");
+                    return Err(Error::Timeout(Some(phase_timeout), "waiting for activities to start up"));
+                }
+                if let Err(err) = self.wait_next_ready(None) {
+                    // An error here, such as ActivityFailed or a timeout, constitutes a startup failure.
+                    // Log the specific error, but pass a generic reason to shutdown_gracefully
+                    error!("A failure occurred during startup: {:?}. Aborting.", err);
+                    self.shutdown_gracefully("Startup failed due to an activity error.");
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Send startup signal to the given activity
@@ -224,6 +920,17 @@ impl Scheduler {
         Self::trigger_activity(id, &signal, connector)
     }
 
+    /// Send stale input signal of the given skipped producer to the given activity
+    fn stale_input(
+        id: &ActivityId,
+        producer: ActivityId,
+        connector: &mut Box<dyn ConnectScheduler>,
+    ) -> Result<(), Error> {
+        debug!("Informing activity {} about stale input of activity {}", id, producer);
+        let signal = Signal::StaleInput((*id, producer));
+        Self::trigger_activity(id, &signal, connector)
+    }
+
     /// Send shutdown signal to the given activity
     fn shutdown_activity(id: &ActivityId, connector: &mut Box<dyn ConnectScheduler>) -> Result<(), Error> {
         debug!("Triggering shutdown for activity {}", id);
@@ -236,6 +943,7 @@ impl Scheduler {
     // #[allow(dead_code)] // This is called from the main binary, not within the library.
     pub(crate) fn shutdown_gracefully(&mut self, reason: &str) {
         info!("Shutting down... Reason: {}", reason);
+        self.publish_status(false);
 
         // --- PHASE 1: Shut down started activities ---
 
@@ -284,6 +992,8 @@ impl Scheduler {
                             info!("Received shutdown confirmation from activity {:?}", id);
                         }
                     },
+                    Ok(Some(Signal::Log(line))) => self.relay_log(line),
+                    Ok(Some(Signal::ActivityPanicked(report))) => self.report_panic(report),
                     Ok(Some(Signal::ActivityFailed((id, err)))) => {
                         // This handles "Activity shutdown error".
                         error!("Activity {} failed during shutdown: {:?}. Continuing.", id, err);
@@ -337,13 +1047,22 @@ impl Scheduler {
                 );
                 break;
             }
-            if let Ok(Some(Signal::TerminateAck(agent_id))) = self.connector.receive(self.receive_timeout) {
-                if pending_agent_acks.remove(&agent_id) {
-                    info!("Received TerminateAck from agent {}", agent_id);
-                }
+            match self.connector.receive(self.receive_timeout) {
+                Ok(Some(Signal::TerminateAck(agent_id))) => {
+                    if pending_agent_acks.remove(&agent_id) {
+                        info!("Received TerminateAck from agent {}", agent_id);
+                    }
+                },
+                Ok(Some(Signal::Log(line))) => self.relay_log(line),
+                _ => {},
             }
         }
 
+        // Tear down the agents which did not terminate when the primary agent is stopped by a signal
+        if !pending_agent_acks.is_empty() && self.shutdown_requested.load(Ordering::Relaxed) {
+            signal_forwarding::terminate_agents(&pending_agent_acks);
+        }
+
         info!("Finished waiting for all acknowledgements. Shutdown complete.");
     }
 
@@ -358,27 +1077,78 @@ impl Scheduler {
     }
 
     /// Wait for the next incoming ready signal
-    fn wait_next_ready(&mut self) -> Result<ActivityId, Error> {
+    ///
+    /// If `max_wait` is given, returns `None` when no ready signal has been received within `max_wait`.
+    /// Also returns `None` when running steps have been finished by degrading an unhealthy agent.
+    /// While waiting, the steps of all monitored activities are checked against their deadlines,
+    /// so that a hung activity is reported as soon as its budget is exceeded.
+    /// The receive timeout applies to the total time without a ready signal, also if the wait is
+    /// interrupted by `max_wait` in between. Its expiry reports the stragglers of the running cycles,
+    /// see [barrier](crate::barrier).
+    fn wait_next_ready(&mut self, max_wait: Option<feo_time::Duration>) -> Result<Option<ActivityId>, Error> {
+        let wait_start = Instant::now();
+        let ready_wait_start = *self.ready_wait_start.get_or_insert(wait_start);
+
         // Wait for next intra-process ready signal from one of the workers
         let activity_id = loop {
-            match self.connector.receive(self.receive_timeout)? {
+            if self.check_heartbeats()? || self.apply_agent_changes() {
+                self.ready_wait_start = None;
+                return Ok(None);
+            }
+            self.poll_log();
+
+            let waited = wait_start.elapsed();
+            let mut timeout = self.receive_timeout.saturating_sub(ready_wait_start.elapsed());
+            if let Some(time_to_deadline) = self.next_deadline() {
+                timeout = min(timeout, time_to_deadline);
+            }
+            if let Some(max_wait) = max_wait {
+                timeout = min(timeout, max_wait.saturating_sub(waited));
+            }
+            if let Some(time_to_check) = self.supervisor.next_check() {
+                timeout = min(timeout, time_to_check);
+            }
+
+            match self.connector.receive(timeout)? {
                 None => {
-                    return Err(Error::Timeout(Some(self.receive_timeout), "waiting for ready signal"));
+                    self.check_running_deadlines();
+                    let waited = wait_start.elapsed();
+                    if ready_wait_start.elapsed() >= self.receive_timeout {
+                        return Err(self.ready_timeout());
+                    }
+                    if max_wait.is_some_and(|max_wait| waited >= max_wait) {
+                        return Ok(None);
+                    }
                 },
-                Some(Signal::Ready((id, _))) => {
+                Some(Signal::Ready((id, _))) if self.rejoining.contains(&id) => self.rejoined_ready(id),
+                Some(Signal::ActivityFailed((id, err))) if self.rejoining.contains(&id) => {
+                    self.rejoined_failed(id, err);
+                },
+                Some(Signal::Ready((id, finished_at))) => {
+                    self.capture_signal(id, SignalKind::StepComplete, finished_at);
+                    self.capture_signal(id, SignalKind::Ready, timestamp());
                     break id;
                 },
                 Some(Signal::ActivityFailed((id, err))) => {
-                    error!(
-                        "Received failure signal {:?} from activity {}. Initiating graceful shutdown.",
-                        err, id
-                    );
-                    return Err(Error::ActivityFailed(id, err));
+                    self.capture_signal(id, SignalKind::Failed, timestamp());
+                    if self.handle_failure(id, err)? {
+                        break id;
+                    }
                 },
                 Some(Signal::TerminateAck(agent_id)) => {
                     trace!("Ignoring TerminateAck from agent {} during normal operation", agent_id);
                     continue;
                 },
+                Some(Signal::Heartbeat(agent_id)) => {
+                    self.supervisor.heartbeat(agent_id);
+                },
+                Some(Signal::Log(line)) => self.relay_log(line),
+                Some(Signal::ActivityPanicked(report)) => self.report_panic(report),
+                Some(Signal::AgentPid((agent_id, pid))) => signal_forwarding::register(agent_id, pid),
+                Some(Signal::MemoryRetained((id, bytes))) => self.record_retained_memory(id, bytes),
+                Some(Signal::WorkerUtilization((id, percent))) => {
+                    self.worker_utilization.insert(id, percent);
+                },
                 Some(other) => {
                     error!("Received unexpected signal {:?} while waiting for ready signal", other);
                 },
@@ -386,21 +1156,398 @@ impl Scheduler {
         };
 
         // Set corresponding ready flag
+        self.ready_wait_start = None;
         let state = self.activity_states.get_mut(&activity_id).unwrap();
         state.ready = true;
         state.ever_ready = true;
-        Ok(activity_id)
+        self.chains[state.chain].barrier.arrive(&activity_id);
+
+        // Check the finished step against its deadline, unless already reported while running
+        let triggered_at = state.step_triggered_at.take();
+        let overrun_reported = state.overrun_reported;
+        let cycle = self.chains[state.chain].cycle;
+        let elapsed = triggered_at.map(elapsed_since);
+        if let Some(elapsed) = elapsed {
+            state.last_step_duration = Some(elapsed);
+            if let Some(statistics) = self.statistics.as_ref() {
+                statistics.record_step(activity_id, elapsed);
+            }
+        }
+        if let (Some(elapsed), Some(budget)) = (elapsed, self.step_deadlines.budget(&activity_id)) {
+            if elapsed > budget && !overrun_reported {
+                self.step_deadlines.report(DeadlineOverrun {
+                    cycle,
+                    activity_id,
+                    budget,
+                    elapsed,
+                    finished: true,
+                });
+            }
+        }
+
+        Ok(Some(activity_id))
     }
 
-    /// Check if all activities have signalled 'ready'
-    fn all_ready(&self) -> bool {
-        self.activity_states.values().all(|v| v.ready)
+    /// Get the error of a receive timeout, reporting the stragglers of the running cycles, if any
+    ///
+    /// The stragglers are logged as well, so that stalls can be diagnosed without tracing.
+    fn ready_timeout(&self) -> Error {
+        let mut stragglers = Vec::new();
+        for chain in self.chains.iter().filter(|chain| chain.running) {
+            for id in chain.barrier.pending() {
+                let state = &self.activity_states[id];
+                let state = match state.step_triggered_at {
+                    Some(triggered_at) => StragglerState::Stepping(elapsed_since(triggered_at)),
+                    None if state.triggered => StragglerState::Held,
+                    None => StragglerState::Blocked,
+                };
+                stragglers.push(Straggler {
+                    chain: chain.id,
+                    cycle: chain.cycle,
+                    activity: *id,
+                    agent: self.supervisor.agent_of(id),
+                    state,
+                });
+            }
+        }
+        if stragglers.is_empty() {
+            return Error::Timeout(Some(self.receive_timeout), "waiting for ready signal");
+        }
+        for straggler in stragglers.iter() {
+            error!("Cycle not finished: {}", straggler.to_string().as_str());
+        }
+        Error::CycleTimeout(CycleTimeout {
+            timeout: self.receive_timeout,
+            stragglers,
+        })
     }
+
+    /// Wait for the given duration while no task chain is running
+    ///
+    /// Returns early when an on-demand task chain has been triggered.
+    /// Heartbeats and log records received while waiting are passed on to the supervision and the merged log.
+    fn wait_idle(&mut self, duration: feo_time::Duration) -> Result<(), Error> {
+        if !self.supervisor.is_active() && self.merged_log.is_none() && !self.connector.accepts_rejoin() {
+            if self.on_demand_chains.is_empty() {
+                Deadline::after(duration).wait();
+            } else {
+                self.trigger.wait(&self.on_demand_chains, duration);
+            }
+            return Ok(());
+        }
+
+        let wait_start = Instant::now();
+        loop {
+            self.check_heartbeats()?;
+            self.apply_agent_changes();
+            self.poll_log();
+
+            let waited = wait_start.elapsed();
+            if waited >= duration || self.trigger.is_pending(&self.on_demand_chains) {
+                return Ok(());
+            }
+            let mut timeout = duration - waited;
+            if let Some(time_to_check) = self.supervisor.next_check() {
+                timeout = min(timeout, time_to_check);
+            }
+            if self.merged_log.is_some() {
+                timeout = min(timeout, MERGE_WINDOW.into());
+            }
+            if !self.on_demand_chains.is_empty() {
+                timeout = min(timeout, TRIGGER_POLL_INTERVAL);
+            }
+
+            match self.connector.receive(timeout)? {
+                None => {},
+                Some(Signal::Heartbeat(agent_id)) => self.supervisor.heartbeat(agent_id),
+                Some(Signal::Log(line)) => self.relay_log(line),
+                Some(Signal::ActivityPanicked(report)) => self.report_panic(report),
+                Some(Signal::AgentPid((agent_id, pid))) => signal_forwarding::register(agent_id, pid),
+                Some(Signal::MemoryRetained((id, bytes))) => self.record_retained_memory(id, bytes),
+                Some(Signal::WorkerUtilization((id, percent))) => {
+                    self.worker_utilization.insert(id, percent);
+                },
+                Some(Signal::TerminateAck(agent_id)) => {
+                    trace!("Ignoring TerminateAck from agent {} during normal operation", agent_id);
+                },
+                Some(Signal::Ready((id, _))) if self.rejoining.contains(&id) => self.rejoined_ready(id),
+                Some(Signal::ActivityFailed((id, err))) if self.rejoining.contains(&id) => {
+                    self.rejoined_failed(id, err);
+                },
+                Some(other) => {
+                    error!(
+                        "Received unexpected signal {:?} while waiting for the next cycle",
+                        other
+                    );
+                },
+            }
+        }
+    }
+
+    /// Add a log record relayed by a secondary agent to the merged log, if any
+    fn relay_log(&mut self, line: LogLine) {
+        if let Some(merged_log) = self.merged_log.as_mut() {
+            merged_log.push(line);
+        }
+    }
+
+    /// Write the log records of the merged log which are due, if any
+    fn poll_log(&mut self) {
+        if let Some(merged_log) = self.merged_log.as_mut() {
+            merged_log.poll();
+        }
+    }
+
+    /// Check the heartbeats of all supervised agents and apply the reaction to unhealthy agents
+    ///
+    /// Returns whether running steps have been finished by degrading their activities,
+    /// or an error if an unhealthy agent requires a shutdown.
+    fn check_heartbeats(&mut self) -> Result<bool, Error> {
+        let mut finished_steps = false;
+        for (agent_id, action) in self.supervisor.check() {
+            match action {
+                SupervisionAction::Log => {},
+                SupervisionAction::Degrade => {
+                    warn!("Degrading all activities of unhealthy agent {}", agent_id);
+                    for id in self.supervisor.activities_of(&agent_id) {
+                        let Some(state) = self.activity_states.get_mut(id) else {
+                            continue;
+                        };
+                        state.degraded = true;
+                        // Do not wait for a running step of the unhealthy agent
+                        if state.triggered && !state.ready {
+                            state.ready = true;
+                            state.step_triggered_at = None;
+                            self.chains[state.chain].barrier.arrive(id);
+                            finished_steps = true;
+                        }
+                    }
+                },
+                SupervisionAction::Shutdown => {
+                    error!("Agent {} is unhealthy. Initiating graceful shutdown.", agent_id);
+                    return Err(Error::AgentUnhealthy(agent_id));
+                },
+            }
+        }
+        Ok(finished_steps)
+    }
+
+    /// Apply the connection losses and rejoins of remote agents, see [rejoin](crate::rejoin)
+    ///
+    /// The activities of lost agents are degraded until they rejoin. Returns whether running steps
+    /// have been finished by degrading their activities.
+    fn apply_agent_changes(&mut self) -> bool {
+        let mut finished_steps = false;
+        for change in self.connector.take_agent_changes() {
+            match change {
+                AgentChange::Lost(agent_id) => {
+                    warn!("Degrading all activities of lost agent {} until it rejoins", agent_id);
+                    for id in self.supervisor.activities_of(&agent_id) {
+                        self.rejoining.remove(id);
+                        self.resuming.remove(id);
+                        let Some(state) = self.activity_states.get_mut(id) else {
+                            continue;
+                        };
+                        state.degraded = true;
+                        // Do not wait for a running step of the lost agent
+                        if state.triggered && !state.ready {
+                            state.ready = true;
+                            state.step_triggered_at = None;
+                            self.chains[state.chain].barrier.arrive(id);
+                            finished_steps = true;
+                        }
+                    }
+                },
+                AgentChange::Rejoined(agent_id) => {
+                    info!("Agent {} rejoined, starting up its activities", agent_id);
+                    for id in self.supervisor.activities_of(&agent_id) {
+                        if let Err(e) = Self::startup_activity(id, &mut self.connector) {
+                            error!("Failed to start up activity {} of rejoined agent: {:?}", id, e);
+                            continue;
+                        }
+                        self.rejoining.insert(*id);
+                    }
+                },
+            }
+        }
+        finished_steps
+    }
+
+    /// Note the finished startup of an activity of a rejoined agent, stepping it from the next cycle on
+    fn rejoined_ready(&mut self, id: ActivityId) {
+        self.rejoining.remove(&id);
+        debug!("Activity {} of rejoined agent started up", id);
+        self.resuming.insert(id);
+    }
+
+    /// Keep an activity of a rejoined agent degraded after its failed startup
+    fn rejoined_failed(&mut self, id: ActivityId, err: ActivityError) {
+        self.rejoining.remove(&id);
+        error!(
+            "Activity {} of rejoined agent failed ({:?}), keeping it degraded",
+            id, err
+        );
+    }
+
+    /// Apply the error policy of the given failed activity
+    ///
+    /// Returns whether the failed step shall be treated as finished,
+    /// or an error if the failure requires a shutdown.
+    fn handle_failure(&mut self, id: ActivityId, err: ActivityError) -> Result<bool, Error> {
+        let policy = self.error_policies.policy(&id);
+        if err != ActivityError::Step || policy == ErrorPolicy::Abort {
+            error!(
+                "Received failure signal {:?} from activity {}. Initiating graceful shutdown.",
+                err, id
+            );
+            return Err(Error::ActivityFailed(id, err));
+        }
+
+        let state = self.activity_states.get_mut(&id).ok_or(Error::ActivityNotFound(id))?;
+        match policy {
+            ErrorPolicy::Abort => unreachable!(),
+            ErrorPolicy::Skip => {
+                warn!("Step of activity {} failed. Skipping step.", id);
+            },
+            ErrorPolicy::Retry(max_retries) => {
+                if state.retries >= max_retries {
+                    error!(
+                        "Step of activity {} failed after {} retries. Initiating graceful shutdown.",
+                        id, state.retries
+                    );
+                    return Err(Error::ActivityFailed(id, err));
+                }
+                state.retries += 1;
+                warn!(
+                    "Step of activity {} failed. Retry {} of {}.",
+                    id, state.retries, max_retries
+                );
+                Self::step_activity(&id, &mut self.connector)?;
+                let triggered_at = timestamp();
+                state.step_triggered_at = Some(triggered_at);
+                state.overrun_reported = false;
+                self.capture_signal(id, SignalKind::Trigger, triggered_at);
+                return Ok(false);
+            },
+            ErrorPolicy::Degrade => {
+                warn!("Step of activity {} failed. Degrading activity.", id);
+                state.degraded = true;
+            },
+            ErrorPolicy::AbortChain => {
+                let chain = &mut self.chains[state.chain];
+                warn!(
+                    "Step of activity {} failed. Aborting cycle {} of task chain {}.",
+                    id, chain.cycle, chain.id
+                );
+                // Mark all activities not triggered yet as done, so that only running steps are awaited
+                for activity_id in chain.activities.iter() {
+                    let state = self.activity_states.get_mut(activity_id).unwrap();
+                    if !state.triggered {
+                        state.triggered = true;
+                        state.ready = true;
+                        chain.barrier.arrive(activity_id);
+                    }
+                }
+            },
+        }
+        Ok(true)
+    }
+
+    /// Capture a signal of a running step of the given activity for recordings
+    fn capture_signal(&self, id: ActivityId, kind: SignalKind, at: Timestamp) {
+        let Some(signals) = self.signals.as_ref() else {
+            return;
+        };
+        let Some(state) = self.activity_states.get(&id) else {
+            return;
+        };
+        // Signals of startup and shutdown are not captured
+        if state.step_triggered_at.is_some() {
+            signals.capture(kind, id, self.chains[state.chain].cycle, at);
+        }
+    }
+
+    /// Get the time left until the earliest deadline of a running and not yet reported step
+    fn next_deadline(&self) -> Option<feo_time::Duration> {
+        self.activity_states
+            .iter()
+            .filter(|(_, state)| !state.overrun_reported)
+            .filter_map(|(id, state)| {
+                let triggered_at = state.step_triggered_at?;
+                let budget = self.step_deadlines.budget(id)?;
+                Some(budget.saturating_sub(elapsed_since(triggered_at)))
+            })
+            .min()
+    }
+
+    /// Report all running steps which have exceeded their deadline
+    fn check_running_deadlines(&mut self) {
+        for (id, state) in self.activity_states.iter_mut() {
+            if state.overrun_reported {
+                continue;
+            }
+            let (Some(triggered_at), Some(budget)) = (state.step_triggered_at, self.step_deadlines.budget(id)) else {
+                continue;
+            };
+            let elapsed = elapsed_since(triggered_at);
+            if elapsed > budget {
+                state.overrun_reported = true;
+                self.step_deadlines.report(DeadlineOverrun {
+                    cycle: self.chains[state.chain].cycle,
+                    activity_id: *id,
+                    budget,
+                    elapsed,
+                    finished: false,
+                });
+            }
+        }
+    }
+}
+
+/// Time elapsed since the given timestamp
+fn elapsed_since(since: Timestamp) -> feo_time::Duration {
+    timestamp().0.saturating_sub(since.0)
+}
+
+/// Current state of a task chain
+struct ChainState {
+    /// ID of the chain
+    id: ChainId,
+    /// Target duration of a cycle of the chain, the maximum duration if cycled on demand
+    cycle_time: feo_time::Duration,
+    /// Whether the chain runs one cycle per trigger instead of periodically
+    on_demand: bool,
+    /// Priority of the steps of the chain on prioritized workers
+    priority: u8,
+    /// Reaction to overrunning cycles of the chain
+    overrun_policy: OverrunPolicy,
+    /// Whether the chain runs at the degraded rate of its overrun policy
+    degraded_rate: bool,
+    /// Activities belonging to the chain
+    activities: Vec<ActivityId>,
+    /// Barrier of the current or last cycle, see [barrier](crate::barrier)
+    barrier: CycleBarrier,
+    /// Whether a cycle of the chain is currently running
+    running: bool,
+    /// Start of the current or last cycle
+    cycle_start: Instant,
+    /// Planned start of the next cycle, or the time of its trigger if cycled on demand
+    next_start: Instant,
+    /// Number of the current cycle
+    cycle: u64,
+    /// Delay of the start of the current or last cycle after its planned start
+    start_jitter: feo_time::Duration,
+    /// Duration of the last completed cycle
+    last_duration: Option<feo_time::Duration>,
+    /// Trace span of the current cycle, entered while the cycle is running
+    span: Option<tracing::Span>,
 }
 
 /// Current state of an activity
 #[derive(Debug, ScoreDebug)]
 struct ActivityState {
+    /// Index of the task chain the activity belongs to
+    chain: usize,
     /// Whether the activity has been triggered for an action
     triggered: bool,
 
@@ -408,30 +1555,303 @@ struct ActivityState {
     ready: bool,
     /// Whether the activity has ever been ready (i.e., has started)
     ever_ready: bool,
+    /// Whether the activity is no longer stepped due to a failure
+    degraded: bool,
+    /// Whether the activity is not stepped on request, see [control](crate::control)
+    disabled: bool,
+    /// Whether the step of the activity has been skipped in the current cycle
+    skipped: bool,
+    /// Number of retries of the current step
+    retries: u32,
+    /// Point in time at which the currently running step was triggered
+    step_triggered_at: Option<Timestamp>,
+    /// Whether a deadline overrun of the currently running step has already been reported
+    overrun_reported: bool,
+    /// Duration of the last finished step
+    last_step_duration: Option<feo_time::Duration>,
+    /// Bytes retained by the last step, if reported, and by all steps since startup,
+    /// see [memory](crate::memory)
+    retained_memory: Option<(i64, i64)>,
 }
 
-#[cfg(feature = "loop_duration_meter")]
-mod loop_duration_meter {
-    use std::println;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervision::Supervision;
+    use alloc::collections::VecDeque;
+    use std::sync::Mutex;
 
-    /// Meter to track and periodically print average durations
-    #[derive(Default)]
-    pub(super) struct LoopDurationMeter<const NUM_STEPS: usize> {
-        duration_micros: usize,
-        num_steps: usize,
+    /// Connector recording the sent signals and receiving the queued ones
+    #[derive(Clone, Default)]
+    struct FakeConnector {
+        sent: Arc<Mutex<Vec<Signal>>>,
+        received: Arc<Mutex<VecDeque<Signal>>>,
     }
 
-    impl<const NUM_STEPS: usize> LoopDurationMeter<NUM_STEPS> {
-        pub fn track(&mut self, duration: &feo_time::Duration) {
-            self.duration_micros += duration.subsec_micros() as usize + 1000000 * duration.as_secs() as usize;
-            self.num_steps += 1;
+    impl FakeConnector {
+        /// Take the IDs of the activities stepped since the last call
+        fn take_steps(&self) -> Vec<ActivityId> {
+            self.sent
+                .lock()
+                .unwrap()
+                .drain(..)
+                .filter_map(|signal| match signal {
+                    Signal::Step((id, _)) => Some(id),
+                    _ => None,
+                })
+                .collect()
+        }
 
-            if self.num_steps == NUM_STEPS {
-                let avg_micros = self.duration_micros / NUM_STEPS;
-                println!("Loop duration {avg_micros}µs (average of {NUM_STEPS} steps)");
-                self.duration_micros = 0;
-                self.num_steps = 0;
-            }
+        /// Queue the given signal to be received by the scheduler
+        fn queue(&self, signal: Signal) {
+            self.received.lock().unwrap().push_back(signal);
+        }
+    }
+
+    impl ConnectScheduler for FakeConnector {
+        fn connect_remotes(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn sync_time(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_connected_agent_ids(&self) -> Vec<AgentId> {
+            Vec::new()
+        }
+
+        fn receive(&mut self, _timeout: feo_time::Duration) -> Result<Option<Signal>, Error> {
+            Ok(self.received.lock().unwrap().pop_front())
+        }
+
+        fn send_to_activity(&mut self, _activity_id: ActivityId, signal: &Signal) -> Result<(), Error> {
+            self.sent.lock().unwrap().push(*signal);
+            Ok(())
         }
+
+        fn broadcast_terminate(&mut self, _signal: &Signal) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// Create a scheduler of the given activities, returning it with its connector
+    fn scheduler(
+        activity_depends: HashMap<ActivityId, Vec<ActivityId>>,
+        activity_workers: HashMap<ActivityId, WorkerId>,
+        task_chains: TaskChains,
+        error_policies: ErrorPolicies,
+    ) -> (Scheduler, FakeConnector) {
+        crate::timestamp::initialize();
+        let connector = FakeConnector::default();
+        let scheduler = Scheduler::new(
+            AgentId::new(1),
+            feo_time::Duration::from_millis(10),
+            feo_time::Duration::from_millis(10),
+            feo_time::Duration::from_millis(10),
+            StartupOrder::new(),
+            activity_depends,
+            activity_workers,
+            Box::new(connector.clone()),
+            Arc::new(AtomicBool::new(false)),
+            StepDeadlines::default(),
+            task_chains,
+            error_policies,
+            AgentSupervisor::new(Supervision::default(), []),
+            None,
+            None,
+            None,
+            None,
+            None,
+            ActivityControl::new(),
+            CycleDebugger::new(),
+            ConfigReload::new(),
+            CycleTrigger::new(),
+            None,
+            None,
+        );
+        (scheduler, connector)
+    }
+
+    /// Create a scheduler of a main chain where activity 2 depends on activity 1,
+    /// applying the given error policy to activity 1
+    fn pipeline(policy: ErrorPolicy) -> (Scheduler, FakeConnector) {
+        use alloc::vec;
+
+        let depends = HashMap::from([
+            (ActivityId::new(1), vec![]),
+            (ActivityId::new(2), vec![ActivityId::new(1)]),
+        ]);
+        let policies = ErrorPolicies::default().with_policy(ActivityId::new(1), policy);
+        scheduler(depends, HashMap::new(), TaskChains::default(), policies)
+    }
+
+    #[test]
+    fn failed_steps_abort_by_default() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = pipeline(ErrorPolicy::Abort);
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        assert!(matches!(
+            scheduler.wait_next_ready(None),
+            Err(Error::ActivityFailed(_, ActivityError::Step))
+        ));
+    }
+
+    #[test]
+    fn failed_startups_abort_regardless_of_policy() {
+        let (mut scheduler, _connector) = pipeline(ErrorPolicy::Skip);
+        assert!(matches!(
+            scheduler.handle_failure(ActivityId::new(1), ActivityError::Startup),
+            Err(Error::ActivityFailed(_, ActivityError::Startup))
+        ));
+    }
+
+    #[test]
+    fn skipped_steps_continue_the_chain() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = pipeline(ErrorPolicy::Skip);
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(1)));
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(2)]);
+    }
+
+    #[test]
+    fn failed_steps_are_retried_up_to_the_limit() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = pipeline(ErrorPolicy::Retry(2));
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+
+        // Retries within the limit step the activity again until it succeeds
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        connector.queue(Signal::Ready((ActivityId::new(1), timestamp())));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(1)));
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1), ActivityId::new(1)]);
+
+        // The retries are counted per cycle
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+        for _ in 0..3 {
+            connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        }
+        assert!(matches!(
+            scheduler.wait_next_ready(None),
+            Err(Error::ActivityFailed(_, ActivityError::Step))
+        ));
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1), ActivityId::new(1)]);
+    }
+
+    #[test]
+    fn degraded_activities_are_no_longer_stepped() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = pipeline(ErrorPolicy::Degrade);
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(1)));
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1), ActivityId::new(2)]);
+
+        // Dependent activities are stepped as if the degraded activity had finished its steps
+        connector.queue(Signal::Ready((ActivityId::new(2), timestamp())));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(2)));
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(2)]);
+    }
+
+    #[test]
+    fn aborted_chains_skip_their_remaining_activities() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = pipeline(ErrorPolicy::AbortChain);
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        connector.queue(Signal::ActivityFailed((ActivityId::new(1), ActivityError::Step)));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(1)));
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+        assert!(scheduler.chain_ready(0));
+
+        // The next cycle steps all activities again
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(1)]);
+    }
+
+    /// Create a scheduler of a main chain with activities 1 and 2 and a diagnostics chain with activity 3,
+    /// all running on the same worker, giving the diagnostics chain the given priority
+    fn shared_worker(priority: u8) -> (Scheduler, FakeConnector) {
+        use alloc::vec;
+
+        let depends = HashMap::from([
+            (ActivityId::new(1), vec![]),
+            (ActivityId::new(2), vec![]),
+            (ActivityId::new(3), vec![]),
+        ]);
+        let workers = depends.keys().map(|id| (*id, WorkerId::new(1))).collect();
+        let diagnostics = ChainId::new(1);
+        let chains = TaskChains::default()
+            .with_chain(diagnostics, feo_time::Duration::from_millis(100), [ActivityId::new(3)])
+            .with_priority(diagnostics, priority);
+        scheduler(depends, workers, chains, ErrorPolicies::default())
+    }
+
+    #[test]
+    fn high_priority_steps_overtake_queued_steps() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = shared_worker(1);
+
+        // The worker gets one step at a time
+        scheduler.start_chain(0);
+        scheduler.step_ready_activities().unwrap();
+        let first = connector.take_steps();
+        assert_eq!(first.len(), 1);
+        scheduler.start_chain(1);
+        scheduler.step_ready_activities().unwrap();
+        assert!(connector.take_steps().is_empty());
+
+        // The step of the higher priority chain is sent before the queued one of the main chain
+        connector.queue(Signal::Ready((first[0], timestamp())));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(first[0]));
+        scheduler.step_ready_activities().unwrap();
+        assert_eq!(connector.take_steps(), vec![ActivityId::new(3)]);
+
+        connector.queue(Signal::Ready((ActivityId::new(3), timestamp())));
+        assert_eq!(scheduler.wait_next_ready(None).unwrap(), Some(ActivityId::new(3)));
+        scheduler.step_ready_activities().unwrap();
+        let second = connector.take_steps();
+        assert_eq!(second.len(), 1);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn equal_priorities_do_not_hold_steps() {
+        use alloc::vec;
+
+        let (mut scheduler, connector) = shared_worker(0);
+        scheduler.start_chain(0);
+        scheduler.start_chain(1);
+        scheduler.step_ready_activities().unwrap();
+        let mut steps = connector.take_steps();
+        steps.sort();
+        assert_eq!(steps, vec![ActivityId::new(1), ActivityId::new(2), ActivityId::new(3)]);
     }
 }
@@ -0,0 +1,189 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Forwarding of POSIX signals from the primary agent to the secondary agents
+//!
+//! The workers of each agent announce the process ID of their agent to the scheduler once the time
+//! is synchronized. With signals selected in the `forward_signals` option of the primary agent's
+//! configuration, these signals are passed on to all announced processes:
+//!
+//! - SIGUSR1 and SIGUSR2 are forwarded right away from the signal handler of the primary agent.
+//! - SIGTERM is forwarded after the shutdown sequence requested by SIGINT or SIGTERM, to the agents
+//!   which did not acknowledge the termination. A second termination signal, which exits the primary
+//!   agent immediately, is forwarded to all agents.
+//!
+//! Stopping the primary agent thus tears down the whole deployment, while the secondary agents still
+//! get the chance to shut down their activities gracefully.
+
+use crate::error::Error;
+use crate::ids::AgentId;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, warn};
+use std::io;
+
+/// Maximum number of agent processes signals are forwarded to
+pub const MAX_AGENTS: usize = 64;
+
+/// Signal forwarded to the secondary agents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ForwardedSignal {
+    /// SIGTERM, forwarded when the primary agent is terminated
+    Term,
+    /// SIGUSR1
+    Usr1,
+    /// SIGUSR2
+    Usr2,
+}
+
+impl ForwardedSignal {
+    /// Number of the signal
+    fn number(self) -> libc::c_int {
+        match self {
+            ForwardedSignal::Term => libc::SIGTERM,
+            ForwardedSignal::Usr1 => libc::SIGUSR1,
+            ForwardedSignal::Usr2 => libc::SIGUSR2,
+        }
+    }
+}
+
+/// Process of an agent, free while the process ID is zero
+struct AgentProcess {
+    agent: AtomicU64,
+    pid: AtomicI32,
+}
+
+/// Announced agent processes, read by the signal handler without locking
+static AGENTS: [AgentProcess; MAX_AGENTS] = [const {
+    AgentProcess {
+        agent: AtomicU64::new(0),
+        pid: AtomicI32::new(0),
+    }
+}; MAX_AGENTS];
+
+/// Whether termination signals are forwarded
+static FORWARD_TERM: AtomicBool = AtomicBool::new(false);
+
+/// Forward the given signals to the announced agent processes
+///
+/// Installs the handler of SIGUSR1 and SIGUSR2, if selected.
+pub(crate) fn enable(signals: &[ForwardedSignal]) -> Result<(), Error> {
+    for signal in signals {
+        if *signal == ForwardedSignal::Term {
+            // Handled together with SIGINT, see `register_sigterm_handler`
+            FORWARD_TERM.store(true, Ordering::Relaxed);
+            continue;
+        }
+
+        // Safety: The handler only reads atomics and calls `kill`, which is async-signal-safe
+        let result = unsafe {
+            let mut action: libc::sigaction = core::mem::zeroed();
+            action.sa_sigaction = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal.number(), &action, core::ptr::null_mut())
+        };
+        if result != 0 {
+            return Err(Error::Io((
+                ScoreDebugIoError(io::Error::last_os_error()),
+                "failed to install handler of forwarded signal",
+            )));
+        }
+        debug!("Forwarding {:?} to secondary agents", signal);
+    }
+    Ok(())
+}
+
+/// Note the process ID announced by the given agent, ignoring the own process
+pub(crate) fn register(agent: AgentId, pid: u32) {
+    if pid == std::process::id() {
+        return;
+    }
+    let agent = u64::from(agent);
+    let slot = AGENTS
+        .iter()
+        .find(|slot| slot.pid.load(Ordering::Acquire) != 0 && slot.agent.load(Ordering::Relaxed) == agent)
+        .or_else(|| AGENTS.iter().find(|slot| slot.pid.load(Ordering::Acquire) == 0));
+    match slot {
+        Some(slot) => {
+            slot.agent.store(agent, Ordering::Relaxed);
+            slot.pid.store(pid as i32, Ordering::Release);
+        },
+        None => warn!(
+            "Not forwarding signals to agent {}, more than {} agent processes",
+            agent, MAX_AGENTS
+        ),
+    }
+}
+
+/// Get the process IDs announced by the secondary agents
+pub fn agent_pids() -> Vec<(AgentId, u32)> {
+    AGENTS
+        .iter()
+        .filter_map(|slot| match slot.pid.load(Ordering::Acquire) {
+            0 => None,
+            pid => Some((AgentId::new(slot.agent.load(Ordering::Relaxed)), pid as u32)),
+        })
+        .collect()
+}
+
+/// Send SIGTERM to the processes of the given agents, if forwarding termination signals
+pub(crate) fn terminate_agents(agents: &BTreeSet<AgentId>) {
+    if !FORWARD_TERM.load(Ordering::Relaxed) {
+        return;
+    }
+    for (agent, pid) in agent_pids() {
+        if agents.contains(&agent) {
+            warn!("Sending SIGTERM to agent {} (PID {})", agent, pid);
+            // Safety: Plain libc call
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        }
+    }
+}
+
+/// Send SIGTERM to the processes of all agents, if forwarding termination signals
+pub(crate) fn terminate_all() {
+    if FORWARD_TERM.load(Ordering::Relaxed) {
+        forward(libc::SIGTERM);
+    }
+}
+
+/// Signal handler sending the received signal to all announced agent processes
+extern "C" fn forward(signal: libc::c_int) {
+    for slot in AGENTS.iter() {
+        let pid = slot.pid.load(Ordering::Acquire);
+        if pid != 0 {
+            // Safety: Plain libc call, async-signal-safe
+            unsafe { libc::kill(pid as libc::pid_t, signal) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announced_pids_are_registered_once_per_agent() {
+        let own_pid = std::process::id();
+        register(AgentId::new(100), own_pid);
+        register(AgentId::new(101), own_pid + 1);
+        register(AgentId::new(102), own_pid + 2);
+        register(AgentId::new(101), own_pid + 3);
+
+        let pids: Vec<_> = agent_pids().into_iter().filter(|(agent, _)| u64::from(*agent) >= 100).collect();
+        assert_eq!(pids, [(AgentId::new(101), own_pid + 3), (AgentId::new(102), own_pid + 2)]);
+    }
+}
@@ -13,6 +13,7 @@
 
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId};
+use crate::rejoin::AgentChange;
 use crate::signalling::common::signals::Signal;
 use alloc::vec::Vec;
 use feo_time::Duration;
@@ -40,6 +41,16 @@ pub(crate) trait ConnectScheduler {
     /// Broadcast termination `signal` to all connected agents
     fn broadcast_terminate(&mut self, signal: &Signal) -> Result<(), Error>;
 
+    /// Check whether lost agents may rejoin, see [rejoin](crate::rejoin)
+    fn accepts_rejoin(&self) -> bool {
+        false
+    }
+
+    /// Take the connection losses and rejoins of remote agents since the last call, see [rejoin](crate::rejoin)
+    fn take_agent_changes(&mut self) -> Vec<AgentChange> {
+        Vec::new()
+    }
+
     /// Take ownership of any background relay threads.
     /// The default implementation returns an empty Vec for connectors that don't have relays.
     fn take_relay_threads(&mut self) -> Vec<JoinHandle<()>> {
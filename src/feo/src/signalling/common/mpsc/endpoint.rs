@@ -14,7 +14,7 @@
 //! Basic components for mpsc channel signalling protocol
 
 use crate::debug_fmt::ScoreDebugHashSet;
-use crate::error::Error;
+use crate::error::{ConnectionError, Error};
 use crate::ids::ChannelId;
 use crate::signalling::common::mpsc::primitives::{channel, Receiver, Sender};
 use crate::signalling::common::signals::Signal;
@@ -34,7 +34,7 @@ impl ProtocolSender {
     }
 
     pub fn send(&mut self, t: ProtocolSignal) -> Result<(), Error> {
-        self.sender.send(t).map_err(|_| Error::Channel("channel closed"))
+        self.sender.send(t).map_err(|_| Error::Connection(ConnectionError::Channel("channel closed")))
     }
 
     pub fn connect_receiver(&mut self, _timeout: Duration) -> Result<(), Error> {
@@ -172,15 +172,15 @@ impl ProtocolMultiSender {
         let sender = self
             .senders
             .get(&channel_id)
-            .ok_or(Error::ChannelNotFound(channel_id))?;
-        sender.sender.send(signal).map_err(|_| Error::Channel("channel closed"))
+            .ok_or(Error::Connection(ConnectionError::ChannelNotFound(channel_id)))?;
+        sender.sender.send(signal).map_err(|_| Error::Connection(ConnectionError::Channel("channel closed")))
     }
     pub fn broadcast(&mut self, signal: ProtocolSignal) -> Result<(), Error> {
         for sender in self.senders.values() {
             sender
                 .sender
                 .send(signal)
-                .map_err(|_| Error::Channel("broadcast failed: a channel was closed"))?;
+                .map_err(|_| Error::Connection(ConnectionError::Channel("broadcast failed: a channel was closed")))?;
         }
         Ok(())
     }
@@ -13,7 +13,7 @@
 
 //! Primitive building blocks of mpsc channel signalling implementation
 
-use crate::error::Error;
+use crate::error::{ConnectionError, Error};
 use core::fmt;
 use feo_time::Duration;
 use std::sync::mpsc;
@@ -42,7 +42,7 @@ impl<T: fmt::Debug + 'static> Receiver<T> {
             Ok(v) => Ok(Some(v)),
             Err(err) => match err {
                 RecvTimeoutError::Timeout => Ok(None),
-                _ => Err(Error::ChannelClosed),
+                _ => Err(Error::Connection(ConnectionError::ChannelClosed)),
             },
         }
     }
@@ -59,7 +59,7 @@ impl<T: fmt::Debug + 'static> Sender<T> {
     }
 
     pub fn send(&mut self, t: T) -> Result<(), Error> {
-        self.sender.send(t).map_err(|_| Error::ChannelClosed)?;
+        self.sender.send(t).map_err(|_| Error::Connection(ConnectionError::ChannelClosed))?;
         Ok(())
     }
 }
@@ -11,7 +11,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::error::Error;
+use crate::error::{Error, SignallingError};
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::common::mpsc::endpoint::{ProtocolReceiver, ProtocolSender, ProtocolSignal};
 use crate::signalling::common::signals::Signal;
@@ -34,7 +34,7 @@ impl WorkerConnector {
         }
         match protocol_signal.unwrap() {
             ProtocolSignal::Core(signal) => Ok(Some(signal)),
-            _ => Err(Error::UnexpectedProtocolSignal),
+            _ => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
         }
     }
 
@@ -14,7 +14,9 @@
 //! Signals
 
 use crate::error::ActivityError;
-use crate::ids::{ActivityId, AgentId};
+use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::log_relay::LogLine;
+use crate::panic_report::PanicReport;
 use crate::timestamp::{SyncInfo, Timestamp};
 use core::fmt::Display;
 use score_log::ScoreDebug;
@@ -35,6 +37,10 @@ pub enum Signal {
     // Signal sent by the scheduler on the primary agent to trigger an activity's step method
     Step((ActivityId, Timestamp)),
 
+    // Signal sent by the scheduler on the primary agent right before a step of the first activity
+    // to inform it about the skipped step of the second activity it depends on
+    StaleInput((ActivityId, ActivityId)),
+
     // Signal sent to indicate that a previously triggered activity method has finished
     Ready((ActivityId, Timestamp)),
 
@@ -46,6 +52,24 @@ pub enum Signal {
 
     // Signal sent by a worker to acknowledge termination
     TerminateAck(AgentId),
+
+    // Signal sent periodically by the workers of secondary agents to the primary agent for supervision
+    Heartbeat(AgentId),
+
+    // Signal sent by the workers of secondary agents to relay a log record to the primary agent
+    Log(LogLine),
+
+    // Signal sent by the workers of each agent after the time synchronization to announce the process ID of the agent
+    AgentPid((AgentId, u32)),
+
+    // Signal sent by the workers to report the bytes retained by a step of an activity, see [memory](crate::memory)
+    MemoryRetained((ActivityId, i64)),
+
+    // Signal sent by the workers at the end of each of their cycles to report their utilization in percent
+    WorkerUtilization((WorkerId, u8)),
+
+    // Signal sent by the workers before the failure of a panicked activity, see [panic_report](crate::panic_report)
+    ActivityPanicked(PanicReport),
 }
 
 impl Display for Signal {
@@ -55,10 +79,17 @@ impl Display for Signal {
             Signal::Startup((id, t)) => write!(f, "Startup({id}, {t:?})"),
             Signal::Shutdown((id, t)) => write!(f, "Shutdown({id}, {t:?})"),
             Signal::Step((id, t)) => write!(f, "Step({id}, {t:?})"),
+            Signal::StaleInput((id, producer)) => write!(f, "StaleInput({id}, {producer})"),
             Signal::Ready((id, t)) => write!(f, "Ready({id}, {t:?})"),
             Signal::ActivityFailed((id, err)) => write!(f, "ActivityFailed({id}, {err:?})"),
             Signal::Terminate(t) => write!(f, "Terminate({t:?})"),
             Signal::TerminateAck(id) => write!(f, "TerminateAck({id})"),
+            Signal::Heartbeat(id) => write!(f, "Heartbeat({id})"),
+            Signal::Log(line) => write!(f, "Log({line})"),
+            Signal::AgentPid((id, pid)) => write!(f, "AgentPid({id}, {pid})"),
+            Signal::MemoryRetained((id, bytes)) => write!(f, "MemoryRetained({id}, {bytes})"),
+            Signal::WorkerUtilization((id, percent)) => write!(f, "WorkerUtilization({id}, {percent})"),
+            Signal::ActivityPanicked(report) => write!(f, "ActivityPanicked({report})"),
         }
     }
 }
@@ -11,18 +11,20 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::error::Error;
+use crate::agent::ConnectionPolicy;
+use crate::error::{ConnectionError, Error};
 use crate::signalling::common::socket::connection::Connection;
-use crate::signalling::common::socket::{FdExt, ProtocolSignal};
+use crate::signalling::common::socket::{FdExt, ProtocolSignal, PROTOCOL_VERSION};
 use alloc::format;
+use core::iter;
 use core::net::SocketAddr;
 use feo_time::Duration;
 use feo_tracing::ScoreDebugIoError;
 use mio::net::{TcpStream, UnixStream};
 use mio::{Events, Interest, Poll, Token};
-use score_log::{info, trace};
+use score_log::{error, info, trace};
+use std::io;
 use std::path::Path;
-use std::{io, thread};
 
 /// Token of the client stream
 const CLIENT_TOKEN: Token = Token(0);
@@ -45,21 +47,22 @@ where
 }
 
 impl SocketClient<TcpStream> {
-    /// Connect to the scheduler on `address`, announcing ourselves with `connect_signals`
-    pub(crate) fn connect(connect_signals: impl IntoIterator<Item = ProtocolSignal>, address: SocketAddr) -> Self {
-        let stream = loop {
-            if let Ok(stream) = std::net::TcpStream::connect(address) {
-                <std::net::TcpStream as FdExt>::set_nonblocking(&stream).expect("failed to set stream non-blocking");
-                break TcpStream::from_std(stream);
-            }
-
-            thread::sleep(Duration::from_millis(300).into());
-        };
+    /// Connect to the scheduler on `address`, announcing our protocol version and ourselves with `connect_signals`
+    ///
+    /// Connection attempts are retried according to `policy`.
+    pub(crate) fn connect(
+        connect_signals: impl IntoIterator<Item = ProtocolSignal>,
+        address: SocketAddr,
+        policy: &ConnectionPolicy,
+    ) -> Result<Self, Error> {
+        let stream = policy.connect(|| std::net::TcpStream::connect(address))?;
+        <std::net::TcpStream as FdExt>::set_nonblocking(&stream).expect("failed to set stream non-blocking");
+        let stream = TcpStream::from_std(stream);
         info!("Successfully connected to {}", format!("{address:?}"));
         stream.set_nodelay(true).unwrap();
         let mut connection = Connection::<TcpStream, ProtocolSignal>::new(stream);
 
-        for signal in connect_signals {
+        for signal in iter::once(ProtocolSignal::Version(PROTOCOL_VERSION)).chain(connect_signals) {
             connection.send(&signal).unwrap();
             trace!("Sent message {:?}", signal);
         }
@@ -69,24 +72,24 @@ impl SocketClient<TcpStream> {
             .register(connection.stream(), CLIENT_TOKEN, Interest::READABLE)
             .unwrap();
 
-        Self { poll, connection }
+        Ok(Self { poll, connection })
     }
 }
 
 impl SocketClient<UnixStream> {
-    /// Connect to the scheduler on `path`, announcing ourselves with `connect_signals`
-    pub(crate) fn connect(connect_signals: impl IntoIterator<Item = ProtocolSignal>, path: &Path) -> Self {
-        let stream = loop {
-            if let Ok(stream) = UnixStream::connect(path) {
-                break stream;
-            }
-
-            thread::sleep(Duration::from_millis(300).into());
-        };
+    /// Connect to the scheduler on `path`, announcing our protocol version and ourselves with `connect_signals`
+    ///
+    /// Connection attempts are retried according to `policy`.
+    pub(crate) fn connect(
+        connect_signals: impl IntoIterator<Item = ProtocolSignal>,
+        path: &Path,
+        policy: &ConnectionPolicy,
+    ) -> Result<Self, Error> {
+        let stream = policy.connect(|| UnixStream::connect(path))?;
         info!("Successfully connected to {:?}", path.to_str().expect("invalid path"));
         let mut connection = Connection::<UnixStream, ProtocolSignal>::new(stream);
 
-        for signal in connect_signals {
+        for signal in iter::once(ProtocolSignal::Version(PROTOCOL_VERSION)).chain(connect_signals) {
             connection.send(&signal).unwrap();
             trace!("Sent message {:?}", signal);
         }
@@ -96,7 +99,7 @@ impl SocketClient<UnixStream> {
             .register(connection.stream(), CLIENT_TOKEN, Interest::READABLE)
             .unwrap();
 
-        Self { poll, connection }
+        Ok(Self { poll, connection })
     }
 }
 
@@ -111,7 +114,11 @@ where
     pub(crate) fn receive(&mut self, events: &mut Events, timeout: Duration) -> Result<Option<ProtocolSignal>, Error> {
         if self.connection.is_readable() {
             match self.connection.read() {
-                Ok(Some(msg)) => return Ok(Some(msg)),
+                Ok(Some(msg)) => {
+                    if let Some(msg) = check_version(msg)? {
+                        return Ok(Some(msg));
+                    }
+                },
                 Ok(None) => {}, // Not enough data yet, proceed to poll
                 Err(e) => return Err(e.into()),
             }
@@ -127,7 +134,7 @@ where
 
         if self.connection.is_readable() {
             match self.connection.read() {
-                Ok(Some(msg)) => return Ok(Some(msg)),
+                Ok(Some(msg)) => return check_version(msg),
                 Ok(None) => {}, // Not enough data yet
                 Err(e) => return Err(e.into()),
             }
@@ -143,3 +150,18 @@ where
             .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to send on connection")))
     }
 }
+
+/// Check the protocol version acknowledged by the server, passing on any other signal
+fn check_version(msg: ProtocolSignal) -> Result<Option<ProtocolSignal>, Error> {
+    match msg {
+        ProtocolSignal::Version(version) if version != PROTOCOL_VERSION => {
+            error!(
+                "Server speaks signalling protocol version {}, expected version {}",
+                version, PROTOCOL_VERSION
+            );
+            Err(Error::Connection(ConnectionError::ProtocolVersion(PROTOCOL_VERSION, Some(version))))
+        },
+        ProtocolSignal::Version(_) => Ok(None),
+        msg => Ok(Some(msg)),
+    }
+}
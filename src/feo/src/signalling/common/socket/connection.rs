@@ -11,15 +11,32 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
+//! Framing of messages on a stream
+//!
+//! Messages are sent as frames of at most [BUFFER_SIZE] bytes. Larger messages, e.g. relayed log lines, are
+//! split transparently into chunk frames, see [EncodeDecode::CHUNK_TAG], and reassembled by the receiving
+//! connection before they are decoded. Messages are limited to [MAX_MESSAGE_SIZE] bytes, a peer sending
+//! larger chunked messages fails the connection.
+
 use crate::signalling::common::socket::EncodeDecode;
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use mio::net::{TcpStream, UnixStream};
 use score_log::trace;
-use std::io::{self, Cursor};
+use std::io;
 
-/// Size of the buffer within a connection
+/// Size of the buffer within a connection, i.e. the maximum size of a frame
 const BUFFER_SIZE: usize = 128;
 
+/// Maximum size of an encoded message, larger messages are neither sent nor reassembled from chunks
+const MAX_MESSAGE_SIZE: usize = 4096;
+
+/// Size of the header of a chunk frame: type and length
+const CHUNK_HEADER_SIZE: usize = 2;
+
+/// Maximum number of bytes of a message carried by a chunk frame
+const CHUNK_DATA_SIZE: usize = BUFFER_SIZE - CHUNK_HEADER_SIZE;
+
 /// Wrapper around a stream to facilitate reading messages
 pub(crate) struct Connection<S, M>
 where
@@ -35,7 +52,9 @@ where
     /// Index until which we have written received data
     recv_end: usize,
     /// Buffer to use during sending
-    send_buffer: [u8; BUFFER_SIZE],
+    send_buffer: Vec<u8>,
+    /// Chunks of a message received so far
+    chunks: Vec<u8>,
     /// Flag whether the stream might be readable
     stream_readable: bool,
     /// Flag whether the buffer might contain a parsable message
@@ -52,7 +71,8 @@ impl<M: EncodeDecode> Connection<TcpStream, M> {
             recv_buffer: [0; BUFFER_SIZE],
             recv_begin: 0,
             recv_end: 0,
-            send_buffer: [0; BUFFER_SIZE],
+            send_buffer: Vec::new(),
+            chunks: Vec::new(),
             stream_readable: false,
             buffer_readable: false,
             _message: PhantomData,
@@ -68,7 +88,8 @@ impl<M: EncodeDecode> Connection<UnixStream, M> {
             recv_buffer: [0; BUFFER_SIZE],
             recv_begin: 0,
             recv_end: 0,
-            send_buffer: [0; BUFFER_SIZE],
+            send_buffer: Vec::new(),
+            chunks: Vec::new(),
             stream_readable: false,
             buffer_readable: false,
             _message: PhantomData,
@@ -82,36 +103,41 @@ where
     M: EncodeDecode,
 {
     /// Try to read from this connection
+    ///
+    /// Reads until a complete message has been received or the stream would block, as the chunks of
+    /// a large message may not fit into the buffer at once.
     pub(crate) fn read(&mut self) -> io::Result<Option<M>> {
-        if self.buffer_readable {
-            if let Some(msg) = self.parse_from_buffer() {
-                return Ok(Some(msg));
+        loop {
+            if self.buffer_readable {
+                if let Some(msg) = self.parse_from_buffer()? {
+                    return Ok(Some(msg));
+                }
             }
-        }
-
-        if self.stream_readable {
-            self.read_from_stream()?;
-        }
 
-        if self.buffer_readable {
-            if let Some(msg) = self.parse_from_buffer() {
-                return Ok(Some(msg));
+            if !self.stream_readable {
+                return Ok(None);
             }
+            self.read_from_stream()?;
         }
-
-        Ok(None)
     }
 
     /// Send a [Message] through the stream
     pub(crate) fn send(&mut self, msg: &M) -> io::Result<()> {
         // Encode message to buffer.
         // Buffered writes reduce the signalling overhead by 33% through less fragmentation.
-        let mut writer = Cursor::new(&mut self.send_buffer[..]);
-        msg.encode(&mut writer)?;
-        let end_idx = writer.position() as usize;
+        self.send_buffer.clear();
+        msg.encode(&mut self.send_buffer)?;
+        let end_idx = self.send_buffer.len();
+        if end_idx > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "message exceeds the maximum size"));
+        }
 
-        // Write the encoded data to the stream.
-        self.stream.write_all(&self.send_buffer[..end_idx])?;
+        // Write the encoded data to the stream, split into chunks if it does not fit into a frame.
+        if end_idx <= BUFFER_SIZE {
+            self.stream.write_all(&self.send_buffer[..end_idx])?;
+        } else {
+            self.stream.write_all(&chunk_frames::<M>(&self.send_buffer[..end_idx]))?;
+        }
         self.stream.flush()?;
 
         Ok(())
@@ -164,30 +190,71 @@ where
     }
 
     /// Try to parse a message from the buffer
-    fn parse_from_buffer(&mut self) -> Option<M> {
-        match M::try_decode(&self.recv_buffer[self.recv_begin..self.recv_end]) {
-            Some((msg, consumed_bytes)) => {
-                self.recv_begin += consumed_bytes;
-
-                // TODO: Optimize
-                // We can pass two const generics:
-                // - BUFFER_SIZE
-                // - MAX_MSG_SIZE
-                // Then we can update the logic to shift the buffer
-                // only when there is less than one maximum message space left.
-                if self.recv_begin > BUFFER_SIZE / 2 {
-                    self.shift_buffer();
-                }
+    ///
+    /// Fails if the chunks received so far do not form a valid message of at most [MAX_MESSAGE_SIZE] bytes.
+    fn parse_from_buffer(&mut self) -> io::Result<Option<M>> {
+        loop {
+            let src = &self.recv_buffer[self.recv_begin..self.recv_end];
+            if src.first() != Some(&M::CHUNK_TAG) {
+                break;
+            }
+            // Append the data of a complete chunk frame to the chunks received so far
+            let chunk = src
+                .get(1)
+                .and_then(|length| src.get(CHUNK_HEADER_SIZE..CHUNK_HEADER_SIZE + *length as usize));
+            let Some(chunk) = chunk else {
+                return Ok(self.await_frame());
+            };
+            let length = chunk.len();
+            self.chunks.extend_from_slice(chunk);
+            self.consume(CHUNK_HEADER_SIZE + length);
+            if self.chunks.first() == Some(&M::CHUNK_TAG) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "chunk frame within a chunked message"));
+            }
+            if let Some((msg, _)) = M::try_decode(&self.chunks)? {
+                trace!("Reassembled message of {} bytes", self.chunks.len());
+                self.chunks.clear();
+                return Ok(Some(msg));
+            }
+            if self.chunks.len() >= MAX_MESSAGE_SIZE {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "chunked message exceeds the maximum size"));
+            }
+        }
 
-                Some(msg)
-            },
-            None => {
-                self.buffer_readable = false;
-                None
+        match M::try_decode(&self.recv_buffer[self.recv_begin..self.recv_end])? {
+            Some((msg, consumed_bytes)) => {
+                self.consume(consumed_bytes);
+                Ok(Some(msg))
             },
+            None => Ok(self.await_frame()),
         }
     }
 
+    /// Mark the given number of bytes at the beginning of the buffer as parsed
+    fn consume(&mut self, bytes: usize) {
+        self.recv_begin += bytes;
+
+        // TODO: Optimize
+        // We can pass two const generics:
+        // - BUFFER_SIZE
+        // - MAX_MSG_SIZE
+        // Then we can update the logic to shift the buffer
+        // only when there is less than one maximum message space left.
+        if self.recv_begin > BUFFER_SIZE / 2 {
+            self.shift_buffer();
+        }
+    }
+
+    /// Wait for the rest of an incomplete frame at the beginning of the buffer to be received
+    fn await_frame(&mut self) -> Option<M> {
+        self.buffer_readable = false;
+        // Make room for the rest of the frame
+        if self.recv_end == BUFFER_SIZE && self.recv_begin > 0 {
+            self.shift_buffer();
+        }
+        None
+    }
+
     /// Copy the remaining, not yet parsed content to the beginning of the buffer, updating indices
     fn shift_buffer(&mut self) {
         trace!("Shifting buffer");
@@ -202,3 +269,14 @@ where
         self.recv_begin = 0;
     }
 }
+
+/// Split an encoded message into chunk frames
+fn chunk_frames<M: EncodeDecode>(message: &[u8]) -> Vec<u8> {
+    let mut frames = Vec::with_capacity(message.len() + message.len().div_ceil(CHUNK_DATA_SIZE) * CHUNK_HEADER_SIZE);
+    for chunk in message.chunks(CHUNK_DATA_SIZE) {
+        frames.push(M::CHUNK_TAG);
+        frames.push(chunk.len() as u8);
+        frames.extend_from_slice(chunk);
+    }
+    frames
+}
@@ -15,8 +15,12 @@
 
 use crate::error::ActivityError;
 use crate::ids::{ActivityId, AgentId, ChannelId, RelayId, WorkerId};
+use crate::log_relay::{LogLine, LOG_LINE_CAPACITY};
+use crate::panic_report::{PanicReport, PANIC_BACKTRACE_CAPACITY, PANIC_MESSAGE_CAPACITY};
 use crate::signalling::common::signals::Signal;
 use crate::timestamp::{SyncInfo, Timestamp};
+use alloc::format;
+use core::time::Duration;
 use score_log::ScoreDebug;
 use std::io::{self, Write};
 use std::os::fd::AsRawFd;
@@ -25,15 +29,44 @@ pub(crate) mod client;
 pub(crate) mod connection;
 pub(crate) mod server;
 
+/// Version of the signalling protocol
+///
+/// Clients announce their version in the first message of each connection, servers acknowledge it with theirs.
+/// Bump the version on every incompatible change of the encoding below.
+pub(crate) const PROTOCOL_VERSION: u16 = 4;
+
+/// Maximum size of an encoded signal, i.e. of a log line or a panic report of full capacity
+///
+/// Backends exchanging signals in fixed-size slots instead of a stream, like shared memory or QNX channels,
+/// size their slots accordingly.
+pub(crate) const MAX_SIGNAL_SIZE: usize = if LOG_SIGNAL_SIZE > PANIC_SIGNAL_SIZE {
+    LOG_SIGNAL_SIZE
+} else {
+    PANIC_SIGNAL_SIZE
+};
+
+/// Maximum size of an encoded log line
+const LOG_SIGNAL_SIZE: usize = 2 + 8 + 8 + 1 + LOG_LINE_CAPACITY;
+
+/// Maximum size of an encoded panic report
+const PANIC_SIGNAL_SIZE: usize = 2 + 8 + 1 + PANIC_MESSAGE_CAPACITY + PANIC_BACKTRACE_CAPACITY;
+
 /// Trait providing encoding and decoding methods
 ///
 /// This is used as a bound on the [connection::Connection] primitive.
 pub(crate) trait EncodeDecode: Sized {
+    /// Type ID of the frames carrying the chunks of messages too large for a single frame,
+    /// see [connection::Connection]
+    const CHUNK_TAG: u8;
+
     /// Encode type to the writer
     fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>;
 
-    /// Try to decode type from the source
-    fn try_decode(src: &[u8]) -> Option<(Self, usize)>;
+    /// Try to decode type from the source, returning the number of bytes consumed
+    ///
+    /// Returns `None` if the source does not hold a complete message yet and fails on invalid data,
+    /// as received from a faulty peer.
+    fn try_decode(src: &[u8]) -> io::Result<Option<(Self, usize)>>;
 }
 
 /// Trait extending methods available on file descriptors
@@ -81,6 +114,48 @@ pub(crate) enum ProtocolSignal {
     ActivityHello(ActivityId),
     /// Hello signal announcing the presence of a generic peer with its [ChannelId]
     ChannelHello(ChannelId),
+    /// Protocol version of the sender, the first signal on each connection
+    Version(u16),
+}
+
+/// Value of the length byte announcing an extended length, see [write_header]
+const EXTENDED_LENGTH: u8 = u8::MAX;
+
+/// Write the header of a signal of the given type with the given length of its data
+///
+/// Lengths below [EXTENDED_LENGTH] are written as a single byte. Longer data is announced by
+/// [EXTENDED_LENGTH], followed by the length as `u16`.
+fn write_header<W: Write>(w: &mut W, tag: SignalTag, length: usize) -> io::Result<()> {
+    match u8::try_from(length) {
+        Ok(length) if length < EXTENDED_LENGTH => w.write_all(&[tag as u8, length]),
+        _ => {
+            let length = u16::try_from(length)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "signal data exceeds the maximum length"))?;
+            w.write_all(&[tag as u8, EXTENDED_LENGTH])?;
+            w.write_all(&length.to_le_bytes())
+        },
+    }
+}
+
+/// Read the type, the length of the data and the size of the header of a signal, if the header is complete
+fn read_header(src: &[u8]) -> Option<(u8, usize, usize)> {
+    match *src {
+        [type_id, EXTENDED_LENGTH, low, high, ..] => Some((type_id, u16::from_le_bytes([low, high]) as usize, 4)),
+        [_, EXTENDED_LENGTH, ..] => None,
+        [type_id, length, ..] => Some((type_id, length as usize, 2)),
+        _ => None,
+    }
+}
+
+/// Read a little endian `u64` at the given offset, if available
+fn read_u64(src: &[u8], offset: usize) -> Option<u64> {
+    let bytes = src.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Error on invalid signal data received from a peer
+fn invalid_data(description: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, description)
 }
 
 /// Encode signal data to a writer
@@ -108,7 +183,10 @@ macro_rules! decode_data {
     ($src:expr; $( $variant:expr ),+; $from:ty => $to:ty) => {{
         // Extract value
         const LENGTH: usize = core::mem::size_of::<$from>();
-        let data: [u8; LENGTH] = (&$src[..LENGTH]).try_into().unwrap();
+        let data: [u8; LENGTH] = $src
+            .get(..LENGTH)
+            .and_then(|data| data.try_into().ok())
+            .ok_or_else(|| invalid_data("truncated signal"))?;
         let value: $to = <$from>::from_le_bytes(data).into();
 
         // Calculate the number of consumed bytes
@@ -120,14 +198,17 @@ macro_rules! decode_data {
             let connector_signal = $variant(connector_signal);
         )+
 
-        Some((connector_signal, consumed_bytes))
+        Ok(Some((connector_signal, consumed_bytes)))
     }};
     // Variant with a wrapped tuple
     ($src:expr; $( $variant:expr ),+; $from1:ty => $to1:ty; $from2:ty => $to2:ty) => {{
         // Extract data for both values
         const LENGTH1: usize = core::mem::size_of::<$from1>();
         const LENGTH2: usize = core::mem::size_of::<$from2>();
-        let data: [u8; LENGTH1 + LENGTH2] = (&$src[..(LENGTH1 + LENGTH2)]).try_into().unwrap();
+        let data: [u8; LENGTH1 + LENGTH2] = $src
+            .get(..(LENGTH1 + LENGTH2))
+            .and_then(|data| data.try_into().ok())
+            .ok_or_else(|| invalid_data("truncated signal"))?;
 
         // Extract first value
         let data1: [u8; LENGTH1] = data[0..LENGTH1].try_into().unwrap();
@@ -146,7 +227,7 @@ macro_rules! decode_data {
             let connector_signal = $variant(connector_signal);
         )+
 
-        Some((connector_signal, consumed_bytes))
+        Ok(Some((connector_signal, consumed_bytes)))
     }};
 }
 
@@ -154,10 +235,15 @@ impl EncodeDecode for ProtocolSignal {
     // Protocol definition for this implementation
     //
     // - 1 byte type
-    // - 1 byte length
+    // - 1 byte length, or 0xFF followed by the length as 2 bytes for data of 255 bytes and more
     // - x bytes data
     //
     // [  type  | length | data .. data .. data ]
+    //
+    // Signals larger than a frame of the connection are split into chunk frames of the same layout,
+    // whose data concatenated is the encoded signal.
+
+    const CHUNK_TAG: u8 = SignalTag::ConnectorChunk as u8;
 
     fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
         match self {
@@ -173,6 +259,9 @@ impl EncodeDecode for ProtocolSignal {
             ProtocolSignal::Core(Signal::Step((activity_id, timestamp))) => {
                 encode_data!(w; SignalTag::CoreStep; activity_id => u64, timestamp => u128);
             },
+            ProtocolSignal::Core(Signal::StaleInput((activity_id, producer))) => {
+                encode_data!(w; SignalTag::CoreStaleInput; activity_id => u64, producer => u64);
+            },
             ProtocolSignal::Core(Signal::Shutdown((activity_id, timestamp))) => {
                 encode_data!(w; SignalTag::CoreShutdown; activity_id => u64, timestamp => u128);
             },
@@ -188,6 +277,37 @@ impl EncodeDecode for ProtocolSignal {
             ProtocolSignal::Core(Signal::TerminateAck(agent_id)) => {
                 encode_data!(w; SignalTag::CoreTerminateAck; agent_id => u64);
             },
+            ProtocolSignal::Core(Signal::Heartbeat(agent_id)) => {
+                encode_data!(w; SignalTag::CoreHeartbeat; agent_id => u64);
+            },
+            ProtocolSignal::Core(Signal::Log(line)) => {
+                // Variable length: agent, timestamp in nanoseconds, level and the text
+                let text = line.text().as_bytes();
+                write_header(w, SignalTag::CoreLog, 8 + 8 + 1 + text.len())?;
+                w.write_all(&u64::from(line.agent).to_le_bytes())?;
+                w.write_all(&(line.timestamp.as_nanos() as u64).to_le_bytes())?;
+                w.write_all(&[line.level_u8()])?;
+                w.write_all(text)?;
+            },
+            ProtocolSignal::Core(Signal::AgentPid((agent_id, pid))) => {
+                encode_data!(w; SignalTag::CoreAgentPid; agent_id => u64, *pid => u32);
+            },
+            ProtocolSignal::Core(Signal::MemoryRetained((activity_id, bytes))) => {
+                encode_data!(w; SignalTag::CoreMemoryRetained; activity_id => u64, *bytes => i64);
+            },
+            ProtocolSignal::Core(Signal::WorkerUtilization((worker_id, percent))) => {
+                encode_data!(w; SignalTag::CoreWorkerUtilization; worker_id => u64, *percent => u8);
+            },
+            ProtocolSignal::Core(Signal::ActivityPanicked(report)) => {
+                // Variable length: activity, length of the message, message and backtrace
+                let message = report.message().as_bytes();
+                let backtrace = report.backtrace().as_bytes();
+                write_header(w, SignalTag::CoreActivityPanicked, 8 + 1 + message.len() + backtrace.len())?;
+                w.write_all(&u64::from(report.activity).to_le_bytes())?;
+                w.write_all(&[message.len() as u8])?;
+                w.write_all(message)?;
+                w.write_all(backtrace)?;
+            },
 
             // Signalling-layer signals
             ProtocolSignal::ActivityHello(worker_id) => {
@@ -207,6 +327,9 @@ impl EncodeDecode for ProtocolSignal {
                     encode_data!(w; SignalTag::ConnectorChannelRelayHello; id => u64)
                 },
             },
+            ProtocolSignal::Version(version) => {
+                encode_data!(w; SignalTag::ConnectorVersion; *version => u16);
+            },
         }
 
         w.flush()?;
@@ -214,26 +337,25 @@ impl EncodeDecode for ProtocolSignal {
         Ok(())
     }
 
-    fn try_decode(src: &[u8]) -> Option<(Self, usize)> {
-        // Return early if we do not have enough data for the protocol header
-        if src.len() < 2 {
-            return None;
-        }
-
-        // Extract protocol header
-        let type_id = u8::from_le_bytes([src[0]]);
-        let length = u8::from_le_bytes([src[1]]) as usize;
+    fn try_decode(src: &[u8]) -> io::Result<Option<(Self, usize)>> {
+        // Extract protocol header, returning early if we do not have enough data for it
+        let Some((type_id, length, header_size)) = read_header(src) else {
+            return Ok(None);
+        };
 
         // Return early if the full data as specified by the header (length) is not available
-        if src.len() < (2 + length) {
-            return None;
+        if src.len() < (header_size + length) {
+            return Ok(None);
         }
 
         // Shorten `src` to `length` to prevent overreads below
-        let src = &src[2..(2 + length)];
+        let src = &src[header_size..(header_size + length)];
 
         let Ok(signal_tag) = type_id.try_into() else {
-            panic!("failed to parse unknown type ID {type_id}");
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to parse unknown type ID {type_id}"),
+            ));
         };
 
         use SignalTag::*;
@@ -250,6 +372,9 @@ impl EncodeDecode for ProtocolSignal {
             CoreStep => {
                 decode_data!(src; Signal::Step, ProtocolSignal::Core; u64 => ActivityId; u128 => Timestamp)
             },
+            CoreStaleInput => {
+                decode_data!(src; Signal::StaleInput, ProtocolSignal::Core; u64 => ActivityId; u64 => ActivityId)
+            },
             CoreShutdown => {
                 decode_data!(src; Signal::Shutdown, ProtocolSignal::Core; u64 => ActivityId; u128 => Timestamp)
             },
@@ -265,6 +390,36 @@ impl EncodeDecode for ProtocolSignal {
             CoreTerminateAck => {
                 decode_data!(src; Signal::TerminateAck, ProtocolSignal::Core; u64 => AgentId)
             },
+            CoreHeartbeat => {
+                decode_data!(src; Signal::Heartbeat, ProtocolSignal::Core; u64 => AgentId)
+            },
+            CoreLog => {
+                let line = match (read_u64(src, 0), read_u64(src, 8), src.get(16), src.get(17..)) {
+                    (Some(agent), Some(nanos), Some(level), Some(text)) => {
+                        LogLine::from_parts(agent.into(), Duration::from_nanos(nanos), *level, text)
+                    },
+                    _ => None,
+                };
+                let line = line.ok_or_else(|| invalid_data("failed to parse invalid log line"))?;
+                Ok(Some((ProtocolSignal::Core(Signal::Log(line)), header_size + length)))
+            },
+            CoreAgentPid => {
+                decode_data!(src; Signal::AgentPid, ProtocolSignal::Core; u64 => AgentId; u32 => u32)
+            },
+            CoreMemoryRetained => {
+                decode_data!(src; Signal::MemoryRetained, ProtocolSignal::Core; u64 => ActivityId; i64 => i64)
+            },
+            CoreWorkerUtilization => {
+                decode_data!(src; Signal::WorkerUtilization, ProtocolSignal::Core; u64 => WorkerId; u8 => u8)
+            },
+            CoreActivityPanicked => {
+                let report = read_u64(src, 0).zip(src.get(8)).and_then(|(activity, message_len)| {
+                    let message_end = 9 + *message_len as usize;
+                    PanicReport::from_parts(activity.into(), src.get(9..message_end)?, src.get(message_end..)?)
+                });
+                let report = report.ok_or_else(|| invalid_data("failed to parse invalid panic report"))?;
+                Ok(Some((ProtocolSignal::Core(Signal::ActivityPanicked(report)), header_size + length)))
+            },
 
             // Signalling-layer signals
             ConnectorActivityHello => {
@@ -282,6 +437,11 @@ impl EncodeDecode for ProtocolSignal {
             ConnectorChannelRelayHello => {
                 decode_data!(src; ChannelId::Relay, ProtocolSignal::ChannelHello; u64 => RelayId)
             },
+            ConnectorVersion => {
+                decode_data!(src; ProtocolSignal::Version; u16 => u16)
+            },
+            // Chunk frames are reassembled by the connection, they never carry a signal of their own
+            ConnectorChunk => Err(invalid_data("failed to parse chunk frame outside of a connection")),
         }
     }
 }
@@ -318,11 +478,20 @@ pub(crate) enum SignalTag {
     CoreActivityFailed = 27,
     CoreTerminate = 25,
     CoreTerminateAck = 26,
+    CoreHeartbeat = 28,
+    CoreLog = 29,
+    CoreStaleInput = 30,
+    CoreAgentPid = 37,
+    CoreMemoryRetained = 38,
+    CoreWorkerUtilization = 39,
+    CoreActivityPanicked = 41,
     ConnectorActivityHello = 31,
     ConnectorChannelActivityHello = 33,
     ConnectorChannelWorkerHello = 34,
     ConnectorChannelAgentHello = 35,
     ConnectorChannelRelayHello = 36,
+    ConnectorVersion = 40,
+    ConnectorChunk = 42,
 }
 
 impl TryFrom<u8> for SignalTag {
@@ -340,40 +509,280 @@ impl TryFrom<u8> for SignalTag {
             v if v == CoreActivityFailed as u8 => Ok(CoreActivityFailed),
             v if v == CoreTerminate as u8 => Ok(CoreTerminate),
             v if v == CoreTerminateAck as u8 => Ok(CoreTerminateAck),
+            v if v == CoreHeartbeat as u8 => Ok(CoreHeartbeat),
+            v if v == CoreLog as u8 => Ok(CoreLog),
+            v if v == CoreStaleInput as u8 => Ok(CoreStaleInput),
+            v if v == CoreAgentPid as u8 => Ok(CoreAgentPid),
+            v if v == CoreMemoryRetained as u8 => Ok(CoreMemoryRetained),
+            v if v == CoreWorkerUtilization as u8 => Ok(CoreWorkerUtilization),
+            v if v == CoreActivityPanicked as u8 => Ok(CoreActivityPanicked),
             v if v == ConnectorActivityHello as u8 => Ok(ConnectorActivityHello),
             v if v == ConnectorChannelActivityHello as u8 => Ok(ConnectorChannelActivityHello),
             v if v == ConnectorChannelWorkerHello as u8 => Ok(ConnectorChannelWorkerHello),
             v if v == ConnectorChannelAgentHello as u8 => Ok(ConnectorChannelAgentHello),
+            v if v == ConnectorChannelRelayHello as u8 => Ok(ConnectorChannelRelayHello),
+            v if v == ConnectorVersion as u8 => Ok(ConnectorVersion),
+            v if v == ConnectorChunk as u8 => Ok(ConnectorChunk),
             other => Err(other),
         }
     }
 }
 
-#[test]
-fn connector_signal_roundtrips() {
-    use feo_time::Duration;
-    let timestamp = Timestamp(Duration::from_secs(1));
-
-    #[rustfmt::skip]
-    let signals_with_consumed_bytes = [
-        (ProtocolSignal::Core(Signal::Startup((ActivityId::from(123), timestamp))), 26),
-        (ProtocolSignal::Core(Signal::Step((ActivityId::from(123), timestamp))), 26),
-        (ProtocolSignal::Core(Signal::Shutdown((ActivityId::from(123), timestamp))), 26),
-        (ProtocolSignal::Core(Signal::Ready((ActivityId::from(123), timestamp))), 26),
-        (ProtocolSignal::Core(Signal::ActivityFailed((ActivityId::from(123), ActivityError::Step))), 11),
-        (ProtocolSignal::ActivityHello(ActivityId::from(123)), 10),
-        (ProtocolSignal::Core(Signal::Terminate(timestamp)), 18),
-        (ProtocolSignal::Core(Signal::TerminateAck(AgentId::from(123))), 10),
-    ];
-
-    for (signal, consumed_bytes) in signals_with_consumed_bytes {
-        let mut buffer = [0; 128];
-        let mut view = &mut buffer[..];
-
-        signal.encode(&mut view).unwrap();
-        let (decoded, consumed) = ProtocolSignal::try_decode(&buffer).unwrap();
-
-        assert_eq!(decoded, signal);
-        assert_eq!(consumed, consumed_bytes);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connector_signal_roundtrips() {
+        use feo_time::Duration;
+        let timestamp = Timestamp(Duration::from_secs(1));
+        let log_line = LogLine::new(
+            AgentId::from(123),
+            core::time::Duration::from_secs(2),
+            score_log::Level::Warn,
+            "hello",
+        );
+        let panic_report = PanicReport::new(ActivityId::from(123), "oops at src/lib.rs:1", "step < run");
+
+        #[rustfmt::skip]
+        let signals_with_consumed_bytes = [
+            (ProtocolSignal::Core(Signal::Startup((ActivityId::from(123), timestamp))), 26),
+            (ProtocolSignal::Core(Signal::Step((ActivityId::from(123), timestamp))), 26),
+            (ProtocolSignal::Core(Signal::StaleInput((ActivityId::from(123), ActivityId::from(45)))), 18),
+            (ProtocolSignal::Core(Signal::Shutdown((ActivityId::from(123), timestamp))), 26),
+            (ProtocolSignal::Core(Signal::Ready((ActivityId::from(123), timestamp))), 26),
+            (ProtocolSignal::Core(Signal::ActivityFailed((ActivityId::from(123), ActivityError::Step))), 11),
+            (ProtocolSignal::ActivityHello(ActivityId::from(123)), 10),
+            (ProtocolSignal::ChannelHello(ChannelId::Relay(RelayId::from(3))), 10),
+            (ProtocolSignal::Version(PROTOCOL_VERSION), 4),
+            (ProtocolSignal::Core(Signal::Terminate(timestamp)), 18),
+            (ProtocolSignal::Core(Signal::TerminateAck(AgentId::from(123))), 10),
+            (ProtocolSignal::Core(Signal::Heartbeat(AgentId::from(123))), 10),
+            (ProtocolSignal::Core(Signal::Log(log_line)), 24),
+            (ProtocolSignal::Core(Signal::AgentPid((AgentId::from(123), 4567))), 14),
+            (ProtocolSignal::Core(Signal::MemoryRetained((ActivityId::from(123), -4096))), 18),
+            (ProtocolSignal::Core(Signal::WorkerUtilization((WorkerId::from(12), 87))), 11),
+            (ProtocolSignal::Core(Signal::ActivityPanicked(panic_report)), 41),
+        ];
+
+        for (signal, consumed_bytes) in signals_with_consumed_bytes {
+            let mut buffer = [0; 128];
+            let mut view = &mut buffer[..];
+
+            signal.encode(&mut view).unwrap();
+            let (decoded, consumed) = ProtocolSignal::try_decode(&buffer).unwrap().unwrap();
+
+            assert_eq!(decoded, signal);
+            assert_eq!(consumed, consumed_bytes);
+        }
+    }
+
+    #[test]
+    fn oversized_signals_are_sent_in_chunks() {
+        use alloc::vec::Vec;
+        use connection::Connection;
+        use mio::net::UnixStream;
+
+        let (sender, receiver) = UnixStream::pair().unwrap();
+        let mut sender = Connection::<UnixStream, ProtocolSignal>::new(sender);
+        let mut receiver = Connection::<UnixStream, ProtocolSignal>::new(receiver);
+
+        let log_line = LogLine::new(
+            AgentId::from(7),
+            core::time::Duration::from_secs(2),
+            score_log::Level::Info,
+            &"x".repeat(LOG_LINE_CAPACITY),
+        );
+        let panic_report = PanicReport::new(
+            ActivityId::from(3),
+            &"m".repeat(PANIC_MESSAGE_CAPACITY),
+            &"b".repeat(PANIC_BACKTRACE_CAPACITY),
+        );
+        let signals = [
+            ProtocolSignal::Core(Signal::Heartbeat(AgentId::from(7))),
+            ProtocolSignal::Core(Signal::Log(log_line)),
+            ProtocolSignal::Core(Signal::ActivityPanicked(panic_report)),
+            ProtocolSignal::Core(Signal::Heartbeat(AgentId::from(8))),
+        ];
+        for signal in signals.iter() {
+            sender.send(signal).unwrap();
+        }
+
+        receiver.set_stream_readable();
+        let mut received = Vec::new();
+        while let Some(signal) = receiver.read().unwrap() {
+            received.push(signal);
+        }
+        assert_eq!(received, signals);
+    }
+
+    #[test]
+    fn long_signal_data_has_extended_length() {
+        use alloc::vec::Vec;
+
+        let mut header = Vec::new();
+        write_header(&mut header, SignalTag::CoreLog, 17).unwrap();
+        write_header(&mut header, SignalTag::CoreLog, 300).unwrap();
+        assert_eq!(header, [SignalTag::CoreLog as u8, 17, SignalTag::CoreLog as u8, EXTENDED_LENGTH, 0x2c, 0x01]);
+        assert_eq!(read_header(&header), Some((SignalTag::CoreLog as u8, 17, 2)));
+        assert_eq!(read_header(&header[2..]), Some((SignalTag::CoreLog as u8, 300, 4)));
+        assert_eq!(read_header(&header[2..5]), None);
+        assert!(write_header(&mut header, SignalTag::CoreLog, usize::from(u16::MAX) + 1).is_err());
+    }
+
+    #[test]
+    fn invalid_chunks_fail_the_connection() {
+        use alloc::vec;
+        use alloc::vec::Vec;
+        use connection::Connection;
+        use mio::net::UnixStream;
+
+        // Chunk frames carrying the given message, as sent by a peer
+        let chunk_frames = |message: &[u8]| {
+            let mut frames = Vec::new();
+            for chunk in message.chunks(100) {
+                frames.extend_from_slice(&[ProtocolSignal::CHUNK_TAG, chunk.len() as u8]);
+                frames.extend_from_slice(chunk);
+            }
+            frames
+        };
+
+        // Chunk tags are never decoded as signals, e.g. from shared memory or QNX messages
+        assert!(ProtocolSignal::try_decode(&[ProtocolSignal::CHUNK_TAG, 2, 0, 0]).is_err());
+
+        let (mut sender, receiver) = UnixStream::pair().unwrap();
+        let mut receiver = Connection::<UnixStream, ProtocolSignal>::new(receiver);
+        sender.write_all(&chunk_frames(&[ProtocolSignal::CHUNK_TAG; 150])).unwrap();
+        receiver.set_stream_readable();
+        assert_eq!(receiver.read().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // The reassembly stops at the maximum size of a message instead of buffering the announced length
+        let mut message = vec![SignalTag::CoreLog as u8, EXTENDED_LENGTH, 0xff, 0xff];
+        message.resize(8192, b'x');
+        let (mut sender, receiver) = UnixStream::pair().unwrap();
+        let mut receiver = Connection::<UnixStream, ProtocolSignal>::new(receiver);
+        sender.write_all(&chunk_frames(&message)).unwrap();
+        receiver.set_stream_readable();
+        assert_eq!(receiver.read().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn invalid_signals_fail_to_decode() {
+        let unknown_tag = [0xfe, 1, 0];
+        assert!(ProtocolSignal::try_decode(&unknown_tag).is_err());
+
+        // A log line too short for its header fields
+        let truncated_log = [SignalTag::CoreLog as u8, 3, 0, 0, 0];
+        assert!(ProtocolSignal::try_decode(&truncated_log).is_err());
+
+        // A panic report announcing a message longer than its data
+        let mut truncated_panic = [0u8; 11];
+        truncated_panic[..2].copy_from_slice(&[SignalTag::CoreActivityPanicked as u8, 9]);
+        truncated_panic[10] = 20;
+        assert!(ProtocolSignal::try_decode(&truncated_panic).is_err());
+
+        // Incomplete signals are no error
+        assert!(ProtocolSignal::try_decode(&[SignalTag::CoreLog as u8, 17, 0]).unwrap().is_none());
+    }
+
+    /// Path of a socket of the given handshake test
+    fn handshake_socket(test: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("feo_handshake_test_{test}_{}.sock", std::process::id()))
+    }
+
+    /// Receive the next signal on the given server, giving up after a second
+    fn server_receive(
+        server: &mut server::UnixServer,
+    ) -> Result<Option<(mio::Token, ProtocolSignal)>, crate::error::Error> {
+        let mut events = mio::Events::with_capacity(8);
+        for _ in 0..100 {
+            if let Some(received) = server.receive(&mut events, feo_time::Duration::from_millis(10))? {
+                return Ok(Some(received));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Receive the next signal on the given client, giving up after a second
+    fn client_receive(client: &mut client::UnixClient) -> Result<Option<ProtocolSignal>, crate::error::Error> {
+        let mut events = mio::Events::with_capacity(8);
+        for _ in 0..100 {
+            if let Some(received) = client.receive(&mut events, feo_time::Duration::from_millis(10))? {
+                return Ok(Some(received));
+            }
+        }
+        Ok(None)
+    }
+
+    #[test]
+    fn matching_protocol_versions_are_negotiated() {
+        use crate::agent::ConnectionPolicy;
+
+        let path = handshake_socket("matching");
+        let mut server = server::UnixServer::new(&path);
+        let hello = ProtocolSignal::ActivityHello(ActivityId::from(1));
+        let mut client = client::UnixClient::connect([hello], &path, &ConnectionPolicy::default()).unwrap();
+
+        // The server consumes the announced version and passes on the following signals
+        let (token, signal) = server_receive(&mut server).unwrap().unwrap();
+        assert_eq!(signal, hello);
+
+        // The client consumes the acknowledged version and passes on the following signals
+        let heartbeat = ProtocolSignal::Core(Signal::Heartbeat(AgentId::from(2)));
+        server.send(&token, &heartbeat).unwrap();
+        assert_eq!(client_receive(&mut client).unwrap(), Some(heartbeat));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mismatched_protocol_versions_fail_the_handshake() {
+        use crate::agent::ConnectionPolicy;
+        use crate::error::{ConnectionError, Error};
+        use std::io::Read;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        // A server rejects a client of another version and tells it its own version
+        let path = handshake_socket("mismatched_server");
+        let mut server = server::UnixServer::new(&path);
+        let mut peer = UnixStream::connect(&path).unwrap();
+        ProtocolSignal::Version(PROTOCOL_VERSION + 1).encode(&mut peer).unwrap();
+        assert!(matches!(
+            server_receive(&mut server),
+            Err(Error::Connection(ConnectionError::ProtocolVersion(PROTOCOL_VERSION, Some(version))))
+                if version == PROTOCOL_VERSION + 1
+        ));
+        let mut ack = [0; 4];
+        peer.read_exact(&mut ack).unwrap();
+        assert_eq!(
+            ProtocolSignal::try_decode(&ack).unwrap(),
+            Some((ProtocolSignal::Version(PROTOCOL_VERSION), 4))
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        // A server rejects a client not announcing its version
+        let path = handshake_socket("missing_version");
+        let mut server = server::UnixServer::new(&path);
+        let mut peer = UnixStream::connect(&path).unwrap();
+        ProtocolSignal::ActivityHello(ActivityId::from(1)).encode(&mut peer).unwrap();
+        assert!(matches!(
+            server_receive(&mut server),
+            Err(Error::Connection(ConnectionError::ProtocolVersion(PROTOCOL_VERSION, None)))
+        ));
+        std::fs::remove_file(&path).unwrap();
+
+        // A client rejects a server acknowledging another version
+        let path = handshake_socket("mismatched_client");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut client = client::UnixClient::connect([], &path, &ConnectionPolicy::default()).unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        ProtocolSignal::Version(PROTOCOL_VERSION + 1).encode(&mut peer).unwrap();
+        assert!(matches!(
+            client_receive(&mut client),
+            Err(Error::Connection(ConnectionError::ProtocolVersion(PROTOCOL_VERSION, Some(version))))
+                if version == PROTOCOL_VERSION + 1
+        ));
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -12,15 +12,19 @@
 // *******************************************************************************
 
 use crate::debug_fmt::ScoreDebugDebug;
+use crate::error::ConnectionError;
 use crate::signalling::common::socket::connection::Connection;
-use crate::signalling::common::socket::{EncodeDecode, ProtocolSignal};
+use crate::signalling::common::socket::{EncodeDecode, ProtocolSignal, PROTOCOL_VERSION};
+use alloc::vec::Vec;
 use core::fmt;
+use core::mem;
 use core::net::SocketAddr;
 use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
 use mio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
 use mio::{event, Events, Interest, Poll, Token};
-use score_log::{debug, info, warn};
-use std::collections::HashMap;
+use score_log::{debug, error, info, warn};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::path::Path;
@@ -47,6 +51,12 @@ where
     accepted_connections: HashMap<Token, Connection<L::Stream, ProtocolSignal>>,
     /// Number of accepted connections, used as ID on accept
     num_accepted_connections: usize,
+    /// Connections whose peer announced our protocol version
+    negotiated: HashSet<Token>,
+    /// Whether connections failing to read are closed instead of failing the receive call
+    drop_closed: bool,
+    /// Connections closed since the last call of [SocketServer::take_closed]
+    closed: Vec<Token>,
 }
 
 impl<L> SocketServer<L>
@@ -106,6 +116,18 @@ where
         }
     }
 
+    /// Close connections failing to read, e.g. because the peer exited, instead of returning an error
+    ///
+    /// The closed connections are taken with [SocketServer::take_closed].
+    pub fn drop_closed_connections(&mut self) {
+        self.drop_closed = true;
+    }
+
+    /// Take the connections closed since the last call
+    pub fn take_closed(&mut self) -> Vec<Token> {
+        mem::take(&mut self.closed)
+    }
+
     /// Accept connections on the listener
     fn accept_connections(&mut self) {
         loop {
@@ -130,15 +152,76 @@ where
     }
 
     /// Try to receive a message
+    ///
+    /// The protocol version announced by the peer with the first signal of a connection is checked here,
+    /// so that peers of another version fail with a clear error instead of misinterpreting signals.
     fn receive_on_readable_connections(&mut self) -> Result<Option<(Token, ProtocolSignal)>, crate::error::Error> {
+        while let Some((token, signal)) = self.read_readable_connections()? {
+            match signal {
+                ProtocolSignal::Version(version) => self.negotiate(token, version)?,
+                _ if !self.negotiated.contains(&token) => {
+                    error!(
+                        "Peer with token {} did not announce its signalling protocol version, expected version {}",
+                        token.0,
+                        PROTOCOL_VERSION
+                    );
+                    return Err(crate::error::Error::Connection(ConnectionError::ProtocolVersion(
+                        PROTOCOL_VERSION,
+                        None,
+                    )));
+                },
+                signal => return Ok(Some((token, signal))),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check the protocol version announced by the peer of the given connection and acknowledge it with ours
+    fn negotiate(&mut self, token: Token, version: u16) -> Result<(), crate::error::Error> {
+        // The peer checks the acknowledged version as well, so both sides report a mismatch
+        if let Err(e) = self.send(&token, &ProtocolSignal::Version(PROTOCOL_VERSION)) {
+            warn!(
+                "Failed to acknowledge protocol version on token {}: {:?}",
+                token.0,
+                ScoreDebugIoError(e)
+            );
+        }
+        if version != PROTOCOL_VERSION {
+            error!(
+                "Peer with token {} speaks signalling protocol version {}, expected version {}",
+                token.0,
+                version,
+                PROTOCOL_VERSION
+            );
+            return Err(crate::error::Error::Connection(ConnectionError::ProtocolVersion(
+                PROTOCOL_VERSION,
+                Some(version),
+            )));
+        }
+        self.negotiated.insert(token);
+        Ok(())
+    }
+
+    /// Read the next signal from any readable connection
+    fn read_readable_connections(&mut self) -> Result<Option<(Token, ProtocolSignal)>, crate::error::Error> {
+        let mut failed = Vec::new();
         for (token, connection) in self.accepted_connections.iter_mut().filter(|(_, c)| c.is_readable()) {
             match connection.read() {
                 Ok(Some(msg)) => return Ok(Some((*token, msg))),
                 Ok(None) => {},
+                Err(_) if self.drop_closed => failed.push(*token),
                 Err(e) => return Err(e.into()),
             }
         }
 
+        for token in failed {
+            if let Some(mut connection) = self.accepted_connections.remove(&token) {
+                let _ = self.poll.registry().deregister(connection.stream());
+                info!("Closed connection with token {}", token.0);
+                self.negotiated.remove(&token);
+                self.closed.push(token);
+            }
+        }
         Ok(None)
     }
 }
@@ -163,6 +246,9 @@ where
             poll,
             accepted_connections,
             num_accepted_connections,
+            negotiated: HashSet::new(),
+            drop_closed: false,
+            closed: Vec::new(),
         }
     }
 }
@@ -20,5 +20,8 @@
 
 pub(crate) mod mpsc;
 pub(crate) mod mw_com;
+#[cfg(all(feature = "signalling_qnx", target_os = "nto"))]
+pub(crate) mod qnx;
 pub(crate) mod scheduler;
+pub(crate) mod shm;
 pub(crate) mod worker;
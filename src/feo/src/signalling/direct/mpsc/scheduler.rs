@@ -11,7 +11,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::error::Error;
+use crate::error::{ConnectionError, Error, SignallingError};
 use crate::ids::{ActivityId, AgentId, ChannelId, WorkerId};
 use crate::signalling::common::interface::ConnectScheduler;
 use crate::signalling::common::mpsc::endpoint::{
@@ -86,7 +86,7 @@ impl SchedulerConnector {
         }
         match protocol_signal.unwrap() {
             ProtocolSignal::Core(signal) => Ok(Some(signal)),
-            _ => Err(Error::UnexpectedProtocolSignal),
+            _ => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
         }
     }
 
@@ -94,7 +94,7 @@ impl SchedulerConnector {
         let worker_id = match channel_id {
             ChannelId::Activity(id) => self.activity_worker_map[&id],
             ChannelId::Worker(id) => id,
-            _ => return Err(Error::ChannelNotFound(channel_id)),
+            _ => return Err(Error::Connection(ConnectionError::ChannelNotFound(channel_id))),
         };
 
         let channel_id = ChannelId::Worker(worker_id);
@@ -12,7 +12,7 @@
 // *******************************************************************************
 
 use crate::alloc::string::ToString;
-use crate::error::Error;
+use crate::error::{Error, SignallingError};
 use crate::ids::ActivityId;
 use crate::ids::AgentId;
 use crate::ids::WorkerId;
@@ -138,7 +138,7 @@ impl ConnectWorker for MwComWorkerConnector {
             trace!("Got signal from scheduler {:?}", signal);
             Ok(Some(signal))
         } else {
-            Err(Error::UnexpectedProtocolSignal)
+            Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal))
         }
     }
 
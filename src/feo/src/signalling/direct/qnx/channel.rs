@@ -0,0 +1,337 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! QNX channels and connections passing protocol signals
+
+use crate::error::{ConnectionError, Error};
+use crate::signalling::common::socket::{EncodeDecode, ProtocolSignal, MAX_SIGNAL_SIZE};
+use alloc::ffi::CString;
+use alloc::string::ToString;
+use core::ffi::{c_char, c_int, c_long, c_uint, c_void};
+use core::mem::size_of;
+use core::ptr;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, warn};
+use std::io;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+/// Maximum size of a message, i.e. the header and an encoded signal
+const MESSAGE_SIZE: usize = HEADER_SIZE + MAX_SIGNAL_SIZE;
+
+/// Size of the header of a message, i.e. the message type and the sender
+const HEADER_SIZE: usize = 12;
+
+/// Type of the messages carrying protocol signals, above the range of the I/O messages `_IO_BASE..=_IO_MAX`
+const MESSAGE_TYPE: u16 = 0x200;
+
+/// Type of the `_IO_CONNECT` message sent by `name_open`
+const IO_CONNECT: u16 = 0x100;
+
+/// Range of the types of I/O messages, `_IO_BASE..=_IO_MAX`
+const IO_MESSAGE_TYPES: core::ops::RangeInclusive<u16> = 0x100..=0x1FF;
+
+/// Pulse code stopping the receive thread of a channel
+const STOP_PULSE_CODE: i8 = 0;
+
+/// Code of the pulse sent by the kernel when a client closes its connection, `_PULSE_CODE_DISCONNECT`
+const PULSE_CODE_DISCONNECT: i8 = -33;
+
+const _: () = assert!(size_of::<Pulse>() <= MESSAGE_SIZE, "a pulse must fit into the receive buffer");
+
+/// Connect to a channel of the local node
+const ND_LOCAL_NODE: u32 = 0;
+
+/// Connection IDs not to be inherited as file descriptors
+const NTO_SIDE_CHANNEL: c_uint = 0x4000_0000;
+
+/// Layout of `name_attach_t`
+#[repr(C)]
+struct NameAttach {
+    dpp: *mut c_void,
+    chid: c_int,
+    mntid: c_int,
+    zero: [c_int; 2],
+}
+
+/// Layout of `struct _pulse`
+#[repr(C)]
+struct Pulse {
+    kind: u16,
+    subtype: u16,
+    code: i8,
+    zero: [u8; 3],
+    /// `union sigval`, of the size and alignment of a pointer
+    value: *mut c_void,
+    scoid: c_int,
+}
+
+unsafe extern "C" {
+    fn name_attach(dpp: *mut c_void, path: *const c_char, flags: c_uint) -> *mut NameAttach;
+    fn name_detach(attach: *mut NameAttach, flags: c_uint) -> c_int;
+    fn name_open(name: *const c_char, flags: c_int) -> c_int;
+    fn name_close(coid: c_int) -> c_int;
+    fn ChannelCreate(flags: c_uint) -> c_int;
+    fn ChannelDestroy(chid: c_int) -> c_int;
+    fn ConnectAttach(nd: u32, pid: libc::pid_t, chid: c_int, index: c_uint, flags: c_int) -> c_int;
+    fn ConnectDetach(coid: c_int) -> c_int;
+    fn MsgSend(coid: c_int, smsg: *const c_void, sbytes: usize, rmsg: *mut c_void, rbytes: usize) -> c_long;
+    fn MsgReceive(chid: c_int, msg: *mut c_void, bytes: usize, info: *mut c_void) -> c_int;
+    fn MsgReply(rcvid: c_int, status: c_long, msg: *const c_void, bytes: usize) -> c_int;
+    fn MsgError(rcvid: c_int, error: c_int) -> c_int;
+    fn MsgSendPulse(coid: c_int, priority: c_int, code: c_int, value: c_int) -> c_int;
+}
+
+/// Sender of a message, identified by its process and the channel it receives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Source {
+    pub(crate) pid: libc::pid_t,
+    pub(crate) chid: c_int,
+}
+
+/// Channel receiving protocol signals in a background thread
+pub(crate) struct Channel {
+    /// Source of the messages sent by the owner of this channel
+    source: Source,
+    /// Name registration, if the channel has been created by name
+    attach: *mut NameAttach,
+    /// Signals received by the receive thread
+    receiver: mpsc::Receiver<(Source, ProtocolSignal)>,
+    /// Receive thread
+    thread: Option<JoinHandle<()>>,
+}
+
+// SAFETY: the name registration is only accessed on drop, by the owner of the channel
+unsafe impl Send for Channel {}
+
+impl Channel {
+    /// Create a channel registered under the given name in the path name space
+    pub(crate) fn attach(name: &str) -> Result<Self, Error> {
+        let name = c_string(name)?;
+        // SAFETY: name is a valid C string, a null dispatch handle requests a plain channel
+        let attach = unsafe { name_attach(ptr::null_mut(), name.as_ptr(), 0) };
+        if attach.is_null() {
+            return Err(os_error("failed to attach QNX channel name"));
+        }
+        // SAFETY: attach is a valid registration returned by name_attach
+        let chid = unsafe { (*attach).chid };
+        Self::start(chid, attach)
+    }
+
+    /// Create an anonymous channel
+    pub(crate) fn create() -> Result<Self, Error> {
+        // SAFETY: plain system call without pointer arguments
+        let chid = unsafe { ChannelCreate(0) };
+        if chid < 0 {
+            return Err(os_error("failed to create QNX channel"));
+        }
+        Self::start(chid, ptr::null_mut())
+    }
+
+    /// Source identifying this channel in the messages sent by its owner
+    pub(crate) fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Wait for a signal for at most `timeout`
+    pub(crate) fn receive(&self, timeout: Duration) -> Result<Option<(Source, ProtocolSignal)>, Error> {
+        match self.receiver.recv_timeout(timeout.into()) {
+            Ok(message) => Ok(Some(message)),
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(Error::Connection(ConnectionError::ChannelClosed)),
+        }
+    }
+
+    fn start(chid: c_int, attach: *mut NameAttach) -> Result<Self, Error> {
+        let source = Source {
+            // SAFETY: plain system call without arguments
+            pid: unsafe { libc::getpid() },
+            chid,
+        };
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("feo-qnx-channel".to_string())
+            .spawn(move || receive_messages(chid, sender))
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn QNX channel thread")))?;
+        Ok(Self {
+            source,
+            attach,
+            receiver,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        match Connection::attach(self.source) {
+            Ok(connection) => {
+                // SAFETY: plain system call on a valid connection
+                unsafe { MsgSendPulse(connection.coid, -1, STOP_PULSE_CODE, 0) };
+                if let Some(thread) = self.thread.take() {
+                    let _ = thread.join();
+                }
+            },
+            Err(e) => warn!("Failed to stop QNX channel thread: {:?}", e),
+        }
+        // SAFETY: the channel and the registration are owned by this instance and no longer received on
+        unsafe {
+            if self.attach.is_null() {
+                ChannelDestroy(self.source.chid);
+            } else {
+                name_detach(self.attach, 0);
+            }
+        }
+    }
+}
+
+/// Main function of the receive thread of a channel
+fn receive_messages(chid: c_int, sender: mpsc::Sender<(Source, ProtocolSignal)>) {
+    let mut buffer = [0u8; MESSAGE_SIZE];
+    loop {
+        // SAFETY: the buffer is valid for writes of its length, the info is optional
+        let rcvid = unsafe { MsgReceive(chid, buffer.as_mut_ptr().cast(), buffer.len(), ptr::null_mut()) };
+        if rcvid < 0 {
+            warn!("Failed to receive on QNX channel: {:?}", ScoreDebugIoError(io::Error::last_os_error()));
+            return;
+        }
+        if rcvid == 0 {
+            // SAFETY: pulses are received as `struct _pulse`, which fits into the buffer
+            let pulse = unsafe { ptr::read_unaligned(buffer.as_ptr().cast::<Pulse>()) };
+            match pulse.code {
+                STOP_PULSE_CODE => {
+                    debug!("Stopping receive thread of QNX channel {}", chid);
+                    return;
+                },
+                PULSE_CODE_DISCONNECT => {
+                    // The server connection of a client which closed its connection has to be released
+                    // SAFETY: plain system call on the server connection of the pulse
+                    unsafe { ConnectDetach(pulse.scoid) };
+                },
+                // Other pulses, e.g. `_PULSE_CODE_UNBLOCK`, need no handling as messages are replied immediately
+                code => debug!("Ignoring pulse with code {} on QNX channel {}", code, chid),
+            }
+            continue;
+        }
+
+        match u16::from_le_bytes([buffer[0], buffer[1]]) {
+            MESSAGE_TYPE => (),
+            IO_CONNECT => {
+                // Accept the connection opened via name_open
+                // SAFETY: rcvid identifies the message received above, the reply carries no data
+                unsafe { MsgReply(rcvid, 0, ptr::null(), 0) };
+                continue;
+            },
+            message_type => {
+                if !IO_MESSAGE_TYPES.contains(&message_type) {
+                    warn!("Received message of unknown type {} on QNX channel {}", message_type, chid);
+                }
+                // SAFETY: rcvid identifies the message received above
+                unsafe { MsgError(rcvid, libc::ENOSYS) };
+                continue;
+            },
+        }
+        // SAFETY: rcvid identifies the message received above, the reply carries no data
+        unsafe { MsgReply(rcvid, 0, ptr::null(), 0) };
+
+        match decode(&buffer) {
+            Some(message) => {
+                if sender.send(message).is_err() {
+                    return;
+                }
+            },
+            None => warn!("Received undecodable message on QNX channel {}", chid),
+        }
+    }
+}
+
+/// Connection to a channel, sending protocol signals
+pub(crate) struct Connection {
+    coid: c_int,
+    /// Whether the connection has been opened by name
+    named: bool,
+}
+
+impl Connection {
+    /// Open a connection to the channel registered under the given name
+    pub(crate) fn open(name: &str) -> io::Result<Self> {
+        let name = CString::new(name).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        // SAFETY: name is a valid C string
+        let coid = unsafe { name_open(name.as_ptr(), 0) };
+        if coid < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { coid, named: true })
+    }
+
+    /// Attach a connection to the channel of the given source
+    pub(crate) fn attach(source: Source) -> Result<Self, Error> {
+        // SAFETY: plain system call without pointer arguments
+        let coid = unsafe { ConnectAttach(ND_LOCAL_NODE, source.pid, source.chid, NTO_SIDE_CHANNEL, 0) };
+        if coid < 0 {
+            return Err(os_error("failed to attach to QNX channel"));
+        }
+        Ok(Self { coid, named: false })
+    }
+
+    /// Send a protocol signal from the given source, blocking until the receiver has received it
+    pub(crate) fn send(&self, source: Source, signal: &ProtocolSignal) -> Result<(), Error> {
+        let mut buffer = [0u8; MESSAGE_SIZE];
+        buffer[..2].copy_from_slice(&MESSAGE_TYPE.to_le_bytes());
+        buffer[4..8].copy_from_slice(&source.pid.to_le_bytes());
+        buffer[8..HEADER_SIZE].copy_from_slice(&source.chid.to_le_bytes());
+        let mut writer = &mut buffer[HEADER_SIZE..];
+        signal
+            .encode(&mut writer)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to encode signal")))?;
+        let len = MESSAGE_SIZE - writer.len();
+        // SAFETY: the buffer is valid for reads of len bytes, no reply data is expected
+        let status = unsafe { MsgSend(self.coid, buffer.as_ptr().cast(), len, ptr::null_mut(), 0) };
+        if status < 0 {
+            return Err(os_error("failed to send QNX message"));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // SAFETY: the connection is owned by this instance
+        unsafe {
+            if self.named {
+                name_close(self.coid);
+            } else {
+                ConnectDetach(self.coid);
+            }
+        }
+    }
+}
+
+/// Decode the sender and the signal of a message
+fn decode(buffer: &[u8; MESSAGE_SIZE]) -> Option<(Source, ProtocolSignal)> {
+    let source = Source {
+        pid: libc::pid_t::from_le_bytes(buffer[4..8].try_into().ok()?),
+        chid: c_int::from_le_bytes(buffer[8..HEADER_SIZE].try_into().ok()?),
+    };
+    let (signal, _) = ProtocolSignal::try_decode(&buffer[HEADER_SIZE..]).ok().flatten()?;
+    Some((source, signal))
+}
+
+fn c_string(name: &str) -> Result<CString, Error> {
+    CString::new(name).map_err(|_| Error::Connection(ConnectionError::Channel("QNX channel name contains a nul byte")))
+}
+
+fn os_error(description: &'static str) -> Error {
+    Error::Io((ScoreDebugIoError(io::Error::last_os_error()), description))
+}
@@ -0,0 +1,32 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Signalling over QNX channels for agents on a QNX target
+//!
+//! The scheduler registers a channel under a name in the QNX path name space with `name_attach`.
+//! Each worker creates an anonymous channel of its own, opens a connection to the channel of the
+//! scheduler with `name_open` and announces its activities. Every message carries the process ID
+//! and channel ID of its sender, so the scheduler attaches a connection to the channel of each
+//! worker on its first message.
+//!
+//! Signals are sent with `MsgSend` and replied to immediately by a receive thread per channel,
+//! which passes them on to the connector. This keeps the blocking time of a sender to the message
+//! pass itself, even while the receiving worker is busy stepping an activity.
+//! The receive thread is stopped with a pulse on drop. It accepts the `_IO_CONNECT` messages of
+//! `name_open`, releases the connections of disconnected clients and ignores other pulses.
+//!
+//! Only available with the `signalling_qnx` feature on QNX targets.
+
+pub(crate) mod channel;
+pub(crate) mod scheduler;
+pub(crate) mod worker;
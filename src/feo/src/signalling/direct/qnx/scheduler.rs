@@ -0,0 +1,163 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! QNX channel based connector for the scheduler
+
+use super::channel::{Channel, Connection, Source};
+use crate::error::{ConnectionError, Error};
+use crate::ids::{ActivityId, AgentId};
+use crate::signalling::common::interface::ConnectScheduler;
+use crate::signalling::common::signals::Signal;
+use crate::signalling::common::socket::ProtocolSignal;
+use crate::timestamp::sync_info;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use score_log::warn;
+use std::collections::{HashMap, HashSet};
+
+/// Connector for the scheduler
+pub(crate) struct QnxSchedulerConnector {
+    /// Name of the channel in the QNX path name space
+    name: String,
+    /// Channel of the scheduler, created when connecting
+    channel: Option<Channel>,
+    /// Connection to the channel of each worker
+    connections: HashMap<Source, Connection>,
+
+    activity_worker_map: HashMap<ActivityId, Source>,
+    activity_agent_map: HashMap<ActivityId, AgentId>,
+
+    all_activities: Vec<ActivityId>,
+    connection_timeout: Duration,
+}
+
+impl QnxSchedulerConnector {
+    /// Create a new instance
+    pub(crate) fn new(
+        name: &str,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        activity_agent_map: HashMap<ActivityId, AgentId>,
+        connection_timeout: Duration,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            channel: None,
+            connections: HashMap::new(),
+            activity_worker_map: HashMap::new(),
+            activity_agent_map,
+            all_activities: activity_ids.into_iter().collect(),
+            connection_timeout,
+        }
+    }
+
+    fn channel(&self) -> &Channel {
+        self.channel.as_ref().expect("QNX channel not created")
+    }
+
+    fn send_to_worker(&self, worker: Source, signal: &Signal) -> Result<(), Error> {
+        self.connections
+            .get(&worker)
+            .ok_or(Error::Connection(ConnectionError::Channel("no connection to QNX worker channel")))?
+            .send(self.channel().source(), &ProtocolSignal::Core(*signal))
+    }
+}
+
+impl ConnectScheduler for QnxSchedulerConnector {
+    fn connect_remotes(&mut self) -> Result<(), Error> {
+        self.channel = Some(Channel::attach(&self.name)?);
+
+        let mut missing_activities: HashSet<ActivityId> = self.all_activities.iter().cloned().collect();
+        let start_time = Instant::now();
+
+        while !missing_activities.is_empty() {
+            let elapsed = start_time.elapsed();
+            if elapsed >= self.connection_timeout {
+                return Err(Error::Io((
+                    ScoreDebugIoError(std::io::ErrorKind::TimedOut.into()),
+                    "CONNECTION_TIMEOUT",
+                )));
+            }
+            let remaining_timeout = self.connection_timeout.saturating_sub(elapsed);
+            let Some((source, signal)) = self.channel().receive(remaining_timeout)? else {
+                continue;
+            };
+            match signal {
+                ProtocolSignal::ActivityHello(activity_id) => {
+                    if !self.connections.contains_key(&source) {
+                        self.connections.insert(source, Connection::attach(source)?);
+                    }
+                    self.activity_worker_map.insert(activity_id, source);
+                    missing_activities.remove(&activity_id);
+                },
+                other => {
+                    warn!(
+                        "received unexpected signal {:?} from QNX channel {} of process {}",
+                        other, source.chid, source.pid
+                    );
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sync_time(&mut self) -> Result<(), Error> {
+        let signal = Signal::StartupSync(sync_info());
+
+        // Send startup time to all workers
+        for worker in self.connections.keys() {
+            self.send_to_worker(*worker, &signal)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_connected_agent_ids(&self) -> Vec<AgentId> {
+        let mut agent_ids: HashSet<AgentId> = HashSet::new();
+        for activity_id in self.activity_worker_map.keys() {
+            if let Some(agent_id) = self.activity_agent_map.get(activity_id) {
+                agent_ids.insert(*agent_id);
+            }
+        }
+        agent_ids.into_iter().collect()
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
+        match self.channel().receive(timeout)? {
+            Some((_, ProtocolSignal::Core(signal))) => Ok(Some(signal)),
+            Some((_, other)) => {
+                warn!("received unexpected protocol signal {:?}", other);
+                Ok(None)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn send_to_activity(&mut self, activity_id: ActivityId, signal: &Signal) -> Result<(), Error> {
+        let worker = *self
+            .activity_worker_map
+            .get(&activity_id)
+            .unwrap_or_else(|| panic!("failed to find QNX worker channel for activity ID {activity_id}"));
+        self.send_to_worker(worker, signal)
+    }
+
+    fn broadcast_terminate(&mut self, signal: &Signal) -> Result<(), Error> {
+        // Connections are unique per worker channel, so every worker receives the signal once
+        for worker in self.connections.keys() {
+            self.send_to_worker(*worker, signal)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! QNX channel based connector for a worker
+
+use super::channel::{Channel, Connection};
+use crate::agent::ConnectionPolicy;
+use crate::error::{Error, SignallingError};
+use crate::ids::ActivityId;
+use crate::signalling::common::interface::ConnectWorker;
+use crate::signalling::common::signals::Signal;
+use crate::signalling::common::socket::ProtocolSignal;
+use alloc::string::String;
+use alloc::vec::Vec;
+use feo_time::Duration;
+
+/// Connector for a worker
+pub(crate) struct QnxWorkerConnector {
+    /// Name of the channel registered by the connector of the scheduler
+    name: String,
+    /// Channel of this worker and the connection to the channel of the scheduler
+    channel: Option<(Channel, Connection)>,
+    /// [ActivityId]s to announce when connecting
+    activity_ids: Vec<ActivityId>,
+    /// Policy for connecting to the scheduler
+    connection_policy: ConnectionPolicy,
+}
+
+impl QnxWorkerConnector {
+    /// Create a new instance
+    pub(crate) fn new(
+        name: String,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        connection_policy: ConnectionPolicy,
+    ) -> Self {
+        let activity_ids = activity_ids.into_iter().collect();
+        Self {
+            name,
+            channel: None,
+            activity_ids,
+            connection_policy,
+        }
+    }
+
+    fn channel(&self) -> (&Channel, &Connection) {
+        let (channel, connection) = self.channel.as_ref().expect("QNX channel not created");
+        (channel, connection)
+    }
+}
+
+impl ConnectWorker for QnxWorkerConnector {
+    fn connect_remote(&mut self) -> Result<(), Error> {
+        let channel = Channel::create()?;
+        let connection = self.connection_policy.connect(|| Connection::open(&self.name))?;
+        for id in &self.activity_ids {
+            connection.send(channel.source(), &ProtocolSignal::ActivityHello(*id))?;
+        }
+        self.channel = Some((channel, connection));
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
+        let (channel, _) = self.channel();
+        match channel.receive(timeout)? {
+            Some((_, ProtocolSignal::Core(signal))) => Ok(Some(signal)),
+            Some(_) => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
+            None => Ok(None),
+        }
+    }
+
+    fn send_to_scheduler(&mut self, signal: &Signal) -> Result<(), Error> {
+        let (channel, connection) = self.channel();
+        connection.send(channel.source(), &ProtocolSignal::Core(*signal))
+    }
+}
@@ -13,6 +13,7 @@
 
 use crate::error::Error;
 use crate::ids::{ActivityId, AgentId};
+use crate::rejoin::AgentChange;
 use crate::signalling::common::interface::ConnectScheduler;
 use crate::signalling::common::signals::Signal;
 use crate::signalling::common::socket::server::{Listen, SocketServer, TcpServer, UnixServer};
@@ -25,7 +26,7 @@ use feo_time::Instant;
 use feo_tracing::ScoreDebugIoError;
 use mio::net::{TcpListener, UnixListener};
 use mio::{Events, Token};
-use score_log::warn;
+use score_log::{debug, info, warn};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
@@ -48,6 +49,13 @@ where
 
     all_activities: Vec<ActivityId>,
     connection_timeout: Duration,
+
+    /// Whether lost agents may rejoin, see [rejoin](crate::rejoin)
+    rejoin: bool,
+    /// Activities not yet reconnected per lost agent
+    rejoining: HashMap<AgentId, HashSet<ActivityId>>,
+    /// Connection losses and rejoins not yet taken by the scheduler
+    agent_changes: Vec<AgentChange>,
 }
 
 impl<L> SchedulerConnector<L>
@@ -74,7 +82,95 @@ where
             activity_agent_map,
             all_activities,
             connection_timeout,
+            rejoin: false,
+            rejoining: HashMap::new(),
+            agent_changes: Vec::new(),
+        }
+    }
+
+    /// Keep running when the connection to an agent is lost and accept its reconnection, see [rejoin](crate::rejoin)
+    pub(crate) fn with_rejoin(mut self, rejoin: bool) -> Self {
+        self.rejoin = rejoin;
+        if rejoin {
+            self.server.drop_closed_connections();
+        }
+        self
+    }
+
+    /// Mark the agent of the given activity as lost, dropping the connections of all its activities
+    fn lose_agent_of(&mut self, activity_id: ActivityId) {
+        let Some(agent_id) = self.activity_agent_map.get(&activity_id).copied() else {
+            return;
+        };
+        if self.rejoining.contains_key(&agent_id) {
+            return;
+        }
+        let activities: HashSet<ActivityId> = self
+            .activity_agent_map
+            .iter()
+            .filter(|(_, agent)| **agent == agent_id)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in activities.iter() {
+            self.activity_id_token_map.remove(id);
+        }
+        warn!("Lost connection to agent {}", agent_id);
+        self.rejoining.insert(agent_id, activities);
+        self.agent_changes.push(AgentChange::Lost(agent_id));
+    }
+
+    /// Mark the agents of the activities on the closed connections as lost
+    fn handle_closed_connections(&mut self) {
+        for token in self.server.take_closed() {
+            let lost: Vec<ActivityId> = self
+                .activity_id_token_map
+                .iter()
+                .filter(|(_, t)| **t == token)
+                .map(|(id, _)| *id)
+                .collect();
+            for activity_id in lost {
+                self.lose_agent_of(activity_id);
+            }
+        }
+    }
+
+    /// Register the connection of an activity of a restarted agent
+    ///
+    /// Once all activities of the agent are connected, their time is synchronized and the agent rejoins.
+    fn reconnect_activity(&mut self, activity_id: ActivityId, token: Token) {
+        let Some(agent_id) = self.activity_agent_map.get(&activity_id).copied() else {
+            warn!("Ignoring connection of unknown activity {}", activity_id);
+            return;
+        };
+        // The new process may connect before the closed connections of the old one are noticed
+        if !self.rejoining.contains_key(&agent_id) {
+            self.lose_agent_of(activity_id);
+        }
+        debug!("Activity {} of agent {} reconnected", activity_id, agent_id);
+        self.activity_id_token_map.insert(activity_id, token);
+        let Some(missing) = self.rejoining.get_mut(&agent_id) else {
+            return;
+        };
+        missing.remove(&activity_id);
+        if !missing.is_empty() {
+            return;
         }
+        self.rejoining.remove(&agent_id);
+
+        let signal = ProtocolSignal::Core(Signal::StartupSync(sync_info()));
+        let tokens: HashSet<Token> = self
+            .activity_agent_map
+            .iter()
+            .filter(|(_, agent)| **agent == agent_id)
+            .filter_map(|(id, _)| self.activity_id_token_map.get(id).copied())
+            .collect();
+        for token in tokens {
+            if let Err(e) = self.server.send(&token, &signal) {
+                warn!("Failed to synchronize rejoining agent {}: {:?}", agent_id, ScoreDebugIoError(e));
+            }
+        }
+        info!("Agent {} reconnected", agent_id);
+        self.agent_changes.push(AgentChange::Rejoined(agent_id));
     }
 }
 
@@ -165,8 +261,14 @@ where
     }
 
     fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
-        match self.server.receive(&mut self.events, timeout) {
+        let received = self.server.receive(&mut self.events, timeout);
+        self.handle_closed_connections();
+        match received {
             Ok(Some((_, ProtocolSignal::Core(signal)))) => Ok(Some(signal)),
+            Ok(Some((token, ProtocolSignal::ActivityHello(activity_id)))) if self.rejoin => {
+                self.reconnect_activity(activity_id, token);
+                Ok(None)
+            },
             Ok(Some((_, other))) => {
                 warn!("received unexpected protocol signal {:?}", other);
                 Ok(None)
@@ -177,13 +279,31 @@ where
     }
 
     fn send_to_activity(&mut self, activity_id: ActivityId, signal: &Signal) -> Result<(), Error> {
-        let token = self
-            .activity_id_token_map
-            .get(&activity_id)
-            .unwrap_or_else(|| panic!("failed to find token for activity ID {activity_id}"));
-        self.server
-            .send(token, &ProtocolSignal::Core(*signal))
-            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to send")))
+        let token = match self.activity_id_token_map.get(&activity_id) {
+            Some(token) => *token,
+            // Signals to the activities of lost agents are dropped, the scheduler degrades them
+            None if self.rejoin => {
+                debug!("Dropping signal {:?} to disconnected activity {}", signal, activity_id);
+                return Ok(());
+            },
+            None => panic!("failed to find token for activity ID {activity_id}"),
+        };
+        match self.server.send(&token, &ProtocolSignal::Core(*signal)) {
+            Ok(()) => Ok(()),
+            Err(_) if self.rejoin => {
+                self.lose_agent_of(activity_id);
+                Ok(())
+            },
+            Err(e) => Err(Error::Io((ScoreDebugIoError(e), "failed to send"))),
+        }
+    }
+
+    fn accepts_rejoin(&self) -> bool {
+        self.rejoin
+    }
+
+    fn take_agent_changes(&mut self) -> Vec<AgentChange> {
+        core::mem::take(&mut self.agent_changes)
     }
 
     fn broadcast_terminate(&mut self, signal: &Signal) -> Result<(), Error> {
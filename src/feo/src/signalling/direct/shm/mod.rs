@@ -0,0 +1,27 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Signalling over shared memory for agents on the same host
+//!
+//! The scheduler creates a shared memory file (preferably located in `/dev/shm`) containing
+//! one message queue for the scheduler and one per worker. Workers map the same file,
+//! claim one of the worker queues and announce their activities to the scheduler.
+//! Signals are written to the queue of the receiver and waiting receivers are woken up
+//! through a futex on the queue, avoiding the socket round trips of the other backends.
+//!
+//! The backend does not detect lost peers: if the primary agent terminates without
+//! sending the termination signal, workers only notice it through their receive timeouts.
+
+pub(crate) mod queue;
+pub(crate) mod scheduler;
+pub(crate) mod worker;
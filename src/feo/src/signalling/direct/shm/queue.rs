@@ -0,0 +1,506 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Shared memory region and its message queues
+
+use crate::error::{ConnectionError, Error};
+use crate::signalling::common::socket::{EncodeDecode, ProtocolSignal, MAX_SIGNAL_SIZE};
+use core::cell::UnsafeCell;
+use core::mem::size_of;
+use core::ptr::NonNull;
+use core::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use score_log::warn;
+use std::fs::{self, File};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of workers connecting to a scheduler
+pub(crate) const MAX_WORKERS: usize = 64;
+
+/// Number of messages per queue
+const QUEUE_CAPACITY: usize = 256;
+
+/// Maximum size of an encoded message
+const MESSAGE_SIZE: usize = MAX_SIGNAL_SIZE;
+
+/// Marker written by the scheduler once the region has been initialized
+const MAGIC: u64 = u64::from_be_bytes(*b"FEO_SHM2");
+
+/// Source ID of messages sent by the scheduler
+pub(crate) const SCHEDULER_SOURCE: u32 = u32::MAX;
+
+/// Layout of the shared memory region
+#[repr(C)]
+struct Shared {
+    /// Set to [MAGIC] after initialization
+    magic: AtomicU64,
+    /// Number of worker queues claimed so far
+    next_worker: AtomicU32,
+    /// Queue of the scheduler
+    scheduler: Queue,
+    /// Queue per worker
+    workers: [Queue; MAX_WORKERS],
+}
+
+/// Mapping of the shared memory region
+pub(crate) struct Region {
+    /// Start of the mapping
+    shared: NonNull<Shared>,
+    /// Path of the shared memory file, removed on drop if created by this mapping
+    owned_path: Option<PathBuf>,
+}
+
+// Safety: all accesses to the shared region are synchronized through atomics
+unsafe impl Send for Region {}
+
+impl Region {
+    /// Create and initialize the region at the given path, replacing any stale file
+    pub(crate) fn create(path: &Path) -> Result<Self, Error> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(Error::Io((
+                    ScoreDebugIoError(e),
+                    "failed to remove stale shared memory file",
+                )))
+            },
+            _ => {},
+        }
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create shared memory file")))?;
+        file.set_len(size_of::<Shared>() as u64)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to size shared memory file")))?;
+        let shared = map(&file).map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to map shared memory")))?;
+
+        // The file is zero-initialized, which is a valid state for all atomics.
+        // Only the sequence numbers of the queue slots need to be set up.
+        let region = Self {
+            shared,
+            owned_path: Some(path.to_path_buf()),
+        };
+        let shared = region.shared();
+        shared.scheduler.init();
+        shared.workers.iter().for_each(Queue::init);
+        shared.magic.store(MAGIC, Ordering::Release);
+        Ok(region)
+    }
+
+    /// Map an existing region created by the scheduler
+    ///
+    /// Fails if the file does not exist or has not been initialized yet.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = File::options().read(true).write(true).open(path)?;
+        if file.metadata()?.len() != size_of::<Shared>() as u64 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let region = Self {
+            shared: map(&file)?,
+            owned_path: None,
+        };
+        if region.shared().magic.load(Ordering::Acquire) != MAGIC {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        Ok(region)
+    }
+
+    /// Claim the queue of a new worker, returning its index
+    pub(crate) fn claim_worker(&self) -> Result<u32, Error> {
+        let index = self.shared().next_worker.fetch_add(1, Ordering::AcqRel);
+        if index as usize >= MAX_WORKERS {
+            return Err(Error::Connection(ConnectionError::Channel("no free worker queue in shared memory")));
+        }
+        Ok(index)
+    }
+
+    /// Queue of the scheduler
+    pub(crate) fn scheduler_queue(&self) -> &Queue {
+        &self.shared().scheduler
+    }
+
+    /// Queue of the worker with the given index
+    pub(crate) fn worker_queue(&self, index: u32) -> Option<&Queue> {
+        self.shared().workers.get(index as usize)
+    }
+
+    fn shared(&self) -> &Shared {
+        // Safety: the mapping is valid for the lifetime of self and only accessed through atomics
+        unsafe { self.shared.as_ref() }
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        // Safety: the pointer and size correspond to the mapping created in `map`
+        unsafe { libc::munmap(self.shared.as_ptr().cast(), size_of::<Shared>()) };
+        if let Some(path) = self.owned_path.take() {
+            if let Err(e) = fs::remove_file(&path) {
+                warn!(
+                    "Failed to remove shared memory file {}: {:?}",
+                    &*path.to_string_lossy(),
+                    ScoreDebugIoError(e)
+                );
+            }
+        }
+    }
+}
+
+/// Map the shared region from the given file
+fn map(file: &File) -> io::Result<NonNull<Shared>> {
+    // Safety: the file descriptor is valid and the file has the size of the mapping
+    let ptr = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            size_of::<Shared>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    NonNull::new(ptr.cast()).ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
+
+/// Message slot of a queue
+#[repr(C)]
+struct Slot {
+    /// Sequence number synchronizing producers and the consumer
+    sequence: AtomicU64,
+    /// Source of the message
+    source: UnsafeCell<u32>,
+    /// Length of the message
+    len: UnsafeCell<u32>,
+    /// Encoded message
+    data: UnsafeCell<[u8; MESSAGE_SIZE]>,
+}
+
+/// Bounded multi-producer single-consumer queue living in shared memory
+///
+/// Producers reserve a slot by advancing `tail` and publish it through the sequence number
+/// of the slot. The consumer waits on the `futex` word, which is incremented on every push.
+#[repr(C)]
+pub(crate) struct Queue {
+    /// Position of the next message to receive
+    head: AtomicU64,
+    /// Position of the next message to send
+    tail: AtomicU64,
+    /// Counter of pushed messages, used as futex word
+    futex: AtomicU32,
+    /// Number of consumers waiting on the futex
+    waiters: AtomicU32,
+    /// Message slots
+    slots: [Slot; QUEUE_CAPACITY],
+}
+
+/// A message received from a queue
+pub(crate) struct Message {
+    /// Source of the message, i.e. the index of the sending worker or [SCHEDULER_SOURCE]
+    pub(crate) source: u32,
+    len: usize,
+    data: [u8; MESSAGE_SIZE],
+}
+
+impl Message {
+    /// Encoded content of the message
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Decode the protocol signal contained in the message
+    pub(crate) fn signal(&self) -> Option<ProtocolSignal> {
+        ProtocolSignal::try_decode(self.bytes()).ok().flatten().map(|(signal, _)| signal)
+    }
+}
+
+impl Queue {
+    /// Initialize the sequence numbers of the zeroed queue
+    fn init(&self) {
+        for (index, slot) in self.slots.iter().enumerate() {
+            slot.sequence.store(index as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Encode and send a protocol signal, failing if the queue is full
+    pub(crate) fn send(&self, source: u32, signal: &ProtocolSignal) -> Result<(), Error> {
+        let mut buffer = [0u8; MESSAGE_SIZE];
+        let mut writer = &mut buffer[..];
+        signal
+            .encode(&mut writer)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to encode signal")))?;
+        let len = MESSAGE_SIZE - writer.len();
+        self.push(source, &buffer[..len])
+    }
+
+    /// Send a message, failing if the queue is full
+    pub(crate) fn push(&self, source: u32, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.len() > MESSAGE_SIZE {
+            return Err(Error::Connection(ConnectionError::Channel("message exceeds shared memory slot size")));
+        }
+
+        // Reserve a slot
+        let mut pos = self.tail.load(Ordering::Relaxed);
+        let slot = loop {
+            let slot = &self.slots[pos as usize % QUEUE_CAPACITY];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            match sequence.cmp(&pos) {
+                core::cmp::Ordering::Equal => {
+                    match self
+                        .tail
+                        .compare_exchange_weak(pos, pos + 1, Ordering::Relaxed, Ordering::Relaxed)
+                    {
+                        Ok(_) => break slot,
+                        Err(current) => pos = current,
+                    }
+                },
+                core::cmp::Ordering::Less => {
+                    return Err(Error::Connection(ConnectionError::Channel("shared memory queue full")))
+                },
+                core::cmp::Ordering::Greater => pos = self.tail.load(Ordering::Relaxed),
+            }
+        };
+
+        // Safety: the slot has been reserved by the successful compare-exchange on `tail`;
+        // neither other producers nor the consumer access its data until the sequence is published.
+        unsafe {
+            *slot.source.get() = source;
+            *slot.len.get() = bytes.len() as u32;
+            (*slot.data.get())[..bytes.len()].copy_from_slice(bytes);
+        }
+        slot.sequence.store(pos + 1, Ordering::Release);
+
+        // Wake up the consumer if it is waiting
+        self.futex.fetch_add(1, Ordering::Release);
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            futex_wake(&self.futex);
+        }
+        Ok(())
+    }
+
+    /// Receive a message, waiting at most `timeout`
+    ///
+    /// Must only be called by the single consumer of the queue. Fails on a corrupt message, see [Queue::pop].
+    pub(crate) fn receive(&self, timeout: Duration) -> Result<Option<Message>, Error> {
+        let start = Instant::now();
+        loop {
+            if let Some(message) = self.pop()? {
+                return Ok(Some(message));
+            }
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+
+            // Announce waiting before checking the queue once more,
+            // so that a producer either sees the waiter or its message is popped here
+            let value = self.futex.load(Ordering::Acquire);
+            self.waiters.fetch_add(1, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+            let popped = self.pop();
+            if !matches!(popped, Ok(None)) {
+                self.waiters.fetch_sub(1, Ordering::SeqCst);
+                return popped;
+            }
+            futex_wait(&self.futex, value, remaining);
+            self.waiters.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Take the next message, if any
+    ///
+    /// The length of a message is written by the producer in another process, a length exceeding the slot
+    /// fails instead of being trusted. The slot is released either way.
+    fn pop(&self) -> Result<Option<Message>, Error> {
+        let pos = self.head.load(Ordering::Relaxed);
+        let slot = &self.slots[pos as usize % QUEUE_CAPACITY];
+        if slot.sequence.load(Ordering::Acquire) != pos + 1 {
+            return Ok(None);
+        }
+
+        // Safety: the message has been published by its producer through the sequence number
+        // and is not modified until the slot is released below.
+        let message = unsafe {
+            Message {
+                source: *slot.source.get(),
+                len: *slot.len.get() as usize,
+                data: *slot.data.get(),
+            }
+        };
+        slot.sequence.store(pos + QUEUE_CAPACITY as u64, Ordering::Release);
+        self.head.store(pos + 1, Ordering::Relaxed);
+        if message.len > MESSAGE_SIZE {
+            return Err(Error::Connection(ConnectionError::Channel(
+                "invalid message length in shared memory queue",
+            )));
+        }
+        Ok(Some(message))
+    }
+}
+
+/// Wait until the futex word differs from `expected`, a wake-up or the timeout
+///
+/// Spurious wake-ups are possible; callers re-check their condition.
+fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Duration) {
+    let timeout: core::time::Duration = timeout.into();
+    let timespec = libc::timespec {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as libc::c_long,
+    };
+    // Safety: the futex word is a valid, aligned u32 for the duration of the call.
+    // The futex is not private, since the queue is shared between processes.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex.as_ptr(),
+            libc::FUTEX_WAIT,
+            expected,
+            &timespec as *const libc::timespec,
+            core::ptr::null::<u32>(),
+            0,
+        )
+    };
+}
+
+/// Wake all waiters on the futex word
+fn futex_wake(futex: &AtomicU32) {
+    // Safety: the futex word is a valid, aligned u32 for the duration of the call
+    unsafe { libc::syscall(libc::SYS_futex, futex.as_ptr(), libc::FUTEX_WAKE, i32::MAX) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path of a shared memory file unique to the given test
+    fn test_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(alloc::format!("feo_shm_test_{test}_{}", std::process::id()))
+    }
+
+    #[test]
+    fn messages_are_exchanged_through_shared_memory() {
+        let path = test_path("exchange");
+        let scheduler = Region::create(&path).unwrap();
+        let worker = Region::open(&path).unwrap();
+
+        let index = worker.claim_worker().unwrap();
+        let sender = std::thread::spawn(move || {
+            for i in 0..QUEUE_CAPACITY {
+                worker.scheduler_queue().push(index, &[i as u8; 3]).unwrap();
+            }
+        });
+        for i in 0..QUEUE_CAPACITY {
+            let message = scheduler.scheduler_queue().receive(Duration::from_secs(5)).unwrap().unwrap();
+            assert_eq!((message.source, message.bytes()), (index, &[i as u8; 3][..]));
+        }
+        sender.join().unwrap();
+        assert!(scheduler
+            .scheduler_queue()
+            .receive(Duration::from_millis(1))
+            .unwrap()
+            .is_none());
+
+        drop(scheduler);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn full_queue_rejects_messages_and_wraps_around() {
+        let path = test_path("overflow");
+        let region = Region::create(&path).unwrap();
+        let queue = region.scheduler_queue();
+
+        for round in 0..3 {
+            for i in 0..QUEUE_CAPACITY {
+                queue.push(round, &[i as u8; 3]).unwrap();
+            }
+            assert!(queue.push(round, &[0xff]).is_err());
+            for i in 0..QUEUE_CAPACITY {
+                let message = queue.receive(Duration::ZERO).unwrap().unwrap();
+                assert_eq!((message.source, message.bytes()), (round, &[i as u8; 3][..]));
+            }
+            assert!(queue.receive(Duration::ZERO).unwrap().is_none());
+        }
+
+        // Positions continue past the capacity
+        assert_eq!(queue.head.load(Ordering::Relaxed), 3 * QUEUE_CAPACITY as u64);
+        assert!(queue.push(0, &[0; MESSAGE_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn corrupt_message_length_is_rejected() {
+        let path = test_path("corrupt");
+        let region = Region::create(&path).unwrap();
+        let queue = region.scheduler_queue();
+
+        queue.push(0, &[1, 2, 3]).unwrap();
+        queue.push(0, &[4, 5, 6]).unwrap();
+        // Safety: the message is published and not popped yet, nothing else accesses the region
+        unsafe { *queue.slots[0].len.get() = MESSAGE_SIZE as u32 + 1 };
+
+        assert!(queue.receive(Duration::ZERO).is_err());
+        // The corrupt slot is released, the queue continues with the next message
+        let message = queue.receive(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(message.bytes(), &[4, 5, 6]);
+    }
+
+    #[test]
+    fn log_line_of_full_capacity_fits_into_a_slot() {
+        use crate::ids::AgentId;
+        use crate::log_relay::{LogLine, LOG_LINE_CAPACITY};
+        use crate::signalling::common::signals::Signal;
+
+        let path = test_path("log_line");
+        let region = Region::create(&path).unwrap();
+        let queue = region.scheduler_queue();
+
+        let line = LogLine::new(
+            AgentId::from(1),
+            core::time::Duration::from_secs(1),
+            score_log::Level::Warn,
+            &"x".repeat(LOG_LINE_CAPACITY),
+        );
+        let signal = ProtocolSignal::Core(Signal::Log(line));
+        queue.send(0, &signal).unwrap();
+        let message = queue.receive(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(message.signal(), Some(signal));
+    }
+
+    #[test]
+    fn panic_report_of_full_capacity_fits_into_a_slot() {
+        use crate::ids::ActivityId;
+        use crate::panic_report::{PanicReport, PANIC_BACKTRACE_CAPACITY, PANIC_MESSAGE_CAPACITY};
+        use crate::signalling::common::signals::Signal;
+
+        let path = test_path("panic_report");
+        let region = Region::create(&path).unwrap();
+        let queue = region.scheduler_queue();
+
+        let report = PanicReport::new(
+            ActivityId::from(1),
+            &"m".repeat(PANIC_MESSAGE_CAPACITY),
+            &"b".repeat(PANIC_BACKTRACE_CAPACITY),
+        );
+        let signal = ProtocolSignal::Core(Signal::ActivityPanicked(report));
+        queue.send(0, &signal).unwrap();
+        let message = queue.receive(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(message.signal(), Some(signal));
+    }
+}
@@ -0,0 +1,167 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Shared memory based connector for the scheduler
+
+use super::queue::{Region, SCHEDULER_SOURCE};
+use crate::error::{ConnectionError, Error};
+use crate::ids::{ActivityId, AgentId};
+use crate::signalling::common::interface::ConnectScheduler;
+use crate::signalling::common::signals::Signal;
+use crate::signalling::common::socket::ProtocolSignal;
+use crate::timestamp::sync_info;
+use alloc::vec::Vec;
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use score_log::warn;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Connector for the scheduler
+pub(crate) struct ShmSchedulerConnector {
+    /// Path of the shared memory file
+    path: PathBuf,
+    /// Shared memory region, created when connecting
+    region: Option<Region>,
+
+    activity_worker_map: HashMap<ActivityId, u32>,
+    activity_agent_map: HashMap<ActivityId, AgentId>,
+
+    all_activities: Vec<ActivityId>,
+    connection_timeout: Duration,
+}
+
+impl ShmSchedulerConnector {
+    /// Create a new instance
+    pub(crate) fn new(
+        path: &Path,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        activity_agent_map: HashMap<ActivityId, AgentId>,
+        connection_timeout: Duration,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            region: None,
+            activity_worker_map: HashMap::new(),
+            activity_agent_map,
+            all_activities: activity_ids.into_iter().collect(),
+            connection_timeout,
+        }
+    }
+
+    fn region(&self) -> &Region {
+        self.region.as_ref().expect("shared memory region not created")
+    }
+
+    fn send_to_worker(&self, index: u32, signal: &Signal) -> Result<(), Error> {
+        self.region()
+            .worker_queue(index)
+            .ok_or(Error::Connection(ConnectionError::Channel("invalid shared memory worker queue")))?
+            .send(SCHEDULER_SOURCE, &ProtocolSignal::Core(*signal))
+    }
+}
+
+impl ConnectScheduler for ShmSchedulerConnector {
+    fn connect_remotes(&mut self) -> Result<(), Error> {
+        self.region = Some(Region::create(&self.path)?);
+
+        let mut missing_activities: HashSet<ActivityId> = self.all_activities.iter().cloned().collect();
+        let start_time = Instant::now();
+
+        while !missing_activities.is_empty() {
+            let elapsed = start_time.elapsed();
+            if elapsed >= self.connection_timeout {
+                return Err(Error::Io((
+                    ScoreDebugIoError(std::io::ErrorKind::TimedOut.into()),
+                    "CONNECTION_TIMEOUT",
+                )));
+            }
+            let remaining_timeout = self.connection_timeout.saturating_sub(elapsed);
+            let Some(message) = self.region().scheduler_queue().receive(remaining_timeout)? else {
+                continue;
+            };
+            match message.signal() {
+                Some(ProtocolSignal::ActivityHello(activity_id)) => {
+                    self.activity_worker_map.insert(activity_id, message.source);
+                    missing_activities.remove(&activity_id);
+                },
+                Some(other) => {
+                    warn!(
+                        "received unexpected signal {:?} from worker queue {}",
+                        other, message.source
+                    );
+                },
+                None => warn!("received undecodable message from worker queue {}", message.source),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sync_time(&mut self) -> Result<(), Error> {
+        let signal = Signal::StartupSync(sync_info());
+
+        // Send startup time to all workers
+        let workers: HashSet<u32> = self.activity_worker_map.values().copied().collect();
+        for index in workers {
+            self.send_to_worker(index, &signal)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_connected_agent_ids(&self) -> Vec<AgentId> {
+        let mut agent_ids: HashSet<AgentId> = HashSet::new();
+        for activity_id in self.activity_worker_map.keys() {
+            if let Some(agent_id) = self.activity_agent_map.get(activity_id) {
+                agent_ids.insert(*agent_id);
+            }
+        }
+        agent_ids.into_iter().collect()
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
+        let Some(message) = self.region().scheduler_queue().receive(timeout)? else {
+            return Ok(None);
+        };
+        match message.signal() {
+            Some(ProtocolSignal::Core(signal)) => Ok(Some(signal)),
+            Some(other) => {
+                warn!("received unexpected protocol signal {:?}", other);
+                Ok(None)
+            },
+            None => {
+                warn!("received undecodable message from worker queue {}", message.source);
+                Ok(None)
+            },
+        }
+    }
+
+    fn send_to_activity(&mut self, activity_id: ActivityId, signal: &Signal) -> Result<(), Error> {
+        let index = *self
+            .activity_worker_map
+            .get(&activity_id)
+            .unwrap_or_else(|| panic!("failed to find worker queue for activity ID {activity_id}"));
+        self.send_to_worker(index, signal)
+    }
+
+    fn broadcast_terminate(&mut self, signal: &Signal) -> Result<(), Error> {
+        // Collect unique worker queues to avoid sending the same message multiple times to the same worker.
+        let workers: HashSet<u32> = self.activity_worker_map.values().copied().collect();
+
+        for index in workers {
+            self.send_to_worker(index, signal)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Shared memory based connector for a worker
+
+use super::queue::Region;
+use crate::agent::ConnectionPolicy;
+use crate::error::{ConnectionError, Error, SignallingError};
+use crate::ids::ActivityId;
+use crate::signalling::common::interface::ConnectWorker;
+use crate::signalling::common::signals::Signal;
+use crate::signalling::common::socket::ProtocolSignal;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use std::path::PathBuf;
+
+/// Connector for a worker
+pub(crate) struct ShmWorkerConnector {
+    /// Path of the shared memory file created by the connector of the scheduler
+    path: PathBuf,
+    /// Shared memory region and the index of the claimed worker queue
+    region: Option<(Region, u32)>,
+    /// [ActivityId]s to announce when connecting
+    activity_ids: Vec<ActivityId>,
+    /// Policy for connecting to the scheduler
+    connection_policy: ConnectionPolicy,
+}
+
+impl ShmWorkerConnector {
+    /// Create a new instance
+    pub(crate) fn new(
+        path: PathBuf,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        connection_policy: ConnectionPolicy,
+    ) -> Self {
+        let activity_ids = activity_ids.into_iter().collect();
+        Self {
+            path,
+            region: None,
+            activity_ids,
+            connection_policy,
+        }
+    }
+
+    fn region(&self) -> (&Region, u32) {
+        let (region, index) = self.region.as_ref().expect("shared memory region not mapped");
+        (region, *index)
+    }
+}
+
+impl ConnectWorker for ShmWorkerConnector {
+    fn connect_remote(&mut self) -> Result<(), Error> {
+        let region = self.connection_policy.connect(|| Region::open(&self.path))?;
+        let index = region.claim_worker()?;
+        for id in &self.activity_ids {
+            region
+                .scheduler_queue()
+                .send(index, &ProtocolSignal::ActivityHello(*id))?;
+        }
+        self.region = Some((region, index));
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
+        let (region, index) = self.region();
+        let queue = region
+            .worker_queue(index)
+            .ok_or(Error::Connection(ConnectionError::Channel("invalid shared memory worker queue")))?;
+        match queue.receive(timeout)?.map(|message| message.signal()) {
+            Some(Some(ProtocolSignal::Core(signal))) => Ok(Some(signal)),
+            Some(_) => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
+            None => Ok(None),
+        }
+    }
+
+    fn send_to_scheduler(&mut self, signal: &Signal) -> Result<(), Error> {
+        let (region, index) = self.region();
+        region.scheduler_queue().send(index, &ProtocolSignal::Core(*signal))
+    }
+}
@@ -11,7 +11,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::error::Error;
+use crate::agent::ConnectionPolicy;
+use crate::error::{Error, SignallingError};
 use crate::ids::ActivityId;
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::common::signals::Signal;
@@ -44,6 +45,8 @@ where
     client: Option<SocketClient<S>>,
     /// [ActivityId]s to announce when connecting
     activity_ids: Vec<ActivityId>,
+    /// Policy for connecting to the scheduler
+    connection_policy: ConnectionPolicy,
 }
 
 impl<E, S> WorkerConnector<E, S>
@@ -58,7 +61,7 @@ where
             .receive(&mut self.events, timeout)
         {
             Ok(Some(ProtocolSignal::Core(signal))) => Ok(Some(signal)),
-            Ok(Some(_signal)) => Err(Error::UnexpectedProtocolSignal),
+            Ok(Some(_signal)) => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
             Ok(None) => Ok(None),
             Err(e) => Err(e),
         }
@@ -74,19 +77,24 @@ where
 
 impl TcpWorkerConnector {
     /// Create a new instance
-    pub(crate) fn new(address: SocketAddr, activity_ids: impl IntoIterator<Item = ActivityId>) -> Self {
+    pub(crate) fn new(
+        address: SocketAddr,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        connection_policy: ConnectionPolicy,
+    ) -> Self {
         let activity_ids = activity_ids.into_iter().collect();
         Self {
             endpoint: address,
             events: Events::with_capacity(32),
             client: None,
             activity_ids,
+            connection_policy,
         }
     }
 
     fn connect_remote(&mut self) -> Result<(), Error> {
         let connect_signals = self.activity_ids.iter().map(|id| ProtocolSignal::ActivityHello(*id));
-        let tcp_client = TcpClient::connect(connect_signals, self.endpoint);
+        let tcp_client = TcpClient::connect(connect_signals, self.endpoint, &self.connection_policy)?;
         self.client = Some(tcp_client);
         Ok(())
     }
@@ -94,19 +102,24 @@ impl TcpWorkerConnector {
 
 impl UnixWorkerConnector {
     /// Create a new instance
-    pub(crate) fn new(path: PathBuf, activity_ids: impl IntoIterator<Item = ActivityId>) -> Self {
+    pub(crate) fn new(
+        path: PathBuf,
+        activity_ids: impl IntoIterator<Item = ActivityId>,
+        connection_policy: ConnectionPolicy,
+    ) -> Self {
         let activity_ids = activity_ids.into_iter().collect();
         Self {
             endpoint: path,
             events: Events::with_capacity(32),
             client: None,
             activity_ids,
+            connection_policy,
         }
     }
 
     fn connect_remote(&mut self) -> Result<(), Error> {
         let connect_signals = self.activity_ids.iter().map(|id| ProtocolSignal::ActivityHello(*id));
-        let unix_client = UnixClient::connect(connect_signals, &self.endpoint);
+        let unix_client = UnixClient::connect(connect_signals, &self.endpoint, &self.connection_policy)?;
         self.client = Some(unix_client);
         Ok(())
     }
@@ -13,7 +13,7 @@
 
 //! Relays transferring signals between intra- and inter-process channels
 
-use crate::error::Error;
+use crate::error::{ConnectionError, Error};
 use crate::ids::{ActivityId, AgentId, ChannelId, WorkerId};
 use crate::signalling::common::signals::Signal;
 use crate::signalling::relayed::interface::{
@@ -81,7 +81,7 @@ impl<Inter: IsChannel, Intra: IsChannel> PrimaryReceiveRelay<Inter, Intra> {
                     error!("[PrimaryReceiveRelay]Reception timed out");
                     continue;
                 },
-                Err(Error::ChannelClosed) => {
+                Err(Error::Connection(ConnectionError::ChannelClosed)) => {
                     debug!("[PrimaryReceiveRelay]Channel closed. Exiting.");
                     return Ok(());
                 },
@@ -183,7 +183,9 @@ impl<Inter: IsChannel, Intra: IsChannel> SecondaryReceiveRelay<Inter, Intra> {
                 error!("[SecondaryReceiveRelay]Reception timed out");
                 Ok(None)
             },
-            Err(Error::ChannelClosed) => Err(Error::ChannelClosed),
+            Err(Error::Connection(ConnectionError::ChannelClosed)) => {
+                Err(Error::Connection(ConnectionError::ChannelClosed))
+            },
             Err(Error::Io((e, _))) if e.0.kind() == ErrorKind::ConnectionReset => {
                 debug!("SecondaryReceiveRelay detected connection to primary reset (expected during shutdown)");
                 Err(Error::Io((e, "connection reset")))
@@ -250,7 +252,7 @@ impl<Inter: IsChannel, Intra: IsChannel> SecondaryReceiveRelay<Inter, Intra> {
                     // Timeout is logged by helper, just continue waiting.
                     continue;
                 },
-                Err(Error::ChannelClosed) => {
+                Err(Error::Connection(ConnectionError::ChannelClosed)) => {
                     debug!("[SecondaryReceiveRelay]Connection to primary lost. Initiating self-shutdown.");
                     return; // Exit the relay thread. The workers will detect the closed channel.
                 },
@@ -279,7 +281,10 @@ impl<Inter: IsChannel, Intra: IsChannel> SecondaryReceiveRelay<Inter, Intra> {
 
             // Handle targeted signals vs. broadcast signals
             match core_signal {
-                Signal::Startup((act_id, _)) | Signal::Step((act_id, _)) | Signal::Shutdown((act_id, _)) => {
+                Signal::Startup((act_id, _))
+                | Signal::Step((act_id, _))
+                | Signal::StaleInput((act_id, _))
+                | Signal::Shutdown((act_id, _)) => {
                     // This is a targeted signal for a specific activity.
                     // Lookup corresponding worker id.
                     let Some(worker_id) = activity_worker_map.get(&act_id) else {
@@ -410,7 +415,7 @@ impl<Inter: IsChannel, Intra: IsChannel> SecondarySendRelay<Inter, Intra> {
                     error!("Reception timed out");
                     continue;
                 },
-                Err(Error::ChannelClosed) => {
+                Err(Error::Connection(ConnectionError::ChannelClosed)) => {
                     debug!("[SecondarySendRelay] detected closed channel from local workers, exiting.");
                     return Ok(());
                 },
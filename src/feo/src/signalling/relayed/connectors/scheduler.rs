@@ -11,7 +11,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
-use crate::error::Error;
+use crate::error::{ConnectionError, Error, SignallingError};
 use crate::ids::{ActivityId, AgentId, ChannelId, WorkerId};
 use crate::signalling::common::interface::ConnectScheduler;
 // Re-export for convenience
@@ -69,7 +69,7 @@ impl<Inter: IsChannel, Intra: IsChannel> SchedulerConnector<Inter, Intra> {
         if let Some(relay) = self.ipc_send_relay.as_mut() {
             relay.send_to_agent(agent_id, signal)
         } else {
-            Err(Error::ChannelNotFound(ChannelId::Agent(agent_id)))
+            Err(Error::Connection(ConnectionError::ChannelNotFound(ChannelId::Agent(agent_id))))
         }
     }
 
@@ -140,7 +140,10 @@ impl<Inter: IsChannel, Intra: IsChannel> ConnectScheduler for SchedulerConnector
     fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
         let received = self.intra_receiver.receive(timeout);
         let signal: Option<Signal> = match received {
-            Ok(Some(s)) => Some(s.try_into().map_err(|_| Error::UnexpectedProtocolSignal)?),
+            Ok(Some(s)) => Some(
+                s.try_into()
+                    .map_err(|_| Error::Signalling(SignallingError::UnexpectedProtocolSignal))?,
+            ),
             Ok(None) => None,
             Err(e) => {
                 return Err(e);
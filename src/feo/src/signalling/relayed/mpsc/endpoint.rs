@@ -13,7 +13,7 @@
 
 //! Communication endpoints for mpsc-based signalling
 
-use crate::error::Error;
+use crate::error::{Error, SignallingError};
 use crate::ids::ChannelId;
 // re-exports for convenience
 pub(crate) use crate::signalling::common::mpsc::endpoint::ProtocolMultiReceiver;
@@ -80,7 +80,7 @@ impl TryFrom<ProtocolSignal> for Signal {
     fn try_from(protocol_signal: ProtocolSignal) -> Result<Self, Self::Error> {
         match protocol_signal {
             ProtocolSignal::Core(s) => Ok(s),
-            _ => Err(Error::UnexpectedProtocolSignal),
+            _ => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
         }
     }
 }
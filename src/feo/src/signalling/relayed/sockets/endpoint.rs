@@ -13,7 +13,8 @@
 
 //! Communication endpoints for socket-based signalling
 
-use crate::error::Error;
+use crate::agent::ConnectionPolicy;
+use crate::error::{Error, SignallingError};
 use crate::ids::ChannelId;
 use crate::signalling::common::signals::Signal;
 use crate::signalling::common::socket::client::{TcpClient, UnixClient};
@@ -59,7 +60,9 @@ pub trait IsClient: HasAddress {
 
     fn receive(&mut self, events: &mut Events, timeout: Duration) -> Result<Option<ProtocolSignal>, Error>;
 
-    fn connect(address: &Self::Address, channel_id: ChannelId) -> Self;
+    fn connect(address: &Self::Address, channel_id: ChannelId, policy: &ConnectionPolicy) -> Result<Self, Error>
+    where
+        Self: Sized;
 }
 
 /// Trait for types having an address (Socket address, Unix path)
@@ -71,15 +74,17 @@ pub trait HasAddress {
 pub(crate) struct ProtocolEndpoint<C: IsClient> {
     channel_id: ChannelId,
     address: C::Address,
+    connection_policy: ConnectionPolicy,
     events: Events,
     client: Option<C>,
 }
 
 impl<C: IsClient> ProtocolEndpoint<C> {
-    pub fn new(socket_addr: C::Address, channel_id: ChannelId) -> Self {
+    pub fn new(socket_addr: C::Address, channel_id: ChannelId, connection_policy: ConnectionPolicy) -> Self {
         Self {
             channel_id,
             address: socket_addr,
+            connection_policy,
             events: Events::with_capacity(EVENTS_CAPACITY),
             client: None,
         }
@@ -111,7 +116,7 @@ impl<C: IsClient> ProtocolEndpoint<C> {
     }
 
     pub fn connect(&mut self, _timeout: Duration) -> Result<(), Error> {
-        let client = C::connect(&self.address, self.channel_id);
+        let client = C::connect(&self.address, self.channel_id, &self.connection_policy)?;
         self.client = Some(client);
         Ok(())
     }
@@ -264,7 +269,7 @@ impl TryFrom<ProtocolSignal> for Signal {
     fn try_from(protocol_signal: ProtocolSignal) -> Result<Self, Self::Error> {
         match protocol_signal {
             ProtocolSignal::Core(s) => Ok(s),
-            _ => Err(Error::UnexpectedProtocolSignal),
+            _ => Err(Error::Signalling(SignallingError::UnexpectedProtocolSignal)),
         }
     }
 }
@@ -348,8 +353,8 @@ impl IsClient for TcpClient {
         self.receive(events, timeout)
     }
 
-    fn connect(address: &Self::Address, channel_id: ChannelId) -> Self {
-        Self::connect([ProtocolSignal::ChannelHello(channel_id)], *address)
+    fn connect(address: &Self::Address, channel_id: ChannelId, policy: &ConnectionPolicy) -> Result<Self, Error> {
+        Self::connect([ProtocolSignal::ChannelHello(channel_id)], *address, policy)
     }
 }
 
@@ -366,7 +371,7 @@ impl IsClient for UnixClient {
         self.receive(events, timeout)
     }
 
-    fn connect(address: &Self::Address, channel_id: ChannelId) -> Self {
-        Self::connect([ProtocolSignal::ChannelHello(channel_id)], address)
+    fn connect(address: &Self::Address, channel_id: ChannelId, policy: &ConnectionPolicy) -> Result<Self, Error> {
+        Self::connect([ProtocolSignal::ChannelHello(channel_id)], address, policy)
     }
 }
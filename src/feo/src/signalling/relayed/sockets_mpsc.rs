@@ -16,6 +16,7 @@
 //! - Inter-process signalling is sockets-based (unix sockets or tcp sockets)
 //! - Intra-process signalling uses mpsc channels
 
+use crate::agent::ConnectionPolicy;
 use crate::ids::{ActivityId, AgentId, ChannelId, RelayId, WorkerId};
 use crate::signalling::common::socket::client::{TcpClient, UnixClient};
 use crate::signalling::common::socket::server::{TcpServer, UnixServer};
@@ -67,12 +68,12 @@ impl IsChannel for InterChannelTcp {
 }
 
 impl SocketChannel for InterChannelTcp {
-    fn new_receiver(address: Self::Address, channel_id: ChannelId) -> Self::Receiver {
-        sockets::endpoint::ProtocolReceiver::<TcpClient>::new(address, channel_id)
+    fn new_receiver(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Receiver {
+        sockets::endpoint::ProtocolReceiver::<TcpClient>::new(address, channel_id, policy)
     }
 
-    fn new_sender(address: Self::Address, channel_id: ChannelId) -> Self::Sender {
-        sockets::endpoint::ProtocolSender::<TcpClient>::new(address, channel_id)
+    fn new_sender(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Sender {
+        sockets::endpoint::ProtocolSender::<TcpClient>::new(address, channel_id, policy)
     }
 
     fn new_multi_receiver<'s, T>(channel_ids: &'s T, address: Self::Address) -> Self::MultiReceiver
@@ -106,12 +107,12 @@ impl IsChannel for InterChannelUnix {
 }
 
 impl SocketChannel for InterChannelUnix {
-    fn new_receiver(address: Self::Address, channel_id: ChannelId) -> Self::Receiver {
-        sockets::endpoint::ProtocolReceiver::<UnixClient>::new(address, channel_id)
+    fn new_receiver(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Receiver {
+        sockets::endpoint::ProtocolReceiver::<UnixClient>::new(address, channel_id, policy)
     }
 
-    fn new_sender(address: Self::Address, channel_id: ChannelId) -> Self::Sender {
-        sockets::endpoint::ProtocolSender::<UnixClient>::new(address, channel_id)
+    fn new_sender(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Sender {
+        sockets::endpoint::ProtocolSender::<UnixClient>::new(address, channel_id, policy)
     }
 
     fn new_multi_receiver<'s, T>(channel_ids: &'s T, address: Self::Address) -> Self::MultiReceiver
@@ -243,6 +244,7 @@ impl<Inter: SocketChannel> secondary::SecondaryConnector<Inter, IntraChannel> {
         bind_address_senders: Inter::Address,
         bind_address_receivers: Inter::Address,
         timeout: Duration,
+        connection_policy: ConnectionPolicy,
     ) -> (Self, HashMap<WorkerId, Builder<WorkerConnector>>) {
         let worker_ids: HashSet<WorkerId> = activity_worker_map.values().copied().collect();
 
@@ -254,6 +256,7 @@ impl<Inter: SocketChannel> secondary::SecondaryConnector<Inter, IntraChannel> {
                 bind_address_senders,
                 bind_address_receivers,
                 timeout,
+                connection_policy,
             );
 
         // create the worker connector builders using the channel endpoint builders
@@ -279,6 +282,7 @@ impl<Inter: SocketChannel> secondary::SecondaryConnector<Inter, IntraChannel> {
         bind_address_senders: Inter::Address,
         bind_address_receivers: Inter::Address,
         timeout: Duration,
+        connection_policy: ConnectionPolicy,
     ) -> (
         Self,
         ChannelToSenderBuilderMap<IntraChannel>,
@@ -288,12 +292,13 @@ impl<Inter: SocketChannel> secondary::SecondaryConnector<Inter, IntraChannel> {
 
         let (intra_receiver_builder, worker_sender_builders) = intra_builders::multi_receiver_builder(&channel_ids);
         let intra_receiver = intra_receiver_builder();
-        let inter_sender = Inter::new_sender(bind_address_senders, ChannelId::Agent(agent_id));
+        let inter_sender = Inter::new_sender(bind_address_senders, ChannelId::Agent(agent_id), connection_policy);
 
         let local_to_ipc_relay = SecondarySendRelay::new(inter_sender, intra_receiver, timeout);
 
         let (intra_sender_builder, worker_receiver_builders) = intra_builders::multi_sender_builder(&channel_ids);
-        let inter_receiver_builder = Inter::receiver_builder(ChannelId::Agent(agent_id), bind_address_receivers);
+        let inter_receiver_builder =
+            Inter::receiver_builder(ChannelId::Agent(agent_id), bind_address_receivers, connection_policy);
 
         let ipc_to_local_relay = SecondaryReceiveRelay::new(
             activity_worker_map,
@@ -311,9 +316,9 @@ impl<Inter: SocketChannel> secondary::SecondaryConnector<Inter, IntraChannel> {
 }
 
 pub(crate) trait SocketChannel: IsChannel + HasAddress {
-    fn new_receiver(address: Self::Address, channel_id: ChannelId) -> Self::Receiver;
+    fn new_receiver(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Receiver;
 
-    fn new_sender(address: Self::Address, channel_id: ChannelId) -> Self::Sender;
+    fn new_sender(address: Self::Address, channel_id: ChannelId, policy: ConnectionPolicy) -> Self::Sender;
 
     fn new_multi_receiver<'s, T>(channel_ids: &'s T, address: Self::Address) -> Self::MultiReceiver
     where
@@ -333,8 +338,12 @@ pub(crate) trait SocketChannel: IsChannel + HasAddress {
     }
 
     // Returns a builder of a receiver
-    fn receiver_builder(channel_id: ChannelId, address: Self::Address) -> Builder<Self::Receiver> {
-        Box::new(move || Self::new_receiver(address, channel_id))
+    fn receiver_builder(
+        channel_id: ChannelId,
+        address: Self::Address,
+        policy: ConnectionPolicy,
+    ) -> Builder<Self::Receiver> {
+        Box::new(move || Self::new_receiver(address, channel_id, policy))
     }
 }
 
@@ -0,0 +1,293 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Hot-standby primary agent
+//!
+//! A standby process runs next to the active primary agent of the direct signalling mode.
+//! The active primary agent configured with a
+//! [standby](crate::agent::direct::primary::PrimaryConfig::standby) address mirrors the state of
+//! its scheduler to the standby after every completed cycle: the connected agents and the cycle
+//! counters of the task chains. The standby blocks in [Standby::wait_for_takeover] until the mirror
+//! connection is closed or no state arrived for [StandbyConfig::takeover_cycles] cycles. It then
+//! starts its own primary agent with the returned state as
+//! [resume](crate::agent::direct::primary::PrimaryConfig::resume) on the failover endpoint.
+//!
+//! Secondary agents configured with a
+//! [failover endpoint](crate::agent::direct::secondary::SecondaryConfig::failover_endpoint)
+//! reconnect their workers to the standby once their connection to the active primary agent is closed.
+//! Activities already started are not started again, and the resumed scheduler continues the cycle
+//! counters of the task chains.
+//!
+//! Failover is supported for the TCP and Unix socket signalling.
+
+use crate::agent::NodeAddress;
+use crate::error::{ConnectionError, Error};
+use alloc::vec::Vec;
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use score_log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// Minimum time between two attempts of the active primary agent to connect to the standby
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Scheduler state mirrored from the active primary agent to the standby
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirroredState {
+    /// Startup time of the active primary agent in nanoseconds since the unix epoch
+    pub startup_ns: u64,
+    /// Number of completed cycles per task chain ID
+    pub cycles: Vec<(u64, u64)>,
+    /// IDs of the remote agents connected to the active primary agent
+    pub agents: Vec<u64>,
+}
+
+/// Configuration of a standby primary agent
+pub struct StandbyConfig {
+    /// Endpoint on which the standby receives the state mirrored by the active primary agent
+    pub mirror: NodeAddress,
+    /// Cycle time of the main task chain
+    pub cycle_time: Duration,
+    /// Number of cycles without mirrored state after which the standby takes over
+    pub takeover_cycles: u32,
+}
+
+/// Standby waiting to take over from the active primary agent
+pub struct Standby {
+    listener: Listener,
+    takeover_timeout: Duration,
+}
+
+impl Standby {
+    /// Bind the mirror endpoint of the standby
+    pub fn new(config: StandbyConfig) -> Result<Self, Error> {
+        let listener = match config.mirror {
+            NodeAddress::Tcp(addr) => TcpListener::bind(addr).map(Listener::Tcp),
+            NodeAddress::UnixSocket(path) => {
+                // Remove a stale socket left by a previous standby
+                let _ = std::fs::remove_file(&path);
+                UnixListener::bind(path).map(Listener::Unix)
+            },
+            _ => {
+                return Err(Error::Connection(ConnectionError::Channel(
+                    "standby mirror requires a TCP or Unix socket endpoint",
+                )))
+            },
+        }
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to bind standby mirror endpoint")))?;
+        let takeover_timeout = Duration(config.cycle_time.0 * config.takeover_cycles.max(1));
+        Ok(Self {
+            listener,
+            takeover_timeout,
+        })
+    }
+
+    /// Wait until the active primary agent fails and return its last mirrored state
+    ///
+    /// Blocks until the active primary agent connected and mirrored its state at least once.
+    /// A failure is detected when the mirror connection is closed or no state arrived within the
+    /// takeover timeout. A primary agent restarted meanwhile may connect again before that.
+    pub fn wait_for_takeover(self) -> Result<MirroredState, Error> {
+        let mut last_state = None;
+        loop {
+            let stream = self
+                .listener
+                .accept()
+                .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to accept mirror connection")))?;
+            info!("Active primary agent connected to standby");
+            stream
+                .set_read_timeout(self.takeover_timeout)
+                .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to set mirror read timeout")))?;
+            let mut reader = BufReader::new(stream);
+            loop {
+                match read_state(&mut reader) {
+                    Ok(state) => last_state = Some(state),
+                    Err(e) => {
+                        warn!("Mirror connection of active primary agent lost: {:?}", ScoreDebugIoError(e));
+                        break;
+                    },
+                }
+            }
+            if let Some(state) = last_state.take() {
+                info!("Standby taking over from failed primary agent");
+                return Ok(state);
+            }
+            debug!("Mirror connection closed before any state arrived, waiting for reconnection");
+        }
+    }
+}
+
+/// Mirror of the scheduler state of the active primary agent to the standby
+pub(crate) struct StandbyMirror {
+    address: NodeAddress,
+    stream: Option<Stream>,
+    last_attempt: Option<Instant>,
+}
+
+impl StandbyMirror {
+    /// Create a mirror to the standby at the given address, connecting lazily
+    pub(crate) fn new(address: NodeAddress) -> Self {
+        Self {
+            address,
+            stream: None,
+            last_attempt: None,
+        }
+    }
+
+    /// Send the current state to the standby
+    ///
+    /// Failures are logged and retried after [RECONNECT_INTERVAL], as a missing standby must not
+    /// disturb the active primary agent.
+    pub(crate) fn send(&mut self, state: &MirroredState) {
+        if self.stream.is_none() {
+            if self.last_attempt.is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL) {
+                return;
+            }
+            self.last_attempt = Some(Instant::now());
+            match Stream::connect(&self.address) {
+                Ok(stream) => {
+                    info!("Connected to standby primary agent");
+                    self.stream = Some(stream);
+                },
+                Err(e) => {
+                    debug!("Failed to connect to standby primary agent: {:?}", ScoreDebugIoError(e));
+                    return;
+                },
+            }
+        }
+
+        let Ok(frame) = postcard::to_allocvec_cobs(state) else {
+            warn!("Failed to serialize mirrored state");
+            return;
+        };
+        if let Some(Err(e)) = self.stream.as_mut().map(|stream| stream.write_all(&frame)) {
+            warn!("Failed to mirror state to standby primary agent: {:?}", ScoreDebugIoError(e));
+            self.stream = None;
+        }
+    }
+}
+
+/// Read the next COBS framed state from the mirror connection
+fn read_state(reader: &mut impl BufRead) -> io::Result<MirroredState> {
+    let mut frame = Vec::new();
+    if reader.read_until(0, &mut frame)? == 0 || frame.last() != Some(&0) {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    postcard::from_bytes_cobs(&mut frame).map_err(|_| ErrorKind::InvalidData.into())
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Listener::Tcp(listener) => listener.accept().map(|(stream, _)| Stream::Tcp(stream)),
+            Listener::Unix(listener) => listener.accept().map(|(stream, _)| Stream::Unix(stream)),
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(address: &NodeAddress) -> io::Result<Self> {
+        let stream = match address {
+            NodeAddress::Tcp(addr) => Stream::Tcp(TcpStream::connect(addr)?),
+            NodeAddress::UnixSocket(path) => Stream::Unix(UnixStream::connect(path)?),
+            _ => return Err(ErrorKind::Unsupported.into()),
+        };
+        // Do not block the scheduler on a stalled standby
+        let timeout = Some(RECONNECT_INTERVAL.into());
+        match &stream {
+            Stream::Tcp(stream) => stream.set_write_timeout(timeout)?,
+            Stream::Unix(stream) => stream.set_write_timeout(timeout)?,
+        }
+        Ok(stream)
+    }
+
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_read_timeout(Some(timeout.into())),
+            Stream::Unix(stream) => stream.set_read_timeout(Some(timeout.into())),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standby_takes_over_with_last_state() {
+        let path = std::env::temp_dir().join(alloc::format!("feo_standby_test_{}.sock", std::process::id()));
+        let standby = Standby::new(StandbyConfig {
+            mirror: NodeAddress::UnixSocket(path.clone()),
+            cycle_time: Duration::from_millis(10),
+            takeover_cycles: 5,
+        })
+        .unwrap();
+
+        let primary = std::thread::spawn(move || {
+            let mut mirror = StandbyMirror::new(NodeAddress::UnixSocket(path.clone()));
+            for cycle in 1..=3 {
+                mirror.send(&MirroredState {
+                    startup_ns: 1,
+                    cycles: alloc::vec![(0, cycle)],
+                    agents: alloc::vec![200],
+                });
+            }
+            // The primary agent fails by closing the mirror connection
+            drop(mirror);
+            let _ = std::fs::remove_file(&path);
+        });
+
+        let state = standby.wait_for_takeover().unwrap();
+        primary.join().unwrap();
+        assert_eq!(state.cycles, [(0, 3)]);
+        assert_eq!(state.agents, [200]);
+    }
+}
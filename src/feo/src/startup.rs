@@ -0,0 +1,126 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Dependency-ordered startup of the activities
+//!
+//! Without further configuration, the scheduler sends the startup signal to all activities at once,
+//! so they start up in an arbitrary order. An activity needing a resource provided by another activity
+//! during its startup, e.g. calibration data loaded by it, declares a startup dependency on that
+//! activity in the [StartupOrder] of the primary agent.
+//!
+//! The activities are then started in phases: the first phase holds the activities without startup
+//! dependencies, each following phase the activities whose startup dependencies are all in earlier
+//! phases. A phase is started once all activities of the previous phase are ready. Each phase has to
+//! complete within the phase timeout, otherwise startup is aborted, naming the activities the phase
+//! is blocked on.
+//!
+//! Startup dependencies are independent of the step dependencies and may cross task chains.
+
+use crate::dependency;
+use crate::error::Error;
+use crate::ids::ActivityId;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use std::collections::HashMap;
+
+/// Startup dependencies of the activities of an application
+#[derive(Debug, Clone, Default)]
+pub struct StartupOrder {
+    /// Activities started up before the given activity, per activity
+    pub dependencies: HashMap<ActivityId, Vec<ActivityId>>,
+    /// Timeout of each phase, the startup timeout of the primary agent if not set
+    pub phase_timeout: Option<Duration>,
+}
+
+impl StartupOrder {
+    /// Create a startup order starting all activities at once
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start up the given activity after the given other activity is ready
+    pub fn with_dependency(mut self, id: ActivityId, starts_after: ActivityId) -> Self {
+        self.dependencies.entry(id).or_default().push(starts_after);
+        self
+    }
+
+    /// Limit the duration of each phase to the given timeout
+    pub fn with_phase_timeout(mut self, timeout: Duration) -> Self {
+        self.phase_timeout = Some(timeout);
+        self
+    }
+
+    /// Check that the startup dependencies name known activities and do not form cycles
+    pub(crate) fn validate(&self, activity_depends: &HashMap<ActivityId, Vec<ActivityId>>) -> Result<(), Error> {
+        if let Some(id) = self.dependencies.keys().find(|id| !activity_depends.contains_key(id)) {
+            return Err(Error::ActivityNotFound(*id));
+        }
+        let graph: HashMap<ActivityId, Vec<ActivityId>> = activity_depends
+            .keys()
+            .map(|id| (*id, self.dependencies.get(id).cloned().unwrap_or_default()))
+            .collect();
+        dependency::validate(&graph, &[]).map_err(Error::from)
+    }
+
+    /// Group the given activities into phases started one after the other, each sorted by ID
+    pub(crate) fn phases(&self, activities: impl IntoIterator<Item = ActivityId>) -> Vec<Vec<ActivityId>> {
+        let mut pending: Vec<ActivityId> = activities.into_iter().collect();
+        pending.sort();
+        let mut phase_of: HashMap<ActivityId, usize> = HashMap::new();
+        let mut phases: Vec<Vec<ActivityId>> = Vec::new();
+        while !pending.is_empty() {
+            let phase = phases.len();
+            // Activities whose startup dependencies are all in earlier phases
+            let (ready, blocked): (Vec<ActivityId>, Vec<ActivityId>) = pending.iter().copied().partition(|id| {
+                self.dependencies
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .all(|dependency| phase_of.get(dependency).is_some_and(|p| *p < phase))
+            });
+            // Validated to be acyclic; start the rest at once should this not hold
+            let ready = if ready.is_empty() { blocked } else { ready };
+            phase_of.extend(ready.iter().map(|id| (*id, phase)));
+            pending.retain(|id| !phase_of.contains_key(id));
+            phases.push(ready);
+        }
+        phases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activities_are_started_in_phases() {
+        use alloc::vec;
+
+        let ids = [1, 2, 3, 4].map(ActivityId::new);
+        let depends: HashMap<ActivityId, Vec<ActivityId>> = ids.iter().map(|id| (*id, Vec::new())).collect();
+
+        assert_eq!(StartupOrder::new().phases(ids), [ids.to_vec()]);
+
+        let order = StartupOrder::new()
+            .with_dependency(ids[0], ids[2])
+            .with_dependency(ids[1], ids[0])
+            .with_dependency(ids[3], ids[2]);
+        assert!(order.validate(&depends).is_ok());
+        assert_eq!(order.phases(ids), [vec![ids[2]], vec![ids[0], ids[3]], vec![ids[1]]]);
+
+        let cyclic = order.with_dependency(ids[2], ids[1]);
+        assert!(matches!(cyclic.validate(&depends), Err(Error::Dependencies(_))));
+        let unknown = StartupOrder::new().with_dependency(ids[0], ActivityId::new(5));
+        assert!(matches!(unknown.validate(&depends), Err(Error::Dependencies(_))));
+    }
+}
@@ -0,0 +1,285 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Cycle statistics of the primary agent
+//!
+//! The scheduler records the start jitter and the duration of each cycle per task chain and the duration
+//! of each step per activity in fixed-size histograms. A [CycleStatistics] handle passed to the primary agent
+//! gives access to the statistics while the agent is running and optionally has them logged periodically.
+
+use crate::ids::{ActivityId, ChainId};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt;
+use feo_time::Duration;
+use std::sync::Mutex;
+
+/// Number of linear sub-buckets per power of two
+const SUB_BUCKETS: usize = 8;
+
+/// Number of buckets of a histogram
+///
+/// Covers durations up to about half a second with a relative error below 12.5%.
+/// Longer durations are counted in the last bucket.
+const BUCKETS: usize = 136;
+
+/// Histogram of durations with microsecond resolution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    /// Number of recorded durations per bucket
+    counts: [u64; BUCKETS],
+    /// Number of recorded durations
+    count: u64,
+    /// Shortest recorded duration in microseconds
+    min: u64,
+    /// Longest recorded duration in microseconds
+    max: u64,
+    /// Sum of all recorded durations in microseconds
+    sum: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BUCKETS],
+            count: 0,
+            min: u64::MAX,
+            max: 0,
+            sum: 0,
+        }
+    }
+}
+
+impl Histogram {
+    /// Record a duration
+    pub fn record(&mut self, duration: Duration) {
+        let micros = duration.0.as_micros().min(u64::MAX as u128) as u64;
+        self.counts[bucket(micros)] += 1;
+        self.count += 1;
+        self.min = self.min.min(micros);
+        self.max = self.max.max(micros);
+        self.sum = self.sum.saturating_add(micros);
+    }
+
+    /// Number of recorded durations
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Shortest recorded duration
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.min))
+    }
+
+    /// Longest recorded duration
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.max))
+    }
+
+    /// Sum of the recorded durations
+    pub fn sum(&self) -> Duration {
+        Duration::from_micros(self.sum)
+    }
+
+    /// Mean of the recorded durations
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_micros(self.sum / self.count))
+    }
+
+    /// Duration not exceeded by the given percentage of the recorded durations, e.g. 99 for the p99
+    ///
+    /// The result is the upper bound of the histogram bucket holding the percentile, limited
+    /// to the longest recorded duration.
+    pub fn percentile(&self, percent: u8) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (self.count * u64::from(percent.min(100))).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_micros(bucket_max(index).min(self.max)));
+            }
+        }
+        self.max()
+    }
+}
+
+/// Index of the bucket counting the given number of microseconds
+fn bucket(micros: u64) -> usize {
+    if micros < SUB_BUCKETS as u64 {
+        return micros as usize;
+    }
+    let exponent = 63 - micros.leading_zeros() as usize;
+    let shift = exponent - SUB_BUCKETS.trailing_zeros() as usize;
+    let sub_bucket = (micros >> shift) as usize - SUB_BUCKETS;
+    (SUB_BUCKETS + shift * SUB_BUCKETS + sub_bucket).min(BUCKETS - 1)
+}
+
+/// Largest number of microseconds counted in the given bucket
+fn bucket_max(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+    if index == BUCKETS - 1 {
+        return u64::MAX;
+    }
+    let shift = (index - SUB_BUCKETS) / SUB_BUCKETS;
+    let sub_bucket = (index - SUB_BUCKETS) % SUB_BUCKETS;
+    (((SUB_BUCKETS + sub_bucket + 1) as u64) << shift) - 1
+}
+
+/// Statistics of a task chain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainStats {
+    /// Delay of the cycle starts after their planned start
+    pub start_jitter: Histogram,
+    /// Durations of the cycles
+    pub duration: Histogram,
+    /// Number of cycles which took at least the cycle time, see [overrun](crate::overrun)
+    pub overruns: u64,
+}
+
+/// Statistics of all task chains and activities of the primary agent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleStats {
+    /// Statistics per task chain
+    pub chains: BTreeMap<ChainId, ChainStats>,
+    /// Step durations per activity
+    pub activities: BTreeMap<ActivityId, Histogram>,
+}
+
+impl fmt::Display for CycleStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (id, chain) in self.chains.iter() {
+            writeln!(
+                f,
+                "{id}: {} cycles, {} overruns, duration {}, start jitter {}",
+                chain.duration.count(),
+                chain.overruns,
+                Summary(&chain.duration),
+                Summary(&chain.start_jitter)
+            )?;
+        }
+        for (id, steps) in self.activities.iter() {
+            writeln!(f, "{id}: {} steps, duration {}", steps.count(), Summary(steps))?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a histogram as shown in the statistics output
+struct Summary<'a>(&'a Histogram);
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (Some(min), Some(mean), Some(p99), Some(max)) =
+            (self.0.min(), self.0.mean(), self.0.percentile(99), self.0.max())
+        else {
+            return write!(f, "-");
+        };
+        write!(f, "min {:?} mean {:?} p99 {:?} max {:?}", min.0, mean.0, p99.0, max.0)
+    }
+}
+
+/// Handle to the cycle statistics collected by the scheduler of the primary agent
+///
+/// Clones share the same statistics, so a clone kept by the application can query the statistics
+/// collected by the scheduler.
+#[derive(Debug, Clone, Default)]
+pub struct CycleStatistics {
+    stats: Arc<Mutex<CycleStats>>,
+    log_interval: Option<Duration>,
+}
+
+impl CycleStatistics {
+    /// Create a new, empty collection of statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Have the scheduler log the statistics at the given interval
+    pub fn with_log_interval(mut self, interval: Duration) -> Self {
+        self.log_interval = Some(interval);
+        self
+    }
+
+    /// Get a copy of the statistics collected so far
+    pub fn snapshot(&self) -> CycleStats {
+        self.stats.lock().expect("poisoned lock").clone()
+    }
+
+    /// Clear the statistics collected so far
+    pub fn reset(&self) {
+        *self.stats.lock().expect("poisoned lock") = CycleStats::default();
+    }
+
+    /// Interval at which the statistics are logged, if any
+    pub(crate) fn log_interval(&self) -> Option<Duration> {
+        self.log_interval
+    }
+
+    /// Record a finished cycle of a task chain
+    pub(crate) fn record_cycle(&self, chain: ChainId, start_jitter: Duration, duration: Duration) {
+        let mut stats = self.stats.lock().expect("poisoned lock");
+        let chain = stats.chains.entry(chain).or_default();
+        chain.start_jitter.record(start_jitter);
+        chain.duration.record(duration);
+    }
+
+    /// Record an overrunning cycle of a task chain
+    pub(crate) fn record_overrun(&self, chain: ChainId) {
+        let mut stats = self.stats.lock().expect("poisoned lock");
+        stats.chains.entry(chain).or_default().overruns += 1;
+    }
+
+    /// Record a finished step of an activity
+    pub(crate) fn record_step(&self, activity: ActivityId, duration: Duration) {
+        let mut stats = self.stats.lock().expect("poisoned lock");
+        stats.activities.entry(activity).or_default().record(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.percentile(99), None);
+
+        for micros in 1..=1000 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.min(), Some(Duration::from_micros(1)));
+        assert_eq!(histogram.max(), Some(Duration::from_micros(1000)));
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(500)));
+
+        // Percentiles are exact up to the bucket resolution
+        let p99 = histogram.percentile(99).unwrap().0.as_micros();
+        assert!((990..=1000).contains(&p99), "p99 {p99}");
+        let p50 = histogram.percentile(50).unwrap().0.as_micros();
+        assert!((500..=500 * 9 / 8).contains(&p50), "p50 {p50}");
+        assert_eq!(histogram.percentile(100), histogram.max());
+
+        // Every bucket holds the values between the maxima of its predecessor and itself
+        for micros in [0, 7, 8, 9, 15, 16, 17, 1000, 123_456] {
+            let index = bucket(micros);
+            assert!(micros <= bucket_max(index));
+            assert!(index == 0 || micros > bucket_max(index - 1));
+        }
+    }
+}
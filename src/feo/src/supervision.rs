@@ -0,0 +1,231 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Heartbeat supervision of secondary agents
+//!
+//! The workers of secondary agents periodically send a heartbeat to the primary agent,
+//! see the `heartbeat_interval` of their configuration. The scheduler of the primary agent
+//! marks a secondary agent as unhealthy if no heartbeat has been received from it within
+//! the configured supervision window.
+
+use crate::ids::{ActivityId, AgentId};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use feo_time::{Duration, Instant};
+use score_log::{error, info, ScoreDebug};
+use std::collections::HashMap;
+
+/// Handler called by the scheduler when an agent becomes unhealthy, returning the reaction to apply
+pub type UnhealthyHandler = Box<dyn FnMut(AgentId) -> SupervisionAction + Send>;
+
+/// Reaction of the scheduler to an unhealthy agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ScoreDebug)]
+pub enum SupervisionAction {
+    /// Log the missed heartbeat and continue
+    #[default]
+    Log,
+    /// Stop stepping the activities of the unhealthy agent for the rest of the run.
+    ///
+    /// Dependent activities are stepped as if the degraded activities had finished their steps.
+    Degrade,
+    /// Shut down all activities and agents
+    Shutdown,
+}
+
+/// Heartbeat supervision configuration of the primary agent
+#[derive(Default)]
+pub struct Supervision {
+    /// Maximum time between two heartbeats of a secondary agent.
+    ///
+    /// Agents are not supervised if not set. The window must be larger than the heartbeat interval
+    /// of the secondary agents and the longest step of their activities, because workers
+    /// do not send heartbeats while running an activity.
+    pub window: Option<Duration>,
+    /// Optional handler deciding on the reaction to an unhealthy agent.
+    ///
+    /// Without a handler, unhealthy agents are only logged.
+    pub on_unhealthy: Option<UnhealthyHandler>,
+}
+
+impl Supervision {
+    /// Create a supervision configuration with the given supervision window
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: Some(window),
+            on_unhealthy: None,
+        }
+    }
+
+    /// Set the handler called when an agent becomes unhealthy
+    pub fn with_unhealthy_handler(
+        mut self,
+        handler: impl FnMut(AgentId) -> SupervisionAction + Send + 'static,
+    ) -> Self {
+        self.on_unhealthy = Some(Box::new(handler));
+        self
+    }
+}
+
+/// Health state of a supervised agent
+struct AgentHealth {
+    /// Time of the last received heartbeat
+    last_seen: Instant,
+    /// Whether the last heartbeat has been received within the supervision window
+    healthy: bool,
+}
+
+/// Heartbeat bookkeeping of the scheduler
+pub(crate) struct AgentSupervisor {
+    /// Supervision configuration
+    config: Supervision,
+    /// Activities per agent
+    agent_activities: HashMap<AgentId, Vec<ActivityId>>,
+    /// Health state per supervised agent
+    agents: HashMap<AgentId, AgentHealth>,
+}
+
+impl AgentSupervisor {
+    /// Create a supervisor from the given configuration and assignment of activities to agents
+    pub(crate) fn new(config: Supervision, activity_agents: impl IntoIterator<Item = (ActivityId, AgentId)>) -> Self {
+        let mut agent_activities: HashMap<AgentId, Vec<ActivityId>> = HashMap::new();
+        for (activity_id, agent_id) in activity_agents {
+            agent_activities.entry(agent_id).or_default().push(activity_id);
+        }
+        Self {
+            config,
+            agent_activities,
+            agents: HashMap::new(),
+        }
+    }
+
+    /// Start supervising the given agents
+    pub(crate) fn start(&mut self, agents: impl IntoIterator<Item = AgentId>) {
+        if self.config.window.is_none() {
+            return;
+        }
+        let now = Instant::now();
+        self.agents = agents
+            .into_iter()
+            .map(|id| {
+                let health = AgentHealth {
+                    last_seen: now,
+                    healthy: true,
+                };
+                (id, health)
+            })
+            .collect();
+    }
+
+    /// Check whether any agent is supervised
+    pub(crate) fn is_active(&self) -> bool {
+        !self.agents.is_empty()
+    }
+
+    /// Record a heartbeat of the given agent
+    pub(crate) fn heartbeat(&mut self, agent_id: AgentId) {
+        let Some(health) = self.agents.get_mut(&agent_id) else {
+            return;
+        };
+        health.last_seen = Instant::now();
+        if !health.healthy {
+            info!(
+                "Received heartbeat from unhealthy agent {}, marking it healthy",
+                agent_id
+            );
+            health.healthy = true;
+        }
+    }
+
+    /// Check whether the given agent is healthy, which is the case for all agents without supervision
+    pub(crate) fn is_healthy(&self, agent_id: &AgentId) -> bool {
+        self.agents.get(agent_id).is_none_or(|health| health.healthy)
+    }
+
+    /// Get the time left until the supervision window of the next healthy agent expires
+    pub(crate) fn next_check(&self) -> Option<Duration> {
+        let window = self.config.window?;
+        self.agents
+            .values()
+            .filter(|health| health.healthy)
+            .map(|health| window.saturating_sub(health.last_seen.elapsed()))
+            .min()
+    }
+
+    /// Mark all agents which missed their heartbeat as unhealthy
+    ///
+    /// Returns the newly unhealthy agents together with the reaction to apply.
+    pub(crate) fn check(&mut self) -> Vec<(AgentId, SupervisionAction)> {
+        let Some(window) = self.config.window else {
+            return Vec::new();
+        };
+        let mut unhealthy = Vec::new();
+        for (id, health) in self.agents.iter_mut() {
+            let elapsed = health.last_seen.elapsed();
+            if !health.healthy || elapsed <= window {
+                continue;
+            }
+            health.healthy = false;
+            error!(
+                "Agent {} missed its heartbeat: last heartbeat {:?} ago, supervision window {:?}",
+                id, elapsed, window
+            );
+            let action = match self.config.on_unhealthy.as_mut() {
+                Some(handler) => handler(*id),
+                None => SupervisionAction::Log,
+            };
+            unhealthy.push((*id, action));
+        }
+        unhealthy
+    }
+
+    /// Get the agent running the given activity, if known
+    pub(crate) fn agent_of(&self, activity_id: &ActivityId) -> Option<AgentId> {
+        self.agent_activities
+            .iter()
+            .find(|(_, activities)| activities.contains(activity_id))
+            .map(|(agent_id, _)| *agent_id)
+    }
+
+    /// Get the activities running on the given agent
+    pub(crate) fn activities_of(&self, agent_id: &AgentId) -> &[ActivityId] {
+        self.agent_activities
+            .get(agent_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missed_heartbeat_marks_agent_unhealthy() {
+        let supervision =
+            Supervision::new(Duration::from_millis(10)).with_unhealthy_handler(|_| SupervisionAction::Degrade);
+        let agent = AgentId::new(1);
+        let mut supervisor = AgentSupervisor::new(supervision, [(ActivityId::new(7), agent)]);
+        supervisor.start([agent]);
+        assert!(supervisor.check().is_empty());
+
+        std::thread::sleep(core::time::Duration::from_millis(20));
+        assert_eq!(supervisor.check(), [(agent, SupervisionAction::Degrade)]);
+        assert_eq!(supervisor.activities_of(&agent), [ActivityId::new(7)]);
+
+        // An unhealthy agent is reported only once and recovers with its next heartbeat
+        assert!(supervisor.check().is_empty());
+        assert_eq!(supervisor.next_check(), None);
+        supervisor.heartbeat(agent);
+        assert!(supervisor.next_check().is_some());
+    }
+}
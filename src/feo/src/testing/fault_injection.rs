@@ -0,0 +1,465 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Injection of faults for resilience testing
+//!
+//! A [FaultPlan] describes which faults to inject into a running FEO application:
+//!
+//! - [Fault::DelaySignal], [Fault::DropSignal] and [Fault::DuplicateSignal] affect the lifecycle signals
+//!   exchanged between the scheduler and the activities, i.e. startup, step and shutdown triggers
+//!   and the ready and failure responses
+//! - [Fault::Panic] and [Fault::Overrun] affect the steps of activities, which panic or take
+//!   longer than usual, respectively
+//!
+//! Faults are decided by a hash of the seed of the plan, the rule, the activity and the number of
+//! events of the activity seen by the rule so far, so a plan injects the same faults in each run,
+//! independent of the interleaving of the threads of the application:
+//!
+//! ```ignore
+//! let injector = fault_injection::install(
+//!     FaultPlan::new(42)
+//!         .with_rule(FaultRule::new(Fault::DropSignal).activity(camera).direction(Direction::ToScheduler).after(10).limit(1))
+//!         .with_rule(FaultRule::new(Fault::Overrun(Duration::from_millis(50))).probability(0.1)),
+//! );
+//! // run the agents...
+//! assert_eq!(injector.injected().len(), 1 + ...);
+//! ```
+//!
+//! The plan is installed process-wide and must be installed before starting the agents of the process.
+//! Signal faults are injected by the scheduler of the primary agent, so they only apply when installed
+//! in the process of the primary agent. Activity faults are injected by the workers running the activities.
+//!
+//! Only available with the `fault_injection` feature, which must not be enabled in production builds.
+
+use crate::error::Error;
+use crate::ids::{ActivityId, AgentId};
+use crate::signalling::common::interface::ConnectScheduler;
+use crate::signalling::common::signals::Signal;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use feo_time::{Duration, Instant};
+use score_log::{warn, ScoreDebug};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+/// Fault to inject
+#[derive(Debug, Clone, Copy, PartialEq, ScoreDebug)]
+pub enum Fault {
+    /// Deliver a lifecycle signal later by the given duration
+    DelaySignal(Duration),
+    /// Never deliver a lifecycle signal
+    DropSignal,
+    /// Deliver a lifecycle signal twice
+    DuplicateSignal,
+    /// Panic in the step of an activity, before running the step
+    Panic,
+    /// Extend the step of an activity by the given duration, before running the step
+    Overrun(Duration),
+}
+
+impl Fault {
+    fn is_signal_fault(&self) -> bool {
+        matches!(self, Fault::DelaySignal(_) | Fault::DropSignal | Fault::DuplicateSignal)
+    }
+}
+
+/// Direction of a lifecycle signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction {
+    /// Startup, step and shutdown triggers sent by the scheduler
+    ToWorker,
+    /// Ready and failure responses sent to the scheduler
+    ToScheduler,
+}
+
+/// Rule of a [FaultPlan] injecting a fault into matching events
+///
+/// Events are the lifecycle signals for signal faults and the steps of activities for activity faults.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    fault: Fault,
+    activity: Option<ActivityId>,
+    direction: Option<Direction>,
+    probability: f64,
+    after: u64,
+    limit: Option<u64>,
+}
+
+impl FaultRule {
+    /// Create a rule injecting the given fault into every event of every activity
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            fault,
+            activity: None,
+            direction: None,
+            probability: 1.0,
+            after: 0,
+            limit: None,
+        }
+    }
+
+    /// Only match events of the given activity
+    pub fn activity(mut self, id: ActivityId) -> Self {
+        self.activity = Some(id);
+        self
+    }
+
+    /// Only match signals in the given direction, ignored for activity faults
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Inject the fault into a matching event with the given probability between 0 and 1
+    pub fn probability(mut self, probability: f64) -> Self {
+        self.probability = probability;
+        self
+    }
+
+    /// Skip the first `events` matching events of each activity
+    pub fn after(mut self, events: u64) -> Self {
+        self.after = events;
+        self
+    }
+
+    /// Inject the fault at most `faults` times per activity
+    pub fn limit(mut self, faults: u64) -> Self {
+        self.limit = Some(faults);
+        self
+    }
+
+    fn matches(&self, activity: ActivityId, direction: Option<Direction>) -> bool {
+        self.activity.is_none_or(|id| id == activity)
+            && match direction {
+                Some(direction) => self.fault.is_signal_fault() && self.direction.is_none_or(|d| d == direction),
+                None => !self.fault.is_signal_fault(),
+            }
+    }
+}
+
+/// Seed-controlled schedule of the faults to inject
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultPlan {
+    seed: u64,
+    rules: Vec<FaultRule>,
+}
+
+impl FaultPlan {
+    /// Create a plan without rules deciding faults with the given seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule, which is evaluated after all rules added before
+    ///
+    /// The first rule injecting a fault into an event wins, later rules do not see the event.
+    pub fn with_rule(mut self, rule: FaultRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+/// Fault injected into an event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InjectedFault {
+    /// Activity of the event
+    pub activity: ActivityId,
+    /// Injected fault
+    pub fault: Fault,
+    /// Number of the event among the events of the activity matched by the rule, starting at 0
+    pub event: u64,
+}
+
+/// Handle to an installed [FaultPlan]
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    shared: Arc<Mutex<InjectorState>>,
+}
+
+#[derive(Debug)]
+struct InjectorState {
+    plan: FaultPlan,
+    /// Number of matched events and injected faults per rule and activity
+    counts: BTreeMap<(usize, ActivityId), (u64, u64)>,
+    /// Faults injected so far
+    injected: Vec<InjectedFault>,
+}
+
+static INJECTOR: Mutex<Option<FaultInjector>> = Mutex::new(None);
+
+/// Install the given plan process-wide, replacing any installed plan
+pub fn install(plan: FaultPlan) -> FaultInjector {
+    let injector = FaultInjector {
+        shared: Arc::new(Mutex::new(InjectorState {
+            plan,
+            counts: BTreeMap::new(),
+            injected: Vec::new(),
+        })),
+    };
+    *lock(&INJECTOR) = Some(injector.clone());
+    injector
+}
+
+/// Remove the installed plan, if any
+pub fn uninstall() {
+    *lock(&INJECTOR) = None;
+}
+
+fn installed() -> Option<FaultInjector> {
+    lock(&INJECTOR).clone()
+}
+
+impl FaultInjector {
+    /// Get the faults injected so far, in the order of injection
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        lock(&self.shared).injected.clone()
+    }
+
+    /// Decide the fault to inject into the next event of the given activity, if any
+    fn decide(&self, activity: ActivityId, direction: Option<Direction>) -> Option<Fault> {
+        let mut state = lock(&self.shared);
+        let InjectorState { plan, counts, injected } = &mut *state;
+        for (index, rule) in plan.rules.iter().enumerate() {
+            if !rule.matches(activity, direction) {
+                continue;
+            }
+            let (events, faults) = counts.entry((index, activity)).or_default();
+            let event = *events;
+            *events += 1;
+            if event < rule.after || rule.limit.is_some_and(|limit| *faults >= limit) {
+                continue;
+            }
+            if unit_interval(plan.seed, index, activity, event) < rule.probability {
+                *faults += 1;
+                injected.push(InjectedFault {
+                    activity,
+                    fault: rule.fault,
+                    event,
+                });
+                return Some(rule.fault);
+            }
+        }
+        None
+    }
+}
+
+/// Inject the activity faults of the installed plan, if any, before a step of the given activity
+pub(crate) fn before_step(activity: ActivityId) {
+    let Some(fault) = installed().and_then(|injector| injector.decide(activity, None)) else {
+        return;
+    };
+    warn!("Injecting fault {:?} into step of activity {}", fault, activity);
+    match fault {
+        Fault::Panic => panic!("fault injected into step of activity {activity}"),
+        Fault::Overrun(duration) => thread::sleep(duration.into()),
+        _ => {},
+    }
+}
+
+/// Wrap the connector of a scheduler to inject the signal faults of the installed plan, if any
+pub(crate) fn wrap_scheduler(connector: Box<dyn ConnectScheduler>) -> Box<dyn ConnectScheduler> {
+    match installed() {
+        Some(injector) => Box::new(FaultyScheduler {
+            connector,
+            injector,
+            outgoing: Vec::new(),
+            incoming: VecDeque::new(),
+        }),
+        None => connector,
+    }
+}
+
+/// Connector of a scheduler injecting signal faults
+struct FaultyScheduler {
+    connector: Box<dyn ConnectScheduler>,
+    injector: FaultInjector,
+    /// Delayed signals to activities, with their due time
+    outgoing: Vec<(Instant, ActivityId, Signal)>,
+    /// Delayed or duplicated signals from activities, with their due time
+    incoming: VecDeque<(Instant, Signal)>,
+}
+
+impl FaultyScheduler {
+    /// Send the delayed signals to activities which are due
+    fn send_due(&mut self) -> Result<(), Error> {
+        let now = Instant::now();
+        let mut index = 0;
+        while index < self.outgoing.len() {
+            if self.outgoing[index].0 <= now {
+                let (_, id, signal) = self.outgoing.remove(index);
+                self.connector.send_to_activity(id, &signal)?;
+            } else {
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the next delayed or duplicated signal from activities which is due
+    fn take_due(&mut self) -> Option<Signal> {
+        let now = Instant::now();
+        let index = self.incoming.iter().position(|(due, _)| *due <= now)?;
+        self.incoming.remove(index).map(|(_, signal)| signal)
+    }
+
+    /// Time until the next delayed signal is due, if any
+    fn next_due(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.outgoing
+            .iter()
+            .map(|(due, _, _)| *due)
+            .chain(self.incoming.iter().map(|(due, _)| *due))
+            .min()
+            .map(|due| due.saturating_duration_since(now))
+    }
+}
+
+impl ConnectScheduler for FaultyScheduler {
+    fn connect_remotes(&mut self) -> Result<(), Error> {
+        self.connector.connect_remotes()
+    }
+
+    fn sync_time(&mut self) -> Result<(), Error> {
+        self.connector.sync_time()
+    }
+
+    fn get_connected_agent_ids(&self) -> Vec<AgentId> {
+        self.connector.get_connected_agent_ids()
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<Signal>, Error> {
+        self.send_due()?;
+        if let Some(signal) = self.take_due() {
+            return Ok(Some(signal));
+        }
+        let timeout = self.next_due().map_or(timeout, |due| due.min(timeout));
+        let Some(signal) = self.connector.receive(timeout)? else {
+            return Ok(None);
+        };
+        let activity = match signal {
+            Signal::Ready((id, _)) | Signal::ActivityFailed((id, _)) => id,
+            _ => return Ok(Some(signal)),
+        };
+        match self.injector.decide(activity, Some(Direction::ToScheduler)) {
+            Some(Fault::DelaySignal(delay)) => {
+                self.incoming.push_back((Instant::now() + delay, signal));
+                Ok(None)
+            },
+            Some(Fault::DropSignal) => Ok(None),
+            Some(Fault::DuplicateSignal) => {
+                self.incoming.push_back((Instant::now(), signal));
+                Ok(Some(signal))
+            },
+            _ => Ok(Some(signal)),
+        }
+    }
+
+    fn send_to_activity(&mut self, activity_id: ActivityId, signal: &Signal) -> Result<(), Error> {
+        self.send_due()?;
+        if !matches!(signal, Signal::Startup(_) | Signal::Step(_) | Signal::Shutdown(_)) {
+            return self.connector.send_to_activity(activity_id, signal);
+        }
+        match self.injector.decide(activity_id, Some(Direction::ToWorker)) {
+            Some(Fault::DelaySignal(delay)) => {
+                self.outgoing.push((Instant::now() + delay, activity_id, *signal));
+                Ok(())
+            },
+            Some(Fault::DropSignal) => Ok(()),
+            Some(Fault::DuplicateSignal) => {
+                self.connector.send_to_activity(activity_id, signal)?;
+                self.connector.send_to_activity(activity_id, signal)
+            },
+            _ => self.connector.send_to_activity(activity_id, signal),
+        }
+    }
+
+    fn broadcast_terminate(&mut self, signal: &Signal) -> Result<(), Error> {
+        self.connector.broadcast_terminate(signal)
+    }
+
+    fn take_relay_threads(&mut self) -> Vec<JoinHandle<()>> {
+        self.connector.take_relay_threads()
+    }
+}
+
+/// Map the given event to a pseudo-random number in `[0, 1)`
+fn unit_interval(seed: u64, rule: usize, activity: ActivityId, event: u64) -> f64 {
+    let mut hash = seed;
+    for value in [rule as u64, u64::from(activity), event] {
+        hash = splitmix64(hash ^ value);
+    }
+    // Use the upper 53 bits, which are exactly representable as f64
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Finalizer of the SplitMix64 generator
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faults_are_decided_by_seed() {
+        let plan = FaultPlan::new(7)
+            .with_rule(
+                FaultRule::new(Fault::DropSignal)
+                    .activity(ActivityId::from(1))
+                    .direction(Direction::ToScheduler)
+                    .after(2)
+                    .limit(1),
+            )
+            .with_rule(FaultRule::new(Fault::Panic).probability(0.5));
+        let decide = |injector: &FaultInjector| {
+            let mut faults = Vec::new();
+            for _ in 0..20 {
+                faults.push(injector.decide(ActivityId::from(1), Some(Direction::ToScheduler)));
+                faults.push(injector.decide(ActivityId::from(1), Some(Direction::ToWorker)));
+                faults.push(injector.decide(ActivityId::from(2), None));
+            }
+            faults
+        };
+        let injector = |plan: &FaultPlan| FaultInjector {
+            shared: Arc::new(Mutex::new(InjectorState {
+                plan: plan.clone(),
+                counts: BTreeMap::new(),
+                injected: Vec::new(),
+            })),
+        };
+
+        let first = injector(&plan);
+        let faults = decide(&first);
+        assert_eq!(faults, decide(&injector(&plan)));
+        assert_eq!(faults[6], Some(Fault::DropSignal));
+        assert!(faults.iter().step_by(3).filter(|fault| fault.is_some()).count() == 1);
+        assert!(faults.iter().skip(1).step_by(3).all(Option::is_none));
+        let panics = faults.iter().skip(2).step_by(3).filter(|fault| fault.is_some()).count();
+        assert!(panics > 0 && panics < 20);
+        assert_eq!(first.injected().len(), 1 + panics);
+        assert_eq!(first.injected()[0].event, 2);
+    }
+}
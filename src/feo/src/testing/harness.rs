@@ -0,0 +1,477 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Harness for end-to-end tests of multi-process FEO applications
+//!
+//! A [Harness] spawns the agents of an application as child processes, waits for the startup of
+//! the application to complete, lets it run a number of cycles, shuts it down and collects the exit
+//! status and the output of each agent:
+//!
+//! ```ignore
+//! let report = Harness::new()?
+//!     .primary("bin/adas_primary", ["--cycle", "10"])
+//!     .secondary("bin/adas_secondary", ["1"])
+//!     .recorder("bin/adas_recorder", [] as [&str; 0])
+//!     .start()?
+//!     .run_cycles(20)?
+//!     .stop()?;
+//! report.assert_success();
+//! ```
+//!
+//! The agents find the paths assigned to them by the harness in their environment, see [HarnessEnv].
+//! Each run uses a temporary directory of its own, so tests can run concurrently without clashing
+//! socket paths. The primary agent must serve [introspection](crate::introspection) on
+//! [HarnessEnv::introspection], which the harness uses to follow the startup and the cycles.
+
+use crate::error::Error;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use feo_time::{Duration, Instant};
+use feo_tracing::ScoreDebugIoError;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::{env, process, thread};
+
+/// Environment variable holding the temporary directory of a harness run
+const DIR_VAR: &str = "FEO_HARNESS_DIR";
+
+/// Environment variable holding the name of an agent of a harness run
+const NAME_VAR: &str = "FEO_HARNESS_AGENT";
+
+/// Interval of polling the primary agent and the child processes
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Paths assigned to an agent spawned by a [Harness]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarnessEnv {
+    /// Temporary directory of the harness run
+    pub dir: PathBuf,
+    /// Name of the agent
+    pub name: String,
+}
+
+impl HarnessEnv {
+    /// Get the paths of the harness run from the environment, if the agent has been spawned by a [Harness]
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            dir: env::var_os(DIR_VAR)?.into(),
+            name: env::var(NAME_VAR).ok()?,
+        })
+    }
+
+    /// Path of the Unix socket with the given name, e.g. of a listener of the primary agent
+    pub fn socket(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.socket"))
+    }
+
+    /// Path of the introspection socket to be served by the primary agent
+    pub fn introspection(&self) -> PathBuf {
+        self.socket("introspection")
+    }
+
+    /// Path of the recording to be written by a recorder agent, named after the agent
+    pub fn recording(&self) -> PathBuf {
+        self.dir.join(format!("{}.rec", self.name))
+    }
+}
+
+/// Role of an agent spawned by a [Harness]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentRole {
+    Primary,
+    Secondary,
+    Recorder,
+}
+
+/// Agent to spawn
+#[derive(Debug, Clone)]
+struct AgentSpec {
+    name: String,
+    role: AgentRole,
+    program: PathBuf,
+    args: Vec<OsString>,
+}
+
+/// Builder and launcher of the agents of a test run
+#[derive(Debug)]
+pub struct Harness {
+    /// Temporary directory of the run
+    dir: PathBuf,
+    /// Agents to spawn, the primary agent first
+    agents: Vec<AgentSpec>,
+    /// Maximum time until the application is running
+    startup_timeout: Duration,
+    /// Maximum time for running the requested cycles
+    cycle_timeout: Duration,
+    /// Maximum time for the agents to exit after the shutdown request
+    shutdown_timeout: Duration,
+    /// Whether to keep the temporary directory after the run
+    keep_dir: bool,
+}
+
+impl Harness {
+    /// Create a harness with a new temporary directory and without agents
+    pub fn new() -> Result<Self, Error> {
+        static RUNS: AtomicU64 = AtomicU64::new(0);
+        let run = RUNS.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("feo_harness_{}_{run}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create harness directory")))?;
+        Ok(Self {
+            dir,
+            agents: Vec::new(),
+            startup_timeout: Duration::from_secs(10),
+            cycle_timeout: Duration::from_secs(30),
+            shutdown_timeout: Duration::from_secs(10),
+            keep_dir: false,
+        })
+    }
+
+    /// Set the primary agent, replacing a previously set primary agent
+    pub fn primary(mut self, program: impl AsRef<Path>, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        self.agents.retain(|agent| agent.role != AgentRole::Primary);
+        let spec = Self::spec("primary".to_string(), AgentRole::Primary, program, args);
+        self.agents.insert(0, spec);
+        self
+    }
+
+    /// Add a secondary agent
+    pub fn secondary(mut self, program: impl AsRef<Path>, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        let name = format!("secondary{}", self.count(AgentRole::Secondary) + 1);
+        self.agents.push(Self::spec(name, AgentRole::Secondary, program, args));
+        self
+    }
+
+    /// Add a secondary agent recording to [HarnessEnv::recording]
+    pub fn recorder(mut self, program: impl AsRef<Path>, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        let name = format!("recorder{}", self.count(AgentRole::Recorder) + 1);
+        self.agents.push(Self::spec(name, AgentRole::Recorder, program, args));
+        self
+    }
+
+    /// Set the maximum time until the application is running, 10 s by default
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum time for running the cycles requested by [HarnessRun::run_cycles], 30 s by default
+    pub fn cycle_timeout(mut self, timeout: Duration) -> Self {
+        self.cycle_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum time for the agents to exit after the shutdown request, 10 s by default
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Keep the temporary directory with the logs and recordings after the run
+    pub fn keep_dir(mut self) -> Self {
+        self.keep_dir = true;
+        self
+    }
+
+    /// Get the temporary directory of the run
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Spawn the agents and wait until the application is running
+    ///
+    /// The primary agent is spawned first. If the application does not reach running within
+    /// the startup timeout, all agents are killed.
+    pub fn start(self) -> Result<HarnessRun, Error> {
+        if self.agents.first().is_none_or(|agent| agent.role != AgentRole::Primary) {
+            return Err(Error::Harness("no primary agent"));
+        }
+        let mut run = HarnessRun {
+            harness: self,
+            children: Vec::new(),
+        };
+        for index in 0..run.harness.agents.len() {
+            let child = run.spawn(&run.harness.agents[index])?;
+            run.children.push(child);
+        }
+        run.wait_running()?;
+        Ok(run)
+    }
+
+    fn spec(
+        name: String,
+        role: AgentRole,
+        program: impl AsRef<Path>,
+        args: impl IntoIterator<Item = impl Into<OsString>>,
+    ) -> AgentSpec {
+        AgentSpec {
+            name,
+            role,
+            program: program.as_ref().to_path_buf(),
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    fn count(&self, role: AgentRole) -> usize {
+        self.agents.iter().filter(|agent| agent.role == role).count()
+    }
+}
+
+/// Running application spawned by a [Harness]
+#[derive(Debug)]
+pub struct HarnessRun {
+    harness: Harness,
+    /// Child processes in the order of the agents of the harness
+    children: Vec<Child>,
+}
+
+impl HarnessRun {
+    /// Get the environment of the agent with the given name, e.g. to find its recording
+    pub fn env(&self, name: &str) -> HarnessEnv {
+        HarnessEnv {
+            dir: self.harness.dir.clone(),
+            name: name.to_string(),
+        }
+    }
+
+    /// Wait until each task chain of the application has completed `cycles` more cycles
+    pub fn run_cycles(mut self, cycles: u64) -> Result<Self, Error> {
+        let start = self.chain_cycles()?;
+        self.wait_until(self.harness.cycle_timeout, "waiting for cycles", |run| {
+            let current = run.chain_cycles()?;
+            Ok(start
+                .iter()
+                .all(|(id, cycle)| current.iter().any(|(c, now)| c == id && *now >= cycle + cycles)))
+        })?;
+        Ok(self)
+    }
+
+    /// Request the shutdown of the application and wait for all agents to exit
+    ///
+    /// The shutdown is requested by sending SIGTERM to the primary agent. Agents not exited
+    /// within the shutdown timeout are killed.
+    pub fn stop(mut self) -> Result<HarnessReport, Error> {
+        // SAFETY: plain system call on the pid of a child process not waited for yet
+        unsafe {
+            libc::kill(self.children[0].id() as libc::pid_t, libc::SIGTERM);
+        }
+        let deadline = Instant::now() + self.harness.shutdown_timeout;
+        let mut agents = Vec::with_capacity(self.children.len());
+        let children = core::mem::take(&mut self.children);
+        for (spec, mut child) in self.harness.agents.iter().zip(children) {
+            let status = loop {
+                match child.try_wait() {
+                    Ok(Some(status)) => break Some(status),
+                    Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL.into()),
+                    _ => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        break None;
+                    },
+                }
+            };
+            agents.push(AgentReport {
+                name: spec.name.clone(),
+                role: spec.role,
+                status,
+                log: fs::read_to_string(self.log_path(&spec.name)).unwrap_or_default(),
+            });
+        }
+        Ok(HarnessReport {
+            dir: self.harness.dir.clone(),
+            agents,
+        })
+    }
+
+    fn spawn(&self, spec: &AgentSpec) -> Result<Child, Error> {
+        let log = File::create(self.log_path(&spec.name))
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create agent log")))?;
+        let stderr = log
+            .try_clone()
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create agent log")))?;
+        Command::new(&spec.program)
+            .args(&spec.args)
+            .env(DIR_VAR, &self.harness.dir)
+            .env(NAME_VAR, &spec.name)
+            .stdin(Stdio::null())
+            .stdout(log)
+            .stderr(stderr)
+            .spawn()
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn agent")))
+    }
+
+    fn log_path(&self, name: &str) -> PathBuf {
+        self.harness.dir.join(format!("{name}.log"))
+    }
+
+    /// Wait until the primary agent reports running
+    fn wait_running(&mut self) -> Result<(), Error> {
+        let result = self.wait_until(self.harness.startup_timeout, "waiting for startup", |run| {
+            Ok(run
+                .query("status")
+                .ok()
+                .is_some_and(|status| status["running"].as_bool() == Some(true)))
+        });
+        if result.is_err() {
+            for child in self.children.iter_mut() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+        result
+    }
+
+    /// Poll `done` until it returns true, failing when an agent exits or after `timeout`
+    fn wait_until(
+        &mut self,
+        timeout: Duration,
+        action: &'static str,
+        mut done: impl FnMut(&Self) -> Result<bool, Error>,
+    ) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if done(self)? {
+                return Ok(());
+            }
+            if self
+                .children
+                .iter_mut()
+                .any(|child| child.try_wait().ok().flatten().is_some())
+            {
+                return Err(Error::Harness("agent exited unexpectedly"));
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(Some(timeout), action));
+            }
+            thread::sleep(POLL_INTERVAL.into());
+        }
+    }
+
+    /// Get the number of completed cycles of each task chain
+    fn chain_cycles(&self) -> Result<Vec<(u64, u64)>, Error> {
+        let chains = self.query("chains")?;
+        Ok(chains
+            .as_array()
+            .map(|chains| {
+                chains
+                    .iter()
+                    .filter_map(|chain| Some((chain["id"].as_u64()?, chain["cycle"].as_u64()?)))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Send a command to the introspection server of the primary agent and parse its response
+    fn query(&self, command: &str) -> Result<serde_json::Value, Error> {
+        let path = self.env("primary").introspection();
+        let io = |e| Error::Io((ScoreDebugIoError(e), "failed to query primary agent"));
+        let mut stream = UnixStream::connect(path).map_err(io)?;
+        stream.write_all(format!("{command}\n").as_bytes()).map_err(io)?;
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).map_err(io)?;
+        serde_json::from_str(&response).map_err(|_| Error::Harness("invalid introspection response"))
+    }
+}
+
+impl Drop for HarnessRun {
+    fn drop(&mut self) {
+        for child in self.children.iter_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        if !self.keep_dir {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Exit status and output of an agent
+#[derive(Debug, Clone)]
+pub struct AgentReport {
+    /// Name of the agent, e.g. `primary` or `secondary1`
+    pub name: String,
+    /// Role of the agent
+    pub role: AgentRole,
+    /// Exit status, `None` if the agent had to be killed
+    pub status: Option<ExitStatus>,
+    /// Standard output and standard error of the agent
+    pub log: String,
+}
+
+/// Outcome of a run of a [Harness]
+#[derive(Debug, Clone)]
+pub struct HarnessReport {
+    /// Temporary directory of the run, removed unless kept with [Harness::keep_dir]
+    pub dir: PathBuf,
+    /// Reports of the agents, the primary agent first
+    pub agents: Vec<AgentReport>,
+}
+
+impl HarnessReport {
+    /// Whether all agents exited successfully
+    pub fn success(&self) -> bool {
+        self.agents
+            .iter()
+            .all(|agent| agent.status.is_some_and(|status| status.success()))
+    }
+
+    /// Get the report of the agent with the given name
+    pub fn agent(&self, name: &str) -> Option<&AgentReport> {
+        self.agents.iter().find(|agent| agent.name == name)
+    }
+
+    /// Panic with the logs of the failed agents unless all agents exited successfully
+    pub fn assert_success(&self) {
+        for agent in self.agents.iter() {
+            match agent.status {
+                Some(status) if status.success() => {},
+                status => panic!("agent {} failed with {:?}, log:\n{}", agent.name, status, agent.log),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harness_reports_failing_primary() {
+        let error = Harness::new()
+            .unwrap()
+            .primary("/bin/sh", ["-c", "echo starting; exit 3"])
+            .startup_timeout(Duration::from_secs(5))
+            .start()
+            .unwrap_err();
+        assert!(matches!(error, Error::Harness("agent exited unexpectedly")));
+
+        let env = HarnessEnv {
+            dir: PathBuf::from("/tmp/run"),
+            name: "recorder1".to_string(),
+        };
+        assert_eq!(env.introspection(), PathBuf::from("/tmp/run/introspection.socket"));
+        assert_eq!(env.recording(), PathBuf::from("/tmp/run/recorder1.rec"));
+    }
+}
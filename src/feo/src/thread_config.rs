@@ -0,0 +1,266 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! CPU affinity and scheduling policy of worker threads
+//!
+//! A [ThreadConfig] can be set per worker and per activity in the [ThreadConfigs] of a secondary agent.
+//! The configuration of a worker is applied to its thread when the worker starts.
+//! The configuration of an activity is applied before each startup, step and shutdown of the activity
+//! and the configuration of the worker thread is restored afterwards, which costs a few system calls
+//! per call of the activity. Isolating latency-critical activities on dedicated workers avoids this overhead.
+//!
+//! Real-time scheduling policies usually require the `CAP_SYS_NICE` capability or a suitable `RLIMIT_RTPRIO`.
+
+use crate::error::Error;
+use crate::ids::{ActivityId, WorkerId};
+use alloc::vec::Vec;
+use core::mem::size_of;
+use feo_tracing::ScoreDebugIoError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io;
+
+/// Scheduling policy of a thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedPolicy {
+    /// Default time-sharing policy (`SCHED_OTHER`)
+    Other,
+    /// Real-time first-in first-out policy (`SCHED_FIFO`)
+    Fifo,
+    /// Real-time round-robin policy (`SCHED_RR`)
+    RoundRobin,
+}
+
+impl SchedPolicy {
+    fn to_raw(self) -> libc::c_int {
+        match self {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::RoundRobin => libc::SCHED_RR,
+        }
+    }
+
+    fn from_raw(policy: libc::c_int) -> Option<Self> {
+        match policy {
+            libc::SCHED_OTHER => Some(SchedPolicy::Other),
+            libc::SCHED_FIFO => Some(SchedPolicy::Fifo),
+            libc::SCHED_RR => Some(SchedPolicy::RoundRobin),
+            _ => None,
+        }
+    }
+}
+
+/// CPU affinity and scheduling policy of a thread
+///
+/// Settings which are not given leave the corresponding attribute of the thread unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThreadConfig {
+    /// CPU cores the thread may run on. The affinity is not changed if empty.
+    #[serde(default)]
+    pub cpus: Vec<usize>,
+    /// Scheduling policy. The policy is not changed if not set.
+    #[serde(default)]
+    pub policy: Option<SchedPolicy>,
+    /// Static priority for the real-time policies, between 1 and 99. Must be 0 for [SchedPolicy::Other].
+    #[serde(default)]
+    pub priority: u8,
+}
+
+impl ThreadConfig {
+    /// Restrict the thread to the given CPU cores
+    pub fn with_cpus(mut self, cpus: impl IntoIterator<Item = usize>) -> Self {
+        self.cpus = cpus.into_iter().collect();
+        self
+    }
+
+    /// Set the scheduling policy and priority of the thread
+    pub fn with_policy(mut self, policy: SchedPolicy, priority: u8) -> Self {
+        self.policy = Some(policy);
+        self.priority = priority;
+        self
+    }
+
+    /// Apply the configuration to the calling thread
+    pub fn apply(&self) -> Result<(), Error> {
+        if !self.cpus.is_empty() {
+            // Safety: cpu_set_t is a plain bit set for which all zeros is a valid, empty value
+            let mut set: libc::cpu_set_t = unsafe { core::mem::zeroed() };
+            for cpu in self.cpus.iter() {
+                if *cpu >= libc::CPU_SETSIZE as usize {
+                    return Err(Error::Io((
+                        ScoreDebugIoError(io::ErrorKind::InvalidInput.into()),
+                        "CPU index exceeds the CPU set size",
+                    )));
+                }
+                // Safety: the index has been checked against the size of the set
+                unsafe { libc::CPU_SET(*cpu, &mut set) };
+            }
+            // Safety: pid 0 refers to the calling thread and the set is valid for the call
+            if unsafe { libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) } != 0 {
+                return Err(Error::Io((
+                    ScoreDebugIoError(io::Error::last_os_error()),
+                    "failed to set CPU affinity",
+                )));
+            }
+        }
+
+        if let Some(policy) = self.policy {
+            let param = libc::sched_param {
+                sched_priority: libc::c_int::from(self.priority),
+            };
+            // Safety: pid 0 refers to the calling thread and the parameter is valid for the call
+            if unsafe { libc::sched_setscheduler(0, policy.to_raw(), &param) } != 0 {
+                return Err(Error::Io((
+                    ScoreDebugIoError(io::Error::last_os_error()),
+                    "failed to set scheduling policy",
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the configuration of the calling thread
+    pub fn current() -> Result<Self, Error> {
+        // Safety: cpu_set_t is a plain bit set for which all zeros is a valid, empty value
+        let mut set: libc::cpu_set_t = unsafe { core::mem::zeroed() };
+        // Safety: pid 0 refers to the calling thread and the set is valid for the call
+        if unsafe { libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set) } != 0 {
+            return Err(Error::Io((
+                ScoreDebugIoError(io::Error::last_os_error()),
+                "failed to get CPU affinity",
+            )));
+        }
+        // Safety: all indices are within the size of the set
+        let cpus = (0..libc::CPU_SETSIZE as usize)
+            .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+            .collect();
+
+        // Safety: pid 0 refers to the calling thread
+        let policy = unsafe { libc::sched_getscheduler(0) };
+        let mut param = libc::sched_param { sched_priority: 0 };
+        // Safety: pid 0 refers to the calling thread and the parameter is valid for the call
+        if policy < 0 || unsafe { libc::sched_getparam(0, &mut param) } != 0 {
+            return Err(Error::Io((
+                ScoreDebugIoError(io::Error::last_os_error()),
+                "failed to get scheduling policy",
+            )));
+        }
+
+        Ok(Self {
+            cpus,
+            policy: SchedPolicy::from_raw(policy),
+            priority: param.sched_priority as u8,
+        })
+    }
+}
+
+/// Thread configurations of the workers and activities of an agent
+#[derive(Debug, Clone, Default)]
+pub struct ThreadConfigs {
+    /// Configuration per worker thread
+    pub workers: HashMap<WorkerId, ThreadConfig>,
+    /// Configuration per activity, applied while the activity is running
+    pub activities: HashMap<ActivityId, ThreadConfig>,
+    /// Number of pool threads per worker running its activities in parallel
+    pub pools: HashMap<WorkerId, usize>,
+}
+
+impl ThreadConfigs {
+    /// Set the configuration of the given worker thread
+    pub fn with_worker(mut self, id: WorkerId, config: ThreadConfig) -> Self {
+        self.workers.insert(id, config);
+        self
+    }
+
+    /// Set the configuration applied while the given activity is running
+    pub fn with_activity(mut self, id: ActivityId, config: ThreadConfig) -> Self {
+        self.activities.insert(id, config);
+        self
+    }
+
+    /// Run the activities of the given worker on a pool of `threads` threads
+    ///
+    /// Independent activities of the worker triggered in the same cycle then run in parallel.
+    /// Each activity is still built and run on a single thread of the pool, which is configured
+    /// like the worker thread.
+    pub fn with_pool(mut self, id: WorkerId, threads: usize) -> Self {
+        self.pools.insert(id, threads);
+        self
+    }
+
+    /// Get the configurations of the given worker and its activities
+    pub(crate) fn for_worker(
+        &self,
+        id: WorkerId,
+        activities: impl IntoIterator<Item = ActivityId>,
+    ) -> WorkerThreadConfigs {
+        let activities = activities
+            .into_iter()
+            .filter_map(|activity_id| Some((activity_id, self.activities.get(&activity_id)?.clone())))
+            .collect();
+        WorkerThreadConfigs {
+            worker: self.workers.get(&id).cloned(),
+            activities,
+            pool_threads: self.pools.get(&id).copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Thread configurations of a single worker and its activities
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorkerThreadConfigs {
+    /// Configuration of the worker thread
+    pub(crate) worker: Option<ThreadConfig>,
+    /// Configuration per activity of the worker
+    pub(crate) activities: HashMap<ActivityId, ThreadConfig>,
+    /// Number of pool threads running the activities, sequential execution on the worker thread if below two
+    pub(crate) pool_threads: usize,
+}
+
+impl WorkerThreadConfigs {
+    /// Get the configurations of a thread running the given activities of the worker on its behalf
+    pub(crate) fn for_activities(&self, ids: impl IntoIterator<Item = ActivityId>) -> Self {
+        Self {
+            worker: self.worker.clone(),
+            activities: ids
+                .into_iter()
+                .filter_map(|id| Some((id, self.activities.get(&id)?.clone())))
+                .collect(),
+            pool_threads: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_config_is_applied_and_restored() {
+        std::thread::spawn(|| {
+            let initial = ThreadConfig::current().unwrap();
+            let cpu = initial.cpus[0];
+
+            ThreadConfig::default().with_cpus([cpu]).apply().unwrap();
+            assert_eq!(ThreadConfig::current().unwrap().cpus, [cpu]);
+
+            initial.apply().unwrap();
+            assert_eq!(ThreadConfig::current().unwrap(), initial);
+        })
+        .join()
+        .unwrap();
+    }
+}
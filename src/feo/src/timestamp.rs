@@ -13,8 +13,9 @@
 
 //! Timestamping module
 
+use crate::debug_fmt::ScoreDebugDebug;
 use feo_time::{Duration, Scaled};
-use score_log::ScoreDebug;
+use score_log::{info, ScoreDebug};
 use std::sync::OnceLock;
 
 /// Maximal acceptable tolerance between when determining startup time info
@@ -44,7 +45,9 @@ pub fn initialize() {
         return;
     }
     let startup_time_info = time_info_now();
-    STARTUP_TIME.set(startup_time_info).ok();
+    if STARTUP_TIME.set(startup_time_info).is_ok() {
+        report_clock();
+    }
 }
 
 /// Initialize the instant of system startup from a given
@@ -85,7 +88,21 @@ pub fn initialize_from(sync_info: SyncInfo) {
         instant: startup_instant,
         systime: startup_time,
     };
-    STARTUP_TIME.set(startup_time_info).ok();
+    if STARTUP_TIME.set(startup_time_info).is_ok() {
+        report_clock();
+    }
+}
+
+/// Calibrate the OS clock backing the FEO clock and report its resolution for timing analysis
+fn report_clock() {
+    let calibration = feo_time::calibrate();
+    info!(
+        "Clock {:?}: resolution {:?}s, measured resolution {:?}s, read cost {:?}s",
+        ScoreDebugDebug::<_, 32>(&calibration.source),
+        calibration.resolution,
+        calibration.measured_resolution,
+        calibration.read_cost
+    );
 }
 
 /// Return the startup instant
@@ -138,6 +155,18 @@ pub struct SyncInfo {
     since_epoch: feo_time::Duration,
 }
 
+impl SyncInfo {
+    /// Create the synchronization information for the given startup time since the UNIX epoch
+    pub(crate) fn from_since_epoch(since_epoch: feo_time::Duration) -> Self {
+        Self { since_epoch }
+    }
+
+    /// Get the startup time of the primary agent as duration since the UNIX epoch
+    pub fn since_epoch(&self) -> feo_time::Duration {
+        self.since_epoch
+    }
+}
+
 /// Return current system time and instant as a TimeInfo object
 fn time_info_now() -> TimeInfo {
     let mut tries_remaining: i32 = MAX_TRIES;
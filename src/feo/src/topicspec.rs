@@ -16,11 +16,13 @@
 use crate::ids::ActivityId;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
+use core::any::type_name;
 use core::fmt::Debug;
 use feo_com::interface::{
     init_topic_primary, init_topic_secondary, ComBackendTopicPrimaryInitialization,
-    ComBackendTopicSecondaryInitialization, FeoComData, FeoComDefault, Topic, TopicHandle,
+    ComBackendTopicSecondaryInitialization, FeoComData, FeoComDefault, TopicHandle, TopicName,
 };
+use feo_com::topic::Topic;
 use score_log::fmt::ScoreDebug;
 
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
@@ -36,27 +38,42 @@ pub enum Direction {
 
 /// Specification of a topic's backend and peers
 pub struct TopicSpecification<'a> {
-    /// The topic
-    pub topic: Topic<'a>,
+    /// Name of the topic
+    pub topic: TopicName<'a>,
+    /// Name of the type of the samples of the topic
+    pub type_name: &'static str,
     /// Peers with [ActivityId] and communication [Direction] for this topic
     pub peers: Vec<(ActivityId, Direction)>,
+    /// Whether the topic may be written by more than one activity
+    pub multiple_writers: bool,
     /// Function to initialize this topic with the number of writers and readers as arguments
     pub init_primary_fn: Box<dyn FnOnce(&ComBackendTopicPrimaryInitialization) -> TopicHandle>,
     pub init_secondary_fn: Box<dyn FnOnce(&ComBackendTopicSecondaryInitialization) -> TopicHandle>,
 }
 
 impl<'a> TopicSpecification<'a> {
+    /// Create the specification of a topic, initialized for samples of its type
     pub fn new<T: FeoComData + FeoComDefault + Debug + ScoreDebug + 'static>(
-        topic: Topic<'a>,
+        topic: Topic<'a, T>,
         peers: Vec<(ActivityId, Direction)>,
     ) -> Self {
         let init_primary_fn = Box::new(init_topic_primary::<T>);
         let init_secondary_fn = Box::new(init_topic_secondary::<T>);
         Self {
-            topic,
+            topic: topic.name(),
+            type_name: type_name::<T>(),
             peers,
+            multiple_writers: false,
             init_primary_fn,
             init_secondary_fn,
         }
     }
+
+    /// Allow more than one activity to write the topic
+    ///
+    /// Topics are expected to have a single writer otherwise, see [crate::dependency::validate].
+    pub fn with_multiple_writers(mut self) -> Self {
+        self.multiple_writers = true;
+        self
+    }
 }
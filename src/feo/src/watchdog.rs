@@ -0,0 +1,147 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Watchdog integration of the primary agent
+//!
+//! The scheduler of the primary agent calls a [WatchdogNotifier] once per completed cycle
+//! of a task chain. An external supervisor, e.g. systemd or a monitoring script, can then
+//! detect a stalled FEO chain from missing notifications.
+
+use crate::error::Error;
+use alloc::boxed::Box;
+use feo_tracing::ScoreDebugIoError;
+use std::fs;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Environment variable holding the address of the systemd notification socket
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// Message resetting the systemd watchdog timer
+const WATCHDOG_MESSAGE: &[u8] = b"WATCHDOG=1";
+
+/// Boxed watchdog notifier as stored in the agent configurations
+pub type Watchdog = Box<dyn WatchdogNotifier>;
+
+/// Notifier of an external watchdog
+pub trait WatchdogNotifier: Send {
+    /// Signal that the scheduler completed a cycle
+    fn notify(&mut self) -> Result<(), Error>;
+}
+
+/// Watchdog notifier for services supervised by systemd with `WatchdogSec=` set
+///
+/// Sends `WATCHDOG=1` to the notification socket of systemd, like `sd_notify`.
+pub struct SystemdNotifier {
+    socket: UnixDatagram,
+    address: SocketAddr,
+}
+
+impl SystemdNotifier {
+    /// Create a notifier for the socket in the `NOTIFY_SOCKET` environment variable
+    ///
+    /// Returns `Ok(None)` if the variable is not set, i.e. the agent is not run by systemd.
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        match std::env::var(NOTIFY_SOCKET) {
+            Ok(address) => Self::new(&address).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Create a notifier for the given socket address
+    ///
+    /// Addresses starting with `@` refer to the abstract socket namespace.
+    pub fn new(address: &str) -> Result<Self, Error> {
+        let address = match address.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name),
+            None => SocketAddr::from_pathname(address),
+        }
+        .map_err(|e| Error::Io((ScoreDebugIoError(e), "invalid systemd notification socket")))?;
+        let socket = UnixDatagram::unbound()
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to create systemd notification socket")))?;
+        Ok(Self { socket, address })
+    }
+}
+
+impl WatchdogNotifier for SystemdNotifier {
+    fn notify(&mut self) -> Result<(), Error> {
+        self.socket
+            .send_to_addr(WATCHDOG_MESSAGE, &self.address)
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to notify systemd watchdog")))?;
+        Ok(())
+    }
+}
+
+/// Watchdog notifier updating the modification time of a file
+///
+/// Fallback for supervisors without a notification protocol, which check the age of the file instead.
+/// The file is created if it does not exist.
+pub struct FileTouchNotifier {
+    path: PathBuf,
+}
+
+impl FileTouchNotifier {
+    /// Create a notifier touching the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the touched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl WatchdogNotifier for FileTouchNotifier {
+    fn notify(&mut self) -> Result<(), Error> {
+        fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to touch watchdog file")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifiers_signal_watchdog() {
+        let directory = std::env::temp_dir().join(alloc::format!("feo_watchdog_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        // systemd notification
+        let socket_path = directory.join("notify.sock");
+        let receiver = UnixDatagram::bind(&socket_path).unwrap();
+        let mut notifier = SystemdNotifier::new(socket_path.to_str().unwrap()).unwrap();
+        notifier.notify().unwrap();
+        let mut buffer = [0u8; 32];
+        let len = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], WATCHDOG_MESSAGE);
+
+        // File touch
+        let mut notifier = FileTouchNotifier::new(directory.join("alive"));
+        notifier.notify().unwrap();
+        let first = fs::metadata(notifier.path()).unwrap().modified().unwrap();
+        std::thread::sleep(core::time::Duration::from_millis(10));
+        notifier.notify().unwrap();
+        assert!(fs::metadata(notifier.path()).unwrap().modified().unwrap() > first);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
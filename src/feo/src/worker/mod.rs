@@ -13,17 +13,37 @@
 
 //! Worker thread running FEO activities
 
-use crate::activity::{Activity, ActivityBuilder};
-use crate::error::Error;
+mod pool;
+mod supervised;
+mod utilization;
+
+use crate::activity::{Activity, ActivityBuilder, ActivityIdAndBuilder};
+use crate::deadline::HardDeadlines;
+use crate::dlt::ActivityScope;
+use crate::error::{ActivityError, ConnectionError, Error, SignallingError};
 use crate::ids::{ActivityId, AgentId, WorkerId};
+use crate::log_relay::LogRelay;
+use crate::memory;
+use crate::panic_report;
+use crate::recording::checkpoint::StateCheckpoints;
 use crate::signalling::common::interface::ConnectWorker;
 use crate::signalling::common::signals::Signal;
+use crate::thread_config::{ThreadConfig, WorkerThreadConfigs};
 use crate::timestamp;
+use crate::worker::pool::{ActivityPool, POLL_INTERVAL};
+use crate::worker::supervised::SupervisedActivity;
+use crate::worker::utilization::Utilization;
+use crate::worker_local::WorkerLocalScope;
 use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::mem;
 use feo_time::Duration;
 use feo_time::Instant;
-use score_log::{debug, error};
-use std::collections::HashMap;
+use feo_tracing::activity::{self as trace_activity, ActivityContext, ContextGuard};
+use score_log::{debug, error, warn};
+use std::collections::{HashMap, HashSet};
 use std::thread;
 
 /// Worker
@@ -31,17 +51,59 @@ use std::thread;
 /// Activities are built in the worker thread with the passed builders
 /// and never move to another thread after being built.
 /// The connector passed to the worker is already connected to the scheduler.
+///
+/// If configured with a pool of threads, the activities are built and run on the pool threads instead,
+/// see [pool]. Activities with a hard deadline are run on threads of their own supervised by the worker,
+/// see [supervised]. Each of these threads has its own [worker-local](crate::worker_local) values.
+///
+/// The worker measures the share of its cycles spent executing its activities, see [utilization].
+///
+/// Panics of the lifecycle methods of activities are caught and answered as failures. The reports of
+/// such panics are sent to the scheduler before the failures, see [panic_report].
+///
+/// Activities run in their [tracing context](feo_tracing::activity), holding the activity, the worker
+/// and the number of steps of the activity so far.
 pub(crate) struct Worker<T: ConnectWorker> {
     /// ID of this worker
     id: WorkerId,
     /// ID of the agent this worker belongs to
     agent_id: AgentId,
-    /// Map from [ActivityId] to the activity
+    /// Builders of the activities, consumed when running the worker
+    builders: Vec<ActivityIdAndBuilder>,
+    /// Map from [ActivityId] to the activity, unless run on a pool
     activities: HashMap<ActivityId, Box<dyn Activity>>,
+    /// Pool of threads running the activities, if configured
+    pool: Option<ActivityPool>,
+    /// Activities with a hard deadline running on supervised threads
+    supervised: HashMap<ActivityId, SupervisedActivity>,
+    /// Activities which have been started and not yet shut down
+    started: HashSet<ActivityId>,
     /// Connector to the scheduler
     connector: T,
     /// Timeout on `receive` calls
     timeout: Duration,
+    /// Interval of heartbeats sent to the scheduler, if any
+    heartbeat_interval: Option<Duration>,
+    /// Thread configurations of this worker and its activities
+    thread_configs: WorkerThreadConfigs,
+    /// Thread configuration to restore after running an activity with its own configuration
+    restore_thread_config: Option<ThreadConfig>,
+    /// Hard step deadlines of the activities of this worker
+    hard_deadlines: HardDeadlines,
+    /// Checkpoints of the states of the activities of this worker, if any
+    checkpoints: Option<StateCheckpoints>,
+    /// Relay of the log records of the agent to the primary agent, if any
+    log_relay: Option<LogRelay>,
+    /// Connector to a standby primary agent, used once the connection to the scheduler is closed
+    failover: Option<T>,
+    /// Whether the worker failed over to the standby primary agent
+    failed_over: bool,
+    /// Activities whose startup has been requested by a scheduler
+    startup_requested: HashSet<ActivityId>,
+    /// Busy time of the worker in its current cycle
+    utilization: Utilization,
+    /// Tracing context of the activities run on the worker thread
+    trace_context: TraceContext,
 }
 
 impl<T: ConnectWorker> Worker<T> {
@@ -52,52 +114,168 @@ impl<T: ConnectWorker> Worker<T> {
         activity_builders: impl IntoIterator<Item = (ActivityId, Box<dyn ActivityBuilder>)>,
         connector: T,
         timeout: Duration,
+        heartbeat_interval: Option<Duration>,
     ) -> Self {
-        // Build activities
-        let activities: HashMap<ActivityId, _> = activity_builders.into_iter().map(|(id, b)| (id, b(id))).collect();
-
         Self {
             id,
             agent_id,
-            activities,
+            builders: activity_builders.into_iter().collect(),
+            activities: HashMap::new(),
+            pool: None,
+            supervised: HashMap::new(),
+            started: HashSet::new(),
             connector,
             timeout,
+            heartbeat_interval,
+            thread_configs: WorkerThreadConfigs::default(),
+            restore_thread_config: None,
+            hard_deadlines: HardDeadlines::default(),
+            checkpoints: None,
+            log_relay: None,
+            failover: None,
+            failed_over: false,
+            startup_requested: HashSet::new(),
+            utilization: Utilization::default(),
+            trace_context: TraceContext::new(id),
         }
     }
 
+    /// Set the thread configurations of this worker and its activities, applied when running the worker
+    pub(crate) fn with_thread_configs(mut self, thread_configs: WorkerThreadConfigs) -> Self {
+        self.thread_configs = thread_configs;
+        self
+    }
+
+    /// Set the hard step deadlines of the activities of this worker, enforced when running the worker
+    pub(crate) fn with_hard_deadlines(mut self, hard_deadlines: HardDeadlines) -> Self {
+        self.hard_deadlines = hard_deadlines;
+        self
+    }
+
+    /// Set the checkpoints saving and restoring the states of the activities of this worker, if any
+    pub(crate) fn with_checkpoints(mut self, checkpoints: Option<StateCheckpoints>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    /// Set the relay whose log records this worker sends to the primary agent, if any
+    pub(crate) fn with_log_relay(mut self, log_relay: Option<LogRelay>) -> Self {
+        self.log_relay = log_relay;
+        self
+    }
+
+    /// Set the connector to a standby primary agent, connected when the connection to the scheduler is closed
+    ///
+    /// Activities already started are not started again for the standby, see [standby](crate::standby).
+    pub(crate) fn with_failover(mut self, failover: Option<T>) -> Self {
+        self.failover = failover;
+        self
+    }
+
     /// Run the worker
     pub(crate) fn run(mut self) -> Result<(), Error> {
         debug!("Running worker {}", self.id);
+        // Worker-local values are torn down once the activities have been shut down and the worker exits
+        let _locals = WorkerLocalScope::enter();
+        self.apply_thread_configs()?;
+        self.build_activities()?;
 
+        let mut next_heartbeat = self.heartbeat_interval.map(|_| Instant::now());
         loop {
-            let signal = match self.connector.receive(self.timeout) {
+            // Send a heartbeat if due and wait no longer than until the next one
+            let mut timeout = self.timeout;
+            if let (Some(interval), Some(due)) = (self.heartbeat_interval, next_heartbeat.as_mut()) {
+                if *due <= Instant::now() {
+                    if let Err(e) = self.connector.send_to_scheduler(&Signal::Heartbeat(self.agent_id)) {
+                        warn!("Worker {} failed to send heartbeat: {:?}", self.id, e);
+                    }
+                    *due = Instant::now() + interval;
+                }
+                timeout = min(timeout, due.saturating_duration_since(Instant::now()));
+            }
+
+            self.forward_logs();
+            self.forward_retained_memory();
+
+            // Pass on the responses of finished activities and check again soon while any is running
+            let pool_running = self.forward_pool_responses()?;
+            if pool_running {
+                timeout = min(timeout, POLL_INTERVAL);
+            }
+
+            let waiting_since = Instant::now();
+            let received = self.connector.receive(timeout);
+            // Waiting for activities running on the pool is part of executing them
+            if pool_running {
+                self.utilization.add_busy(waiting_since.elapsed());
+            }
+            let signal = match received {
                 Ok(Some(s)) => s,
                 Ok(None) => {
                     // TODO: Manage timeout
                     continue;
                 },
-                Err(Error::ChannelClosed) => {
+                Err(Error::Connection(ConnectionError::ChannelClosed)) if self.failover.is_some() => {
+                    warn!(
+                        "Worker {} lost connection to scheduler, failing over to standby primary agent",
+                        self.id
+                    );
+                    let mut failover = self.failover.take().unwrap();
+                    failover.connect_remote()?;
+                    self.connector = failover;
+                    self.failed_over = true;
+                    continue;
+                },
+                Err(Error::Connection(ConnectionError::ChannelClosed)) => {
                     debug!(
                         "Worker {} detected closed channel from scheduler/relay. Exiting.",
                         self.id
                     );
+                    self.shutdown_started_activities();
                     return Ok(()); // Graceful exit
                 },
                 Err(e) => return Err(e), // Propagate other errors
             };
 
+            let received_at = Instant::now();
+            if let Signal::Step((id, _)) = signal {
+                if let Some(percent) = self.utilization.step(id) {
+                    self.report_utilization(percent);
+                }
+            }
             match signal {
-                Signal::Startup((id, _)) | Signal::Step((id, _)) | Signal::Shutdown((id, _)) => {
+                Signal::Startup((id, _)) if self.failed_over && self.startup_requested.contains(&id) => {
+                    // Started before for the failed primary agent
+                    debug!("Worker {} resuming activity {} without startup", self.id, id);
+                    self.connector.send_to_scheduler(&Signal::Ready((id, timestamp::timestamp())))?;
+                },
+                Signal::Startup((id, _)) => {
+                    self.startup_requested.insert(id);
+                    self.handle_activity_signal(&id, &signal)?;
+                },
+                Signal::Step((id, _)) | Signal::Shutdown((id, _)) => {
                     self.handle_activity_signal(&id, &signal)?;
                 },
+                Signal::StaleInput((id, producer)) => {
+                    self.handle_stale_input(id, producer)?;
+                },
                 Signal::StartupSync(sync_info) => {
                     timestamp::initialize_from(sync_info);
+                    // Announce the process of the agent, see [signal_forwarding](crate::signal_forwarding)
+                    let announce = Signal::AgentPid((self.agent_id, std::process::id()));
+                    if let Err(e) = self.connector.send_to_scheduler(&announce) {
+                        warn!("Worker {} failed to announce its process ID: {:?}", self.id, e);
+                    }
                 },
                 Signal::Terminate(_) => {
                     debug!(
                         "Worker {} received Terminate signal. Acknowledging and exiting.",
                         self.id
                     );
+                    // Activities must not be left running if their shutdown signal was missed,
+                    // e.g. because the scheduler stopped waiting for the shutdown acknowledgements.
+                    self.shutdown_started_activities();
+                    self.forward_logs();
                     //connection reset may happen if primary terminated and closed its sockets
                     if let Err(e) = self.connector.send_to_scheduler(&Signal::TerminateAck(self.agent_id)) {
                         debug!(
@@ -111,53 +289,304 @@ impl<T: ConnectWorker> Worker<T> {
                     thread::sleep(Duration::from_millis(100).into());
                     return Ok(()); // Graceful exit
                 },
-                other => return Err(Error::UnexpectedSignal(other)),
+                other => return Err(Error::Signalling(SignallingError::UnexpectedSignal(other))),
+            }
+            self.utilization.add_busy(received_at.elapsed());
+        }
+    }
+
+    /// Emit the utilization of the last cycle as trace counter and report it to the scheduler
+    fn report_utilization(&mut self, percent: u8) {
+        feo_tracing::counter!(format!("worker_utilization_{}", self.id.id()).as_str(), percent);
+        if let Err(e) = self.connector.send_to_scheduler(&Signal::WorkerUtilization((self.id, percent))) {
+            warn!("Worker {} failed to report its utilization: {:?}", self.id, e);
+        }
+    }
+
+    /// Report the memory retained by the steps measured in this process, see [memory](crate::memory)
+    fn forward_retained_memory(&mut self) {
+        for (id, bytes) in memory::take_retained() {
+            if let Err(e) = self.connector.send_to_scheduler(&Signal::MemoryRetained((id, bytes))) {
+                warn!(
+                    "Worker {} failed to report memory retained by activity {}: {:?}",
+                    self.id, id, e
+                );
             }
         }
     }
 
+    /// Send the reports of the activities panicked in this process, see [panic_report]
+    fn forward_panics(&mut self) {
+        for report in panic_report::take() {
+            if let Err(e) = self.connector.send_to_scheduler(&Signal::ActivityPanicked(report)) {
+                warn!(
+                    "Worker {} failed to report panic of activity {}: {:?}",
+                    self.id, report.activity, e
+                );
+            }
+        }
+    }
+
+    /// Send the queued log records to the primary agent, if relayed
+    fn forward_logs(&mut self) {
+        if let Some(relay) = self.log_relay.as_ref() {
+            relay.forward(|line| self.connector.send_to_scheduler(&Signal::Log(line)));
+        }
+    }
+
+    /// Apply the thread configuration of the worker and remember it for activities with their own configuration
+    fn apply_thread_configs(&mut self) -> Result<(), Error> {
+        if let Some(config) = self.thread_configs.worker.as_ref() {
+            config.apply()?;
+        }
+        if !self.thread_configs.activities.is_empty() {
+            self.restore_thread_config = Some(ThreadConfig::current()?);
+        }
+        Ok(())
+    }
+
+    /// Build the activities, on the pool threads or supervised threads if configured
+    fn build_activities(&mut self) -> Result<(), Error> {
+        let (supervised, builders): (Vec<_>, Vec<_>) = mem::take(&mut self.builders)
+            .into_iter()
+            .partition(|(id, _)| self.hard_deadlines.budget(id).is_some());
+        for (id, builder) in supervised {
+            let budget = self.hard_deadlines.budget(&id).unwrap_or_default();
+            let activity = SupervisedActivity::new(
+                self.id,
+                (id, builder),
+                budget,
+                self.hard_deadlines.action,
+                &self.thread_configs,
+                self.checkpoints.clone(),
+            )?;
+            self.supervised.insert(id, activity);
+        }
+
+        if self.thread_configs.pool_threads > 1 {
+            debug!(
+                "Worker {} running {} activities on {} pool threads",
+                self.id,
+                builders.len(),
+                self.thread_configs.pool_threads
+            );
+            let pool = ActivityPool::new(
+                self.id,
+                builders,
+                self.thread_configs.pool_threads,
+                &self.thread_configs,
+                self.checkpoints.clone(),
+            )?;
+            self.pool = Some(pool);
+        } else {
+            self.activities = builders.into_iter().map(|(id, b)| (id, b(id))).collect();
+        }
+        Ok(())
+    }
+
+    /// Send the responses of all activities finished on the pool to the scheduler
+    ///
+    /// Returns whether any activity is still running on the pool.
+    fn forward_pool_responses(&mut self) -> Result<bool, Error> {
+        let Some(pool) = self.pool.as_mut() else {
+            return Ok(false);
+        };
+        while let Some(response) = pool.try_response()? {
+            self.forward_panics();
+            self.connector.send_to_scheduler(&response)?;
+        }
+        Ok(pool.is_running())
+    }
+
     fn handle_activity_signal(&mut self, id: &ActivityId, signal: &Signal) -> Result<(), Error> {
+        if let Some(activity) = self.supervised.get_mut(id) {
+            let response_signal = activity.run(signal)?;
+            self.forward_panics();
+            let result = self.connector.send_to_scheduler(&response_signal);
+            if activity.must_abort() {
+                supervised::abort(*id);
+            }
+            return result;
+        }
+        if let Some(pool) = self.pool.as_mut() {
+            return pool.dispatch(id, *signal);
+        }
+        let Some(config) = self.thread_configs.activities.get(id) else {
+            return self.run_activity_signal(id, signal);
+        };
+        if let Err(e) = config.apply() {
+            warn!("Failed to apply thread configuration of activity {}: {:?}", id, e);
+        }
+        let result = self.run_activity_signal(id, signal);
+        if let Some(config) = self.restore_thread_config.as_ref() {
+            if let Err(e) = config.apply() {
+                warn!("Failed to restore thread configuration of worker {}: {:?}", self.id, e);
+            }
+        }
+        result
+    }
+
+    /// Inform the given activity about the stale input of a skipped producer, before its next step
+    fn handle_stale_input(&mut self, id: ActivityId, producer: ActivityId) -> Result<(), Error> {
+        let signal = Signal::StaleInput((id, producer));
+        if let Some(activity) = self.supervised.get_mut(&id) {
+            return activity.notify(&signal);
+        }
+        if let Some(pool) = self.pool.as_mut() {
+            return pool.notify(&id, signal);
+        }
+        let activity = self.activities.get_mut(&id).ok_or(Error::ActivityNotFound(id))?;
+        activity.input_stale(producer);
+        Ok(())
+    }
+
+    fn run_activity_signal(&mut self, id: &ActivityId, signal: &Signal) -> Result<(), Error> {
         let activity = self.activities.get_mut(id).ok_or(Error::ActivityNotFound(*id))?;
-        let start = Instant::now();
-
-        match signal {
-            Signal::Startup((activity_id, _ts)) => {
-                let response_signal = match activity.startup() {
-                    Ok(()) => Signal::Ready((*activity_id, timestamp::timestamp())),
-                    Err(e) => {
-                        error!("Activity {} failed during startup: {:?}", id, e);
-                        Signal::ActivityFailed((*id, e))
-                    },
-                };
-                let elapsed = start.elapsed();
-                debug!("Ran startup of activity {:?} in {:?}", id, elapsed);
-                self.connector.send_to_scheduler(&response_signal)
-            },
-            Signal::Step((activity_id, _ts)) => {
-                let response_signal = match activity.step() {
-                    Ok(()) => Signal::Ready((*activity_id, timestamp::timestamp())),
-                    Err(e) => {
-                        error!("Activity {} failed during step: {:?}", id, e);
-                        Signal::ActivityFailed((*id, e))
-                    },
-                };
-                let elapsed = start.elapsed();
-                debug!("Stepped activity {:?} in {:?}", id, elapsed);
-                self.connector.send_to_scheduler(&response_signal)
-            },
-            Signal::Shutdown((activity_id, _ts)) => {
-                let response_signal = match activity.shutdown() {
-                    Ok(()) => Signal::Ready((*activity_id, timestamp::timestamp())),
-                    Err(e) => {
-                        error!("Activity {} failed during shutdown: {:?}", id, e);
-                        Signal::ActivityFailed((*id, e))
-                    },
-                };
-                let elapsed = start.elapsed();
-                debug!("Ran shutdown of activity {:?} in {}", id, elapsed);
-                self.connector.send_to_scheduler(&response_signal)
-            },
-            other => Err(Error::UnexpectedSignal(*other)),
+        let response_signal = execute(
+            activity.as_mut(),
+            &mut self.started,
+            self.checkpoints.as_ref(),
+            &mut self.trace_context,
+            signal,
+        )?;
+        self.forward_panics();
+        self.connector.send_to_scheduler(&response_signal)
+    }
+
+    /// Run the shutdown of all activities which have been started but not shut down yet
+    fn shutdown_started_activities(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.stop();
+        }
+        for (_, activity) in self.supervised.drain() {
+            activity.stop();
+        }
+        for id in self.started.drain() {
+            let Some(activity) = self.activities.get_mut(&id) else {
+                continue;
+            };
+            warn!("Worker {} shutting down activity {} before exiting", self.id, id);
+            if let Err(e) = activity.shutdown() {
+                error!("Activity {} failed during shutdown: {:?}", id, e);
+            }
+        }
+    }
+}
+
+/// Get the activity addressed by a lifecycle or stale input signal
+fn signal_activity(signal: &Signal) -> Option<ActivityId> {
+    match signal {
+        Signal::Startup((id, _)) | Signal::Step((id, _)) | Signal::StaleInput((id, _)) | Signal::Shutdown((id, _)) => {
+            Some(*id)
+        },
+        _ => None,
+    }
+}
+
+/// Tracing context of the activities run on a thread, counting the steps of each activity
+struct TraceContext {
+    /// Worker running the activities
+    worker: WorkerId,
+    /// Number of steps of each activity so far
+    steps: HashMap<ActivityId, u64>,
+}
+
+impl TraceContext {
+    fn new(worker: WorkerId) -> Self {
+        Self {
+            worker,
+            steps: HashMap::new(),
+        }
+    }
+
+    /// Enter the tracing context of the activity addressed by the given lifecycle signal
+    fn enter(&mut self, signal: &Signal) -> Option<ContextGuard> {
+        let id = signal_activity(signal)?;
+        let steps = self.steps.entry(id).or_default();
+        let cycle = *steps;
+        if let Signal::Step(_) = signal {
+            *steps += 1;
         }
+        Some(trace_activity::enter(ActivityContext {
+            activity: id.id(),
+            worker: self.worker.id(),
+            cycle,
+        }))
+    }
+}
+
+/// Run a lifecycle signal on the given activity and get the response to the scheduler
+///
+/// Keeps track of the started activities, which need to be shut down before exiting.
+/// Panics of the activity are answered as failures, see [panic_report].
+/// With checkpoints, the state of the activity is restored after its startup and saved after its steps when due.
+fn execute(
+    activity: &mut dyn Activity,
+    started: &mut HashSet<ActivityId>,
+    checkpoints: Option<&StateCheckpoints>,
+    trace_context: &mut TraceContext,
+    signal: &Signal,
+) -> Result<Signal, Error> {
+    let start = Instant::now();
+    let _scope = signal_activity(signal).map(ActivityScope::enter);
+    let _trace_scope = trace_context.enter(signal);
+
+    match signal {
+        Signal::Startup((id, _ts)) => {
+            let result = panic_report::catch(ActivityError::Startup, || {
+                activity
+                    .startup()
+                    .and_then(|()| checkpoints.map_or(Ok(()), |c| c.restore(*id, activity)))
+            });
+            let response_signal = match result {
+                Ok(()) => {
+                    started.insert(*id);
+                    Signal::Ready((*id, timestamp::timestamp()))
+                },
+                Err(e) => {
+                    error!("Activity {} failed during startup: {:?}", id, e);
+                    Signal::ActivityFailed((*id, e))
+                },
+            };
+            let elapsed = start.elapsed();
+            debug!("Ran startup of activity {:?} in {:?}", id, elapsed);
+            Ok(response_signal)
+        },
+        Signal::Step((id, _ts)) => {
+            let step = || {
+                #[cfg(feature = "fault_injection")]
+                crate::testing::fault_injection::before_step(*id);
+                activity.step()
+            };
+            let response_signal = match memory::measure_step(*id, || panic_report::catch(ActivityError::Step, step)) {
+                Ok(()) => {
+                    if let Some(checkpoints) = checkpoints {
+                        checkpoints.stepped(*id, activity);
+                    }
+                    Signal::Ready((*id, timestamp::timestamp()))
+                },
+                Err(e) => {
+                    error!("Activity {} failed during step: {:?}", id, e);
+                    Signal::ActivityFailed((*id, e))
+                },
+            };
+            let elapsed = start.elapsed();
+            debug!("Stepped activity {:?} in {:?}", id, elapsed);
+            Ok(response_signal)
+        },
+        Signal::Shutdown((id, _ts)) => {
+            started.remove(id);
+            let response_signal = match panic_report::catch(ActivityError::Shutdown, || activity.shutdown()) {
+                Ok(()) => Signal::Ready((*id, timestamp::timestamp())),
+                Err(e) => {
+                    error!("Activity {} failed during shutdown: {:?}", id, e);
+                    Signal::ActivityFailed((*id, e))
+                },
+            };
+            let elapsed = start.elapsed();
+            debug!("Ran shutdown of activity {:?} in {}", id, elapsed);
+            Ok(response_signal)
+        },
+        other => Err(Error::Signalling(SignallingError::UnexpectedSignal(*other))),
     }
 }
@@ -0,0 +1,335 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Thread pool running the activities of a worker in parallel
+//!
+//! Each activity is assigned to one pool thread, where it is built and run, so activities still
+//! never move between threads. The worker thread dispatches the signals of the scheduler to the pool
+//! threads without waiting for their completion, so that independent activities triggered by the
+//! scheduler in the same cycle run in parallel. The scheduler keeps triggering activities only once
+//! their dependencies are ready, so the declared dependencies are respected as in sequential execution.
+
+use crate::activity::{Activity, ActivityIdAndBuilder};
+use crate::error::{ActivityError, ConnectionError, Error};
+use crate::ids::{ActivityId, WorkerId};
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::signalling::common::signals::Signal;
+use crate::thread_config::{ThreadConfig, WorkerThreadConfigs};
+use crate::worker::{execute, signal_activity, TraceContext};
+use crate::worker_local::WorkerLocalScope;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::{error, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+
+/// Interval at which the worker thread checks for finished activities while any is running
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_micros(200);
+
+/// Pool of threads running the activities of a worker
+pub(crate) struct ActivityPool {
+    /// Senders of signals to each pool thread
+    jobs: Vec<Sender<Signal>>,
+    /// Handles of the pool threads
+    threads: Vec<JoinHandle<()>>,
+    /// Index of the pool thread running each activity
+    owners: HashMap<ActivityId, usize>,
+    /// Receiver of the responses of the pool threads to the scheduler
+    responses: Receiver<Signal>,
+    /// Number of dispatched signals not answered yet
+    running: usize,
+}
+
+impl ActivityPool {
+    /// Start a pool with the given number of threads and distribute the activities among them
+    ///
+    /// Activities are assigned to the threads in the order of their IDs. The pool has no more threads
+    /// than activities.
+    pub(crate) fn new(
+        worker_id: WorkerId,
+        mut builders: Vec<ActivityIdAndBuilder>,
+        threads: usize,
+        thread_configs: &WorkerThreadConfigs,
+        checkpoints: Option<StateCheckpoints>,
+    ) -> Result<Self, Error> {
+        builders.sort_by_key(|(id, _)| *id);
+        let threads = threads.clamp(1, builders.len().max(1));
+
+        let mut assigned: Vec<Vec<ActivityIdAndBuilder>> = (0..threads).map(|_| Vec::new()).collect();
+        let mut owners = HashMap::new();
+        for (index, (id, builder)) in builders.into_iter().enumerate() {
+            owners.insert(id, index % threads);
+            assigned[index % threads].push((id, builder));
+        }
+
+        let (response_sender, responses) = mpsc::channel();
+        let mut jobs = Vec::with_capacity(threads);
+        let mut handles = Vec::with_capacity(threads);
+        for (index, builders) in assigned.into_iter().enumerate() {
+            let (job_sender, job_receiver) = mpsc::channel();
+            let thread_configs = thread_configs.for_activities(builders.iter().map(|(id, _)| *id));
+            let responses = response_sender.clone();
+            let checkpoints = checkpoints.clone();
+            let handle = thread::Builder::new()
+                .name(format!("feo-{worker_id}-pool-{index}"))
+                .spawn(move || run_thread(worker_id, builders, job_receiver, responses, thread_configs, checkpoints))
+                .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn activity pool thread")))?;
+            jobs.push(job_sender);
+            handles.push(handle);
+        }
+
+        Ok(Self {
+            jobs,
+            threads: handles,
+            owners,
+            responses,
+            running: 0,
+        })
+    }
+
+    /// Pass a lifecycle signal to the thread running its activity
+    pub(crate) fn dispatch(&mut self, id: &ActivityId, signal: Signal) -> Result<(), Error> {
+        let owner = *self.owners.get(id).ok_or(Error::ActivityNotFound(*id))?;
+        self.jobs[owner]
+            .send(signal)
+            .map_err(|_| Error::Connection(ConnectionError::Channel("activity pool thread terminated")))?;
+        self.running += 1;
+        Ok(())
+    }
+
+    /// Pass a signal not answered by the activity, such as a stale input, to the thread running its activity
+    pub(crate) fn notify(&mut self, id: &ActivityId, signal: Signal) -> Result<(), Error> {
+        let owner = *self.owners.get(id).ok_or(Error::ActivityNotFound(*id))?;
+        self.jobs[owner]
+            .send(signal)
+            .map_err(|_| Error::Connection(ConnectionError::Channel("activity pool thread terminated")))
+    }
+
+    /// Check whether any dispatched signal has not been answered yet
+    pub(crate) fn is_running(&self) -> bool {
+        self.running > 0
+    }
+
+    /// Get the next response of a finished activity, if any
+    pub(crate) fn try_response(&mut self) -> Result<Option<Signal>, Error> {
+        match self.responses.try_recv() {
+            Ok(signal) => {
+                self.running -= 1;
+                Ok(Some(signal))
+            },
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Error::Connection(ConnectionError::Channel(
+                "activity pool threads terminated",
+            ))),
+        }
+    }
+
+    /// Stop the pool threads, shutting down all activities which have been started but not shut down yet
+    pub(crate) fn stop(self) {
+        drop(self.jobs);
+        for thread in self.threads {
+            if thread.join().is_err() {
+                error!("Activity pool thread panicked");
+            }
+        }
+    }
+}
+
+/// Main function of a pool thread
+pub(super) fn run_thread(
+    worker_id: WorkerId,
+    builders: Vec<ActivityIdAndBuilder>,
+    jobs: Receiver<Signal>,
+    responses: Sender<Signal>,
+    thread_configs: WorkerThreadConfigs,
+    checkpoints: Option<StateCheckpoints>,
+) {
+    let _locals = WorkerLocalScope::enter();
+    if let Some(config) = thread_configs.worker.as_ref() {
+        if let Err(e) = config.apply() {
+            warn!("Failed to apply thread configuration of activity pool thread: {:?}", e);
+        }
+    }
+    let restore = if thread_configs.activities.is_empty() {
+        None
+    } else {
+        ThreadConfig::current().ok()
+    };
+
+    let mut activities: HashMap<ActivityId, Box<dyn Activity>> =
+        builders.into_iter().map(|(id, builder)| (id, builder(id))).collect();
+    let mut started = HashSet::new();
+    let mut trace_context = TraceContext::new(worker_id);
+
+    for signal in jobs.iter() {
+        let Some(id) = signal_activity(&signal) else {
+            error!("Activity pool received unexpected signal {:?}", signal);
+            continue;
+        };
+        if let Signal::StaleInput((_, producer)) = signal {
+            if let Some(activity) = activities.get_mut(&id) {
+                activity.input_stale(producer);
+            }
+            continue;
+        }
+        let config = thread_configs.activities.get(&id);
+        if let Some(Err(e)) = config.map(ThreadConfig::apply) {
+            warn!("Failed to apply thread configuration of activity {}: {:?}", id, e);
+        }
+        let response = match activities.get_mut(&id) {
+            Some(activity) => execute(
+                activity.as_mut(),
+                &mut started,
+                checkpoints.as_ref(),
+                &mut trace_context,
+                &signal,
+            ),
+            None => Err(Error::ActivityNotFound(id)),
+        };
+        if config.is_some() {
+            if let Some(Err(e)) = restore.as_ref().map(ThreadConfig::apply) {
+                warn!(
+                    "Failed to restore thread configuration of activity pool thread: {:?}",
+                    e
+                );
+            }
+        }
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Activity pool failed to run signal {:?}: {:?}", signal, e);
+                // Answer the signal anyway, the worker waits for a response to each dispatched signal
+                let error = match signal {
+                    Signal::Startup(_) => ActivityError::Startup,
+                    Signal::Shutdown(_) => ActivityError::Shutdown,
+                    _ => ActivityError::Step,
+                };
+                Signal::ActivityFailed((id, error))
+            },
+        };
+        if responses.send(response).is_err() {
+            break;
+        }
+    }
+
+    for id in started.drain() {
+        let Some(activity) = activities.get_mut(&id) else {
+            continue;
+        };
+        warn!("Activity pool shutting down activity {} before exiting", id);
+        if let Err(e) = activity.shutdown() {
+            error!("Activity {} failed during shutdown: {:?}", id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_runs_activities_in_parallel() {
+        use std::sync::{Arc, Barrier};
+
+        /// Activity waiting in its step for all other activities to step concurrently
+        struct Waiting {
+            id: ActivityId,
+            barrier: Arc<Barrier>,
+        }
+
+        impl Activity for Waiting {
+            fn id(&self) -> ActivityId {
+                self.id
+            }
+
+            fn startup(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+
+            fn step(&mut self) -> Result<(), ActivityError> {
+                self.barrier.wait();
+                Ok(())
+            }
+
+            fn shutdown(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+        }
+
+        let barrier = Arc::new(Barrier::new(2));
+        let builders: Vec<ActivityIdAndBuilder> = [1, 2]
+            .into_iter()
+            .map(|id| {
+                let barrier = barrier.clone();
+                let builder: Box<dyn crate::activity::ActivityBuilder> =
+                    Box::new(move |id| Box::new(Waiting { id, barrier }) as Box<dyn Activity>);
+                (ActivityId::from(id), builder)
+            })
+            .collect();
+        let thread_configs = WorkerThreadConfigs::default();
+        let mut pool = ActivityPool::new(WorkerId::from(1), builders, 2, &thread_configs, None).unwrap();
+
+        // Both steps only finish if run in parallel
+        let ts = crate::timestamp::timestamp();
+        for id in [1, 2].map(ActivityId::from) {
+            pool.dispatch(&id, Signal::Startup((id, ts))).unwrap();
+            pool.dispatch(&id, Signal::Step((id, ts))).unwrap();
+        }
+        let mut ready = Vec::new();
+        while pool.is_running() {
+            match pool.try_response().unwrap() {
+                Some(Signal::Ready((id, _))) => ready.push(id),
+                Some(other) => panic!("unexpected response {other:?}"),
+                None => thread::sleep(POLL_INTERVAL.into()),
+            }
+        }
+        assert_eq!(ready.len(), 4);
+        pool.stop();
+    }
+
+    #[test]
+    fn failing_signals_are_answered() {
+        let (job_sender, jobs) = mpsc::channel();
+        let (response_sender, responses) = mpsc::channel();
+
+        // The pool thread does not run the activity, so the signals cannot be run
+        let ts = crate::timestamp::timestamp();
+        let id = ActivityId::from(9);
+        job_sender.send(Signal::Startup((id, ts))).unwrap();
+        job_sender.send(Signal::Step((id, ts))).unwrap();
+        job_sender.send(Signal::Shutdown((id, ts))).unwrap();
+        drop(job_sender);
+        run_thread(
+            WorkerId::from(1),
+            Vec::new(),
+            jobs,
+            response_sender,
+            WorkerThreadConfigs::default(),
+            None,
+        );
+
+        let failed: Vec<Signal> = responses.try_iter().collect();
+        assert_eq!(
+            failed,
+            [
+                Signal::ActivityFailed((id, ActivityError::Startup)),
+                Signal::ActivityFailed((id, ActivityError::Step)),
+                Signal::ActivityFailed((id, ActivityError::Shutdown)),
+            ]
+        );
+    }
+}
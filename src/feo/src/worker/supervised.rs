@@ -0,0 +1,226 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Activities supervised for hard step deadlines
+//!
+//! An activity with a hard deadline is built and run on a thread of its own, like on an activity pool
+//! with a single thread. The worker thread waits for each step no longer than the hard deadline, so that
+//! a hung step cannot stall the worker. Since a thread cannot be cancelled safely, the thread of a hung
+//! activity is parked, i.e. left behind and never signalled again, or the process is aborted.
+
+use crate::activity::ActivityIdAndBuilder;
+use crate::deadline::HardDeadlineAction;
+use crate::error::{ActivityError, ConnectionError, Error, SignallingError};
+use crate::ids::{ActivityId, WorkerId};
+use crate::recording::checkpoint::StateCheckpoints;
+use crate::signalling::common::signals::Signal;
+use crate::thread_config::WorkerThreadConfigs;
+use crate::worker::pool::run_thread;
+use alloc::format;
+use alloc::vec;
+use feo_time::Duration;
+use feo_tracing::ScoreDebugIoError;
+use score_log::error;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Activity running on a thread supervised by its worker
+pub(crate) struct SupervisedActivity {
+    /// ID of the activity
+    id: ActivityId,
+    /// Hard deadline of each step
+    budget: Duration,
+    /// Reaction to a step exceeding the hard deadline
+    action: HardDeadlineAction,
+    /// Sender of signals to the thread of the activity
+    jobs: Sender<Signal>,
+    /// Receiver of the responses of the thread of the activity
+    responses: Receiver<Signal>,
+    /// Handle of the thread of the activity
+    thread: JoinHandle<()>,
+    /// Whether the thread of the activity has been parked after exceeding the hard deadline
+    parked: bool,
+}
+
+impl SupervisedActivity {
+    /// Start the thread of the given activity, which is built on that thread
+    pub(crate) fn new(
+        worker_id: WorkerId,
+        (id, builder): ActivityIdAndBuilder,
+        budget: Duration,
+        action: HardDeadlineAction,
+        thread_configs: &WorkerThreadConfigs,
+        checkpoints: Option<StateCheckpoints>,
+    ) -> Result<Self, Error> {
+        let (jobs, job_receiver) = mpsc::channel();
+        let (response_sender, responses) = mpsc::channel();
+        let thread_configs = thread_configs.for_activities([id]);
+        let thread = thread::Builder::new()
+            .name(format!("feo-{worker_id}-{id}"))
+            .spawn(move || {
+                run_thread(
+                    worker_id,
+                    vec![(id, builder)],
+                    job_receiver,
+                    response_sender,
+                    thread_configs,
+                    checkpoints,
+                )
+            })
+            .map_err(|e| Error::Io((ScoreDebugIoError(e), "failed to spawn supervised activity thread")))?;
+
+        Ok(Self {
+            id,
+            budget,
+            action,
+            jobs,
+            responses,
+            thread,
+            parked: false,
+        })
+    }
+
+    /// Run a lifecycle signal on the activity and get the response to the scheduler
+    ///
+    /// Steps exceeding the hard deadline and all signals to a parked activity are answered as failed.
+    pub(crate) fn run(&mut self, signal: &Signal) -> Result<Signal, Error> {
+        let id = self.id;
+        let failed = move |error| Signal::ActivityFailed((id, error));
+        let error = match signal {
+            Signal::Startup(_) => ActivityError::Startup,
+            Signal::Step(_) => ActivityError::Step,
+            Signal::Shutdown(_) => ActivityError::Shutdown,
+            other => return Err(Error::Signalling(SignallingError::UnexpectedSignal(*other))),
+        };
+        if self.parked {
+            return Ok(failed(error));
+        }
+
+        self.jobs
+            .send(*signal)
+            .map_err(|_| Error::Connection(ConnectionError::Channel("supervised activity thread terminated")))?;
+        let response = match signal {
+            Signal::Step(_) => self.responses.recv_timeout(self.budget.into()),
+            _ => self.responses.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match response {
+            Ok(response) => Ok(response),
+            Err(RecvTimeoutError::Timeout) => {
+                error!(
+                    "Activity {} exceeded its hard step deadline of {:?}, parking its thread",
+                    self.id, self.budget
+                );
+                self.parked = true;
+                Ok(failed(error))
+            },
+            Err(RecvTimeoutError::Disconnected) => Err(Error::Connection(ConnectionError::Channel(
+                "supervised activity thread terminated",
+            ))),
+        }
+    }
+
+    /// Pass a signal not answered by the activity, such as a stale input, to the thread of the activity
+    ///
+    /// Signals to a parked activity are dropped.
+    pub(crate) fn notify(&mut self, signal: &Signal) -> Result<(), Error> {
+        if self.parked {
+            return Ok(());
+        }
+        self.jobs
+            .send(*signal)
+            .map_err(|_| Error::Connection(ConnectionError::Channel("supervised activity thread terminated")))
+    }
+
+    /// Check whether the process must be aborted because the activity exceeded its hard deadline
+    pub(crate) fn must_abort(&self) -> bool {
+        self.parked && self.action == HardDeadlineAction::Abort
+    }
+
+    /// Stop the thread of the activity, shutting down the activity if it has been started
+    ///
+    /// The thread of a parked activity is left behind.
+    pub(crate) fn stop(self) {
+        drop(self.jobs);
+        if self.parked {
+            return;
+        }
+        if self.thread.join().is_err() {
+            error!("Thread of supervised activity {} panicked", self.id);
+        }
+    }
+}
+
+/// Abort the process after an activity exceeded its hard deadline
+///
+/// Gives the failure report to the scheduler a moment to be sent before.
+pub(crate) fn abort(id: ActivityId) -> ! {
+    error!("Activity {} exceeded its hard step deadline, aborting the process", id);
+    thread::sleep(Duration::from_millis(100).into());
+    std::process::abort()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hung_step_is_reported_and_parked() {
+        use crate::activity::{Activity, ActivityBuilder};
+        use alloc::boxed::Box;
+
+        /// Activity hanging in its step
+        struct Hanging(ActivityId);
+
+        impl Activity for Hanging {
+            fn id(&self) -> ActivityId {
+                self.0
+            }
+
+            fn startup(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+
+            fn step(&mut self) -> Result<(), ActivityError> {
+                thread::sleep(core::time::Duration::from_millis(500));
+                Ok(())
+            }
+
+            fn shutdown(&mut self) -> Result<(), ActivityError> {
+                Ok(())
+            }
+        }
+
+        let id = ActivityId::from(1);
+        let builder: Box<dyn ActivityBuilder> = Box::new(|id| Box::new(Hanging(id)) as Box<dyn Activity>);
+        let mut activity = SupervisedActivity::new(
+            WorkerId::from(1),
+            (id, builder),
+            Duration::from_millis(20),
+            HardDeadlineAction::Park,
+            &WorkerThreadConfigs::default(),
+            None,
+        )
+        .unwrap();
+
+        let ts = crate::timestamp::timestamp();
+        assert!(matches!(activity.run(&Signal::Startup((id, ts))), Ok(Signal::Ready(_))));
+        let failed = Signal::ActivityFailed((id, ActivityError::Step));
+        assert_eq!(activity.run(&Signal::Step((id, ts))).unwrap(), failed);
+        assert!(!activity.must_abort());
+
+        // A parked activity is not run anymore
+        let failed = Signal::ActivityFailed((id, ActivityError::Shutdown));
+        assert_eq!(activity.run(&Signal::Shutdown((id, ts))).unwrap(), failed);
+        activity.stop();
+    }
+}
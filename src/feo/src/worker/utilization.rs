@@ -0,0 +1,62 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Utilization of the workers
+//!
+//! Each worker measures the share of each of its cycles it spends executing its activities, as opposed
+//! to waiting for the next trigger signal. A cycle of a worker lasts from a step of the first activity
+//! stepped by the worker to the next step of the same activity; for a worker running activities of
+//! several task chains, this is the cycle of the chain of that activity.
+//!
+//! At the end of each cycle, the worker emits its utilization in percent as counter
+//! `worker_utilization_<id>` on the trace of its agent and reports it to the scheduler, which publishes
+//! it per worker via [introspection](crate::introspection). Workers running their activities on a
+//! [pool](super::pool) count the time any activity is running on the pool as busy.
+
+use crate::ids::ActivityId;
+use feo_time::{Duration, Instant};
+
+/// Busy time of a worker in its current cycle
+#[derive(Debug, Default)]
+pub(super) struct Utilization {
+    /// Activity whose steps delimit the cycles of the worker, the first activity stepped
+    cycle_activity: Option<ActivityId>,
+    /// Start of the current cycle
+    cycle_start: Option<Instant>,
+    /// Time spent executing in the current cycle
+    busy: Duration,
+}
+
+impl Utilization {
+    /// Account the given time spent executing to the current cycle
+    pub(super) fn add_busy(&mut self, duration: Duration) {
+        self.busy = self.busy + duration;
+    }
+
+    /// Note the step signal of the given activity, returning the utilization in percent if it ends a cycle
+    pub(super) fn step(&mut self, id: ActivityId) -> Option<u8> {
+        let now = Instant::now();
+        let cycle_activity = *self.cycle_activity.get_or_insert(id);
+        if cycle_activity != id {
+            return None;
+        }
+        let busy = core::mem::take(&mut self.busy);
+        let cycle_start = self.cycle_start.replace(now)?;
+        let cycle = now.saturating_duration_since(cycle_start);
+        if cycle.is_zero() {
+            return None;
+        }
+        let percent = (busy.as_nanos() * 100 / cycle.as_nanos()).min(100);
+        Some(percent as u8)
+    }
+}
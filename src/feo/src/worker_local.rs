@@ -0,0 +1,229 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Storage local to the threads running activities
+//!
+//! Some resources must be created once per thread and shared by all activities running on it,
+//! e.g. a GPU context or a handle of a C library which is not thread-safe. A [WorkerLocal] holds
+//! such a resource without resorting to statics in the activities: create it once during startup,
+//! pass clones to the activity builders, and access the value of the current thread with
+//! [WorkerLocal::with] in the activity steps.
+//!
+//! The value is initialized on the first access on each thread running activities, i.e. a worker
+//! thread, an activity pool thread (see [ThreadConfig](crate::thread_config)) or the thread of an
+//! activity with a hard deadline (see [deadline](crate::deadline)). When the thread exits after the
+//! shutdown of its activities, its values are torn down in the reverse order of their initialization.
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::RefCell;
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use score_log::warn;
+
+/// Source of the unique keys of worker-local values
+static NEXT_KEY: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    /// Worker-local values of this thread in the order of their initialization, if running activities
+    static VALUES: RefCell<Option<Vec<Entry>>> = const { RefCell::new(None) };
+}
+
+/// Worker-local value of a thread
+struct Entry {
+    /// Key of the [WorkerLocal] the value belongs to
+    key: usize,
+    /// The value in a `RefCell`
+    value: Rc<dyn Any>,
+    /// Teardown of the value
+    teardown: Box<dyn FnOnce(Rc<dyn Any>)>,
+}
+
+/// Handle of a value initialized once per thread running activities
+///
+/// Clones of the handle refer to the same values.
+pub struct WorkerLocal<T>(Arc<Inner<T>>);
+
+struct Inner<T> {
+    /// Key of the values of this handle
+    key: usize,
+    /// Initialization of the value of a thread
+    init: Box<dyn Fn() -> T + Send + Sync>,
+    /// Teardown of the value of a thread
+    teardown: Box<dyn Fn(T) + Send + Sync>,
+}
+
+impl<T: 'static> WorkerLocal<T> {
+    /// Create a handle of values initialized with the given function and dropped on teardown
+    pub fn new(init: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self::with_teardown(init, drop)
+    }
+
+    /// Create a handle of values initialized and torn down with the given functions
+    pub fn with_teardown(
+        init: impl Fn() -> T + Send + Sync + 'static,
+        teardown: impl Fn(T) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(Inner {
+            key: NEXT_KEY.fetch_add(1, Ordering::Relaxed),
+            init: Box::new(init),
+            teardown: Box::new(teardown),
+        }))
+    }
+
+    /// Call the given function with the value of the current thread, initializing it on first access
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current thread does not run activities, or if the value is accessed again
+    /// from within the given function.
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let value = match self.get() {
+            Some(value) => value,
+            None => self.init(),
+        };
+        let value = value
+            .downcast::<RefCell<T>>()
+            .expect("worker-local value of unexpected type");
+        let mut value = value.borrow_mut();
+        f(&mut value)
+    }
+
+    /// Get the value of the current thread, if initialized
+    fn get(&self) -> Option<Rc<dyn Any>> {
+        VALUES.with_borrow(|values| {
+            values
+                .as_ref()
+                .expect("worker-local value accessed outside of a thread running activities")
+                .iter()
+                .find(|entry| entry.key == self.0.key)
+                .map(|entry| entry.value.clone())
+        })
+    }
+
+    /// Initialize the value of the current thread
+    fn init(&self) -> Rc<dyn Any> {
+        // Initialized without borrowing the values, as the initialization may access other values
+        let value: Rc<dyn Any> = Rc::new(RefCell::new((self.0.init)()));
+        let inner = self.0.clone();
+        let teardown = Box::new(move |value: Rc<dyn Any>| {
+            match value.downcast::<RefCell<T>>().map(Rc::try_unwrap) {
+                Ok(Ok(value)) => (inner.teardown)(value.into_inner()),
+                _ => warn!("Worker-local value still in use, skipping its teardown"),
+            }
+        });
+        VALUES.with_borrow_mut(|values| {
+            values
+                .as_mut()
+                .expect("worker-local value accessed outside of a thread running activities")
+                .push(Entry {
+                    key: self.0.key,
+                    value: value.clone(),
+                    teardown,
+                })
+        });
+        value
+    }
+}
+
+// Implemented manually to not require `T: Clone`
+impl<T> Clone for WorkerLocal<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for WorkerLocal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WorkerLocal").field(&self.0.key).finish()
+    }
+}
+
+/// Scope of the worker-local values of a thread running activities
+///
+/// Tears down the values initialized within the scope in reverse order when dropped.
+pub(crate) struct WorkerLocalScope {
+    /// Values of an enclosing scope, restored when dropped
+    previous: Option<Vec<Entry>>,
+}
+
+impl WorkerLocalScope {
+    /// Enter the scope on the current thread
+    pub(crate) fn enter() -> Self {
+        Self {
+            previous: VALUES.replace(Some(Vec::new())),
+        }
+    }
+}
+
+impl Drop for WorkerLocalScope {
+    fn drop(&mut self) {
+        let values = VALUES.replace(self.previous.take());
+        for entry in values.into_iter().flatten().rev() {
+            (entry.teardown)(entry.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_local_to_threads_and_torn_down_in_reverse_order() {
+        use alloc::string::{String, ToString};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::<String>::new()));
+        let local = |name: &'static str| {
+            let (init_log, teardown_log) = (log.clone(), log.clone());
+            WorkerLocal::with_teardown(
+                move || {
+                    init_log.lock().unwrap().push(alloc::format!("init {name}"));
+                    0u32
+                },
+                move |value| teardown_log.lock().unwrap().push(alloc::format!("teardown {name} {value}")),
+            )
+        };
+        let (context, handle) = (local("context"), local("handle"));
+
+        let run_thread = |steps: u32| {
+            let (context, handle) = (context.clone(), handle.clone());
+            std::thread::spawn(move || {
+                let _scope = WorkerLocalScope::enter();
+                for _ in 0..steps {
+                    context.with(|value| *value += 1);
+                    handle.with(|value| *value += 10);
+                }
+            })
+            .join()
+            .unwrap();
+        };
+        run_thread(2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["init context", "init handle", "teardown handle 20", "teardown context 2"].map(ToString::to_string)
+        );
+
+        // Another thread initializes values of its own
+        log.lock().unwrap().clear();
+        run_thread(1);
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["init context", "init handle", "teardown handle 10", "teardown context 1"].map(ToString::to_string)
+        );
+    }
+}
@@ -0,0 +1,441 @@
+// *******************************************************************************
+// Copyright (c) 2025 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Apache License Version 2.0 which is available at
+// <https://www.apache.org/licenses/LICENSE-2.0>
+//
+// SPDX-License-Identifier: Apache-2.0
+// *******************************************************************************
+
+//! Convenience builders for the packets of a trace
+//!
+//! Track descriptors and track events need a number of nested and optional fields to be set,
+//! which is the same boilerplate for every producer of traces. The builders set sensible defaults,
+//! so that a producer only states what differs:
+//!
+//! ```ignore
+//! let process = TrackBuilder::process(1, 42, Some("primary")).packet();
+//! let event = EventBuilder::instant(1, "com_publish")
+//!     .annotation("topic", "camera")
+//!     .flow(7)
+//!     .packet(timestamp, sequence_id);
+//! ```
+//!
+//! An [Interner] replaces the event names, categories, source locations and annotation names of the packets
+//! of a sequence with interned ids, which reduces the size of traces with many events of the same names.
+
+use crate::{
+    clock_snapshot, counter_descriptor, debug_annotation, trace_packet, track_descriptor, track_event, ClockSnapshot,
+    CounterDescriptor, DebugAnnotation, DebugAnnotationName, EventCategory, EventName, FtraceEvent, FtraceEventBundle,
+    InternedData, ProcessDescriptor, SourceLocation, ThreadDescriptor, TracePacket, TrackDescriptor, TrackEvent,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Builder of a [TrackDescriptor]
+#[derive(Debug, Clone, Default)]
+pub struct TrackBuilder {
+    descriptor: TrackDescriptor,
+}
+
+impl TrackBuilder {
+    /// Start a track of the process with the given pid
+    pub fn process(uuid: u64, pid: u32, name: Option<&str>) -> Self {
+        let process = ProcessDescriptor {
+            pid: Some(pid as _),
+            process_name: name.map(str::to_string),
+            ..Default::default()
+        };
+        Self::new(uuid, name).with(|descriptor| descriptor.process = Some(process))
+    }
+
+    /// Start a track of the thread with the given tid in the process with the given pid
+    pub fn thread(uuid: u64, pid: u32, tid: u32, name: Option<&str>) -> Self {
+        let thread = ThreadDescriptor {
+            pid: Some(pid as _),
+            tid: Some(tid as _),
+            thread_name: name.map(str::to_string),
+            ..Default::default()
+        };
+        Self::new(uuid, name).with(|descriptor| descriptor.thread = Some(thread))
+    }
+
+    /// Start a track neither bound to a process nor a thread, e.g. nested into a process track with [Self::parent]
+    pub fn custom(uuid: u64, name: &str) -> Self {
+        Self::new(uuid, Some(name))
+    }
+
+    /// Start a counter track with values in the given unit, e.g. nested into a process track with [Self::parent]
+    pub fn counter(uuid: u64, name: &str, unit: counter_descriptor::Unit) -> Self {
+        let counter = CounterDescriptor {
+            unit: Some(unit.into()),
+            ..Default::default()
+        };
+        Self::new(uuid, Some(name)).with(|descriptor| descriptor.counter = Some(counter))
+    }
+
+    /// Nest the track into the track with the given uuid
+    pub fn parent(self, parent_uuid: u64) -> Self {
+        self.with(|descriptor| descriptor.parent_uuid = Some(parent_uuid))
+    }
+
+    /// Get the track descriptor
+    pub fn build(self) -> TrackDescriptor {
+        self.descriptor
+    }
+
+    /// Get a packet describing the track
+    pub fn packet(self) -> TracePacket {
+        TracePacket {
+            data: Some(trace_packet::Data::TrackDescriptor(self.descriptor)),
+            ..Default::default()
+        }
+    }
+
+    fn new(uuid: u64, name: Option<&str>) -> Self {
+        Self {
+            descriptor: TrackDescriptor {
+                uuid: Some(uuid),
+                static_or_dynamic_name: name
+                    .map(str::to_string)
+                    .map(track_descriptor::StaticOrDynamicName::Name),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn with(mut self, f: impl FnOnce(&mut TrackDescriptor)) -> Self {
+        f(&mut self.descriptor);
+        self
+    }
+}
+
+/// Value of a debug annotation of an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnnotationValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+impl From<&str> for AnnotationValue {
+    fn from(value: &str) -> Self {
+        AnnotationValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for AnnotationValue {
+    fn from(value: String) -> Self {
+        AnnotationValue::Str(value)
+    }
+}
+
+impl From<i64> for AnnotationValue {
+    fn from(value: i64) -> Self {
+        AnnotationValue::I64(value)
+    }
+}
+
+impl From<u64> for AnnotationValue {
+    fn from(value: u64) -> Self {
+        AnnotationValue::U64(value)
+    }
+}
+
+impl From<f64> for AnnotationValue {
+    fn from(value: f64) -> Self {
+        AnnotationValue::F64(value)
+    }
+}
+
+impl From<bool> for AnnotationValue {
+    fn from(value: bool) -> Self {
+        AnnotationValue::Bool(value)
+    }
+}
+
+/// Create a debug annotation with the given name and value
+pub fn annotation(name: &str, value: impl Into<AnnotationValue>) -> DebugAnnotation {
+    let value = match value.into() {
+        AnnotationValue::Str(value) => debug_annotation::Value::StringValue(value),
+        AnnotationValue::I64(value) => debug_annotation::Value::IntValue(value),
+        AnnotationValue::U64(value) => debug_annotation::Value::UintValue(value),
+        AnnotationValue::F64(value) => debug_annotation::Value::DoubleValue(value),
+        AnnotationValue::Bool(value) => debug_annotation::Value::BoolValue(value),
+    };
+    DebugAnnotation {
+        name_field: Some(debug_annotation::NameField::Name(name.to_string())),
+        value: Some(value),
+        ..Default::default()
+    }
+}
+
+/// Builder of a [TrackEvent]
+#[derive(Debug, Clone, Default)]
+pub struct EventBuilder {
+    event: TrackEvent,
+}
+
+impl EventBuilder {
+    /// Start an event beginning a slice on the given track
+    pub fn slice_begin(track_uuid: u64, name: &str) -> Self {
+        Self::new(track_uuid, Some(name), track_event::Type::SliceBegin)
+    }
+
+    /// Start an event ending the innermost open slice on the given track
+    pub fn slice_end(track_uuid: u64) -> Self {
+        Self::new(track_uuid, None, track_event::Type::SliceEnd)
+    }
+
+    /// Start an instant event on the given track
+    pub fn instant(track_uuid: u64, name: &str) -> Self {
+        Self::new(track_uuid, Some(name), track_event::Type::Instant)
+    }
+
+    /// Start an event setting the value of the given counter track
+    pub fn counter(track_uuid: u64, value: i64) -> Self {
+        let mut builder = Self::new(track_uuid, None, track_event::Type::Counter);
+        builder.event.counter_value_field = Some(track_event::CounterValueField::CounterValue(value));
+        builder
+    }
+
+    /// Start an event setting the floating point value of the given counter track
+    pub fn double_counter(track_uuid: u64, value: f64) -> Self {
+        let mut builder = Self::new(track_uuid, None, track_event::Type::Counter);
+        builder.event.counter_value_field = Some(track_event::CounterValueField::DoubleCounterValue(value));
+        builder
+    }
+
+    /// Set the name of the event, e.g. of a slice end
+    pub fn name(mut self, name: &str) -> Self {
+        self.event.name_field = Some(track_event::NameField::Name(name.to_string()));
+        self
+    }
+
+    /// Add a category
+    pub fn category(mut self, category: &str) -> Self {
+        self.event.categories.push(category.to_string());
+        self
+    }
+
+    /// Set the source location of the event
+    pub fn source_location(mut self, file_name: &str, line_number: u32) -> Self {
+        self.event.source_location_field = Some(track_event::SourceLocationField::SourceLocation(SourceLocation {
+            file_name: Some(file_name.to_string()),
+            line_number: Some(line_number),
+            ..Default::default()
+        }));
+        self
+    }
+
+    /// Add a debug annotation with the given name and value
+    pub fn annotation(mut self, name: &str, value: impl Into<AnnotationValue>) -> Self {
+        self.event.debug_annotations.push(annotation(name, value));
+        self
+    }
+
+    /// Add the given debug annotations
+    pub fn annotations(mut self, annotations: impl IntoIterator<Item = DebugAnnotation>) -> Self {
+        self.event.debug_annotations.extend(annotations);
+        self
+    }
+
+    /// Connect the event to the flow with the given id
+    pub fn flow(mut self, flow_id: u64) -> Self {
+        self.event.flow_ids.push(flow_id);
+        self
+    }
+
+    /// Connect the event to the flow with the given id as its last event
+    pub fn terminating_flow(mut self, flow_id: u64) -> Self {
+        self.event.terminating_flow_ids.push(flow_id);
+        self
+    }
+
+    /// Get the track event
+    pub fn build(self) -> TrackEvent {
+        self.event
+    }
+
+    /// Get a packet of the event at the given timestamp in nanoseconds, on the given packet sequence
+    pub fn packet(self, timestamp: u64, sequence_id: u32) -> TracePacket {
+        TracePacket {
+            data: Some(trace_packet::Data::TrackEvent(self.event)),
+            timestamp: Some(timestamp),
+            optional_trusted_packet_sequence_id: Some(
+                trace_packet::OptionalTrustedPacketSequenceId::TrustedPacketSequenceId(sequence_id),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn new(track_uuid: u64, name: Option<&str>, r#type: track_event::Type) -> Self {
+        Self {
+            event: TrackEvent {
+                r#type: Some(r#type.into()),
+                track_uuid: Some(track_uuid),
+                name_field: name.map(|name| track_event::NameField::Name(name.to_string())),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Get a packet with a snapshot of the given clock readings as clock id and timestamp in nanoseconds,
+/// on the given packet sequence
+///
+/// Clock ids from 64 to 127 are clocks defined by the producer, scoped to the packet sequence. Packets referring
+/// to them with their `timestamp_clock_id` are converted to the trace clock by way of the snapshots.
+pub fn clock_snapshot(clocks: impl IntoIterator<Item = (u32, u64)>, sequence_id: u32) -> TracePacket {
+    let clocks = clocks
+        .into_iter()
+        .map(|(clock_id, timestamp)| clock_snapshot::Clock {
+            clock_id: Some(clock_id),
+            timestamp: Some(timestamp),
+            ..Default::default()
+        })
+        .collect();
+    TracePacket {
+        data: Some(trace_packet::Data::ClockSnapshot(ClockSnapshot {
+            clocks,
+            ..Default::default()
+        })),
+        optional_trusted_packet_sequence_id: Some(
+            trace_packet::OptionalTrustedPacketSequenceId::TrustedPacketSequenceId(sequence_id),
+        ),
+        ..Default::default()
+    }
+}
+
+/// Get a packet with the given ftrace events of one CPU, on the given packet sequence
+///
+/// The events carry their own timestamps, on the boot clock unless stated otherwise in the bundle.
+pub fn ftrace_events(cpu: u32, events: Vec<FtraceEvent>, sequence_id: u32) -> TracePacket {
+    TracePacket {
+        data: Some(trace_packet::Data::FtraceEvents(FtraceEventBundle {
+            cpu: Some(cpu),
+            event: events,
+            ..Default::default()
+        })),
+        optional_trusted_packet_sequence_id: Some(
+            trace_packet::OptionalTrustedPacketSequenceId::TrustedPacketSequenceId(sequence_id),
+        ),
+        ..Default::default()
+    }
+}
+
+/// Interning of the event names, categories, source locations and annotation names of the packets of one
+/// packet sequence
+///
+/// Names are replaced with ids, and each name is added to the interned data of the first packet using it.
+/// The first packet passed clears the incremental state of the sequence, so all packets interned by an
+/// interner must be written to the same sequence in the order they have been interned.
+#[derive(Debug, Default)]
+pub struct Interner {
+    /// Interned event names
+    event_names: HashMap<String, u64>,
+    /// Interned categories
+    categories: HashMap<String, u64>,
+    /// Interned source locations, keyed by file name and line number
+    source_locations: HashMap<(String, u32), u64>,
+    /// Interned debug annotation names
+    annotation_names: HashMap<String, u64>,
+    /// Whether the incremental state of the sequence has been cleared
+    cleared: bool,
+}
+
+impl Interner {
+    /// Create an interner with an empty state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the names, categories and source location of the track event in the packet, if any, with interned ids
+    pub fn intern(&mut self, packet: &mut TracePacket) {
+        let Some(trace_packet::Data::TrackEvent(event)) = packet.data.as_mut() else {
+            return;
+        };
+        let mut interned = InternedData::default();
+
+        if let Some(track_event::NameField::Name(name)) = event.name_field.take() {
+            let (iid, new) = intern(&mut self.event_names, name.as_str());
+            if new {
+                interned.event_names.push(EventName {
+                    iid: Some(iid),
+                    name: Some(name),
+                });
+            }
+            event.name_field = Some(track_event::NameField::NameIid(iid));
+        }
+        for name in event.categories.drain(..) {
+            let (iid, new) = intern(&mut self.categories, name.as_str());
+            if new {
+                interned.event_categories.push(EventCategory {
+                    iid: Some(iid),
+                    name: Some(name),
+                });
+            }
+            event.category_iids.push(iid);
+        }
+        if let Some(track_event::SourceLocationField::SourceLocation(location)) = event.source_location_field.take() {
+            let key = (location.file_name.clone().unwrap_or_default(), location.line_number.unwrap_or_default());
+            let (iid, new) = intern(&mut self.source_locations, &key);
+            if new {
+                interned.source_locations.push(SourceLocation {
+                    iid: Some(iid),
+                    ..location
+                });
+            }
+            event.source_location_field = Some(track_event::SourceLocationField::SourceLocationIid(iid));
+        }
+        for annotation in event.debug_annotations.iter_mut() {
+            let Some(debug_annotation::NameField::Name(name)) = annotation.name_field.take() else {
+                continue;
+            };
+            let (iid, new) = intern(&mut self.annotation_names, name.as_str());
+            if new {
+                interned.debug_annotation_names.push(DebugAnnotationName {
+                    iid: Some(iid),
+                    name: Some(name),
+                });
+            }
+            annotation.name_field = Some(debug_annotation::NameField::NameIid(iid));
+        }
+
+        let mut flags = trace_packet::SequenceFlags::SeqNeedsIncrementalState as u32;
+        if !self.cleared {
+            flags |= trace_packet::SequenceFlags::SeqIncrementalStateCleared as u32;
+            self.cleared = true;
+        }
+        packet.sequence_flags = Some(flags);
+        if interned != InternedData::default() {
+            packet.interned_data = Some(interned);
+        }
+    }
+
+    /// Forget all interned names, so that the next packet clears the incremental state of the sequence again
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Get the id of the given key, interning it if new, and whether it is new
+fn intern<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ToOwned<Owned = K> + ?Sized>(
+    keys: &mut HashMap<K, u64>,
+    key: &Q,
+) -> (u64, bool) {
+    if let Some(iid) = keys.get(key) {
+        return (*iid, false);
+    }
+    // Interned ids start at 1
+    let iid = keys.len() as u64 + 1;
+    keys.insert(key.to_owned(), iid);
+    (iid, true)
+}
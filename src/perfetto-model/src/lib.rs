@@ -10,4 +10,17 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
+
+//! Prost generated bindings of the Perfetto trace format, with [builders] for the common packets
+//! and [read_trace] to read traces written in the binary format.
+
+pub mod builders;
+
 pub use perfetto_proto::perfetto::protos::*;
+
+/// Read a trace in the binary format, i.e. a sequence of serialized [Trace] messages such as a trace file
+///
+/// The packets of all messages are concatenated into a single [Trace].
+pub fn read_trace(bytes: &[u8]) -> Result<Trace, prost::DecodeError> {
+    <Trace as prost::Message>::decode(bytes)
+}
@@ -19,6 +19,7 @@ use feo::error::ActivityError;
 use feo::ids::ActivityId;
 use feo_com::interface::FeoComData;
 use feo_com::interface::{ActivityInput, ActivityOutput};
+use feo_com::topic::Topic;
 use feo_tracing::instrument;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use score_log::debug;
@@ -192,7 +193,7 @@ impl Activity for Monitor {
 }
 
 /// Create an activity input.
-fn activity_input<T>(topic: &str) -> Box<dyn ActivityInput<T>>
+fn activity_input<T>(topic: Topic<'_, T>) -> Box<dyn ActivityInput<T>>
 where
     T: FeoComData + 'static,
 {
@@ -207,7 +208,7 @@ where
 }
 
 /// Create an activity output.
-fn activity_output<T>(topic: &str) -> Box<dyn ActivityOutput<T>>
+fn activity_output<T>(topic: Topic<'_, T>) -> Box<dyn ActivityOutput<T>>
 where
     T: FeoComData + 'static,
 {
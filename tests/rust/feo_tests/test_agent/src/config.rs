@@ -11,6 +11,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // *******************************************************************************
 
+use crate::scenario::Counter;
 use com_api::LolaRuntimeImpl;
 use core::net::{IpAddr, Ipv4Addr, SocketAddr};
 use feo::ids::AgentId;
@@ -21,15 +22,15 @@ pub const PRIMARY_AGENT_ID: AgentId = AgentId::new(100);
 pub const BIND_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8081);
 pub const BIND_ADDR2: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8082);
 pub const DEFAULT_FEO_CYCLE_TIME: Duration = Duration::from_millis(100);
-pub const SOCKET_PATH: &str = "/tmp/feo_listener1.socket";
-pub const SOCKET_PATH2: &str = "/tmp/feo_listener2.socket";
 
 #[cfg(feature = "com_iox2")]
 pub const COM_BACKEND: ComBackend = ComBackend::Iox2;
 #[cfg(feature = "com_linux_shm")]
 pub const COM_BACKEND: ComBackend = ComBackend::LinuxShm;
 
-pub const TOPIC_COUNTER: &str = "/feo/com/test/counter";
+feo_com::topics! {
+    pub TOPIC_COUNTER: Counter = "/feo/com/test/counter";
+}
 
 pub fn mw_com_runtime() -> &'static LolaRuntimeImpl {
     use com_api::Builder;
@@ -14,14 +14,14 @@
 use crate::config::mw_com_runtime;
 use crate::config::COM_BACKEND;
 use crate::config::PRIMARY_AGENT_ID;
-use crate::config::{BIND_ADDR, BIND_ADDR2, DEFAULT_FEO_CYCLE_TIME, SOCKET_PATH, SOCKET_PATH2};
+use crate::config::{BIND_ADDR, BIND_ADDR2, DEFAULT_FEO_CYCLE_TIME};
 use crate::scenario::ScenarioConfig;
 use crate::{Scenario, Signalling};
 use feo::agent::com_init::initialize_com_primary;
 use feo::error::Error;
+use feo::paths;
 use feo_time::Duration;
 use score_log::info;
-use std::path::PathBuf;
 
 pub trait PrimaryLauncher {
     /// Launch primary FEO agent
@@ -70,6 +70,7 @@ impl PrimaryLauncher for Signalling {
                         .unwrap(),
                     timeout: Duration::from_secs(10),
                     startup_timeout: Duration::from_secs(10),
+                    ..Default::default()
                 };
 
                 Primary::new(config).unwrap().run().unwrap();
@@ -92,6 +93,7 @@ impl PrimaryLauncher for Signalling {
                     endpoint: NodeAddress::Tcp(BIND_ADDR),
                     activity_agent_map: scenario.activity_agent_map(),
                     all_agent_assignments,
+                    ..Default::default()
                 };
 
                 Primary::new(config, runtime).unwrap().run().unwrap();
@@ -111,9 +113,10 @@ impl PrimaryLauncher for Signalling {
                     timeout: Duration::from_secs(10),
                     connection_timeout: Duration::from_secs(10),
                     startup_timeout: Duration::from_secs(10),
-                    endpoint: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH)),
+                    endpoint: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener1")),
                     activity_agent_map: scenario.activity_agent_map(),
                     all_agent_assignments,
+                    ..Default::default()
                 };
 
                 Primary::new(config, runtime).unwrap().run().unwrap();
@@ -138,6 +141,7 @@ impl PrimaryLauncher for Signalling {
                     id: PRIMARY_AGENT_ID,
                     worker_agent_map: scenario.worker_agent_map(),
                     activity_worker_map: scenario.activity_worker_map(),
+                    ..Default::default()
                 };
 
                 Primary::new(config, runtime).unwrap().run().unwrap();
@@ -157,11 +161,12 @@ impl PrimaryLauncher for Signalling {
                     timeout: Duration::from_secs(10),
                     connection_timeout: Duration::from_secs(10),
                     startup_timeout: Duration::from_secs(10),
-                    bind_address_senders: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH)),
-                    bind_address_receivers: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH2)),
+                    bind_address_senders: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener1")),
+                    bind_address_receivers: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener2")),
                     id: PRIMARY_AGENT_ID,
                     worker_agent_map: scenario.worker_agent_map(),
                     activity_worker_map: scenario.activity_worker_map(),
+                    ..Default::default()
                 };
 
                 Primary::new(config, runtime).unwrap().run().unwrap();
@@ -184,6 +189,7 @@ impl PrimaryLauncher for Signalling {
                     endpoint: NodeAddress::MwCom,
                     activity_agent_map: scenario.activity_agent_map(),
                     all_agent_assignments,
+                    ..Default::default()
                 };
 
                 Primary::new(config, runtime).unwrap().run().unwrap();
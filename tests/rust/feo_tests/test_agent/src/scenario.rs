@@ -122,10 +122,7 @@ impl ScenarioConfig for Scenario {
     fn topic_dependencies(&self) -> Vec<TopicSpecification<'static>> {
         use Direction::*;
 
-        vec![TopicSpecification::new::<Counter>(
-            TOPIC_COUNTER,
-            vec![(0.into(), Outgoing), (1.into(), Incoming)],
-        )]
+        vec![TopicSpecification::new(TOPIC_COUNTER, vec![(0.into(), Outgoing), (1.into(), Incoming)])]
     }
 }
 
@@ -13,14 +13,14 @@
 
 use feo_time::Duration;
 use std::collections::HashSet;
-use std::path::PathBuf;
 
 use crate::config::mw_com_runtime;
-use crate::config::{BIND_ADDR, BIND_ADDR2, COM_BACKEND, SOCKET_PATH, SOCKET_PATH2};
+use crate::config::{BIND_ADDR, BIND_ADDR2, COM_BACKEND};
 use feo::agent::com_init::initialize_com_secondary;
 use feo::agent::NodeAddress;
 use feo::error::Error;
 use feo::ids::{ActivityId, AgentId};
+use feo::paths;
 use score_log::info;
 
 use crate::scenario::ScenarioConfig as _;
@@ -64,10 +64,12 @@ impl SecondaryLauncher for Signalling {
                     id: agent_id,
                     worker_assignments: scenario.agent_assignments(server_name).remove(&agent_id).unwrap(),
                     timeout: Duration::from_secs(1),
+                    heartbeat_interval: None,
                     endpoint: NodeAddress::Tcp(BIND_ADDR),
+                    ..Default::default()
                 };
 
-                Secondary::new(config, runtime).run();
+                Secondary::new(config, runtime).run().unwrap();
             },
             Signalling::DirectUnix => {
                 use feo::agent::direct::secondary::{Secondary, SecondaryConfig};
@@ -76,10 +78,12 @@ impl SecondaryLauncher for Signalling {
                     id: agent_id,
                     worker_assignments: scenario.agent_assignments(server_name).remove(&agent_id).unwrap(),
                     timeout: Duration::from_secs(1),
-                    endpoint: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH)),
+                    heartbeat_interval: None,
+                    endpoint: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener1")),
+                    ..Default::default()
                 };
 
-                Secondary::new(config, runtime).run();
+                Secondary::new(config, runtime).run().unwrap();
             },
             Signalling::RelayedTcp => {
                 use feo::agent::relayed::secondary::{Secondary, SecondaryConfig};
@@ -88,11 +92,13 @@ impl SecondaryLauncher for Signalling {
                     id: agent_id,
                     worker_assignments: scenario.agent_assignments(server_name).remove(&agent_id).unwrap(),
                     timeout: Duration::from_secs(10),
+                    heartbeat_interval: None,
                     bind_address_senders: NodeAddress::Tcp(BIND_ADDR),
                     bind_address_receivers: NodeAddress::Tcp(BIND_ADDR2),
+                    ..Default::default()
                 };
 
-                Secondary::new(config).run();
+                Secondary::new(config).unwrap().run().unwrap();
             },
             Signalling::RelayedUnix => {
                 use feo::agent::relayed::secondary::{Secondary, SecondaryConfig};
@@ -101,11 +107,13 @@ impl SecondaryLauncher for Signalling {
                     id: agent_id,
                     worker_assignments: scenario.agent_assignments(server_name).remove(&agent_id).unwrap(),
                     timeout: Duration::from_secs(10),
-                    bind_address_senders: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH)),
-                    bind_address_receivers: NodeAddress::UnixSocket(PathBuf::from(SOCKET_PATH2)),
+                    heartbeat_interval: None,
+                    bind_address_senders: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener1")),
+                    bind_address_receivers: NodeAddress::UnixSocket(paths::signalling_socket(None, "listener2")),
+                    ..Default::default()
                 };
 
-                Secondary::new(config).run();
+                Secondary::new(config).unwrap().run().unwrap();
             },
             Signalling::MwCom => {
                 use feo::agent::direct::secondary::{Secondary, SecondaryConfig};
@@ -114,10 +122,12 @@ impl SecondaryLauncher for Signalling {
                     id: agent_id,
                     worker_assignments: scenario.agent_assignments(server_name).remove(&agent_id).unwrap(),
                     timeout: Duration::from_secs(1),
+                    heartbeat_interval: None,
                     endpoint: NodeAddress::MwCom,
+                    ..Default::default()
                 };
 
-                Secondary::new(config, runtime).run();
+                Secondary::new(config, runtime).run().unwrap();
             },
         }
 